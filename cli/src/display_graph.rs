@@ -246,6 +246,8 @@ impl<'a> DisplayGraph<'a> {
             SomeGraphDef::Onnx(onnx, _) => self.with_onnx_model(onnx),
             #[cfg(feature = "tf")]
             SomeGraphDef::Tf(tf) => self.with_tf_graph_def(tf),
+            #[cfg(feature = "tflite")]
+            SomeGraphDef::Tflite(_) => Ok(self),
         }
     }
 
@@ -0,0 +1,18 @@
+use crate::errors::*;
+use crate::Parameters;
+use tract_core::internal::*;
+
+pub fn handle(params: Parameters, output: &str) -> CliResult<()> {
+    let tract = &params.tract_model;
+    let model: &dyn Model = if let Some(m) = tract.downcast_ref::<TypedModel>() {
+        m
+    } else if let Some(m) = tract.downcast_ref::<NormalizedModel>() {
+        m
+    } else {
+        bail!("Only typed and normalized models can be exported to ONNX")
+    };
+    let mut file = std::fs::File::create(output)?;
+    tract_onnx::export::write_model(model, &mut file)?;
+    info!("Wrote {}", output);
+    Ok(())
+}
@@ -17,6 +17,8 @@ extern crate tract_core;
 extern crate tract_onnx;
 #[cfg(feature = "tf")]
 extern crate tract_tensorflow;
+#[cfg(feature = "tflite")]
+extern crate tract_tflite;
 
 #[allow(unused_imports)]
 use itertools::Itertools;
@@ -39,7 +41,10 @@ mod display_graph;
 mod draw;
 mod dump;
 mod errors;
+#[cfg(feature = "onnx")]
+mod export;
 mod format;
+mod memory;
 mod optimize_check;
 mod profile;
 mod run;
@@ -77,7 +82,7 @@ fn main() {
         (@arg model: +takes_value "Sets the model to use")
 
         (@arg format: -f +takes_value
-            "Hint the model format ('kaldi', 'onnx' or 'tf') instead of guess from extension.")
+            "Hint the model format ('kaldi', 'onnx', 'tf' or 'tflite') instead of guess from extension.")
 
         (@arg input: -i --input +takes_value +multiple number_of_values(1)
             "Set input shape and type (@file.pb or @file.npz:thing.npy or 3x4xi32).")
@@ -126,6 +131,8 @@ fn main() {
          "Pass to stop preprocessing after.")
 
         (@arg optimize: -O --optimize "Optimize before running")
+        (@arg threads: --threads +takes_value
+            "Run matrix products and convolutions on a pool of this many threads")
         (@arg pulse: --pulse +takes_value "Translate to pulse network")
 
         (@arg verbosity: -v ... "Sets the level of verbosity.")
@@ -199,6 +206,11 @@ fn main() {
         );
     app = app.subcommand(output_options(dump));
 
+    let export_onnx = clap::SubCommand::with_name("export-onnx")
+        .long_about("Writes the typed or normalized model as an ONNX protobuf.")
+        .arg(Arg::with_name("output").takes_value(true).required(true).help("Output filename"));
+    app = app.subcommand(output_options(export_onnx));
+
     let draw = clap::SubCommand::with_name("draw");
     app = app.subcommand(output_options(draw));
 
@@ -250,6 +262,10 @@ fn main() {
     let cost = clap::SubCommand::with_name("cost").help("Compute a cost on (some) operations.");
     app = app.subcommand(output_options(cost));
 
+    let memory = clap::SubCommand::with_name("memory")
+        .help("Plan intermediate values memory, and report peak memory usage.");
+    app = app.subcommand(output_options(memory));
+
     let optimize = clap::SubCommand::with_name("optimize").help("Optimize the graph");
     app = app.subcommand(output_options(optimize));
 
@@ -334,6 +350,8 @@ pub enum SomeGraphDef {
     Tf(GraphDef),
     #[cfg(feature = "onnx")]
     Onnx(tract_onnx::pb::ModelProto, tract_onnx::model::ParseResult),
+    #[cfg(feature = "tflite")]
+    Tflite(tract_tflite::model::TfliteProtoModel),
 }
 
 /// Structure holding the parsed parameters.
@@ -365,6 +383,8 @@ impl Parameters {
         let name = matches.value_of("model").unwrap();
         let format = matches.value_of("format").unwrap_or(if name.ends_with(".onnx") {
             "onnx"
+        } else if name.ends_with(".tflite") {
+            "tflite"
         } else {
             "tf"
         });
@@ -385,7 +405,8 @@ impl Parameters {
                 let onnx = tract_onnx::onnx();
                 info_usage("load framework (onnx)");
                 let graph = onnx.proto_model_for_path(&name)?;
-                let parsed = onnx.parse(&graph)?;
+                let parsed =
+                    onnx.parse_with_model_dir(&graph, std::path::Path::new(&name).parent())?;
                 let tract = parsed.model.clone();
                 (SomeGraphDef::Onnx(graph, parsed), tract)
             }
@@ -393,12 +414,30 @@ impl Parameters {
             "tf" => {
                 let tf = tract_tensorflow::tensorflow();
                 info_usage("load framework (tf)");
-                let mut graph = tf.proto_model_for_path(&name)?;
-                if matches.is_present("determinize") {
-                    tract_tensorflow::Tensorflow::determinize(&mut graph)?;
+                if std::path::Path::new(&name).is_dir() {
+                    use tract_tensorflow::saved_model::{DEFAULT_SIGNATURE, DEFAULT_TAGS};
+                    let mut smg = tf.saved_model_graph(&name, DEFAULT_TAGS, DEFAULT_SIGNATURE)?;
+                    if matches.is_present("determinize") {
+                        tract_tensorflow::Tensorflow::determinize(&mut smg.graph)?;
+                    }
+                    let tract = tf.model_for_saved_model_graph(&smg)?;
+                    (SomeGraphDef::Tf(smg.graph), tract)
+                } else {
+                    let mut graph = tf.proto_model_for_path(&name)?;
+                    if matches.is_present("determinize") {
+                        tract_tensorflow::Tensorflow::determinize(&mut graph)?;
+                    }
+                    let tract = tf.model_for_proto_model(&graph)?;
+                    (SomeGraphDef::Tf(graph), tract)
                 }
-                let tract = tf.model_for_proto_model(&graph)?;
-                (SomeGraphDef::Tf(graph), tract)
+            }
+            #[cfg(feature = "tflite")]
+            "tflite" => {
+                let tflite = tract_tflite::tflite();
+                info_usage("load framework (tflite)");
+                let graph = tflite.proto_model_for_path(&name)?;
+                let tract = tflite.model_for_proto_model(&graph)?;
+                (SomeGraphDef::Tflite(graph), tract)
             }
             _ => bail!(
                 "Format {} not supported. You may need to recompile tract with the right features.",
@@ -758,9 +797,29 @@ fn handle(matches: clap::ArgMatches) -> CliResult<()> {
             println!("{}", names);
             println!("\n");
         }
+        #[cfg(feature = "tflite")]
+        {
+            let tflite = tract_tflite::tflite();
+            let names = tflite
+                .op_register
+                .0
+                .keys()
+                .sorted()
+                .into_iter()
+                .map(|k| format!("{:?}", k))
+                .join(", ");
+            println!("TensorFlow Lite:\n");
+            println!("{}", names);
+            println!("\n");
+        }
         return Ok(());
     }
 
+    if let Some(threads) = matches.value_of("threads") {
+        let pool = tract_core::threads::pool(threads.parse()?).map_err(|e| e.to_string())?;
+        tract_core::threads::set_default_pool(Some(pool));
+    }
+
     let mut params = Parameters::from_clap(&matches)?;
 
     match matches.subcommand() {
@@ -804,10 +863,19 @@ fn handle(matches: clap::ArgMatches) -> CliResult<()> {
 
         ("cost", Some(m)) => crate::cost::handle(params, display_options_from_clap(&matches, m)?),
 
+        ("memory", Some(m)) => {
+            crate::memory::handle(params, display_options_from_clap(&matches, m)?)
+        }
+
         ("draw", Some(m)) => {
             crate::draw::render(&*params.tract_model, display_options_from_clap(&matches, m)?)
         }
 
+        #[cfg(feature = "onnx")]
+        ("export-onnx", Some(m)) => export::handle(params, m.value_of("output").unwrap()),
+        #[cfg(not(feature = "onnx"))]
+        ("export-onnx", _) => bail!("Need onnx feature to be able to export to ONNX"),
+
         ("dump", Some(m)) => {
             params.assertions = Some(Assertions::from_clap(m, &*params.output_names)?);
             let inner = m
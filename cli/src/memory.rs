@@ -0,0 +1,49 @@
+use crate::display_graph::*;
+use crate::errors::*;
+use crate::{Model, Parameters};
+use tract_core::internal::*;
+use tract_core::memory_plan::MemoryPlan;
+
+pub fn handle(params: Parameters, options: DisplayOptions) -> CliResult<()> {
+    let tract = &params.tract_model;
+    if let Some(_) = tract.downcast_ref::<InferenceModel>() {
+        bail!("Memory planning only work on a typed model")
+    } else if let Some(m) = tract.downcast_ref::<TypedModel>() {
+        handle_t(m, &params, options)
+    } else if let Some(m) = tract.downcast_ref::<NormalizedModel>() {
+        handle_t(&m.clone().into_typed()?, &params, options)
+    } else {
+        bail!("Pulse model are unsupported here")
+    }
+}
+
+fn handle_t(model: &TypedModel, params: &Parameters, options: DisplayOptions) -> CliResult<()> {
+    let plan = SimplePlan::new(model)?;
+    let memory = MemoryPlan::new(&plan)?;
+    let mut display_graph =
+        DisplayGraph::from_model_and_options(model as &dyn Model, options.into())?
+            .with_graph_def(&params.graph)?;
+    for (node, slots) in memory.slots.iter().enumerate() {
+        let rows: Vec<String> = slots
+            .iter()
+            .enumerate()
+            .filter_map(|(ix, slot)| slot.as_ref().map(|s| (ix, s)))
+            .map(|(ix, s)| {
+                format!(
+                    "Output {}: {} bytes at {} (steps {}..={})",
+                    ix, s.size, s.offset, s.first_step, s.last_step
+                )
+            })
+            .collect();
+        if !rows.is_empty() {
+            display_graph.add_node_section(node, rows)?;
+        }
+    }
+    display_graph.render()?;
+    println!("Arena: {} bytes", memory.arena_size);
+    println!("Outputs: {} bytes", memory.outputs_size);
+    println!("Planned: {} bytes", memory.planned_memory());
+    println!("Outside the arena: {} bytes", memory.unplanned_size);
+    println!("Bound: {} bytes", memory.memory_bound());
+    Ok(())
+}
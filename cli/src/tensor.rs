@@ -13,7 +13,7 @@ pub fn parse_spec(size: &str) -> CliResult<TensorFact> {
     }
 
     let last = splits.last().unwrap();
-    let (datum_type, shape) = if last.ends_with("S") || last.parse::<i32>().is_ok() {
+    let (datum_type, shape) = if last.parse::<TDim>().is_ok() {
         (None, &*splits)
     } else {
         let datum_type = match splits.last().unwrap().to_lowercase().as_str() {
@@ -143,13 +143,21 @@ pub fn tensor_for_fact(fact: &TensorFact, streaming_dim: Option<usize>) -> CliRe
         if fact.stream_info()?.is_some() && streaming_dim.is_none() {
             Err("random tensor requires a streaming dim")?
         }
+        let mut symbols = HashMap::new();
+        if let Some(s) = streaming_dim {
+            symbols.insert('S', s as i32);
+        }
         Ok(random(
             fact.shape
                 .concretize()
                 .unwrap()
                 .iter()
-                .map(|d| d.to_integer().ok().map(|d| d as usize).or(streaming_dim).unwrap())
-                .collect(),
+                .map(|d| {
+                    d.eval_with(&symbols).map(|d| d as usize).map_err(|_| {
+                        format!("Can not generate random tensor: symbolic dimension {}", d)
+                    })
+                })
+                .collect::<Result<_, _>>()?,
             fact.datum_type.concretize().ok_or_else(|| {
                 format!("Can not generate random tensor: unknown datum_type: {:?}", fact)
             })?,
@@ -14,7 +14,7 @@ pub use arrays::ArrayDatum;
 use serde::ser::{Serialize, Serializer};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub enum DatumType {
     Bool,
     U8,
@@ -173,6 +173,16 @@ try_into!(i32, i16);
 try_into!(i64, i16);
 try_into!(i64, i32);
 
+try_into!(u8, i16);
+try_into!(u8, i32);
+try_into!(u8, i64);
+try_into!(u16, i32);
+try_into!(u16, i64);
+try_into!(i32, u8);
+try_into!(i64, u8);
+try_into!(i32, u16);
+try_into!(i64, u16);
+
 try_into!(f64, f32);
 try_into!(f32, f64);
 
@@ -180,16 +190,22 @@ try_into!(i8, f32);
 try_into!(i16, f32);
 try_into!(i32, f32);
 try_into!(i64, f32);
+try_into!(u8, f32);
+try_into!(u16, f32);
 
 try_into!(i8, f64);
 try_into!(i16, f64);
 try_into!(i32, f64);
 try_into!(i64, f64);
+try_into!(u8, f64);
+try_into!(u16, f64);
 
 try_into!(f32, i8);
 try_into!(f32, i16);
 try_into!(f32, i32);
 try_into!(f32, i64);
+try_into!(f32, u8);
+try_into!(f32, u16);
 
 try_into!(f64, i8);
 try_into!(f64, i16);
@@ -316,20 +332,17 @@ pub trait FloatLike: Datum {
 }
 
 impl FloatLike for f16 {
-    fn mmm(_m: usize, _k: usize, _n: usize) -> Box<dyn tract_linalg::mmm::MatMatMul<Self>> {
-        unimplemented!("f16 ops");
+    fn mmm(m: usize, k: usize, n: usize) -> Box<dyn tract_linalg::mmm::MatMatMul<Self>> {
+        (tract_linalg::ops().hmmm)(m, k, n)
     }
-    fn packed_vec_mat_mul(
-        _k: usize,
-        _n: usize,
-    ) -> Box<dyn tract_linalg::vecmatmul::VecMatMul<Self>> {
-        unimplemented!("f16 ops");
+    fn packed_vec_mat_mul(k: usize, n: usize) -> Box<dyn tract_linalg::vecmatmul::VecMatMul<Self>> {
+        (tract_linalg::ops().hvmm)(k, n)
     }
     fn sigmoid() -> Box<dyn tract_linalg::sigmoid::Sigmoid<Self>> {
-        unimplemented!("f16 ops");
+        (tract_linalg::ops().hsigmoid)()
     }
     fn tanh() -> Box<dyn tract_linalg::tanh::Tanh<Self>> {
-        unimplemented!("f16 ops");
+        (tract_linalg::ops().htanh)()
     }
 }
 
@@ -366,6 +379,36 @@ impl FloatLike for f64 {
     }
 }
 
+pub trait QuantizedLike:
+    Datum + Copy + std::ops::Add + std::ops::Mul + num_traits::Zero + num_traits::AsPrimitive<i32>
+{
+    fn qmmm(
+        m: usize,
+        k: usize,
+        n: usize,
+    ) -> Box<dyn tract_linalg::mmm::QMatMatMul<Self, i8>>;
+}
+
+impl QuantizedLike for i8 {
+    fn qmmm(
+        m: usize,
+        k: usize,
+        n: usize,
+    ) -> Box<dyn tract_linalg::mmm::QMatMatMul<Self, i8>> {
+        (tract_linalg::ops().qmmm_i8_i8)(m, k, n)
+    }
+}
+
+impl QuantizedLike for u8 {
+    fn qmmm(
+        m: usize,
+        k: usize,
+        n: usize,
+    ) -> Box<dyn tract_linalg::mmm::QMatMatMul<Self, i8>> {
+        (tract_linalg::ops().qmmm_u8_i8)(m, k, n)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::internal::*;
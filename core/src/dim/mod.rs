@@ -1,4 +1,5 @@
 //! Extended dimension support
+use std::collections::{BTreeSet, HashMap};
 use std::fmt;
 use std::ops;
 use std::str::FromStr;
@@ -18,7 +19,7 @@ use crate::TractResult;
 /// Implemented by:
 ///
 /// * `usize` for regular dimensions
-/// * `TDim` supporting regular and streaming dimensions
+/// * `TDim` supporting regular, symbolic and streaming dimensions
 pub trait DimLike:
     Clone
     + Default
@@ -66,10 +67,10 @@ impl DimLike for usize {
     }
 }
 
-/// An arithmetic expression built with integer and the special value S for
-/// the streaming dimension.
+/// An arithmetic expression built with integers and symbols, like S for the
+/// streaming dimension, or N for a batch size.
 #[derive(Clone, PartialEq, Eq)]
-#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct TDim(Stack);
 
 impl Default for TDim {
@@ -96,9 +97,14 @@ impl TDim {
         self.as_const().map(|i| i == 1).unwrap_or(false)
     }
 
+    /// A symbolic dimension, like N for an unspecified batch size.
+    pub fn sym(symbol: char) -> TDim {
+        TDim(Stack::sym(symbol))
+    }
+
     /// The special value S, for streaming.
     pub fn s() -> TDim {
-        TDim(Stack::sym('S'))
+        Self::sym('S')
     }
 
     /// The special value S, for streaming.
@@ -106,7 +112,8 @@ impl TDim {
         Self::s()
     }
 
-    /// Try to convert the value to an integer, if it does not contains S.
+    /// Try to convert the value to an integer, if it does not contains any
+    /// symbol.
     pub fn as_const(&self) -> Option<i32> {
         self.to_integer().ok()
     }
@@ -116,11 +123,26 @@ impl TDim {
         self.0.eval(&hashmap!('S' => s)).ok()
     }
 
-    /// Is the value dependend on S ?
+    /// Eval the value for the given symbol values.
+    pub fn eval_with(&self, values: &HashMap<char, i32>) -> TractResult<i32> {
+        self.0.eval(values)
+    }
+
+    /// Is the value dependent on S ?
     pub fn is_stream(&self) -> bool {
+        self.symbols().contains(&'S')
+    }
+
+    /// Does the value depend on any symbol ?
+    pub fn is_symbolic(&self) -> bool {
         self.as_const().is_none()
     }
 
+    /// Symbols the value depends on.
+    pub fn symbols(&self) -> BTreeSet<char> {
+        self.0.symbols()
+    }
+
     /// Convert to integer if possible.
     pub fn to_integer(&self) -> TractResult<i32> {
         self.0.eval(&hashmap!())
@@ -386,14 +408,17 @@ impl<'a> From<&'a usize> for TDim {
 impl FromStr for TDim {
     type Err = std::num::ParseIntError;
     fn from_str(s: &str) -> Result<TDim, Self::Err> {
-        if s == "S" {
-            Ok(TDim::s())
-        } else if s.ends_with("S") {
-            let number: String = s.chars().take_while(|c| c.is_digit(10)).collect();
-            let number: i32 = number.parse::<i32>().map(|i| i.into())?;
-            Ok(TDim::s() * number)
-        } else {
-            s.parse::<i32>().map(|i| i.into())
+        match s.chars().last() {
+            Some(symbol) if symbol.is_ascii_uppercase() => {
+                let number = &s[..s.len() - 1];
+                let sym = TDim::sym(symbol);
+                if number.len() == 0 {
+                    Ok(sym)
+                } else {
+                    Ok(sym * number.parse::<i32>()?)
+                }
+            }
+            _ => s.parse::<i32>().map(|i| i.into()),
         }
     }
 }
@@ -1,14 +1,14 @@
 use super::tree::ExpNode;
 use crate::model::TVec;
 use crate::TractResult;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::{fmt, ops};
 
 #[derive(Clone)]
 pub struct Stack(TVec<StackOp>);
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
-#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub enum StackOp {
     Sym(char),
     Val(i32),
@@ -39,6 +39,17 @@ impl ::serde::Serialize for Stack {
     }
 }
 
+#[cfg(feature = "serialize")]
+impl<'de> ::serde::Deserialize<'de> for Stack {
+    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Stack, D::Error>
+    where
+        D: ::serde::Deserializer<'de>,
+    {
+        let ops: Vec<StackOp> = ::serde::Deserialize::deserialize(deserializer)?;
+        Ok(Stack(ops.into()))
+    }
+}
+
 impl Eq for Stack {}
 
 impl PartialEq for Stack {
@@ -101,6 +112,13 @@ impl Stack {
         Ok(stack[0])
     }
 
+    pub fn symbols(&self) -> BTreeSet<char> {
+        self.0
+            .iter()
+            .filter_map(|op| if let StackOp::Sym(s) = op { Some(*s) } else { None })
+            .collect()
+    }
+
     pub fn format(&self) -> TractResult<String> {
         Ok(format!("{:?}", ExpNode::from_ops(&self)))
     }
@@ -413,6 +431,15 @@ mod tests {
         assert_eq!(e, Stack::from(0));
     }
 
+    #[test]
+    fn multiple_symbols() {
+        let e = Stack::sym('N') * Stack::sym('S') + Stack::sym('S') * Stack::sym('N');
+        assert_eq!(e, Stack::from(2) * Stack::sym('N') * Stack::sym('S'));
+        assert_eq!(e.eval(&hashmap! {'N' => 3, 'S' => 5}).unwrap(), 30);
+        assert!(e.eval(&hashmap! {'S' => 5}).is_err());
+        assert_eq!(e.symbols(), ['N', 'S'].iter().cloned().collect());
+    }
+
     #[test]
     fn conv2d_ex_1() {
         let e = (Stack::from(1) - 1 + 1).div_ceil(&1.into());
@@ -424,5 +451,4 @@ mod tests {
         let e = (Stack::sym('S') - 3 + 1).div_ceil(&1.into());
         assert_eq!(e, Stack::sym('S') + -2);
     }
-
 }
@@ -283,6 +283,7 @@ impl ExpNode {
                         }
                     }
                 } else {
+                    reduced.sort();
                     Mul(value, reduced)
                 }
             }
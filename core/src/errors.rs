@@ -12,6 +12,8 @@ error_chain! {
         NumParseInt(::std::num::ParseIntError);
         Infallible(std::convert::Infallible);
         AllocLayout(std::alloc::LayoutErr);
+        ThreadPoolBuild(tract_linalg::threads::ThreadPoolBuildError);
+        Bincode(::bincode::Error) #[cfg(feature = "serialize")];
     }
     errors {
         StreamTensor {}
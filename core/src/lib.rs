@@ -45,6 +45,8 @@
 //! tract-tensorflow or tract-onnx crates.
 //!
 
+#[cfg(feature = "serialize")]
+extern crate bincode;
 extern crate bit_set;
 #[macro_use]
 extern crate derive_new;
@@ -73,6 +75,7 @@ extern crate objekt;
 extern crate env_logger;
 #[cfg(test)]
 extern crate proptest;
+extern crate rayon;
 #[cfg(feature = "serialize")]
 extern crate serde;
 extern crate smallvec;
@@ -94,13 +97,18 @@ pub mod datum;
 pub mod dim;
 pub mod errors;
 pub mod framework;
+pub mod memory_plan;
 pub mod model;
+pub mod parallel_plan;
 mod optim;
 pub mod plan;
 pub mod pulse;
+#[cfg(feature = "serialize")]
+pub mod serialization;
 pub mod tensor;
 
 pub use crate::errors::*;
+pub use tract_linalg::threads;
 
 /// This prelude is meant for code using tract.
 pub mod prelude {
@@ -110,6 +118,7 @@ pub mod prelude {
     pub use crate::errors::*;
     pub use crate::framework::Framework;
     pub use crate::model::*;
+    pub use crate::parallel_plan::{ParallelPlan, ParallelState};
     pub use crate::plan::{SimplePlan, SimpleState};
     pub use crate::tensor::litteral::*;
     pub use crate::tensor::{IntoArcTensor, IntoTensor, Tensor};
@@ -123,14 +132,14 @@ pub mod internal {
     pub use crate::analyser::rules::{InferenceResult, InferenceRulesOp, Solver, TensorProxy};
     pub use crate::analyser::types::TypeFact;
     pub use crate::analyser::types::*;
-    pub use crate::datum::FloatLike;
+    pub use crate::datum::{FloatLike, QuantizedLike};
     pub use crate::dim::{DimLike, TDim, ToDim};
     pub use crate::element_map;
     pub use crate::framework::*;
     pub use crate::model::*;
     pub use crate::ops::{
-        check_input_arity, check_output_arity, AxesInfo, AxisInfo, Cost, InferenceOp, Op, OpState,
-        StatefullOp, StatelessOp, Validation,
+        check_input_arity, check_output_arity, resolve_axis, AxesInfo, AxisInfo, Cost, InferenceOp,
+        Op, OpState, StatefullOp, StatelessOp, Validation,
     };
     pub use crate::plan::SessionState;
     pub use crate::prelude::*;
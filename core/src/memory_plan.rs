@@ -0,0 +1,376 @@
+//! Static memory planning.
+//!
+//! For plans over models with fully known shapes, the lifetime of each
+//! intermediate value is derived from the plan order, and the value is given
+//! a slot in a single buffer (the arena) allocated once per state. Values
+//! never alive at the same time can share the same bytes.
+//!
+//! While a planned node runs, `Tensor::uninitialized*` calls matching one of
+//! the node output facts return the planned slot instead of allocating, so
+//! ops building their outputs this way write them in place.
+//!
+//! Only values that can not escape the run are planned: outputs of stateless
+//! nodes, read only by stateless nodes, and not outputs of the plan. A
+//! stateful op could keep a reference to its inputs across runs. The other
+//! intermediate values are allocated on their own, and accounted for
+//! separately.
+use std::borrow::Borrow;
+use std::cell::RefCell;
+use std::fmt::{Debug, Display};
+
+use crate::internal::*;
+use crate::model::{ModelImpl, TensorInfo};
+use crate::ops::konst::Const;
+
+/// Alignment of the arena and of every slot in it, in bytes.
+pub const ARENA_ALIGNMENT: usize = 64;
+
+/// A planned output value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Slot {
+    pub datum_type: DatumType,
+    pub shape: TVec<usize>,
+    /// Offset in the arena, in bytes.
+    pub offset: usize,
+    /// Size in the arena, in bytes (rounded up to the arena alignment).
+    pub size: usize,
+    /// Step producing the value.
+    pub first_step: usize,
+    /// Last step reading the value.
+    pub last_step: usize,
+}
+
+impl Slot {
+    fn overlaps_in_time(&self, other: &Slot) -> bool {
+        self.first_step <= other.last_step && other.first_step <= self.last_step
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MemoryPlan {
+    /// For each node, and each of its outputs, the planned slot. `None` for
+    /// values living outside the arena: inputs, constants, outputs of the
+    /// plan, inputs and outputs of stateful ops.
+    pub slots: Vec<TVec<Option<Slot>>>,
+    /// Size of the arena, in bytes.
+    pub arena_size: usize,
+    /// Size of the outputs of the plan, in bytes. They are allocated on their
+    /// own, as they outlive the run.
+    pub outputs_size: usize,
+    /// Peak size of the intermediate values living outside the arena, in
+    /// bytes: inputs and outputs of stateful ops, strings and dimensions
+    /// (without the heap data they point to).
+    pub unplanned_size: usize,
+}
+
+impl MemoryPlan {
+    pub fn new<TI, O, M>(plan: &SimplePlan<TI, O, M>) -> TractResult<MemoryPlan>
+    where
+        TI: TensorInfo + Clone + 'static,
+        O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static,
+        M: Borrow<ModelImpl<TI, O>>,
+    {
+        let model = plan.model();
+        let mut session = SessionState::default();
+        let mut stateless = vec![];
+        for node in model.nodes() {
+            stateless.push(
+                node.op().as_stateless().is_some()
+                    && node.op().state(&mut session, node.id)?.is_none(),
+            );
+        }
+        let mut last_step = vec![None; model.nodes().len()];
+        for (step, &n) in plan.order.iter().enumerate() {
+            for i in &model.node(n).inputs {
+                last_step[i.node] = Some(step);
+            }
+        }
+        let mut slots: Vec<TVec<Option<Slot>>> =
+            model.nodes().iter().map(|n| n.outputs.iter().map(|_| None).collect()).collect();
+        let mut outputs_size = 0;
+        let mut planned = vec![];
+        let mut unplanned = vec![];
+        for (step, &n) in plan.order.iter().enumerate() {
+            let node = model.node(n);
+            let is_output = plan.outputs.iter().any(|o| o.node == n);
+            for (ix, output) in node.outputs.iter().enumerate() {
+                let fact = output.fact.to_tensor_fact();
+                let (dt, shape) =
+                    match (fact.datum_type.concretize(), fact.shape.as_concrete_finite()?) {
+                        (Some(dt), Some(shape)) => (dt, shape),
+                        _ => bail!(
+                            "Memory planning requires known shapes, {} output is {:?}",
+                            node,
+                            fact
+                        ),
+                    };
+                let bytes = shape.iter().product::<usize>() * dt.size_of();
+                if is_output {
+                    outputs_size += bytes;
+                    continue;
+                }
+                if bytes == 0
+                    || node.op().downcast_ref::<Const>().is_some()
+                    || plan.model().input_outlets()?.contains(&OutletId::new(n, ix))
+                {
+                    continue;
+                }
+                let last_step = last_step[n].unwrap_or(step);
+                if dt == DatumType::String
+                    || dt == DatumType::TDim
+                    || !stateless[n]
+                    || output.successors.iter().any(|succ| !stateless[succ.node])
+                {
+                    unplanned.push((bytes, step, last_step));
+                    continue;
+                }
+                let size = (bytes + ARENA_ALIGNMENT - 1) / ARENA_ALIGNMENT * ARENA_ALIGNMENT;
+                planned.push((
+                    n,
+                    ix,
+                    Slot { datum_type: dt, shape, offset: 0, size, first_step: step, last_step },
+                ));
+            }
+        }
+        // greedy by size: biggest values first, each at the lowest offset not
+        // conflicting with already placed values alive at the same time
+        planned.sort_by_key(|(_, _, slot)| (std::cmp::Reverse(slot.size), slot.first_step));
+        let mut placed: Vec<Slot> = vec![];
+        let mut arena_size = 0;
+        for (node, ix, mut slot) in planned {
+            let mut conflicts: Vec<&Slot> =
+                placed.iter().filter(|other| slot.overlaps_in_time(other)).collect();
+            conflicts.sort_by_key(|other| other.offset);
+            let mut offset = 0;
+            for other in conflicts {
+                if other.offset >= offset + slot.size {
+                    break;
+                }
+                offset = offset.max(other.offset + other.size);
+            }
+            slot.offset = offset;
+            arena_size = arena_size.max(offset + slot.size);
+            placed.push(slot.clone());
+            slots[node][ix] = Some(slot);
+        }
+        let unplanned_size = (0..plan.order.len())
+            .map(|step| {
+                unplanned
+                    .iter()
+                    .filter(|&&(_, first, last)| first <= step && step <= last)
+                    .map(|&(bytes, _, _)| bytes)
+                    .sum::<usize>()
+            })
+            .max()
+            .unwrap_or(0);
+        Ok(MemoryPlan { slots, arena_size, outputs_size, unplanned_size })
+    }
+
+    /// Memory planned for a run, in bytes: the arena and the plan outputs.
+    /// Values outside the arena are not included, see `memory_bound`.
+    pub fn planned_memory(&self) -> usize {
+        self.arena_size + self.outputs_size
+    }
+
+    /// Upper bound of the memory held by the values of a run, in bytes: the
+    /// planned memory and the peak of the values outside the arena. Values
+    /// an op allocates elsewhere than in their slot are moved to it, so only
+    /// the scratch space ops use while running is left out.
+    pub fn memory_bound(&self) -> usize {
+        self.planned_memory() + self.unplanned_size
+    }
+
+    /// Runs `f` with the slots of `node` available to `Tensor` allocations.
+    pub(crate) fn with_slots<R>(&self, arena: *mut u8, node: usize, f: impl FnOnce() -> R) -> R {
+        struct Restore(Vec<(DatumType, TVec<usize>, *mut u8)>);
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                let previous = std::mem::replace(&mut self.0, vec![]);
+                AVAILABLE_SLOTS.with(|s| *s.borrow_mut() = previous);
+            }
+        }
+        let slots = self.slots[node]
+            .iter()
+            .filter_map(|s| s.as_ref())
+            .map(|s| unsafe { (s.datum_type, s.shape.clone(), arena.add(s.offset)) })
+            .collect();
+        let _restore = Restore(AVAILABLE_SLOTS.with(|s| s.replace(slots)));
+        f()
+    }
+
+    /// Makes sure the outputs of `node` do not use arena bytes outside of
+    /// their own slots. This happens when an op returns one of its inputs, or
+    /// works in place on it: the value is then moved to its slot, or to the
+    /// heap if it has none. Planned values the op has not allocated in the
+    /// arena are moved to their slot too.
+    pub(crate) fn settle(
+        &self,
+        arena: *mut u8,
+        node: usize,
+        values: TVec<Arc<Tensor>>,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        let end = unsafe { arena.add(self.arena_size) } as *const u8;
+        values
+            .into_iter()
+            .enumerate()
+            .map(|(ix, value)| {
+                let data = value.as_bytes().as_ptr();
+                let in_arena = data >= arena as *const u8 && data < end;
+                match self.slots[node].get(ix).and_then(|s| s.as_ref()) {
+                    Some(slot) if unsafe { arena.add(slot.offset) } as *const u8 == data => {
+                        Ok(value)
+                    }
+                    Some(slot)
+                        if slot.datum_type == value.datum_type()
+                            && &*slot.shape == value.shape() =>
+                    unsafe {
+                        let target = arena.add(slot.offset);
+                        data.copy_to_nonoverlapping(target, value.as_bytes().len());
+                        Ok(Tensor::from_arena(
+                            slot.datum_type,
+                            &slot.shape,
+                            target,
+                            ARENA_ALIGNMENT,
+                        )?
+                        .into_arc_tensor())
+                    },
+                    _ if !in_arena => Ok(value),
+                    _ => Ok(Arc::new(value.as_ref().clone())),
+                }
+            })
+            .collect()
+    }
+}
+
+thread_local! {
+    static AVAILABLE_SLOTS: RefCell<Vec<(DatumType, TVec<usize>, *mut u8)>> = RefCell::new(vec![]);
+}
+
+/// Hands out (once) the slot planned for a value of this type and shape, if
+/// the running node has one.
+pub(crate) fn take_slot(dt: DatumType, shape: &[usize], alignment: usize) -> Option<*mut u8> {
+    if alignment > ARENA_ALIGNMENT || ARENA_ALIGNMENT % alignment != 0 {
+        return None;
+    }
+    AVAILABLE_SLOTS.with(|slots| {
+        let mut slots = slots.borrow_mut();
+        let ix = slots.iter().position(|s| s.0 == dt && &*s.1 == shape)?;
+        Some(slots.remove(ix).2)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::math;
+
+    fn chain(len: usize) -> TypedModel {
+        let mut model = TypedModel::default();
+        let fact = TypedTensorInfo::dt_shape(f32::datum_type(), [16usize].as_ref()).unwrap();
+        let mut wire = OutletId::new(model.add_source("source", fact).unwrap(), 0);
+        for i in 0..len {
+            let op = math::add::unary(rctensor0(i as f32));
+            wire = model.wire_node(format!("add-{}", i), op, &[wire]).unwrap()[0];
+        }
+        model.auto_outputs().unwrap();
+        model
+    }
+
+    #[test]
+    fn chain_uses_two_slots() {
+        let model = chain(5);
+        let plan = SimplePlan::new(&model).unwrap();
+        let memory = MemoryPlan::new(&plan).unwrap();
+        assert_eq!(memory.arena_size, 2 * ARENA_ALIGNMENT);
+        assert_eq!(memory.outputs_size, 16 * 4);
+        assert_eq!(memory.planned_memory(), 2 * ARENA_ALIGNMENT + 16 * 4);
+        let source = model.node_by_name("source").unwrap().id;
+        let output = model.node_by_name("add-4").unwrap().id;
+        assert!(memory.slots[source][0].is_none());
+        assert!(memory.slots[output][0].is_none());
+    }
+
+    #[test]
+    fn live_values_do_not_overlap() {
+        let mut model = TypedModel::default();
+        let fact = TypedTensorInfo::dt_shape(f32::datum_type(), [16usize].as_ref()).unwrap();
+        let source = OutletId::new(model.add_source("source", fact).unwrap(), 0);
+        let a = model.wire_node("a", math::add::unary(rctensor0(1f32)), &[source]).unwrap()[0];
+        let b = model.wire_node("b", math::add::unary(rctensor0(2f32)), &[source]).unwrap()[0];
+        let c = model.wire_node("c", math::add::bin(), &[a, b]).unwrap()[0];
+        model.wire_node("d", math::add::unary(rctensor0(3f32)), &[c]).unwrap();
+        model.auto_outputs().unwrap();
+        let plan = SimplePlan::new(&model).unwrap();
+        let memory = MemoryPlan::new(&plan).unwrap();
+        let slot = |name| memory.slots[model.node_by_name(name).unwrap().id][0].clone().unwrap();
+        assert_ne!(slot("a").offset, slot("b").offset);
+        assert_ne!(slot("a").offset, slot("c").offset);
+        assert_ne!(slot("b").offset, slot("c").offset);
+        assert_eq!(memory.arena_size, 3 * ARENA_ALIGNMENT);
+    }
+
+    #[test]
+    fn stateful_inputs_are_not_planned() {
+        use crate::pulse::delay::Delay;
+        use crate::pulse::PulsedTensorFact;
+        let mut model = TypedModel::default();
+        let fact = TypedTensorInfo::dt_shape(f32::datum_type(), [16usize].as_ref()).unwrap();
+        let source = OutletId::new(model.add_source("source", fact).unwrap(), 0);
+        let a = model.wire_node("a", math::add::unary(rctensor0(1f32)), &[source]).unwrap()[0];
+        let pulse = PulsedTensorFact {
+            dt: f32::datum_type(),
+            shape: tvec!(16),
+            axis: 0,
+            dim: 16.to_dim(),
+            delay: 0,
+        };
+        let delay = model.wire_node("delay", Delay::new(pulse, 4, 0), &[a]).unwrap()[0];
+        model.wire_node("b", math::add::unary(rctensor0(2f32)), &[delay]).unwrap();
+        model.auto_outputs().unwrap();
+        let plan = SimplePlan::new(&model).unwrap().with_memory_plan().unwrap();
+        let memory = plan.memory_plan.as_ref().unwrap();
+        assert!(memory.slots[model.node_by_name("a").unwrap().id][0].is_none());
+        assert!(memory.slots[model.node_by_name("delay").unwrap().id][0].is_none());
+        // a and delay are both alive while delay runs
+        assert_eq!(memory.unplanned_size, 2 * 16 * 4);
+        assert_eq!(memory.memory_bound(), memory.planned_memory() + 2 * 16 * 4);
+        let mut expected = SimpleState::new(SimplePlan::new(&model).unwrap()).unwrap();
+        let mut state = SimpleState::new(&plan).unwrap();
+        for i in 0..3 {
+            let input = tensor1(&[i as f32; 16]);
+            assert_eq!(
+                state.run(tvec!(input.clone())).unwrap(),
+                expected.run(tvec!(input)).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn heap_values_are_moved_to_their_slot() {
+        let model = chain(3);
+        let plan = SimplePlan::new(&model).unwrap();
+        let memory = MemoryPlan::new(&plan).unwrap();
+        assert_eq!(memory.unplanned_size, 0);
+        let mut arena =
+            unsafe { Tensor::uninitialized_aligned::<u8>(&[memory.arena_size], ARENA_ALIGNMENT) }
+                .unwrap();
+        let node = model.node_by_name("add-0").unwrap().id;
+        let value = rctensor1(&[2f32; 16]);
+        let settled =
+            memory.settle(arena.as_ptr_mut::<u8>().unwrap(), node, tvec!(value.clone())).unwrap();
+        assert!(settled[0].in_arena());
+        assert_eq!(settled[0], value);
+    }
+
+    #[test]
+    fn planned_run_matches_plain_run() {
+        let model = chain(5);
+        let input = tensor1(&[1f32; 16]);
+        let expected = SimplePlan::new(&model).unwrap().run(tvec!(input.clone())).unwrap();
+        let plan = SimplePlan::new(&model).unwrap().with_memory_plan().unwrap();
+        let mut state = SimpleState::new(&plan).unwrap();
+        for _ in 0..3 {
+            assert_eq!(state.run(tvec!(input.clone())).unwrap(), expected);
+        }
+    }
+}
@@ -60,6 +60,7 @@ pub use self::tensor_info::*;
 pub use crate::analyser::types::TensorFact;
 pub use crate::ops::{InferenceOp, Op, TypedOp};
 
+use crate::analyser::types::Fact;
 use crate::plan::{SimplePlan, SimpleState};
 use crate::TractResult;
 
@@ -261,7 +262,39 @@ impl InferenceModel {
     }
 }
 
+/// Symbols the dimensions of a model and of its nested models depend on.
+fn used_symbols(model: &dyn Model, used: &mut std::collections::BTreeSet<char>) {
+    for id in 0..model.nodes_len() {
+        for ix in 0..model.node_output_count(id) {
+            let fact = model.outlet_tensorfact(OutletId::new(id, ix));
+            used.extend(fact.shape.dims().filter_map(|d| d.concretize()).flat_map(|d| d.symbols()));
+        }
+        for (_, nested) in model.node_op(id).nested_models() {
+            used_symbols(nested, used);
+        }
+    }
+}
+
+/// A symbol none of `models` (nor their nested models) depends on yet.
+pub(crate) fn unused_symbol(models: &[&dyn Model]) -> TractResult<char> {
+    let mut used = std::collections::BTreeSet::new();
+    for model in models {
+        used_symbols(*model, &mut used);
+    }
+    (b'a'..=b'z')
+        .chain(b'A'..=b'Z')
+        .map(char::from)
+        .find(|c| *c != 'S' && !used.contains(c))
+        .ok_or_else(|| "All symbols are already in use".into())
+}
+
 impl TypedModel {
+    /// A symbol no dimension in the model depends on yet, for an output
+    /// dimension depending on input values.
+    pub fn unused_symbol(&self) -> TractResult<char> {
+        unused_symbol(&[self])
+    }
+
     /// Perform declutter pass on the network.
     pub fn declutter(self) -> TractResult<TypedModel> {
         let mut model = self;
@@ -330,6 +363,7 @@ impl NormalizedModel {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::internal::*;
 
     #[test]
     fn test() {
@@ -338,4 +372,40 @@ mod test {
         is_sync::<TypedModel>();
         is_sync::<NormalizedModel>();
     }
+
+    #[test]
+    fn symbolic_dims() {
+        let mut model = InferenceModel::default();
+        let shape = ShapeFact::from(tvec!(TDim::sym('N'), 3.to_dim(), TDim::s()));
+        let source = model.add_source("x", TensorFact::dt_shape(f32::datum_type(), shape)).unwrap();
+        let abs = model
+            .chain_after(
+                OutletId::new(source, 0),
+                "abs",
+                crate::ops::math::Abs::default(),
+                tvec!(TensorFact::default()),
+            )
+            .unwrap();
+        model.set_output_outlets(&[OutletId::new(abs, 0)]).unwrap();
+        let model = model.into_optimized().unwrap();
+        assert_eq!(
+            model
+                .outlet_fact(OutletId::new(model.output_outlets().unwrap()[0].node, 0))
+                .unwrap()
+                .shape
+                .symbols(),
+            ['N', 'S'].iter().cloned().collect()
+        );
+        let plan = SimplePlan::new(&model).unwrap();
+        for &(n, s) in &[(2, 5), (4, 1)] {
+            let input = Tensor::from(ndarray::ArrayD::<f32>::zeros(vec![n, 3, s]));
+            let mut state = SimpleState::new(&plan).unwrap();
+            let output = state.run(tvec!(input)).unwrap();
+            assert_eq!(output[0].shape(), &[n, 3, s]);
+            assert_eq!(
+                state.session_state.resolved_symbols,
+                hashmap!('N' => n as i32, 'S' => s as i32)
+            );
+        }
+    }
 }
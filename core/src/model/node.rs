@@ -103,7 +103,7 @@ impl<TI: TensorInfo> fmt::Debug for OutletFact<TI> {
 /// (as the graph typically connect one single node output to one or several
 /// inputs slots)
 #[derive(Clone, Copy, PartialEq, Eq, Hash, new)]
-#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct OutletId {
     /// node identifier in the graph
     pub node: usize,
@@ -131,7 +131,7 @@ impl From<(usize, usize)> for OutletId {
 
 /// Identifier for a node input in the graph.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, new, Ord, PartialOrd)]
-#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct InletId {
     /// node identifier in the graph
     pub node: usize,
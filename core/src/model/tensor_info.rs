@@ -3,6 +3,7 @@ use crate::internal::*;
 use crate::prelude::*;
 use crate::tensor::Tensor;
 use downcast_rs::Downcast;
+use std::collections::BTreeSet;
 use std::convert::{TryFrom, TryInto};
 use std::fmt;
 
@@ -28,14 +29,7 @@ impl<'a> TryFrom<&'a TensorFact> for TypedTensorInfo {
         if let (Some(datum_type), Some(shape)) =
             (fact.datum_type.concretize(), fact.shape.concretize())
         {
-            let stream_info = shape
-                .iter()
-                .cloned()
-                .enumerate()
-                .find(|d| d.1.to_integer().is_err())
-                .map(|(axis, len)| StreamInfo { axis, len });
-            let shape = shape.iter().map(|d| d.to_integer().unwrap_or(0) as usize).collect();
-            let shape = ShapeInfo { shape, stream_info };
+            let shape = ShapeInfo::from_dims(shape)?;
             Ok(TypedTensorInfo { datum_type, shape, konst: fact.value.concretize() })
         } else {
             bail!("Can not make a TypedTensorInfo out of {:?}", fact)
@@ -58,6 +52,7 @@ impl<'a> From<&'a Tensor> for TensorFact {
 
 /// Streaming information for a streamed tensor.
 #[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct StreamInfo {
     /// Streaming axis
     pub axis: usize,
@@ -67,89 +62,60 @@ pub struct StreamInfo {
 
 /// Fully determined dimension of a tensor.
 ///
-/// Tensors in tract can have one streaming dimension. TDim generalize the
-/// regular tensor dimensions (usize) to arithmetic expressions of `S`, the
-/// (sometimes hypothetical) tensor length on the streaming axis.
+/// TDim generalize the regular tensor dimensions (usize) to arithmetic
+/// expressions of symbols, like `N` for a batch size left open. Tensors in
+/// tract can have one streaming dimension, built on the special symbol `S`,
+/// the (sometimes hypothetical) tensor length on the streaming axis.
 #[derive(Clone)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct ShapeInfo {
-    shape: TVec<usize>,
+    dims: TVec<TDim>,
+    /// Shape as integers, for tensors without symbolic dimensions.
+    concrete: Option<TVec<usize>>,
     /// Optional information for streaming tensors. None for regular tensors.
     pub stream_info: Option<StreamInfo>,
 }
 
 impl PartialEq for ShapeInfo {
     fn eq(&self, other: &ShapeInfo) -> bool {
-        self.shape.len() == other.shape.len() && self.iter().zip(other.iter()).all(|(a, b)| a == b)
+        self.dims == other.dims
     }
 }
 
 impl ShapeInfo {
     /// Rank of the tensor.
     pub fn rank(&self) -> usize {
-        self.shape.len()
+        self.dims.len()
     }
 
     /// Extended dimension of the i-th axis.
     ///
-    /// The TDim will wrap a plain integer for regular (non-streaming) tensors.
+    /// The TDim will wrap a plain integer for regular (non-symbolic) dimensions.
     pub fn dim(&self, i: usize) -> TDim {
-        if let Some(ref stream) = self.stream_info {
-            if stream.axis == i {
-                return stream.len.clone();
-            }
-        }
-        self.shape[i].to_dim()
+        self.dims[i].clone()
     }
 
     /// Set the i-th axis dimension.
     pub fn set_dim(&mut self, i: usize, dim: TDim) -> TractResult<()> {
-        if let Some(ref stream) = self.stream_info {
-            if let Ok(int) = dim.to_integer() {
-                self.shape[i] = int as _;
-                if stream.axis == i {
-                    self.stream_info = None;
-                }
-            } else {
-                if stream.axis != i {
-                    bail!("Attempt at building a shape with two streaming dim")
-                } else {
-                    self.stream_info = Some(StreamInfo { len: dim, axis: i })
-                }
-            }
-        } else {
-            if let Ok(int) = dim.to_integer() {
-                self.shape[i] = int as _;
-            } else {
-                self.shape[i] = 0;
-                self.stream_info = Some(StreamInfo { len: dim, axis: i })
-            }
-        }
+        let mut dims = self.dims.clone();
+        dims[i] = dim;
+        *self = ShapeInfo::from_dims(dims)?;
         Ok(())
     }
 
-    /// Shape of the tensor, unless it is streaming.
+    /// Shape of the tensor, unless it has symbolic dimensions.
     pub fn as_finite(&self) -> Option<&[usize]> {
-        match self.stream_info {
-            None => Some(&*self.shape),
-            _ => None,
-        }
+        self.concrete.as_ref().map(|c| &**c)
     }
 
     /// Iterator over dimension of the shape.
     pub fn iter<'a>(&'a self) -> impl Iterator<Item = TDim> + 'a {
-        self.shape.clone().into_iter().enumerate().map(move |(ix, d)| {
-            if let Some(ref info) = self.stream_info {
-                if ix == info.axis {
-                    return info.len.clone();
-                }
-            }
-            (d as i64).to_dim()
-        })
+        self.dims.iter().cloned()
     }
 
     /// Convert the shape to an array of extended dimensions.
     pub fn to_tvec(&self) -> TVec<TDim> {
-        self.iter().collect::<TVec<TDim>>()
+        self.dims.clone()
     }
 
     /// Convert the shape to a fully determined shape fact.
@@ -157,26 +123,33 @@ impl ShapeInfo {
         ShapeFact::from(self.iter())
     }
 
+    /// Symbols appearing in the shape dimensions.
+    pub fn symbols(&self) -> BTreeSet<char> {
+        self.dims.iter().flat_map(|d| d.symbols()).collect()
+    }
+
+    /// Concrete shape for the given symbol values.
+    pub fn eval(&self, values: &HashMap<char, i32>) -> TractResult<TVec<usize>> {
+        self.dims.iter().map(|d| Ok(d.eval_with(values)? as usize)).collect()
+    }
+
     pub fn from_dims<T: AsRef<[TDim]> + std::fmt::Debug>(it: T) -> TractResult<ShapeInfo> {
         let count = it.as_ref().iter().filter(|t| t.is_stream()).count();
         if count > 1 {
             bail!("Shape with two streaming dims are invalid: {:?}", it)
-        } else {
-            let stream_info = it
-                .as_ref()
-                .iter()
-                .enumerate()
-                .find(|(_ix, d)| d.is_stream())
-                .map(|(ix, d)| StreamInfo { axis: ix, len: d.clone() });
-            Ok(ShapeInfo {
-                shape: it
-                    .as_ref()
-                    .iter()
-                    .map(|t| t.to_integer().map(|i| i as usize).unwrap_or(0))
-                    .collect(),
-                stream_info,
-            })
         }
+        let stream_info = it
+            .as_ref()
+            .iter()
+            .enumerate()
+            .find(|(_ix, d)| d.is_stream())
+            .map(|(ix, d)| StreamInfo { axis: ix, len: d.clone() });
+        let concrete = it
+            .as_ref()
+            .iter()
+            .map(|d| d.to_integer().ok().map(|i| i as usize))
+            .collect::<Option<TVec<usize>>>();
+        Ok(ShapeInfo { dims: it.as_ref().into(), concrete, stream_info })
     }
 }
 
@@ -197,7 +170,11 @@ impl TryFrom<&[TDim]> for ShapeInfo {
 impl TryFrom<&[usize]> for ShapeInfo {
     type Error = TractError;
     fn try_from(it: &[usize]) -> TractResult<ShapeInfo> {
-        Ok(ShapeInfo { shape: it.into(), stream_info: None })
+        Ok(ShapeInfo {
+            dims: it.iter().map(|d| d.to_dim()).collect(),
+            concrete: Some(it.into()),
+            stream_info: None,
+        })
     }
 }
 
@@ -210,6 +187,7 @@ impl fmt::Debug for ShapeInfo {
 
 /// Fully determined tensor information for TypedModel.
 #[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct TypedTensorInfo {
     /// tensor element type
     pub datum_type: DatumType,
@@ -262,7 +240,7 @@ impl From<Arc<Tensor>> for TypedTensorInfo {
     fn from(t: Arc<Tensor>) -> TypedTensorInfo {
         TypedTensorInfo {
             datum_type: t.datum_type(),
-            shape: ShapeInfo { shape: t.shape().into(), stream_info: None },
+            shape: t.shape().try_into().unwrap(),
             konst: Some(t),
         }
     }
@@ -294,6 +272,7 @@ impl fmt::Debug for TypedTensorInfo {
 /// Constant value is not allowed, as all tensors in normalized forms are
 /// variables.
 #[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct NormalizedTensorInfo {
     /// tensor element type
     pub datum_type: DatumType,
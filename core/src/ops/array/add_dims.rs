@@ -1,6 +1,7 @@
 use crate::internal::*;
 
 #[derive(Debug, Clone, new)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct AddDims {
     pub axes: Vec<usize>,
 }
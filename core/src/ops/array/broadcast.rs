@@ -64,8 +64,9 @@ impl InferenceRulesOp for MultiBroadcastTo {
 }
 
 #[derive(Debug, Clone, new, Default)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct TypedMultiBroadcastTo {
-    shape: TVec<TDim>,
+    pub shape: TVec<TDim>,
 }
 
 impl Op for TypedMultiBroadcastTo {
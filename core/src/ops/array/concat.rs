@@ -11,18 +11,8 @@ pub struct Concat {
 }
 
 impl Concat {
-    fn resolve_axis(&self, rank: i64) -> TractResult<usize> {
-        if 0 <= self.axis && self.axis <= rank - 1 {
-            Ok(self.axis as usize)
-        } else if -rank <= self.axis && self.axis < 0 {
-            Ok((self.axis + rank) as usize)
-        } else {
-            bail!("Illegal combination of values for rank and axis: {} and {}", rank, self.axis)
-        }
-    }
-
     fn eval<T: Datum>(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
-        let axis = self.resolve_axis(inputs[0].shape().len() as i64)?;
+        let axis = resolve_axis(self.axis, inputs[0].shape().len())?;
         let mut slices: TVec<FixedConcatSlice<T>> = tvec![];
         for input in &inputs {
             let shape = Tensor::shape(&input);
@@ -45,7 +35,7 @@ impl Op for Concat {
         let inputs = model.node_input_facts(node.id)?;
 
         if let Some(super_type) = DatumType::super_type_for(inputs.iter().map(|x| x.datum_type)) {
-            let axis = self.resolve_axis(inputs[0].shape.rank() as i64)?;
+            let axis = resolve_axis(self.axis, inputs[0].shape.rank())?;
 
             let mut slices: TVec<NormConcatSlice> = tvec![];
             for input in inputs.iter() {
@@ -116,7 +106,7 @@ impl InferenceRulesOp for Concat {
             s.equals(&outputs[0].datum_type, super_type)
         })?;
         s.given(&inputs[0].rank, move |s, rank| {
-            let axis = self.resolve_axis(rank as i64)?;
+            let axis = resolve_axis(self.axis, rank as usize)?;
             s.equals(
                 crate::analyser::rules::expr::SumExp::new(
                     (0..n).map(|i| (&inputs[i].shape[axis]).bex()).collect(),
@@ -144,7 +134,7 @@ impl TypedOp for Concat {
     typed_op_as_op!();
 
     fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
-        let axis = self.resolve_axis(inputs[0].shape.rank() as i64)?;
+        let axis = resolve_axis(self.axis, inputs[0].shape.rank())?;
         let mut shape = inputs[0].shape.clone();
         let dim = inputs.iter().map(|f| f.shape.dim(axis)).sum::<TDim>();
         shape.set_dim(axis, dim)?;
@@ -154,6 +144,7 @@ impl TypedOp for Concat {
 
 /// NormConcatSlice: fully decluttered Concat equivalent
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub enum NormConcatSlice {
     Const(Tensor),
     Var,
@@ -176,6 +167,7 @@ impl NormConcatSlice {
 }
 
 #[derive(new, Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct NormConcat {
     pub axis: usize,
     pub slices: TVec<NormConcatSlice>,
@@ -270,10 +262,6 @@ impl TypedOp for NormConcat {
         mapping: &HashMap<OutletId, OutletId>,
         _pulse: usize,
     ) -> TractResult<TVec<OutletId>> {
-        if node.inputs.len() > 1 {
-            bail!("Pulsification not implemented for more than one input to Concat")
-        }
-
         let input = mapping[&node.inputs[0]];
         let fact = target.outlet_fact(input)?;
 
@@ -282,7 +270,7 @@ impl TypedOp for NormConcat {
                 self, source, node, target, mapping
             ))
         } else {
-            bail!("Pulsify for Concat on a separate axis is not implemented (but possible)");
+            self.pulsify_along_other_axis(node, target, mapping)
         }
     }
 }
@@ -301,6 +289,47 @@ impl StatelessOp for NormConcat {
 }
 
 impl NormConcat {
+    /// Concatenating streams along another axis than the streaming one only
+    /// needs the inputs to be in sync: they must stream along the same axis,
+    /// with the same pulse and length. Delays are aligned on the largest one.
+    fn pulsify_along_other_axis(
+        &self,
+        node: &NormalizedNode,
+        target: &mut PulsedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        if self.slices.iter().any(|s| !s.is_var()) {
+            bail!("Concat of constants along a separate axis can not be pulsified")
+        }
+        let facts = node
+            .inputs
+            .iter()
+            .map(|i| Ok(target.outlet_fact(mapping[i])?.clone()))
+            .collect::<TractResult<TVec<_>>>()?;
+        for f in &facts[1..] {
+            if f.axis != facts[0].axis || f.pulse() != facts[0].pulse() || f.dim != facts[0].dim {
+                bail!("Can not concat streams with different axes, pulses or lengths: {:?}", facts)
+            }
+        }
+        let delay = facts.iter().map(|f| f.delay).max().unwrap();
+        let mut fact = facts[0].clone();
+        fact.delay = delay;
+        fact.shape[self.axis] = facts.iter().map(|f| f.shape[self.axis]).sum();
+        let id = target.add_node(&*node.name, self.clone(), tvec!(fact))?;
+        for (ix, input) in node.inputs.iter().enumerate() {
+            let mut prec = mapping[input];
+            if facts[ix].delay < delay {
+                let mut fixed = facts[ix].clone();
+                fixed.delay = delay;
+                let buffer_op = Delay::new(facts[ix].clone(), delay - facts[ix].delay, 0);
+                let name = format!("{}/Delay-{}", node.name, ix);
+                prec = OutletId::new(target.chain_after(prec, name, buffer_op, tvec!(fixed))?, 0);
+            }
+            target.add_edge(prec, InletId::new(id, ix))?;
+        }
+        Ok(tvec!(OutletId::new(id, 0)))
+    }
+
     fn pulsify_along_concat_axis_t<T: Datum>(
         &self,
         _source: &NormalizedModel,
@@ -505,6 +534,17 @@ impl<T: Datum> OpState for PulsedSameAxisConcatState<T> {
 
         return Ok(tvec!(data.into_arc_tensor()));
     }
+
+    #[cfg(feature = "serialize")]
+    fn save_state(&self) -> TractResult<Vec<u8>> {
+        Ok(bincode::serialize(&self.current_pos)?)
+    }
+
+    #[cfg(feature = "serialize")]
+    fn load_state(&mut self, data: &[u8]) -> TractResult<()> {
+        self.current_pos = bincode::deserialize(data)?;
+        Ok(())
+    }
 }
 
 ////////////////////////////////////////////////
@@ -599,3 +639,70 @@ impl<T: Datum> TypedOp for FixedConcat<T> {
         Ok(tvec!(fact))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::array::{Pad, PadMode};
+    use crate::pulse::{PulsedModelState, PulsedTensorFact};
+
+    /// Concatenates a [1, 1, S] stream with itself going through a padded
+    /// identity convolution, along the channel axis.
+    fn model(before: usize, after: usize) -> InferenceModel {
+        let mut model = InferenceModel::default();
+        let shape = tvec!(1.to_dim(), 1.to_dim(), TDim::s());
+        let a = model.add_source("a", TensorFact::dt_shape(f32::datum_type(), shape)).unwrap();
+        let pad = Pad::new(
+            vec![(0, 0), (0, 0), (before, after)],
+            PadMode::Constant(Arc::new(Tensor::from(0f32))),
+        );
+        model.chain_default("pad", pad).unwrap();
+        let conv = model.chain_default("conv", crate::ops::cnn::Conv::default()).unwrap();
+        let kernel = model.add_const("kernel", tensor3(&[[[0.0f32, 1.0, 0.0]]])).unwrap();
+        model.add_edge(OutletId::new(kernel, 0), InletId::new(conv, 1)).unwrap();
+        let inputs = [OutletId::new(a, 0), OutletId::new(conv, 0)];
+        model.wire_node("concat", Concat::new(1), &inputs).unwrap();
+        model.auto_outputs().unwrap();
+        model
+    }
+
+    #[test]
+    fn pulsify_along_other_axis_aligns_delays() {
+        let model = model(1, 1).into_normalized().unwrap();
+        let pulsed = PulsedModel::new(&model, 2).unwrap();
+        let fact = pulsed.output_fact(0).unwrap();
+        assert_eq!((fact.axis, fact.delay, &*fact.shape), (2, 2, &[1, 2, 2][..]));
+        let plan = SimplePlan::new(pulsed).unwrap();
+        let mut state = PulsedModelState::new(&plan).unwrap();
+        let mut got = vec![];
+        for chunk in &[[1.0f32, 2.0], [3.0, 4.0]] {
+            got.extend(state.run(tvec!(tensor3(&[[*chunk]]))).unwrap());
+        }
+        let nan = tensor0(std::f32::NAN);
+        got.extend(state.finish_stream(tvec!(tensor3(&[[[5.0f32]]])), &nan).unwrap());
+        let views = got.iter().map(|t| t.to_array_view::<f32>().unwrap()).collect::<Vec<_>>();
+        let input = [1.0f32, 2.0, 3.0, 4.0, 5.0];
+        assert_eq!(Tensor::from(stack(Axis(2), &views).unwrap()), tensor3(&[[input, input]]));
+    }
+
+    #[test]
+    fn pulsify_along_other_axis_needs_same_pulse() {
+        let model = model(1, 1).into_normalized().unwrap();
+        let concat = model.node_by_name("concat").unwrap().id;
+        let mut target = PulsedModel::default();
+        let mut mapping = HashMap::new();
+        for (ix, pulse) in [2, 4].iter().enumerate() {
+            let fact = PulsedTensorFact {
+                dt: f32::datum_type(),
+                shape: tvec!(1, 1, *pulse),
+                axis: 2,
+                dim: TDim::s(),
+                delay: 0,
+            };
+            let source = target.add_source(format!("input-{}", ix), fact).unwrap();
+            mapping.insert(model.node(concat).inputs[ix], OutletId::new(source, 0));
+        }
+        let node = model.node(concat);
+        assert!(node.op.pulsify(&model, node, &mut target, &mapping, 2).is_err());
+    }
+}
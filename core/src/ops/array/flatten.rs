@@ -1,8 +1,9 @@
 use crate::internal::*;
 
 #[derive(Debug, Clone, new, Default)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct Flatten {
-    axis: usize,
+    pub axis: usize,
 }
 
 impl Flatten {
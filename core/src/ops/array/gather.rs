@@ -3,7 +3,17 @@ use ndarray::*;
 
 #[derive(Debug, Clone, new)]
 pub struct Gather {
-    axis: i64,
+    pub axis: i64,
+}
+
+/// Resolves an index along an axis of length `dim`, negative indices counting
+/// from the end.
+pub(crate) fn resolve_index(index: i64, dim: usize) -> TractResult<usize> {
+    let resolved = if index < 0 { index + dim as i64 } else { index };
+    if resolved < 0 || resolved >= dim as i64 {
+        bail!("Index {} is out of bounds for an axis of length {}", index, dim)
+    }
+    Ok(resolved as usize)
 }
 
 impl Op for Gather {
@@ -15,22 +25,12 @@ impl Op for Gather {
 }
 
 impl Gather {
-    fn resolved_axis(&self, rank: usize) -> TractResult<usize> {
-        if 0 <= self.axis && self.axis <= rank as i64 - 1 {
-            Ok(self.axis as usize)
-        } else if -(rank as i64) <= self.axis && self.axis < 0 {
-            Ok((self.axis + rank as i64) as usize)
-        } else {
-            bail!("Illegal combination of values for rank and axis")
-        }
-    }
-
     fn compute_output_shape<D: DimLike>(
         &self,
         input_shape: &[D],
         indices_shape: &[D],
     ) -> TractResult<TVec<D>> {
-        let axis = self.resolved_axis(input_shape.len())?;
+        let axis = resolve_axis(self.axis, input_shape.len())?;
         let mut output_shape = tvec![];
         for (idx, dim) in input_shape.iter().enumerate() {
             if idx != axis {
@@ -47,32 +47,27 @@ impl Gather {
     fn eval_t<T: Datum>(
         &self,
         data: Arc<Tensor>,
-        indices: &Arc<Tensor>,
+        indices: &ArrayViewD<i64>,
     ) -> TractResult<Arc<Tensor>> {
         let data_view = data.to_array_view::<T>()?;
-        let axis = self.resolved_axis(data.shape().len())?;
+        let axis = resolve_axis(self.axis, data.shape().len())?;
+        let dim = data_view.shape()[axis];
         if indices.shape().len() == 0 {
-            let mut index = *indices.to_scalar::<i64>()?;
-            if index < 0 {
-                index += data_view.shape()[0] as i64;
-            }
-            return Ok(data_view
-                .index_axis(Axis(axis), index as usize)
-                .to_owned()
-                .into_arc_tensor());
+            let index = resolve_index(*indices.iter().next().unwrap(), dim)?;
+            return Ok(data_view.index_axis(Axis(axis), index).to_owned().into_arc_tensor());
         }
 
         let mut output: Array<T, _> = unsafe {
             T::uninitialized_array(&*self.compute_output_shape(data.shape(), indices.shape())?)
         };
-        for (pattern, index) in indices.to_array_view::<i64>()?.indexed_iter() {
+        for (pattern, index) in indices.indexed_iter() {
             {
                 let mut to_update = output.index_axis_mut(Axis(axis), pattern[0]);
                 for idx in 1..pattern.ndim() {
                     to_update = to_update.index_axis_move(Axis(0), pattern[idx]);
                 }
 
-                to_update.assign(&data_view.index_axis(Axis(axis), *index as usize));
+                to_update.assign(&data_view.index_axis(Axis(axis), resolve_index(*index, dim)?));
             }
         }
         Ok(output.into_arc_tensor())
@@ -95,6 +90,8 @@ impl StatelessOp for Gather {
     /// Evaluates the operation given the input tensors.
     fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
         let (data, indices) = args_2!(inputs);
+        let indices = indices.cast_to::<i64>()?;
+        let indices = indices.to_array_view::<i64>()?;
         Ok(tvec!(dispatch_datum!(Self::eval_t(data.datum_type())(&self, data, &indices))?))
     }
 }
@@ -139,4 +136,13 @@ mod tests {
             assert_eq!(*output.to_scalar::<i64>().unwrap(), idx + 1);
         }
     }
+
+    #[test]
+    fn test_should_gather_negative_indices() {
+        let data = rctensor2(&[[1i64, 2, 3], [4, 5, 6]]);
+        let outputs = Gather::new(1).eval(tvec![data.clone(), rctensor0(-1i64)]).unwrap();
+        assert_eq!(outputs[0], rctensor1(&[3i64, 6]));
+        let outputs = Gather::new(1).eval(tvec![data, rctensor1(&[-3i64, 1])]).unwrap();
+        assert_eq!(outputs[0], rctensor2(&[[1i64, 2], [4, 5]]));
+    }
 }
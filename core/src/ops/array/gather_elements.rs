@@ -0,0 +1,101 @@
+use crate::internal::*;
+use ndarray::*;
+
+use super::gather::resolve_index;
+
+/// Picks one element of the data for each element of the indices, which have
+/// the same rank as the data. Along `axis`, the coordinate is given by the
+/// index, other coordinates are the index ones.
+#[derive(Debug, Clone, new)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct GatherElements {
+    pub axis: i64,
+}
+
+impl GatherElements {
+    fn eval_t<T: Datum>(
+        &self,
+        data: Arc<Tensor>,
+        indices: &ArrayViewD<i64>,
+    ) -> TractResult<Arc<Tensor>> {
+        let data = data.to_array_view::<T>()?;
+        let axis = resolve_axis(self.axis, data.ndim())?;
+        if indices.ndim() != data.ndim() {
+            bail!("GatherElements expects indices of rank {}, got {}", data.ndim(), indices.ndim())
+        }
+        let values = indices
+            .indexed_iter()
+            .map(|(mut coords, &index)| {
+                coords[axis] = resolve_index(index, data.shape()[axis])?;
+                data.get(&coords).cloned().ok_or_else(|| {
+                    format!("Indices {:?} are out of data shape {:?}", coords, data.shape()).into()
+                })
+            })
+            .collect::<TractResult<Vec<T>>>()?;
+        Ok(ArrayD::from_shape_vec(indices.shape(), values)?.into_arc_tensor())
+    }
+}
+
+impl Op for GatherElements {
+    fn name(&self) -> Cow<str> {
+        "GatherElements".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {}", self.axis)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for GatherElements {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (data, indices) = args_2!(inputs);
+        let indices = indices.cast_to::<i64>()?;
+        let indices = indices.to_array_view::<i64>()?;
+        Ok(tvec!(dispatch_datum!(Self::eval_t(data.datum_type())(self, data, &indices))?))
+    }
+}
+
+impl InferenceRulesOp for GatherElements {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 2)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, &inputs[1].rank)?;
+        s.equals(&inputs[1].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    inference_op_as_op!();
+    to_typed!();
+}
+
+impl TypedOp for GatherElements {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        Ok(tvec!(TypedTensorInfo::dt_shape(inputs[0].datum_type, &*inputs[1].shape.to_tvec())?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gather_elements() {
+        let data = rctensor2(&[[1, 2, 3], [4, 5, 6], [7, 8, 9]]);
+        let op = GatherElements::new(0);
+        let output = op.eval(tvec!(data.clone(), rctensor2(&[[1i64, 2, 0], [2, 0, 0]]))).unwrap();
+        assert_eq!(output[0], rctensor2(&[[4, 8, 3], [7, 2, 3]]));
+        let op = GatherElements::new(-1);
+        let output = op.eval(tvec!(data, rctensor2(&[[-1i32], [0], [1]]))).unwrap();
+        assert_eq!(output[0], rctensor2(&[[3], [4], [8]]));
+    }
+}
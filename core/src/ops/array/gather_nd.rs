@@ -1,15 +1,15 @@
+use crate::internal::*;
 use ndarray::*;
 
-use tract_core::internal::*;
+use super::gather::resolve_index;
 
-use crate::model::ParsingContext;
-use crate::tfpb::node_def::NodeDef;
-
-#[derive(Debug, Clone, new)]
-pub struct GatherNd {}
-
-pub fn gather_nd(_ctx: &ParsingContext, _pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
-    Ok(Box::new(GatherNd::new()))
+/// Gathers slices of the data: the last axis of the indices holds
+/// coordinates into the data, starting after the `batch_dims` first axes,
+/// which are shared by data and indices.
+#[derive(Debug, Clone, new, Default)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct GatherNd {
+    pub batch_dims: usize,
 }
 
 impl GatherNd {
@@ -19,15 +19,23 @@ impl GatherNd {
         indices_shape: &[D],
     ) -> TractResult<TVec<D>> {
         let mut shape: TVec<D> = indices_shape.into();
-        let n = shape.pop().unwrap().to_integer()? as usize;
-        shape.extend(data_shape[n..].iter().cloned());
+        let n =
+            shape.pop().ok_or("GatherNd expects indices of rank 1 or more")?.to_integer()? as usize;
+        if self.batch_dims + n > data_shape.len() {
+            bail!(
+                "GatherNd: {} coordinates for data of rank {}",
+                self.batch_dims + n,
+                data_shape.len()
+            )
+        }
+        shape.extend(data_shape[self.batch_dims + n..].iter().cloned());
         Ok(shape)
     }
 
     fn eval_t<T: Datum>(
         &self,
         data: &Arc<Tensor>,
-        indices: &ArrayViewD<i32>,
+        indices: &ArrayViewD<i64>,
     ) -> TractResult<TVec<Arc<Tensor>>> {
         let data = data.to_array_view::<T>()?;
         let shape = self.compute_shape(&data.shape(), &indices.shape())?;
@@ -35,13 +43,17 @@ impl GatherNd {
         for prefix in ndarray::indices(&indices.shape()[0..indices.ndim() - 1]) {
             let mut dst = array.view_mut();
             let mut coords = indices.view();
-            for &x in prefix.slice().iter() {
+            let mut src = data.view();
+            for (axis, &x) in prefix.slice().iter().enumerate() {
                 dst.index_axis_inplace(Axis(0), x);
                 coords.index_axis_inplace(Axis(0), x);
+                if axis < self.batch_dims {
+                    src.index_axis_inplace(Axis(0), x);
+                }
             }
-            let mut src = data.view();
             for &x in coords.iter() {
-                src.index_axis_inplace(Axis(0), x as _);
+                let x = resolve_index(x, src.shape()[0])?;
+                src.index_axis_inplace(Axis(0), x);
             }
             dst.assign(&src);
         }
@@ -51,7 +63,11 @@ impl GatherNd {
 
 impl Op for GatherNd {
     fn name(&self) -> Cow<str> {
-        "tf.GatherNd".into()
+        "GatherNd".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("batch_dims: {}", self.batch_dims)])
     }
 
     op_as_typed_op!();
@@ -60,8 +76,8 @@ impl Op for GatherNd {
 impl StatelessOp for GatherNd {
     fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
         let (data, indices) = args_2!(inputs);
-        let indices = indices.cast_to::<i32>()?;
-        let indices = indices.to_array_view::<i32>()?;
+        let indices = indices.cast_to::<i64>()?;
+        let indices = indices.to_array_view::<i64>()?;
         dispatch_datum!(Self::eval_t(data.datum_type())(self, &data, &indices))
     }
 }
@@ -83,11 +99,19 @@ impl InferenceRulesOp for GatherNd {
             }
             s.given_2(
                 &inputs[1].shape[indices_rank - 1],
-                &inputs[1].rank,
+                &inputs[0].rank,
                 move |s, n, input_rank| {
                     if let Ok(n) = n.to_integer() {
-                        for i in 0..(input_rank - n) as usize {
-                            s.equals(&outputs[0].shape[indices_rank - 1 + i], &inputs[1].shape[i])?;
+                        let n = self.batch_dims + n as usize;
+                        s.equals(
+                            &outputs[0].rank,
+                            (indices_rank - 1 + input_rank as usize - n) as i32,
+                        )?;
+                        for i in 0..(input_rank as usize - n) {
+                            s.equals(
+                                &outputs[0].shape[indices_rank - 1 + i],
+                                &inputs[0].shape[n + i],
+                            )?;
                         }
                     }
                     Ok(())
@@ -116,7 +140,7 @@ mod tests {
     // https://www.tensorflow.org/api_docs/python/tf/gather_nd
     #[test]
     fn simple_indexing() {
-        let g = GatherNd::new();
+        let g = GatherNd::default();
         assert_eq!(
             g.eval(tvec!(rctensor2(&[[1, 2], [3, 4]]), rctensor2(&[[0, 0], [1, 1]]))).unwrap(),
             tvec!(rctensor1(&[1, 4]))
@@ -125,7 +149,7 @@ mod tests {
 
     #[test]
     fn slice_indexing() {
-        let g = GatherNd::new();
+        let g = GatherNd::default();
         assert_eq!(
             g.eval(tvec!(rctensor2(&[[1, 2], [3, 4]]), rctensor2(&[[1], [0]]))).unwrap(),
             tvec!(rctensor2(&[[3, 4], [1, 2]]))
@@ -134,7 +158,7 @@ mod tests {
 
     #[test]
     fn tensor_3d_1() {
-        let g = GatherNd::new();
+        let g = GatherNd::default();
         let t = rctensor3(&[[[10, 20], [30, 40]], [[11, 21], [31, 41]]]);
         assert_eq!(
             g.eval(tvec!(t.clone(), rctensor2(&[[1]]))).unwrap(),
@@ -144,7 +168,7 @@ mod tests {
 
     #[test]
     fn tensor_3d_2() {
-        let g = GatherNd::new();
+        let g = GatherNd::default();
         let t = rctensor3(&[[[10, 20], [30, 40]], [[11, 21], [31, 41]]]);
         assert_eq!(
             g.eval(tvec!(t.clone(), rctensor2(&[[0, 1], [1, 0]]))).unwrap(),
@@ -154,11 +178,31 @@ mod tests {
 
     #[test]
     fn tensor_3d_3() {
-        let g = GatherNd::new();
+        let g = GatherNd::default();
         let t = rctensor3(&[[[10, 20], [30, 40]], [[11, 21], [31, 41]]]);
         assert_eq!(
             g.eval(tvec!(t.clone(), rctensor2(&[[0, 0, 1], [1, 0, 1]]))).unwrap(),
             tvec!(rctensor1(&[20, 21]))
         );
     }
+
+    #[test]
+    fn negative_indices() {
+        let g = GatherNd::default();
+        assert_eq!(
+            g.eval(tvec!(rctensor2(&[[1, 2], [3, 4]]), rctensor2(&[[-1i64, 0], [0, -1]]))).unwrap(),
+            tvec!(rctensor1(&[3, 2]))
+        );
+    }
+
+    // https://github.com/onnx/onnx/blob/master/docs/Operators.md#GatherND
+    #[test]
+    fn batch_dims() {
+        let g = GatherNd::new(1);
+        let t = rctensor3(&[[[0, 1], [2, 3]], [[4, 5], [6, 7]]]);
+        assert_eq!(
+            g.eval(tvec!(t, rctensor2(&[[1i64], [0]]))).unwrap(),
+            tvec!(rctensor2(&[[2, 3], [4, 5]]))
+        );
+    }
 }
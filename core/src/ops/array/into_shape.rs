@@ -2,8 +2,9 @@ use crate::internal::*;
 use itertools::Itertools;
 
 #[derive(Debug, Clone, new, Default)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct IntoShape {
-    shape: TVec<usize>,
+    pub shape: TVec<usize>,
 }
 
 impl IntoShape {
@@ -49,11 +49,15 @@ mod constant_of_shape;
 mod crop;
 mod flatten;
 mod gather;
+mod gather_elements;
+mod gather_nd;
 mod into_shape;
+mod one_hot;
 mod pad;
 mod permute_axes;
 mod reshape;
 mod rm_dims;
+mod scatter;
 mod shape;
 mod size;
 mod slice;
@@ -63,21 +67,25 @@ mod tile;
 
 pub use self::add_dims::AddDims;
 pub use self::broadcast::{MultiBroadcastTo, TypedMultiBroadcastTo};
-pub use self::concat::Concat;
+pub use self::concat::{Concat, NormConcat, NormConcatSlice};
 pub use self::constant_like::ConstantLike;
 pub use self::constant_like::EyeLike;
 pub use self::constant_of_shape::ConstantOfShape;
 pub use self::crop::Crop;
 pub use self::flatten::Flatten;
 pub use self::gather::Gather;
+pub use self::gather_elements::GatherElements;
+pub use self::gather_nd::GatherNd;
 pub use self::into_shape::IntoShape;
+pub use self::one_hot::OneHot;
 pub use self::pad::{Pad, PadMode};
 pub use self::permute_axes::PermuteAxes;
 pub use self::reshape::Reshape;
 pub use self::rm_dims::RmDims;
+pub use self::scatter::{ScatterElements, ScatterNd, ScatterReduction};
 pub use self::shape::Shape;
 pub use self::size::Size;
 pub use self::slice::Slice;
 pub use self::split::Split;
 pub use self::squeeze::Squeeze;
-pub use self::tile::Tile;
+pub use self::tile::{Tile, TypedTile};
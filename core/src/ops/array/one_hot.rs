@@ -0,0 +1,115 @@
+use crate::internal::*;
+use ndarray::*;
+
+/// One-hot encoding of integer indices: a new axis of length `dim` is
+/// inserted at `axis`, set to `on` at the index position and `off` elsewhere.
+/// Indices out of `0..dim` only get `off` values, unless
+/// `wrap_negative_indices` is set and they are in `-dim..0`.
+#[derive(Debug, Clone, new)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct OneHot {
+    pub axis: usize,
+    pub dim: usize,
+    pub off: Arc<Tensor>,
+    pub on: Arc<Tensor>,
+    pub wrap_negative_indices: bool,
+}
+
+impl OneHot {
+    fn eval_t<T: Datum>(&self, indices: &ArrayViewD<i64>) -> TractResult<Arc<Tensor>> {
+        let off = self.off.to_scalar::<T>()?;
+        let on = self.on.to_scalar::<T>()?;
+        let mut shape: TVec<usize> = indices.shape().into();
+        shape.insert(self.axis, self.dim);
+        let mut output = ArrayD::from_elem(&*shape, off.clone());
+        for (coords, &index) in indices.indexed_iter() {
+            let index = if index < 0 && self.wrap_negative_indices {
+                index + self.dim as i64
+            } else {
+                index
+            };
+            if index >= 0 && index < self.dim as i64 {
+                let mut coords: TVec<usize> = coords.slice().into();
+                coords.insert(self.axis, index as usize);
+                output[&*coords] = on.clone();
+            }
+        }
+        Ok(output.into_arc_tensor())
+    }
+}
+
+impl Op for OneHot {
+    fn name(&self) -> Cow<str> {
+        "OneHot".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!(
+            "axis: {} dim: {} off: {:?} on: {:?}",
+            self.axis, self.dim, self.off, self.on
+        )])
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for OneHot {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let indices = args_1!(inputs);
+        let indices = indices.cast_to::<i64>()?;
+        let indices = indices.to_array_view::<i64>()?;
+        Ok(tvec!(dispatch_datum!(Self::eval_t(self.on.datum_type())(self, &indices))?))
+    }
+}
+
+impl InferenceRulesOp for OneHot {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&outputs[0].datum_type, self.on.datum_type())?;
+        s.equals(inputs[0].rank.bex() + 1, outputs[0].rank.bex())?;
+        s.given(&inputs[0].rank, move |s, rank| {
+            for i in 0..rank as usize {
+                let o = if i < self.axis { i } else { i + 1 };
+                s.equals(&inputs[0].shape[i], &outputs[0].shape[o])?;
+            }
+            s.equals(&outputs[0].shape[self.axis], self.dim.to_dim())
+        })
+    }
+
+    inference_op_as_op!();
+    to_typed!();
+}
+
+impl TypedOp for OneHot {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        let mut shape = inputs[0].shape.to_tvec();
+        shape.insert(self.axis, self.dim.to_dim());
+        Ok(tvec!(TypedTensorInfo::dt_shape(self.on.datum_type(), &*shape)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_hot() {
+        let op = OneHot::new(1, 3, rctensor0(0.0f32), rctensor0(1.0f32), true);
+        let output = op.eval(tvec!(rctensor1(&[0i64, 2, -1, 3]))).unwrap();
+        assert_eq!(
+            output[0],
+            rctensor2(&[[1.0f32, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 0.0, 1.0], [0.0, 0.0, 0.0]])
+        );
+        let op = OneHot::new(1, 3, rctensor0(0i32), rctensor0(5i32), false);
+        let output = op.eval(tvec!(rctensor1(&[1i32, -1]))).unwrap();
+        assert_eq!(output[0], rctensor2(&[[0i32, 5, 0], [0, 0, 0]]));
+    }
+}
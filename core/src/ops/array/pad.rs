@@ -3,6 +3,7 @@ use crate::pulse::delay::Delay;
 use ndarray::*;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub enum PadMode {
     Constant(Arc<Tensor>),
     Reflect,
@@ -16,9 +17,10 @@ impl Default for PadMode {
 }
 
 #[derive(Debug, Clone, new, Default)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct Pad {
-    pads: Vec<(usize, usize)>,
-    mode: PadMode,
+    pub pads: Vec<(usize, usize)>,
+    pub mode: PadMode,
 }
 
 impl Pad {
@@ -278,6 +280,19 @@ impl<T: Datum + Copy> OpState for PulsePadOpState<T> {
 
         Ok(tvec!(data.into_arc_tensor()))
     }
+
+    #[cfg(feature = "serialize")]
+    fn save_state(&self) -> TractResult<Vec<u8>> {
+        Ok(bincode::serialize(&(self.current_pos, &self.last_valid_frame))?)
+    }
+
+    #[cfg(feature = "serialize")]
+    fn load_state(&mut self, data: &[u8]) -> TractResult<()> {
+        let (current_pos, last_valid_frame) = bincode::deserialize(data)?;
+        self.current_pos = current_pos;
+        self.last_valid_frame = last_valid_frame;
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Default, new)]
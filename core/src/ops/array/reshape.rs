@@ -1,6 +1,12 @@
 use crate::internal::*;
 
+/// Reshape to the shape given as second input, with ONNX conventions: 0
+/// copies the input dimension, and -1 is inferred from the others.
+///
+/// In a typed model, the shape must be a constant. Reshape handles a symbolic
+/// input shape, and is decluttered to IntoShape once it is known.
 #[derive(Debug, Clone, new, Default)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct Reshape {}
 
 impl Reshape {
@@ -14,14 +20,16 @@ impl Reshape {
             .map(|(&shape, input)| if shape > 0 { D::from(shape as usize) } else { input.clone() })
             .collect();
         if let Some(minus_one) = shape.iter().position(|d| *d == -1) {
-            let prod_input: usize =
-                input.iter().try_fold(1, |acc, dim| dim.to_integer().map(|a| a as usize * acc))?;
-            let prod_shape: usize = result
+            // input axes copied by a 0 cancel out with themselves, so the
+            // quotient stays exact with symbolic dimensions
+            let prod_input: D = input
                 .iter()
                 .enumerate()
-                .filter(|(ix, _)| *ix != minus_one)
-                .try_fold(1, |acc, (_, dim)| dim.to_integer().map(|a| a as usize * acc))?;
-            result[minus_one] = D::from(prod_input / prod_shape);
+                .filter(|(ix, _)| shape.get(*ix) != Some(&0))
+                .map(|(_, dim)| dim.clone())
+                .product();
+            let prod_shape: usize = shape.iter().filter(|d| **d > 0).map(|&d| d as usize).product();
+            result[minus_one] = prod_input / prod_shape;
         }
         Ok(result)
     }
@@ -41,7 +49,20 @@ impl Op for Reshape {
         "Reshape".into()
     }
 
-    not_a_typed_op!();
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let shape = model.outlet_fact(OutletId::new(node.id, 0))?.shape.as_finite();
+        if let Some(shape) = shape {
+            let op = super::IntoShape::new(shape.into());
+            return Ok(Some(TypedModelPatch::single_unary_op(model, node, op)?));
+        }
+        Ok(None)
+    }
+
+    op_as_typed_op!();
 }
 
 impl StatelessOp for Reshape {
@@ -82,13 +103,35 @@ impl InferenceRulesOp for Reshape {
         mapping: &HashMap<OutletId, OutletId>,
     ) -> TractResult<TVec<OutletId>> {
         if let Some(ref shape) = target.outlet_fact(mapping[&node.inputs[1]])?.konst {
-            let shape: TVec<usize> =
-                shape.cast_to::<i64>()?.as_slice::<i64>()?.iter().map(|i| *i as usize).collect();
-            let op = super::IntoShape::new(shape);
-            return target.wire_node(&*node.name, op, [mapping[&node.inputs[0]]].as_ref());
+            let input_shape = target.outlet_fact(mapping[&node.inputs[0]])?.shape.to_tvec();
+            let shape: Vec<isize> =
+                shape.cast_to::<i64>()?.as_slice::<i64>()?.iter().map(|i| *i as isize).collect();
+            let shape = self
+                .compute_shape(&input_shape, &shape)?
+                .iter()
+                .map(|d| d.to_integer().map(|d| d as usize))
+                .collect::<TractResult<TVec<usize>>>();
+            if let Ok(shape) = shape {
+                let op = super::IntoShape::new(shape);
+                return target.wire_node(&*node.name, op, [mapping[&node.inputs[0]]].as_ref());
+            }
+            let inputs = node.inputs.iter().map(|i| mapping[i]).collect::<TVec<_>>();
+            return target.wire_node(&*node.name, self.clone(), &*inputs);
         }
         bail!("shape input is variable")
     }
 
     inference_op_as_op!();
 }
+
+impl TypedOp for Reshape {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        let shape = inputs[1].konst.as_ref().ok_or("Reshape needs a constant shape")?;
+        let shape: Vec<isize> =
+            shape.cast_to::<i64>()?.as_slice::<i64>()?.iter().map(|i| *i as isize).collect();
+        let shape = self.compute_shape(&*inputs[0].shape.to_tvec(), &shape)?;
+        Ok(tvec!(TypedTensorInfo::dt_shape(inputs[0].datum_type, &*shape)?))
+    }
+}
@@ -0,0 +1,331 @@
+use crate::internal::*;
+use ndarray::*;
+
+use super::gather::resolve_index;
+
+/// How updates are combined with the data they land on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum ScatterReduction {
+    /// Updates replace the data.
+    None,
+    Add,
+    Mul,
+    Max,
+    Min,
+}
+
+impl ScatterReduction {
+    fn combine<T>(&self) -> fn(&mut T, &T)
+    where
+        T: Datum + PartialOrd + std::ops::Add<Output = T> + std::ops::Mul<Output = T>,
+    {
+        match self {
+            ScatterReduction::None => |a, b| *a = b.clone(),
+            ScatterReduction::Add => |a, b| *a = a.clone() + b.clone(),
+            ScatterReduction::Mul => |a, b| *a = a.clone() * b.clone(),
+            ScatterReduction::Max => |a, b| {
+                if *b > *a {
+                    *a = b.clone()
+                }
+            },
+            ScatterReduction::Min => |a, b| {
+                if *b < *a {
+                    *a = b.clone()
+                }
+            },
+        }
+    }
+}
+
+/// Copy of the data, with one element updated for each element of the
+/// indices and updates, which have the same shape and the data rank. Along
+/// `axis`, the coordinate is given by the index, other coordinates are the
+/// index ones.
+#[derive(Debug, Clone, new)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct ScatterElements {
+    pub axis: i64,
+    pub reduction: ScatterReduction,
+}
+
+impl ScatterElements {
+    fn scatter<T: Datum>(
+        &self,
+        data: &Tensor,
+        indices: &ArrayViewD<i64>,
+        updates: &Tensor,
+        combine: fn(&mut T, &T),
+    ) -> TractResult<Arc<Tensor>> {
+        let mut data = data.to_array_view::<T>()?.to_owned();
+        let updates = updates.to_array_view::<T>()?;
+        let axis = resolve_axis(self.axis, data.ndim())?;
+        if indices.ndim() != data.ndim() || indices.shape() != updates.shape() {
+            bail!(
+                "ScatterElements with data {:?}, indices {:?} and updates {:?}",
+                data.shape(),
+                indices.shape(),
+                updates.shape()
+            )
+        }
+        for ((mut coords, &index), update) in indices.indexed_iter().zip(updates.iter()) {
+            coords[axis] = resolve_index(index, data.shape()[axis])?;
+            let shape = data.shape().to_vec();
+            let target = data
+                .get_mut(&coords)
+                .ok_or_else(|| format!("Indices {:?} are out of data shape {:?}", coords, shape))?;
+            combine(target, update);
+        }
+        Ok(data.into_arc_tensor())
+    }
+
+    fn eval_t<T: Datum>(
+        &self,
+        data: &Tensor,
+        indices: &ArrayViewD<i64>,
+        updates: &Tensor,
+    ) -> TractResult<Arc<Tensor>> {
+        self.scatter::<T>(data, indices, updates, |a, b| *a = b.clone())
+    }
+
+    fn eval_numbers_t<T>(
+        &self,
+        data: &Tensor,
+        indices: &ArrayViewD<i64>,
+        updates: &Tensor,
+    ) -> TractResult<Arc<Tensor>>
+    where
+        T: Datum + PartialOrd + std::ops::Add<Output = T> + std::ops::Mul<Output = T>,
+    {
+        self.scatter::<T>(data, indices, updates, self.reduction.combine())
+    }
+}
+
+impl Op for ScatterElements {
+    fn name(&self) -> Cow<str> {
+        "ScatterElements".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {} reduction: {:?}", self.axis, self.reduction)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for ScatterElements {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (data, indices, updates) = args_3!(inputs);
+        let indices = indices.cast_to::<i64>()?;
+        let indices = indices.to_array_view::<i64>()?;
+        let dt = data.datum_type();
+        let output = if self.reduction == ScatterReduction::None {
+            dispatch_datum!(Self::eval_t(dt)(self, &data, &indices, &updates))?
+        } else {
+            dispatch_numbers!(Self::eval_numbers_t(dt)(self, &data, &indices, &updates))?
+        };
+        Ok(tvec!(output))
+    }
+}
+
+impl InferenceRulesOp for ScatterElements {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 3)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].datum_type, &inputs[2].datum_type)?;
+        s.equals(&inputs[0].rank, &inputs[1].rank)?;
+        s.equals(&inputs[1].shape, &inputs[2].shape)?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    inference_op_as_op!();
+    to_typed!();
+}
+
+impl TypedOp for ScatterElements {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        Ok(tvec!(TypedTensorInfo::dt_shape(inputs[0].datum_type, &*inputs[0].shape.to_tvec())?))
+    }
+}
+
+/// Copy of the data, with slices updated: the last axis of the indices holds
+/// coordinates into the data, the updates have the indices shape (last axis
+/// excepted) followed by the shape of the updated slices.
+#[derive(Debug, Clone, new)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct ScatterNd {
+    pub reduction: ScatterReduction,
+}
+
+impl ScatterNd {
+    fn scatter<T: Datum>(
+        &self,
+        data: &Tensor,
+        indices: &ArrayViewD<i64>,
+        updates: &Tensor,
+        combine: fn(&mut T, &T),
+    ) -> TractResult<Arc<Tensor>> {
+        let mut data = data.to_array_view::<T>()?.to_owned();
+        let updates = updates.to_array_view::<T>()?;
+        let prefix_rank = indices.ndim().saturating_sub(1);
+        let n = indices.shape().get(prefix_rank).cloned().unwrap_or(0);
+        if indices.ndim() == 0
+            || n > data.ndim()
+            || updates.shape()[..]
+                != [&indices.shape()[..prefix_rank], &data.shape()[n..]].concat()[..]
+        {
+            bail!(
+                "ScatterNd with data {:?}, indices {:?} and updates {:?}",
+                data.shape(),
+                indices.shape(),
+                updates.shape()
+            )
+        }
+        for prefix in ndarray::indices(&indices.shape()[..prefix_rank]) {
+            let mut coords = indices.view();
+            let mut src = updates.view();
+            for &x in prefix.slice().iter() {
+                coords.index_axis_inplace(Axis(0), x);
+                src.index_axis_inplace(Axis(0), x);
+            }
+            let mut dst = data.view_mut();
+            for &x in coords.iter() {
+                let x = resolve_index(x, dst.shape()[0])?;
+                dst.index_axis_inplace(Axis(0), x);
+            }
+            dst.zip_mut_with(&src, |a, b| combine(a, b));
+        }
+        Ok(data.into_arc_tensor())
+    }
+
+    fn eval_t<T: Datum>(
+        &self,
+        data: &Tensor,
+        indices: &ArrayViewD<i64>,
+        updates: &Tensor,
+    ) -> TractResult<Arc<Tensor>> {
+        self.scatter::<T>(data, indices, updates, |a, b| *a = b.clone())
+    }
+
+    fn eval_numbers_t<T>(
+        &self,
+        data: &Tensor,
+        indices: &ArrayViewD<i64>,
+        updates: &Tensor,
+    ) -> TractResult<Arc<Tensor>>
+    where
+        T: Datum + PartialOrd + std::ops::Add<Output = T> + std::ops::Mul<Output = T>,
+    {
+        self.scatter::<T>(data, indices, updates, self.reduction.combine())
+    }
+}
+
+impl Op for ScatterNd {
+    fn name(&self) -> Cow<str> {
+        "ScatterNd".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("reduction: {:?}", self.reduction)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for ScatterNd {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (data, indices, updates) = args_3!(inputs);
+        let indices = indices.cast_to::<i64>()?;
+        let indices = indices.to_array_view::<i64>()?;
+        let dt = data.datum_type();
+        let output = if self.reduction == ScatterReduction::None {
+            dispatch_datum!(Self::eval_t(dt)(self, &data, &indices, &updates))?
+        } else {
+            dispatch_numbers!(Self::eval_numbers_t(dt)(self, &data, &indices, &updates))?
+        };
+        Ok(tvec!(output))
+    }
+}
+
+impl InferenceRulesOp for ScatterNd {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 3)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].datum_type, &inputs[2].datum_type)?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    inference_op_as_op!();
+    to_typed!();
+}
+
+impl TypedOp for ScatterNd {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        Ok(tvec!(TypedTensorInfo::dt_shape(inputs[0].datum_type, &*inputs[0].shape.to_tvec())?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // https://github.com/onnx/onnx/blob/master/docs/Operators.md#ScatterElements
+    #[test]
+    fn scatter_elements() {
+        let op = ScatterElements::new(0, ScatterReduction::None);
+        let data = rctensor2(&[[0.0f32, 0.0, 0.0], [0.0, 0.0, 0.0], [0.0, 0.0, 0.0]]);
+        let indices = rctensor2(&[[1i64, 0, 2], [0, 2, 1]]);
+        let updates = rctensor2(&[[1.0f32, 1.1, 1.2], [2.0, 2.1, 2.2]]);
+        let output = op.eval(tvec!(data, indices, updates)).unwrap();
+        assert_eq!(output[0], rctensor2(&[[2.0f32, 1.1, 0.0], [1.0, 0.0, 2.2], [0.0, 2.1, 1.2]]));
+    }
+
+    #[test]
+    fn scatter_elements_negative_indices_and_add() {
+        let op = ScatterElements::new(-1, ScatterReduction::Add);
+        let data = rctensor2(&[[1, 2, 3, 4, 5]]);
+        let indices = rctensor2(&[[1i64, -2, 1]]);
+        let updates = rctensor2(&[[10, 20, 30]]);
+        let output = op.eval(tvec!(data, indices, updates)).unwrap();
+        assert_eq!(output[0], rctensor2(&[[1, 42, 3, 24, 5]]));
+    }
+
+    // https://github.com/onnx/onnx/blob/master/docs/Operators.md#ScatterND
+    #[test]
+    fn scatter_nd_elements() {
+        let op = ScatterNd::new(ScatterReduction::None);
+        let data = rctensor1(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        let indices = rctensor2(&[[4i64], [3], [1], [-1]]);
+        let updates = rctensor1(&[9, 10, 11, 12]);
+        let output = op.eval(tvec!(data, indices, updates)).unwrap();
+        assert_eq!(output[0], rctensor1(&[1, 11, 3, 10, 9, 6, 7, 12]));
+    }
+
+    #[test]
+    fn scatter_nd_slices() {
+        let op = ScatterNd::new(ScatterReduction::Max);
+        let data = rctensor3(&[[[1, 2], [3, 4]], [[5, 6], [7, 8]]]);
+        let indices = rctensor2(&[[0i64], [0]]);
+        let updates = rctensor3(&[[[0, 5], [0, 5]], [[2, 0], [2, 0]]]);
+        let output = op.eval(tvec!(data, indices, updates)).unwrap();
+        assert_eq!(output[0], rctensor3(&[[[2, 5], [3, 5]], [[5, 6], [7, 8]]]));
+    }
+}
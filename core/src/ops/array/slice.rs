@@ -2,6 +2,7 @@ use crate::internal::*;
 use ndarray::prelude::*;
 
 #[derive(Debug, Clone, new, Default)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct Slice<D: DimLike + ToDim> {
     pub axis: usize,
     pub start: D,
@@ -156,6 +157,7 @@ impl<D: DimLike + ToDim> TypedOp for Slice<D> {
                 tvec!(fact),
             )?
         } else {
+            fact.shape[self.axis] = (self.end.clone() - &self.start).to_integer()? as usize;
             target.chain_after(input, &*node.name, self.clone(), tvec!(fact))?
         };
         Ok(tvec!(OutletId::new(id, 0)))
@@ -2,10 +2,11 @@ use crate::internal::*;
 use ndarray::*;
 
 #[derive(Debug, Clone, new, Default)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct Split {
-    axis: usize,
-    outputs: usize,
-    split: Option<Vec<usize>>,
+    pub axis: usize,
+    pub outputs: usize,
+    pub split: Option<Vec<usize>>,
 }
 
 impl Split {
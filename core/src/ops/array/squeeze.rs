@@ -3,6 +3,7 @@ use crate::internal::*;
 use super::RmDims;
 
 #[derive(Debug, Clone, new, Default)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct Squeeze {
     axes: Option<Vec<usize>>,
 }
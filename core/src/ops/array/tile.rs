@@ -80,8 +80,9 @@ impl InferenceRulesOp for Tile {
 }
 
 #[derive(Debug, Clone, new, Default)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct TypedTile {
-    multipliers: TVec<usize>,
+    pub multipliers: TVec<usize>,
 }
 
 impl TypedTile {
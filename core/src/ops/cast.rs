@@ -1,8 +1,9 @@
 use crate::internal::*;
 
 #[derive(Debug, Clone, new)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct Cast {
-    to: DatumType,
+    pub to: DatumType,
 }
 
 impl Cast {
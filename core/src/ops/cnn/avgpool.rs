@@ -10,9 +10,10 @@ use crate::ops::nn::DataShape;
 // TODO check why AvgPool need to be typed
 
 #[derive(Debug, Clone, new, Default)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct AvgPool {
-    pool_spec: PoolSpec,
-    count_include_pad: bool,
+    pub pool_spec: PoolSpec,
+    pub count_include_pad: bool,
 }
 
 impl AvgPool {
@@ -103,11 +104,11 @@ pub struct AvgPoolFixed<T: Datum + Float + Sum>
 where
     usize: AsPrimitive<T>,
 {
-    patch: Patch,
-    input_shape: DataShape,
-    output_shape: DataShape,
-    count_include_pad: bool,
-    _casper: PhantomData<T>,
+    pub(crate) patch: Patch,
+    pub(crate) input_shape: DataShape,
+    pub(crate) output_shape: DataShape,
+    pub(crate) count_include_pad: bool,
+    pub(crate) _casper: PhantomData<T>,
 }
 
 impl<T: Datum + Float + Sum> Op for AvgPoolFixed<T>
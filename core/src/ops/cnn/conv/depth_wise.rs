@@ -9,10 +9,10 @@ pub struct DepthWise<T>
 where
     T: Datum + Clone + ndarray::LinalgScalar + std::ops::AddAssign<T> + PartialEq + Sum,
 {
-    patch: Patch,
-    input_shape: DataShape,
-    output_shape: DataShape,
-    kernel_chw: ArrayD<T>,
+    pub(crate) patch: Patch,
+    pub(crate) input_shape: DataShape,
+    pub(crate) output_shape: DataShape,
+    pub(crate) kernel_chw: ArrayD<T>,
 }
 
 impl<T> Op for DepthWise<T>
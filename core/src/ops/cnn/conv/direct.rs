@@ -5,13 +5,13 @@ use tract_linalg::mmm::*;
 
 #[derive(Debug, Clone, new)]
 pub struct Direct {
-    tile: Box<dyn MatMatMul<f32>>,
-    data_offsets: Vec<isize>,
-    kernel_offsets: Vec<isize>,
-    input_shape: DataShape,
-    output_shape: DataShape,
-    packed_filters: Tensor,
-    fused_ops: Vec<FusedSpec<f32>>,
+    pub(crate) tile: Box<dyn MatMatMul<f32>>,
+    pub(crate) data_offsets: Vec<isize>,
+    pub(crate) kernel_offsets: Vec<isize>,
+    pub(crate) input_shape: DataShape,
+    pub(crate) output_shape: DataShape,
+    pub(crate) packed_filters: Tensor,
+    pub(crate) fused_ops: Vec<FusedSpec<f32>>,
 }
 
 impl Direct {
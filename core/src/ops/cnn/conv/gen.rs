@@ -242,8 +242,8 @@ impl TypedOp for Conv {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::ops::cnn::conv::KernelFormat::HWIO;
-    use crate::ops::nn::DataFormat::NHWC;
+    use crate::ops::cnn::conv::KernelFormat::{HWIO, OIHW};
+    use crate::ops::nn::DataFormat::{NCHW, NHWC};
     use ndarray::*;
 
     #[test]
@@ -289,6 +289,54 @@ mod test {
         assert_eq!(facts.1, tvec!(TensorFact::dt_shape(DatumType::F32, shapefact!(1, 2, 2, 1))));
     }
 
+    #[test]
+    fn test_eval_threaded_is_bit_identical() {
+        use tract_linalg::threads;
+        let op = Conv::new(NCHW, OIHW, None, None, PaddingSpec::SameUpper, None, 2);
+        let i = ArrayD::from_shape_fn(vec![3, 4, 9, 7], |ix| {
+            (ix.slice().iter().sum::<usize>() as f32).sin()
+        })
+        .into_arc_tensor();
+        let k = ArrayD::from_shape_fn(vec![6, 2, 3, 3], |ix| {
+            (ix.slice().iter().product::<usize>() as f32).cos()
+        })
+        .into_arc_tensor();
+        let single = threads::with_pool(None, || op.eval(tvec!(i.clone(), k.clone()))).unwrap();
+        let threaded =
+            threads::with_pool(Some(threads::pool(4).unwrap()), || op.eval(tvec!(i, k))).unwrap();
+        assert_eq!(single, threaded);
+    }
+
+    #[test]
+    fn test_eval_f16_matches_f32() {
+        let op = Conv::new(NCHW, OIHW, None, None, PaddingSpec::SameUpper, None, 2);
+        let i = ArrayD::from_shape_fn(vec![2, 4, 9, 7], |ix| {
+            (ix.slice().iter().sum::<usize>() as f32).sin()
+        })
+        .into_tensor();
+        let k = ArrayD::from_shape_fn(vec![6, 2, 3, 3], |ix| {
+            (ix.slice().iter().product::<usize>() as f32).cos()
+        })
+        .into_tensor();
+        let expected = op.eval(tvec!(i.clone().into(), k.clone().into())).unwrap();
+        let i = i.cast_to::<f16>().unwrap().into_owned().into_arc_tensor();
+        let k = k.cast_to::<f16>().unwrap().into_owned().into_arc_tensor();
+        let found = op.eval(tvec!(i, k)).unwrap();
+        assert_eq!(found[0].datum_type(), f16::datum_type());
+        let found = found[0].cast_to::<f32>().unwrap();
+        let found = found.to_array_view::<f32>().unwrap();
+        let expected = expected[0].to_array_view::<f32>().unwrap();
+        assert_eq!(found.shape(), expected.shape());
+        for (found, expected) in found.iter().zip(expected.iter()) {
+            assert!(
+                (found - expected).abs() <= 1e-2 * expected.abs().max(1.0),
+                "{} {}",
+                found,
+                expected
+            );
+        }
+    }
+
     #[test]
     fn test_eval_nhwc_1() {
         let op = Conv::new(NHWC, HWIO, None, None, PaddingSpec::SameUpper, None, 1);
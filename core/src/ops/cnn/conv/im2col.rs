@@ -1,4 +1,5 @@
 use tract_linalg::frame::PackB;
+use tract_linalg::threads::{self, Shared};
 
 use crate::internal::*;
 use ndarray::prelude::*;
@@ -66,14 +67,17 @@ impl<T: Copy + Datum + Mul + Zero> Im2Col<T> {
         let mut packed = unsafe {
             Tensor::uninitialized_aligned::<T>(&*self.output_shape.shape, self.b_pack.alignment())?
         };
-        for i in 0..*self.input_shape.n_dim() {
-            for g in 0..self.group {
-                let mut packed = packed.to_array_view_mut::<T>()?;
-                packed.slice_axis_inplace(Axis(0), (i..=i).into());
-                packed.slice_axis_inplace(Axis(1), (g..=g).into());
-                self.patcher.patch(self, input, packed.as_slice_mut().unwrap(), i, g);
+        let packed_len = self.b_pack.len();
+        let packed_ptr = Shared(packed.as_ptr_mut::<T>()?);
+        threads::for_each_range(*self.input_shape.n_dim() * self.group, |items| {
+            for item in items {
+                let (i, g) = (item / self.group, item % self.group);
+                let packed = unsafe {
+                    std::slice::from_raw_parts_mut(packed_ptr.0.add(item * packed_len), packed_len)
+                };
+                self.patcher.patch(self, input, packed, i, g);
             }
-        }
+        });
         Ok(packed)
     }
 }
@@ -9,6 +9,7 @@ use crate::ops::cnn::Patch;
 use crate::ops::nn::{DataFormat, DataShape};
 
 use tract_linalg::frame::mmm::{FusedSpec, MatMatMul};
+use tract_linalg::threads::{self, Shared};
 
 /*
  * group=1, N=1         N>1             g>1
@@ -73,29 +74,32 @@ where
             DataFormat::NCHW => (self.n as isize, 1),
         };
 
-        for i in 0..*self.output_shape.n() {
-            unsafe {
-                let output_i =
-                    output.as_mut_ptr().offset(*self.output_shape.n_stride() as isize * i as isize);
-                for g in 0..self.group {
-                    let a = &self.packed_kernels[g];
-                    let output_i_g = output_i.offset(
-                        *self.output_shape.c_stride() as isize * co_per_group as isize * g as isize,
+        let kernels = Shared(
+            self.packed_kernels.iter().map(|k| k.as_ptr::<T>()).collect::<TractResult<Vec<_>>>()?,
+        );
+        let output_ptr = Shared(output.as_mut_ptr());
+        let packed_input_ptr = Shared(packed_input.as_ptr());
+        threads::for_each_range(*self.output_shape.n() * self.group, |items| {
+            for item in items {
+                let (i, g) = (item / self.group, item % self.group);
+                unsafe {
+                    let output_i_g = output_ptr.0.offset(
+                        *self.output_shape.n_stride() as isize * i as isize
+                            + *self.output_shape.c_stride() as isize
+                                * co_per_group as isize
+                                * g as isize,
                     );
-
+                    let packed_input_i_g =
+                        packed_input_ptr.0.offset(((self.group * i + g) * packed_b_len) as isize);
                     self.tile.run(
-                        &self.tile.a_from_packed(a.as_ptr()?),
-                        &self.tile.b_from_packed(
-                            packed_input
-                                .as_ptr()
-                                .offset(((self.group * i + g) * packed_b_len) as isize),
-                        ),
+                        &self.tile.a_from_packed(kernels.0[g]),
+                        &self.tile.b_from_packed(packed_input_i_g),
                         &mut self.tile.c_from_data_and_strides(output_i_g, rsc, csc),
                         &*self.non_linear,
                     );
                 }
             }
-        }
+        });
         Ok(output)
     }
 }
@@ -6,11 +6,16 @@ mod mat_mat;
 mod unary;
 mod vec_mat;
 
+pub use self::depth_wise::DepthWise;
 pub use self::direct::Direct;
 pub use self::gen::Conv;
+pub use self::im2col::Im2Col;
+pub use self::mat_mat::MatMat;
 pub use self::unary::ConvUnary;
+pub use self::vec_mat::VecMat;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub enum KernelFormat {
     OIHW,
     HWIO,
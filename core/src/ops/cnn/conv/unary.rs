@@ -17,6 +17,7 @@ use crate::ops::nn::DataFormat;
 use std::iter::Sum;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct ConvUnary {
     pub data_format: DataFormat,
     pub kernel_fmt: KernelFormat,
@@ -8,6 +8,7 @@ use crate::ops::cnn::conv::KernelFormat;
 use crate::ops::cnn::Patch;
 use crate::ops::nn::{DataFormat, DataShape};
 
+use tract_linalg::threads::{self, Shared};
 use tract_linalg::vecmatmul::VecMatMul;
 
 #[derive(Debug, Clone, new)]
@@ -37,32 +38,35 @@ where
         let packed_b_len = self.vmm.b_pack().len();
 
         let co_per_group = self.output_shape.c() / self.group;
-        for i in 0..*self.output_shape.n() {
-            unsafe {
-                let output_i =
-                    output.as_mut_ptr().offset(*self.output_shape.n_stride() as isize * i as isize);
-                for g in 0..self.group {
-                    let a = &self.packed_kernels[g];
-                    let output_i_g = output_i.offset(
-                        *self.output_shape.c_stride() as isize * co_per_group as isize * g as isize,
+        let stride_output = match self.output_shape.fmt {
+            DataFormat::NHWC => self.group as isize,
+            DataFormat::NCHW => 1,
+        };
+
+        let kernels = Shared(
+            self.packed_kernels.iter().map(|k| k.as_ptr::<T>()).collect::<TractResult<Vec<_>>>()?,
+        );
+        let output_ptr = Shared(output.as_mut_ptr());
+        let packed_input_ptr = Shared(packed_input.as_ptr());
+        threads::for_each_range(*self.output_shape.n() * self.group, |items| {
+            for item in items {
+                let (i, g) = (item / self.group, item % self.group);
+                unsafe {
+                    let output_i_g = output_ptr.0.offset(
+                        *self.output_shape.n_stride() as isize * i as isize
+                            + *self.output_shape.c_stride() as isize
+                                * co_per_group as isize
+                                * g as isize,
                     );
-
-                    let stride_output = match self.output_shape.fmt {
-                        DataFormat::NHWC => self.group as isize,
-                        DataFormat::NCHW => 1,
-                    };
-
                     self.vmm.vec_mat_mul_prepacked(
-                        a.as_ptr()?,
-                        packed_input
-                            .as_ptr()
-                            .offset(((self.group * i + g) * packed_b_len) as isize),
+                        kernels.0[g],
+                        packed_input_ptr.0.offset(((self.group * i + g) * packed_b_len) as isize),
                         output_i_g,
                         stride_output,
                     );
                 }
             }
-        }
+        });
 
         Ok(output)
     }
@@ -0,0 +1,404 @@
+use crate::internal::*;
+
+use super::DeconvUnary;
+use crate::dim::DimLike;
+use crate::ops::cnn::conv::KernelFormat;
+use crate::ops::cnn::PaddingSpec;
+use crate::ops::nn::DataFormat;
+use std::borrow::Borrow;
+
+/// Transposed (fractionally-strided) convolution.
+///
+/// Kernel format, padding, strides and dilations are the ones of the
+/// convolution being transposed: running a `Conv` with the same parameters
+/// on the output of a `Deconv` gives back a tensor of the `Deconv` input shape.
+#[derive(Debug, Clone, new)]
+pub struct Deconv {
+    pub data_format: DataFormat,
+    pub kernel_fmt: KernelFormat,
+    pub dilations: Option<TVec<usize>>,
+    pub kernel_shape: Option<TVec<usize>>,
+    pub padding: PaddingSpec,
+    pub strides: Option<TVec<usize>>,
+    pub output_padding: Option<TVec<usize>>,
+    pub output_shape: Option<TVec<usize>>,
+    pub group: usize,
+}
+
+impl ::std::default::Default for Deconv {
+    fn default() -> Deconv {
+        Deconv {
+            data_format: DataFormat::default(),
+            kernel_fmt: KernelFormat::default(),
+            dilations: None,
+            kernel_shape: None,
+            padding: PaddingSpec::default(),
+            strides: None,
+            output_padding: None,
+            output_shape: None,
+            group: 1,
+        }
+    }
+}
+
+impl Deconv {
+    fn spatial_attr(attr: &Option<TVec<usize>>, rank: usize, default: usize) -> TVec<usize> {
+        attr.clone().unwrap_or_else(|| tvec!(default; rank))
+    }
+
+    pub fn output_shape<D: DimLike>(&self, ishape: &[D], kshape: &[usize]) -> TVec<D> {
+        debug_assert_eq!(ishape.len(), kshape.len(), "Input and kernel should have the same rank");
+        let mut result: TVec<D> = ishape.into();
+        let ishape = self.data_format.shape(ishape);
+        let spatial_rank = ishape.hw_rank();
+        let kernel_spatial_shape = &kshape[self.kernel_fmt.h_axis()..][..spatial_rank];
+        let dilations = Self::spatial_attr(&self.dilations, spatial_rank, 1);
+        let strides = Self::spatial_attr(&self.strides, spatial_rank, 1);
+        let output_padding = Self::spatial_attr(&self.output_padding, spatial_rank, 0);
+        let (_, channels_out) = super::kernel_o_i(self.kernel_fmt, kshape);
+        result[ishape.c_axis()] = (channels_out * self.group).into();
+        for ix in 0..spatial_rank {
+            let input = ishape.hw_dims()[ix].clone();
+            let kernel_field = (kernel_spatial_shape[ix] - 1) * dilations[ix] + 1;
+            result[ishape.h_axis() + ix] = if let Some(shape) = &self.output_shape {
+                // some exporters give the full NCHW shape, spatial dims are last
+                shape[shape.len() - spatial_rank + ix].into()
+            } else {
+                match &self.padding {
+                    PaddingSpec::SameUpper | PaddingSpec::SameLower => input * strides[ix],
+                    PaddingSpec::Valid => {
+                        (input - 1) * strides[ix] + (output_padding[ix] + kernel_field)
+                    }
+                    PaddingSpec::Explicit(bef, aft) => {
+                        (input - 1) * strides[ix] + (output_padding[ix] + kernel_field)
+                            - (bef[ix] + aft[ix])
+                    }
+                }
+            };
+        }
+        result
+    }
+
+    /// Padding of the transposed convolution once the output geometry is
+    /// known. Follows the ONNX ConvTranspose rule: when the output shape is
+    /// imposed, the total padding is split between both ends, with the
+    /// extra unit at the end for SAME_UPPER and at the beginning otherwise.
+    pub fn computed_padding(
+        &self,
+        input_spatial_shape: &[usize],
+        output_spatial_shape: &[usize],
+        kernel_spatial_shape: &[usize],
+    ) -> PaddingSpec {
+        match (&self.padding, &self.output_shape) {
+            (PaddingSpec::Valid, None) | (PaddingSpec::Explicit(_, _), None) => {
+                return self.padding.clone()
+            }
+            _ => (),
+        }
+        let rank = input_spatial_shape.len();
+        let dilations = Self::spatial_attr(&self.dilations, rank, 1);
+        let strides = Self::spatial_attr(&self.strides, rank, 1);
+        let output_padding = Self::spatial_attr(&self.output_padding, rank, 0);
+        let (before, after) = (0..rank)
+            .map(|ix| {
+                let kernel_field = (kernel_spatial_shape[ix] - 1) * dilations[ix] + 1;
+                let total = ((input_spatial_shape[ix] - 1) * strides[ix]
+                    + output_padding[ix]
+                    + kernel_field) as isize
+                    - output_spatial_shape[ix] as isize;
+                let total = total.max(0) as usize;
+                if self.padding == PaddingSpec::SameUpper {
+                    (total / 2, total - total / 2)
+                } else {
+                    (total - total / 2, total / 2)
+                }
+            })
+            .unzip();
+        PaddingSpec::Explicit(before, after)
+    }
+
+    pub fn to_unary(
+        &self,
+        inputs: &[impl Borrow<TypedTensorInfo>],
+    ) -> TractResult<Option<DeconvUnary>> {
+        let input = &inputs[0];
+        let kernel = &inputs[1];
+        let input_shape = self.data_format.shape(input.borrow().shape.iter().collect::<TVec<_>>());
+        let kshape = kernel.borrow().shape.iter().collect::<TVec<_>>();
+        let channels_in = match self.kernel_fmt {
+            KernelFormat::OIHW => kshape[0].clone(),
+            KernelFormat::HWIO => kshape[kshape.len() - 1].clone(),
+        };
+        if input_shape.c_dim() != &channels_in {
+            bail!("Input has {} channels, kernel expects {}", input_shape.c_dim(), channels_in)
+        }
+        let input_spatial_shape = input_shape
+            .hw_dims()
+            .iter()
+            .map(|d| d.to_integer().map(|d| d as usize))
+            .collect::<TractResult<TVec<usize>>>();
+        if let (Some(kvalue), Ok(input_spatial_shape)) =
+            (kernel.borrow().konst.clone(), input_spatial_shape)
+        {
+            let ishape: TVec<TDim> = input.borrow().shape.iter().collect();
+            let oshape = self.output_shape(&*ishape, kvalue.shape());
+            let output_spatial_shape = self
+                .data_format
+                .shape(&*oshape)
+                .hw_dims()
+                .iter()
+                .map(|d| d.to_integer().map(|d| d as usize))
+                .collect::<TractResult<TVec<usize>>>()?;
+            let kernel_spatial_shape =
+                &kvalue.shape()[self.kernel_fmt.h_axis()..][..input_spatial_shape.len()];
+            let padding = self.computed_padding(
+                &input_spatial_shape,
+                &output_spatial_shape,
+                kernel_spatial_shape,
+            );
+            let reduced = DeconvUnary::new(&self, &ishape, &oshape, kvalue.into_tensor(), padding)?;
+            return Ok(Some(reduced));
+        }
+        Ok(None)
+    }
+
+    pub fn add_bias_t<T: FloatLike + std::ops::AddAssign>(
+        &self,
+        deconv_result: &mut Tensor,
+        bias: &Tensor,
+    ) -> TractResult<()> {
+        let mut deconv = deconv_result.to_array_view_mut::<T>()?;
+        let shape = self.data_format.shape(deconv.shape());
+        let bias = bias.to_array_view::<T>()?;
+        let mut reshaped = vec![1; deconv.ndim()];
+        reshaped[shape.c_axis()] = bias.len();
+        deconv += &bias.into_shape(reshaped)?;
+        Ok(())
+    }
+}
+
+impl Op for Deconv {
+    fn name(&self) -> Cow<str> {
+        "Deconv".into()
+    }
+
+    fn cost(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<(Cost, TDim)>> {
+        let unary = self
+            .to_unary(&*inputs)?
+            .ok_or_else(|| format!("Can not unarize deconv: {:?}", self))?;
+        unary.cost(&[inputs[0]])
+    }
+
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let inputs = model.node_input_facts(node.id)?;
+        if let Some(op) = self.to_unary(&*inputs)? {
+            let mut patch = TypedModelPatch::default();
+            patch.tap_model(model, node.inputs[0])?;
+            let mut output: OutletId =
+                patch.chain(&*node.name, op, tvec!(node.outputs[0].fact.clone()))?.into();
+            if let Some(bias) = node.inputs.get(2) {
+                let mut tap = patch.tap_model(model, *bias)?;
+                if self.data_format == DataFormat::NCHW {
+                    let data_rank = node.outputs[0].fact.shape.rank();
+                    let add_dims = crate::ops::array::AddDims::new((1..data_rank - 1).collect());
+                    tap = patch.wire_node(
+                        format!("{}-reshaped-bias", node.name),
+                        add_dims,
+                        [tap].as_ref(),
+                    )?[0];
+                }
+                output = patch.wire_node(
+                    format!("{}-add-bias", node.name),
+                    crate::ops::math::add::bin(),
+                    [output, tap].as_ref(),
+                )?[0];
+            }
+            patch.shunt_outside(OutletId::new(node.id, 0), output)?;
+            return Ok(Some(patch));
+        } else {
+            Ok(None)
+        }
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for Deconv {
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let inputs_info: TVec<TypedTensorInfo> =
+            inputs.iter().map(|t| TypedTensorInfo::from(&**t)).collect();
+        let unary = self.to_unary(&*inputs_info)?.unwrap();
+        let mut result = unary.eval(tvec!(inputs[0].clone()))?;
+        if let Some(bias) = inputs.get(2) {
+            let mut result = result.remove(0).into_tensor();
+            dispatch_floatlike!(Self::add_bias_t(bias.datum_type())(self, &mut result, bias))?;
+            Ok(tvec!(result.into_arc_tensor()))
+        } else {
+            Ok(result)
+        }
+    }
+}
+
+impl InferenceRulesOp for Deconv {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        if inputs.len() < 2 {
+            bail!("Wrong number of inputs. Expected 2 or more, got {}", inputs.len());
+        }
+        if let Some(kshape) = &self.kernel_shape {
+            s.equals(&inputs[1].rank, kshape.len() as i32 + 2)?;
+            for (ix, dim) in kshape.iter().enumerate() {
+                s.equals(&inputs[1].shape[ix + self.kernel_fmt.h_axis()], TDim::from(*dim as i32))?;
+            }
+        }
+        s.equals(&inputs[0].rank, &inputs[1].rank)?;
+        s.equals(&outputs[0].rank, &inputs[1].rank)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals_all(wrap![&outputs[0].datum_type, &inputs[0].datum_type, &inputs[1].datum_type])?;
+        if inputs.len() == 3 {
+            s.equals(&inputs[2].rank, 1)?;
+            s.equals(&outputs[0].datum_type, &inputs[2].datum_type)?;
+            s.given(&inputs[1].rank, move |s, krank| {
+                let filter_i = match self.kernel_fmt {
+                    KernelFormat::OIHW => &inputs[1].shape[1],
+                    KernelFormat::HWIO => &inputs[1].shape[krank as usize - 2],
+                };
+                s.equals(inputs[2].shape[0].bex(), self.group as i32 * filter_i.bex())
+            })?
+        }
+        s.given_2(&inputs[0].rank, &inputs[1].rank, move |s, irank, krank| {
+            let input_c = if self.data_format == DataFormat::NHWC {
+                &inputs[0].shape[irank as usize - 1]
+            } else {
+                &inputs[0].shape[1]
+            };
+            let filter_o = match self.kernel_fmt {
+                KernelFormat::OIHW => &inputs[1].shape[0],
+                KernelFormat::HWIO => &inputs[1].shape[krank as usize - 1],
+            };
+            s.equals(input_c, filter_o)
+        })?;
+        s.given_2(&inputs[0].shape, &inputs[1].shape, move |s, ishape, kshape| {
+            if kshape.iter().all(|d| d.to_integer().is_ok()) {
+                let kshape: TVec<usize> =
+                    kshape.iter().map(|d| d.to_integer().unwrap() as _).collect();
+                let oshape = self.output_shape(&*ishape, &*kshape);
+                s.equals(&outputs[0].shape, oshape)?;
+            }
+            Ok(())
+        })
+    }
+
+    inference_op_as_op!();
+    to_typed!();
+}
+
+impl TypedOp for Deconv {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        if inputs[1].shape.iter().all(|d| d.to_integer().is_ok()) {
+            let kshape: TVec<usize> =
+                inputs[1].shape.iter().map(|d| d.to_integer().unwrap() as _).collect();
+            let oshape = self.output_shape(&*inputs[0].shape.to_tvec(), &*kshape);
+            Ok(tvec!(TypedTensorInfo::dt_shape(inputs[0].datum_type, &*oshape)?))
+        } else {
+            bail!("Streaming on kernel is not typeable")
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::cnn::conv::KernelFormat::HWIO;
+    use crate::ops::nn::DataFormat::NHWC;
+
+    #[test]
+    fn test_infer_stride_2() {
+        let mut op = Deconv::default();
+        op.strides = Some(tvec![2, 2]);
+        let ifact = TensorFact::dt_shape(DatumType::F32, shapefact!(1, 1, 3, 2));
+        let kfact = TensorFact::dt_shape(DatumType::F32, shapefact!(1, 2, 3, 3));
+        let ofact = TensorFact::default();
+        let facts = op.infer_facts(tvec!(&ifact, &kfact), tvec!(&ofact), tvec!()).unwrap();
+        assert_eq!(facts.1, tvec!(TensorFact::dt_shape(DatumType::F32, shapefact!(1, 2, 7, 5))));
+    }
+
+    #[test]
+    fn test_infer_output_padding() {
+        let mut op = Deconv::default();
+        op.strides = Some(tvec![2, 2]);
+        op.output_padding = Some(tvec![1, 0]);
+        op.padding = PaddingSpec::Explicit(tvec![1, 1], tvec![1, 1]);
+        let ifact = TensorFact::dt_shape(DatumType::F32, shapefact!(1, 1, 3, 2));
+        let kfact = TensorFact::dt_shape(DatumType::F32, shapefact!(1, 1, 3, 3));
+        let ofact = TensorFact::default();
+        let facts = op.infer_facts(tvec!(&ifact, &kfact), tvec!(&ofact), tvec!()).unwrap();
+        assert_eq!(facts.1, tvec!(TensorFact::dt_shape(DatumType::F32, shapefact!(1, 1, 6, 3))));
+    }
+
+    #[test]
+    fn test_computed_padding_same_upper() {
+        let mut op = Deconv::default();
+        op.strides = Some(tvec![2]);
+        op.padding = PaddingSpec::SameUpper;
+        assert_eq!(
+            op.computed_padding(&[3], &[6], &[4]),
+            PaddingSpec::Explicit(tvec![1], tvec![1])
+        );
+        assert_eq!(
+            op.computed_padding(&[3], &[6], &[3]),
+            PaddingSpec::Explicit(tvec![0], tvec![1])
+        );
+    }
+
+    #[test]
+    fn test_eval_1d_stride_2() {
+        let mut op = Deconv::default();
+        op.strides = Some(tvec![2]);
+        let i = rctensor3(&[[[1.0f32, 2.0, 3.0]]]);
+        let k = rctensor3(&[[[1.0f32, 10.0, 100.0]]]);
+        let res = op.eval(tvec!(i, k)).unwrap();
+        assert_eq!(res, tvec!(rctensor3(&[[[1.0f32, 10.0, 102.0, 20.0, 203.0, 30.0, 300.0]]])));
+    }
+
+    #[test]
+    fn test_eval_1d_padding_dilation() {
+        let mut op = Deconv::default();
+        op.dilations = Some(tvec![2]);
+        op.padding = PaddingSpec::Explicit(tvec![1], tvec![1]);
+        let i = rctensor3(&[[[1.0f32, 2.0, 3.0]]]);
+        let k = rctensor3(&[[[1.0f32, 10.0]]]);
+        let res = op.eval(tvec!(i, k)).unwrap();
+        assert_eq!(res, tvec!(rctensor3(&[[[2.0f32, 13.0, 20.0]]])));
+    }
+
+    #[test]
+    fn test_eval_groups() {
+        let mut op = Deconv::default();
+        op.group = 2;
+        let i = rctensor3(&[[[1.0f32, 2.0], [3.0, 4.0]]]);
+        let k = rctensor3(&[[[1.0f32]], [[10.0]]]);
+        let res = op.eval(tvec!(i, k)).unwrap();
+        assert_eq!(res, tvec!(rctensor3(&[[[1.0f32, 2.0], [30.0, 40.0]]])));
+    }
+
+    #[test]
+    fn test_eval_nhwc_channels_and_bias() {
+        let op = Deconv::new(NHWC, HWIO, None, None, PaddingSpec::Valid, None, None, None, 1);
+        let i = rctensor3(&[[[1.0f32, 2.0], [3.0, 4.0]]]);
+        let k = rctensor3(&[[[1.0f32, 0.0], [0.0, 1.0], [1.0, 1.0]]]);
+        let b = rctensor1(&[0.5f32, 0.0, 0.0]);
+        let res = op.eval(tvec!(i, k, b)).unwrap();
+        assert_eq!(res, tvec!(rctensor3(&[[[1.5f32, 2.0, 3.0], [3.5, 4.0, 7.0]]])));
+    }
+}
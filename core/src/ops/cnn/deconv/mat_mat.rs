@@ -0,0 +1,140 @@
+use num_traits::Zero;
+use std::ops::{Add, AddAssign, Mul};
+
+use crate::internal::*;
+use ndarray::prelude::*;
+
+use crate::ops::cnn::Patch;
+use crate::ops::nn::DataShape;
+
+use tract_linalg::frame::mmm::MatMatMul;
+
+/*
+ * Per batch item and group:
+ *
+ * A: kernel                    B: input                C: columns
+ *  * O/g*h*w rows               * I/g rows              * O/g*h*w rows
+ *  * I/g cols                   * H*W cols              * H*W cols
+ *
+ * Then each column (an input point) is scattered and accumulated into the
+ * output, following the patch of the convolution going from the output
+ * back to the input.
+ */
+
+#[derive(Debug, Clone, new)]
+pub struct DeconvMatMat<T>
+where
+    T: Datum + Add + Mul + Zero + Copy,
+{
+    pub patch: Patch,
+    pub input_shape: DataShape,
+    pub output_shape: DataShape,
+    pub packed_kernels: Vec<Tensor>,
+    pub group: usize,
+    pub mm: Box<dyn MatMatMul<T>>,
+}
+
+impl<T> DeconvMatMat<T>
+where
+    T: Datum + Add + Mul + Zero + Copy + AddAssign,
+{
+    fn deconv_gemm(&self, input: &ArrayViewD<T>) -> TractResult<ArrayD<T>> {
+        let mut output = ArrayD::<T>::zeros(&*self.output_shape.shape);
+        let (m, n) = (self.mm.m(), self.mm.n());
+        let b_pack = self.mm.b_pack();
+        let mut packed_input =
+            unsafe { Tensor::uninitialized_aligned::<T>(&[b_pack.len()], b_pack.alignment())? };
+        let mut columns = unsafe { Array2::<T>::uninitialized((m, n)) };
+
+        let ci_per_group = self.input_shape.c() / self.group;
+        let co_per_group = self.output_shape.c() / self.group;
+        let kernel_surface = m / co_per_group;
+        let output_c_stride = *self.output_shape.c_stride() as isize;
+
+        for i in 0..*self.input_shape.n() {
+            for g in 0..self.group {
+                unsafe {
+                    let input_i_g = input.as_ptr().offset(
+                        (*self.input_shape.n_stride() * i
+                            + *self.input_shape.c_stride() * ci_per_group * g)
+                            as isize,
+                    );
+                    b_pack.pack(
+                        packed_input.as_ptr_mut()?,
+                        input_i_g,
+                        *self.input_shape.c_stride() as isize,
+                        *self.input_shape.w_stride() as isize,
+                    );
+                    self.mm.run(
+                        &self.mm.a_from_packed(self.packed_kernels[g].as_ptr()?),
+                        &self.mm.b_from_packed(packed_input.as_ptr()?),
+                        &mut self.mm.c_from_data_and_strides(columns.as_mut_ptr(), n as isize, 1),
+                        &[],
+                    );
+                    let output_i_g = output.as_mut_ptr().offset(
+                        (*self.output_shape.n_stride() * i
+                            + *self.output_shape.c_stride() * co_per_group * g)
+                            as isize,
+                    );
+                    let columns = columns.as_ptr();
+                    self.patch.visit_output(|visitor| {
+                        let col = visitor.output_offset;
+                        for (kix, offset) in visitor.valid_offsets_with_indexes() {
+                            for co in 0..co_per_group {
+                                let row = (co * kernel_surface + kix) as isize;
+                                *output_i_g.offset(offset + co as isize * output_c_stride) +=
+                                    *columns.offset(row * n as isize + col);
+                            }
+                        }
+                    });
+                }
+            }
+        }
+        Ok(output)
+    }
+}
+
+impl<T> Op for DeconvMatMat<T>
+where
+    T: Datum + Add + Mul + Zero + Copy + AddAssign,
+{
+    fn name(&self) -> Cow<str> {
+        "DeconvMatMat".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("{:?}", self.mm)])
+    }
+
+    fn cost(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<(Cost, TDim)>> {
+        let batch = inputs[0].shape.dim(0);
+        Ok(tvec!((
+            Cost::FMA(T::datum_type()),
+            batch * self.group * self.mm.m() * self.mm.k() * self.mm.n()
+        )))
+    }
+
+    op_as_typed_op!();
+}
+
+impl<T> StatelessOp for DeconvMatMat<T>
+where
+    T: Datum + Add + Mul + Zero + Copy + AddAssign,
+{
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let output = self.deconv_gemm(&input.to_array_view::<T>()?)?;
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl<T> TypedOp for DeconvMatMat<T>
+where
+    T: Datum + Add + Mul + Zero + Copy + AddAssign,
+{
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        Ok(tvec!(TypedTensorInfo::dt_shape(inputs[0].datum_type, &*self.output_shape.shape)?))
+    }
+}
@@ -0,0 +1,23 @@
+mod gen;
+mod mat_mat;
+mod unary;
+
+pub use self::gen::Deconv;
+pub use self::mat_mat::DeconvMatMat;
+pub use self::unary::DeconvUnary;
+
+use crate::ops::cnn::KernelFormat;
+
+/*
+ * The kernel of a transposed convolution is the kernel of the convolution it
+ * transposes: O is the number of channels of the deconvolution input, I the
+ * number of channels of the deconvolution output (per group).
+ */
+pub(super) fn kernel_o_i(kernel_fmt: KernelFormat, kernel_shape: &[usize]) -> (usize, usize) {
+    match kernel_fmt {
+        KernelFormat::OIHW => (kernel_shape[0], kernel_shape[1]),
+        KernelFormat::HWIO => {
+            (kernel_shape[kernel_shape.len() - 1], kernel_shape[kernel_shape.len() - 2])
+        }
+    }
+}
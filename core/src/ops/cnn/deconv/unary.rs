@@ -0,0 +1,231 @@
+use ndarray::*;
+
+use crate::internal::*;
+
+use super::mat_mat::DeconvMatMat;
+use super::Deconv;
+use crate::ops::cnn::conv::KernelFormat;
+use crate::ops::cnn::{PaddingSpec, Patch, PatchSpec};
+use crate::ops::nn::DataFormat;
+
+/// Transposed convolution with a constant kernel. The padding is always
+/// explicit: it is resolved against the input and output shapes by `Deconv`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct DeconvUnary {
+    pub data_format: DataFormat,
+    pub kernel_fmt: KernelFormat,
+    pub padding: PaddingSpec,
+    pub dilations: TVec<usize>,
+    pub strides: TVec<usize>,
+    pub kernel: Tensor,
+
+    pub full_input_shape: TVec<TDim>,
+    pub full_output_shape: TVec<TDim>,
+    pub group: usize,
+}
+
+impl DeconvUnary {
+    pub fn new(
+        deconv: &Deconv,
+        full_input_shape: &[TDim],
+        full_output_shape: &[TDim],
+        kernel: Tensor,
+        padding: PaddingSpec,
+    ) -> TractResult<DeconvUnary> {
+        for td in full_output_shape {
+            if let Ok(d) = td.to_integer() {
+                if d < 0 {
+                    bail!("Negative output shape dim detected");
+                }
+            }
+        }
+        let spatial_rank = full_input_shape.len() - 2;
+        let dilations =
+            deconv.dilations.as_ref().map(|a| TVec::from(&**a)).unwrap_or(tvec!(1; spatial_rank));
+        let strides =
+            deconv.strides.as_ref().map(|a| TVec::from(&**a)).unwrap_or(tvec!(1; spatial_rank));
+
+        let unary = DeconvUnary {
+            data_format: deconv.data_format,
+            kernel_fmt: deconv.kernel_fmt,
+            padding,
+            dilations,
+            strides,
+            kernel,
+            full_input_shape: full_input_shape.into(),
+            full_output_shape: full_output_shape.into(),
+            group: deconv.group,
+        };
+        Ok(unary)
+    }
+
+    fn kernel_spatial_shape(&self) -> &[usize] {
+        &self.kernel.shape()[self.kernel_fmt.h_axis()..][..(self.full_input_shape.len() - 2)]
+    }
+
+    fn output_channels(&self) -> usize {
+        super::kernel_o_i(self.kernel_fmt, self.kernel.shape()).1 * self.group
+    }
+
+    fn output_full_shape(&self, input_full_shape: &[usize]) -> TractResult<TVec<usize>> {
+        let input_shape = self.data_format.shape(input_full_shape);
+        let mut shape = self.full_output_shape.clone();
+        shape[input_shape.n_axis()] = input_shape.n().to_dim();
+        shape.iter().map(|d| d.to_integer().map(|d| d as usize)).collect()
+    }
+
+    /// The patch of the convolution going from the deconvolution output to its
+    /// input. It maps each (input point, kernel point) pair to the output
+    /// point it contributes to.
+    fn patch(&self, output_full_shape: &[usize]) -> Patch {
+        let spec = PatchSpec::for_full_shape(self.data_format, output_full_shape)
+            .with_kernel_shape(self.kernel_spatial_shape().into())
+            .with_padding(self.padding.clone())
+            .with_dilations(self.dilations.clone())
+            .with_strides(self.strides.clone());
+        spec.into_patch()
+    }
+
+    /// Reorganize the kernel as group, output channel, kernel point, input
+    /// channel.
+    fn kernel_as_group_o_hw_i<T: Datum>(&self) -> TractResult<Array4<T>> {
+        let kernel = self.kernel.to_array_view::<T>()?;
+        let (channels_in, co_per_group) = super::kernel_o_i(self.kernel_fmt, kernel.shape());
+        let ci_per_group = channels_in / self.group;
+        let kernel_surface = self.kernel_spatial_shape().iter().product::<usize>();
+        let final_shape = (self.group, co_per_group, kernel_surface, ci_per_group);
+        trace!("kernel shape (group, output, hw, input) = {:?}", final_shape);
+        let permuted = match self.kernel_fmt {
+            KernelFormat::OIHW => kernel
+                .into_shape((self.group, ci_per_group, co_per_group, kernel_surface))?
+                .permuted_axes((0, 2, 3, 1)),
+            KernelFormat::HWIO => kernel
+                .into_shape((kernel_surface, co_per_group, self.group, ci_per_group))?
+                .permuted_axes((2, 1, 0, 3)),
+        };
+        Ok(Array4::<T>::from_shape_vec(final_shape, permuted.iter().cloned().collect())?)
+    }
+
+    pub fn to_mat_mat<T>(&self, input_full_shape: &[usize]) -> TractResult<DeconvMatMat<T>>
+    where
+        T: Datum + Copy + ndarray::LinalgScalar + std::ops::AddAssign<T> + FloatLike,
+    {
+        trace!("to_mat_mat: {:?}", self);
+        let output_full_shape = self.output_full_shape(input_full_shape)?;
+        let patch = self.patch(&output_full_shape);
+        let input_shape = self.data_format.shape(input_full_shape.into());
+        let output_shape = self.data_format.shape(output_full_shape);
+        if &*patch.output_shape != input_shape.hw_dims() {
+            bail!(
+                "Inconsistent deconvolution geometry: output {:?} maps back to {:?}, input is {:?}",
+                output_shape.hw_dims(),
+                patch.output_shape,
+                input_shape.hw_dims()
+            );
+        }
+
+        let kernel = self.kernel_as_group_o_hw_i::<T>()?;
+        let m = kernel.shape()[1] * kernel.shape()[2];
+        let k = kernel.shape()[3];
+        let n = input_shape.hw_dims().iter().cloned().product::<usize>();
+
+        let mm = T::mmm(m, k, n);
+        let packed_kernels = kernel
+            .outer_iter()
+            .map(|kernel| {
+                let mut packed = unsafe {
+                    Tensor::uninitialized_aligned::<T>(
+                        &[mm.a_pack().len()],
+                        mm.a_pack().alignment(),
+                    )?
+                };
+                mm.a_pack().pack(packed.as_ptr_mut()?, kernel.as_ptr(), k as isize, 1);
+                Ok(packed)
+            })
+            .collect::<TractResult<Vec<_>>>()?;
+
+        Ok(DeconvMatMat::new(patch, input_shape, output_shape, packed_kernels, self.group, mm))
+    }
+
+    fn to_boxed_mat_mat<T>(&self, input_full_shape: &[usize]) -> TractResult<Box<dyn TypedOp>>
+    where
+        T: Datum + Copy + ndarray::LinalgScalar + std::ops::AddAssign<T> + FloatLike,
+    {
+        Ok(Box::new(self.to_mat_mat::<T>(input_full_shape)?))
+    }
+
+    fn eval_t<T>(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>>
+    where
+        T: Datum + Copy + ndarray::LinalgScalar + std::ops::AddAssign<T> + FloatLike,
+    {
+        self.to_mat_mat::<T>(inputs[0].shape())?.eval(inputs)
+    }
+}
+
+impl Op for DeconvUnary {
+    fn name(&self) -> Cow<str> {
+        "DeconvUnary".into()
+    }
+
+    fn cost(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<(Cost, TDim)>> {
+        let shape = self.data_format.shape(inputs[0].shape.iter().collect::<TVec<TDim>>());
+        let n_input_points: TDim = shape.hw_dims().iter().cloned().product::<TDim>();
+        let kernel_surface = self.kernel_spatial_shape().iter().product::<usize>().to_dim();
+        Ok(tvec!((
+            Cost::FMA(f32::datum_type()),
+            shape.n().clone()
+                * shape.c()
+                * self.output_channels().to_dim()
+                * n_input_points
+                * kernel_surface
+                / self.group
+        )))
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![
+            format!("Data format: {:?}", self.data_format),
+            format!(
+                "Kernel shape, {:?}: {:?} (strides:{:?} dilations:{:?} groups:{}))",
+                self.kernel_fmt,
+                self.kernel.shape(),
+                self.strides,
+                self.dilations,
+                self.group
+            ),
+            format!("Padding: {:?}", self.padding),
+        ])
+    }
+
+    fn codegen(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let input_fact = model.outlet_fact(node.inputs[0])?;
+        if let Some(shape) = input_fact.shape.as_finite() {
+            let dt = input_fact.datum_type;
+            let op = dispatch_floatlike!(Self::to_boxed_mat_mat(dt)(self, &shape))?;
+            return Ok(Some(TypedModelPatch::single_unary_op(model, node, op)?));
+        }
+        Ok(None)
+    }
+
+    canonic!();
+    op_as_typed_op!();
+}
+
+impl StatelessOp for DeconvUnary {
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        dispatch_floatlike!(Self::eval_t(inputs[0].datum_type())(self, inputs))
+    }
+}
+
+impl TypedOp for DeconvUnary {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        Ok(tvec!(TypedTensorInfo::dt_shape(inputs[0].datum_type, &*self.full_output_shape)?))
+    }
+}
@@ -7,9 +7,10 @@ use crate::ops::cnn::Patch;
 use crate::ops::nn::DataShape;
 
 #[derive(Debug, Clone, new, Default)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct MaxPool {
-    pool_spec: PoolSpec,
-    with_index_outputs: Option<DatumType>,
+    pub pool_spec: PoolSpec,
+    pub with_index_outputs: Option<DatumType>,
 }
 
 impl MaxPool {
@@ -104,11 +105,11 @@ impl TypedOp for MaxPool {
 
 #[derive(Debug, Clone, new)]
 pub struct MaxPoolFixed<T: Datum + Float> {
-    patch: Patch,
-    input_shape: DataShape,
-    output_shape: DataShape,
-    with_index_outputs: Option<DatumType>,
-    _casper: PhantomData<T>,
+    pub(crate) patch: Patch,
+    pub(crate) input_shape: DataShape,
+    pub(crate) output_shape: DataShape,
+    pub(crate) with_index_outputs: Option<DatumType>,
+    pub(crate) _casper: PhantomData<T>,
 }
 
 impl<T: Datum + Float> Op for MaxPoolFixed<T> {
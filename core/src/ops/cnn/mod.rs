@@ -1,14 +1,16 @@
 mod avgpool;
 pub mod conv;
+pub mod deconv;
 mod maxpool;
 mod padding;
 mod patch_axis;
 mod patches;
 pub mod pools;
 
-pub use self::avgpool::AvgPool;
+pub use self::avgpool::{AvgPool, AvgPoolFixed};
 pub use self::conv::{Conv, ConvUnary, KernelFormat};
-pub use self::maxpool::MaxPool;
+pub use self::deconv::{Deconv, DeconvUnary};
+pub use self::maxpool::{MaxPool, MaxPoolFixed};
 pub use self::padding::PaddingSpec;
 pub use self::patch_axis::PatchAxis;
 pub use self::patches::{Patch, PatchSpec};
@@ -1,6 +1,7 @@
 use crate::internal::*;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub enum PaddingSpec {
     Explicit(TVec<usize>, TVec<usize>),
     Valid,
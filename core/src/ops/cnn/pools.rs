@@ -4,11 +4,12 @@ use crate::ops::cnn::{PaddingSpec, Patch, PatchSpec};
 use crate::ops::nn::{DataFormat, DataShape};
 
 #[derive(Debug, Clone, new, Default)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct PoolSpec {
-    data_format: DataFormat,
-    kernel_shape: TVec<usize>,
-    padding: PaddingSpec,
-    strides: Option<TVec<usize>>,
+    pub data_format: DataFormat,
+    pub kernel_shape: TVec<usize>,
+    pub padding: PaddingSpec,
+    pub strides: Option<TVec<usize>>,
 }
 
 impl PoolSpec {
@@ -0,0 +1,383 @@
+use crate::internal::*;
+
+/// Conditional evaluation of one of two nested models.
+///
+/// Input 0 is the boolean condition. Each branch reads the other inputs it
+/// needs through its input mapping: branch input `i` is fed by the outer
+/// input `mapping[i]`. Only the selected branch is evaluated.
+#[derive(Debug, Clone, new)]
+pub struct If {
+    pub then_body: InferenceModel,
+    pub then_input_mapping: Vec<usize>,
+    pub else_body: InferenceModel,
+    pub else_input_mapping: Vec<usize>,
+}
+
+/// Inputs of the branch with the given input mapping.
+fn branch_inputs(mapping: &[usize], inputs: &[Arc<Tensor>]) -> TVec<Tensor> {
+    mapping.iter().map(|&slot| inputs[slot].clone().into_tensor()).collect()
+}
+
+impl If {
+    pub(super) fn to_typed_if(&self) -> TractResult<TypedIf> {
+        TypedIf::new(
+            self.then_body.clone().into_typed()?,
+            self.then_input_mapping.clone(),
+            self.else_body.clone().into_typed()?,
+            self.else_input_mapping.clone(),
+        )
+    }
+
+    fn unify_branch_inputs(
+        body: &mut InferenceModel,
+        mapping: &[usize],
+        inputs: &mut [TensorFact],
+    ) -> TractResult<()> {
+        for (ix, &slot) in mapping.iter().enumerate() {
+            inputs[slot].unify_with_mut(body.input_fact_mut(ix)?)?;
+        }
+        Ok(())
+    }
+
+    /// The parts of a shape both branches agree on.
+    fn common_shape(a: &ShapeFact, b: &ShapeFact) -> ShapeFact {
+        match (a.rank(), b.rank()) {
+            (GenericFact::Only(ra), GenericFact::Only(rb)) if ra == rb => ShapeFact::closed(
+                a.dims()
+                    .zip(b.dims())
+                    .map(|(da, db)| if da == db { da } else { GenericFact::Any })
+                    .collect(),
+            ),
+            _ => ShapeFact::default(),
+        }
+    }
+}
+
+impl Op for If {
+    fn name(&self) -> Cow<str> {
+        "If".into()
+    }
+
+    fn nested_models(&self) -> Vec<(Cow<str>, &dyn Model)> {
+        vec![("then".into(), &self.then_body), ("else".into(), &self.else_body)]
+    }
+
+    not_a_typed_op!();
+}
+
+impl StatelessOp for If {
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (body, mapping) = if *inputs[0].to_scalar::<bool>()? {
+            (&self.then_body, &self.then_input_mapping)
+        } else {
+            (&self.else_body, &self.else_input_mapping)
+        };
+        SimplePlan::new(body)?.run(branch_inputs(mapping, &inputs))
+    }
+}
+
+impl InferenceOp for If {
+    fn infer_facts(
+        &mut self,
+        inputs: TVec<&TensorFact>,
+        outputs: TVec<&TensorFact>,
+        _observed: TVec<&TensorFact>,
+    ) -> TractResult<(TVec<TensorFact>, TVec<TensorFact>, TVec<TensorFact>)> {
+        let nboutputs = self.then_body.output_outlets()?.len();
+        if self.else_body.output_outlets()?.len() != nboutputs {
+            bail!(
+                "If branches have {} and {} outputs",
+                nboutputs,
+                self.else_body.output_outlets()?.len()
+            )
+        }
+        if outputs.len() != nboutputs {
+            bail!("If has {} outputs, branches have {}", outputs.len(), nboutputs)
+        }
+        let mut inputs: TVec<TensorFact> = inputs.into_iter().cloned().collect();
+        let mut outputs: TVec<TensorFact> = outputs.into_iter().cloned().collect();
+        inputs[0].datum_type.unify_with(&bool::datum_type().into())?;
+        Self::unify_branch_inputs(&mut self.then_body, &self.then_input_mapping, &mut inputs)?;
+        Self::unify_branch_inputs(&mut self.else_body, &self.else_input_mapping, &mut inputs)?;
+        self.then_body
+            .analyse(false)
+            .map_err(|e| format!("analysing then branch: {}\n{:#?}", e, self.then_body))?;
+        self.else_body
+            .analyse(false)
+            .map_err(|e| format!("analysing else branch: {}\n{:#?}", e, self.else_body))?;
+        Self::unify_branch_inputs(&mut self.then_body, &self.then_input_mapping, &mut inputs)?;
+        Self::unify_branch_inputs(&mut self.else_body, &self.else_input_mapping, &mut inputs)?;
+        let cond = inputs[0].value.concretize();
+        for (ix, output) in outputs.iter_mut().enumerate() {
+            let then_fact = self.then_body.output_fact_mut(ix)?;
+            output.datum_type.unify_with_mut(&mut then_fact.datum_type)?;
+            let else_fact = self.else_body.output_fact_mut(ix)?;
+            output.datum_type.unify_with_mut(&mut else_fact.datum_type)?;
+            let then_fact = self.then_body.output_fact(ix)?;
+            let else_fact = self.else_body.output_fact(ix)?;
+            output.shape.unify_with(&Self::common_shape(&then_fact.shape, &else_fact.shape))?;
+            if let Some(cond) = &cond {
+                output.unify_with(if *cond.to_scalar::<bool>()? {
+                    then_fact
+                } else {
+                    else_fact
+                })?;
+            }
+        }
+        Ok((inputs, outputs, tvec!()))
+    }
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let inputs = node.inputs.iter().map(|m| mapping[m]).collect::<TVec<_>>();
+        target.wire_node(&*node.name, self.to_typed_if()?, &*inputs)
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.then_body.output_outlets()?.len())
+    }
+
+    inference_op_as_op!();
+}
+
+/// Typed form of `If`. Both branches must produce the same output facts.
+///
+/// The branch plans are built with the op, not at each evaluation.
+#[derive(Debug, Clone)]
+pub struct TypedIf {
+    then_plan: Arc<TypedSimplePlan<TypedModel>>,
+    pub then_input_mapping: Vec<usize>,
+    else_plan: Arc<TypedSimplePlan<TypedModel>>,
+    pub else_input_mapping: Vec<usize>,
+    decluttered: bool,
+    optimized: bool,
+}
+
+impl TypedIf {
+    pub fn new(
+        then_body: TypedModel,
+        then_input_mapping: Vec<usize>,
+        else_body: TypedModel,
+        else_input_mapping: Vec<usize>,
+    ) -> TractResult<TypedIf> {
+        Ok(TypedIf {
+            then_plan: Arc::new(SimplePlan::new(then_body)?),
+            then_input_mapping,
+            else_plan: Arc::new(SimplePlan::new(else_body)?),
+            else_input_mapping,
+            decluttered: false,
+            optimized: false,
+        })
+    }
+
+    pub fn then_body(&self) -> &TypedModel {
+        self.then_plan.model()
+    }
+
+    pub fn else_body(&self) -> &TypedModel {
+        self.else_plan.model()
+    }
+
+    /// Replaces the op by the nodes of one of its branches.
+    fn inline_branch(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+        cond: bool,
+    ) -> TractResult<TypedModelPatch> {
+        let (body, body_mapping, label) = if cond {
+            (self.then_body(), &self.then_input_mapping, "then")
+        } else {
+            (self.else_body(), &self.else_input_mapping, "else")
+        };
+        let mut patch = TypedModelPatch::default();
+        let mut mapping = HashMap::<OutletId, OutletId>::new();
+        for (ix, &slot) in body_mapping.iter().enumerate() {
+            let tap = patch.tap_model(model, node.inputs[slot])?;
+            mapping.insert(body.input_outlets()?[ix], tap);
+        }
+        for n in body.eval_order()? {
+            if body.input_outlets()?.iter().any(|i| i.node == n) {
+                continue;
+            }
+            let inner = body.node(n);
+            let facts = inner.outputs.iter().map(|o| o.fact.clone()).collect();
+            let id = patch.add_node(
+                format!("{}.{}.{}", node.name, label, inner.name),
+                inner.op.clone(),
+                facts,
+            )?;
+            for (ix, input) in inner.inputs.iter().enumerate() {
+                patch.add_edge(mapping[input], InletId::new(id, ix))?;
+            }
+            for ix in 0..inner.outputs.len() {
+                mapping.insert(OutletId::new(n, ix), OutletId::new(id, ix));
+            }
+        }
+        for (ix, output) in body.output_outlets()?.iter().enumerate() {
+            patch.shunt_outside(OutletId::new(node.id, ix), mapping[output])?;
+        }
+        Ok(patch)
+    }
+}
+
+impl Op for TypedIf {
+    fn name(&self) -> Cow<str> {
+        "TypedIf".into()
+    }
+
+    fn nested_models(&self) -> Vec<(Cow<str>, &dyn Model)> {
+        vec![("then".into(), self.then_body()), ("else".into(), self.else_body())]
+    }
+
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        if let Some(cond) = &model.outlet_fact(node.inputs[0])?.konst {
+            return Ok(Some(self.inline_branch(model, node, *cond.to_scalar::<bool>()?)?));
+        }
+        if !self.decluttered {
+            let mut new = TypedIf::new(
+                self.then_body().clone().declutter()?,
+                self.then_input_mapping.clone(),
+                self.else_body().clone().declutter()?,
+                self.else_input_mapping.clone(),
+            )?;
+            new.decluttered = true;
+            return Ok(Some(TypedModelPatch::replace_single_op(model, node, &node.inputs, new)?));
+        }
+        Ok(None)
+    }
+
+    fn codegen(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        if !self.optimized {
+            let mut new = TypedIf::new(
+                self.then_body().clone().into_optimized()?,
+                self.then_input_mapping.clone(),
+                self.else_body().clone().into_optimized()?,
+                self.else_input_mapping.clone(),
+            )?;
+            new.decluttered = true;
+            new.optimized = true;
+            return Ok(Some(TypedModelPatch::replace_single_op(model, node, &node.inputs, new)?));
+        }
+        Ok(None)
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for TypedIf {
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (plan, mapping) = if *inputs[0].to_scalar::<bool>()? {
+            (&self.then_plan, &self.then_input_mapping)
+        } else {
+            (&self.else_plan, &self.else_input_mapping)
+        };
+        plan.run(branch_inputs(mapping, &inputs))
+    }
+}
+
+impl TypedOp for TypedIf {
+    typed_op_as_op!();
+
+    fn output_facts(&self, _inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        let then_outputs = self.then_body().output_outlets()?;
+        let else_outputs = self.else_body().output_outlets()?;
+        if then_outputs.len() != else_outputs.len() {
+            bail!("If branches have {} and {} outputs", then_outputs.len(), else_outputs.len())
+        }
+        (0..then_outputs.len())
+            .map(|ix| {
+                let then_fact = self.then_body().output_fact(ix)?;
+                let else_fact = self.else_body().output_fact(ix)?;
+                if then_fact.datum_type != else_fact.datum_type
+                    || then_fact.shape != else_fact.shape
+                {
+                    bail!("If branches output #{} differ: {:?} and {:?}", ix, then_fact, else_fact)
+                }
+                Ok(TypedTensorInfo::dt_shape(then_fact.datum_type, then_fact.shape.clone())?)
+            })
+            .collect()
+    }
+
+    fn nested_model_multipliers(&self, _inputs: &[&TypedTensorInfo]) -> Vec<(Cow<str>, f32)> {
+        vec![("then".into(), 0.5), ("else".into(), 0.5)]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::math;
+
+    fn branch(value: f32) -> InferenceModel {
+        let mut body = InferenceModel::default();
+        let x =
+            body.add_source("x", TensorFact::dt_shape(f32::datum_type(), tvec!(2usize))).unwrap();
+        let value = body.add_const("value", tensor0(value)).unwrap();
+        body.wire_node("add", math::add::bin(), &[OutletId::new(x, 0), OutletId::new(value, 0)])
+            .unwrap();
+        body.auto_outputs().unwrap();
+        body
+    }
+
+    fn model() -> InferenceModel {
+        let mut model = InferenceModel::default();
+        let cond = model
+            .add_source("cond", TensorFact::dt_shape(bool::datum_type(), [0usize; 0].as_ref()))
+            .unwrap();
+        let x =
+            model.add_source("x", TensorFact::dt_shape(f32::datum_type(), tvec!(2usize))).unwrap();
+        let op = If::new(branch(1.0), vec![1], branch(-1.0), vec![1]);
+        model.wire_node("if", op, &[OutletId::new(cond, 0), OutletId::new(x, 0)]).unwrap();
+        model.auto_outputs().unwrap();
+        model
+    }
+
+    #[test]
+    fn eval_selected_branch() {
+        let model = model();
+        let plan = SimplePlan::new(&model).unwrap();
+        let x = tensor1(&[1f32, 2.0]);
+        let result = plan.run(tvec!(tensor0(true), x.clone())).unwrap();
+        assert_eq!(*result[0], tensor1(&[2f32, 3.0]));
+        let result = plan.run(tvec!(tensor0(false), x)).unwrap();
+        assert_eq!(*result[0], tensor1(&[0f32, 1.0]));
+    }
+
+    #[test]
+    fn typed_if_matches_inference() {
+        let model = model().into_optimized().unwrap();
+        let plan = SimplePlan::new(&model).unwrap();
+        let x = tensor1(&[1f32, 2.0]);
+        let result = plan.run(tvec!(tensor0(false), x)).unwrap();
+        assert_eq!(*result[0], tensor1(&[0f32, 1.0]));
+    }
+
+    #[test]
+    fn constant_condition_is_inlined() {
+        let mut model = TypedModel::default();
+        let fact = TypedTensorInfo::dt_shape(f32::datum_type(), [2usize].as_ref()).unwrap();
+        let x = OutletId::new(model.add_source("x", fact).unwrap(), 0);
+        let cond = model.add_const("cond", tensor0(true)).unwrap();
+        let op = If::new(branch(1.0), vec![1], branch(-1.0), vec![1]).to_typed_if().unwrap();
+        model.wire_node("if", op, &[OutletId::new(cond, 0), x]).unwrap();
+        model.auto_outputs().unwrap();
+        let model = model.declutter().unwrap();
+        assert!(model.nodes().iter().all(|n| n.op_as::<TypedIf>().is_none()));
+        let result = SimplePlan::new(&model).unwrap().run(tvec!(tensor1(&[1f32, 2.0]))).unwrap();
+        assert_eq!(*result[0], tensor1(&[2f32, 3.0]));
+    }
+}
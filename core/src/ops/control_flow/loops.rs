@@ -0,0 +1,579 @@
+use std::borrow::Borrow;
+use std::fmt::{Debug, Display};
+
+use ndarray::*;
+
+use crate::internal::*;
+use crate::model::{ModelImpl, TensorInfo};
+use crate::ops::identity::Identity;
+
+/// Repeated evaluation of a nested model, bounded by an optional trip count
+/// and an optional termination condition.
+///
+/// Op inputs are the trip count (an i64 scalar, if `trip_count_input` is set),
+/// the initial condition (a bool scalar, if `cond_input` is set), the initial
+/// values of the `carried` loop carried values, then values the body reads
+/// as is at each iteration.
+///
+/// Body inputs are the iteration number, the condition, the loop carried
+/// values and the values read as is. Body outputs are the condition for the
+/// next iteration, the updated loop carried values, then scan outputs.
+///
+/// Op outputs are the final loop carried values, then the scan outputs of
+/// every iteration, stacked along a new first axis. Loop carried values must
+/// keep their type and shape across iterations.
+#[derive(Debug, Clone, new)]
+pub struct Loop {
+    pub body: InferenceModel,
+    pub trip_count_input: Option<usize>,
+    pub cond_input: Option<usize>,
+    pub carried: usize,
+}
+
+fn first_carried_input(trip_count_input: Option<usize>, cond_input: Option<usize>) -> usize {
+    trip_count_input.iter().chain(cond_input.iter()).count()
+}
+
+/// Does the body pass the condition through, or make it constant true ?
+fn body_keeps_condition<TI, O>(body: &ModelImpl<TI, O>) -> TractResult<bool>
+where
+    TI: TensorInfo + Clone + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static,
+{
+    let cond_in = body.input_outlets()?[1];
+    let mut cond_out = body.output_outlets()?[0];
+    if let Some(cond) = body.outlet_fact(cond_out)?.to_tensor_fact().value.concretize() {
+        return Ok(*cond.to_scalar::<bool>()?);
+    }
+    while cond_out != cond_in {
+        let node = body.node(cond_out.node);
+        if node.op_is::<Identity>() {
+            cond_out = node.inputs[0];
+        } else {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Iteration count, when it does not depend on the data.
+fn static_iterations<TI, O>(
+    body: &ModelImpl<TI, O>,
+    trip_count: Option<Arc<Tensor>>,
+    cond: Option<Option<Arc<Tensor>>>,
+) -> TractResult<Option<usize>>
+where
+    TI: TensorInfo + Clone + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static,
+{
+    let trip_count = if let Some(trip_count) = trip_count {
+        *trip_count.cast_to::<i64>()?.to_scalar::<i64>()?
+    } else {
+        return Ok(None);
+    };
+    let starts = match cond {
+        None => true,
+        Some(Some(cond)) => *cond.to_scalar::<bool>()?,
+        Some(None) => return Ok(None),
+    };
+    if !starts {
+        Ok(Some(0))
+    } else if body_keeps_condition(body)? {
+        Ok(Some(trip_count.max(0) as usize))
+    } else {
+        Ok(None)
+    }
+}
+
+fn scalar_input<T: Datum>(value: T, rank: Option<i32>) -> Tensor {
+    if rank == Some(1) {
+        tensor1(&[value])
+    } else {
+        tensor0(value)
+    }
+}
+
+fn stack_t<T: Datum>(values: &[Arc<Tensor>]) -> TractResult<Tensor> {
+    let views = values
+        .iter()
+        .map(|v| Ok(v.to_array_view::<T>()?.insert_axis(Axis(0))))
+        .collect::<TractResult<Vec<_>>>()?;
+    Ok(T::stack_views(0, &views)?.into_tensor())
+}
+
+fn eval_loop<TI, O, M>(
+    plan: &SimplePlan<TI, O, M>,
+    trip_count_input: Option<usize>,
+    cond_input: Option<usize>,
+    carried: usize,
+    inputs: TVec<Arc<Tensor>>,
+) -> TractResult<TVec<Arc<Tensor>>>
+where
+    TI: TensorInfo + Clone + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static,
+    M: Borrow<ModelImpl<TI, O>>,
+{
+    let body = plan.model();
+    let trip_count = match trip_count_input {
+        Some(ix) => Some(*inputs[ix].cast_to::<i64>()?.to_scalar::<i64>()?),
+        None => None,
+    };
+    let mut cond = match cond_input {
+        Some(ix) => *inputs[ix].to_scalar::<bool>()?,
+        None => true,
+    };
+    let first = first_carried_input(trip_count_input, cond_input);
+    let mut values: TVec<Arc<Tensor>> = inputs[first..][..carried].into();
+    let closures = &inputs[first + carried..];
+    let scans = body.output_outlets()?.len() - 1 - carried;
+    let mut scanned: Vec<Vec<Arc<Tensor>>> = vec![vec![]; scans];
+    let iter_rank = body.input_fact(0)?.to_tensor_fact().shape.rank().concretize();
+    let cond_rank = body.input_fact(1)?.to_tensor_fact().shape.rank().concretize();
+    let mut state = SimpleState::new(plan)?;
+    let mut i = 0i64;
+    while cond && trip_count.map(|t| i < t).unwrap_or(true) {
+        let mut iter_inputs = tvec!(scalar_input(i, iter_rank), scalar_input(cond, cond_rank));
+        iter_inputs.extend(values.iter().chain(closures.iter()).map(|t| t.clone().into_tensor()));
+        let mut outputs = state
+            .run(iter_inputs)
+            .chain_err(|| format!("Evaluating loop body, iteration {}", i))?
+            .into_iter();
+        cond = *outputs.next().unwrap().to_scalar::<bool>()?;
+        values = outputs.by_ref().take(carried).collect();
+        for (scan, value) in scanned.iter_mut().zip(outputs) {
+            scan.push(value);
+        }
+        i += 1;
+    }
+    for (ix, scan) in scanned.into_iter().enumerate() {
+        let stacked = if !scan.is_empty() {
+            dispatch_datum!(stack_t(scan[0].datum_type())(&scan))?
+        } else {
+            let fact = body.output_fact(1 + carried + ix)?.to_tensor_fact();
+            let dt = fact.datum_type.concretize().ok_or("Loop scan output has unknown type")?;
+            let mut shape = fact.shape.as_concrete_finite()?.unwrap_or(tvec!());
+            shape.insert(0, 0);
+            unsafe { Tensor::uninitialized_dt(dt, &*shape)? }
+        };
+        values.push(stacked.into_arc_tensor());
+    }
+    Ok(values)
+}
+
+impl Loop {
+    fn first_carried_input(&self) -> usize {
+        first_carried_input(self.trip_count_input, self.cond_input)
+    }
+
+    pub(super) fn to_typed_loop(&self) -> TractResult<TypedLoop> {
+        TypedLoop::new(
+            self.body.clone().into_typed()?,
+            self.trip_count_input,
+            self.cond_input,
+            self.carried,
+        )
+    }
+
+    /// Unify type and shape, leaving values alone as they change across
+    /// iterations.
+    fn unify_carried(facts: &mut [&mut TensorFact]) -> TractResult<()> {
+        let mut common = TensorFact::default();
+        for fact in facts.iter() {
+            common.datum_type.unify_with(&fact.datum_type)?;
+            common.shape.unify_with(&fact.shape)?;
+        }
+        for fact in facts.iter_mut() {
+            fact.datum_type.unify_with(&common.datum_type)?;
+            fact.shape.unify_with(&common.shape)?;
+        }
+        Ok(())
+    }
+
+    fn unify_facts(
+        &mut self,
+        inputs: &mut [TensorFact],
+        outputs: &mut [TensorFact],
+    ) -> TractResult<()> {
+        if let Some(ix) = self.trip_count_input {
+            inputs[ix].datum_type.unify_with(&i64::datum_type().into())?;
+        }
+        if let Some(ix) = self.cond_input {
+            inputs[ix].datum_type.unify_with(&bool::datum_type().into())?;
+        }
+        for (ix, dt) in [i64::datum_type(), bool::datum_type()].iter().enumerate() {
+            let fact = self.body.input_fact_mut(ix)?;
+            fact.datum_type.unify_with(&(*dt).into())?;
+            if fact.shape.rank() == GenericFact::Any {
+                fact.shape.unify_with(&ShapeFact::closed(tvec!()))?;
+            }
+        }
+        self.body.output_fact_mut(0)?.datum_type.unify_with(&bool::datum_type().into())?;
+        let first = self.first_carried_input();
+        for k in 0..self.carried {
+            let body_input = self.body.input_outlets()?[2 + k];
+            let body_output = self.body.output_outlets()?[1 + k];
+            let mut inner = self.body.outlets_fact_mut(&[body_input, body_output])?;
+            inner.push(&mut inputs[first + k]);
+            inner.push(&mut outputs[k]);
+            Self::unify_carried(&mut inner)?;
+        }
+        for (ix, input) in inputs[first + self.carried..].iter_mut().enumerate() {
+            input.unify_with_mut(self.body.input_fact_mut(2 + self.carried + ix)?)?;
+        }
+        let iterations = static_iterations(
+            &self.body,
+            self.trip_count_input.and_then(|ix| inputs[ix].value.concretize()),
+            self.cond_input.map(|ix| inputs[ix].value.concretize()),
+        )?;
+        for (ix, output) in outputs[self.carried..].iter_mut().enumerate() {
+            let fact = self.body.output_fact(1 + self.carried + ix)?;
+            output.datum_type.unify_with(&fact.datum_type)?;
+            if let GenericFact::Only(_) = fact.shape.rank() {
+                let iterations =
+                    iterations.map(|i| GenericFact::Only(i.to_dim())).unwrap_or_default();
+                let shape = std::iter::once(iterations).chain(fact.shape.dims()).collect();
+                output.shape.unify_with(&ShapeFact::closed(shape))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Op for Loop {
+    fn name(&self) -> Cow<str> {
+        "Loop".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!(
+            "trip count: {:?}, condition: {:?}, carried: {}",
+            self.trip_count_input, self.cond_input, self.carried
+        )])
+    }
+
+    fn nested_models(&self) -> Vec<(Cow<str>, &dyn Model)> {
+        vec![("loop".into(), &self.body)]
+    }
+
+    not_a_typed_op!();
+}
+
+impl StatelessOp for Loop {
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let plan = SimplePlan::new(&self.body)?;
+        eval_loop(&plan, self.trip_count_input, self.cond_input, self.carried, inputs)
+    }
+}
+
+impl InferenceOp for Loop {
+    fn infer_facts(
+        &mut self,
+        inputs: TVec<&TensorFact>,
+        outputs: TVec<&TensorFact>,
+        _observed: TVec<&TensorFact>,
+    ) -> TractResult<(TVec<TensorFact>, TVec<TensorFact>, TVec<TensorFact>)> {
+        let body_inputs = self.body.input_outlets()?.len();
+        let body_outputs = self.body.output_outlets()?.len();
+        if body_inputs < 2 + self.carried || body_outputs < 1 + self.carried {
+            bail!(
+                "Loop body has {} inputs and {} outputs, expected at least {} and {}",
+                body_inputs,
+                body_outputs,
+                2 + self.carried,
+                1 + self.carried
+            )
+        }
+        let expected_inputs = self.first_carried_input() + body_inputs - 2;
+        if inputs.len() != expected_inputs {
+            bail!("Loop receives {} inputs, body expects {}", inputs.len(), expected_inputs)
+        }
+        if outputs.len() != body_outputs - 1 {
+            bail!("Loop has {} outputs, body has {}", outputs.len(), body_outputs - 1)
+        }
+        let mut inputs: TVec<TensorFact> = inputs.into_iter().cloned().collect();
+        let mut outputs: TVec<TensorFact> = outputs.into_iter().cloned().collect();
+        self.unify_facts(&mut inputs, &mut outputs)?;
+        self.body
+            .analyse(false)
+            .map_err(|e| format!("analysing loop body: {}\n{:#?}", e, self.body))?;
+        self.unify_facts(&mut inputs, &mut outputs)?;
+        Ok((inputs, outputs, tvec!()))
+    }
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let inputs = node.inputs.iter().map(|m| mapping[m]).collect::<TVec<_>>();
+        let mut op = self.to_typed_loop()?;
+        op.iterations_symbol = Some(crate::model::unused_symbol(&[target, op.body()])?);
+        target.wire_node(&*node.name, op, &*inputs)
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.body.output_outlets()?.len() - 1)
+    }
+
+    inference_op_as_op!();
+}
+
+/// Typed form of `Loop`.
+///
+/// Scan outputs are stacked along an axis as long as the iteration count. It
+/// is known if it does not depend on the data (a constant trip count, and a
+/// condition constant or passed through by the body), and typed with
+/// `iterations_symbol` otherwise.
+///
+/// The body plan is built with the op, not at each evaluation.
+#[derive(Debug, Clone)]
+pub struct TypedLoop {
+    plan: Arc<TypedSimplePlan<TypedModel>>,
+    pub trip_count_input: Option<usize>,
+    pub cond_input: Option<usize>,
+    pub carried: usize,
+    /// Length of the scan outputs when the iteration count depends on the
+    /// data, picked when translating to a typed model.
+    pub iterations_symbol: Option<char>,
+    decluttered: bool,
+    optimized: bool,
+}
+
+impl TypedLoop {
+    pub fn new(
+        body: TypedModel,
+        trip_count_input: Option<usize>,
+        cond_input: Option<usize>,
+        carried: usize,
+    ) -> TractResult<TypedLoop> {
+        Ok(TypedLoop {
+            plan: Arc::new(SimplePlan::new(body)?),
+            trip_count_input,
+            cond_input,
+            carried,
+            iterations_symbol: None,
+            decluttered: false,
+            optimized: false,
+        })
+    }
+
+    pub fn body(&self) -> &TypedModel {
+        self.plan.model()
+    }
+
+    fn static_iterations(&self, inputs: &[&TypedTensorInfo]) -> TractResult<Option<usize>> {
+        static_iterations(
+            self.body(),
+            self.trip_count_input.and_then(|ix| inputs[ix].konst.clone()),
+            self.cond_input.map(|ix| inputs[ix].konst.clone()),
+        )
+    }
+}
+
+impl Op for TypedLoop {
+    fn name(&self) -> Cow<str> {
+        "TypedLoop".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!(
+            "trip count: {:?}, condition: {:?}, carried: {}",
+            self.trip_count_input, self.cond_input, self.carried
+        )])
+    }
+
+    fn nested_models(&self) -> Vec<(Cow<str>, &dyn Model)> {
+        vec![("loop".into(), self.body())]
+    }
+
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        if !self.decluttered {
+            let mut new = TypedLoop::new(
+                self.body().clone().declutter()?,
+                self.trip_count_input,
+                self.cond_input,
+                self.carried,
+            )?;
+            new.iterations_symbol = self.iterations_symbol;
+            new.decluttered = true;
+            return Ok(Some(TypedModelPatch::replace_single_op(model, node, &node.inputs, new)?));
+        }
+        Ok(None)
+    }
+
+    fn codegen(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        if !self.optimized {
+            let mut new = TypedLoop::new(
+                self.body().clone().into_optimized()?,
+                self.trip_count_input,
+                self.cond_input,
+                self.carried,
+            )?;
+            new.iterations_symbol = self.iterations_symbol;
+            new.decluttered = true;
+            new.optimized = true;
+            return Ok(Some(TypedModelPatch::replace_single_op(model, node, &node.inputs, new)?));
+        }
+        Ok(None)
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for TypedLoop {
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        eval_loop(&self.plan, self.trip_count_input, self.cond_input, self.carried, inputs)
+    }
+}
+
+impl TypedOp for TypedLoop {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        let first = first_carried_input(self.trip_count_input, self.cond_input);
+        let mut outputs: TVec<TypedTensorInfo> = inputs[first..][..self.carried]
+            .iter()
+            .map(|fact| TypedTensorInfo::dt_shape(fact.datum_type, fact.shape.clone()))
+            .collect::<TractResult<_>>()?;
+        let scans = self.body().output_outlets()?.len() - 1 - self.carried;
+        if scans > 0 {
+            let iterations = match self.static_iterations(inputs)? {
+                Some(iterations) => iterations.to_dim(),
+                None => TDim::sym(
+                    self.iterations_symbol.ok_or("Loop needs a symbol for its iteration count")?,
+                ),
+            };
+            for ix in 0..scans {
+                let fact = self.body().output_fact(1 + self.carried + ix)?;
+                let shape: TVec<TDim> =
+                    std::iter::once(iterations.clone()).chain(fact.shape.iter()).collect();
+                outputs.push(TypedTensorInfo::dt_shape(fact.datum_type, &*shape)?);
+            }
+        }
+        Ok(outputs)
+    }
+
+    fn nested_model_multipliers(&self, inputs: &[&TypedTensorInfo]) -> Vec<(Cow<str>, f32)> {
+        let iterations = self.static_iterations(inputs).ok().and_then(|i| i).unwrap_or(1);
+        vec![("loop".into(), iterations as f32)]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::{logic, math};
+
+    // body: (i, cond, x) -> (cond', x + 1, x + 1)
+    fn body(cond: impl Fn(&mut InferenceModel, OutletId, OutletId) -> OutletId) -> InferenceModel {
+        let mut body = InferenceModel::default();
+        body.add_source_default("i").unwrap();
+        let cond_in = OutletId::new(body.add_source_default("cond").unwrap(), 0);
+        let x = OutletId::new(body.add_source_default("x").unwrap(), 0);
+        let one = OutletId::new(body.add_const("one", tensor0(1f32)).unwrap(), 0);
+        let next = body.wire_node("next", math::add::bin(), &[x, one]).unwrap()[0];
+        let cond_out = cond(&mut body, cond_in, next);
+        let scan = body.wire_node("scan", Identity::default(), &[next]).unwrap()[0];
+        body.set_output_outlets(&[cond_out, next, scan]).unwrap();
+        body
+    }
+
+    fn model(loop_op: Loop, inputs: &[TensorFact]) -> InferenceModel {
+        let mut model = InferenceModel::default();
+        let inputs: TVec<OutletId> = inputs
+            .iter()
+            .enumerate()
+            .map(|(ix, fact)| {
+                OutletId::new(model.add_source(format!("input-{}", ix), fact.clone()).unwrap(), 0)
+            })
+            .collect();
+        model.wire_node("loop", loop_op, &*inputs).unwrap();
+        model.auto_outputs().unwrap();
+        model.analyse(false).unwrap();
+        model
+    }
+
+    fn scalar(dt: DatumType) -> TensorFact {
+        TensorFact::dt_shape(dt, ShapeFact::closed(tvec!()))
+    }
+
+    #[test]
+    fn trip_count() {
+        let inputs =
+            [scalar(i64::datum_type()), TensorFact::dt_shape(f32::datum_type(), tvec!(2usize))];
+        let model = model(Loop::new(body(|_, cond, _| cond), Some(0), None, 1), &inputs);
+        let plan = SimplePlan::new(&model).unwrap();
+        let result = plan.run(tvec!(tensor0(3i64), tensor1(&[0f32, 10.0]))).unwrap();
+        assert_eq!(*result[0], tensor1(&[3f32, 13.0]));
+        assert_eq!(*result[1], tensor2(&[[1f32, 11.0], [2.0, 12.0], [3.0, 13.0]]));
+    }
+
+    #[test]
+    fn condition() {
+        let body = body(|body, _, next| {
+            let three = OutletId::new(body.add_const("three", tensor0(3f32)).unwrap(), 0);
+            body.wire_node("cond_out", logic::lesser::bin(), &[next, three]).unwrap()[0]
+        });
+        let inputs = [scalar(bool::datum_type()), scalar(f32::datum_type())];
+        let model = model(Loop::new(body, None, Some(0), 1), &inputs);
+        let plan = SimplePlan::new(&model).unwrap();
+        let result = plan.run(tvec!(tensor0(true), tensor0(0f32))).unwrap();
+        assert_eq!(*result[0], tensor0(3f32));
+        assert_eq!(*result[1], tensor1(&[1f32, 2.0, 3.0]));
+        let result = plan.run(tvec!(tensor0(false), tensor0(0f32))).unwrap();
+        assert_eq!(*result[0], tensor0(0f32));
+        assert_eq!(result[1].shape(), &[0]);
+    }
+
+    #[test]
+    fn typed_with_constant_trip_count() {
+        let mut model = InferenceModel::default();
+        let trip_count = model.add_const("trip_count", tensor0(3i64)).unwrap();
+        let x =
+            model.add_source("x", TensorFact::dt_shape(f32::datum_type(), tvec!(2usize))).unwrap();
+        let op = Loop::new(body(|_, cond, _| cond), Some(0), None, 1);
+        model.wire_node("loop", op, &[OutletId::new(trip_count, 0), OutletId::new(x, 0)]).unwrap();
+        model.auto_outputs().unwrap();
+        let model = model.into_optimized().unwrap();
+        assert_eq!(model.output_fact(1).unwrap().shape.as_finite().unwrap(), &[3, 2]);
+        let result = SimplePlan::new(&model).unwrap().run(tvec!(tensor1(&[0f32, 10.0]))).unwrap();
+        assert_eq!(*result[1], tensor2(&[[1f32, 11.0], [2.0, 12.0], [3.0, 13.0]]));
+    }
+
+    #[test]
+    fn typed_with_data_dependent_iterations() {
+        let mut model = InferenceModel::default();
+        let trip_count = model.add_source("trip_count", scalar(i64::datum_type())).unwrap();
+        let cond = model.add_const("cond", tensor0(true)).unwrap();
+        let x = model.add_source("x", scalar(f32::datum_type())).unwrap();
+        let body = body(|body, _, next| {
+            let zero = OutletId::new(body.add_const("zero", tensor0(0f32)).unwrap(), 0);
+            body.wire_node("cond_out", logic::lesser::bin(), &[next, zero]).unwrap()[0]
+        });
+        let inputs = [trip_count, cond, x].iter().map(|&n| OutletId::new(n, 0)).collect::<Vec<_>>();
+        model.wire_node("loop", Loop::new(body, Some(0), Some(1), 1), &*inputs).unwrap();
+        model.auto_outputs().unwrap();
+        let model = model.into_optimized().unwrap();
+        let scan = model.output_fact(1).unwrap().shape.to_tvec();
+        assert_eq!(scan.len(), 1);
+        assert!(scan[0].is_symbolic());
+        let plan = SimplePlan::new(&model).unwrap();
+        // stops on the trip count
+        let result = plan.run(tvec!(tensor0(2i64), tensor0(-10f32))).unwrap();
+        assert_eq!(*result[1], tensor1(&[-9f32, -8.0]));
+        // stops on the condition
+        let result = plan.run(tvec!(tensor0(10i64), tensor0(-2f32))).unwrap();
+        assert_eq!(*result[0], tensor0(0f32));
+        assert_eq!(*result[1], tensor1(&[-1f32, 0.0]));
+    }
+}
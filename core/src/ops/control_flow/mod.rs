@@ -0,0 +1,6 @@
+//! Control flow operators, evaluating nested models.
+mod if_then_else;
+mod loops;
+
+pub use self::if_then_else::{If, TypedIf};
+pub use self::loops::{Loop, TypedLoop};
@@ -7,6 +7,7 @@ mod conv;
 mod scan;
 
 #[derive(Debug, Clone, new, Default, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct Downsample {
     axis: usize,
     stride: usize,
@@ -1,6 +1,7 @@
 use crate::internal::*;
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct Identity;
 
 impl Op for Identity {
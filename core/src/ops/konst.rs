@@ -1,8 +1,9 @@
 use crate::internal::*;
 
 #[derive(Debug, Clone, new)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct Const {
-    value: Arc<Tensor>,
+    pub value: Arc<Tensor>,
 }
 
 impl Const {
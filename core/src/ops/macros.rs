@@ -346,6 +346,7 @@ macro_rules! element_map_with_params {
         use $crate::internal::*;
 
         #[derive(Debug, Clone, new, Default)]
+        #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
         pub struct $Name {
             $( pub $pname: $pty ),*
         }
@@ -612,7 +613,7 @@ macro_rules! dispatch_floatlike {
     ($($path:ident)::* ($dt:expr) ($($args:expr),*)) => { {
         use $crate::datum::DatumType;
         match $dt {
-            DatumType::F16  => $($path)::*::<f32>($($args),*), // FIXME !!!
+            DatumType::F16  => $($path)::*::<f16>($($args),*),
             DatumType::F32  => $($path)::*::<f32>($($args),*),
             DatumType::F64  => $($path)::*::<f64>($($args),*),
             _ => bail!("{:?} is not float-like", $dt)
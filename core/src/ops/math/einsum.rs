@@ -0,0 +1,560 @@
+use std::ops::{Add, Mul};
+
+use crate::internal::*;
+use itertools::Itertools;
+use ndarray::*;
+
+use super::MatMul;
+use crate::ops::array::{IntoShape, PermuteAxes, Reshape};
+
+/// Einstein summation over its inputs.
+///
+/// Each term holds one label per axis, '.' standing for an ellipsis. Labels
+/// absent from the output are summed over. Axes sharing a label must have
+/// the same dimension: no broadcasting is performed.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct Einsum {
+    pub inputs: Vec<Vec<char>>,
+    pub output: Option<Vec<char>>,
+}
+
+/// Einsum terms with ellipsis expanded and implicit output made explicit.
+#[derive(Debug, Clone)]
+struct Resolved {
+    inputs: Vec<Vec<char>>,
+    output: Vec<char>,
+}
+
+impl Resolved {
+    fn labels(&self) -> impl Iterator<Item = char> + '_ {
+        self.inputs.iter().flat_map(|term| term.iter().cloned()).unique()
+    }
+
+    fn dims<D: DimLike>(&self, shapes: &[&[D]]) -> TractResult<HashMap<char, D>> {
+        let mut dims = HashMap::<char, D>::new();
+        for (term, shape) in self.inputs.iter().zip(shapes.iter()) {
+            for (&label, dim) in term.iter().zip(shape.iter()) {
+                if let Some(prev) = dims.get(&label) {
+                    if prev != dim {
+                        bail!("Einsum: axes labelled {} have dims {:?} and {:?}", label, prev, dim)
+                    }
+                } else {
+                    dims.insert(label, dim.clone());
+                }
+            }
+        }
+        Ok(dims)
+    }
+
+    fn output_shape<D: DimLike>(&self, shapes: &[&[D]]) -> TractResult<TVec<D>> {
+        let dims = self.dims(shapes)?;
+        Ok(self.output.iter().map(|l| dims[l].clone()).collect())
+    }
+}
+
+impl Einsum {
+    /// Parse an equation like "bij,bjk->bik".
+    pub fn parse(equation: &str) -> TractResult<Einsum> {
+        let equation: String = equation.chars().filter(|c| !c.is_whitespace()).collect();
+        let (inputs, output) = match equation.split("->").collect::<Vec<_>>().as_slice() {
+            [inputs] => (*inputs, None),
+            [inputs, output] => (*inputs, Some(*output)),
+            _ => bail!("Einsum: invalid equation {}", equation),
+        };
+        fn term(term: &str) -> TractResult<Vec<char>> {
+            let mut labels = vec![];
+            for (ix, part) in term.split("...").enumerate() {
+                if ix == 1 {
+                    labels.push('.');
+                } else if ix > 1 {
+                    bail!("Einsum: more than one ellipsis in {}", term)
+                }
+                if let Some(c) = part.chars().find(|c| !c.is_ascii_alphabetic()) {
+                    bail!("Einsum: invalid label {} in {}", c, term)
+                }
+                labels.extend(part.chars());
+            }
+            Ok(labels)
+        }
+        let inputs = inputs.split(',').map(term).collect::<TractResult<Vec<_>>>()?;
+        let output = output.map(term).transpose()?;
+        if let Some(output) = &output {
+            if output.iter().unique().count() != output.len() {
+                bail!("Einsum: repeated label in output {}", equation)
+            }
+        }
+        Ok(Einsum { inputs, output })
+    }
+
+    fn equation(&self) -> String {
+        let term = |t: &Vec<char>| {
+            t.iter().map(|&c| if c == '.' { "...".to_string() } else { c.to_string() }).join("")
+        };
+        let mut equation = self.inputs.iter().map(term).join(",");
+        if let Some(output) = &self.output {
+            equation.push_str("->");
+            equation.push_str(&term(output));
+        }
+        equation
+    }
+
+    fn resolve(&self, ranks: &[usize]) -> TractResult<Resolved> {
+        if ranks.len() != self.inputs.len() {
+            bail!(
+                "Einsum {} expects {} inputs, got {}",
+                self.equation(),
+                self.inputs.len(),
+                ranks.len()
+            )
+        }
+        // ellipsis axes get labels outside of the ascii range, aligned from
+        // the right so that they match across terms
+        let ellipsis = |len: usize| -> Vec<char> {
+            (0..len).rev().map(|i| std::char::from_u32(0xE000 + i as u32).unwrap()).collect()
+        };
+        let mut ellipsis_len = 0;
+        let mut inputs = vec![];
+        for (term, &rank) in self.inputs.iter().zip(ranks.iter()) {
+            let explicit = term.iter().filter(|&&c| c != '.').count();
+            if term.contains(&'.') {
+                if rank < explicit {
+                    bail!("Einsum {}: input of rank {} is too small", self.equation(), rank)
+                }
+                ellipsis_len = ellipsis_len.max(rank - explicit);
+                let mut labels = vec![];
+                for &c in term {
+                    if c == '.' {
+                        labels.extend(ellipsis(rank - explicit));
+                    } else {
+                        labels.push(c);
+                    }
+                }
+                inputs.push(labels);
+            } else if rank == explicit {
+                inputs.push(term.clone());
+            } else {
+                bail!(
+                    "Einsum {}: expected an input of rank {}, got {}",
+                    self.equation(),
+                    explicit,
+                    rank
+                )
+            }
+        }
+        let output = if let Some(output) = &self.output {
+            let mut labels = vec![];
+            for &c in output {
+                if c == '.' {
+                    labels.extend(ellipsis(ellipsis_len));
+                } else if inputs.iter().any(|term| term.contains(&c)) {
+                    labels.push(c);
+                } else {
+                    bail!("Einsum {}: output label {} is not in inputs", self.equation(), c)
+                }
+            }
+            labels
+        } else {
+            let counts = inputs.iter().flat_map(|term| term.iter()).fold(
+                HashMap::<char, usize>::new(),
+                |mut counts, &c| {
+                    *counts.entry(c).or_insert(0) += 1;
+                    counts
+                },
+            );
+            let mut labels = ellipsis(ellipsis_len);
+            labels.extend(
+                counts
+                    .iter()
+                    .filter(|&(c, n)| *n == 1 && c.is_ascii_alphabetic())
+                    .map(|(c, _)| *c)
+                    .sorted(),
+            );
+            labels
+        };
+        Ok(Resolved { inputs, output })
+    }
+
+    fn eval_t<T>(&self, inputs: &[Arc<Tensor>]) -> TractResult<Tensor>
+    where
+        T: Datum + Add<Output = T> + Mul<Output = T>,
+    {
+        let views =
+            inputs.iter().map(|t| t.to_array_view::<T>()).collect::<TractResult<Vec<_>>>()?;
+        let shapes = views.iter().map(|v| v.shape()).collect::<Vec<_>>();
+        let resolved = self.resolve(&*shapes.iter().map(|s| s.len()).collect::<Vec<_>>())?;
+        let dims = resolved.dims(&shapes)?;
+        let summed = resolved.labels().filter(|l| !resolved.output.contains(l)).collect::<Vec<_>>();
+        let summed_shape = summed.iter().map(|l| dims[l]).collect::<Vec<_>>();
+        // each input axis reads its coordinate either from the output
+        // coordinates, or from the summed ones
+        let sources = resolved
+            .inputs
+            .iter()
+            .map(|term| {
+                term.iter()
+                    .map(|l| match resolved.output.iter().position(|o| o == l) {
+                        Some(ix) => Ok(ix),
+                        None => Err(summed.iter().position(|s| s == l).unwrap()),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let output_shape = resolved.output.iter().map(|l| dims[l]).collect::<Vec<_>>();
+        let mut coords = resolved.inputs.iter().map(|t| vec![0; t.len()]).collect::<Vec<_>>();
+        let output = ArrayD::from_shape_fn(&*output_shape, |output_coords| {
+            let mut acc = T::default();
+            for summed_coords in indices(&*summed_shape) {
+                let mut product: Option<T> = None;
+                for (ix, view) in views.iter().enumerate() {
+                    for (axis, source) in sources[ix].iter().enumerate() {
+                        coords[ix][axis] = match source {
+                            Ok(o) => output_coords[*o],
+                            Err(s) => summed_coords[*s],
+                        };
+                    }
+                    let value = view[&*coords[ix]].clone();
+                    product = Some(match product {
+                        Some(p) => p * value,
+                        None => value,
+                    });
+                }
+                acc = acc + product.unwrap();
+            }
+            acc
+        });
+        Ok(output.into_tensor())
+    }
+
+    /// Lower a two-input einsum to a MatMul, surrounded by axes permutations
+    /// and reshapes. Labels are sorted in batch (on both inputs and the
+    /// output), m (a and output), k (a and b) and n (b and output) axes.
+    fn lower_to_mat_mul(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        if node.inputs.len() != 2 {
+            return Ok(None);
+        }
+        let (a, b) = (model.outlet_fact(node.inputs[0])?, model.outlet_fact(node.inputs[1])?);
+        match a.datum_type {
+            DatumType::F16 | DatumType::F32 | DatumType::F64 => (),
+            _ => return Ok(None),
+        }
+        let (a_shape, b_shape) = (a.shape.to_tvec(), b.shape.to_tvec());
+        let resolved = self.resolve(&[a_shape.len(), b_shape.len()])?;
+        let (la, lb, lo) = (&resolved.inputs[0], &resolved.inputs[1], &resolved.output);
+        if la.iter().unique().count() != la.len() || lb.iter().unique().count() != lb.len() {
+            return Ok(None);
+        }
+        if la
+            .iter()
+            .chain(lb.iter())
+            .any(|l| !lo.contains(l) && !(la.contains(l) && lb.contains(l)))
+        {
+            return Ok(None);
+        }
+        let dims = resolved.dims(&[&*a_shape, &*b_shape])?;
+        let batch =
+            lo.iter().filter(|l| la.contains(l) && lb.contains(l)).cloned().collect::<Vec<_>>();
+        let m =
+            lo.iter().filter(|l| la.contains(l) && !lb.contains(l)).cloned().collect::<Vec<_>>();
+        let n =
+            lo.iter().filter(|l| lb.contains(l) && !la.contains(l)).cloned().collect::<Vec<_>>();
+        let k =
+            la.iter().filter(|l| lb.contains(l) && !lo.contains(l)).cloned().collect::<Vec<_>>();
+        let batch_shape = batch.iter().map(|l| dims[l].clone()).collect::<TVec<_>>();
+        let product = |labels: &[char]| labels.iter().map(|l| dims[l].clone()).product::<TDim>();
+        // IntoShape needs all dimensions known. Otherwise, a Reshape keeps the
+        // ones equal to the input dimension at the same place (0) and infers at
+        // most one (-1).
+        fn reshape(
+            patch: &mut TypedModelPatch,
+            name: String,
+            wire: OutletId,
+            shape: &[TDim],
+        ) -> TractResult<Option<OutletId>> {
+            let input = patch.outlet_fact(wire)?.shape.to_tvec();
+            if &*input == shape {
+                return Ok(Some(wire));
+            }
+            let finite: TractResult<TVec<usize>> =
+                shape.iter().map(|d| Ok(d.to_integer()? as usize)).collect();
+            if let Ok(shape) = finite {
+                let op = IntoShape::new(shape);
+                return Ok(Some(patch.wire_node(format!("{}-IntoShape", name), op, &[wire])?[0]));
+            }
+            let mut spec = tvec!();
+            for (ix, dim) in shape.iter().enumerate() {
+                spec.push(if let Ok(d) = dim.to_integer() {
+                    d as i64
+                } else if input.get(ix) == Some(dim) {
+                    0
+                } else if !spec.contains(&-1) {
+                    -1
+                } else {
+                    return Ok(None);
+                })
+            }
+            let spec = patch.add_const(format!("{}-shape", name), tensor1(&*spec))?;
+            let op = Reshape::new();
+            Ok(Some(patch.wire_node(format!("{}-Reshape", name), op, &[wire, spec.into()])?[0]))
+        }
+
+        let mut patch = TypedModelPatch::default();
+        let mut prepare = |patch: &mut TypedModelPatch,
+                           input: usize,
+                           labels: &[char],
+                           order: Vec<char>,
+                           shape: TVec<TDim>|
+         -> TractResult<Option<OutletId>> {
+            let name = format!("{}-{}", node.name, if input == 0 { "a" } else { "b" });
+            let mut wire = patch.tap_model(model, node.inputs[input])?;
+            let perm = order
+                .iter()
+                .map(|o| labels.iter().position(|l| l == o).unwrap())
+                .collect::<Vec<_>>();
+            if perm.iter().enumerate().any(|(ix, &p)| ix != p) {
+                wire = patch.wire_node(
+                    format!("{}-PermuteAxes", name),
+                    PermuteAxes::new(Some(perm)),
+                    &[wire],
+                )?[0];
+            }
+            reshape(patch, name, wire, &shape)
+        };
+        let mut a_shape = batch_shape.clone();
+        a_shape.push(product(&m));
+        a_shape.push(product(&k));
+        let a = prepare(
+            &mut patch,
+            0,
+            la,
+            batch.iter().chain(&m).chain(&k).cloned().collect(),
+            a_shape,
+        )?;
+        let a = match a {
+            Some(a) => a,
+            None => return Ok(None),
+        };
+        let mut b_shape = batch_shape.clone();
+        b_shape.push(product(&k));
+        b_shape.push(product(&n));
+        let b = prepare(
+            &mut patch,
+            1,
+            lb,
+            batch.iter().chain(&k).chain(&n).cloned().collect(),
+            b_shape,
+        )?;
+        let b = match b {
+            Some(b) => b,
+            None => return Ok(None),
+        };
+        let mut wire =
+            patch.wire_node(format!("{}-MatMul", node.name), MatMul::default(), &[a, b])?[0];
+        let labels = batch.iter().chain(&m).chain(&n).cloned().collect::<Vec<_>>();
+        let shape = labels.iter().map(|l| dims[l].clone()).collect::<TVec<_>>();
+        wire = match reshape(&mut patch, node.name.clone(), wire, &shape)? {
+            Some(wire) => wire,
+            None => return Ok(None),
+        };
+        let perm =
+            lo.iter().map(|o| labels.iter().position(|l| l == o).unwrap()).collect::<Vec<_>>();
+        if perm.iter().enumerate().any(|(ix, &p)| ix != p) {
+            wire = patch.wire_node(
+                format!("{}-PermuteAxes", node.name),
+                PermuteAxes::new(Some(perm)),
+                &[wire],
+            )?[0];
+        }
+        patch.shunt_outside(OutletId::new(node.id, 0), wire)?;
+        Ok(Some(patch))
+    }
+}
+
+impl Op for Einsum {
+    fn name(&self) -> Cow<str> {
+        "Einsum".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![self.equation()])
+    }
+
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        self.lower_to_mat_mul(model, node)
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for Einsum {
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let output = dispatch_numbers!(Self::eval_t(inputs[0].datum_type())(self, &*inputs))?;
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl InferenceRulesOp for Einsum {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, self.inputs.len())?;
+        check_output_arity(&outputs, 1)?;
+        for input in inputs {
+            s.equals(&input.datum_type, &outputs[0].datum_type)?;
+        }
+        s.given_all(inputs.iter().map(|i| &i.rank), move |s, ranks| {
+            let ranks = ranks.iter().map(|&r| r as usize).collect::<Vec<_>>();
+            let resolved = self.resolve(&ranks)?;
+            s.equals(&outputs[0].rank, resolved.output.len() as i32)?;
+            for label in resolved.labels() {
+                let mut dims = vec![];
+                for (ix, term) in resolved.inputs.iter().enumerate() {
+                    for axis in term.iter().positions(|&l| l == label) {
+                        dims.push(inputs[ix].shape[axis].bex());
+                    }
+                }
+                if let Some(axis) = resolved.output.iter().position(|&l| l == label) {
+                    dims.push(outputs[0].shape[axis].bex());
+                }
+                s.equals_all(dims)?;
+            }
+            Ok(())
+        })
+    }
+
+    inference_op_as_op!();
+    to_typed!();
+}
+
+impl TypedOp for Einsum {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        let shapes = inputs.iter().map(|i| i.shape.to_tvec()).collect::<Vec<_>>();
+        let shapes = shapes.iter().map(|s| &**s).collect::<Vec<_>>();
+        let resolved = self.resolve(&*shapes.iter().map(|s| s.len()).collect::<Vec<_>>())?;
+        let shape = resolved.output_shape(&*shapes)?;
+        Ok(tvec!(TypedTensorInfo::dt_shape(inputs[0].datum_type, &*shape)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::math::mat_mul::MatMulUnary;
+
+    #[test]
+    fn parse() {
+        let op = Einsum::parse("...ij, ...jk -> ...ik").unwrap();
+        assert_eq!(op.inputs, vec!(vec!('.', 'i', 'j'), vec!('.', 'j', 'k')));
+        assert_eq!(op.output, Some(vec!('.', 'i', 'k')));
+        assert_eq!(op.equation(), "...ij,...jk->...ik");
+        assert!(Einsum::parse("i.j->ij").is_err());
+        assert!(Einsum::parse("ij->ii").is_err());
+    }
+
+    #[test]
+    fn eval() {
+        let a = rctensor2(&[[1i32, 2], [3, 4]]);
+        let b = rctensor2(&[[5i32, 6], [7, 8]]);
+        let eval = |eq: &str, inputs: TVec<Arc<Tensor>>| {
+            Einsum::parse(eq).unwrap().eval(inputs).unwrap().remove(0)
+        };
+        assert_eq!(eval("ij,jk", tvec!(a.clone(), b.clone())), rctensor2(&[[19, 22], [43, 50]]));
+        assert_eq!(
+            eval("ij,jk->ki", tvec!(a.clone(), b.clone())),
+            rctensor2(&[[19, 43], [22, 50]])
+        );
+        assert_eq!(eval("ii", tvec!(a.clone())), rctensor0(5));
+        assert_eq!(eval("ii->i", tvec!(a.clone())), rctensor1(&[1, 4]));
+        assert_eq!(eval("ij->", tvec!(a.clone())), rctensor0(10));
+        assert_eq!(eval("i,i", tvec!(rctensor1(&[1i32, 2]), rctensor1(&[3i32, 4]))), rctensor0(11));
+        assert_eq!(eval("...j,j", tvec!(a.clone(), rctensor1(&[1i32, 10]))), rctensor1(&[21, 43]));
+    }
+
+    #[test]
+    fn lower_to_mat_mul() {
+        let a =
+            Tensor::from(Array::from_shape_fn((2, 3, 4), |(b, i, j)| (b * 12 + i * 4 + j) as f32));
+        let b = Tensor::from(Array::from_shape_fn((5, 4), |(k, j)| (k * 4 + j) as f32 / 10.0));
+        let op = Einsum::parse("bij,kj->bki").unwrap();
+        let expected = op.eval(tvec!(a.clone().into(), b.clone().into())).unwrap();
+
+        let mut model = TypedModel::default();
+        let fact = TypedTensorInfo::dt_shape(f32::datum_type(), [2usize, 3, 4].as_ref()).unwrap();
+        let source = OutletId::new(model.add_source("a", fact).unwrap(), 0);
+        let b = model.add_const("b", b).unwrap();
+        model.wire_node("einsum", op, &[source, OutletId::new(b, 0)]).unwrap();
+        model.auto_outputs().unwrap();
+        let model = model.declutter().unwrap();
+        assert!(model.nodes().iter().all(|n| n.op_as::<Einsum>().is_none()));
+        assert!(model.nodes().iter().any(|n| n.op_is::<MatMulUnary>()));
+        let found = SimplePlan::new(&model).unwrap().run(tvec!(a)).unwrap();
+        found[0].close_enough(&expected[0], true).unwrap();
+    }
+
+    #[test]
+    fn lower_to_mat_mul_with_symbolic_dims() {
+        let n = TDim::sym('N');
+        let konst = Tensor::from(Array::from_shape_fn((5, 4), |(k, j)| (k * 4 + j) as f32 / 10.0));
+        for (equation, a_shape, b_shape) in &[
+            ("bij,kj->bki", tvec!(n.clone(), 3.to_dim(), 4.to_dim()), None),
+            (
+                "bcij,bjk->bcik",
+                tvec!(n.clone(), 2.to_dim(), 3.to_dim(), 4.to_dim()),
+                Some(tvec!(n.clone(), 4.to_dim(), 2.to_dim())),
+            ),
+            (
+                "ijb,jk->bik",
+                tvec!(3.to_dim(), 4.to_dim(), n.clone()),
+                Some(tvec!(4.to_dim(), 2.to_dim())),
+            ),
+        ] {
+            let op = Einsum::parse(equation).unwrap();
+            let mut model = TypedModel::default();
+            let fact = |shape: &TVec<TDim>| {
+                TypedTensorInfo::dt_shape(f32::datum_type(), &**shape).unwrap()
+            };
+            let a = OutletId::new(model.add_source("a", fact(a_shape)).unwrap(), 0);
+            let b = match b_shape {
+                Some(shape) => model.add_source("b", fact(shape)).unwrap(),
+                None => model.add_const("b", konst.clone()).unwrap(),
+            };
+            model.wire_node("einsum", op.clone(), &[a, OutletId::new(b, 0)]).unwrap();
+            model.auto_outputs().unwrap();
+            let model = model.declutter().unwrap();
+            assert!(model.nodes().iter().all(|n| n.op_as::<Einsum>().is_none()), "{}", equation);
+            assert!(model.nodes().iter().any(|n| n.op_is::<Reshape>()), "{}", equation);
+
+            let concrete = |shape: &TVec<TDim>| {
+                let shape = shape
+                    .iter()
+                    .map(|d| d.eval_with(&hashmap!('N' => 2)).unwrap() as usize)
+                    .collect::<Vec<_>>();
+                let len = shape.iter().product::<usize>();
+                let data = (0..len).map(|x| x as f32 / 10.0).collect::<Vec<_>>();
+                Tensor::from(Array::from_shape_vec(shape, data).unwrap())
+            };
+            let mut inputs = tvec!(concrete(a_shape));
+            inputs.extend(b_shape.as_ref().map(concrete));
+            let mut op_inputs: TVec<Arc<Tensor>> = inputs.iter().cloned().map(Arc::new).collect();
+            if b_shape.is_none() {
+                op_inputs.push(konst.clone().into());
+            }
+            let expected = op.eval(op_inputs).unwrap();
+            let found = SimplePlan::new(&model).unwrap().run(inputs).unwrap();
+            found[0].close_enough(&expected[0], true).unwrap();
+        }
+    }
+}
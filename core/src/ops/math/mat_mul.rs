@@ -68,7 +68,7 @@ fn eval_t<T: Copy + Datum + LinalgScalar + FloatLike>(
     Ok(c.into_tensor())
 }
 
-fn infer_shapes<D: DimLike>(
+pub fn infer_shapes<D: DimLike>(
     mut ashape: TVec<D>,
     mut bshape: TVec<D>,
     a_trans: bool,
@@ -113,19 +113,19 @@ fn infer_shapes<D: DimLike>(
 }
 
 #[derive(Debug, Clone)]
-struct Geo<T: Copy + Datum + Add + Mul + Zero + FloatLike> {
+pub(crate) struct Geo<T: Copy + Datum + Add + Mul + Zero + FloatLike> {
     m: usize,
     k: usize,
     n: usize,
-    mm: Box<dyn MatMatMul<T>>,
-    a_shape: TVec<usize>,
-    a_trans: bool,
-    b_shape: TVec<usize>,
-    b_trans: bool,
+    pub(crate) mm: Box<dyn MatMatMul<T>>,
+    pub(crate) a_shape: TVec<usize>,
+    pub(crate) a_trans: bool,
+    pub(crate) b_shape: TVec<usize>,
+    pub(crate) b_trans: bool,
     bc_a_shape: TVec<usize>,
     bc_b_shape: TVec<usize>,
     c_shape: TVec<usize>,
-    c_trans: bool,
+    pub(crate) c_trans: bool,
     c_shape_prefix: TVec<usize>,
     a_stride_prefix: TVec<usize>,
     b_stride_prefix: TVec<usize>,
@@ -198,10 +198,11 @@ impl<T: Copy + Datum + Add + Mul + Zero + FloatLike> Geo<T> {
 }
 
 #[derive(Debug, Clone, new, Default)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct MatMul {
-    a_trans: bool,
-    b_trans: bool,
-    c_trans: bool,
+    pub a_trans: bool,
+    pub b_trans: bool,
+    pub c_trans: bool,
 }
 
 impl Op for MatMul {
@@ -309,11 +310,12 @@ impl TypedOp for MatMul {
 }
 
 #[derive(Debug, Clone, new)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct MatMulUnary {
-    a: Arc<Tensor>,
-    a_trans: bool,
-    b_trans: bool,
-    c_trans: bool,
+    pub a: Arc<Tensor>,
+    pub a_trans: bool,
+    pub b_trans: bool,
+    pub c_trans: bool,
 }
 
 impl Op for MatMulUnary {
@@ -444,9 +446,9 @@ where
     T: Copy + Datum + Add + Mul + Zero + FloatLike,
     f32: ::num_traits::AsPrimitive<T>,
 {
-    packed_as: ArrayD<Tensor>,
-    geo: Geo<T>,
-    non_linear: Vec<FusedSpec<T>>,
+    pub(crate) packed_as: ArrayD<Tensor>,
+    pub(crate) geo: Geo<T>,
+    pub(crate) non_linear: Vec<FusedSpec<T>>,
 }
 
 fn new_mat_mul_unary_finite<T>(
@@ -671,4 +673,28 @@ mod test {
         let c_found = op.eval(tvec!(b, a)).unwrap().pop().unwrap();
         c.close_enough(&c_found, true).unwrap();
     }
+
+    #[test]
+    fn bin_f16() {
+        let a =
+            tensor2(&[[0f32, 1.0, 2.0], [3.0, 4.0, 5.0]]).cast_to::<f16>().unwrap().into_owned();
+        let b = tensor2(&[[0f32], [1.0], [2.0]]).cast_to::<f16>().unwrap().into_owned();
+        let c = tensor2(&[[5f32], [14.0]]);
+        let op = MatMul::new(false, false, false);
+        let c_found = op.eval(tvec!(a.into(), b.into())).unwrap().pop().unwrap();
+        assert_eq!(c_found.datum_type(), f16::datum_type());
+        c.close_enough(&c_found, true).unwrap();
+    }
+
+    #[test]
+    fn unary_f16() {
+        let a =
+            tensor2(&[[0f32, 1.0, 2.0], [3.0, 4.0, 5.0]]).cast_to::<f16>().unwrap().into_owned();
+        let b = tensor2(&[[0f32], [1.0], [2.0]]).cast_to::<f16>().unwrap().into_owned();
+        let c = tensor2(&[[5f32], [14.0]]);
+        let op = MatMulUnary::new(a.into_arc_tensor(), false, false, false);
+        let c_found = op.eval(tvec!(b.into())).unwrap().pop().unwrap();
+        assert_eq!(c_found.datum_type(), f16::datum_type());
+        c.close_enough(&c_found, true).unwrap();
+    }
 }
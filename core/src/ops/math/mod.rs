@@ -1,5 +1,7 @@
+pub mod einsum;
 pub mod mat_mul;
 
+pub use self::einsum::Einsum;
 pub use self::mat_mul::MatMul;
 use crate::internal::*;
 use num_traits::AsPrimitive;
@@ -27,6 +29,8 @@ bin_to_super_type!(max, Max, flip:commute,
      [i8, i16, i32, i64, u8, u16] => |c, a, b| *c = *a.max(b));
 bin_to_super_type!(pow, Pow,
      [f32, f64] => |c,a,b| *c = a.powf(*b));
+bin_to_super_type!(squared_difference, SquaredDifference,
+     [f32, f64, i32, i64] => |c,a,b| *c = (a.clone() - b) * (a.clone() - b));
 
 fn flip_sub(_op: &dyn BinMiniOp, t: &Arc<Tensor>) -> Option<UnaryOp> {
     let mut t = t.clone().into_tensor();
@@ -47,6 +51,7 @@ element_map!(Ln, [f16, f32, f64], |x| x.ln());
 element_map!(Sqrt, [f16, f32, f64], |x| x.sqrt());
 element_map!(Recip, [f16, f32], |x| x.recip());
 element_map!(Rsqrt, [f16, f32], |x| x.sqrt().recip());
+element_map!(Square, [f16, f32, f64], |x| x * x);
 
 element_map!(Ceil, [f16, f32, f64], |x| x.ceil());
 element_map!(Floor, [f16, f32, f64], |x| x.floor());
@@ -94,7 +99,10 @@ element_map!(Atan, [f16, f32, f64], |x| x.atan());
 
 element_map!(Cosh, [f16, f32, f64], |x| x.cosh());
 element_map!(Sinh, [f16, f32, f64], |x| x.sinh());
-element_map_inplace!(Tanh, [f32], |xs| <f32 as FloatLike>::tanh().run(xs));
+element_map_inplace!(Tanh, match
+     f16 => { |xs| <f16 as FloatLike>::tanh().run(xs) },
+     f32 => { |xs| <f32 as FloatLike>::tanh().run(xs) }
+);
 element_map!(Acosh, [f16, f32, f64], |x| x.acosh());
 element_map!(Asinh, [f16, f32, f64], |x| x.asinh());
 element_map!(Atanh, [f16, f32, f64], |x| x.atanh());
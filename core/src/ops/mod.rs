@@ -15,6 +15,7 @@ pub mod axis;
 pub mod array;
 pub mod cast;
 pub mod cnn;
+pub mod control_flow;
 pub mod downsample;
 pub mod dummy;
 pub mod identity;
@@ -22,6 +23,7 @@ pub mod konst;
 pub mod logic;
 pub mod math;
 pub mod nn;
+pub mod quant;
 pub mod scan;
 pub mod source;
 pub mod unimpl;
@@ -45,6 +47,18 @@ pub fn check_output_arity(outputs: &[TensorProxy], expected: usize) -> TractResu
     }
 }
 
+/// Resolve a possibly negative axis, counted from the end, for a tensor of
+/// the given rank.
+pub fn resolve_axis(axis: i64, rank: usize) -> TractResult<usize> {
+    if 0 <= axis && axis < rank as i64 {
+        Ok(axis as usize)
+    } else if -(rank as i64) <= axis && axis < 0 {
+        Ok((axis + rank as i64) as usize)
+    } else {
+        bail!("Illegal combination of values for rank and axis: {} and {}", rank, axis)
+    }
+}
+
 /// Level of precision to be expected in implementations comparisons.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Validation {
@@ -70,6 +84,21 @@ pub trait OpState: fmt::Debug + Send + objekt::Clone {
         op: &dyn Op,
         inputs: TVec<Arc<Tensor>>,
     ) -> TractResult<TVec<Arc<Tensor>>>;
+
+    /// Serialize the state, so that a paused session can be resumed later,
+    /// possibly in another process. States opt in by implementing both
+    /// `save_state` and `load_state`.
+    #[cfg(feature = "serialize")]
+    fn save_state(&self) -> TractResult<Vec<u8>> {
+        bail!("{} can not be saved", std::any::type_name::<Self>())
+    }
+
+    /// Restore a state saved by `save_state`.
+    #[cfg(feature = "serialize")]
+    #[allow(unused_variables)]
+    fn load_state(&mut self, data: &[u8]) -> TractResult<()> {
+        bail!("{} can not be loaded", std::any::type_name::<Self>())
+    }
 }
 
 pub trait StatelessOp: Op {
@@ -2,10 +2,11 @@ use crate::internal::*;
 use ndarray::*;
 
 #[derive(Debug, Clone, new, Default)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct ArgMaxMin {
-    max: bool,
-    axis: usize,
-    keepdims: bool,
+    pub max: bool,
+    pub axis: usize,
+    pub keepdims: bool,
 }
 
 impl ArgMaxMin {
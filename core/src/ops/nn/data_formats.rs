@@ -3,6 +3,7 @@ use crate::model::TVec;
 use std::fmt;
 
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub enum DataFormat {
     NCHW,
     NHWC,
@@ -0,0 +1,263 @@
+use crate::internal::*;
+use ndarray::prelude::*;
+
+use super::DataFormat;
+use crate::ops::array::{IntoShape, PermuteAxes};
+
+/// Order of the depth dimension components when it is split in blocks:
+/// depth-column-row (TensorFlow, ONNX default) or column-row-depth (ONNX CRD
+/// mode, equivalent to PyTorch PixelShuffle).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum DepthToSpaceMode {
+    DCR,
+    CRD,
+}
+
+impl Default for DepthToSpaceMode {
+    fn default() -> DepthToSpaceMode {
+        DepthToSpaceMode::DCR
+    }
+}
+
+/// Both ops are a reshape of the 4D input to 6D, an axes permutation and
+/// a reshape to the 4D output.
+struct Rearrangement<D: DimLike> {
+    inner: TVec<D>,
+    permutation: TVec<usize>,
+    output: TVec<D>,
+}
+
+fn eval_t<T: Datum>(input: Arc<Tensor>, r: &Rearrangement<usize>) -> TractResult<Arc<Tensor>> {
+    let input = input.into_tensor().into_array::<T>()?.into_shape(&*r.inner)?;
+    let permuted = input.permuted_axes(&*r.permutation);
+    Ok(ArrayD::from_shape_vec(&*r.output, permuted.iter().cloned().collect())?.into_arc_tensor())
+}
+
+fn declutter_rearrangement(
+    model: &TypedModel,
+    node: &TypedNode,
+    r: Rearrangement<usize>,
+) -> TractResult<Option<TypedModelPatch>> {
+    let mut patch = TypedModelPatch::default();
+    let mut wire = patch.tap_model(model, node.inputs[0])?;
+    wire = patch.wire_node(format!("{}-split", node.name), IntoShape::new(r.inner), &[wire])?[0];
+    wire = patch.wire_node(
+        format!("{}-permute", node.name),
+        PermuteAxes::new(Some(r.permutation.to_vec())),
+        &[wire],
+    )?[0];
+    wire = patch.wire_node(format!("{}-merge", node.name), IntoShape::new(r.output), &[wire])?[0];
+    patch.shunt_outside(OutletId::new(node.id, 0), wire)?;
+    Ok(Some(patch))
+}
+
+macro_rules! rearrangement_op {
+    ($op:ident) => {
+        impl Op for $op {
+            fn name(&self) -> Cow<str> {
+                stringify!($op).into()
+            }
+
+            fn info(&self) -> TractResult<Vec<String>> {
+                Ok(vec![format!("{:?}", self)])
+            }
+
+            fn declutter(
+                &self,
+                model: &TypedModel,
+                node: &TypedNode,
+            ) -> TractResult<Option<TypedModelPatch>> {
+                if let Some(shape) = model.outlet_fact(node.inputs[0])?.shape.as_finite() {
+                    declutter_rearrangement(model, node, self.rearrangement(shape)?)
+                } else {
+                    Ok(None)
+                }
+            }
+
+            op_as_typed_op!();
+        }
+
+        impl StatelessOp for $op {
+            fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+                let input = args_1!(inputs);
+                let r = self.rearrangement(input.shape())?;
+                Ok(tvec!(dispatch_datum!(eval_t(input.datum_type())(input, &r))?))
+            }
+        }
+
+        impl InferenceRulesOp for $op {
+            fn rules<'r, 'p: 'r, 's: 'r>(
+                &'s self,
+                s: &mut Solver<'r>,
+                inputs: &'p [TensorProxy],
+                outputs: &'p [TensorProxy],
+            ) -> InferenceResult {
+                check_input_arity(&inputs, 1)?;
+                check_output_arity(&outputs, 1)?;
+                s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+                s.equals(&inputs[0].rank, 4)?;
+                s.equals(&outputs[0].rank, 4)?;
+                s.given(&inputs[0].shape, move |s, shape| {
+                    s.equals(&outputs[0].shape, self.rearrangement(&*shape)?.output)
+                })
+            }
+
+            inference_op_as_op!();
+            to_typed!();
+        }
+
+        impl TypedOp for $op {
+            typed_op_as_op!();
+
+            fn output_facts(
+                &self,
+                inputs: &[&TypedTensorInfo],
+            ) -> TractResult<TVec<TypedTensorInfo>> {
+                let output = self.rearrangement(&*inputs[0].shape.to_tvec())?.output;
+                Ok(tvec!(TypedTensorInfo::dt_shape(inputs[0].datum_type, &*output)?))
+            }
+        }
+    };
+}
+
+/// Moves blocks of data from the depth (channels) dimension to the spatial
+/// dimensions: a NCHW input becomes N x C/b² x H.b x W.b.
+#[derive(Debug, Clone, new)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct DepthToSpace {
+    pub block_size: usize,
+    pub mode: DepthToSpaceMode,
+    pub data_format: DataFormat,
+}
+
+impl DepthToSpace {
+    fn rearrangement<D: DimLike>(&self, shape: &[D]) -> TractResult<Rearrangement<D>> {
+        if shape.len() != 4 {
+            bail!("DepthToSpace expects a 4D input, got {:?}", shape)
+        }
+        let b = self.block_size;
+        if b == 0 {
+            bail!("DepthToSpace expects a positive block size")
+        }
+        let shape = self.data_format.shape(shape);
+        let (n, c) = (shape.n().clone(), shape.c().clone());
+        let (h, w) = (shape.hw_dims()[0].clone(), shape.hw_dims()[1].clone());
+        if c.clone() % (b * b) != D::zero() {
+            bail!("DepthToSpace expects depth to be a multiple of {}, got {}", b * b, c)
+        }
+        let c = c / (b * b);
+        let output =
+            self.data_format.from_n_c_hw(n.clone(), c.clone(), [h.clone() * b, w.clone() * b]);
+        let b = D::from(b);
+        use self::DepthToSpaceMode::*;
+        use super::DataFormat::*;
+        let (inner, permutation) = match (self.data_format, self.mode) {
+            (NCHW, DCR) => (tvec!(n, b.clone(), b, c, h, w), tvec!(0, 3, 4, 1, 5, 2)),
+            (NCHW, CRD) => (tvec!(n, c, b.clone(), b, h, w), tvec!(0, 1, 4, 2, 5, 3)),
+            (NHWC, DCR) => (tvec!(n, h, w, b.clone(), b, c), tvec!(0, 1, 3, 2, 4, 5)),
+            (NHWC, CRD) => (tvec!(n, h, w, c, b.clone(), b), tvec!(0, 1, 4, 2, 5, 3)),
+        };
+        Ok(Rearrangement { inner, permutation, output: output.shape })
+    }
+}
+
+rearrangement_op!(DepthToSpace);
+
+/// Moves blocks of spatial data to the depth (channels) dimension, in
+/// depth-column-row order: a NCHW input becomes N x C.b² x H/b x W/b.
+#[derive(Debug, Clone, new)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct SpaceToDepth {
+    pub block_size: usize,
+    pub data_format: DataFormat,
+}
+
+impl SpaceToDepth {
+    fn rearrangement<D: DimLike>(&self, shape: &[D]) -> TractResult<Rearrangement<D>> {
+        if shape.len() != 4 {
+            bail!("SpaceToDepth expects a 4D input, got {:?}", shape)
+        }
+        let b = self.block_size;
+        if b == 0 {
+            bail!("SpaceToDepth expects a positive block size")
+        }
+        let shape = self.data_format.shape(shape);
+        let (n, c) = (shape.n().clone(), shape.c().clone());
+        let (h, w) = (shape.hw_dims()[0].clone(), shape.hw_dims()[1].clone());
+        if h.clone() % b != D::zero() || w.clone() % b != D::zero() {
+            bail!("SpaceToDepth expects spatial dims to be multiples of {}, got {:?}", b, shape)
+        }
+        let (h, w) = (h / b, w / b);
+        let output =
+            self.data_format.from_n_c_hw(n.clone(), c.clone() * (b * b), [h.clone(), w.clone()]);
+        let b = D::from(b);
+        let (inner, permutation) = match self.data_format {
+            DataFormat::NCHW => (tvec!(n, c, h, b.clone(), w, b), tvec!(0, 3, 5, 1, 2, 4)),
+            DataFormat::NHWC => (tvec!(n, h, b.clone(), w, b, c), tvec!(0, 1, 3, 2, 4, 5)),
+        };
+        Ok(Rearrangement { inner, permutation, output: output.shape })
+    }
+}
+
+rearrangement_op!(SpaceToDepth);
+
+#[cfg(test)]
+mod tests {
+    use super::DataFormat::*;
+    use super::DepthToSpaceMode::*;
+    use super::*;
+
+    #[test]
+    fn depth_to_space_modes() {
+        let input = Tensor::from(Array::from_shape_vec((1, 8, 1, 1), (0..8).collect()).unwrap());
+        let dcr = DepthToSpace::new(2, DCR, NCHW).eval(tvec!(input.clone().into())).unwrap();
+        assert_eq!(dcr[0], rctensor4(&[[[[0, 2], [4, 6]], [[1, 3], [5, 7]]]]));
+        let crd = DepthToSpace::new(2, CRD, NCHW).eval(tvec!(input.into())).unwrap();
+        assert_eq!(crd[0], rctensor4(&[[[[0, 1], [2, 3]], [[4, 5], [6, 7]]]]));
+    }
+
+    // https://www.tensorflow.org/api_docs/python/tf/nn/depth_to_space
+    #[test]
+    fn depth_to_space_nhwc() {
+        let output =
+            DepthToSpace::new(2, DCR, NHWC).eval(tvec!(rctensor4(&[[[[1, 2, 3, 4]]]]))).unwrap();
+        assert_eq!(output[0], rctensor4(&[[[[1], [2]], [[3], [4]]]]));
+        let back = SpaceToDepth::new(2, NHWC).eval(output).unwrap();
+        assert_eq!(back[0], rctensor4(&[[[[1, 2, 3, 4]]]]));
+    }
+
+    #[test]
+    fn zero_block_size_is_rejected() {
+        let input = rctensor4(&[[[[1, 2, 3, 4]]]]);
+        assert!(DepthToSpace::new(0, DCR, NHWC).eval(tvec!(input.clone())).is_err());
+        assert!(SpaceToDepth::new(0, NHWC).eval(tvec!(input)).is_err());
+    }
+
+    #[test]
+    fn declutter() {
+        let input = Tensor::from(
+            Array::from_shape_vec((2, 8, 2, 4), (0..128).map(|x| x as f32).collect()).unwrap(),
+        );
+        let ops: Vec<Box<dyn TypedOp>> = vec![
+            Box::new(DepthToSpace::new(2, DCR, NCHW)),
+            Box::new(DepthToSpace::new(2, CRD, NCHW)),
+            Box::new(DepthToSpace::new(2, DCR, NHWC)),
+            Box::new(DepthToSpace::new(2, CRD, NHWC)),
+            Box::new(SpaceToDepth::new(2, NCHW)),
+            Box::new(SpaceToDepth::new(2, NHWC)),
+        ];
+        for op in ops {
+            let expected = op.as_stateless().unwrap().eval(tvec!(input.clone().into())).unwrap();
+            let mut model = TypedModel::default();
+            let fact = TypedTensorInfo::dt_shape(f32::datum_type(), input.shape()).unwrap();
+            let source = model.add_source("input", fact).unwrap();
+            model.wire_node("op", op, &[OutletId::new(source, 0)]).unwrap();
+            model.auto_outputs().unwrap();
+            let model = model.declutter().unwrap();
+            assert!(model.nodes().iter().any(|n| n.op_is::<PermuteAxes>()));
+            let found = SimplePlan::new(&model).unwrap().run(tvec!(input.clone())).unwrap();
+            assert_eq!(found[0], expected[0]);
+        }
+    }
+}
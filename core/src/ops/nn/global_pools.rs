@@ -67,7 +67,7 @@ impl TypedOp for GlobalAvgPool {
 
 #[derive(Debug, Clone, new, Default)]
 pub struct GlobalLpPool {
-    p: usize, //    data_is_nhwc: bool, // default is nchw (onnx)
+    pub p: usize, //    data_is_nhwc: bool, // default is nchw (onnx)
 }
 
 impl GlobalLpPool {
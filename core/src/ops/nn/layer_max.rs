@@ -1,8 +1,9 @@
 use crate::internal::*;
 
 #[derive(Debug, Clone, new, Default)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct LayerHardmax {
-    axis: isize,
+    pub axis: isize,
 }
 
 impl LayerHardmax {
@@ -12,8 +13,7 @@ impl LayerHardmax {
     ) -> TractResult<TVec<Arc<Tensor>>> {
         let array = input.into_tensor().into_array::<D>()?;
         let shape = array.shape().to_vec();
-        let axis =
-            if self.axis < 0 { shape.len() as isize + self.axis } else { self.axis } as usize;
+        let axis = resolve_axis(self.axis as i64, shape.len())?;
         let first_dim: usize = array.shape()[0..axis].iter().product();
         let second_dim: usize = array.len() / first_dim;
         let mut array = array.into_shape((first_dim, second_dim))?;
@@ -86,8 +86,9 @@ impl TypedOp for LayerHardmax {
 }
 
 #[derive(Debug, Clone, new, Default)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct LayerLogSoftmax {
-    axis: isize,
+    pub axis: isize,
 }
 
 impl LayerLogSoftmax {
@@ -97,8 +98,7 @@ impl LayerLogSoftmax {
     ) -> TractResult<TVec<Arc<Tensor>>> {
         let array = input.into_tensor().into_array::<D>()?;
         let shape = array.shape().to_vec();
-        let axis =
-            if self.axis < 0 { shape.len() as isize + self.axis } else { self.axis } as usize;
+        let axis = resolve_axis(self.axis as i64, shape.len())?;
         let first_dim: usize = array.shape()[0..axis].iter().product();
         let second_dim: usize = array.len() / first_dim;
         let mut array = array.into_shape((first_dim, second_dim))?;
@@ -168,8 +168,9 @@ impl TypedOp for LayerLogSoftmax {
 }
 
 #[derive(Debug, Clone, new, Default)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct LayerSoftmax {
-    axis: isize,
+    pub axis: isize,
 }
 
 impl LayerSoftmax {
@@ -179,8 +180,7 @@ impl LayerSoftmax {
     ) -> TractResult<TVec<Arc<Tensor>>> {
         let array = input.into_tensor().into_array::<D>()?;
         let shape = array.shape().to_vec();
-        let axis =
-            if self.axis < 0 { shape.len() as isize + self.axis } else { self.axis } as usize;
+        let axis = resolve_axis(self.axis as i64, shape.len())?;
         let first_dim: usize = array.shape()[0..axis].iter().product();
         let second_dim: usize = array.len() / first_dim;
         let mut array = array.into_shape((first_dim, second_dim))?;
@@ -269,7 +269,7 @@ fn pulsify(
     mapping: &HashMap<OutletId, OutletId>,
 ) -> TractResult<TVec<OutletId>> {
     let input_fact = target.outlet_fact(mapping[&node.inputs[0]])?.clone();
-    let axis = if axis < 0 { input_fact.shape.len() as isize + axis } else { axis } as usize;
+    let axis = resolve_axis(axis as i64, input_fact.shape.len())?;
     if input_fact.axis != axis {
         let id = target.add_node(&*node.name, objekt::clone_box(op), tvec!(input_fact))?;
         target.add_edge(mapping[&node.inputs[0]], InletId::new(id, 0))?;
@@ -3,10 +3,10 @@ use ndarray::prelude::*;
 
 #[derive(Debug, Clone, new, Default)]
 pub struct Lrn {
-    alpha: f32,
-    beta: f32,
-    bias: f32,
-    size: usize,
+    pub alpha: f32,
+    pub beta: f32,
+    pub bias: f32,
+    pub size: usize,
 }
 
 impl Lrn {
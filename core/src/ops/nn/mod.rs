@@ -1,22 +1,39 @@
 mod arg_max_min;
 mod data_formats;
+mod depth_to_space;
 mod global_pools;
 mod layer_max;
 mod lrn;
+mod non_max_suppression;
+mod normalization;
 mod reduce;
+mod resize;
+mod top_k;
 
 pub use self::arg_max_min::ArgMaxMin;
 pub use self::data_formats::{BaseDataShape, DataFormat, DataShape};
+pub use self::depth_to_space::{DepthToSpace, DepthToSpaceMode, SpaceToDepth};
 pub use self::global_pools::{GlobalAvgPool, GlobalLpPool, GlobalMaxPool};
 pub use self::layer_max::{LayerHardmax, LayerLogSoftmax, LayerSoftmax};
 pub use self::lrn::Lrn;
-pub use self::reduce::{Reduce, Reducer};
+pub use self::non_max_suppression::{select_boxes, BoxFormat, NonMaxSuppression};
+pub use self::normalization::{
+    InstanceNormalization, LayerNorm, LpNormalization, MeanVarianceNormalization,
+};
+pub use self::reduce::{Reduce, Reducer, TypedReduce};
+pub use self::resize::{
+    CoordTransformer, Interpolator, NearestMode, Resize, ResizeTarget, TypedResize,
+};
+pub use self::top_k::TopK;
 
 use num_traits::AsPrimitive;
 
 element_map!(Softplus, [f32], |x| (x.exp() + 1.0).ln());
 element_map!(Softsign, [f32], |x| x / (x.abs() + 1.0));
-element_map_inplace!(Sigmoid, [f32], |xs| f32::sigmoid().run(xs));
+element_map_inplace!(Sigmoid, match
+     f16 => { |xs| f16::sigmoid().run(xs) },
+     f32 => { |xs| f32::sigmoid().run(xs) }
+);
 
 element_map_with_params!(
     Elu,
@@ -0,0 +1,298 @@
+use crate::internal::*;
+use ndarray::*;
+
+/// Layout of the four coordinates of a box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum BoxFormat {
+    /// [y1, x1, y2, x2], for any pair of diagonal corners.
+    Corners,
+    /// [x_center, y_center, width, height].
+    CenterSize,
+}
+
+impl BoxFormat {
+    /// [y_min, x_min, y_max, x_max]
+    fn corners(&self, b: ArrayView1<f32>) -> [f32; 4] {
+        match self {
+            BoxFormat::Corners => [b[0].min(b[2]), b[1].min(b[3]), b[0].max(b[2]), b[1].max(b[3])],
+            BoxFormat::CenterSize => {
+                let (half_w, half_h) = (b[2] / 2.0, b[3] / 2.0);
+                [b[1] - half_h, b[0] - half_w, b[1] + half_h, b[0] + half_w]
+            }
+        }
+    }
+}
+
+fn iou(a: &[f32; 4], b: &[f32; 4]) -> f32 {
+    let area = |c: &[f32; 4]| (c[2] - c[0]) * (c[3] - c[1]);
+    let (area_a, area_b) = (area(a), area(b));
+    if area_a <= 0.0 || area_b <= 0.0 {
+        return 0.0;
+    }
+    let h = (a[2].min(b[2]) - a[0].max(b[0])).max(0.0);
+    let w = (a[3].min(b[3]) - a[1].max(b[1])).max(0.0);
+    let intersection = h * w;
+    intersection / (area_a + area_b - intersection)
+}
+
+/// Greedy non maximum suppression over [boxes, 4] boxes and their [boxes]
+/// scores. Boxes scoring above `score_threshold` are taken by decreasing
+/// score, and dropped if their IoU with a box already selected is greater
+/// than `iou_threshold`. Returns the indices of at most `max_output` boxes,
+/// in selection order.
+pub fn select_boxes(
+    boxes: ArrayView2<f32>,
+    scores: ArrayView1<f32>,
+    format: BoxFormat,
+    max_output: usize,
+    iou_threshold: f32,
+    score_threshold: Option<f32>,
+) -> Vec<usize> {
+    let mut candidates: Vec<usize> = (0..scores.len())
+        .filter(|&ix| score_threshold.map(|th| scores[ix] > th).unwrap_or(true))
+        .collect();
+    // stable sort: ties are broken by lowest index, NaN scores come last
+    candidates.sort_by(|&a, &b| {
+        scores[a].is_nan().cmp(&scores[b].is_nan()).then_with(|| {
+            scores[b].partial_cmp(&scores[a]).unwrap_or(std::cmp::Ordering::Equal)
+        })
+    });
+    let mut selected: Vec<(usize, [f32; 4])> = vec![];
+    for ix in candidates {
+        if selected.len() >= max_output {
+            break;
+        }
+        let corners = format.corners(boxes.row(ix));
+        if selected.iter().all(|(_, other)| iou(&corners, other) <= iou_threshold) {
+            selected.push((ix, corners));
+        }
+    }
+    selected.into_iter().map(|(ix, _)| ix).collect()
+}
+
+/// ONNX NonMaxSuppression: boxes are [batch, boxes, 4] and scores
+/// [batch, classes, boxes]. Output is [selected, 3] (batch, class, box)
+/// indices.
+///
+/// The number of selected boxes depends on the data: it is left open during
+/// analysis, and typed as a symbol of its own.
+#[derive(Debug, Clone, new)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct NonMaxSuppression {
+    pub box_format: BoxFormat,
+    pub optional_max_output_input: Option<usize>,
+    pub optional_iou_threshold_input: Option<usize>,
+    pub optional_score_threshold_input: Option<usize>,
+    /// Number of selected boxes, picked when translating to a typed model.
+    #[new(default)]
+    pub selected_symbol: Option<char>,
+}
+
+impl NonMaxSuppression {
+    fn scalar<T: Datum>(inputs: &TVec<Arc<Tensor>>, ix: Option<usize>) -> TractResult<Option<T>> {
+        if let Some(ix) = ix {
+            let input = inputs[ix].cast_to::<T>()?;
+            Ok(input.as_slice::<T>()?.get(0).cloned())
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl Op for NonMaxSuppression {
+    fn name(&self) -> Cow<str> {
+        "NonMaxSuppression".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("{:?}", self.box_format)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for NonMaxSuppression {
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let max_output = Self::scalar::<i64>(&inputs, self.optional_max_output_input)?
+            .unwrap_or(0)
+            .max(0) as usize;
+        let iou_threshold =
+            Self::scalar::<f32>(&inputs, self.optional_iou_threshold_input)?.unwrap_or(0.0);
+        let score_threshold = Self::scalar::<f32>(&inputs, self.optional_score_threshold_input)?;
+        let boxes = inputs[0].to_array_view::<f32>()?.into_dimensionality::<Ix3>()?;
+        let scores = inputs[1].to_array_view::<f32>()?.into_dimensionality::<Ix3>()?;
+        if boxes.shape()[0] != scores.shape()[0]
+            || boxes.shape()[1] != scores.shape()[2]
+            || boxes.shape()[2] != 4
+        {
+            bail!("Inconsistent boxes {:?} and scores {:?}", boxes.shape(), scores.shape())
+        }
+        let mut selected: Vec<i64> = vec![];
+        for (batch, (boxes, scores)) in boxes.outer_iter().zip(scores.outer_iter()).enumerate() {
+            for (class, scores) in scores.outer_iter().enumerate() {
+                for ix in select_boxes(
+                    boxes.view(),
+                    scores,
+                    self.box_format,
+                    max_output,
+                    iou_threshold,
+                    score_threshold,
+                ) {
+                    selected.extend(&[batch as i64, class as i64, ix as i64]);
+                }
+            }
+        }
+        let selected = Array2::from_shape_vec((selected.len() / 3, 3), selected)?;
+        Ok(tvec!(selected.into_arc_tensor()))
+    }
+}
+
+impl InferenceRulesOp for NonMaxSuppression {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        let arity = 2 + [
+            self.optional_max_output_input,
+            self.optional_iou_threshold_input,
+            self.optional_score_threshold_input,
+        ]
+        .iter()
+        .filter(|i| i.is_some())
+        .count();
+        check_input_arity(&inputs, arity)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, f32::datum_type())?;
+        s.equals(&inputs[1].datum_type, f32::datum_type())?;
+        s.equals(&inputs[0].rank, 3)?;
+        s.equals(&inputs[1].rank, 3)?;
+        s.equals(&inputs[0].shape[0], &inputs[1].shape[0])?;
+        s.equals(&inputs[0].shape[1], &inputs[1].shape[2])?;
+        s.equals(&inputs[0].shape[2], 4.to_dim())?;
+        s.equals(&outputs[0].datum_type, i64::datum_type())?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&outputs[0].shape[1], 3.to_dim())?;
+        Ok(())
+    }
+
+    inference_op_as_op!();
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let inputs = node.inputs.iter().map(|m| mapping[m]).collect::<TVec<_>>();
+        let op =
+            NonMaxSuppression { selected_symbol: Some(target.unused_symbol()?), ..self.clone() };
+        target.wire_node(&*node.name, op, &*inputs)
+    }
+}
+
+impl TypedOp for NonMaxSuppression {
+    typed_op_as_op!();
+
+    fn output_facts(&self, _inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        let selected = self.selected_symbol.ok_or("NonMaxSuppression needs a symbol")?;
+        Ok(tvec!(TypedTensorInfo::dt_shape(
+            i64::datum_type(),
+            [TDim::sym(selected), 3.to_dim()].as_ref()
+        )?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // from the ONNX test suite
+    fn boxes() -> Arc<Tensor> {
+        rctensor3(&[[
+            [0.0f32, 0.0, 1.0, 1.0],
+            [0.0, 0.1, 1.0, 1.1],
+            [0.0, -0.1, 1.0, 0.9],
+            [0.0, 10.0, 1.0, 11.0],
+            [0.0, 10.1, 1.0, 11.1],
+            [0.0, 100.0, 1.0, 101.0],
+        ]])
+    }
+
+    fn scores() -> Arc<Tensor> {
+        rctensor3(&[[[0.9f32, 0.75, 0.6, 0.95, 0.5, 0.3]]])
+    }
+
+    #[test]
+    fn suppress_by_iou() {
+        let op = NonMaxSuppression::new(BoxFormat::Corners, Some(2), Some(3), None);
+        let result =
+            op.eval(tvec!(boxes(), scores(), rctensor1(&[3i64]), rctensor1(&[0.5f32]))).unwrap();
+        assert_eq!(result[0], rctensor2(&[[0i64, 0, 3], [0, 0, 0], [0, 0, 5]]));
+    }
+
+    #[test]
+    fn nan_scores_come_last() {
+        let scores = rctensor3(&[[[std::f32::NAN, 0.75, 0.6, std::f32::NAN, 0.5, 0.3]]]);
+        let op = NonMaxSuppression::new(BoxFormat::Corners, Some(2), Some(3), None);
+        let result =
+            op.eval(tvec!(boxes(), scores, rctensor1(&[6i64]), rctensor1(&[0.5f32]))).unwrap();
+        assert_eq!(result[0], rctensor2(&[[0i64, 0, 1], [0, 0, 4], [0, 0, 5]]));
+    }
+
+    #[test]
+    fn suppress_by_score_and_flipped_corners() {
+        let boxes = boxes().into_tensor().into_array::<f32>().unwrap();
+        let flipped = Array3::from_shape_fn((1, 6, 4), |(b, i, c)| boxes[[b, i, (c + 2) % 4]]);
+        let op = NonMaxSuppression::new(BoxFormat::Corners, Some(2), Some(3), Some(4));
+        let result = op
+            .eval(tvec!(
+                flipped.into_arc_tensor(),
+                scores(),
+                rctensor1(&[3i64]),
+                rctensor1(&[0.5f32]),
+                rctensor1(&[0.4f32])
+            ))
+            .unwrap();
+        assert_eq!(result[0], rctensor2(&[[0i64, 0, 3], [0, 0, 0]]));
+    }
+
+    #[test]
+    fn center_point_boxes() {
+        let boxes = rctensor3(&[[
+            [0.5f32, 0.5, 1.0, 1.0],
+            [0.5, 0.6, 1.0, 1.0],
+            [0.5, 0.4, 1.0, 1.0],
+            [0.5, 10.5, 1.0, 1.0],
+            [0.5, 10.6, 1.0, 1.0],
+            [0.5, 100.5, 1.0, 1.0],
+        ]]);
+        let op = NonMaxSuppression::new(BoxFormat::CenterSize, Some(2), Some(3), None);
+        let result =
+            op.eval(tvec!(boxes, scores(), rctensor1(&[3i64]), rctensor1(&[0.5f32]))).unwrap();
+        assert_eq!(result[0], rctensor2(&[[0i64, 0, 3], [0, 0, 0], [0, 0, 5]]));
+    }
+
+    #[test]
+    fn selected_symbol_is_not_an_input_one() {
+        let mut model = InferenceModel::default();
+        let a = TDim::sym('a');
+        let fact = |shape| TensorFact::dt_shape(f32::datum_type(), shape);
+        let b = model.add_source("boxes", fact(tvec!(1.to_dim(), a.clone(), 4.to_dim()))).unwrap();
+        let s = model.add_source("scores", fact(tvec!(1.to_dim(), 1.to_dim(), a.clone()))).unwrap();
+        let inputs = [OutletId::new(b, 0), OutletId::new(s, 0)];
+        let op = NonMaxSuppression::new(BoxFormat::Corners, None, None, None);
+        let nms = model.wire_node("nms", op, &inputs).unwrap();
+        model.set_output_outlets(&nms).unwrap();
+        let model = model.into_typed().unwrap();
+        let selected = model.outlet_fact(nms[0]).unwrap().shape.dim(0);
+        assert!(selected.is_symbolic() && selected != a);
+        let outputs = SimplePlan::new(&model)
+            .unwrap()
+            .run(tvec!(boxes().into_tensor(), scores().into_tensor()));
+        assert_eq!(outputs.unwrap()[0].shape(), &[0, 3]);
+    }
+}
@@ -0,0 +1,687 @@
+use crate::internal::*;
+use ndarray::prelude::*;
+use num_traits::{Float, FromPrimitive};
+
+use super::reduce::{Reducer, TypedReduce};
+use crate::ops::array::AddDims;
+use crate::ops::binary::{BinMiniOp, MergeOp, TypedBinOp, UnaryOp};
+use crate::ops::math;
+
+fn resolve_axes(axes: &[i64], rank: usize) -> TractResult<TVec<usize>> {
+    let mut axes = axes.iter().map(|&a| resolve_axis(a, rank)).collect::<TractResult<TVec<_>>>()?;
+    axes.sort();
+    Ok(axes)
+}
+
+/// (x - mean) / sqrt(var + epsilon), mean and variance being computed over
+/// `axes`.
+fn normalize_t<T>(input: Arc<Tensor>, axes: &[usize], epsilon: f32) -> TractResult<ArrayD<T>>
+where
+    T: Datum + Float + FromPrimitive,
+{
+    let mean = Reducer::Mean.reduce(axes, input.clone())?.into_array::<T>()?;
+    let centered = input.to_array_view::<T>()?.to_owned() - &mean;
+    let square = (&centered * &centered).into_arc_tensor();
+    let var = Reducer::Mean.reduce(axes, square)?.into_array::<T>()?;
+    let epsilon = T::from_f32(epsilon).unwrap();
+    Ok(centered / &var.mapv(|v| (v + epsilon).sqrt()))
+}
+
+fn same_as_input_rules<'r, 'p: 'r>(
+    s: &mut Solver<'r>,
+    inputs: &'p [TensorProxy],
+    outputs: &'p [TensorProxy],
+) -> InferenceResult {
+    check_output_arity(&outputs, 1)?;
+    s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+    s.equals(&inputs[0].shape, &outputs[0].shape)?;
+    Ok(())
+}
+
+/// Normalizes the input to zero mean and unit variance over `axes`.
+#[derive(Debug, Clone, new)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct MeanVarianceNormalization {
+    pub axes: TVec<i64>,
+    pub epsilon: f32,
+}
+
+impl Op for MeanVarianceNormalization {
+    fn name(&self) -> Cow<str> {
+        "MeanVarianceNormalization".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axes: {:?} epsilon: {}", self.axes, self.epsilon)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for MeanVarianceNormalization {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let axes = resolve_axes(&self.axes, input.shape().len())?;
+        fn eval_t<T: Datum + Float + FromPrimitive>(
+            input: Arc<Tensor>,
+            axes: &[usize],
+            epsilon: f32,
+        ) -> TractResult<Arc<Tensor>> {
+            Ok(normalize_t::<T>(input, axes, epsilon)?.into_arc_tensor())
+        }
+        Ok(tvec!(dispatch_floatlike!(eval_t(input.datum_type())(input, &*axes, self.epsilon))?))
+    }
+}
+
+impl InferenceRulesOp for MeanVarianceNormalization {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        same_as_input_rules(s, inputs, outputs)
+    }
+
+    inference_op_as_op!();
+    to_typed!();
+}
+
+impl TypedOp for MeanVarianceNormalization {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        Ok(tvec!(TypedTensorInfo::dt_shape(inputs[0].datum_type, inputs[0].shape.clone())?))
+    }
+}
+
+/// Instance normalization of a NC... input: mean and variance are computed
+/// over spatial axes, then per-channel scale and bias are applied.
+#[derive(Debug, Clone, new)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct InstanceNormalization {
+    pub epsilon: f32,
+}
+
+impl InstanceNormalization {
+    fn eval_t<T>(
+        &self,
+        input: Arc<Tensor>,
+        scale: Arc<Tensor>,
+        bias: Arc<Tensor>,
+    ) -> TractResult<Arc<Tensor>>
+    where
+        T: Datum + Float + FromPrimitive,
+    {
+        let rank = input.shape().len();
+        let axes = (2..rank).collect::<TVec<_>>();
+        let mut shape = tvec!(1; rank - 1);
+        shape[0] = scale.len();
+        let scale = scale.to_array_view::<T>()?.into_shape(&*shape)?;
+        let bias = bias.to_array_view::<T>()?.into_shape(&*shape)?;
+        let output = normalize_t::<T>(input, &*axes, self.epsilon)? * &scale + &bias;
+        Ok(output.into_arc_tensor())
+    }
+}
+
+impl Op for InstanceNormalization {
+    fn name(&self) -> Cow<str> {
+        "InstanceNormalization".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("epsilon: {}", self.epsilon)])
+    }
+
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let rank = model.outlet_fact(node.inputs[0])?.shape.rank();
+        let mut patch = TypedModelPatch::default();
+        let mut wire = patch.tap_model(model, node.inputs[0])?;
+        let axes = (2..rank as i64).collect();
+        wire = patch.wire_node(
+            format!("{}-normalize", node.name),
+            MeanVarianceNormalization::new(axes, self.epsilon),
+            &[wire],
+        )?[0];
+        for (ix, mini_op) in
+            [Box::new(math::Mul) as Box<dyn BinMiniOp>, Box::new(math::Add)].iter().enumerate()
+        {
+            let name = format!("{}-{}", node.name, mini_op.name());
+            let param = patch.tap_model(model, node.inputs[ix + 1])?;
+            let param = patch.wire_node(
+                format!("{}-add-dims", name),
+                AddDims::new((1..rank - 1).collect()),
+                &[param],
+            )?[0];
+            wire = patch.wire_node(name, TypedBinOp(mini_op.clone()), &[wire, param])?[0];
+        }
+        patch.shunt_outside(OutletId::new(node.id, 0), wire)?;
+        Ok(Some(patch))
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for InstanceNormalization {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (input, scale, bias) = args_3!(inputs);
+        let output =
+            dispatch_floatlike!(Self::eval_t(input.datum_type())(self, input, scale, bias))?;
+        Ok(tvec!(output))
+    }
+}
+
+impl InferenceRulesOp for InstanceNormalization {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 3)?;
+        for input in &inputs[1..] {
+            s.equals(&input.datum_type, &inputs[0].datum_type)?;
+            s.equals(&input.rank, 1)?;
+            s.equals(&input.shape[0], &inputs[0].shape[1])?;
+        }
+        same_as_input_rules(s, inputs, outputs)
+    }
+
+    inference_op_as_op!();
+    to_typed!();
+}
+
+impl TypedOp for InstanceNormalization {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        Ok(tvec!(TypedTensorInfo::dt_shape(inputs[0].datum_type, inputs[0].shape.clone())?))
+    }
+}
+
+/// Layer normalization: mean and variance are computed over the axes from
+/// `axis` to the last one, then scale and optional bias (with the shape of
+/// these axes) are applied.
+#[derive(Debug, Clone, new)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct LayerNorm {
+    pub axis: i64,
+    pub epsilon: f32,
+}
+
+impl LayerNorm {
+    fn axes(&self, rank: usize) -> TractResult<TVec<i64>> {
+        Ok((resolve_axis(self.axis, rank)? as i64..rank as i64).collect())
+    }
+
+    fn eval_t<T>(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<Arc<Tensor>>
+    where
+        T: Datum + Float + FromPrimitive,
+    {
+        let input = inputs.remove(0);
+        let axes = resolve_axes(&self.axes(input.shape().len())?, input.shape().len())?;
+        let mut output = normalize_t::<T>(input, &*axes, self.epsilon)?;
+        output = output * &inputs[0].to_array_view::<T>()?;
+        if let Some(bias) = inputs.get(1) {
+            output = output + &bias.to_array_view::<T>()?;
+        }
+        Ok(output.into_arc_tensor())
+    }
+}
+
+impl Op for LayerNorm {
+    fn name(&self) -> Cow<str> {
+        "LayerNorm".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {} epsilon: {}", self.axis, self.epsilon)])
+    }
+
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let rank = model.outlet_fact(node.inputs[0])?.shape.rank();
+        let mut patch = TypedModelPatch::default();
+        let mut wire = patch.tap_model(model, node.inputs[0])?;
+        wire = patch.wire_node(
+            format!("{}-normalize", node.name),
+            MeanVarianceNormalization::new(self.axes(rank)?, self.epsilon),
+            &[wire],
+        )?[0];
+        let params = [Box::new(math::Mul) as Box<dyn BinMiniOp>, Box::new(math::Add)];
+        for (mini_op, input) in params.iter().zip(node.inputs[1..].iter()) {
+            let param = patch.tap_model(model, *input)?;
+            wire = patch.wire_node(
+                format!("{}-{}", node.name, mini_op.name()),
+                TypedBinOp(mini_op.clone()),
+                &[wire, param],
+            )?[0];
+        }
+        patch.shunt_outside(OutletId::new(node.id, 0), wire)?;
+        Ok(Some(patch))
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for LayerNorm {
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let output = dispatch_floatlike!(Self::eval_t(inputs[0].datum_type())(self, inputs))?;
+        Ok(tvec!(output))
+    }
+}
+
+impl InferenceRulesOp for LayerNorm {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        if inputs.len() != 2 && inputs.len() != 3 {
+            bail!("LayerNorm expects 2 or 3 inputs, got {}", inputs.len())
+        }
+        s.given(&inputs[0].rank, move |s, rank| {
+            let axis = resolve_axis(self.axis, rank as usize)?;
+            for input in &inputs[1..] {
+                s.equals(&input.datum_type, &inputs[0].datum_type)?;
+                s.equals(&input.rank, rank - axis as i32)?;
+                for ax in axis..rank as usize {
+                    s.equals(&input.shape[ax - axis], &inputs[0].shape[ax])?;
+                }
+            }
+            Ok(())
+        })?;
+        same_as_input_rules(s, inputs, outputs)
+    }
+
+    inference_op_as_op!();
+    to_typed!();
+}
+
+impl TypedOp for LayerNorm {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        Ok(tvec!(TypedTensorInfo::dt_shape(inputs[0].datum_type, inputs[0].shape.clone())?))
+    }
+}
+
+/// Divides the input by its L1 or L2 norm along `axis`.
+#[derive(Debug, Clone, new)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct LpNormalization {
+    pub axis: i64,
+    pub p: usize,
+}
+
+impl LpNormalization {
+    fn eval_t<T>(&self, input: Arc<Tensor>) -> TractResult<Arc<Tensor>>
+    where
+        T: Datum + Float,
+    {
+        let axis = resolve_axis(self.axis, input.shape().len())?;
+        let reducer = match self.p {
+            1 => Reducer::L1,
+            2 => Reducer::L2,
+            p => bail!("LpNormalization expects p to be 1 or 2, got {}", p),
+        };
+        let norm = reducer.reduce(&[axis], input.clone())?.into_array::<T>()?;
+        Ok((input.to_array_view::<T>()?.to_owned() / &norm).into_arc_tensor())
+    }
+}
+
+impl Op for LpNormalization {
+    fn name(&self) -> Cow<str> {
+        "LpNormalization".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis: {} p: {}", self.axis, self.p)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for LpNormalization {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        Ok(tvec!(dispatch_floatlike!(Self::eval_t(input.datum_type())(self, input))?))
+    }
+}
+
+impl InferenceRulesOp for LpNormalization {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        same_as_input_rules(s, inputs, outputs)
+    }
+
+    inference_op_as_op!();
+    to_typed!();
+}
+
+impl TypedOp for LpNormalization {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        Ok(tvec!(TypedTensorInfo::dt_shape(inputs[0].datum_type, inputs[0].shape.clone())?))
+    }
+}
+
+/// Consumers of `outlet`, skipping the ones a patch applied earlier in the
+/// same pass has already left dangling.
+fn successors<'a>(model: &'a TypedModel, outlet: OutletId) -> impl Iterator<Item = &'a TypedNode> {
+    model.node(outlet.node).outputs[outlet.slot]
+        .successors
+        .iter()
+        .map(move |i| model.node(i.node))
+        .filter(move |n| {
+            n.outputs.iter().any(|o| o.successors.len() > 0)
+                || model.outputs.iter().any(|o| o.node == n.id)
+        })
+}
+
+fn bin_op_is<B: BinMiniOp>(node: &TypedNode) -> bool {
+    if let Some(op) = node.op_as::<TypedBinOp>() {
+        op.0.is::<B>()
+    } else if let Some(op) = node.op_as::<MergeOp>() {
+        op.0.is::<B>()
+    } else {
+        false
+    }
+}
+
+fn is_mean_over(node: &TypedNode, axes: &[usize]) -> bool {
+    node.op_as::<TypedReduce>()
+        .map(|op| if let Reducer::Mean = op.reducer { &*op.axes == axes } else { false })
+        .unwrap_or(false)
+}
+
+fn bin_op_on<B: BinMiniOp>(node: &TypedNode, a: OutletId, b: OutletId) -> bool {
+    bin_op_is::<B>(node) && (&*node.inputs == &[a, b] || &*node.inputs == &[b, a])
+}
+
+/// Constant `a` of an `a op input` node.
+fn unary_op_constant<B: BinMiniOp>(node: &TypedNode) -> Option<Arc<Tensor>> {
+    node.op_as::<UnaryOp>().filter(|op| op.mini_op.is::<B>()).map(|op| op.a.clone())
+}
+
+/// Recognizes `x * inv + (offset - mean * inv)`, or `x * inv + -mean * inv`
+/// without offset, `inv` being `rsqrt(var + epsilon)` optionally multiplied
+/// by a scale: the form of tf.nn.batch_normalization. Returns the output
+/// node, the scale and the offset.
+fn match_batch_normalization<'a>(
+    model: &'a TypedModel,
+    x: OutletId,
+    mean: OutletId,
+    rsqrt: OutletId,
+) -> Option<(&'a TypedNode, Option<Arc<Tensor>>, Option<Arc<Tensor>>)> {
+    let mut invs = vec![(rsqrt, None)];
+    for mul in successors(model, rsqrt) {
+        if let Some(scale) = unary_op_constant::<math::Mul>(mul) {
+            invs.push((OutletId::new(mul.id, 0), Some(scale)));
+        }
+    }
+    let neg_means = successors(model, mean)
+        .filter(|n| n.op_is::<math::Neg>())
+        .map(|n| OutletId::new(n.id, 0))
+        .collect::<Vec<_>>();
+    for (inv, scale) in invs {
+        for x_inv in successors(model, inv).filter(|n| bin_op_on::<math::Mul>(n, x, inv)) {
+            let x_inv = OutletId::new(x_inv.id, 0);
+            let mut shifts = vec![];
+            for mean_inv in successors(model, inv) {
+                let mean_inv_outlet = OutletId::new(mean_inv.id, 0);
+                if bin_op_on::<math::Mul>(mean_inv, mean, inv) {
+                    for sub in successors(model, mean_inv_outlet) {
+                        if let Some(offset) = unary_op_constant::<math::Sub>(sub) {
+                            shifts.push((OutletId::new(sub.id, 0), Some(offset)));
+                        }
+                    }
+                } else if neg_means.iter().any(|&neg| bin_op_on::<math::Mul>(mean_inv, neg, inv)) {
+                    shifts.push((mean_inv_outlet, None));
+                }
+            }
+            for (shift, offset) in shifts {
+                if let Some(output) =
+                    successors(model, x_inv).find(|n| bin_op_on::<math::Add>(n, x_inv, shift))
+                {
+                    return Some((output, scale, offset));
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Recognizes `(x - mean(x)) * rsqrt(mean((x - mean(x))²) + epsilon)`, or
+/// its `/ sqrt(...)` variant, as decomposed by TensorFlow, starting from the
+/// `mean` node, and replaces it by a MeanVarianceNormalization. The
+/// tf.nn.batch_normalization form is recognized too, its scale and offset
+/// applied after the normalization.
+pub(crate) fn declutter_mean_variance(
+    model: &TypedModel,
+    mean: &TypedNode,
+    axes: &[usize],
+) -> TractResult<Option<TypedModelPatch>> {
+    let x = mean.inputs[0];
+    let mean_outlet = OutletId::new(mean.id, 0);
+    let squared_differences = successors(model, mean_outlet)
+        .filter(|n| bin_op_is::<math::SquaredDifference>(n) && &*n.inputs == &[x, mean_outlet])
+        .collect::<Vec<_>>();
+    // squares of the centered values, with the centered values when computed
+    let mut squares = squared_differences.iter().map(|&n| (n, None)).collect::<Vec<_>>();
+    for centered in successors(model, mean_outlet) {
+        if !bin_op_is::<math::Sub>(centered) || &*centered.inputs != &[x, mean_outlet] {
+            continue;
+        }
+        let centered_outlet = OutletId::new(centered.id, 0);
+        squares.extend(
+            successors(model, centered_outlet)
+                .filter(|n| {
+                    (bin_op_is::<math::Mul>(n) && &*n.inputs == &[centered_outlet, centered_outlet])
+                        || n.op_is::<math::Square>()
+                })
+                .chain(squared_differences.iter().cloned())
+                .map(|n| (n, Some(centered_outlet))),
+        );
+    }
+    for (square, centered) in squares {
+        for var in successors(model, OutletId::new(square.id, 0)) {
+            if !is_mean_over(var, axes) {
+                continue;
+            }
+            let mut candidates = vec![(0.0, OutletId::new(var.id, 0))];
+            for add in successors(model, OutletId::new(var.id, 0)) {
+                if let Some(op) = add.op_as::<UnaryOp>() {
+                    if op.mini_op.is::<math::Add>() && op.a.len() == 1 {
+                        let epsilon = *op.a.cast_to::<f32>()?.as_slice::<f32>()?.get(0).unwrap();
+                        candidates.push((epsilon, OutletId::new(add.id, 0)));
+                    }
+                }
+            }
+            for (epsilon, var_eps) in candidates {
+                for den in successors(model, var_eps) {
+                    let den_outlet = OutletId::new(den.id, 0);
+                    let found = centered.and_then(|centered| {
+                        successors(model, den_outlet).find(|n| {
+                            if den.op_is::<math::Rsqrt>() {
+                                bin_op_on::<math::Mul>(n, centered, den_outlet)
+                            } else if den.op_is::<math::Sqrt>() && bin_op_is::<math::Div>(n) {
+                                &*n.inputs == &[centered, den_outlet]
+                            } else {
+                                false
+                            }
+                        })
+                    });
+                    let found = found.map(|output| (output, None, None)).or_else(|| {
+                        if den.op_is::<math::Rsqrt>() {
+                            match_batch_normalization(model, x, mean_outlet, den_outlet)
+                        } else {
+                            None
+                        }
+                    });
+                    if let Some((output, scale, offset)) = found {
+                        let mut patch = TypedModelPatch::default();
+                        let tap = patch.tap_model(model, x)?;
+                        let op = MeanVarianceNormalization::new(
+                            axes.iter().map(|&a| a as i64).collect(),
+                            epsilon,
+                        );
+                        let name = &*output.name;
+                        let mut wire = patch.wire_node(name, op, &[tap])?[0];
+                        if let Some(scale) = scale {
+                            let op = math::mul::unary(scale);
+                            wire = patch.wire_node(format!("{}-scale", name), op, &[wire])?[0];
+                        }
+                        if let Some(offset) = offset {
+                            let op = math::add::unary(offset);
+                            wire = patch.wire_node(format!("{}-offset", name), op, &[wire])?[0];
+                        }
+                        patch.shunt_outside(OutletId::new(output.id, 0), wire)?;
+                        return Ok(Some(patch));
+                    }
+                }
+            }
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mean_variance_normalization() {
+        let op = MeanVarianceNormalization::new(tvec!(-1), 0.0);
+        let output = op.eval(tvec!(rctensor2(&[[1f32, 3.0], [2.0, 6.0]]))).unwrap();
+        assert_eq!(output[0], rctensor2(&[[-1f32, 1.0], [-1.0, 1.0]]));
+        let op = LpNormalization::new(0, 2);
+        let output = op.eval(tvec!(rctensor2(&[[3f32, 1.0], [4.0, 0.0]]))).unwrap();
+        output[0].close_enough(&tensor2(&[[0.6f32, 1.0], [0.8, 0.0]]), false).unwrap();
+    }
+
+    #[test]
+    fn instance_and_layer_norm_declutter() {
+        let input = tensor3(&[[[1f32, 3.0], [2.0, 8.0]], [[0.0, 1.0], [5.0, 6.0]]]);
+        let cases: Vec<(Box<dyn TypedOp>, TVec<Tensor>)> = vec![
+            (
+                Box::new(InstanceNormalization::new(1e-5)),
+                tvec!(tensor1(&[2f32, 3.0]), tensor1(&[0.5f32, -1.0])),
+            ),
+            (Box::new(LayerNorm::new(-2, 1e-5)), tvec!(tensor2(&[[1f32, 2.0], [3.0, 4.0]]))),
+        ];
+        for (op, params) in cases {
+            let mut inputs = tvec!(input.clone().into_arc_tensor());
+            inputs.extend(params.iter().map(|p| p.clone().into_arc_tensor()));
+            let expected = op.as_stateless().unwrap().eval(inputs).unwrap();
+
+            let mut model = TypedModel::default();
+            let fact =
+                TypedTensorInfo::dt_shape(f32::datum_type(), [2usize, 2, 2].as_ref()).unwrap();
+            let mut wires = tvec!(OutletId::new(model.add_source("input", fact).unwrap(), 0));
+            for (ix, param) in params.into_iter().enumerate() {
+                wires.push(OutletId::new(
+                    model.add_const(format!("param-{}", ix), param).unwrap(),
+                    0,
+                ));
+            }
+            model.wire_node("norm", op, &wires).unwrap();
+            model.auto_outputs().unwrap();
+            let model = model.declutter().unwrap();
+            assert!(model.nodes().iter().any(|n| n.op_is::<MeanVarianceNormalization>()));
+            let found = SimplePlan::new(&model).unwrap().run(tvec!(input.clone())).unwrap();
+            found[0].close_enough(&expected[0], true).unwrap();
+        }
+    }
+
+    #[test]
+    fn match_decomposed_mean_variance() {
+        let mut model = TypedModel::default();
+        let fact = TypedTensorInfo::dt_shape(f32::datum_type(), [2usize, 3].as_ref()).unwrap();
+        let x = OutletId::new(model.add_source("x", fact).unwrap(), 0);
+        let mean =
+            model.wire_node("mean", TypedReduce::new(tvec!(1), Reducer::Mean), &[x]).unwrap()[0];
+        let centered =
+            model.wire_node("centered", TypedBinOp(Box::new(math::Sub)), &[x, mean]).unwrap()[0];
+        let square = model
+            .wire_node("square", TypedBinOp(Box::new(math::SquaredDifference)), &[x, mean])
+            .unwrap()[0];
+        let var = model
+            .wire_node("var", TypedReduce::new(tvec!(1), Reducer::Mean), &[square])
+            .unwrap()[0];
+        let eps = OutletId::new(model.add_const("eps", tensor0(1e-3f32)).unwrap(), 0);
+        let var_eps =
+            model.wire_node("var_eps", TypedBinOp(Box::new(math::Add)), &[var, eps]).unwrap()[0];
+        let rsqrt = model.wire_node("rsqrt", math::Rsqrt::default(), &[var_eps]).unwrap()[0];
+        model.wire_node("output", TypedBinOp(Box::new(math::Mul)), &[rsqrt, centered]).unwrap();
+        model.auto_outputs().unwrap();
+
+        let input = tensor2(&[[1f32, 2.0, 6.0], [-1.0, 0.0, 1.0]]);
+        let expected = SimplePlan::new(&model).unwrap().run(tvec!(input.clone())).unwrap();
+        let model = model.declutter().unwrap();
+        assert_eq!(model.nodes().len(), 2);
+        let op = model.node(1).op_as::<MeanVarianceNormalization>().unwrap();
+        assert_eq!(op.epsilon, 1e-3);
+        let found = SimplePlan::new(&model).unwrap().run(tvec!(input)).unwrap();
+        found[0].close_enough(&expected[0], true).unwrap();
+    }
+
+    #[test]
+    fn match_batch_normalization() {
+        for &(scale, offset) in &[(true, true), (false, false)] {
+            let mut model = TypedModel::default();
+            let fact = TypedTensorInfo::dt_shape(f32::datum_type(), [2usize, 3].as_ref()).unwrap();
+            let x = OutletId::new(model.add_source("x", fact).unwrap(), 0);
+            let reduce = || TypedReduce::new(tvec!(1), Reducer::Mean);
+            let bin = |op: Box<dyn BinMiniOp>| TypedBinOp(op);
+            let mean = model.wire_node("mean", reduce(), &[x]).unwrap()[0];
+            let square =
+                model.wire_node("square", bin(Box::new(math::SquaredDifference)), &[x, mean]);
+            let var = model.wire_node("var", reduce(), &square.unwrap()).unwrap()[0];
+            let eps = OutletId::new(model.add_const("eps", tensor0(1e-3f32)).unwrap(), 0);
+            let var_eps = model.wire_node("var_eps", bin(Box::new(math::Add)), &[var, eps]);
+            let mut inv = model.wire_node("rsqrt", math::Rsqrt::default(), &var_eps.unwrap());
+            if scale {
+                let scale = model.add_const("scale", tensor1(&[2f32, 1.0, -1.0])).unwrap();
+                let wires = [inv.unwrap()[0], OutletId::new(scale, 0)];
+                inv = model.wire_node("inv", bin(Box::new(math::Mul)), &wires);
+            }
+            let inv = inv.unwrap()[0];
+            let x_inv = model.wire_node("x_inv", bin(Box::new(math::Mul)), &[x, inv]).unwrap()[0];
+            let shift = if offset {
+                let mean_inv = model.wire_node("mean_inv", bin(Box::new(math::Mul)), &[mean, inv]);
+                let offset = model.add_const("offset", tensor1(&[0.5f32, 0.0, 1.0])).unwrap();
+                let wires = [OutletId::new(offset, 0), mean_inv.unwrap()[0]];
+                model.wire_node("shift", bin(Box::new(math::Sub)), &wires).unwrap()[0]
+            } else {
+                let neg = model.wire_node("neg", math::Neg::default(), &[mean]).unwrap()[0];
+                model.wire_node("shift", bin(Box::new(math::Mul)), &[neg, inv]).unwrap()[0]
+            };
+            model.wire_node("output", bin(Box::new(math::Add)), &[x_inv, shift]).unwrap();
+            model.auto_outputs().unwrap();
+
+            let input = tensor2(&[[1f32, 2.0, 6.0], [-1.0, 0.0, 1.0]]);
+            let expected = SimplePlan::new(&model).unwrap().run(tvec!(input.clone())).unwrap();
+            let model = model.declutter().unwrap();
+            assert!(model.node(1).op_is::<MeanVarianceNormalization>());
+            assert_eq!(model.nodes().len(), 2 + scale as usize + offset as usize);
+            let found = SimplePlan::new(&model).unwrap().run(tvec!(input)).unwrap();
+            found[0].close_enough(&expected[0], true).unwrap();
+        }
+    }
+}
@@ -29,6 +29,7 @@ macro_rules! reduce_floatlike {
 }
 
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub enum Reducer {
     L1,
     L2,
@@ -43,7 +44,7 @@ pub enum Reducer {
 }
 
 impl Reducer {
-    fn reduce(&self, axes: &[usize], input: Arc<Tensor>) -> TractResult<Tensor> {
+    pub(crate) fn reduce(&self, axes: &[usize], input: Arc<Tensor>) -> TractResult<Tensor> {
         let dt = input.datum_type();
         match self {
             Reducer::L1 => match dt {
@@ -196,7 +197,7 @@ impl Reduce {
             Some(original_axes) => {
                 let mut ans: Vec<usize> = vec![];
                 for or_ax in original_axes.iter() {
-                    ans.push(Self::resolve_axis(*or_ax, rank).unwrap());
+                    ans.push(resolve_axis(*or_ax, rank).unwrap());
                 }
                 Some(ans)
             }
@@ -223,20 +224,10 @@ impl Reduce {
             .collect()
     }
 
-    fn resolve_axis(axis: i64, rank: usize) -> TractResult<usize> {
-        if 0 <= axis && axis as usize <= rank - 1 {
-            Ok(axis as usize)
-        } else if -(rank as i64) <= axis && axis < 0 {
-            Ok((axis + rank as i64) as usize)
-        } else {
-            bail!("Illegal combination of values for rank and axis: {} and {}", rank, axis)
-        }
-    }
-
     fn resolve_axes(&self, input_rank: usize) -> TractResult<TVec<usize>> {
         let mut axes: TVec<usize> = match self.axes.as_ref() {
             None => Ok((0..input_rank).collect()),
-            Some(axis) => axis.iter().map(|&a| Self::resolve_axis(a, input_rank)).collect(),
+            Some(axis) => axis.iter().map(|&a| resolve_axis(a, input_rank)).collect(),
         }?;
         axes.sort();
         Ok(axes)
@@ -319,9 +310,10 @@ impl InferenceRulesOp for Reduce {
 }
 
 #[derive(Clone, Debug, new)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct TypedReduce {
-    axes: TVec<usize>,
-    reducer: Reducer,
+    pub axes: TVec<usize>,
+    pub reducer: Reducer,
 }
 
 impl Op for TypedReduce {
@@ -332,6 +324,17 @@ impl Op for TypedReduce {
         Ok(vec![format!("axes: {:?}", self.axes)])
     }
     canonic!();
+
+    fn declutter(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        if let Reducer::Mean = self.reducer {
+            return super::normalization::declutter_mean_variance(model, node, &*self.axes);
+        }
+        Ok(None)
+    }
     op_as_typed_op!();
 }
 
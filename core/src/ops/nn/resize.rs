@@ -0,0 +1,535 @@
+use crate::internal::*;
+use ndarray::*;
+use num_traits::{AsPrimitive, Float};
+
+/// Maps output coordinates to input coordinates, along one axis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum CoordTransformer {
+    HalfPixel,
+    PytorchHalfPixel,
+    AlignCorners,
+    Asymmetric,
+    TfHalfPixelForNN,
+}
+
+impl CoordTransformer {
+    pub fn transform(&self, x_out: usize, scale: f32, len_in: usize, len_out: usize) -> f32 {
+        let x_out = x_out as f32;
+        match self {
+            CoordTransformer::HalfPixel => (x_out + 0.5) / scale - 0.5,
+            CoordTransformer::PytorchHalfPixel if len_out > 1 => (x_out + 0.5) / scale - 0.5,
+            CoordTransformer::PytorchHalfPixel => 0.0,
+            CoordTransformer::AlignCorners if len_out > 1 => {
+                x_out * (len_in as f32 - 1.0) / (len_out as f32 - 1.0)
+            }
+            CoordTransformer::AlignCorners => 0.0,
+            CoordTransformer::Asymmetric => x_out / scale,
+            CoordTransformer::TfHalfPixelForNN => (x_out + 0.5) / scale,
+        }
+    }
+}
+
+/// Rounding of input coordinates for nearest interpolation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum NearestMode {
+    RoundPreferFloor,
+    RoundPreferCeil,
+    Floor,
+    Ceil,
+}
+
+impl NearestMode {
+    fn round(&self, x: f32) -> f32 {
+        match self {
+            NearestMode::RoundPreferFloor if x - x.floor() == 0.5 => x.floor(),
+            NearestMode::RoundPreferCeil if x - x.floor() == 0.5 => x.ceil(),
+            NearestMode::RoundPreferFloor | NearestMode::RoundPreferCeil => x.round(),
+            NearestMode::Floor => x.floor(),
+            NearestMode::Ceil => x.ceil(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum Interpolator {
+    Nearest(NearestMode),
+    Linear,
+    /// Cubic convolution, with the given `a` coefficient.
+    Cubic(f32),
+}
+
+impl Interpolator {
+    /// Input indices (clamped to the input edges) and weights contributing
+    /// to the output point mapped at `x` in the input.
+    fn taps(&self, x: f32, len_in: usize) -> TractResult<TVec<(usize, f32)>> {
+        if len_in == 0 {
+            bail!("Can not interpolate over an empty axis")
+        }
+        let clamp = |i: f32| (i.max(0.0) as usize).min(len_in - 1);
+        let taps = match self {
+            Interpolator::Nearest(mode) => tvec!((clamp(mode.round(x)), 1.0)),
+            Interpolator::Linear => {
+                let x0 = x.floor();
+                let t = x - x0;
+                tvec!((clamp(x0), 1.0 - t), (clamp(x0 + 1.0), t))
+            }
+            Interpolator::Cubic(a) => {
+                let a = *a;
+                let x0 = x.floor();
+                let t = x - x0;
+                let far = |d: f32| ((a * d - 5.0 * a) * d + 8.0 * a) * d - 4.0 * a;
+                let near = |d: f32| ((a + 2.0) * d - (a + 3.0)) * d * d + 1.0;
+                tvec!(
+                    (clamp(x0 - 1.0), far(t + 1.0)),
+                    (clamp(x0), near(t)),
+                    (clamp(x0 + 1.0), near(1.0 - t)),
+                    (clamp(x0 + 2.0), far(2.0 - t)),
+                )
+            }
+        };
+        Ok(taps)
+    }
+}
+
+/// Output lengths of the resized axes, as factors of the input lengths, or
+/// as absolute lengths.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum ResizeTarget {
+    Scales(TVec<f32>),
+    Sizes(TVec<usize>),
+}
+
+impl ResizeTarget {
+    fn from_inputs(scales: Option<&Tensor>, sizes: Option<&Tensor>) -> TractResult<ResizeTarget> {
+        if let Some(sizes) = sizes.filter(|s| s.len() > 0) {
+            let sizes = sizes.cast_to::<i64>()?;
+            return Ok(ResizeTarget::Sizes(
+                sizes.as_slice::<i64>()?.iter().map(|&s| s as usize).collect(),
+            ));
+        }
+        if let Some(scales) = scales.filter(|s| s.len() > 0) {
+            let scales = scales.cast_to::<f32>()?;
+            return Ok(ResizeTarget::Scales(scales.as_slice::<f32>()?.into()));
+        }
+        bail!("Resize needs either scales or sizes")
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            ResizeTarget::Scales(s) => s.len(),
+            ResizeTarget::Sizes(s) => s.len(),
+        }
+    }
+
+    /// Output length of the `ix`-th resized axis.
+    fn output_dim(&self, ix: usize, input: &TDim) -> TractResult<TDim> {
+        match self {
+            ResizeTarget::Sizes(sizes) => Ok(sizes[ix].to_dim()),
+            ResizeTarget::Scales(scales) => {
+                let scale = scales[ix];
+                if let Ok(input) = input.to_integer() {
+                    Ok(((input as f32 * scale).floor() as usize).to_dim())
+                } else if scale.fract() == 0.0 {
+                    Ok(input.clone() * scale as i32)
+                } else if (1.0 / scale).fract() == 0.0 {
+                    Ok(input.clone() / (1.0 / scale) as i32)
+                } else {
+                    bail!("Can not scale symbolic dimension {:?} by {}", input, scale)
+                }
+            }
+        }
+    }
+
+    /// Scale used by the coordinate transformation of the `ix`-th resized axis.
+    fn scale(&self, ix: usize, len_in: usize, len_out: usize) -> f32 {
+        match self {
+            ResizeTarget::Scales(scales) => scales[ix],
+            ResizeTarget::Sizes(_) => len_out as f32 / len_in as f32,
+        }
+    }
+}
+
+/// Resampling with scales or sizes as inputs.
+///
+/// When `axes` is not set, scales and sizes cover all the input axes.
+#[derive(Debug, Clone, new)]
+pub struct Resize {
+    pub axes: Option<TVec<usize>>,
+    pub coord_transformer: CoordTransformer,
+    pub interpolator: Interpolator,
+    pub optional_scales_input: Option<usize>,
+    pub optional_sizes_input: Option<usize>,
+}
+
+impl Resize {
+    fn typed(&self, rank: usize, target: ResizeTarget) -> TractResult<TypedResize> {
+        let axes: TVec<usize> = self.axes.clone().unwrap_or_else(|| (0..rank).collect());
+        if axes.len() != target.len() {
+            bail!("Resize on axes {:?} got {:?}", axes, target)
+        }
+        Ok(TypedResize::new(axes, target, self.coord_transformer, self.interpolator))
+    }
+}
+
+impl Op for Resize {
+    fn name(&self) -> Cow<str> {
+        "Resize".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("{:?} {:?}", self.coord_transformer, self.interpolator)])
+    }
+
+    not_a_typed_op!();
+}
+
+impl StatelessOp for Resize {
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let target = ResizeTarget::from_inputs(
+            self.optional_scales_input.and_then(|ix| inputs.get(ix)).map(|t| &**t),
+            self.optional_sizes_input.and_then(|ix| inputs.get(ix)).map(|t| &**t),
+        )?;
+        self.typed(inputs[0].shape().len(), target)?.eval(tvec!(inputs[0].clone()))
+    }
+}
+
+impl InferenceRulesOp for Resize {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, &outputs[0].rank)?;
+        let set_shape = move |s: &mut Solver<'r>, ishape: TVec<TDim>, target: ResizeTarget| {
+            let op = self.typed(ishape.len(), target)?;
+            s.equals(&outputs[0].shape, op.output_shape(&*ishape)?)
+        };
+        match (self.optional_scales_input, self.optional_sizes_input) {
+            (Some(scales), Some(sizes)) => {
+                s.given_2(&inputs[0].shape, &inputs[sizes].value, move |s, ishape, sizes| {
+                    if sizes.len() > 0 {
+                        set_shape(s, ishape, ResizeTarget::from_inputs(None, Some(&*sizes))?)
+                    } else {
+                        s.given(&inputs[scales].value, move |s, scales| {
+                            set_shape(
+                                s,
+                                ishape.clone(),
+                                ResizeTarget::from_inputs(Some(&*scales), None)?,
+                            )
+                        })
+                    }
+                })
+            }
+            (scales, sizes) => {
+                let (ix, is_sizes) = match (scales, sizes) {
+                    (_, Some(sizes)) => (sizes, true),
+                    (Some(scales), _) => (scales, false),
+                    _ => bail!("Resize needs either scales or sizes"),
+                };
+                s.given_2(&inputs[0].shape, &inputs[ix].value, move |s, ishape, value| {
+                    let target = if is_sizes {
+                        ResizeTarget::from_inputs(None, Some(&*value))?
+                    } else {
+                        ResizeTarget::from_inputs(Some(&*value), None)?
+                    };
+                    set_shape(s, ishape, target)
+                })
+            }
+        }
+    }
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let konst = |ix: Option<usize>| -> TractResult<Option<Arc<Tensor>>> {
+            match ix {
+                Some(ix) => Ok(target.outlet_fact(mapping[&node.inputs[ix]])?.konst.clone()),
+                None => Ok(None),
+            }
+        };
+        let scales = konst(self.optional_scales_input)?;
+        let sizes = konst(self.optional_sizes_input)?;
+        if scales.is_none() && sizes.is_none() {
+            bail!("Resize scales or sizes input is variable")
+        }
+        let resize_target =
+            ResizeTarget::from_inputs(scales.as_ref().map(|t| &**t), sizes.as_ref().map(|t| &**t))?;
+        let input = mapping[&node.inputs[0]];
+        let op = self.typed(target.outlet_fact(input)?.shape.rank(), resize_target)?;
+        target.wire_node(&*node.name, op, [input].as_ref())
+    }
+
+    inference_op_as_op!();
+}
+
+#[derive(Debug, Clone, new, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct TypedResize {
+    pub axes: TVec<usize>,
+    pub target: ResizeTarget,
+    pub coord_transformer: CoordTransformer,
+    pub interpolator: Interpolator,
+}
+
+impl TypedResize {
+    pub fn output_shape(&self, input: &[TDim]) -> TractResult<TVec<TDim>> {
+        let mut shape: TVec<TDim> = input.into();
+        for (ix, &axis) in self.axes.iter().enumerate() {
+            shape[axis] = self.target.output_dim(ix, &input[axis])?;
+        }
+        Ok(shape)
+    }
+
+    /// For each resized axis, the taps of each output point.
+    fn taps(&self, input_shape: &[usize]) -> TractResult<TVec<(usize, Vec<TVec<(usize, f32)>>)>> {
+        let shape: TVec<TDim> = input_shape.iter().map(|d| d.to_dim()).collect();
+        let output_shape = self.output_shape(&*shape)?;
+        self.axes
+            .iter()
+            .enumerate()
+            .map(|(ix, &axis)| {
+                let len_in = input_shape[axis];
+                let len_out = output_shape[axis].to_integer()? as usize;
+                let scale = self.target.scale(ix, len_in, len_out);
+                let taps = (0..len_out)
+                    .map(|x| {
+                        let x = self.coord_transformer.transform(x, scale, len_in, len_out);
+                        self.interpolator.taps(x, len_in)
+                    })
+                    .collect::<TractResult<_>>()?;
+                Ok((axis, taps))
+            })
+            .collect()
+    }
+
+    fn eval_nearest<T: Datum>(&self, input: &Tensor) -> TractResult<Arc<Tensor>> {
+        let mut data = input.to_array_view::<T>()?.to_owned();
+        for (axis, taps) in self.taps(input.shape())? {
+            let mut shape = data.shape().to_vec();
+            shape[axis] = taps.len();
+            let mut output = ArrayD::<T>::default(&*shape);
+            for (x, taps) in taps.iter().enumerate() {
+                output
+                    .index_axis_mut(Axis(axis), x)
+                    .assign(&data.index_axis(Axis(axis), taps[0].0));
+            }
+            data = output;
+        }
+        Ok(data.into_arc_tensor())
+    }
+
+    fn eval_interpolated<T>(&self, input: &Tensor) -> TractResult<Arc<Tensor>>
+    where
+        T: Datum + Float,
+        f32: AsPrimitive<T>,
+    {
+        let mut data = input.to_array_view::<T>()?.to_owned();
+        for (axis, taps) in self.taps(input.shape())? {
+            let mut shape = data.shape().to_vec();
+            shape[axis] = taps.len();
+            let mut output = ArrayD::<T>::zeros(&*shape);
+            for (x, taps) in taps.iter().enumerate() {
+                let mut output = output.index_axis_mut(Axis(axis), x);
+                for &(ix, weight) in taps {
+                    let weight: T = weight.as_();
+                    Zip::from(&mut output)
+                        .and(&data.index_axis(Axis(axis), ix))
+                        .apply(|o, &i| *o = *o + i * weight);
+                }
+            }
+            data = output;
+        }
+        Ok(data.into_arc_tensor())
+    }
+}
+
+impl Op for TypedResize {
+    fn name(&self) -> Cow<str> {
+        "TypedResize".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![
+            format!("axes: {:?} {:?}", self.axes, self.target),
+            format!("{:?} {:?}", self.coord_transformer, self.interpolator),
+        ])
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for TypedResize {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let output = match (self.interpolator, input.datum_type()) {
+            (Interpolator::Nearest(_), dt) => {
+                dispatch_datum!(Self::eval_nearest(dt)(self, &*input))?
+            }
+            (_, DatumType::F32) => self.eval_interpolated::<f32>(&*input)?,
+            (_, DatumType::F64) => self.eval_interpolated::<f64>(&*input)?,
+            (_, dt) => bail!("{:?} interpolation is not supported for {:?}", self.interpolator, dt),
+        };
+        Ok(tvec!(output))
+    }
+}
+
+impl InferenceRulesOp for TypedResize {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[0].rank, &outputs[0].rank)?;
+        s.given(&inputs[0].shape, move |s, ishape| {
+            s.equals(&outputs[0].shape, self.output_shape(&*ishape)?)
+        })
+    }
+
+    inference_op_as_op!();
+    to_typed!();
+}
+
+impl TypedOp for TypedResize {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        let shape = self.output_shape(&*inputs[0].shape.to_tvec())?;
+        Ok(tvec!(TypedTensorInfo::dt_shape(inputs[0].datum_type, &*shape)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resize(
+        input: Tensor,
+        target: ResizeTarget,
+        coord_transformer: CoordTransformer,
+        interpolator: Interpolator,
+    ) -> Tensor {
+        let axes = (0..input.shape().len()).collect();
+        let op = TypedResize::new(axes, target, coord_transformer, interpolator);
+        op.eval(tvec!(input.into_arc_tensor())).unwrap().remove(0).into_tensor()
+    }
+
+    #[test]
+    fn nearest_upsample_asymmetric_floor() {
+        let output = resize(
+            tensor2(&[[1, 2], [3, 4]]),
+            ResizeTarget::Scales(tvec!(2.0, 3.0)),
+            CoordTransformer::Asymmetric,
+            Interpolator::Nearest(NearestMode::Floor),
+        );
+        assert_eq!(
+            output,
+            tensor2(&[
+                [1, 1, 1, 2, 2, 2],
+                [1, 1, 1, 2, 2, 2],
+                [3, 3, 3, 4, 4, 4],
+                [3, 3, 3, 4, 4, 4]
+            ])
+        );
+    }
+
+    #[test]
+    fn empty_axis() {
+        let op = TypedResize::new(
+            tvec!(0),
+            ResizeTarget::Sizes(tvec!(2)),
+            CoordTransformer::Asymmetric,
+            Interpolator::Linear,
+        );
+        assert!(op.eval(tvec!(rctensor1(&[0f32; 0]))).is_err());
+        let op = TypedResize::new(
+            tvec!(0),
+            ResizeTarget::Sizes(tvec!(0)),
+            CoordTransformer::Asymmetric,
+            Interpolator::Linear,
+        );
+        assert_eq!(op.eval(tvec!(rctensor1(&[0f32; 0]))).unwrap()[0], rctensor1(&[0f32; 0]));
+    }
+
+    #[test]
+    fn linear_half_pixel() {
+        let output = resize(
+            tensor1(&[1f32, 2., 3., 4.]),
+            ResizeTarget::Scales(tvec!(2.0)),
+            CoordTransformer::HalfPixel,
+            Interpolator::Linear,
+        );
+        assert_eq!(output, tensor1(&[1f32, 1.25, 1.75, 2.25, 2.75, 3.25, 3.75, 4.]));
+    }
+
+    #[test]
+    fn linear_align_corners_sizes() {
+        let output = resize(
+            tensor1(&[1f32, 2., 4.]),
+            ResizeTarget::Sizes(tvec!(5)),
+            CoordTransformer::AlignCorners,
+            Interpolator::Linear,
+        );
+        assert_eq!(output, tensor1(&[1f32, 1.5, 2., 3., 4.]));
+    }
+
+    #[test]
+    fn cubic_keeps_constant_and_linear_data() {
+        let output = resize(
+            tensor1(&[3f32, 3., 3., 3.]),
+            ResizeTarget::Scales(tvec!(2.0)),
+            CoordTransformer::HalfPixel,
+            Interpolator::Cubic(-0.75),
+        );
+        output.close_enough(&tensor1(&[3f32; 8]), true).unwrap();
+        let output = resize(
+            tensor1(&[0f32, 1., 2., 3., 4., 5.]),
+            ResizeTarget::Sizes(tvec!(11)),
+            CoordTransformer::AlignCorners,
+            Interpolator::Cubic(-0.5),
+        );
+        // away from the edges, cubic convolution reproduces linear data
+        let output = output.as_slice::<f32>().unwrap();
+        for x in 2..8 {
+            assert!((output[x] - x as f32 * 0.5).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn symbolic_output_shape() {
+        let op = TypedResize::new(
+            tvec!(1),
+            ResizeTarget::Scales(tvec!(2.0)),
+            CoordTransformer::Asymmetric,
+            Interpolator::Nearest(NearestMode::Floor),
+        );
+        assert_eq!(
+            op.output_shape(&[1.to_dim(), TDim::s()]).unwrap(),
+            tvec!(1.to_dim(), TDim::s() * 2)
+        );
+        let op = TypedResize::new(
+            tvec!(1),
+            ResizeTarget::Scales(tvec!(0.5)),
+            CoordTransformer::Asymmetric,
+            Interpolator::Nearest(NearestMode::Floor),
+        );
+        assert_eq!(
+            op.output_shape(&[1.to_dim(), TDim::s()]).unwrap(),
+            tvec!(1.to_dim(), TDim::s() / 2)
+        );
+    }
+}
@@ -0,0 +1,249 @@
+use crate::internal::*;
+use ndarray::*;
+
+/// Largest (or smallest) `k` values along an axis, and their indices.
+///
+/// `k` is the second input. When it is not known during analysis, the output
+/// length along the axis is left open, and typed as a symbol of its own.
+#[derive(Debug, Clone, new)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct TopK {
+    pub axis: i64,
+    pub largest: bool,
+    /// If false, the selected values are kept in input order.
+    pub sorted: bool,
+    pub indices_type: DatumType,
+    /// Output length along the axis when `k` is not a constant, picked when
+    /// translating to a typed model.
+    #[new(default)]
+    pub k_symbol: Option<char>,
+}
+
+/// Total order on a `PartialOrd` type, ranking NaN above every other value
+/// (as numpy and torch do). Plain `partial_cmp` calls a NaN equal to anything,
+/// which is not transitive and leaves the sort result undefined.
+fn nan_is_greatest<T: PartialOrd>(a: &T, b: &T) -> std::cmp::Ordering {
+    a.partial_cmp(b).unwrap_or_else(|| {
+        #[allow(clippy::eq_op)]
+        let is_nan = |x: &T| x != x;
+        is_nan(a).cmp(&is_nan(b))
+    })
+}
+
+impl TopK {
+    fn k(k: &Tensor) -> TractResult<usize> {
+        let k = k.cast_to::<i64>()?;
+        match k.as_slice::<i64>()? {
+            [k] if *k >= 0 => Ok(*k as usize),
+            _ => bail!("TopK expects k to be a single non negative integer, got {:?}", k),
+        }
+    }
+
+    fn eval_t<T: Datum + PartialOrd>(
+        &self,
+        input: Arc<Tensor>,
+        k: usize,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = input.to_array_view::<T>()?;
+        let axis = resolve_axis(self.axis, input.ndim())?;
+        if k > input.shape()[axis] {
+            bail!("TopK: k={} is greater than axis {} length {}", k, axis, input.shape()[axis])
+        }
+        let mut shape: TVec<usize> = input.shape().into();
+        shape[axis] = k;
+        let mut values = unsafe { T::uninitialized_array(&*shape) };
+        let mut indices = ArrayD::<i64>::zeros(&*shape);
+        let mut lane: Vec<(usize, &T)> = vec![];
+        for ((input, mut values), mut indices) in input
+            .lanes(Axis(axis))
+            .into_iter()
+            .zip(values.lanes_mut(Axis(axis)))
+            .zip(indices.lanes_mut(Axis(axis)))
+        {
+            lane.clear();
+            lane.extend(input.into_iter().enumerate());
+            // stable sort: ties are broken by lowest index
+            lane.sort_by(|a, b| {
+                let order = nan_is_greatest(a.1, b.1);
+                if self.largest {
+                    order.reverse()
+                } else {
+                    order
+                }
+            });
+            lane.truncate(k);
+            if !self.sorted {
+                lane.sort_by_key(|pair| pair.0);
+            }
+            for (ix, (index, value)) in lane.iter().enumerate() {
+                values[ix] = (*value).clone();
+                indices[ix] = *index as i64;
+            }
+        }
+        let indices = indices.into_tensor().cast_to_dt(self.indices_type)?.into_owned();
+        Ok(tvec!(values.into_arc_tensor(), indices.into_arc_tensor()))
+    }
+}
+
+impl Op for TopK {
+    fn name(&self) -> Cow<str> {
+        "TopK".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("axis:{} largest:{} sorted:{}", self.axis, self.largest, self.sorted)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for TopK {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (input, k) = args_2!(inputs);
+        let k = Self::k(&k)?;
+        dispatch_numbers!(Self::eval_t(input.datum_type())(self, input, k))
+    }
+}
+
+impl InferenceRulesOp for TopK {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 2)?;
+        check_output_arity(&outputs, 2)?;
+        s.equals(&inputs[0].datum_type, &outputs[0].datum_type)?;
+        s.equals(&outputs[1].datum_type, self.indices_type)?;
+        s.equals(&inputs[0].rank, &outputs[0].rank)?;
+        s.equals(&inputs[0].rank, &outputs[1].rank)?;
+        s.given(&inputs[0].rank, move |s, rank| {
+            let axis = resolve_axis(self.axis, rank as usize)?;
+            for i in 0..rank as usize {
+                if i != axis {
+                    s.equals(&inputs[0].shape[i], &outputs[0].shape[i])?;
+                }
+                s.equals(&outputs[0].shape[i], &outputs[1].shape[i])?;
+            }
+            s.given(&inputs[1].value, move |s, k| {
+                s.equals(&outputs[0].shape[axis], Self::k(&k)?.to_dim())
+            })
+        })
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(2)
+    }
+
+    inference_op_as_op!();
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let inputs = node.inputs.iter().map(|m| mapping[m]).collect::<TVec<_>>();
+        let mut op = self.clone();
+        if target.outlet_fact(inputs[1])?.konst.is_none() {
+            op.k_symbol = Some(target.unused_symbol()?);
+        }
+        target.wire_node(&*node.name, op, &*inputs)
+    }
+}
+
+impl TypedOp for TopK {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        let mut shape = inputs[0].shape.to_tvec();
+        let axis = resolve_axis(self.axis, shape.len())?;
+        shape[axis] = match inputs[1].konst {
+            Some(ref k) => Self::k(k)?.to_dim(),
+            None => TDim::sym(self.k_symbol.ok_or("TopK needs a symbol for a variable k")?),
+        };
+        Ok(tvec!(
+            TypedTensorInfo::dt_shape(inputs[0].datum_type, &*shape)?,
+            TypedTensorInfo::dt_shape(self.indices_type, &*shape)?
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn top_k_largest_and_smallest() {
+        let input: Arc<Tensor> = rctensor2(&[[1.0f32, 4.0, 3.0, 4.0], [0.0, -1.0, 2.0, 1.0]]);
+        let k: Arc<Tensor> = rctensor1(&[2i64]);
+        let op = TopK::new(1, true, true, i64::datum_type());
+        let result = op.eval(tvec!(input.clone(), k.clone())).unwrap();
+        assert_eq!(result[0], rctensor2(&[[4.0f32, 4.0], [2.0, 1.0]]));
+        assert_eq!(result[1], rctensor2(&[[1i64, 3], [2, 3]]));
+        let op = TopK::new(-1, false, true, i32::datum_type());
+        let result = op.eval(tvec!(input.clone(), k.clone())).unwrap();
+        assert_eq!(result[0], rctensor2(&[[1.0f32, 3.0], [-1.0, 0.0]]));
+        assert_eq!(result[1], rctensor2(&[[0i32, 2], [1, 0]]));
+        let op = TopK::new(0, true, false, i64::datum_type());
+        let result = op.eval(tvec!(input, rctensor1(&[1i64]))).unwrap();
+        assert_eq!(result[0], rctensor2(&[[1.0f32, 4.0, 3.0, 4.0]]));
+        assert_eq!(result[1], rctensor2(&[[0i64, 0, 0, 0]]));
+    }
+
+    #[test]
+    fn top_k_nan_ranks_greatest() {
+        let input: Arc<Tensor> = rctensor1(&[1.0f32, std::f32::NAN, 3.0, 2.0, std::f32::NAN]);
+        let k: Arc<Tensor> = rctensor1(&[3i64]);
+        let op = TopK::new(0, true, true, i64::datum_type());
+        let result = op.eval(tvec!(input.clone(), k.clone())).unwrap();
+        assert_eq!(result[1], rctensor1(&[1i64, 4, 2]));
+        let values = result[0].as_slice::<f32>().unwrap();
+        assert!(values[0].is_nan() && values[1].is_nan() && values[2] == 3.0);
+        let op = TopK::new(0, false, true, i64::datum_type());
+        let result = op.eval(tvec!(input, k)).unwrap();
+        assert_eq!(result[0], rctensor1(&[1.0f32, 2.0, 3.0]));
+        assert_eq!(result[1], rctensor1(&[0i64, 3, 2]));
+    }
+
+    #[test]
+    fn top_k_variable_k_is_symbolic() {
+        let mut model = InferenceModel::default();
+        let input = model
+            .add_source("input", TensorFact::dt_shape(f32::datum_type(), shapefact!(3, 10)))
+            .unwrap();
+        let mut outputs = tvec!();
+        for name in &["a", "b"] {
+            let k = TensorFact::dt_shape(i64::datum_type(), shapefact!(1));
+            let k = model.add_source(format!("k-{}", name), k).unwrap();
+            let top_k = model
+                .wire_node(
+                    format!("top_k-{}", name),
+                    TopK::new(1, true, true, i64::datum_type()),
+                    &[OutletId::new(input, 0), OutletId::new(k, 0)],
+                )
+                .unwrap();
+            outputs.push(top_k[0]);
+        }
+        model.set_output_outlets(&outputs).unwrap();
+        let model = model.into_typed().unwrap();
+        let dims = model
+            .output_outlets()
+            .unwrap()
+            .iter()
+            .map(|o| model.outlet_fact(*o).unwrap().shape.to_tvec())
+            .collect::<Vec<_>>();
+        assert_eq!(dims[0][0], 3.to_dim());
+        assert!(dims[0][1].is_symbolic() && dims[1][1].is_symbolic());
+        assert_ne!(dims[0][1], dims[1][1]);
+        let input = Tensor::from(ndarray::Array2::<f32>::zeros((3, 10)));
+        let outputs = SimplePlan::new(&model)
+            .unwrap()
+            .run(tvec!(input, tensor1(&[4i64]), tensor1(&[2i64])))
+            .unwrap();
+        assert_eq!(outputs[0].shape(), &[3, 4]);
+        assert_eq!(outputs[1].shape(), &[3, 2]);
+    }
+}
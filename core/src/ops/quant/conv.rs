@@ -0,0 +1,317 @@
+use crate::internal::*;
+use ndarray::*;
+
+use super::{QConvPacked, QParams};
+use crate::ops::cnn::{PaddingSpec, PatchSpec};
+use crate::ops::nn::DataFormat;
+
+/*
+ * Per batch item and group:
+ *
+ * A: kernel                    B: im2col(input)        C: output
+ *  * O/g rows                   * I/g*h*w rows          * O/g rows
+ *  * I/g*h*w cols               * H*W cols              * H*W cols
+ *
+ * Padded input points are filled with the input zero point.
+ */
+
+/// Integer convolution with a constant OIHW kernel: the kernel is the `a`
+/// operand of the quantization parameters, the input is `b`. The optional
+/// bias is i32, in the accumulator domain.
+#[derive(Debug, Clone, new)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct QConv {
+    pub data_format: DataFormat,
+    pub padding: PaddingSpec,
+    pub dilations: TVec<usize>,
+    pub strides: TVec<usize>,
+    pub group: usize,
+    pub kernel: Arc<Tensor>,
+    pub bias: Option<Arc<Tensor>>,
+    pub params: QParams,
+}
+
+impl QConv {
+    pub fn output_shape<D: DimLike>(&self, ishape: &[D]) -> TVec<D> {
+        let mut result: TVec<D> = ishape.into();
+        let ishape = self.data_format.shape(ishape);
+        let computed = self.padding.compute(
+            ishape.hw_dims(),
+            &self.kernel.shape()[2..],
+            &*self.dilations,
+            &*self.strides,
+        );
+        result[ishape.c_axis()] = self.kernel.shape()[0].into();
+        for (ix, d) in computed.iter().enumerate() {
+            result[ishape.h_axis() + ix] = d.output.clone();
+        }
+        result
+    }
+
+    /// Packs the kernel of each group for a given input shape.
+    pub fn to_packed<TA: QuantizedLike>(
+        &self,
+        input_full_shape: &[usize],
+    ) -> TractResult<QConvPacked<TA>> {
+        let input_shape = self.data_format.shape(TVec::from(input_full_shape));
+        let kernel = self.kernel.to_array_view::<TA>()?;
+        let kernel_spatial_shape = &kernel.shape()[2..];
+        let patch = PatchSpec::for_data_shape(input_shape.clone())
+            .with_kernel_shape(kernel_spatial_shape.into())
+            .with_padding(self.padding.clone())
+            .with_dilations(self.dilations.clone())
+            .with_strides(self.strides.clone())
+            .into_patch();
+        let output_shape =
+            self.data_format.from_n_c_hw(*input_shape.n(), kernel.shape()[0], &*patch.output_shape);
+
+        let ci_per_group = input_shape.c() / self.group;
+        if kernel.shape()[1] != ci_per_group {
+            bail!(
+                "Kernel {:?} does not match input {:?} with {} groups",
+                kernel.shape(),
+                input_shape.shape,
+                self.group
+            )
+        }
+        let kernel_surface = kernel_spatial_shape.iter().product::<usize>();
+        let m = kernel.shape()[0] / self.group;
+        let k = ci_per_group * kernel_surface;
+        let n = patch.output_shape.iter().product::<usize>();
+        let kernel = kernel.into_shape((self.group, m, k))?;
+
+        let mm = TA::qmmm(m, k, n);
+        let mut packed_kernels = vec![];
+        let mut a_row_sums = vec![];
+        for g in 0..self.group {
+            let kernel = kernel.index_axis(Axis(0), g);
+            let mut packed = unsafe {
+                Tensor::uninitialized_aligned::<TA>(&[mm.a_pack().len()], mm.a_pack().alignment())?
+            };
+            mm.a_pack().pack(packed.as_ptr_mut()?, kernel.as_ptr(), k as isize, 1);
+            packed_kernels.push(packed);
+            a_row_sums
+                .push(kernel.outer_iter().map(|row| row.iter().map(|&x| x.as_()).sum()).collect());
+        }
+        Ok(QConvPacked::new(
+            patch,
+            input_shape,
+            output_shape,
+            self.group,
+            packed_kernels,
+            a_row_sums,
+            self.bias.clone(),
+            self.params.clone(),
+            mm,
+        ))
+    }
+
+    fn eval_t<TA: QuantizedLike>(&self, input: &Tensor) -> TractResult<Tensor> {
+        self.to_packed::<TA>(input.shape())?.eval_t(input)
+    }
+}
+
+impl Op for QConv {
+    fn name(&self) -> Cow<str> {
+        "QConv".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![
+            format!("Data format: {:?}", self.data_format),
+            format!(
+                "Kernel shape: {:?} (strides:{:?} dilations:{:?} groups:{}))",
+                self.kernel.shape(),
+                self.strides,
+                self.dilations,
+                self.group
+            ),
+            format!("Padding: {:?}", self.padding),
+            format!("{:?}", self.params),
+        ])
+    }
+
+    fn cost(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<(Cost, TDim)>> {
+        let output_shape = self.output_shape(&*inputs[0].shape.to_tvec());
+        let kernel_surface = self.kernel.shape()[2..].iter().product::<usize>();
+        let ci_per_group = self.kernel.shape()[1];
+        Ok(tvec!((
+            Cost::FMA(inputs[0].datum_type),
+            output_shape.iter().cloned().product::<TDim>() * ci_per_group * kernel_surface
+        )))
+    }
+
+    fn codegen(
+        &self,
+        model: &TypedModel,
+        node: &TypedNode,
+    ) -> TractResult<Option<TypedModelPatch>> {
+        let input_fact = model.outlet_fact(node.inputs[0])?;
+        if let Some(shape) = input_fact.shape.as_finite() {
+            let op: Box<dyn TypedOp> = match self.kernel.datum_type() {
+                DatumType::U8 => Box::new(self.to_packed::<u8>(&*shape)?),
+                DatumType::I8 => Box::new(self.to_packed::<i8>(&*shape)?),
+                dt => bail!("Quantized kernel must be u8 or i8, got {:?}", dt),
+            };
+            return Ok(Some(TypedModelPatch::single_unary_op(model, node, op)?));
+        }
+        Ok(None)
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for QConv {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let output = match self.kernel.datum_type() {
+            DatumType::U8 => self.eval_t::<u8>(&input)?,
+            DatumType::I8 => self.eval_t::<i8>(&input)?,
+            dt => bail!("Quantized kernel must be u8 or i8, got {:?}", dt),
+        };
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl InferenceRulesOp for QConv {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&outputs[0].datum_type, self.params.c_datum_type)?;
+        s.equals(&inputs[0].rank, self.kernel.shape().len() as i32)?;
+        s.equals(&outputs[0].rank, self.kernel.shape().len() as i32)?;
+        s.given(&inputs[0].shape, move |s, ishape| {
+            s.equals(&outputs[0].shape, self.output_shape(&*ishape))
+        })?;
+        Ok(())
+    }
+
+    inference_op_as_op!();
+    to_typed!();
+}
+
+impl TypedOp for QConv {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        Ok(tvec!(TypedTensorInfo::dt_shape(
+            self.params.c_datum_type,
+            &*self.output_shape(&*inputs[0].shape.to_tvec())
+        )?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conv_1d(
+        input: &[u8],
+        kernel: &[u8],
+        padding: PaddingSpec,
+        params: QParams,
+        bias: Option<i32>,
+    ) -> Tensor {
+        let op = QConv::new(
+            DataFormat::NCHW,
+            padding,
+            tvec!(1),
+            tvec!(1),
+            1,
+            Array::from_shape_vec((1, 1, kernel.len()), kernel.to_vec()).unwrap().into_arc_tensor(),
+            bias.map(|b| rctensor1(&[b])),
+            params,
+        );
+        let input = Array::from_shape_vec((1, 1, input.len()), input.to_vec()).unwrap();
+        op.eval(tvec!(input.into_arc_tensor())).unwrap().remove(0).into_tensor()
+    }
+
+    #[test]
+    fn conv_integer_valid() {
+        let output =
+            conv_1d(&[3, 4, 5, 6], &[2, 3], PaddingSpec::Valid, QParams::integer(1, 2), None);
+        assert_eq!(output, tensor3(&[[[1i32 * 1 + 2 * 2, 1 * 2 + 2 * 3, 1 * 3 + 2 * 4]]]));
+    }
+
+    #[test]
+    fn conv_integer_padding_uses_zero_point() {
+        let output = conv_1d(
+            &[3, 4],
+            &[2, 3],
+            PaddingSpec::Explicit(tvec!(1), tvec!(1)),
+            QParams::integer(1, 2),
+            Some(10),
+        );
+        assert_eq!(output, tensor3(&[[[10 + 2 * 1, 10 + 1 * 1 + 2 * 2, 10 + 1 * 2]]]));
+    }
+
+    #[test]
+    fn qlinear_conv() {
+        // accumulators with bias are 11 and 14, scaled by 0.5 (rounding ties
+        // up), then shifted by the output zero point
+        let output = conv_1d(
+            &[3, 4, 5],
+            &[2, 3],
+            PaddingSpec::Valid,
+            QParams::per_tensor(DatumType::U8, 1, 2, 100, 0.5),
+            Some(6),
+        );
+        assert_eq!(output, tensor3(&[[[100u8 + 6, 100 + 7]]]));
+    }
+
+    #[test]
+    fn per_channel() {
+        // real kernel: [[1, 2], [1, 3]], real input: [1, 2, 3]
+        let op = |params| {
+            QConv::new(
+                DataFormat::NCHW,
+                PaddingSpec::Valid,
+                tvec!(1),
+                tvec!(1),
+                1,
+                rctensor3(&[[[2u8, 3]], [[5, 7]]]),
+                None,
+                params,
+            )
+        };
+        let input = rctensor3(&[[[3u8, 4, 5]]]);
+        let output = op(QParams::new(DatumType::I32, tvec!(1, 4), 2, 0, tvec!(1.0)))
+            .eval(tvec!(input.clone()));
+        assert_eq!(*output.unwrap()[0], tensor3(&[[[5i32, 8], [7, 11]]]));
+        let output = op(QParams::new(DatumType::U8, tvec!(1, 4), 2, 100, tvec!(1.0, 0.5)))
+            .eval(tvec!(input));
+        assert_eq!(*output.unwrap()[0], tensor3(&[[[105u8, 108], [104, 106]]]));
+    }
+
+    #[test]
+    fn codegen_packs_kernel() {
+        let op = QConv::new(
+            DataFormat::NCHW,
+            PaddingSpec::Explicit(tvec!(1), tvec!(0)),
+            tvec!(1),
+            tvec!(1),
+            2,
+            rctensor3(&[[[2u8, 3]], [[5, 7]]]),
+            Some(rctensor1(&[10i32, 20])),
+            QParams::new(DatumType::U8, tvec!(1, 4), 2, 100, tvec!(1.0, 0.5)),
+        );
+        let input = tensor3(&[[[3u8, 4, 5], [5, 4, 3]]]);
+        let expected = op.eval(tvec!(input.clone().into_arc_tensor())).unwrap();
+
+        let mut model = TypedModel::default();
+        let fact = TypedTensorInfo::dt_shape(u8::datum_type(), input.shape()).unwrap();
+        let source = model.add_source("input", fact).unwrap();
+        let output_fact = op.output_facts(&[&model.outlet_fact(OutletId::new(source, 0)).unwrap()]);
+        model.chain_after(OutletId::new(source, 0), "conv", op, output_fact.unwrap()).unwrap();
+        model.auto_outputs().unwrap();
+        let model = model.into_optimized().unwrap();
+        assert!(model.node(1).op_is::<QConvPacked<u8>>());
+        let found = SimplePlan::new(&model).unwrap().run(tvec!(input)).unwrap();
+        assert_eq!(found, expected);
+    }
+}
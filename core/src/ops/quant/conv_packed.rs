@@ -0,0 +1,142 @@
+use crate::internal::*;
+use ndarray::*;
+
+use super::QParams;
+use crate::ops::cnn::Patch;
+use crate::ops::nn::DataShape;
+
+use tract_linalg::mmm::QMatMatMul;
+
+/// QConv for a known input shape, with the kernel of each group packed and
+/// its row sums computed once.
+#[derive(Debug, Clone, new)]
+pub struct QConvPacked<TA: QuantizedLike> {
+    pub patch: Patch,
+    pub input_shape: DataShape,
+    pub output_shape: DataShape,
+    pub group: usize,
+    pub packed_kernels: Vec<Tensor>,
+    pub a_row_sums: Vec<Vec<i32>>,
+    pub bias: Option<Arc<Tensor>>,
+    pub params: QParams,
+    pub mm: Box<dyn QMatMatMul<TA, i8>>,
+}
+
+impl<TA: QuantizedLike> QConvPacked<TA> {
+    pub(super) fn eval_t(&self, input: &Tensor) -> TractResult<Tensor> {
+        let (input, zero_point_b) = super::b_as_i8(input, self.params.zero_point_b)?;
+        let params = QParams { zero_point_b, ..self.params.clone() };
+        let (m, k, n) = (self.mm.m(), self.mm.k(), self.mm.n());
+        let ci_per_group = self.input_shape.c() / self.group;
+        let kernel_surface = k / ci_per_group;
+        let bias = self.bias.as_ref().map(|b| b.as_slice::<i32>()).transpose()?;
+
+        let mut pb = unsafe {
+            Tensor::uninitialized_aligned::<i8>(
+                &[self.mm.b_pack().len()],
+                self.mm.b_pack().alignment(),
+            )?
+        };
+        let mut im2col = Array2::<i8>::zeros((k, n));
+        let mut output = ArrayD::<i32>::zeros(&*self.output_shape.shape);
+        let input_c_stride = *self.input_shape.c_stride() as isize;
+
+        for g in 0..self.group {
+            let params = params.rows(m * g, m)?;
+            for i in 0..*self.input_shape.n() {
+                im2col.fill(zero_point_b as i8);
+                unsafe {
+                    let input_i_g = input.as_ptr().offset(
+                        (*self.input_shape.n_stride() * i
+                            + *self.input_shape.c_stride() * ci_per_group * g)
+                            as isize,
+                    );
+                    self.patch.visit_output(|scanner| {
+                        let col = scanner.output_offset as usize;
+                        for (kix, offset) in scanner.valid_offsets_with_indexes() {
+                            for ci in 0..ci_per_group {
+                                im2col[(ci * kernel_surface + kix, col)] =
+                                    *input_i_g.offset(offset + ci as isize * input_c_stride);
+                            }
+                        }
+                    });
+                }
+                let b_col_sums: Vec<i32> = im2col
+                    .gencolumns()
+                    .into_iter()
+                    .map(|col| col.iter().map(|&x| x as i32).sum())
+                    .collect();
+                // the output rows of the group, with all spatial positions
+                // in one strided axis
+                let mut c = unsafe {
+                    ArrayViewMut2::from_shape_ptr(
+                        (m, n).strides((
+                            *self.output_shape.c_stride(),
+                            *self.output_shape.w_stride(),
+                        )),
+                        output.as_mut_ptr().offset(
+                            (*self.output_shape.n_stride() * i
+                                + *self.output_shape.c_stride() * m * g)
+                                as isize,
+                        ),
+                    )
+                };
+                let specs = params.fused_specs(
+                    k,
+                    &self.a_row_sums[g],
+                    &b_col_sums,
+                    bias.map(|b| &b[m * g..][..m]),
+                    &mut c,
+                )?;
+                self.mm.b_pack().pack(pb.as_ptr_mut()?, im2col.as_ptr(), n as isize, 1);
+                unsafe {
+                    self.mm.run_q(
+                        &self.mm.a_from_packed(self.packed_kernels[g].as_ptr()?),
+                        &self.mm.b_from_packed(pb.as_ptr()?),
+                        &mut self.mm.c_from_data_and_strides(
+                            c.as_mut_ptr(),
+                            c.strides()[0],
+                            c.strides()[1],
+                        ),
+                        &specs,
+                    );
+                }
+            }
+        }
+        params.finalize(output)
+    }
+}
+
+impl<TA: QuantizedLike> Op for QConvPacked<TA> {
+    fn name(&self) -> Cow<str> {
+        format!("QConvPacked<{:?}>", TA::datum_type()).into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("{:?}", self.mm), format!("{:?}", self.params)])
+    }
+
+    fn cost(&self, _inputs: &[&TypedTensorInfo]) -> TractResult<TVec<(Cost, TDim)>> {
+        Ok(tvec!((
+            Cost::FMA(TA::datum_type()),
+            (*self.input_shape.n() * self.group * self.mm.m() * self.mm.k() * self.mm.n()).into()
+        )))
+    }
+
+    op_as_typed_op!();
+}
+
+impl<TA: QuantizedLike> StatelessOp for QConvPacked<TA> {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        Ok(tvec!(self.eval_t(&input)?.into_arc_tensor()))
+    }
+}
+
+impl<TA: QuantizedLike> TypedOp for QConvPacked<TA> {
+    typed_op_as_op!();
+
+    fn output_facts(&self, _inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        Ok(tvec!(TypedTensorInfo::dt_shape(self.params.c_datum_type, &*self.output_shape.shape)?))
+    }
+}
@@ -0,0 +1,201 @@
+use crate::internal::*;
+use ndarray::*;
+
+use super::QParams;
+use crate::ops::math::mat_mul::infer_shapes;
+
+/// Integer matrix product, broadcasting like numpy's matmul.
+#[derive(Debug, Clone, new)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct QMatMul {
+    pub params: QParams,
+}
+
+impl QMatMul {
+    fn eval_t<TA: QuantizedLike>(&self, a: &Tensor, b: &Tensor) -> TractResult<Tensor> {
+        let a = a.to_array_view::<TA>()?;
+        let (b, zero_point_b) = super::b_as_i8(b, self.params.zero_point_b)?;
+        let params = QParams { zero_point_b, ..self.params.clone() };
+        let (bc_a_shape, bc_b_shape, c_shape) =
+            infer_shapes(a.shape().into(), b.shape().into(), false, false, false)?;
+        let a = a.into_shape(&*bc_a_shape)?;
+        let b = b.into_shape(&*bc_b_shape)?;
+        let rank = c_shape.len();
+        let (m, k, n) = (c_shape[rank - 2], bc_a_shape[rank - 1], c_shape[rank - 1]);
+        let mm = TA::qmmm(m, k, n);
+        let mut c = ArrayD::<i32>::zeros(&*c_shape);
+
+        let mut pa = unsafe {
+            Tensor::uninitialized_aligned::<TA>(&[mm.a_pack().len()], mm.a_pack().alignment())?
+        };
+        let mut pb = unsafe {
+            Tensor::uninitialized_aligned::<i8>(&[mm.b_pack().len()], mm.b_pack().alignment())?
+        };
+
+        for prefix in indices(&c_shape[..rank - 2]).into_iter() {
+            let mut a = a.view();
+            let mut b = b.view();
+            let mut c = c.view_mut();
+            for (axis, &dim) in prefix.slice().iter().enumerate() {
+                let d = dim.min(a.shape()[axis] - 1);
+                a.slice_axis_inplace(Axis(axis), (d..=d).into());
+                let d = dim.min(b.shape()[axis] - 1);
+                b.slice_axis_inplace(Axis(axis), (d..=d).into());
+                c.slice_axis_inplace(Axis(axis), (dim..=dim).into());
+            }
+            let a = a.into_shape((m, k))?;
+            let b = b.into_shape((k, n))?;
+            let mut c = c.into_shape((m, n))?;
+            let a_row_sums: Vec<i32> =
+                a.outer_iter().map(|row| row.iter().map(|&x| x.as_()).sum()).collect();
+            let b_col_sums: Vec<i32> =
+                b.gencolumns().into_iter().map(|col| col.iter().map(|&x| x as i32).sum()).collect();
+            let specs = params.fused_specs(k, &a_row_sums, &b_col_sums, None, &mut c)?;
+
+            mm.a_pack().pack(pa.as_ptr_mut()?, a.as_ptr(), a.strides()[0], a.strides()[1]);
+            mm.b_pack().pack(pb.as_ptr_mut()?, b.as_ptr(), b.strides()[0], b.strides()[1]);
+            unsafe {
+                mm.run_q(
+                    &mm.a_from_packed(pa.as_ptr()?),
+                    &mm.b_from_packed(pb.as_ptr()?),
+                    &mut mm.c_from_data_and_strides(c.as_mut_ptr(), c.strides()[0], c.strides()[1]),
+                    &specs,
+                );
+            }
+        }
+        params.finalize(c)
+    }
+}
+
+impl Op for QMatMul {
+    fn name(&self) -> Cow<str> {
+        "QMatMul".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("{:?}", self.params)])
+    }
+
+    fn cost(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<(Cost, TDim)>> {
+        let (bc_a_shape, _, bc_c_shape) = infer_shapes(
+            inputs[0].shape.iter().collect(),
+            inputs[1].shape.iter().collect(),
+            false,
+            false,
+            false,
+        )?;
+        let k = bc_a_shape[bc_a_shape.len() - 1].clone();
+        Ok(tvec!((
+            Cost::FMA(inputs[0].datum_type),
+            bc_c_shape.iter().cloned().product::<TDim>() * k
+        )))
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for QMatMul {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (a, b) = args_2!(inputs);
+        let c = match a.datum_type() {
+            DatumType::U8 => self.eval_t::<u8>(&a, &b)?,
+            DatumType::I8 => self.eval_t::<i8>(&a, &b)?,
+            dt => bail!("Quantized operand must be u8 or i8, got {:?}", dt),
+        };
+        Ok(tvec!(c.into_arc_tensor()))
+    }
+}
+
+impl InferenceRulesOp for QMatMul {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 2)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&outputs[0].datum_type, self.params.c_datum_type)?;
+        s.given_2(&inputs[0].shape, &inputs[1].shape, move |s, ashape, bshape| {
+            let (_, _, cshape) = infer_shapes(ashape, bshape, false, false, false)?;
+            s.equals(&outputs[0].shape, cshape)
+        })?;
+        Ok(())
+    }
+
+    inference_op_as_op!();
+    to_typed!();
+}
+
+impl TypedOp for QMatMul {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        Ok(tvec!(TypedTensorInfo::dt_shape(
+            self.params.c_datum_type,
+            &*infer_shapes(
+                inputs[0].shape.to_tvec(),
+                inputs[1].shape.to_tvec(),
+                false,
+                false,
+                false
+            )?
+            .2
+        )?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mat_mul_integer() {
+        let a = rctensor2(&[[11u8, 7, 3], [10, 6, 2], [9, 5, 1], [8, 4, 0]]);
+        let b = rctensor2(&[[1i8, 4], [2, 5], [3, 6]]);
+        let op = QMatMul::new(QParams::integer(12, 0));
+        let c = op.eval(tvec!(a, b)).unwrap();
+        assert_eq!(*c[0], tensor2(&[[-38i32, -83], [-44, -98], [-50, -113], [-56, -128]]));
+    }
+
+    #[test]
+    fn mat_mul_u8_u8_zero_points() {
+        let a = rctensor2(&[[2u8, 3], [4, 5]]);
+        let b = rctensor2(&[[130u8, 127], [129, 128]]);
+        let op = QMatMul::new(QParams::integer(1, 128));
+        let c = op.eval(tvec!(a, b)).unwrap();
+        assert_eq!(*c[0], tensor2(&[[4i32, -1], [10, -3]]));
+    }
+
+    #[test]
+    fn mat_mul_per_row_zero_point() {
+        let a = rctensor2(&[[2u8, 3], [5, 7]]);
+        let b = rctensor2(&[[3i8], [4]]);
+        let op = QMatMul::new(QParams::new(DatumType::I32, tvec!(1, 4), 2, 0, tvec!(1.0)));
+        let c = op.eval(tvec!(a, b)).unwrap();
+        assert_eq!(*c[0], tensor2(&[[5i32], [7]]));
+    }
+
+    #[test]
+    fn qlinear_mat_mul() {
+        // real a: [[1, 2]], real b: [[1], [1]], real c: 3
+        let a = rctensor2(&[[12u8, 14]]);
+        let b = rctensor2(&[[2i8], [2]]);
+        let op = QMatMul::new(QParams::per_tensor(DatumType::U8, 10, 0, 100, 0.5 * 0.5 / 1.0));
+        let c = op.eval(tvec!(a.clone(), b.clone())).unwrap();
+        // 3 / 1.0 + 100
+        assert_eq!(*c[0], tensor2(&[[103u8]]));
+        let op = QMatMul::new(QParams::per_tensor(DatumType::U8, 10, 0, 100, 0.5 * 0.5 / 0.25));
+        let c = op.eval(tvec!(a.clone(), b.clone())).unwrap();
+        // 3 / 0.25 + 100
+        assert_eq!(*c[0], tensor2(&[[112u8]]));
+        let op = QMatMul::new(QParams::per_tensor(DatumType::U8, 10, 0, 100, 0.5 * 0.5 / 0.1));
+        let c = op.eval(tvec!(a.clone(), b.clone())).unwrap();
+        // 3 / 0.1 + 100
+        assert_eq!(*c[0], tensor2(&[[130u8]]));
+        let op = QMatMul::new(QParams::per_tensor(DatumType::U8, 10, 0, 100, 0.5 * 0.5 / 0.01));
+        let c = op.eval(tvec!(a, b)).unwrap();
+        // 3 / 0.01 + 100, saturated
+        assert_eq!(*c[0], tensor2(&[[255u8]]));
+    }
+}
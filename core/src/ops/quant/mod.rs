@@ -0,0 +1,373 @@
+//! Integer quantized operators.
+//!
+//! A quantized value `q` stands for the real value `scale * (q - zero_point)`.
+use num_traits::AsPrimitive;
+
+use crate::internal::*;
+use ndarray::*;
+
+use tract_linalg::mmm::{FusedSpec, QFusedSpec};
+
+mod conv;
+mod conv_packed;
+mod mat_mul;
+
+pub use self::conv::QConv;
+pub use self::conv_packed::QConvPacked;
+pub use self::mat_mul::QMatMul;
+
+/// Rounds to the nearest integer, ties to even.
+pub fn round_ties_to_even(x: f32) -> f32 {
+    let r = x.round();
+    if (r - x).abs() == 0.5 {
+        2.0 * (x / 2.0).round()
+    } else {
+        r
+    }
+}
+
+/// Splits a positive real scale factor into a Q31 fixed point multiplier and
+/// a shift, as expected by `QFusedSpec::PerRowQScale`. A positive shift is
+/// a right shift, a negative one (for scales of 1 and above) a left shift.
+pub fn fixed_point_scale(scale: f32) -> TractResult<(i32, isize)> {
+    if !(scale > 0.0 && scale.is_finite()) {
+        bail!("Quantized scale factor must be positive, got {}", scale)
+    }
+    let mut normalized = scale as f64;
+    let mut shift = 0;
+    while normalized < 0.5 {
+        normalized *= 2.0;
+        shift += 1;
+    }
+    while normalized >= 1.0 {
+        normalized /= 2.0;
+        shift -= 1;
+    }
+    let mut mult = (normalized * (1i64 << 31) as f64).round() as i64;
+    if mult == 1i64 << 31 {
+        mult /= 2;
+        shift -= 1;
+    }
+    Ok((mult as i32, shift))
+}
+
+/// Saturation bounds of an integer datum type.
+pub fn saturation_range(dt: DatumType) -> TractResult<(i32, i32)> {
+    match dt {
+        DatumType::U8 => Ok((0, 255)),
+        DatumType::I8 => Ok((-128, 127)),
+        DatumType::I32 => Ok((std::i32::MIN, std::i32::MAX)),
+        _ => bail!("{:?} is not a quantized type", dt),
+    }
+}
+
+/// Quantization parameters of an integer matrix product or convolution,
+/// `a` and `b` being the operands and `c` the output.
+///
+/// With an i32 output, `c` is the exact product of `a - zero_point_a` by
+/// `b - zero_point_b`. With an 8-bit output, the product is requantized:
+/// multiplied by `scale`, shifted by `zero_point_c` and saturated.
+///
+/// `zero_point_a` and `scale` hold either one value, or one value per row of
+/// `a` (per output channel of a convolution).
+#[derive(Debug, Clone, new)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct QParams {
+    pub c_datum_type: DatumType,
+    pub zero_point_a: TVec<i32>,
+    pub zero_point_b: i32,
+    pub zero_point_c: i32,
+    pub scale: TVec<f32>,
+}
+
+impl QParams {
+    /// Parameters for a plain integer product, with an i32 output.
+    pub fn integer(zero_point_a: i32, zero_point_b: i32) -> QParams {
+        QParams::new(DatumType::I32, tvec!(zero_point_a), zero_point_b, 0, tvec!(1.0))
+    }
+
+    /// Per-tensor parameters of a requantized product.
+    pub fn per_tensor(
+        c_datum_type: DatumType,
+        zero_point_a: i32,
+        zero_point_b: i32,
+        zero_point_c: i32,
+        scale: f32,
+    ) -> QParams {
+        QParams::new(c_datum_type, tvec!(zero_point_a), zero_point_b, zero_point_c, tvec!(scale))
+    }
+
+    /// Parameters of the `len` rows of `a` starting at `start`.
+    pub(crate) fn rows(&self, start: usize, len: usize) -> TractResult<QParams> {
+        fn slice<T: Copy>(values: &TVec<T>, start: usize, len: usize) -> TractResult<TVec<T>> {
+            if values.len() == 1 {
+                Ok(values.clone())
+            } else {
+                Ok(values
+                    .get(start..start + len)
+                    .ok_or_else(|| format!("No parameters for rows {}..{}", start, start + len))?
+                    .into())
+            }
+        }
+        Ok(QParams {
+            zero_point_a: slice(&self.zero_point_a, start, len)?,
+            scale: slice(&self.scale, start, len)?,
+            ..self.clone()
+        })
+    }
+
+    /*
+     * sum((a - za) * (b - zb)) =
+     *     sum(a * b) - zb * rows_sums(a) - za * cols_sums(b) + k * za * zb
+     *
+     * With a per-row za, the za * cols_sums(b) term is an outer product: it
+     * is written to c, which the kernel then adds.
+     */
+    pub(crate) fn fused_specs(
+        &self,
+        k: usize,
+        a_row_sums: &[i32],
+        b_col_sums: &[i32],
+        bias: Option<&[i32]>,
+        c: &mut ArrayViewMut2<i32>,
+    ) -> TractResult<Vec<QFusedSpec>> {
+        let m = a_row_sums.len();
+        let za = per_row(&self.zero_point_a, m, "zero point")?;
+        let zb = self.zero_point_b;
+        let mut specs = vec![];
+        if za.iter().any(|&z| z != 0) || zb != 0 || bias.is_some() {
+            let rows = a_row_sums
+                .iter()
+                .enumerate()
+                .map(|(ix, s)| k as i32 * za[ix] * zb - zb * s + bias.map(|b| b[ix]).unwrap_or(0))
+                .collect();
+            specs.push(FusedSpec::PerRowAdd(rows).into());
+        }
+        if za.iter().any(|&z| z != za[0]) {
+            c.indexed_iter_mut().for_each(|((row, col), x)| *x = -za[row] * b_col_sums[col]);
+            specs.push(FusedSpec::AddC.into());
+        } else if za.iter().any(|&z| z != 0) {
+            specs
+                .push(FusedSpec::PerColAdd(b_col_sums.iter().map(|s| -za[0] * s).collect()).into());
+        }
+        if self.c_datum_type != DatumType::I32 {
+            let (min, max) = saturation_range(self.c_datum_type)?;
+            let (mults, shifts): (Vec<i32>, Vec<isize>) = per_row(&self.scale, m, "scale")?
+                .into_iter()
+                .map(fixed_point_scale)
+                .collect::<TractResult<Vec<_>>>()?
+                .into_iter()
+                .unzip();
+            if shifts.iter().any(|&s| s < 0) {
+                specs.push(QFusedSpec::PerRowQShift(
+                    shifts.iter().map(|&s| (-s).max(0) as i32).collect(),
+                ));
+            }
+            specs.push(QFusedSpec::PerRowQScale(mults));
+            if shifts.iter().any(|&s| s > 0) {
+                specs.push(QFusedSpec::PerRowQShift(
+                    shifts.iter().map(|&s| -s.max(0) as i32).collect(),
+                ));
+            }
+            specs.push(FusedSpec::PerRowAdd(vec![self.zero_point_c; m]).into());
+            specs.push(FusedSpec::Max(min).into());
+            specs.push(FusedSpec::Min(max).into());
+        }
+        Ok(specs)
+    }
+
+    /// Narrows the i32 product to the output type. Values are already
+    /// saturated by the fused specs.
+    pub(crate) fn finalize(&self, c: ArrayD<i32>) -> TractResult<Tensor> {
+        match self.c_datum_type {
+            DatumType::I32 => Ok(c.into_tensor()),
+            DatumType::U8 => Ok(c.mapv(|x| x as u8).into_tensor()),
+            DatumType::I8 => Ok(c.mapv(|x| x as i8).into_tensor()),
+            dt => bail!("{:?} is not a quantized type", dt),
+        }
+    }
+}
+
+/// Broadcasts a per-tensor or per-row parameter to the `m` rows.
+fn per_row<T: Copy>(values: &[T], m: usize, name: &str) -> TractResult<Vec<T>> {
+    if values.len() == m {
+        Ok(values.to_vec())
+    } else if values.len() == 1 {
+        Ok(vec![values[0]; m])
+    } else {
+        bail!("Expected one {} or {} (one per row), got {}", name, m, values.len())
+    }
+}
+
+/// The kernels only accept i8 as their second operand: u8 values (and their
+/// zero point) are shifted by 128.
+pub(crate) fn b_as_i8(b: &Tensor, zero_point_b: i32) -> TractResult<(ArrayD<i8>, i32)> {
+    match b.datum_type() {
+        DatumType::I8 => Ok((b.to_array_view::<i8>()?.to_owned(), zero_point_b)),
+        DatumType::U8 => {
+            Ok((b.to_array_view::<u8>()?.mapv(|x| (x as i32 - 128) as i8), zero_point_b - 128))
+        }
+        dt => bail!("Quantized operand must be u8 or i8, got {:?}", dt),
+    }
+}
+
+/// Converts f32 to u8 or i8, rounding ties to even and saturating.
+#[derive(Debug, Clone, new)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct QuantizeLinear {
+    pub scale: f32,
+    pub zero_point: i32,
+    pub to: DatumType,
+}
+
+impl QuantizeLinear {
+    fn eval_t<T: Datum + Copy>(&self, input: &Tensor) -> TractResult<Tensor>
+    where
+        i32: AsPrimitive<T>,
+    {
+        let (min, max) = saturation_range(self.to)?;
+        let (min, max) = (min as f32, max as f32);
+        let input = input.cast_to::<f32>()?;
+        let output = input.to_array_view::<f32>()?.mapv(|x| {
+            let q = round_ties_to_even(x / self.scale) + self.zero_point as f32;
+            (q.max(min).min(max) as i32).as_()
+        });
+        Ok(output.into_tensor())
+    }
+}
+
+impl Op for QuantizeLinear {
+    fn name(&self) -> Cow<str> {
+        "QuantizeLinear".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for QuantizeLinear {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let output = match self.to {
+            DatumType::U8 => self.eval_t::<u8>(&input)?,
+            DatumType::I8 => self.eval_t::<i8>(&input)?,
+            dt => bail!("QuantizeLinear can not output {:?}", dt),
+        };
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl InferenceRulesOp for QuantizeLinear {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&outputs[0].datum_type, self.to)?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    inference_op_as_op!();
+    to_typed!();
+}
+
+impl TypedOp for QuantizeLinear {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        Ok(tvec!(TypedTensorInfo::dt_shape(self.to, inputs[0].shape.clone())?))
+    }
+}
+
+/// Converts u8, i8 or i32 to f32.
+#[derive(Debug, Clone, new)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct DequantizeLinear {
+    pub scale: f32,
+    pub zero_point: i32,
+}
+
+impl Op for DequantizeLinear {
+    fn name(&self) -> Cow<str> {
+        "DequantizeLinear".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for DequantizeLinear {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let input = input.cast_to::<i32>()?;
+        let output =
+            input.to_array_view::<i32>()?.mapv(|x| (x - self.zero_point) as f32 * self.scale);
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl InferenceRulesOp for DequantizeLinear {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    inference_op_as_op!();
+    to_typed!();
+}
+
+impl TypedOp for DequantizeLinear {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        Ok(tvec!(TypedTensorInfo::dt_shape(f32::datum_type(), inputs[0].shape.clone())?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ties_to_even() {
+        assert_eq!(round_ties_to_even(2.5), 2.0);
+        assert_eq!(round_ties_to_even(3.5), 4.0);
+        assert_eq!(round_ties_to_even(-2.5), -2.0);
+        assert_eq!(round_ties_to_even(2.4), 2.0);
+        assert_eq!(round_ties_to_even(-2.6), -3.0);
+    }
+
+    #[test]
+    fn fixed_point() {
+        assert_eq!(fixed_point_scale(0.5).unwrap(), (1 << 30, 0));
+        assert_eq!(fixed_point_scale(0.125).unwrap(), (1 << 30, 2));
+        assert_eq!(fixed_point_scale(1.0).unwrap(), (1 << 30, -1));
+        assert_eq!(fixed_point_scale(1.5).unwrap(), (3 << 29, -1));
+        assert_eq!(fixed_point_scale(12.0).unwrap(), (3 << 29, -4));
+        assert!(fixed_point_scale(0.0).is_err());
+    }
+
+    #[test]
+    fn quantize_u8() {
+        let op = QuantizeLinear::new(2.0, 128, DatumType::U8);
+        let output = op.eval(tvec!(rctensor1(&[0.0f32, 2.0, 3.0, 5.0, -1000.0, 1000.0]))).unwrap();
+        assert_eq!(*output[0], tensor1(&[128u8, 129, 130, 130, 0, 255]));
+    }
+
+    #[test]
+    fn dequantize_i8() {
+        let op = DequantizeLinear::new(0.5, -2);
+        let output = op.eval(tvec!(rctensor1(&[-2i8, 0, 127]))).unwrap();
+        assert_eq!(*output[0], tensor1(&[0.0f32, 1.0, 64.5]));
+    }
+}
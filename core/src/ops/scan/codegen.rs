@@ -228,6 +228,21 @@ impl OpState for State {
 
         Ok(outputs.into_iter().map(Arc::new).collect())
     }
+
+    #[cfg(feature = "serialize")]
+    fn save_state(&self) -> TractResult<Vec<u8>> {
+        let model_state = self.model_state.save_state()?;
+        Ok(bincode::serialize(&(self.position, &self.hidden_state, model_state))?)
+    }
+
+    #[cfg(feature = "serialize")]
+    fn load_state(&mut self, data: &[u8]) -> TractResult<()> {
+        let (position, hidden_state, model_state): (usize, TVec<Tensor>, Vec<u8>) =
+            bincode::deserialize(data)?;
+        self.position = position;
+        self.hidden_state = hidden_state;
+        self.model_state.load_state(&model_state)
+    }
 }
 
 impl TypedOp for Codegen {
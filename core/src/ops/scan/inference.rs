@@ -62,7 +62,11 @@ impl Inference {
                     full_slot: im.full_slot,
                     full_dim_hint: im.full_dim_hint.clone(),
                     last_value_slot: im.last_value_slot,
-                    chunk: typed_model.input_fact(ix)?.shape.dim(im.axis),
+                    chunk: if im.full_slot.is_some() {
+                        typed_model.output_fact(ix)?.shape.dim(im.axis)
+                    } else {
+                        1.to_dim()
+                    },
                 })
             })
             .collect::<TractResult<_>>()?;
@@ -136,7 +140,14 @@ impl Inference {
                         self.body.output_outlets()?[inner_model_output_ix],
                     ])?;
                     facts.push(&mut inputs[*outer_input_ix]);
-                    Fact::unify_all(&mut facts)?;
+                    // the value changes from one iteration to the next: only
+                    // type and shape are shared
+                    Fact::unify_all(
+                        &mut facts.iter_mut().map(|f| &mut f.datum_type).collect::<TVec<_>>(),
+                    )?;
+                    Fact::unify_all(
+                        &mut facts.iter_mut().map(|f| &mut f.shape).collect::<TVec<_>>(),
+                    )?;
                 }
             }
         }
@@ -233,3 +244,46 @@ impl InferenceOp for Inference {
 
     inference_op_as_op!();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::math;
+
+    // body: (state, x) -> (x + state, state + state), x being scanned by
+    // chunks of 2 and state initialized from a constant
+    #[test]
+    fn full_output_chunk_and_constant_state() {
+        let mut body = InferenceModel::default();
+        let state = OutletId::new(body.add_source_default("state").unwrap(), 0);
+        let fact = TensorFact::dt_shape(f32::datum_type(), tvec!(2usize));
+        let x = OutletId::new(body.add_source("x", fact).unwrap(), 0);
+        let y = body.wire_node("y", math::add::bin(), &[x, state]).unwrap()[0];
+        let next = body.wire_node("next", math::add::bin(), &[state, state]).unwrap()[0];
+        body.set_output_outlets(&[y, next]).unwrap();
+        let scan = Inference::new(
+            body,
+            vec![
+                InputMapping::State { initializer: StateInitializer::FromInput(1) },
+                InputMapping::Scan { slot: 0, axis: 0, chunk: () },
+            ],
+            vec![
+                OutputMapping::new(Some(0), 0, (), None, None, false),
+                OutputMapping::new(None, 0, (), None, Some(1), true),
+            ],
+            None,
+        );
+
+        let mut model = InferenceModel::default();
+        let fact = TensorFact::dt_shape(f32::datum_type(), tvec!(4usize));
+        let x = OutletId::new(model.add_source("x", fact).unwrap(), 0);
+        let init = OutletId::new(model.add_const("init", tensor1(&[10f32])).unwrap(), 0);
+        model.wire_node("scan", scan, &[x, init]).unwrap();
+        model.auto_outputs().unwrap();
+        let model = model.into_typed().unwrap();
+        let plan = SimplePlan::new(&model).unwrap();
+        let result = plan.run(tvec!(tensor1(&[1f32, 2.0, 3.0, 4.0]))).unwrap();
+        assert_eq!(*result[0], tensor1(&[11f32, 12.0, 23.0, 24.0]));
+        assert_eq!(*result[1], tensor1(&[40f32]));
+    }
+}
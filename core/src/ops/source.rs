@@ -12,6 +12,16 @@ impl OpState for SourceState {
     ) -> TractResult<TVec<Arc<Tensor>>> {
         Ok(tvec!(session.inputs[&self.0].clone()))
     }
+
+    #[cfg(feature = "serialize")]
+    fn save_state(&self) -> TractResult<Vec<u8>> {
+        Ok(vec![])
+    }
+
+    #[cfg(feature = "serialize")]
+    fn load_state(&mut self, _data: &[u8]) -> TractResult<()> {
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, new)]
@@ -67,6 +77,7 @@ impl InferenceRulesOp for Source {
 }
 
 #[derive(Debug, Clone, new)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct TypedSource {
     fact: TypedTensorInfo,
 }
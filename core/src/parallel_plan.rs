@@ -0,0 +1,403 @@
+//! A plan running independent nodes of a model at the same time.
+//!
+//! Nodes are evaluated on a thread pool as soon as the nodes they depend on
+//! are done. Stateful nodes run one at a time, in the order of the underlying
+//! `SimplePlan`, as they share the session state.
+use std::borrow::Borrow;
+use std::fmt::{Debug, Display};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::internal::*;
+use crate::model::{ModelImpl, OutletId, TensorInfo};
+use crate::plan::SessionState;
+use tract_linalg::threads::{self, ThreadPool};
+
+#[derive(Debug, Clone)]
+pub struct ParallelPlan<TI, O, M>
+where
+    TI: TensorInfo + Clone + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static,
+    M: Borrow<ModelImpl<TI, O>>,
+{
+    pub plan: SimplePlan<TI, O, M>,
+    pub pool: Arc<ThreadPool>,
+    /// For each step, the number of steps it waits for.
+    pub predecessors: Vec<usize>,
+    /// For each step, the steps waiting for it.
+    pub successors: Vec<TVec<usize>>,
+    /// For each node, the number of steps reading its outputs before they can
+    /// be released. Zero for the outputs of the plan, they are never released.
+    pub readers: Vec<usize>,
+    _casper: PhantomData<(TI, O)>,
+}
+
+impl<TI, O, M> ParallelPlan<TI, O, M>
+where
+    TI: TensorInfo + Clone + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static,
+    M: Borrow<ModelImpl<TI, O>>,
+{
+    /// This contructor returns a plan that will compute all the model default outputs in one pass.
+    pub fn new(model: M, pool: Arc<ThreadPool>) -> TractResult<ParallelPlan<TI, O, M>> {
+        Self::from_plan(SimplePlan::new(model)?, pool)
+    }
+
+    /// This contructor returns a plan that will compute all specified outputs in one pass.
+    pub fn new_for_outputs(
+        model: M,
+        outputs: &[OutletId],
+        pool: Arc<ThreadPool>,
+    ) -> TractResult<ParallelPlan<TI, O, M>> {
+        Self::from_plan(SimplePlan::new_for_outputs(model, outputs)?, pool)
+    }
+
+    pub fn from_plan(
+        plan: SimplePlan<TI, O, M>,
+        pool: Arc<ThreadPool>,
+    ) -> TractResult<ParallelPlan<TI, O, M>> {
+        let model = plan.model();
+        let mut step_of_node = vec![None; model.nodes().len()];
+        for (step, &node) in plan.order.iter().enumerate() {
+            step_of_node[node] = Some(step);
+        }
+        let mut predecessors = vec![0; plan.order.len()];
+        let mut successors = vec![tvec!(); plan.order.len()];
+        let mut readers = vec![0; model.nodes().len()];
+        let inputs = model.input_outlets()?.iter().map(|o| o.node).collect::<Vec<_>>();
+        for (step, &node) in plan.order.iter().enumerate() {
+            if inputs.contains(&node) {
+                continue;
+            }
+            let node = model.node(node);
+            let mut precursors: TVec<usize> = node.inputs.iter().map(|i| i.node).collect();
+            precursors.extend(node.control_inputs.iter().cloned());
+            precursors.sort();
+            precursors.dedup();
+            for prec in precursors {
+                if let Some(prec_step) = step_of_node[prec] {
+                    predecessors[step] += 1;
+                    successors[prec_step].push(step);
+                }
+                if node.inputs.iter().any(|i| i.node == prec) {
+                    readers[prec] += 1;
+                }
+            }
+        }
+        for output in &plan.outputs {
+            readers[output.node] = 0;
+        }
+        Ok(ParallelPlan { plan, pool, predecessors, successors, readers, _casper: PhantomData })
+    }
+
+    pub fn run(&self, inputs: TVec<Tensor>) -> TractResult<TVec<Arc<Tensor>>>
+    where
+        TI: Send + Sync,
+        O: Send + Sync,
+        M: Sync,
+    {
+        let mut state = ParallelState::new(self)?;
+        state.run(inputs)
+    }
+
+    pub fn model(&self) -> &ModelImpl<TI, O> {
+        self.plan.model()
+    }
+}
+
+#[derive(Debug)]
+pub struct ParallelState<TI, O, M, P>
+where
+    TI: TensorInfo + Clone + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static,
+    M: Borrow<ModelImpl<TI, O>>,
+    P: Borrow<ParallelPlan<TI, O, M>>,
+{
+    plan: P,
+    pub states: Vec<Option<Box<dyn OpState>>>,
+    pub session_state: SessionState,
+    /// For each step, the next stateful step, if the step is stateful.
+    next_stateful: Vec<Option<usize>>,
+    /// For each step, whether it waits for a previous stateful step.
+    after_stateful: Vec<bool>,
+    _phantom: PhantomData<(M, TI, O)>,
+}
+
+impl<TI, O, M, P> ParallelState<TI, O, M, P>
+where
+    TI: TensorInfo + Clone + Send + Sync + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + Send + Sync + 'static,
+    M: Borrow<ModelImpl<TI, O>> + Sync,
+    P: Borrow<ParallelPlan<TI, O, M>>,
+{
+    pub fn new(plan: P) -> TractResult<ParallelState<TI, O, M, P>> {
+        let mut session_state = SessionState::default();
+        let states = {
+            let model = plan.borrow().model();
+            model
+                .nodes()
+                .iter()
+                .map(|n: &BaseNode<TI, O>| n.op().state(&mut session_state, n.id))
+                .collect::<TractResult<Vec<_>>>()?
+        };
+        let order = &plan.borrow().plan.order;
+        let mut next_stateful = vec![None; order.len()];
+        let mut after_stateful = vec![false; order.len()];
+        let mut previous: Option<usize> = None;
+        for (step, &node) in order.iter().enumerate() {
+            if states[node].is_some() {
+                if let Some(previous) = previous {
+                    next_stateful[previous] = Some(step);
+                    after_stateful[step] = true;
+                }
+                previous = Some(step);
+            }
+        }
+        Ok(ParallelState {
+            plan,
+            states,
+            session_state,
+            next_stateful,
+            after_stateful,
+            _phantom: PhantomData,
+        })
+    }
+
+    pub fn plan(&self) -> &ParallelPlan<TI, O, M> {
+        self.plan.borrow()
+    }
+
+    pub fn model(&self) -> &ModelImpl<TI, O> {
+        self.plan().model()
+    }
+
+    pub fn set_inputs(&mut self, inputs: TVec<Tensor>) -> TractResult<()> {
+        let &mut ParallelState { ref plan, ref mut session_state, .. } = self;
+        plan.borrow().model().input_outlets()?.iter().zip(inputs).for_each(|(input, t)| {
+            session_state.inputs.insert(input.node, t.into());
+        });
+        Ok(())
+    }
+
+    pub fn run(&mut self, inputs: TVec<Tensor>) -> TractResult<TVec<Arc<Tensor>>> {
+        self.set_inputs(inputs)?;
+        let &mut ParallelState {
+            ref plan,
+            ref mut states,
+            ref mut session_state,
+            ref next_stateful,
+            ref after_stateful,
+            ..
+        } = self;
+        let plan: &ParallelPlan<TI, O, M> = plan.borrow();
+        let run = Run {
+            plan,
+            model: plan.model(),
+            next_stateful,
+            pending: (0..plan.plan.order.len())
+                .map(|s| AtomicUsize::new(plan.predecessors[s] + after_stateful[s] as usize))
+                .collect(),
+            readers: plan.readers.iter().map(|&r| AtomicUsize::new(r)).collect(),
+            values: (0..plan.model().nodes().len()).map(|_| Mutex::new(None)).collect(),
+            states: states.drain(..).map(Mutex::new).collect(),
+            session_state: Mutex::new(session_state),
+            error: Mutex::new(None),
+        };
+        let ready: Vec<usize> = (0..plan.plan.order.len())
+            .filter(|&step| run.pending[step].load(Ordering::SeqCst) == 0)
+            .collect();
+        plan.pool.scope(|scope| {
+            for step in ready {
+                run.spawn(scope, step);
+            }
+        });
+        let Run { values, states: run_states, error, .. } = run;
+        *states = run_states.into_iter().map(|s| s.into_inner().unwrap()).collect();
+        if let Some(e) = error.into_inner().unwrap() {
+            return Err(e);
+        }
+        let values: Vec<_> = values.into_iter().map(|v| v.into_inner().unwrap()).collect();
+        plan.plan
+            .outputs
+            .iter()
+            .map(|o| -> TractResult<Arc<Tensor>> {
+                Ok(values[o.node].as_ref().ok_or_else(|| format!("Output {:?} not computed", o))?
+                    [o.slot]
+                    .clone())
+            })
+            .collect()
+    }
+}
+
+/// The shared bookkeeping of one run.
+struct Run<'a, TI, O, M>
+where
+    TI: TensorInfo + Clone + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + 'static,
+    M: Borrow<ModelImpl<TI, O>>,
+{
+    plan: &'a ParallelPlan<TI, O, M>,
+    model: &'a ModelImpl<TI, O>,
+    next_stateful: &'a [Option<usize>],
+    pending: Vec<AtomicUsize>,
+    readers: Vec<AtomicUsize>,
+    values: Vec<Mutex<Option<TVec<Arc<Tensor>>>>>,
+    states: Vec<Mutex<Option<Box<dyn OpState>>>>,
+    session_state: Mutex<&'a mut SessionState>,
+    error: Mutex<Option<TractError>>,
+}
+
+impl<'a, TI, O, M> Run<'a, TI, O, M>
+where
+    TI: TensorInfo + Clone + Send + Sync + 'static,
+    O: Debug + Display + AsRef<dyn Op> + AsMut<dyn Op> + Clone + Send + Sync + 'static,
+    M: Borrow<ModelImpl<TI, O>> + Sync,
+{
+    fn spawn<'s>(&'s self, scope: &rayon::Scope<'s>, step: usize) {
+        scope.spawn(move |scope| {
+            let result = threads::with_pool(Some(self.plan.pool.clone()), || self.eval(step));
+            if let Err(e) = result {
+                let mut error = self.error.lock().unwrap();
+                if error.is_none() {
+                    *error = Some(e);
+                }
+                return;
+            }
+            let next = self.plan.successors[step].iter().chain(self.next_stateful[step].iter());
+            for &next in next {
+                if self.pending[next].fetch_sub(1, Ordering::SeqCst) == 1 {
+                    self.spawn(scope, next);
+                }
+            }
+        })
+    }
+
+    fn eval(&self, step: usize) -> TractResult<()> {
+        if self.error.lock().unwrap().is_some() {
+            return Ok(());
+        }
+        let node = self.model.node(self.plan.plan.order[step]);
+        trace!("Running step {}, node {}", step, node);
+        let mut inputs: TVec<Arc<Tensor>> = tvec![];
+        for i in &node.inputs {
+            let values = self.values[i.node].lock().unwrap();
+            let prec = values.as_ref().ok_or_else(|| {
+                format!("Computing {}, precursor {} not done", node, self.model.node(i.node))
+            })?;
+            inputs.push(prec[i.slot].clone())
+        }
+
+        let mut precursors: TVec<usize> = node.inputs.iter().map(|i| i.node).collect();
+        precursors.sort();
+        precursors.dedup();
+        for prec in precursors {
+            if self.plan.readers[prec] > 0 && self.readers[prec].fetch_sub(1, Ordering::SeqCst) == 1
+            {
+                trace!("  flushing node {} {}", prec, node);
+                *self.values[prec].lock().unwrap() = None;
+            }
+        }
+
+        let vs = match *self.states[node.id].lock().unwrap() {
+            Some(ref mut state) => {
+                state.eval(&mut *self.session_state.lock().unwrap(), node.op(), inputs)
+            }
+            None => node.op().as_stateless().expect("as_stateless").eval(inputs),
+        }
+        .chain_err(|| format!("Evaluating {}", node))?;
+        *self.values[node.id].lock().unwrap() = Some(vs);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::math;
+
+    fn branches() -> TractResult<TypedModel> {
+        let mut model = TypedModel::default();
+        let fact = TypedTensorInfo::dt_shape(f32::datum_type(), [4usize].as_ref())?;
+        let a = model.add_source("a", fact)?;
+        let a = OutletId::new(a, 0);
+        let mut tails = tvec!();
+        for i in 0..4 {
+            let mut wire = a;
+            for j in 0..3 {
+                let op = math::add::unary(rctensor0((i * 3 + j) as f32));
+                wire = model.wire_node(format!("add-{}-{}", i, j), op, &[wire])?[0];
+                wire =
+                    model.wire_node(format!("tanh-{}-{}", i, j), math::Tanh::default(), &[wire])?
+                        [0];
+            }
+            tails.push(wire);
+        }
+        let mut wire = tails[0];
+        for (i, &tail) in tails.iter().enumerate().skip(1) {
+            wire = model.wire_node(format!("join-{}", i), math::add::bin(), &[wire, tail])?[0];
+        }
+        model.set_output_outlets(&[wire, tails[1]])?;
+        Ok(model)
+    }
+
+    #[test]
+    fn same_as_simple_plan() {
+        let model = branches().unwrap();
+        let input = tensor1(&[0.0f32, 1.0, -2.0, 0.5]);
+        let expected = SimplePlan::new(&model).unwrap().run(tvec!(input.clone())).unwrap();
+        let plan = ParallelPlan::new(&model, threads::pool(3).unwrap()).unwrap();
+        for _ in 0..10 {
+            assert_eq!(plan.run(tvec!(input.clone())).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn flushes_intermediate_values_only() {
+        let model = branches().unwrap();
+        let plan = ParallelPlan::new(&model, threads::pool(2).unwrap()).unwrap();
+        let a = model.node_by_name("a").unwrap().id;
+        let tail = model.node_by_name("tanh-1-2").unwrap().id;
+        assert_eq!(plan.readers[a], 4);
+        assert_eq!(plan.readers[tail], 0);
+    }
+
+    #[test]
+    fn stateful_ops_keep_their_state() {
+        use crate::pulse::delay::Delay;
+        use crate::pulse::{PulsedModel, PulsedTensorFact};
+        let mut model = PulsedModel::default();
+        let fact = |delay| PulsedTensorFact {
+            dt: u8::datum_type(),
+            shape: tvec![3],
+            axis: 0,
+            dim: TDim::s(),
+            delay,
+        };
+        let source = OutletId::new(model.add_source("source", fact(0)).unwrap(), 0);
+        let mut outputs = vec![];
+        for delay in 1..4 {
+            let id = model
+                .add_node(
+                    format!("delay-{}", delay),
+                    Delay::new(fact(0), delay, 0),
+                    tvec!(fact(delay)),
+                )
+                .unwrap();
+            model.add_edge(source, InletId::new(id, 0)).unwrap();
+            outputs.push(OutletId::new(id, 0));
+        }
+        model.set_output_outlets(&outputs).unwrap();
+
+        let mut simple = SimpleState::new(SimplePlan::new(&model).unwrap()).unwrap();
+        let plan = ParallelPlan::new(&model, threads::pool(3).unwrap()).unwrap();
+        let mut parallel = ParallelState::new(&plan).unwrap();
+        for i in 0..5u8 {
+            let input = tensor1(&[3 * i, 3 * i + 1, 3 * i + 2]);
+            assert_eq!(
+                parallel.run(tvec!(input.clone())).unwrap(),
+                simple.run(tvec!(input)).unwrap()
+            );
+        }
+    }
+}
@@ -3,14 +3,61 @@ use std::fmt::{Debug, Display};
 use std::marker::PhantomData;
 
 use crate::internal::*;
+use crate::memory_plan::{MemoryPlan, ARENA_ALIGNMENT};
 use crate::model::order::eval_order_for_nodes;
 use crate::model::{ModelImpl, OutletId, TensorInfo};
+use tract_linalg::threads::{self, ThreadPool};
 
 #[derive(Debug, Default)]
 pub struct SessionState {
     pub inputs: HashMap<usize, Arc<Tensor>>,
     pub known_stream_len: Option<usize>,
     pub tensors: HashMap<String, Tensor>,
+    /// Values of the symbolic dimensions for the current run, bound from the
+    /// input tensors shapes.
+    pub resolved_symbols: HashMap<char, i32>,
+}
+
+impl SessionState {
+    /// Bind the symbols appearing as plain dimensions in an input fact to the
+    /// actual input tensor dimensions.
+    pub fn bind_symbols(&mut self, fact: &TensorFact, tensor: &Tensor) -> TractResult<()> {
+        let shape = if let Some(shape) = fact.shape.concretize() { shape } else { return Ok(()) };
+        for (dim, &actual) in shape.iter().zip(tensor.shape().iter()) {
+            let symbols = dim.symbols();
+            if symbols.len() != 1 {
+                continue;
+            }
+            let symbol = *symbols.iter().next().unwrap();
+            if *dim != TDim::sym(symbol) {
+                continue;
+            }
+            match self.resolved_symbols.get(&symbol) {
+                Some(&value) if value != actual as i32 => {
+                    bail!("Inconsistent value for symbol {}: {} and {}", symbol, value, actual)
+                }
+                _ => {
+                    self.resolved_symbols.insert(symbol, actual as i32);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Substitute the resolved symbols in a fact shape. Returns None if some
+    /// symbols are still unknown.
+    pub fn resolve_fact(&self, fact: TensorFact) -> TractResult<Option<TensorFact>> {
+        let shape =
+            if let Some(shape) = fact.shape.concretize() { shape } else { return Ok(Some(fact)) };
+        if shape.iter().all(|d| !d.is_symbolic()) {
+            return Ok(Some(fact));
+        }
+        let shape = shape
+            .iter()
+            .map(|d| d.eval_with(&self.resolved_symbols).ok().map(|d| d as usize))
+            .collect::<Option<TVec<usize>>>();
+        Ok(shape.map(|shape| TensorFact { shape: ShapeFact::from(shape), ..fact }))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -24,6 +71,8 @@ where
     pub outputs: Vec<OutletId>,
     pub order: Vec<usize>,
     pub flush_lists: Vec<TVec<usize>>,
+    pub thread_pool: Option<Arc<ThreadPool>>,
+    pub memory_plan: Option<MemoryPlan>,
     _casper: PhantomData<(TI, O)>,
 }
 
@@ -67,10 +116,32 @@ where
             order,
             flush_lists,
             outputs: outputs.to_vec(),
+            thread_pool: None,
+            memory_plan: None,
             _casper: PhantomData,
         })
     }
 
+    /// Runs the parallelizable loops of this plan on `pool`. Plans without a
+    /// pool use the process default (see `threads::set_default_pool`), single
+    /// threaded unless set.
+    pub fn with_thread_pool(self, pool: Arc<ThreadPool>) -> SimplePlan<TI, O, M> {
+        SimplePlan { thread_pool: Some(pool), ..self }
+    }
+
+    /// Runs the parallelizable loops of this plan on a new pool of `threads`
+    /// workers.
+    pub fn with_threads(self, threads: usize) -> TractResult<SimplePlan<TI, O, M>> {
+        Ok(self.with_thread_pool(tract_linalg::threads::pool(threads)?))
+    }
+
+    /// Plans the memory of intermediate values: states will allocate them
+    /// in a single buffer, sized ahead of time. Requires known shapes.
+    pub fn with_memory_plan(self) -> TractResult<SimplePlan<TI, O, M>> {
+        let memory_plan = MemoryPlan::new(&self)?;
+        Ok(SimplePlan { memory_plan: Some(memory_plan), ..self })
+    }
+
     pub fn run(&self, inputs: TVec<Tensor>) -> TractResult<TVec<Arc<Tensor>>> {
         let mut state = SimpleState::new(self)?;
         state.run(inputs)
@@ -81,6 +152,16 @@ where
     }
 }
 
+/// Serialized form of a `SimpleState` between two runs. Wires are reset
+/// after each run, so only the session and the operator states are kept.
+#[cfg(feature = "serialize")]
+#[derive(Serialize, Deserialize)]
+struct SimpleStateSnapshot {
+    known_stream_len: Option<usize>,
+    tensors: HashMap<String, Tensor>,
+    states: Vec<Option<Vec<u8>>>,
+}
+
 #[derive(Debug)]
 pub struct SimpleState<TI, O, M, P>
 where
@@ -93,6 +174,9 @@ where
     pub states: Vec<Option<Box<dyn OpState>>>,
     pub session_state: SessionState,
     pub values: Vec<Option<TVec<Arc<Tensor>>>>,
+    /// Buffer for memory planned values. Declared after `values`, so they
+    /// are dropped before it.
+    arena: Option<Tensor>,
     _phantom: PhantomData<(M, TI, O)>,
 }
 
@@ -115,7 +199,24 @@ where
             plans: self.plans.clone(),
             states,
             session_state: SessionState::default(),
-            values: self.values.clone(),
+            values: self
+                .values
+                .iter()
+                .map(|v| {
+                    v.as_ref().map(|v| {
+                        v.iter()
+                            .map(|t| {
+                                if t.in_arena() {
+                                    Arc::new(t.as_ref().clone())
+                                } else {
+                                    t.clone()
+                                }
+                            })
+                            .collect()
+                    })
+                })
+                .collect(),
+            arena: None,
             _phantom: PhantomData,
         }
     }
@@ -141,7 +242,14 @@ where
             .iter()
             .map(|n: &BaseNode<TI, O>| n.op().state(&mut session, n.id))
             .collect::<TractResult<_>>()?;
-        Ok(SimpleState { plans, states, session_state: session, values, _phantom: PhantomData })
+        Ok(SimpleState {
+            plans,
+            states,
+            session_state: session,
+            values,
+            arena: None,
+            _phantom: PhantomData,
+        })
     }
 
     /// Reset wires state.
@@ -163,6 +271,57 @@ where
         Ok(())
     }
 
+    /// Serialize the session and operator states, so that the state can be
+    /// restored with `load_state` on a state built from the same model.
+    #[cfg(feature = "serialize")]
+    pub fn save_state(&self) -> TractResult<Vec<u8>> {
+        let model = self.model();
+        let states = self
+            .states
+            .iter()
+            .enumerate()
+            .map(|(ix, state)| {
+                state
+                    .as_ref()
+                    .map(|s| s.save_state().chain_err(|| format!("Saving {}", model.node(ix))))
+                    .transpose()
+            })
+            .collect::<TractResult<Vec<_>>>()?;
+        let snapshot = SimpleStateSnapshot {
+            known_stream_len: self.session_state.known_stream_len,
+            tensors: self.session_state.tensors.clone(),
+            states,
+        };
+        Ok(bincode::serialize(&snapshot)?)
+    }
+
+    /// Restore a state serialized by `save_state`.
+    #[cfg(feature = "serialize")]
+    pub fn load_state(&mut self, data: &[u8]) -> TractResult<()> {
+        let snapshot: SimpleStateSnapshot = bincode::deserialize(data)?;
+        if snapshot.states.len() != self.states.len() {
+            bail!(
+                "Saved state is for a model with {} nodes, this one has {}",
+                snapshot.states.len(),
+                self.states.len()
+            )
+        }
+        let SimpleState { ref plans, ref mut session_state, ref mut states, .. } = self;
+        let model = plans[0].borrow().model();
+        for (ix, (state, saved)) in states.iter_mut().zip(snapshot.states.iter()).enumerate() {
+            match (state, saved) {
+                (Some(state), Some(saved)) => {
+                    state.load_state(saved).chain_err(|| format!("Loading {}", model.node(ix)))?
+                }
+                (None, None) => (),
+                _ => bail!("Saved state does not match node {}", model.node(ix)),
+            }
+        }
+        session_state.known_stream_len = snapshot.known_stream_len;
+        session_state.tensors = snapshot.tensors;
+        Ok(())
+    }
+
     pub fn run(&mut self, inputs: TVec<Tensor>) -> TractResult<TVec<Arc<Tensor>>> {
         self.run_plan(inputs, 0)
     }
@@ -171,6 +330,17 @@ where
         &mut self,
         inputs: TVec<Tensor>,
         plan: usize,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        match self.plans[plan].borrow().thread_pool.clone() {
+            Some(pool) => threads::with_pool(Some(pool), || self.run_plan_steps(inputs, plan)),
+            None => self.run_plan_steps(inputs, plan),
+        }
+    }
+
+    fn run_plan_steps(
+        &mut self,
+        inputs: TVec<Tensor>,
+        plan: usize,
     ) -> TractResult<TVec<Arc<Tensor>>> {
         let mut result = tvec!();
         {
@@ -180,10 +350,29 @@ where
                 ref mut session_state,
                 ref mut states,
                 ref mut values,
+                ref mut arena,
                 ..
             } = self;
             let plan = plans[plan].borrow();
             let model = plan.model().borrow();
+            let arena_ptr = match plan.memory_plan {
+                Some(ref memory_plan) => {
+                    if arena.as_ref().map(|a| a.len()).unwrap_or(0) < memory_plan.arena_size {
+                        values.iter_mut().for_each(|v| *v = None);
+                        *arena = Some(unsafe {
+                            Tensor::uninitialized_aligned::<u8>(
+                                &[memory_plan.arena_size],
+                                ARENA_ALIGNMENT,
+                            )?
+                        });
+                    }
+                    match arena {
+                        Some(arena) => arena.as_ptr_mut::<u8>()?,
+                        None => std::ptr::null_mut(),
+                    }
+                }
+                None => std::ptr::null_mut(),
+            };
             for (step, n) in plan.order.iter().enumerate() {
                 let node = model.node(*n);
                 trace!("Running step {}, node {}", step, node);
@@ -213,12 +402,11 @@ where
                         );
                     }
                     for (ix, (v, f)) in inputs.iter().zip(facts.iter()).enumerate() {
-                        if f.to_tensor_fact().shape.is_concrete()
-                            && f.to_tensor_fact().stream_info()?.is_some()
-                        {
-                            continue;
-                        }
-                        if let Err(e) = f.to_tensor_fact().unify(&v.clone().into()) {
+                        let f = match session_state.resolve_fact(f.to_tensor_fact())? {
+                            Some(f) => f,
+                            None => continue,
+                        };
+                        if let Err(e) = f.unify(&v.clone().into()) {
                             bail!(
                                 "Evaluating {}: input {:?}, expected {:?}, got {:?} ({})",
                                 node,
@@ -231,11 +419,18 @@ where
                     }
                 }
 
-                let vs = match states[node.id] {
-                    Some(ref mut state) => state.eval(session_state, node.op(), inputs),
-                    None => node.op().as_stateless().expect("as_stateless").eval(inputs),
+                let vs = match (&mut states[node.id], &plan.memory_plan) {
+                    (Some(state), _) => state.eval(session_state, node.op(), inputs),
+                    (None, Some(memory_plan)) => memory_plan.with_slots(arena_ptr, node.id, || {
+                        node.op().as_stateless().expect("as_stateless").eval(inputs)
+                    }),
+                    (None, None) => node.op().as_stateless().expect("as_stateless").eval(inputs),
                 }
                 .chain_err(|| format!("Evaluating {}", node))?;
+                let vs = match plan.memory_plan {
+                    Some(ref memory_plan) => memory_plan.settle(arena_ptr, node.id, vs)?,
+                    None => vs,
+                };
 
                 if cfg!(debug_assertions) {
                     let facts = model.node_output_facts(node.id)?;
@@ -251,12 +446,11 @@ where
                         if node.outputs[ix].successors.len() == 0 {
                             continue;
                         }
-                        if f.to_tensor_fact().shape.is_concrete()
-                            && f.to_tensor_fact().stream_info()?.is_some()
-                        {
-                            continue;
-                        }
-                        if let Err(e) = f.to_tensor_fact().unify(&v.clone().into()) {
+                        let f = match session_state.resolve_fact(f.to_tensor_fact())? {
+                            Some(f) => f,
+                            None => continue,
+                        };
+                        if let Err(e) = f.unify(&v.clone().into()) {
                             bail!(
                                 "Evaluating {}: output {:?}, expected {:?}, got {:?} ({})",
                                 node,
@@ -281,20 +475,29 @@ where
 
     pub fn set_inputs(&mut self, inputs: TVec<Tensor>) -> TractResult<()> {
         let SimpleState { ref plans, ref mut session_state, .. } = self;
-        plans[0].borrow().model().input_outlets()?.iter().zip(inputs).for_each(|(input, t)| {
+        let model = plans[0].borrow().model();
+        session_state.resolved_symbols.clear();
+        for (input, t) in model.input_outlets()?.iter().zip(inputs) {
+            session_state.bind_symbols(&model.outlet_fact(*input)?.to_tensor_fact(), &t)?;
             session_state.inputs.insert(input.node, t.into());
-        });
+        }
         Ok(())
     }
 
     pub fn set_input(&mut self, input: usize, t: Tensor) -> TractResult<()> {
-        let id = self
+        let outlet = *self
             .model()
             .input_outlets()?
             .get(input)
-            .ok_or_else(|| format!("Invalid input id for model ({}).", input))?
-            .node;
-        self.session_state.inputs.insert(id, t.into());
+            .ok_or_else(|| format!("Invalid input id for model ({}).", input))?;
+        let fact = self.model().outlet_fact(outlet)?.to_tensor_fact();
+        if let Some(shape) = fact.shape.concretize() {
+            for symbol in shape.iter().flat_map(|d| d.symbols()) {
+                self.session_state.resolved_symbols.remove(&symbol);
+            }
+        }
+        self.session_state.bind_symbols(&fact, &t)?;
+        self.session_state.inputs.insert(outlet.node, t.into());
         Ok(())
     }
 
@@ -386,7 +589,14 @@ where
             .take()
             .ok_or("Node is not computed")?
             .into_iter()
-            .map(|v| Arc::try_unwrap(v).unwrap_or_else(|v| (*v).clone()))
+            .map(|v| {
+                if v.in_arena() {
+                    // the arena belongs to the state, the value must not outlive it
+                    v.as_ref().clone()
+                } else {
+                    Arc::try_unwrap(v).unwrap_or_else(|v| (*v).clone())
+                }
+            })
             .collect())
     }
 
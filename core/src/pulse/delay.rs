@@ -55,6 +55,17 @@ impl OpState for DelayState {
         let op = op.downcast_ref::<Delay>().ok_or("Wrong Op type")?;
         Ok(tvec!(dispatch_datum!(Self::eval_t(input.datum_type())(self, op, input))?))
     }
+
+    #[cfg(feature = "serialize")]
+    fn save_state(&self) -> TractResult<Vec<u8>> {
+        Ok(bincode::serialize(&self.buffer)?)
+    }
+
+    #[cfg(feature = "serialize")]
+    fn load_state(&mut self, data: &[u8]) -> TractResult<()> {
+        self.buffer = bincode::deserialize(data)?;
+        Ok(())
+    }
 }
 
 #[derive(Clone, Debug, new, PartialEq)]
@@ -4,6 +4,9 @@ use std::fmt;
 use std::convert::TryFrom;
 
 pub mod delay;
+pub mod stream;
+
+pub use self::stream::{PulsedModelState, PulsedPlan};
 
 #[derive(Clone, PartialEq)]
 pub struct PulsedTensorFact {
@@ -72,9 +75,7 @@ impl PulsedTensorFact {
     }
 
     pub fn to_streaming_fact(&self) -> NormalizedTensorInfo {
-        let mut info = self.to_pulse_fact();
-        info.shape.stream_info = Some(StreamInfo { axis: self.axis, len: self.dim.clone() });
-        info
+        NormalizedTensorInfo::dt_shape(self.dt, &*self.streaming_shape()).unwrap()
     }
 }
 
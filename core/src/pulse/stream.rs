@@ -0,0 +1,341 @@
+use crate::internal::*;
+use crate::pulse::PulsedTensorFact;
+use ndarray::*;
+
+use std::borrow::Borrow;
+
+pub type PulsedPlan<M = PulsedModel> = SimplePlan<PulsedTensorFact, Box<dyn TypedOp>, M>;
+
+/// Runs a pulsed model over a stream.
+///
+/// Outputs are stripped of the frames introduced by the model delay, so
+/// concatenating everything returned by `run` and `finish_stream` gives the
+/// output of the non-pulsed model.
+#[derive(Debug)]
+pub struct PulsedModelState<M, P>
+where
+    M: Borrow<PulsedModel>,
+    P: Borrow<PulsedPlan<M>> + Clone,
+{
+    pub state: SimpleState<PulsedTensorFact, Box<dyn TypedOp>, M, P>,
+    /// Number of input frames fed so far.
+    written: usize,
+    /// Number of frames produced so far by each output, delay included.
+    produced: TVec<usize>,
+}
+
+impl<M, P> PulsedModelState<M, P>
+where
+    M: Borrow<PulsedModel>,
+    P: Borrow<PulsedPlan<M>> + Clone,
+{
+    pub fn new(plan: P) -> TractResult<PulsedModelState<M, P>> {
+        let inputs = plan.borrow().model().input_outlets()?.len();
+        if inputs != 1 {
+            bail!("PulsedModelState needs a model with one streaming input, found {}", inputs)
+        }
+        let produced = tvec!(0; plan.borrow().model().output_outlets()?.len());
+        Ok(PulsedModelState { state: SimpleState::new(plan)?, written: 0, produced })
+    }
+
+    pub fn model(&self) -> &PulsedModel {
+        self.state.model()
+    }
+
+    /// Feed one pulse of input, and get the valid output frames it yields (possibly none at all while the model fills its delay).
+    pub fn run(&mut self, inputs: TVec<Tensor>) -> TractResult<TVec<Tensor>> {
+        if self.state.session_state.known_stream_len.is_some() {
+            bail!("Stream is already finished")
+        }
+        let len = self.chunk_len(&inputs)?;
+        let pulse = self.input_pulse()?;
+        if len != pulse {
+            bail!("Expected a pulse of {} frames, got {}", pulse, len)
+        }
+        self.written += pulse;
+        self.run_pulse(inputs)
+    }
+
+    /// Signal the end of the stream, feeding the last input chunk (shorter
+    /// than a pulse, possibly empty). The input is padded with `pad`, a
+    /// scalar of the input type, and the model fed with as many padding
+    /// pulses as needed to flush all the remaining valid output frames.
+    pub fn finish_stream(
+        &mut self,
+        inputs: TVec<Tensor>,
+        pad: &Tensor,
+    ) -> TractResult<TVec<Tensor>> {
+        if self.state.session_state.known_stream_len.is_some() {
+            bail!("Stream is already finished")
+        }
+        let len = self.chunk_len(&inputs)?;
+        let pulse = self.input_pulse()?;
+        if len > pulse {
+            bail!("Last chunk can not be longer than a pulse ({} > {})", len, pulse)
+        }
+        let dt = self.model().input_fact(0)?.dt;
+        if pad.shape().len() != 0 || pad.datum_type() != dt {
+            bail!("Padding must be a {:?} scalar, got {:?}", dt, pad)
+        }
+        self.written += len;
+        self.state.session_state.known_stream_len = Some(self.written);
+        for ix in 0..self.produced.len() {
+            if self.remaining(ix).is_none() {
+                bail!("Can not compute length of output {} at end of stream", ix)
+            }
+        }
+        let mut inputs = inputs
+            .into_iter()
+            .enumerate()
+            .map(|(ix, input)| {
+                let axis = self.model().input_fact(ix)?.axis;
+                dispatch_datum!(self::pad_t(dt)(&input, axis, pulse, pad))
+            })
+            .collect::<TractResult<TVec<Tensor>>>()?;
+
+        let mut chunks: TVec<TVec<Tensor>> = tvec!(tvec!(); self.produced.len());
+        loop {
+            for (ix, chunk) in self.run_pulse(inputs)?.into_iter().enumerate() {
+                chunks[ix].push(chunk);
+            }
+            if (0..self.produced.len()).all(|ix| self.remaining(ix) == Some(0)) {
+                break;
+            }
+            inputs = (0..self.model().input_outlets()?.len())
+                .map(|ix| {
+                    let fact = self.model().input_fact(ix)?;
+                    dispatch_datum!(self::fill_t(dt)(&fact.shape, pad))
+                })
+                .collect::<TractResult<_>>()?;
+        }
+        chunks
+            .into_iter()
+            .enumerate()
+            .map(|(ix, chunks)| {
+                let fact = self.model().output_fact(ix)?;
+                dispatch_datum!(self::concat_t(fact.dt)(fact.axis, &chunks))
+            })
+            .collect()
+    }
+
+    /// Serialize the stream position and the model state.
+    #[cfg(feature = "serialize")]
+    pub fn save_state(&self) -> TractResult<Vec<u8>> {
+        let state = self.state.save_state()?;
+        Ok(bincode::serialize(&(self.written, &self.produced, state))?)
+    }
+
+    /// Restore a state serialized by `save_state`.
+    #[cfg(feature = "serialize")]
+    pub fn load_state(&mut self, data: &[u8]) -> TractResult<()> {
+        let (written, produced, state): (usize, TVec<usize>, Vec<u8>) = bincode::deserialize(data)?;
+        if produced.len() != self.produced.len() {
+            bail!("Saved state has {} outputs, model has {}", produced.len(), self.produced.len())
+        }
+        self.written = written;
+        self.produced = produced;
+        self.state.load_state(&state)
+    }
+
+    fn input_pulse(&self) -> TractResult<usize> {
+        Ok(self.model().input_fact(0)?.pulse())
+    }
+
+    /// Common length of the inputs along their streaming axes.
+    fn chunk_len(&self, inputs: &[Tensor]) -> TractResult<usize> {
+        let lens = inputs
+            .iter()
+            .enumerate()
+            .map(|(ix, input)| Ok(input.shape()[self.model().input_fact(ix)?.axis]))
+            .collect::<TractResult<TVec<usize>>>()?;
+        match lens.first() {
+            None => bail!("Pulsed model needs at least one input"),
+            Some(&len) if lens.iter().any(|&l| l != len) => {
+                bail!("Inputs have different lengths along their streaming axes: {:?}", lens)
+            }
+            Some(&len) => Ok(len),
+        }
+    }
+
+    /// Number of valid frames output `ix` still has to produce.
+    fn remaining(&self, ix: usize) -> Option<usize> {
+        let fact = self.model().output_fact(ix).ok()?;
+        let len = self.output_len(fact)?;
+        Some((fact.delay + len).saturating_sub(self.produced[ix]))
+    }
+
+    /// Output length for the input frames written so far. Output frames
+    /// after it can not be computed yet, so they are padding artefacts.
+    fn output_len(&self, fact: &PulsedTensorFact) -> Option<usize> {
+        fact.dim.eval(self.written as i32).map(|l| l.max(0) as usize)
+    }
+
+    /// Run a pulse, and keep the valid part of each output.
+    fn run_pulse(&mut self, inputs: TVec<Tensor>) -> TractResult<TVec<Tensor>> {
+        let outputs = self.state.run(inputs)?;
+        outputs
+            .into_iter()
+            .enumerate()
+            .map(|(ix, output)| {
+                let fact = self.model().output_fact(ix)?.clone();
+                let begin = self.produced[ix];
+                let end = begin + fact.pulse();
+                self.produced[ix] = end;
+                let valid_end = self.output_len(&fact).map(|l| fact.delay + l).unwrap_or(end);
+                let from = begin.max(fact.delay).min(end) - begin;
+                let to = end.min(valid_end).max(begin + from) - begin;
+                dispatch_datum!(self::slice_t(fact.dt)(&output, fact.axis, from, to))
+            })
+            .collect()
+    }
+}
+
+fn slice_t<T: Datum>(input: &Tensor, axis: usize, from: usize, to: usize) -> TractResult<Tensor> {
+    let input = input.to_array_view::<T>()?;
+    if from == to {
+        // ndarray chokes on copying empty strided views
+        let mut shape = input.shape().to_vec();
+        shape[axis] = 0;
+        return Ok(ArrayD::<T>::default(shape).into());
+    }
+    Ok(input.slice_axis(Axis(axis), (from..to).into()).to_owned().into())
+}
+
+fn pad_t<T: Datum>(input: &Tensor, axis: usize, len: usize, pad: &Tensor) -> TractResult<Tensor> {
+    let input = input.to_array_view::<T>()?;
+    let mut shape = input.shape().to_vec();
+    shape[axis] = len;
+    let mut padded = ArrayD::<T>::from_elem(shape, pad.to_scalar::<T>()?.clone());
+    padded.slice_axis_mut(Axis(axis), (..input.shape()[axis]).into()).assign(&input);
+    Ok(padded.into())
+}
+
+fn fill_t<T: Datum>(shape: &[usize], pad: &Tensor) -> TractResult<Tensor> {
+    Ok(ArrayD::<T>::from_elem(shape, pad.to_scalar::<T>()?.clone()).into())
+}
+
+fn concat_t<T: Datum>(axis: usize, chunks: &[Tensor]) -> TractResult<Tensor> {
+    let views = chunks.iter().map(|t| t.to_array_view::<T>()).collect::<TractResult<Vec<_>>>()?;
+    let mut shape = views[0].shape().to_vec();
+    shape[axis] = views.iter().map(|v| v.shape()[axis]).sum();
+    let mut output = ArrayD::<T>::default(shape);
+    let mut offset = 0;
+    for view in views {
+        let len = view.shape()[axis];
+        output.slice_axis_mut(Axis(axis), (offset..offset + len).into()).assign(&view);
+        offset += len;
+    }
+    Ok(output.into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ops::array::{Pad, PadMode};
+
+    /// Also checks a state can run a plan borrowing its model, like the cli
+    /// does.
+    #[test]
+    fn finish_stream_flushes_delayed_frames() {
+        let mut model = InferenceModel::default();
+        let _ = model
+            .add_source("a", TensorFact::dt_shape(f32::datum_type(), tvec!(TDim::s())))
+            .unwrap();
+        model
+            .chain_default(
+                "pad",
+                Pad::new(vec![(2, 1)], PadMode::Constant(Arc::new(Tensor::from(-1f32)))),
+            )
+            .unwrap();
+        model.auto_outputs().unwrap();
+        let pulsed = PulsedModel::new(&model.into_normalized().unwrap(), 2).unwrap();
+        let plan = SimplePlan::new(&pulsed).unwrap();
+        let mut state = PulsedModelState::new(&plan).unwrap();
+        let mut got = vec![];
+        for chunk in &[[1.0f32, 2.0], [3.0, 4.0]] {
+            let output = state.run(tvec!(tensor1(chunk))).unwrap();
+            got.extend(output[0].as_slice::<f32>().unwrap().iter().cloned());
+        }
+        let output =
+            state.finish_stream(tvec!(tensor1(&[5.0f32])), &tensor0(std::f32::NAN)).unwrap();
+        got.extend(output[0].as_slice::<f32>().unwrap().iter().cloned());
+        assert_eq!(got, vec![-1.0, -1.0, 1.0, 2.0, 3.0, 4.0, 5.0, -1.0]);
+        assert!(state.run(tvec!(tensor1(&[6.0f32, 7.0]))).is_err());
+    }
+
+    #[test]
+    fn several_streaming_inputs_are_rejected() {
+        let mut model = InferenceModel::default();
+        let fact = TensorFact::dt_shape(f32::datum_type(), tvec!(TDim::s()));
+        let a = model.add_source("a", fact.clone()).unwrap();
+        let b = model.add_source("b", fact).unwrap();
+        let inputs = [OutletId::new(a, 0), OutletId::new(b, 0)];
+        model.wire_node("add", crate::ops::math::add::bin(), &inputs).unwrap();
+        model.auto_outputs().unwrap();
+        let pulsed = PulsedModel::new(&model.into_normalized().unwrap(), 2).unwrap();
+        let plan = SimplePlan::new(pulsed).unwrap();
+        assert!(PulsedModelState::new(&plan).is_err());
+    }
+
+    /// Runs pad, conv and relu over a stream, pausing after two pulses to
+    /// save the state and resume in a new one, and checks the output matches
+    /// an uninterrupted run.
+    #[cfg(feature = "serialize")]
+    fn check_save_and_load_state(memory_plan: bool) {
+        let mut model = InferenceModel::default();
+        let kernel = model.add_const("kernel", tensor3(&[[[0.5f32, 1.0, -0.1]]])).unwrap();
+        let shape = tvec!(1.to_dim(), 1.to_dim(), TDim::s());
+        let _ = model.add_source("a", TensorFact::dt_shape(f32::datum_type(), shape)).unwrap();
+        let pad = Pad::new(
+            vec![(0, 0), (0, 0), (2, 1)],
+            PadMode::Constant(Arc::new(Tensor::from(-1f32))),
+        );
+        model.chain_default("pad", pad).unwrap();
+        let conv = model.chain_default("conv", crate::ops::cnn::Conv::default()).unwrap();
+        model.add_edge(OutletId::new(kernel, 0), InletId::new(conv, 1)).unwrap();
+        model.chain_default("relu", crate::ops::math::ScalarMax::new(0.0)).unwrap();
+        model.auto_outputs().unwrap();
+        let pulsed = PulsedModel::new(&model.into_normalized().unwrap(), 2).unwrap();
+        let mut plan = SimplePlan::new(pulsed).unwrap();
+        if memory_plan {
+            plan = plan.with_memory_plan().unwrap();
+            assert!(plan.memory_plan.as_ref().unwrap().arena_size > 0);
+        }
+        let chunks: Vec<Tensor> =
+            (0..4).map(|i| tensor3(&[[[2.0 * i as f32, 2.0 * i as f32 + 1.0]]])).collect();
+        let last = tensor3(&[[[8.0f32]]]);
+
+        let mut state = PulsedModelState::new(&plan).unwrap();
+        let mut expected = vec![];
+        for c in &chunks {
+            expected.extend(state.run(tvec!(c.clone())).unwrap());
+        }
+        expected.extend(state.finish_stream(tvec!(last.clone()), &tensor0(0f32)).unwrap());
+
+        let mut state = PulsedModelState::new(&plan).unwrap();
+        let mut got = vec![];
+        for c in &chunks[..2] {
+            got.extend(state.run(tvec!(c.clone())).unwrap());
+        }
+        let saved = state.save_state().unwrap();
+        let mut state = PulsedModelState::new(&plan).unwrap();
+        state.load_state(&saved).unwrap();
+        for c in &chunks[2..] {
+            got.extend(state.run(tvec!(c.clone())).unwrap());
+        }
+        got.extend(state.finish_stream(tvec!(last), &tensor0(0f32)).unwrap());
+        assert_eq!(got, expected);
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn save_and_load_state() {
+        check_save_and_load_state(false)
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn save_and_load_state_with_arena() {
+        check_save_and_load_state(true)
+    }
+}
@@ -0,0 +1,217 @@
+//! Native serialization format for typed models.
+//!
+//! A decluttered or optimized `TypedModel` can be written to disk and loaded
+//! back without going through a framework parser, type inference, or the
+//! optimisation passes. Tensor facts and constants are stored as is, and
+//! operators go through an `OpRegistry` that maps each operator type to a
+//! stable key and a serializable form.
+//!
+//! Optimized operators carry data packed for the matrix multiplication kernel
+//! picked for the current CPU. Loading such a model on a platform where a
+//! different kernel geometry is selected fails with an explicit error: the
+//! model must be optimized again there.
+//!
+//! ```no_run
+//! # extern crate tract_core;
+//! # fn main() -> tract_core::TractResult<()> {
+//! use tract_core::prelude::*;
+//! # let model = TypedModel::default();
+//! let mut buffer = vec![];
+//! tract_core::serialization::save(&model, &mut buffer)?;
+//! let plan = tract_core::serialization::load_plan(&mut &*buffer)?;
+//! # Ok(())
+//! # }
+//! ```
+use crate::internal::*;
+use std::io::{Read, Write};
+
+mod ops;
+mod registry;
+
+pub use self::registry::{OpRegistry, OpSnapshot};
+
+const MAGIC: &[u8; 8] = b"tractnnf";
+const VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    version: u32,
+    little_endian: bool,
+}
+
+impl Header {
+    fn current() -> Header {
+        Header { version: VERSION, little_endian: cfg!(target_endian = "little") }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct NodeSnapshot {
+    name: String,
+    inputs: Vec<OutletId>,
+    control_inputs: Vec<usize>,
+    op: OpSnapshot,
+    outputs: Vec<TypedTensorInfo>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ModelSnapshot {
+    nodes: Vec<NodeSnapshot>,
+    inputs: Vec<OutletId>,
+    outputs: Vec<OutletId>,
+}
+
+/// Write a model using the core operator registry.
+pub fn save(model: &TypedModel, writer: &mut dyn Write) -> TractResult<()> {
+    save_with_registry(&OpRegistry::core(), model, writer)
+}
+
+/// Write a model, using `registry` to serialize operators.
+pub fn save_with_registry(
+    registry: &OpRegistry,
+    model: &TypedModel,
+    writer: &mut dyn Write,
+) -> TractResult<()> {
+    let nodes = model
+        .nodes()
+        .iter()
+        .map(|node| {
+            Ok(NodeSnapshot {
+                name: node.name.clone(),
+                inputs: node.inputs.clone(),
+                control_inputs: node.control_inputs.clone(),
+                op: registry
+                    .save_op(&*node.op)
+                    .chain_err(|| format!("Serializing node {}", node))?,
+                outputs: node.outputs.iter().map(|o| o.fact.clone()).collect(),
+            })
+        })
+        .collect::<TractResult<Vec<_>>>()?;
+    let snapshot = ModelSnapshot {
+        nodes,
+        inputs: model.input_outlets()?.to_vec(),
+        outputs: model.output_outlets()?.to_vec(),
+    };
+    writer.write_all(MAGIC)?;
+    bincode::serialize_into(&mut *writer, &Header::current())?;
+    bincode::serialize_into(&mut *writer, &snapshot)?;
+    Ok(())
+}
+
+/// Read a model using the core operator registry.
+pub fn load(reader: &mut dyn Read) -> TractResult<TypedModel> {
+    load_with_registry(&OpRegistry::core(), reader)
+}
+
+/// Read a model, using `registry` to deserialize operators.
+pub fn load_with_registry(registry: &OpRegistry, reader: &mut dyn Read) -> TractResult<TypedModel> {
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        bail!("Not a tract native model")
+    }
+    let header: Header = bincode::deserialize_from(&mut *reader)?;
+    if header.version != VERSION {
+        bail!("Unsupported format version {} (expected {})", header.version, VERSION)
+    }
+    if header.little_endian != cfg!(target_endian = "little") {
+        bail!("Model was serialized on a platform with a different endianness")
+    }
+    let snapshot: ModelSnapshot = bincode::deserialize_from(&mut *reader)?;
+    let mut model = TypedModel::default();
+    for node in &snapshot.nodes {
+        let op = registry.load_op(&node.op).chain_err(|| format!("Loading node {}", node.name))?;
+        let id = model.add_node(&*node.name, op, node.outputs.iter().cloned().collect())?;
+        model.node_mut(id).control_inputs = node.control_inputs.clone();
+    }
+    for (id, node) in snapshot.nodes.iter().enumerate() {
+        for (slot, input) in node.inputs.iter().enumerate() {
+            model.add_edge(*input, InletId::new(id, slot))?;
+        }
+    }
+    model.set_input_outlets(&snapshot.inputs)?;
+    model.set_output_outlets(&snapshot.outputs)?;
+    Ok(model)
+}
+
+/// Read a model and make it runnable.
+pub fn load_plan(reader: &mut dyn Read) -> TractResult<TypedSimplePlan<TypedModel>> {
+    SimplePlan::new(load(reader)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::arr1;
+
+    fn round_trip(model: TypedModel, input: Tensor) -> TractResult<()> {
+        let expected = SimplePlan::new(&model)?.run(tvec!(input.clone()))?;
+        let mut buffer = vec![];
+        save(&model, &mut buffer)?;
+        let found = load_plan(&mut &*buffer)?.run(tvec!(input))?;
+        assert_eq!(expected, found);
+        Ok(())
+    }
+
+    fn add_model() -> TractResult<InferenceModel> {
+        let mut model = InferenceModel::default();
+        let input = model.add_source("input", TensorFact::dt_shape(f32::datum_type(), vec![3]))?;
+        let three = model.add_const("three", arr1(&[3f32, 3.0, 3.0]))?;
+        let add = model.add_node_default("add", crate::ops::math::add::bin())?;
+        model.add_edge(OutletId::new(input, 0), InletId::new(add, 0))?;
+        model.add_edge(OutletId::new(three, 0), InletId::new(add, 1))?;
+        model.auto_outputs()?;
+        Ok(model)
+    }
+
+    #[test]
+    fn round_trip_decluttered() {
+        let model = add_model().unwrap().into_typed().unwrap().declutter().unwrap();
+        round_trip(model, tensor1(&[1.0f32, 2.0, 3.0])).unwrap()
+    }
+
+    #[test]
+    fn round_trip_optimized() {
+        round_trip(add_model().unwrap().into_optimized().unwrap(), tensor1(&[1.0f32, 2.0, 3.0]))
+            .unwrap()
+    }
+
+    #[test]
+    fn round_trip_q_conv_packed() {
+        use crate::ops::cnn::PaddingSpec;
+        use crate::ops::nn::DataFormat;
+        use crate::ops::quant::{QConv, QParams};
+        let mut model = InferenceModel::default();
+        let input = model
+            .add_source("input", TensorFact::dt_shape(u8::datum_type(), vec![1, 2, 4]))
+            .unwrap();
+        let conv = QConv::new(
+            DataFormat::NCHW,
+            PaddingSpec::Valid,
+            tvec!(1),
+            tvec!(1),
+            1,
+            rctensor3(&[[[2i8, 3], [1, -1]], [[5, 7], [0, 2]]]),
+            None,
+            QParams::new(DatumType::U8, tvec!(1, 4), 2, 100, tvec!(1.0, 0.5)),
+        );
+        let conv = model.add_node_default("conv", conv).unwrap();
+        model.add_edge(OutletId::new(input, 0), InletId::new(conv, 0)).unwrap();
+        model.auto_outputs().unwrap();
+        let model = model.into_optimized().unwrap();
+        assert!(model.node(1).op_is::<crate::ops::quant::QConvPacked<i8>>());
+        round_trip(model, tensor3(&[[[3u8, 4, 5, 6], [9, 8, 7, 6]]])).unwrap()
+    }
+
+    #[test]
+    fn unsupported_op() {
+        let mut model = TypedModel::default();
+        let fact = TypedTensorInfo::dt_shape(f32::datum_type(), &[3usize][..]).unwrap();
+        let source = model.add_source("input", fact.clone()).unwrap();
+        let dummy = crate::ops::dummy::Dummy::new();
+        model.chain_after(OutletId::new(source, 0), "dummy", dummy, tvec!(fact)).unwrap();
+        model.auto_outputs().unwrap();
+        let mut buffer = vec![];
+        assert!(save(&model, &mut buffer).is_err());
+    }
+}
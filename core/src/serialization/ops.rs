@@ -0,0 +1,639 @@
+use crate::internal::*;
+use ndarray::ArrayD;
+
+use tract_linalg::frame::mmm::{FusedSpec, MatMatMul};
+
+use super::OpRegistry;
+use crate::ops;
+use crate::ops::binary::BinMiniOp;
+use crate::ops::cnn::conv::{DepthWise, Direct, Im2Col, KernelFormat, MatMat, VecMat};
+use crate::ops::cnn::deconv::DeconvMatMat;
+use crate::ops::cnn::{AvgPoolFixed, MaxPoolFixed, PatchSpec};
+use crate::ops::math::mat_mul::{Geo, MatMulUnaryFinite};
+use crate::ops::nn::{DataFormat, DataShape};
+use crate::ops::quant::{QConvPacked, QParams};
+
+pub(super) fn register_core_ops(reg: &mut OpRegistry) {
+    reg.register_serde::<ops::source::TypedSource>("Source");
+    reg.register_serde::<ops::konst::Const>("Const");
+    reg.register_serde::<ops::identity::Identity>("Identity");
+    reg.register_serde::<ops::cast::Cast>("Cast");
+    reg.register_serde::<ops::Downsample>("Downsample");
+
+    reg.register_serde::<ops::array::AddDims>("AddDims");
+    reg.register_serde::<ops::array::RmDims>("RmDims");
+    reg.register_serde::<ops::array::PermuteAxes>("PermuteAxes");
+    reg.register_serde::<ops::array::IntoShape>("IntoShape");
+    reg.register_serde::<ops::array::Reshape>("Reshape");
+    reg.register_serde::<ops::array::Flatten>("Flatten");
+    reg.register_serde::<ops::array::Squeeze>("Squeeze");
+    reg.register_serde::<ops::array::Split>("Split");
+    reg.register_serde::<ops::array::Slice<usize>>("Slice<usize>");
+    reg.register_serde::<ops::array::Slice<TDim>>("Slice<TDim>");
+    reg.register_serde::<ops::array::Pad>("Pad");
+    reg.register_serde::<ops::array::TypedMultiBroadcastTo>("MultiBroadcastTo");
+    reg.register_serde::<ops::array::NormConcat>("NormConcat");
+    reg.register_serde::<ops::array::GatherElements>("GatherElements");
+    reg.register_serde::<ops::array::GatherNd>("GatherNd");
+    reg.register_serde::<ops::array::ScatterElements>("ScatterElements");
+    reg.register_serde::<ops::array::ScatterNd>("ScatterNd");
+    reg.register_serde::<ops::array::OneHot>("OneHot");
+
+    reg.register_serde::<ops::nn::ArgMaxMin>("ArgMaxMin");
+    reg.register_serde::<ops::nn::DepthToSpace>("DepthToSpace");
+    reg.register_serde::<ops::nn::SpaceToDepth>("SpaceToDepth");
+    reg.register_serde::<ops::nn::LayerHardmax>("LayerHardmax");
+    reg.register_serde::<ops::nn::LayerLogSoftmax>("LayerLogSoftmax");
+    reg.register_serde::<ops::nn::LayerSoftmax>("LayerSoftmax");
+    reg.register_serde::<ops::nn::TypedReduce>("Reduce");
+    reg.register_serde::<ops::nn::TypedResize>("Resize");
+    reg.register_serde::<ops::nn::TopK>("TopK");
+    reg.register_serde::<ops::nn::NonMaxSuppression>("NonMaxSuppression");
+    reg.register_serde::<ops::nn::InstanceNormalization>("InstanceNormalization");
+    reg.register_serde::<ops::nn::LayerNorm>("LayerNorm");
+    reg.register_serde::<ops::nn::LpNormalization>("LpNormalization");
+    reg.register_serde::<ops::nn::MeanVarianceNormalization>("MeanVarianceNormalization");
+
+    reg.register_serde::<ops::math::Einsum>("Einsum");
+    reg.register_serde::<ops::math::MatMul>("MatMul");
+    reg.register_serde::<ops::math::mat_mul::MatMulUnary>("MatMulUnary");
+    reg.register_serde::<ops::cnn::ConvUnary>("ConvUnary");
+    reg.register_serde::<ops::cnn::DeconvUnary>("DeconvUnary");
+    reg.register_serde::<ops::cnn::MaxPool>("MaxPool");
+    reg.register_serde::<ops::cnn::AvgPool>("AvgPool");
+    reg.register_serde::<ops::quant::QuantizeLinear>("QuantizeLinear");
+    reg.register_serde::<ops::quant::DequantizeLinear>("DequantizeLinear");
+    reg.register_serde::<ops::quant::QMatMul>("QMatMul");
+    reg.register_serde::<ops::quant::QConv>("QConv");
+
+    macro_rules! default {
+        ($($path:ident :: $op:ident),*) => {
+            $( reg.register_default::<ops::$path::$op>(stringify!($op)); )*
+        }
+    }
+    default!(logic::Iff, logic::Not);
+    default!(nn::Softplus, nn::Softsign, nn::Sigmoid, nn::GlobalAvgPool, nn::GlobalMaxPool);
+    default!(math::Abs, math::Exp, math::Ln, math::Sqrt, math::Recip, math::Rsqrt);
+    default!(math::Square, math::Ceil, math::Floor, math::Neg, math::Sign, math::IsNan);
+    default!(math::Cos, math::Sin, math::Tan, math::Acos, math::Asin, math::Atan);
+    default!(math::Cosh, math::Sinh, math::Tanh, math::Acosh, math::Asinh, math::Atanh);
+
+    macro_rules! serde {
+        ($($path:ident :: $op:ident),*) => {
+            $( reg.register_serde::<ops::$path::$op>(stringify!($op)); )*
+        }
+    }
+    serde!(math::ScalarMin, math::ScalarMax, math::ScalarMinMax);
+    serde!(nn::Elu, nn::Hardsigmoid, nn::LeakyRelu, nn::ParametricSoftplus);
+    serde!(nn::ScaledTanh, nn::Selu, nn::ThresholdedRelu);
+
+    reg.register(
+        "TypedBinOp",
+        |op: &ops::binary::TypedBinOp| Ok(op.0.name().to_string()),
+        |name| Ok(ops::binary::TypedBinOp(mini_op(&name)?)),
+    );
+    reg.register(
+        "MergeOp",
+        |op: &ops::binary::MergeOp| Ok(op.0.name().to_string()),
+        |name| Ok(ops::binary::MergeOp(mini_op(&name)?)),
+    );
+    reg.register(
+        "MergeOpUnicast",
+        |op: &ops::binary::MergeOpUnicast| Ok(op.0.name().to_string()),
+        |name| Ok(ops::binary::MergeOpUnicast(mini_op(&name)?)),
+    );
+    reg.register(
+        "UnaryOp",
+        |op: &ops::binary::UnaryOp| Ok((op.mini_op.name().to_string(), op.a.clone())),
+        |(name, a): (String, Arc<Tensor>)| Ok(ops::binary::UnaryOp::new(mini_op(&name)?, a)),
+    );
+
+    reg.register("MatMulUnaryFinite<f32>", save_mat_mul_unary_finite, load_mat_mul_unary_finite);
+    reg.register("MatMat<f32>", save_mat_mat, load_mat_mat);
+    reg.register("VecMat<f32>", save_vec_mat, load_vec_mat);
+    reg.register("Im2Col<f32>", save_im2col, load_im2col);
+    reg.register("Direct", save_direct, load_direct);
+    reg.register("DepthWise<f32>", save_depth_wise, load_depth_wise);
+    reg.register("DeconvMatMat<f32>", save_deconv_mat_mat, load_deconv_mat_mat);
+    reg.register("MaxPoolFixed<f32>", save_max_pool_fixed, load_max_pool_fixed);
+    reg.register("AvgPoolFixed<f32>", save_avg_pool_fixed, load_avg_pool_fixed);
+    reg.register("QConvPacked<u8>", save_q_conv_packed::<u8>, load_q_conv_packed::<u8>);
+    reg.register("QConvPacked<i8>", save_q_conv_packed::<i8>, load_q_conv_packed::<i8>);
+}
+
+fn mini_op(name: &str) -> TractResult<Box<dyn BinMiniOp>> {
+    use crate::ops::{logic, math};
+    let op: Box<dyn BinMiniOp> = match name {
+        "Add" => Box::new(math::Add),
+        "Sub" => Box::new(math::Sub),
+        "Mul" => Box::new(math::Mul),
+        "Div" => Box::new(math::Div),
+        "Rem" => Box::new(math::Rem),
+        "Min" => Box::new(math::Min),
+        "Max" => Box::new(math::Max),
+        "Pow" => Box::new(math::Pow),
+        "SquaredDifference" => Box::new(math::SquaredDifference),
+        "And" => Box::new(logic::And),
+        "Or" => Box::new(logic::Or),
+        "Xor" => Box::new(logic::Xor),
+        "Equals" => Box::new(logic::Equals),
+        "Lesser" => Box::new(logic::Lesser),
+        "LesserEqual" => Box::new(logic::LesserEqual),
+        "Greater" => Box::new(logic::Greater),
+        "GreaterEqual" => Box::new(logic::GreaterEqual),
+        _ => bail!("Unknown binary operator {}", name),
+    };
+    Ok(op)
+}
+
+/// Kernels are picked at runtime depending on the CPU. Packed operands are
+/// only valid for the kernel geometry they were packed for, so we record it
+/// and refuse to load on a mismatch.
+fn check_kernel(saved: &str, found: String) -> TractResult<()> {
+    if saved != found {
+        bail!("Model was optimized for kernel {}, this platform provides {}", saved, found)
+    }
+    Ok(())
+}
+
+fn mmm_signature(mm: &dyn MatMatMul<f32>) -> String {
+    format!("{:?} {:?}", mm.a_pack(), mm.b_pack())
+}
+
+/// Deserialized tensors are aligned for their datum type only. Packed
+/// operands need the kernel alignment.
+fn realign<T: Datum + Copy>(tensor: &Tensor, alignment: usize) -> TractResult<Tensor> {
+    let mut aligned = unsafe { Tensor::uninitialized_aligned::<T>(tensor.shape(), alignment)? };
+    aligned.as_slice_mut::<T>()?.copy_from_slice(tensor.as_slice::<T>()?);
+    Ok(aligned)
+}
+
+#[derive(Serialize, Deserialize)]
+struct DataShapeSnapshot {
+    fmt: DataFormat,
+    shape: TVec<usize>,
+}
+
+impl<'a> From<&'a DataShape> for DataShapeSnapshot {
+    fn from(shape: &'a DataShape) -> DataShapeSnapshot {
+        DataShapeSnapshot { fmt: shape.fmt, shape: shape.shape.clone() }
+    }
+}
+
+impl DataShapeSnapshot {
+    fn into_data_shape(self) -> DataShape {
+        self.fmt.shape(self.shape)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum FusedSpecSnapshot {
+    Min(f32),
+    Max(f32),
+    AddC,
+    PerRowMul(Vec<f32>),
+    PerRowAdd(Vec<f32>),
+    PerColMul(Vec<f32>),
+    PerColAdd(Vec<f32>),
+}
+
+fn save_fused(specs: &[FusedSpec<f32>]) -> Vec<FusedSpecSnapshot> {
+    specs
+        .iter()
+        .map(|spec| match spec {
+            FusedSpec::Min(m) => FusedSpecSnapshot::Min(*m),
+            FusedSpec::Max(m) => FusedSpecSnapshot::Max(*m),
+            FusedSpec::AddC => FusedSpecSnapshot::AddC,
+            FusedSpec::PerRowMul(v) => FusedSpecSnapshot::PerRowMul(v.clone()),
+            FusedSpec::PerRowAdd(v) => FusedSpecSnapshot::PerRowAdd(v.clone()),
+            FusedSpec::PerColMul(v) => FusedSpecSnapshot::PerColMul(v.clone()),
+            FusedSpec::PerColAdd(v) => FusedSpecSnapshot::PerColAdd(v.clone()),
+        })
+        .collect()
+}
+
+fn load_fused(specs: Vec<FusedSpecSnapshot>) -> Vec<FusedSpec<f32>> {
+    specs
+        .into_iter()
+        .map(|spec| match spec {
+            FusedSpecSnapshot::Min(m) => FusedSpec::Min(m),
+            FusedSpecSnapshot::Max(m) => FusedSpec::Max(m),
+            FusedSpecSnapshot::AddC => FusedSpec::AddC,
+            FusedSpecSnapshot::PerRowMul(v) => FusedSpec::PerRowMul(v),
+            FusedSpecSnapshot::PerRowAdd(v) => FusedSpec::PerRowAdd(v),
+            FusedSpecSnapshot::PerColMul(v) => FusedSpec::PerColMul(v),
+            FusedSpecSnapshot::PerColAdd(v) => FusedSpec::PerColAdd(v),
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize)]
+struct MatMulUnaryFiniteSnapshot {
+    packed_as_shape: Vec<usize>,
+    packed_as: Vec<Tensor>,
+    a_shape: TVec<usize>,
+    b_shape: TVec<usize>,
+    a_trans: bool,
+    b_trans: bool,
+    c_trans: bool,
+    kernel: String,
+    non_linear: Vec<FusedSpecSnapshot>,
+}
+
+fn save_mat_mul_unary_finite(
+    op: &MatMulUnaryFinite<f32>,
+) -> TractResult<MatMulUnaryFiniteSnapshot> {
+    Ok(MatMulUnaryFiniteSnapshot {
+        packed_as_shape: op.packed_as.shape().to_vec(),
+        packed_as: op.packed_as.iter().cloned().collect(),
+        a_shape: op.geo.a_shape.clone(),
+        b_shape: op.geo.b_shape.clone(),
+        a_trans: op.geo.a_trans,
+        b_trans: op.geo.b_trans,
+        c_trans: op.geo.c_trans,
+        kernel: mmm_signature(&*op.geo.mm),
+        non_linear: save_fused(&op.non_linear),
+    })
+}
+
+fn load_mat_mul_unary_finite(
+    snap: MatMulUnaryFiniteSnapshot,
+) -> TractResult<MatMulUnaryFinite<f32>> {
+    let geo =
+        Geo::<f32>::new(&snap.a_shape, &snap.b_shape, snap.a_trans, snap.b_trans, snap.c_trans)?;
+    check_kernel(&snap.kernel, mmm_signature(&*geo.mm))?;
+    let alignment = geo.mm.a_pack().alignment();
+    let packed_as = snap
+        .packed_as
+        .iter()
+        .map(|t| realign::<f32>(t, alignment))
+        .collect::<TractResult<Vec<_>>>()?;
+    let packed_as = ArrayD::from_shape_vec(&*snap.packed_as_shape, packed_as)?;
+    Ok(MatMulUnaryFinite { packed_as, geo, non_linear: load_fused(snap.non_linear) })
+}
+
+#[derive(Serialize, Deserialize)]
+struct MatMatSnapshot {
+    patch: PatchSpec,
+    output_shape: DataShapeSnapshot,
+    m: usize,
+    k: usize,
+    n: usize,
+    kernel_fmt: KernelFormat,
+    packed_kernels: Vec<Tensor>,
+    group: usize,
+    kernel: String,
+    non_linear: Vec<FusedSpecSnapshot>,
+}
+
+fn save_mat_mat(op: &MatMat<f32>) -> TractResult<MatMatSnapshot> {
+    Ok(MatMatSnapshot {
+        patch: op.patch.spec.clone(),
+        output_shape: (&op.output_shape).into(),
+        m: op.m,
+        k: op.k,
+        n: op.n,
+        kernel_fmt: op.kernel_fmt,
+        packed_kernels: op.packed_kernels.clone(),
+        group: op.group,
+        kernel: mmm_signature(&*op.tile),
+        non_linear: save_fused(&op.non_linear),
+    })
+}
+
+fn load_mat_mat(snap: MatMatSnapshot) -> TractResult<MatMat<f32>> {
+    let tile = f32::mmm(snap.m, snap.k, snap.n);
+    check_kernel(&snap.kernel, mmm_signature(&*tile))?;
+    let alignment = tile.a_pack().alignment();
+    let packed_kernels = snap
+        .packed_kernels
+        .iter()
+        .map(|t| realign::<f32>(t, alignment))
+        .collect::<TractResult<Vec<_>>>()?;
+    Ok(MatMat::new(
+        snap.patch.into_patch(),
+        snap.output_shape.into_data_shape(),
+        snap.m,
+        snap.k,
+        snap.n,
+        snap.kernel_fmt,
+        packed_kernels,
+        snap.group,
+        tile,
+        load_fused(snap.non_linear),
+    ))
+}
+
+#[derive(Serialize, Deserialize)]
+struct VecMatSnapshot {
+    patch: PatchSpec,
+    output_shape: DataShapeSnapshot,
+    k: usize,
+    n: usize,
+    kernel_fmt: KernelFormat,
+    packed_kernels: Vec<Tensor>,
+    group: usize,
+    kernel: String,
+}
+
+fn save_vec_mat(op: &VecMat<f32>) -> TractResult<VecMatSnapshot> {
+    Ok(VecMatSnapshot {
+        patch: op.patch.spec.clone(),
+        output_shape: (&op.output_shape).into(),
+        k: op.k,
+        n: op.n,
+        kernel_fmt: op.kernel_fmt,
+        packed_kernels: op.packed_kernels.clone(),
+        group: op.group,
+        kernel: format!("{:?}", op.vmm),
+    })
+}
+
+fn load_vec_mat(snap: VecMatSnapshot) -> TractResult<VecMat<f32>> {
+    let vmm = f32::packed_vec_mat_mul(snap.k, snap.n);
+    check_kernel(&snap.kernel, format!("{:?}", vmm))?;
+    let packed_kernels = snap
+        .packed_kernels
+        .iter()
+        .map(|t| realign::<f32>(t, vmm.packed_a_alignment()))
+        .collect::<TractResult<Vec<_>>>()?;
+    Ok(VecMat::new(
+        snap.patch.into_patch(),
+        snap.output_shape.into_data_shape(),
+        snap.k,
+        snap.n,
+        snap.kernel_fmt,
+        packed_kernels,
+        snap.group,
+        vmm,
+    ))
+}
+
+#[derive(Serialize, Deserialize)]
+struct Im2ColSnapshot {
+    patch: PatchSpec,
+    input_shape: DataShapeSnapshot,
+    m: usize,
+    k: usize,
+    n: usize,
+    group: usize,
+    ci_per_group: usize,
+    b_pack: String,
+}
+
+fn save_im2col(op: &Im2Col<f32>) -> TractResult<Im2ColSnapshot> {
+    Ok(Im2ColSnapshot {
+        patch: op.patch.spec.clone(),
+        input_shape: (&op.input_shape).into(),
+        m: op.m,
+        k: op.k,
+        n: op.n,
+        group: op.group,
+        ci_per_group: op.ci_per_group,
+        b_pack: format!("{:?}", op.b_pack),
+    })
+}
+
+fn load_im2col(snap: Im2ColSnapshot) -> TractResult<Im2Col<f32>> {
+    // same kernel choice as ConvUnary::to_im2col_pair
+    let b_pack = if snap.m > 1 {
+        f32::mmm(snap.m, snap.k, snap.n).b_pack()
+    } else {
+        f32::packed_vec_mat_mul(snap.k, snap.n).b_pack()
+    };
+    check_kernel(&snap.b_pack, format!("{:?}", b_pack))?;
+    Ok(Im2Col::new(
+        snap.patch.into_patch(),
+        snap.input_shape.into_data_shape(),
+        snap.m,
+        snap.k,
+        snap.n,
+        snap.group,
+        snap.ci_per_group,
+        b_pack,
+    ))
+}
+
+#[derive(Serialize, Deserialize)]
+struct DirectSnapshot {
+    m: usize,
+    k: usize,
+    n: usize,
+    kernel: String,
+    data_offsets: Vec<isize>,
+    kernel_offsets: Vec<isize>,
+    input_shape: DataShapeSnapshot,
+    output_shape: DataShapeSnapshot,
+    packed_filters: Tensor,
+    fused_ops: Vec<FusedSpecSnapshot>,
+}
+
+fn save_direct(op: &Direct) -> TractResult<DirectSnapshot> {
+    Ok(DirectSnapshot {
+        m: op.tile.m(),
+        k: op.tile.k(),
+        n: op.tile.n(),
+        kernel: mmm_signature(&*op.tile),
+        data_offsets: op.data_offsets.clone(),
+        kernel_offsets: op.kernel_offsets.clone(),
+        input_shape: (&op.input_shape).into(),
+        output_shape: (&op.output_shape).into(),
+        packed_filters: op.packed_filters.clone(),
+        fused_ops: save_fused(&op.fused_ops),
+    })
+}
+
+fn load_direct(snap: DirectSnapshot) -> TractResult<Direct> {
+    let tile = f32::mmm(snap.m, snap.k, snap.n);
+    check_kernel(&snap.kernel, mmm_signature(&*tile))?;
+    let packed_filters = realign::<f32>(&snap.packed_filters, tile.a_pack().alignment())?;
+    Ok(Direct::new(
+        tile,
+        snap.data_offsets,
+        snap.kernel_offsets,
+        snap.input_shape.into_data_shape(),
+        snap.output_shape.into_data_shape(),
+        packed_filters,
+        load_fused(snap.fused_ops),
+    ))
+}
+
+#[derive(Serialize, Deserialize)]
+struct DepthWiseSnapshot {
+    patch: PatchSpec,
+    input_shape: DataShapeSnapshot,
+    output_shape: DataShapeSnapshot,
+    kernel_chw: Tensor,
+}
+
+fn save_depth_wise(op: &DepthWise<f32>) -> TractResult<DepthWiseSnapshot> {
+    Ok(DepthWiseSnapshot {
+        patch: op.patch.spec.clone(),
+        input_shape: (&op.input_shape).into(),
+        output_shape: (&op.output_shape).into(),
+        kernel_chw: op.kernel_chw.clone().into(),
+    })
+}
+
+fn load_depth_wise(snap: DepthWiseSnapshot) -> TractResult<DepthWise<f32>> {
+    Ok(DepthWise::new(
+        snap.patch.into_patch(),
+        snap.input_shape.into_data_shape(),
+        snap.output_shape.into_data_shape(),
+        snap.kernel_chw.into_array::<f32>()?,
+    ))
+}
+
+#[derive(Serialize, Deserialize)]
+struct DeconvMatMatSnapshot {
+    patch: PatchSpec,
+    input_shape: DataShapeSnapshot,
+    output_shape: DataShapeSnapshot,
+    packed_kernels: Vec<Tensor>,
+    group: usize,
+    m: usize,
+    k: usize,
+    n: usize,
+    kernel: String,
+}
+
+fn save_deconv_mat_mat(op: &DeconvMatMat<f32>) -> TractResult<DeconvMatMatSnapshot> {
+    Ok(DeconvMatMatSnapshot {
+        patch: op.patch.spec.clone(),
+        input_shape: (&op.input_shape).into(),
+        output_shape: (&op.output_shape).into(),
+        packed_kernels: op.packed_kernels.clone(),
+        group: op.group,
+        m: op.mm.m(),
+        k: op.mm.k(),
+        n: op.mm.n(),
+        kernel: mmm_signature(&*op.mm),
+    })
+}
+
+fn load_deconv_mat_mat(snap: DeconvMatMatSnapshot) -> TractResult<DeconvMatMat<f32>> {
+    let mm = f32::mmm(snap.m, snap.k, snap.n);
+    check_kernel(&snap.kernel, mmm_signature(&*mm))?;
+    let alignment = mm.a_pack().alignment();
+    let packed_kernels = snap
+        .packed_kernels
+        .iter()
+        .map(|t| realign::<f32>(t, alignment))
+        .collect::<TractResult<Vec<_>>>()?;
+    Ok(DeconvMatMat::new(
+        snap.patch.into_patch(),
+        snap.input_shape.into_data_shape(),
+        snap.output_shape.into_data_shape(),
+        packed_kernels,
+        snap.group,
+        mm,
+    ))
+}
+
+#[derive(Serialize, Deserialize)]
+struct MaxPoolFixedSnapshot {
+    patch: PatchSpec,
+    input_shape: DataShapeSnapshot,
+    output_shape: DataShapeSnapshot,
+    with_index_outputs: Option<DatumType>,
+}
+
+fn save_max_pool_fixed(op: &MaxPoolFixed<f32>) -> TractResult<MaxPoolFixedSnapshot> {
+    Ok(MaxPoolFixedSnapshot {
+        patch: op.patch.spec.clone(),
+        input_shape: (&op.input_shape).into(),
+        output_shape: (&op.output_shape).into(),
+        with_index_outputs: op.with_index_outputs,
+    })
+}
+
+fn load_max_pool_fixed(snap: MaxPoolFixedSnapshot) -> TractResult<MaxPoolFixed<f32>> {
+    Ok(MaxPoolFixed::new(
+        snap.patch.into_patch(),
+        snap.input_shape.into_data_shape(),
+        snap.output_shape.into_data_shape(),
+        snap.with_index_outputs,
+    ))
+}
+
+#[derive(Serialize, Deserialize)]
+struct AvgPoolFixedSnapshot {
+    patch: PatchSpec,
+    input_shape: DataShapeSnapshot,
+    output_shape: DataShapeSnapshot,
+    count_include_pad: bool,
+}
+
+fn save_avg_pool_fixed(op: &AvgPoolFixed<f32>) -> TractResult<AvgPoolFixedSnapshot> {
+    Ok(AvgPoolFixedSnapshot {
+        patch: op.patch.spec.clone(),
+        input_shape: (&op.input_shape).into(),
+        output_shape: (&op.output_shape).into(),
+        count_include_pad: op.count_include_pad,
+    })
+}
+
+fn load_avg_pool_fixed(snap: AvgPoolFixedSnapshot) -> TractResult<AvgPoolFixed<f32>> {
+    Ok(AvgPoolFixed::new(
+        snap.patch.into_patch(),
+        snap.input_shape.into_data_shape(),
+        snap.output_shape.into_data_shape(),
+        snap.count_include_pad,
+    ))
+}
+
+#[derive(Serialize, Deserialize)]
+struct QConvPackedSnapshot {
+    patch: PatchSpec,
+    input_shape: DataShapeSnapshot,
+    output_shape: DataShapeSnapshot,
+    group: usize,
+    m: usize,
+    k: usize,
+    n: usize,
+    packed_kernels: Vec<Tensor>,
+    a_row_sums: Vec<Vec<i32>>,
+    bias: Option<Arc<Tensor>>,
+    params: QParams,
+    kernel: String,
+}
+
+fn save_q_conv_packed<TA: QuantizedLike>(op: &QConvPacked<TA>) -> TractResult<QConvPackedSnapshot> {
+    Ok(QConvPackedSnapshot {
+        patch: op.patch.spec.clone(),
+        input_shape: (&op.input_shape).into(),
+        output_shape: (&op.output_shape).into(),
+        group: op.group,
+        m: op.mm.m(),
+        k: op.mm.k(),
+        n: op.mm.n(),
+        packed_kernels: op.packed_kernels.clone(),
+        a_row_sums: op.a_row_sums.clone(),
+        bias: op.bias.clone(),
+        params: op.params.clone(),
+        kernel: format!("{:?} {:?}", op.mm.a_pack(), op.mm.b_pack()),
+    })
+}
+
+fn load_q_conv_packed<TA: QuantizedLike>(
+    snap: QConvPackedSnapshot,
+) -> TractResult<QConvPacked<TA>> {
+    let mm = TA::qmmm(snap.m, snap.k, snap.n);
+    check_kernel(&snap.kernel, format!("{:?} {:?}", mm.a_pack(), mm.b_pack()))?;
+    let packed_kernels = snap
+        .packed_kernels
+        .iter()
+        .map(|t| realign::<TA>(t, mm.a_pack().alignment()))
+        .collect::<TractResult<Vec<_>>>()?;
+    Ok(QConvPacked::new(
+        snap.patch.into_patch(),
+        snap.input_shape.into_data_shape(),
+        snap.output_shape.into_data_shape(),
+        snap.group,
+        packed_kernels,
+        snap.a_row_sums,
+        snap.bias,
+        snap.params,
+        mm,
+    ))
+}
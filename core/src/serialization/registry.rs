@@ -0,0 +1,94 @@
+use crate::internal::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::any::TypeId;
+
+/// Serialized form of an operator: the key it was registered with, and its
+/// bincode-encoded parameters.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OpSnapshot {
+    pub key: String,
+    pub payload: Vec<u8>,
+}
+
+type Saver = Box<dyn Fn(&dyn TypedOp) -> TractResult<OpSnapshot> + Send + Sync>;
+type Loader = Box<dyn Fn(&[u8]) -> TractResult<Box<dyn TypedOp>> + Send + Sync>;
+
+/// Maps operator types to stable keys and (de)serialization functions.
+///
+/// `OpRegistry::core()` covers the operators found in decluttered and
+/// optimized models built by tract-core. Framework crates can register
+/// their own operators on top of it.
+#[derive(Default)]
+pub struct OpRegistry {
+    savers: HashMap<TypeId, Saver>,
+    loaders: HashMap<String, Loader>,
+}
+
+impl OpRegistry {
+    /// A registry populated with tract-core operators.
+    pub fn core() -> OpRegistry {
+        let mut reg = OpRegistry::default();
+        super::ops::register_core_ops(&mut reg);
+        reg
+    }
+
+    /// Register an operator going through a serializable proxy type `P`.
+    pub fn register<O, P>(
+        &mut self,
+        key: &'static str,
+        save: fn(&O) -> TractResult<P>,
+        load: fn(P) -> TractResult<O>,
+    ) where
+        O: TypedOp,
+        P: Serialize + DeserializeOwned + 'static,
+    {
+        self.savers.insert(
+            TypeId::of::<O>(),
+            Box::new(move |op: &dyn TypedOp| {
+                let op = op.as_op().downcast_ref::<O>().ok_or("Inconsistent op registry")?;
+                let payload = bincode::serialize(&save(op)?)?;
+                Ok(OpSnapshot { key: key.to_string(), payload })
+            }),
+        );
+        self.loaders.insert(
+            key.to_string(),
+            Box::new(move |payload: &[u8]| {
+                let proxy: P = bincode::deserialize(payload)?;
+                Ok(Box::new(load(proxy)?) as Box<dyn TypedOp>)
+            }),
+        );
+    }
+
+    /// Register an operator that is its own serializable form.
+    pub fn register_serde<O>(&mut self, key: &'static str)
+    where
+        O: TypedOp + Clone + Serialize + DeserializeOwned,
+    {
+        self.register::<O, O>(key, |op| Ok(op.clone()), |op| Ok(op))
+    }
+
+    /// Register a parameter-less operator, rebuilt with `Default` on load.
+    pub fn register_default<O>(&mut self, key: &'static str)
+    where
+        O: TypedOp + Default,
+    {
+        self.register::<O, ()>(key, |_| Ok(()), |_| Ok(O::default()))
+    }
+
+    pub fn save_op(&self, op: &dyn TypedOp) -> TractResult<OpSnapshot> {
+        let saver = self
+            .savers
+            .get(&op.as_op().as_any().type_id())
+            .ok_or_else(|| format!("Serialization not supported for operator {}", op.name()))?;
+        saver(op)
+    }
+
+    pub fn load_op(&self, snapshot: &OpSnapshot) -> TractResult<Box<dyn TypedOp>> {
+        let loader = self
+            .loaders
+            .get(&snapshot.key)
+            .ok_or_else(|| format!("No operator registered as {}", snapshot.key))?;
+        loader(&snapshot.payload).chain_err(|| format!("Failed to load operator {}", snapshot.key))
+    }
+}
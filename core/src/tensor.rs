@@ -7,6 +7,8 @@ use std::mem::{align_of, size_of};
 
 use tract_linalg::f16::f16;
 
+#[cfg(feature = "serialize")]
+use serde::de::{Deserialize, Deserializer};
 #[cfg(feature = "serialize")]
 use serde::ser::{Serialize, Serializer};
 use std::sync::Arc;
@@ -16,6 +18,8 @@ pub mod litteral;
 /// Tensor is a concrete tensor in tract.
 pub struct Tensor {
     null: bool,
+    /// Data lives in a memory plan arena, and is not freed with the tensor.
+    arena: bool,
     dt: DatumType,
     shape: TVec<usize>,
     layout: alloc::Layout,
@@ -43,7 +47,7 @@ impl Clone for Tensor {
             unsafe {
                 let data = alloc::alloc(self.layout) as *mut u8;
                 self.data.copy_to_nonoverlapping(data, self.layout.size());
-                Tensor { data, shape: self.shape.clone(), ..*self }
+                Tensor { data, arena: false, shape: self.shape.clone(), ..*self }
             }
         }
     }
@@ -73,7 +77,7 @@ impl Drop for Tensor {
                     .for_each(|s| std::ptr::drop_in_place(s as *mut TDim));
             }
         }
-        if !self.data.is_null() && self.layout.size() > 0 {
+        if !self.arena && !self.data.is_null() && self.layout.size() > 0 {
             unsafe { alloc::dealloc(self.data, self.layout) }
         }
     }
@@ -109,10 +113,25 @@ impl Tensor {
         } else if dt == TDim::datum_type() {
             return Ok(ndarray::ArrayD::<TDim>::default(shape).into());
         }
+        if let Some(data) = crate::memory_plan::take_slot(dt, shape, alignment) {
+            return Self::from_arena(dt, shape, data, alignment);
+        }
         let bytes = shape.iter().cloned().product::<usize>() * dt.size_of();
         let layout = alloc::Layout::from_size_align(bytes, alignment)?;
         let data = if bytes == 0 { std::ptr::null() } else { alloc::alloc(layout) } as *mut u8;
-        Ok(Tensor { null: false, layout, dt, shape: shape.into(), data })
+        Ok(Tensor { null: false, arena: false, layout, dt, shape: shape.into(), data })
+    }
+
+    /// Create an uninitialized tensor over a memory plan arena slot.
+    pub(crate) unsafe fn from_arena(
+        dt: DatumType,
+        shape: &[usize],
+        data: *mut u8,
+        alignment: usize,
+    ) -> TractResult<Tensor> {
+        let bytes = shape.iter().cloned().product::<usize>() * dt.size_of();
+        let layout = alloc::Layout::from_size_align(bytes, alignment)?;
+        Ok(Tensor { null: false, arena: true, layout, dt, shape: shape.into(), data })
     }
 
     /// Create an tensor from raw data.
@@ -131,7 +150,7 @@ impl Tensor {
         let layout = alloc::Layout::from_size_align(bytes, dt.alignment())?;
         let data = alloc::alloc(layout);
         content.as_ptr().copy_to_nonoverlapping(data, bytes);
-        Ok(Tensor { null: false, dt, shape: shape.into(), data, layout })
+        Ok(Tensor { null: false, arena: false, dt, shape: shape.into(), data, layout })
     }
 
     /// Creates a null tensor (this is rare, and should stay that way).
@@ -143,6 +162,7 @@ impl Tensor {
     pub unsafe fn null_dt(dt: DatumType, shape: &[usize]) -> TractResult<Tensor> {
         Ok(Tensor {
             null: true,
+            arena: false,
             dt,
             shape: shape.into(),
             data: std::ptr::null::<u8>() as *mut u8,
@@ -150,6 +170,11 @@ impl Tensor {
         })
     }
 
+    /// Check whether the data lives in a memory plan arena.
+    pub(crate) fn in_arena(&self) -> bool {
+        self.arena
+    }
+
     /// Check weather self is a null tensor.
     pub fn is_null(&self) -> bool {
         self.null
@@ -299,6 +324,17 @@ impl Tensor {
         self.as_ptr::<D>().map(|p| p as *mut D)
     }
 
+    /// Access the underlying buffer as raw bytes.
+    ///
+    /// Meaningless for String and TDim tensors.
+    pub fn as_bytes(&self) -> &[u8] {
+        if self.data.is_null() {
+            &[]
+        } else {
+            unsafe { std::slice::from_raw_parts(self.data, self.layout.size()) }
+        }
+    }
+
     /// Access the data as a slice.
     pub fn as_slice<D: Datum>(&self) -> TractResult<&[D]> {
         unsafe { Ok(std::slice::from_raw_parts::<D>(self.as_ptr()?, self.len())) }
@@ -364,24 +400,39 @@ impl Tensor {
             (I64, I16) => self.cast::<i64, i16>()?,
             (I32, I64) => self.cast::<i32, i64>()?,
             (I64, I32) => self.cast::<i64, i32>()?,
+            (U8, I16) => self.cast::<u8, i16>()?,
+            (U8, I32) => self.cast::<u8, i32>()?,
+            (U8, I64) => self.cast::<u8, i64>()?,
+            (U16, I32) => self.cast::<u16, i32>()?,
+            (U16, I64) => self.cast::<u16, i64>()?,
+            (I32, U8) => self.cast::<i32, u8>()?,
+            (I64, U8) => self.cast::<i64, u8>()?,
+            (I32, U16) => self.cast::<i32, u16>()?,
+            (I64, U16) => self.cast::<i64, u16>()?,
 
             (Bool, F32) => self.cast::<bool, f32>()?,
             (I8, F32) => self.cast::<i8, f32>()?,
             (I16, F32) => self.cast::<i16, f32>()?,
             (I32, F32) => self.cast::<i32, f32>()?,
             (I64, F32) => self.cast::<i64, f32>()?,
+            (U8, F32) => self.cast::<u8, f32>()?,
+            (U16, F32) => self.cast::<u16, f32>()?,
 
             (Bool, F64) => self.cast::<bool, f64>()?,
             (I8, F64) => self.cast::<i8, f64>()?,
             (I16, F64) => self.cast::<i16, f64>()?,
             (I32, F64) => self.cast::<i32, f64>()?,
             (I64, F64) => self.cast::<i64, f64>()?,
+            (U8, F64) => self.cast::<u8, f64>()?,
+            (U16, F64) => self.cast::<u16, f64>()?,
 
             (F32, Bool) => self.cast::<f32, bool>()?,
             (F32, I8) => self.cast::<f32, i8>()?,
             (F32, I16) => self.cast::<f32, i16>()?,
             (F32, I32) => self.cast::<f32, i32>()?,
             (F32, I64) => self.cast::<f32, i64>()?,
+            (F32, U8) => self.cast::<f32, u8>()?,
+            (F32, U16) => self.cast::<f32, u16>()?,
 
             (F64, Bool) => self.cast::<f64, bool>()?,
             (F64, I8) => self.cast::<f64, i8>()?,
@@ -417,7 +468,7 @@ impl Tensor {
         let layout =
             alloc::Layout::from_size_align(vec.len() * size_of::<T>(), align_of::<T>()).unwrap();
         let data = Box::into_raw(vec) as *mut u8;
-        Tensor { null: false, dt: T::datum_type(), shape, layout, data }
+        Tensor { null: false, arena: false, dt: T::datum_type(), shape, layout, data }
     }
 }
 
@@ -437,34 +488,82 @@ impl fmt::Debug for Tensor {
     }
 }
 
+#[cfg(feature = "serialize")]
+#[derive(Serialize, Deserialize)]
+enum TensorContent<'a> {
+    Raw(Cow<'a, [u8]>),
+    TDim(Cow<'a, [TDim]>),
+    String(Cow<'a, [std::string::String]>),
+}
+
+#[cfg(feature = "serialize")]
+#[derive(Serialize, Deserialize)]
+struct TensorSnapshot<'a> {
+    null: bool,
+    dt: DatumType,
+    shape: Cow<'a, [usize]>,
+    content: TensorContent<'a>,
+}
+
 #[cfg(feature = "serialize")]
 impl Serialize for Tensor {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        macro_rules! serialize_inner {
-            ($type:ident, $m:ident) => {{
-                let data =
-                    (stringify!($type), self.shape(), $m.iter().cloned().collect::<Vec<_>>());
-                data.serialize(serializer)
-            }};
+        use serde::ser::Error;
+        let content = match self.dt {
+            DatumType::TDim => {
+                TensorContent::TDim(self.as_slice::<TDim>().map_err(S::Error::custom)?.into())
+            }
+            DatumType::String => TensorContent::String(
+                self.as_slice::<std::string::String>().map_err(S::Error::custom)?.into(),
+            ),
+            _ => TensorContent::Raw(self.as_bytes().into()),
         };
+        TensorSnapshot { null: self.null, dt: self.dt, shape: (&*self.shape).into(), content }
+            .serialize(serializer)
+    }
+}
 
-        use Tensor::*;
-        match self {
-            Bool(m) => serialize_inner!(bool, m),
-            U8(m) => serialize_inner!(u8, m),
-            U16(m) => serialize_inner!(u16, m),
-            I8(m) => serialize_inner!(i8, m),
-            I16(m) => serialize_inner!(i16, m),
-            I32(m) => serialize_inner!(i32, m),
-            I64(m) => serialize_inner!(i64, m),
-            F16(m) => serialize_inner!(f16, m),
-            F32(m) => serialize_inner!(f32, m),
-            F64(m) => serialize_inner!(f64, m),
-            TDim(m) => serialize_inner!(TDim, m),
-            String(m) => serialize_inner!(str, m),
+#[cfg(feature = "serialize")]
+impl<'de> Deserialize<'de> for Tensor {
+    fn deserialize<D>(deserializer: D) -> Result<Tensor, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        use serde::de::Error;
+        let snapshot = TensorSnapshot::deserialize(deserializer)?;
+        let shape = &*snapshot.shape;
+        let len = shape.iter().cloned().product::<usize>();
+        if snapshot.null {
+            return unsafe { Tensor::null_dt(snapshot.dt, shape) }.map_err(D::Error::custom);
+        }
+        match snapshot.content {
+            TensorContent::TDim(data) if snapshot.dt == DatumType::TDim && data.len() == len => {
+                ArrayD::from_shape_vec(shape, data.into_owned())
+                    .map(Tensor::from)
+                    .map_err(D::Error::custom)
+            }
+            TensorContent::String(data)
+                if snapshot.dt == DatumType::String && data.len() == len =>
+            {
+                ArrayD::from_shape_vec(shape, data.into_owned())
+                    .map(Tensor::from)
+                    .map_err(D::Error::custom)
+            }
+            TensorContent::Raw(data) if data.len() == len * snapshot.dt.size_of() => unsafe {
+                if data.len() == 0 {
+                    Tensor::uninitialized_dt(snapshot.dt, shape)
+                } else {
+                    Tensor::from_raw_dt(snapshot.dt, shape, &data)
+                }
+                .map_err(D::Error::custom)
+            },
+            _ => Err(D::Error::custom(format!(
+                "Inconsistent tensor content for {:?} {:?}",
+                snapshot.dt, shape
+            ))),
         }
     }
 }
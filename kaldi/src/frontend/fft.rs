@@ -0,0 +1,163 @@
+use tract_core::internal::*;
+use tract_core::ndarray;
+
+use super::{frame_output_facts, frame_rules, pulsify_frame_op};
+
+/// Power (or magnitude) spectrum of each frame: the frames are zero-padded to
+/// `n` samples, and the `n / 2 + 1` non-negative frequency bins of their
+/// discrete Fourier transform are kept.
+#[derive(Clone, Debug, new)]
+pub struct RealFft {
+    n: usize,
+    power: bool,
+}
+
+impl RealFft {
+    fn bins(&self) -> usize {
+        self.n / 2 + 1
+    }
+}
+
+impl Op for RealFft {
+    fn name(&self) -> std::borrow::Cow<str> {
+        "kaldi.RealFft".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("n:{} power:{}", self.n, self.power)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for RealFft {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let frames = input.to_array_view::<f32>()?.into_dimensionality::<ndarray::Ix2>()?;
+        if frames.shape()[1] > self.n {
+            bail!("Frames of {} samples do not fit in a {} points FFT", frames.shape()[1], self.n)
+        }
+        let twiddles = twiddles(self.n);
+        let mut output = ndarray::Array2::<f32>::zeros((frames.shape()[0], self.bins()));
+        let mut buffer = vec![(0.0f32, 0.0f32); self.n];
+        for (frame, mut spectrum) in frames.outer_iter().zip(output.outer_iter_mut()) {
+            for (ix, b) in buffer.iter_mut().enumerate() {
+                *b = (frame.get(ix).cloned().unwrap_or(0.0), 0.0);
+            }
+            fft(&mut buffer, &twiddles);
+            for (s, &(re, im)) in spectrum.iter_mut().zip(buffer.iter()) {
+                let power = re * re + im * im;
+                *s = if self.power { power } else { power.sqrt() };
+            }
+        }
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl InferenceRulesOp for RealFft {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        frame_rules(s, inputs, outputs, Some(self.bins()))
+    }
+
+    inference_op_as_op!();
+    to_typed!();
+}
+
+impl TypedOp for RealFft {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        frame_output_facts(inputs, Some(self.bins()))
+    }
+
+    fn pulsify(
+        &self,
+        _source: &NormalizedModel,
+        node: &NormalizedNode,
+        target: &mut PulsedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+        _pulse: usize,
+    ) -> TractResult<TVec<OutletId>> {
+        pulsify_frame_op(self.clone(), node, target, mapping, Some(self.bins()))
+    }
+}
+
+/// exp(-2iπk/n) for k in 0..n
+fn twiddles(n: usize) -> Vec<(f32, f32)> {
+    (0..n)
+        .map(|k| {
+            let angle = -2.0 * std::f64::consts::PI * k as f64 / n as f64;
+            (angle.cos() as f32, angle.sin() as f32)
+        })
+        .collect()
+}
+
+fn mul(a: (f32, f32), b: (f32, f32)) -> (f32, f32) {
+    (a.0 * b.0 - a.1 * b.1, a.0 * b.1 + a.1 * b.0)
+}
+
+/// Discrete Fourier transform, the slow way.
+fn dft(data: &[(f32, f32)], twiddles: &[(f32, f32)]) -> Vec<(f32, f32)> {
+    let n = data.len();
+    (0..n)
+        .map(|k| {
+            data.iter().enumerate().fold((0.0, 0.0), |acc, (j, &x)| {
+                let p = mul(x, twiddles[(j * k) % n]);
+                (acc.0 + p.0, acc.1 + p.1)
+            })
+        })
+        .collect()
+}
+
+/// In place discrete Fourier transform. Radix-2 when the size is a power of
+/// two, plain DFT otherwise.
+fn fft(data: &mut [(f32, f32)], twiddles: &[(f32, f32)]) {
+    let n = data.len();
+    if !n.is_power_of_two() {
+        let transformed = dft(data, twiddles);
+        data.copy_from_slice(&transformed);
+        return;
+    }
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits().checked_shr(usize::max_value().count_ones() - bits).unwrap_or(0);
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+    let mut len = 2;
+    while len <= n {
+        let step = n / len;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let a = data[start + k];
+                let b = mul(data[start + k + len / 2], twiddles[k * step]);
+                data[start + k] = (a.0 + b.0, a.1 + b.1);
+                data[start + k + len / 2] = (a.0 - b.0, a.1 - b.1);
+            }
+        }
+        len *= 2;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fft_matches_dft() {
+        let signal: Vec<(f32, f32)> = (0..16).map(|i| ((i as f32 * 0.7).sin(), 0.0)).collect();
+        let twiddles = twiddles(16);
+        let mut radix2 = signal.clone();
+        fft(&mut radix2, &twiddles);
+        let dft = dft(&signal, &twiddles);
+        for (a, b) in radix2.iter().zip(dft.iter()) {
+            assert!((a.0 - b.0).abs() < 1e-4 && (a.1 - b.1).abs() < 1e-4, "{:?} {:?}", a, b);
+        }
+    }
+}
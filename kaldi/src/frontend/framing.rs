@@ -0,0 +1,123 @@
+use tract_core::internal::*;
+use tract_core::ndarray;
+
+/// Cuts a [samples] signal into [frames, frame_length] overlapping frames,
+/// starting every `frame_shift` samples. Like Kaldi with `--snip-edges=true`,
+/// only frames that fit completely in the signal are produced.
+#[derive(Clone, Debug, new)]
+pub struct Framing {
+    frame_length: usize,
+    frame_shift: usize,
+}
+
+impl Framing {
+    fn frames(&self, samples: TDim) -> TDim {
+        (samples - (self.frame_length - 1)).div_ceil(self.frame_shift.to_dim())
+    }
+}
+
+impl Op for Framing {
+    fn name(&self) -> std::borrow::Cow<str> {
+        "kaldi.Framing".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("length:{} shift:{}", self.frame_length, self.frame_shift)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for Framing {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let input = input.to_array_view::<f32>()?.into_dimensionality::<ndarray::Ix1>()?;
+        let frames = if input.len() < self.frame_length {
+            0
+        } else {
+            (input.len() - self.frame_length) / self.frame_shift + 1
+        };
+        let output = ndarray::Array2::from_shape_fn((frames, self.frame_length), |(f, i)| {
+            input[f * self.frame_shift + i]
+        });
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl InferenceRulesOp for Framing {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, f32::datum_type())?;
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        s.equals(&inputs[0].rank, 1)?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&outputs[0].shape[1], self.frame_length.to_dim())?;
+        s.given(&inputs[0].shape[0], move |s, samples| {
+            s.equals(&outputs[0].shape[0], self.frames(samples))
+        })
+    }
+
+    inference_op_as_op!();
+    to_typed!();
+}
+
+impl TypedOp for Framing {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        let shape = [self.frames(inputs[0].shape.dim(0)), self.frame_length.to_dim()];
+        Ok(tvec!(TypedTensorInfo::dt_shape(f32::datum_type(), shape.as_ref())?))
+    }
+
+    /// Framing is a strided convolution: pulses are given the overlap with
+    /// the previous pulse they need, and the delay is rounded so that frames
+    /// start on a pulse boundary.
+    fn pulsify(
+        &self,
+        _source: &NormalizedModel,
+        node: &NormalizedNode,
+        target: &mut PulsedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+        _pulse: usize,
+    ) -> TractResult<TVec<OutletId>> {
+        let mut input = mapping[&node.inputs[0]];
+        let fact = target.outlet_fact(input)?.clone();
+        if fact.pulse() % self.frame_shift != 0 {
+            bail!(
+                "Framing pulsification requires the pulse to be a multiple of the frame shift. Got pulse={}, shift={}",
+                fact.pulse(),
+                self.frame_shift
+            )
+        }
+        let overlap = self.frame_length.saturating_sub(self.frame_shift);
+        let mut augmented_fact = fact.clone();
+        augmented_fact.shape[0] += overlap;
+        augmented_fact.delay += overlap;
+        augmented_fact.delay = augmented_fact.delay.div_ceil(self.frame_shift) * self.frame_shift;
+
+        let mut output_fact = fact.clone();
+        output_fact.shape = tvec!(fact.pulse() / self.frame_shift, self.frame_length);
+        output_fact.delay = augmented_fact.delay / self.frame_shift;
+        output_fact.dim = self.frames(fact.dim.clone());
+
+        if augmented_fact != fact {
+            let extra_delay = augmented_fact.delay - fact.delay - overlap;
+            let delay = tract_core::pulse::delay::Delay::new(fact.clone(), extra_delay, overlap);
+            let id = target.chain_after(
+                input,
+                format!("{}/Delay", node.name),
+                delay,
+                tvec!(augmented_fact),
+            )?;
+            input = OutletId::new(id, 0);
+        }
+        let id = target.chain_after(input, &*node.name, self.clone(), tvec!(output_fact))?;
+        Ok(tvec!(OutletId::new(id, 0)))
+    }
+}
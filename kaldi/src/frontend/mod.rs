@@ -0,0 +1,433 @@
+//! Kaldi feature extraction (`compute-fbank-feats`, `compute-mfcc-feats`,
+//! `apply-cmvn`) as tract operators, so that a pulsed model can go from raw
+//! PCM samples to features to acoustic model outputs.
+//!
+//! Input is a [samples] f32 signal, with Kaldi scale (16 bits integer range).
+//! Dithering is not supported: results match Kaldi run with `--dither=0`.
+
+use tract_core::internal::*;
+
+mod fft;
+mod framing;
+mod transform;
+mod window;
+
+pub use self::fft::RealFft;
+pub use self::framing::Framing;
+pub use self::transform::{dct_matrix, mel_banks, mel_scale, FrameTransform};
+pub use self::window::{window, LogEnergy, PreEmphasis, RemoveDcOffset, WindowType};
+
+/// Framing and windowing options, with Kaldi defaults.
+#[derive(Clone, Debug)]
+pub struct FrameOptions {
+    pub sample_freq: f32,
+    pub frame_shift_ms: f32,
+    pub frame_length_ms: f32,
+    pub preemph_coeff: f32,
+    pub remove_dc_offset: bool,
+    pub window_type: WindowType,
+    pub round_to_power_of_two: bool,
+}
+
+impl Default for FrameOptions {
+    fn default() -> FrameOptions {
+        FrameOptions {
+            sample_freq: 16000.0,
+            frame_shift_ms: 10.0,
+            frame_length_ms: 25.0,
+            preemph_coeff: 0.97,
+            remove_dc_offset: true,
+            window_type: WindowType::Povey,
+            round_to_power_of_two: true,
+        }
+    }
+}
+
+impl FrameOptions {
+    pub fn window_shift(&self) -> usize {
+        (self.sample_freq * 0.001 * self.frame_shift_ms) as usize
+    }
+
+    pub fn window_size(&self) -> usize {
+        (self.sample_freq * 0.001 * self.frame_length_ms) as usize
+    }
+
+    pub fn padded_window_size(&self) -> usize {
+        if self.round_to_power_of_two {
+            self.window_size().next_power_of_two()
+        } else {
+            self.window_size()
+        }
+    }
+}
+
+/// Mel filterbank options, with Kaldi defaults for fbank and MFCC.
+#[derive(Clone, Debug)]
+pub struct MelOptions {
+    pub num_bins: usize,
+    pub low_freq: f32,
+    /// If not positive, offset from the Nyquist frequency.
+    pub high_freq: f32,
+}
+
+impl Default for MelOptions {
+    fn default() -> MelOptions {
+        MelOptions { num_bins: 23, low_freq: 20.0, high_freq: 0.0 }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FbankOptions {
+    pub frame: FrameOptions,
+    pub mel: MelOptions,
+    pub use_log_fbank: bool,
+    pub use_power: bool,
+}
+
+impl Default for FbankOptions {
+    fn default() -> FbankOptions {
+        FbankOptions {
+            frame: FrameOptions::default(),
+            mel: MelOptions::default(),
+            use_log_fbank: true,
+            use_power: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MfccOptions {
+    pub frame: FrameOptions,
+    pub mel: MelOptions,
+    pub num_ceps: usize,
+    /// Replace the first cepstral coefficient by the frame log energy.
+    pub use_energy: bool,
+    pub cepstral_lifter: f32,
+}
+
+impl Default for MfccOptions {
+    fn default() -> MfccOptions {
+        MfccOptions {
+            frame: FrameOptions::default(),
+            mel: MelOptions::default(),
+            num_ceps: 13,
+            use_energy: true,
+            cepstral_lifter: 22.0,
+        }
+    }
+}
+
+/// Wire the signal to windowed frames. Also returns the frames log energy,
+/// before pre-emphasis and windowing.
+fn wire_frames(
+    model: &mut InferenceModel,
+    name: &str,
+    input: OutletId,
+    options: &FrameOptions,
+) -> TractResult<(OutletId, OutletId)> {
+    let framing = Framing::new(options.window_size(), options.window_shift());
+    let mut wire = model.wire_node(format!("{}-Framing", name), framing, &[input])?[0];
+    if options.remove_dc_offset {
+        wire = model.wire_node(format!("{}-RemoveDcOffset", name), RemoveDcOffset, &[wire])?[0];
+    }
+    let energy = model.wire_node(format!("{}-LogEnergy", name), LogEnergy, &[wire])?[0];
+    if options.preemph_coeff != 0.0 {
+        let preemph = PreEmphasis::new(options.preemph_coeff);
+        wire = model.wire_node(format!("{}-PreEmphasis", name), preemph, &[wire])?[0];
+    }
+    let window = model.add_const(
+        format!("{}-Window", name),
+        window(options.window_type, options.window_size()),
+    )?;
+    wire = model.wire_node(
+        format!("{}-Windowing", name),
+        tract_core::ops::math::mul::bin(),
+        &[wire, OutletId::new(window, 0)],
+    )?[0];
+    Ok((wire, energy))
+}
+
+/// Wire windowed frames to mel filterbank energies.
+fn wire_mel(
+    model: &mut InferenceModel,
+    name: &str,
+    frames: OutletId,
+    frame: &FrameOptions,
+    mel: &MelOptions,
+    use_power: bool,
+) -> TractResult<OutletId> {
+    let fft_len = frame.padded_window_size();
+    let fft = RealFft::new(fft_len, use_power);
+    let spectrum = model.wire_node(format!("{}-Fft", name), fft, &[frames])?[0];
+    let banks = mel_banks(mel.num_bins, fft_len, frame.sample_freq, mel.low_freq, mel.high_freq)?;
+    let mel = FrameTransform::new(banks.into_arc_tensor());
+    Ok(model.wire_node(format!("{}-Mel", name), mel, &[spectrum])?[0])
+}
+
+fn wire_log(model: &mut InferenceModel, name: &str, input: OutletId) -> TractResult<OutletId> {
+    let floor = tract_core::ops::math::ScalarMax::new(std::f32::EPSILON);
+    let floored = model.wire_node(format!("{}-Floor", name), floor, &[input])?[0];
+    Ok(model.wire_node(
+        format!("{}-Log", name),
+        tract_core::ops::math::Ln::default(),
+        &[floored],
+    )?[0])
+}
+
+/// Filterbank features of a [samples] signal, like `compute-fbank-feats`.
+pub fn wire_fbank(
+    model: &mut InferenceModel,
+    name: &str,
+    input: OutletId,
+    options: &FbankOptions,
+) -> TractResult<OutletId> {
+    let (frames, _) = wire_frames(model, name, input, &options.frame)?;
+    let mel = wire_mel(model, name, frames, &options.frame, &options.mel, options.use_power)?;
+    if options.use_log_fbank {
+        wire_log(model, name, mel)
+    } else {
+        Ok(mel)
+    }
+}
+
+/// MFCC features of a [samples] signal, like `compute-mfcc-feats`.
+pub fn wire_mfcc(
+    model: &mut InferenceModel,
+    name: &str,
+    input: OutletId,
+    options: &MfccOptions,
+) -> TractResult<OutletId> {
+    let (frames, energy) = wire_frames(model, name, input, &options.frame)?;
+    let mel = wire_mel(model, name, frames, &options.frame, &options.mel, true)?;
+    let log = wire_log(model, name, mel)?;
+    let dct = dct_matrix(options.mel.num_bins, options.num_ceps, options.cepstral_lifter);
+    let dct = FrameTransform::new(dct.into_arc_tensor());
+    let ceps = model.wire_node(format!("{}-Dct", name), dct, &[log])?[0];
+    if !options.use_energy {
+        return Ok(ceps);
+    }
+    let slice = tract_core::ops::array::Slice::new(1, 1, options.num_ceps);
+    let ceps = model.wire_node(format!("{}-Ceps", name), slice, &[ceps])?[0];
+    let concat = tract_core::ops::array::Concat::new(1);
+    Ok(model.wire_node(format!("{}-WithEnergy", name), concat, &[energy, ceps])?[0])
+}
+
+/// Normalize [frames, dim] features with Kaldi CMVN statistics, like
+/// `apply-cmvn`.
+pub fn wire_cmvn(
+    model: &mut InferenceModel,
+    name: &str,
+    input: OutletId,
+    stats: &Tensor,
+    norm_vars: bool,
+) -> TractResult<OutletId> {
+    let op = crate::ops::scale_and_offset::cmvn(stats, norm_vars)?;
+    Ok(model.wire_node(name, op, &[input])?[0])
+}
+
+/// Rules for ops transforming [frames, n] into [frames, width] (n if None).
+fn frame_rules<'r, 'p: 'r>(
+    s: &mut Solver<'r>,
+    inputs: &'p [TensorProxy],
+    outputs: &'p [TensorProxy],
+    width: Option<usize>,
+) -> InferenceResult {
+    check_input_arity(&inputs, 1)?;
+    check_output_arity(&outputs, 1)?;
+    s.equals(&inputs[0].datum_type, f32::datum_type())?;
+    s.equals(&outputs[0].datum_type, f32::datum_type())?;
+    s.equals(&inputs[0].rank, 2)?;
+    s.equals(&outputs[0].rank, 2)?;
+    s.equals(&inputs[0].shape[0], &outputs[0].shape[0])?;
+    match width {
+        Some(width) => s.equals(&outputs[0].shape[1], width.to_dim()),
+        None => s.equals(&inputs[0].shape[1], &outputs[0].shape[1]),
+    }
+}
+
+fn frame_output_facts(
+    inputs: &[&TypedTensorInfo],
+    width: Option<usize>,
+) -> TractResult<TVec<TypedTensorInfo>> {
+    let mut fact = inputs[0].clone();
+    if let Some(width) = width {
+        fact.shape.set_dim(1, width.to_dim())?;
+    }
+    Ok(tvec!(fact))
+}
+
+/// Frames are independent from each other, so these ops work the same on
+/// pulses.
+fn pulsify_frame_op(
+    op: impl Into<Box<dyn TypedOp>>,
+    node: &NormalizedNode,
+    target: &mut PulsedModel,
+    mapping: &HashMap<OutletId, OutletId>,
+    width: Option<usize>,
+) -> TractResult<TVec<OutletId>> {
+    let input = mapping[&node.inputs[0]];
+    let mut fact = target.outlet_fact(input)?.clone();
+    if fact.axis != 0 {
+        bail!("{} expects frames to be the streaming axis", node.name)
+    }
+    if let Some(width) = width {
+        fact.shape[1] = width;
+    }
+    let id = target.chain_after(input, &*node.name, op, tvec!(fact))?;
+    Ok(tvec!(OutletId::new(id, 0)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tract_core::ndarray::{stack, Array1, ArrayView2, Axis};
+    use tract_core::pulse::PulsedModelState;
+
+    fn signal(len: usize) -> Array1<f32> {
+        Array1::from_shape_fn(len, |i| {
+            let t = i as f32 / 16000.0;
+            3000.0 * (2.0 * 440.0 * std::f32::consts::PI * t).sin()
+                + 1000.0 * (2.0 * 3100.0 * std::f32::consts::PI * t).cos()
+                + ((i * 7919) % 201) as f32
+                - 100.0
+        })
+    }
+
+    fn model(
+        features: impl Fn(&mut InferenceModel, OutletId) -> TractResult<OutletId>,
+    ) -> InferenceModel {
+        let mut model = InferenceModel::default();
+        let source = model
+            .add_source("pcm", TensorFact::dt_shape(f32::datum_type(), tvec!(TDim::s())))
+            .unwrap();
+        let output = features(&mut model, OutletId::new(source, 0)).unwrap();
+        model.set_output_outlets(&[output]).unwrap();
+        model
+    }
+
+    /// Run the model on the whole signal, then pulse by pulse, and check
+    /// they agree.
+    fn check_pulsed(model: InferenceModel, signal: &Array1<f32>, pulse: usize) -> Tensor {
+        let plan = SimplePlan::new(model.clone().into_typed().unwrap()).unwrap();
+        let expected = plan.run(tvec!(signal.clone().into_tensor())).unwrap().remove(0);
+
+        let pulsed = PulsedModel::new(&model.into_normalized().unwrap(), pulse).unwrap();
+        let plan = SimplePlan::new(pulsed).unwrap();
+        let mut state = PulsedModelState::new(&plan).unwrap();
+        let mut chunks = vec![];
+        let mut pos = 0;
+        while pos + pulse <= signal.len() {
+            let chunk = signal.slice_axis(Axis(0), (pos..pos + pulse).into()).to_owned();
+            chunks.extend(state.run(tvec!(chunk.into_tensor())).unwrap());
+            pos += pulse;
+        }
+        let last = signal.slice_axis(Axis(0), (pos..).into()).to_owned();
+        chunks.extend(
+            state.finish_stream(tvec!(last.into_tensor()), &tensor0(std::f32::NAN)).unwrap(),
+        );
+        let views: Vec<ArrayView2<f32>> = chunks
+            .iter()
+            .map(|c| c.to_array_view::<f32>().unwrap().into_dimensionality().unwrap())
+            .collect();
+        let got = stack(Axis(0), &*views).unwrap();
+        let expected_view = expected.to_array_view::<f32>().unwrap();
+        assert_eq!(got.shape(), expected_view.shape());
+        got.iter().zip(expected_view.iter()).for_each(|(a, b)| assert!((a - b).abs() < 1e-3));
+        expected.into_tensor()
+    }
+
+    /// Samples of a 16 bits mono wav file, at Kaldi scale.
+    fn read_wav(path: &str) -> Array1<f32> {
+        let wav = std::fs::read(path).unwrap();
+        assert_eq!((&wav[0..4], &wav[8..12]), (&b"RIFF"[..], &b"WAVE"[..]));
+        let mut pos = 12;
+        loop {
+            let len = u32::from_le_bytes([wav[pos + 4], wav[pos + 5], wav[pos + 6], wav[pos + 7]]);
+            let chunk = &wav[pos + 8..pos + 8 + len as usize];
+            if &wav[pos..pos + 4] == b"fmt " {
+                assert_eq!(&chunk[0..4], &[1, 0, 1, 0], "expects mono PCM");
+                assert_eq!(&chunk[14..16], &[16, 0], "expects 16 bits samples");
+            } else if &wav[pos..pos + 4] == b"data" {
+                return chunk.chunks(2).map(|s| i16::from_le_bytes([s[0], s[1]]) as f32).collect();
+            }
+            pos += 8 + len as usize + len as usize % 2;
+        }
+    }
+
+    /// A single matrix from a Kaldi text archive.
+    fn read_ark(path: &str) -> tract_core::ndarray::Array2<f32> {
+        let ark = std::fs::read_to_string(path).unwrap();
+        let rows: Vec<Vec<f32>> = ark
+            .lines()
+            .skip(1)
+            .map(|l| {
+                l.split_whitespace().filter(|v| *v != "]").map(|v| v.parse().unwrap()).collect()
+            })
+            .collect();
+        let shape = (rows.len(), rows[0].len());
+        tract_core::ndarray::Array2::from_shape_vec(shape, rows.concat()).unwrap()
+    }
+
+    /// Compare features on test_cases/frontend/input.wav with the reference
+    /// archives there. test_cases/frontend/kaldi-version tells which Kaldi
+    /// produced them (see regenerate.sh).
+    fn check_kaldi(model: InferenceModel, expected: &str) {
+        let signal = read_wav("test_cases/frontend/input.wav");
+        let expected = read_ark(&format!("test_cases/frontend/{}", expected));
+        let plan = SimplePlan::new(model.into_typed().unwrap()).unwrap();
+        let got = plan.run(tvec!(signal.into_tensor())).unwrap().remove(0);
+        let got = got.to_array_view::<f32>().unwrap();
+        assert_eq!(got.shape(), expected.shape());
+        for ((ix, a), b) in got.indexed_iter().zip(expected.iter()) {
+            assert!((a - b).abs() < 1e-3 * b.abs().max(1.0), "at {:?}: {} != {}", ix, a, b);
+        }
+    }
+
+    #[test]
+    fn fbank_like_kaldi() {
+        let model = model(|m, i| wire_fbank(m, "fbank", i, &FbankOptions::default()));
+        check_kaldi(model, "fbank.ark");
+    }
+
+    #[test]
+    fn mfcc_like_kaldi() {
+        let model = model(|m, i| wire_mfcc(m, "mfcc", i, &MfccOptions::default()));
+        check_kaldi(model, "mfcc.ark");
+    }
+
+    #[test]
+    fn fbank_pulsed() {
+        let model = model(|m, i| wire_fbank(m, "fbank", i, &FbankOptions::default()));
+        let fbank = check_pulsed(model, &signal(4321), 640);
+        // (4321 - 400) / 160 + 1
+        assert_eq!(fbank.shape(), &[25, 23]);
+    }
+
+    #[test]
+    fn mfcc_cmvn_pulsed() {
+        let mut stats = tract_core::ndarray::Array2::<f32>::zeros((2, 14));
+        stats.row_mut(0).fill(10.0);
+        stats.row_mut(1).fill(200.0);
+        stats[(0, 13)] = 5.0;
+        let stats = stats.into_tensor();
+        let model = model(|m, i| {
+            let mfcc = wire_mfcc(m, "mfcc", i, &MfccOptions::default())?;
+            wire_cmvn(m, "cmvn", mfcc, &stats, true)
+        });
+        let mfcc = check_pulsed(model, &signal(3000), 320);
+        assert_eq!(mfcc.shape(), &[17, 13]);
+    }
+
+    #[test]
+    fn mel_banks_are_triangles() {
+        let banks = mel_banks(23, 512, 16000.0, 20.0, 0.0).unwrap();
+        let banks = banks.to_array_view::<f32>().unwrap();
+        assert_eq!(banks.shape(), &[257, 23]);
+        for bin in 0..23 {
+            let column = banks.index_axis(Axis(1), bin);
+            assert!(column.iter().all(|&w| w >= 0.0 && w <= 1.0));
+            assert!(column.iter().any(|&w| w > 0.5));
+        }
+        assert!(banks.index_axis(Axis(0), 256).iter().all(|&w| w == 0.0));
+    }
+}
@@ -0,0 +1,141 @@
+use tract_core::internal::*;
+use tract_core::ndarray;
+
+use super::{frame_output_facts, frame_rules, pulsify_frame_op};
+
+/// Mel scale, as in Kaldi.
+pub fn mel_scale(freq: f32) -> f32 {
+    1127.0 * (1.0 + freq / 700.0).ln()
+}
+
+/// Triangular mel filterbank weights, as a [fft_len / 2 + 1, num_bins]
+/// matrix applying to a power spectrum. Like Kaldi, the Nyquist bin is
+/// ignored. A non-positive `high_freq` is an offset from the Nyquist
+/// frequency.
+pub fn mel_banks(
+    num_bins: usize,
+    fft_len: usize,
+    sample_freq: f32,
+    low_freq: f32,
+    high_freq: f32,
+) -> TractResult<Tensor> {
+    let nyquist = 0.5 * sample_freq;
+    let high_freq = if high_freq > 0.0 { high_freq } else { nyquist + high_freq };
+    if low_freq < 0.0 || low_freq >= nyquist || high_freq <= low_freq || high_freq > nyquist {
+        bail!(
+            "Bad values in options: low-freq {} and high-freq {} vs. nyquist {}",
+            low_freq,
+            high_freq,
+            nyquist
+        )
+    }
+    let fft_bin_width = sample_freq / fft_len as f32;
+    let mel_low = mel_scale(low_freq);
+    let mel_high = mel_scale(high_freq);
+    let mel_delta = (mel_high - mel_low) / (num_bins + 1) as f32;
+    let mut weights = ndarray::Array2::<f32>::zeros((fft_len / 2 + 1, num_bins));
+    for bin in 0..num_bins {
+        let left = mel_low + bin as f32 * mel_delta;
+        let center = mel_low + (bin + 1) as f32 * mel_delta;
+        let right = mel_low + (bin + 2) as f32 * mel_delta;
+        for i in 0..fft_len / 2 {
+            let mel = mel_scale(fft_bin_width * i as f32);
+            if mel > left && mel < right {
+                weights[(i, bin)] = if mel <= center {
+                    (mel - left) / (center - left)
+                } else {
+                    (right - mel) / (right - center)
+                };
+            }
+        }
+    }
+    Ok(weights.into_tensor())
+}
+
+/// DCT-II as a [num_bins, num_ceps] matrix, with Kaldi normalization and
+/// the cepstral liftering folded in (no liftering if `lifter` is zero).
+pub fn dct_matrix(num_bins: usize, num_ceps: usize, lifter: f32) -> Tensor {
+    ndarray::Array2::from_shape_fn((num_bins, num_ceps), |(n, k)| {
+        let dct = if k == 0 {
+            (1.0 / num_bins as f64).sqrt()
+        } else {
+            (2.0 / num_bins as f64).sqrt()
+                * (std::f64::consts::PI / num_bins as f64 * (n as f64 + 0.5) * k as f64).cos()
+        };
+        let lift = if lifter != 0.0 {
+            1.0 + 0.5 * lifter as f64 * (std::f64::consts::PI * k as f64 / lifter as f64).sin()
+        } else {
+            1.0
+        };
+        (dct * lift) as f32
+    })
+    .into_tensor()
+}
+
+/// Applies the same linear transform to every frame: [frames, n] times a
+/// [n, m] matrix. Used for mel filterbanks and DCT.
+#[derive(Clone, Debug, new)]
+pub struct FrameTransform {
+    matrix: Arc<Tensor>,
+}
+
+impl FrameTransform {
+    fn output_width(&self) -> usize {
+        self.matrix.shape()[1]
+    }
+}
+
+impl Op for FrameTransform {
+    fn name(&self) -> std::borrow::Cow<str> {
+        "kaldi.FrameTransform".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("matrix: {:?}", self.matrix)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for FrameTransform {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let frames = input.to_array_view::<f32>()?.into_dimensionality::<ndarray::Ix2>()?;
+        let matrix = self.matrix.to_array_view::<f32>()?.into_dimensionality::<ndarray::Ix2>()?;
+        Ok(tvec!(frames.dot(&matrix).into_arc_tensor()))
+    }
+}
+
+impl InferenceRulesOp for FrameTransform {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        frame_rules(s, inputs, outputs, Some(self.output_width()))?;
+        s.equals(&inputs[0].shape[1], self.matrix.shape()[0].to_dim())
+    }
+
+    inference_op_as_op!();
+    to_typed!();
+}
+
+impl TypedOp for FrameTransform {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        frame_output_facts(inputs, Some(self.output_width()))
+    }
+
+    fn pulsify(
+        &self,
+        _source: &NormalizedModel,
+        node: &NormalizedNode,
+        target: &mut PulsedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+        _pulse: usize,
+    ) -> TractResult<TVec<OutletId>> {
+        pulsify_frame_op(self.clone(), node, target, mapping, Some(self.output_width()))
+    }
+}
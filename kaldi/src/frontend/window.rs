@@ -0,0 +1,213 @@
+use tract_core::internal::*;
+use tract_core::ndarray;
+
+use super::{frame_output_facts, frame_rules, pulsify_frame_op};
+
+/// Window functions, as in Kaldi `--window-type`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WindowType {
+    Hamming,
+    Hanning,
+    Povey,
+    Rectangular,
+    Blackman,
+}
+
+/// Window coefficients for a `len` samples frame.
+pub fn window(window_type: WindowType, len: usize) -> Tensor {
+    let a = 2.0 * std::f64::consts::PI / (len as f64 - 1.0);
+    ndarray::Array1::from_shape_fn(len, |i| {
+        let i = i as f64;
+        let w = match window_type {
+            WindowType::Hamming => 0.54 - 0.46 * (a * i).cos(),
+            WindowType::Hanning => 0.5 - 0.5 * (a * i).cos(),
+            WindowType::Povey => (0.5 - 0.5 * (a * i).cos()).powf(0.85),
+            WindowType::Rectangular => 1.0,
+            WindowType::Blackman => 0.42 - 0.5 * (a * i).cos() + 0.08 * (2.0 * a * i).cos(),
+        };
+        w as f32
+    })
+    .into_tensor()
+}
+
+/// Subtracts its mean from each frame.
+#[derive(Clone, Debug, Default)]
+pub struct RemoveDcOffset;
+
+impl Op for RemoveDcOffset {
+    fn name(&self) -> std::borrow::Cow<str> {
+        "kaldi.RemoveDcOffset".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for RemoveDcOffset {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let mut frames = input.into_tensor().into_array::<f32>()?;
+        for mut frame in frames.outer_iter_mut() {
+            let mean = frame.sum() / frame.len() as f32;
+            frame -= mean;
+        }
+        Ok(tvec!(frames.into_arc_tensor()))
+    }
+}
+
+impl InferenceRulesOp for RemoveDcOffset {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        frame_rules(s, inputs, outputs, None)
+    }
+
+    inference_op_as_op!();
+    to_typed!();
+}
+
+impl TypedOp for RemoveDcOffset {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        frame_output_facts(inputs, None)
+    }
+
+    fn pulsify(
+        &self,
+        _source: &NormalizedModel,
+        node: &NormalizedNode,
+        target: &mut PulsedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+        _pulse: usize,
+    ) -> TractResult<TVec<OutletId>> {
+        pulsify_frame_op(self.clone(), node, target, mapping, None)
+    }
+}
+
+/// Pre-emphasis inside each frame: `x[i] -= coefficient * x[i - 1]`, with
+/// the first sample of the frame standing for its own predecessor.
+#[derive(Clone, Debug, new)]
+pub struct PreEmphasis {
+    coefficient: f32,
+}
+
+impl Op for PreEmphasis {
+    fn name(&self) -> std::borrow::Cow<str> {
+        "kaldi.PreEmphasis".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("coefficient:{}", self.coefficient)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for PreEmphasis {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let mut frames = input.into_tensor().into_array::<f32>()?;
+        for mut frame in frames.outer_iter_mut() {
+            for i in (1..frame.len()).rev() {
+                frame[i] -= self.coefficient * frame[i - 1];
+            }
+            if frame.len() > 0 {
+                frame[0] -= self.coefficient * frame[0];
+            }
+        }
+        Ok(tvec!(frames.into_arc_tensor()))
+    }
+}
+
+impl InferenceRulesOp for PreEmphasis {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        frame_rules(s, inputs, outputs, None)
+    }
+
+    inference_op_as_op!();
+    to_typed!();
+}
+
+impl TypedOp for PreEmphasis {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        frame_output_facts(inputs, None)
+    }
+
+    fn pulsify(
+        &self,
+        _source: &NormalizedModel,
+        node: &NormalizedNode,
+        target: &mut PulsedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+        _pulse: usize,
+    ) -> TractResult<TVec<OutletId>> {
+        pulsify_frame_op(self.clone(), node, target, mapping, None)
+    }
+}
+
+/// Log of the energy of each frame, floored at `f32::EPSILON`: [frames, 1].
+#[derive(Clone, Debug, Default)]
+pub struct LogEnergy;
+
+impl Op for LogEnergy {
+    fn name(&self) -> std::borrow::Cow<str> {
+        "kaldi.LogEnergy".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for LogEnergy {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let frames = input.to_array_view::<f32>()?.into_dimensionality::<ndarray::Ix2>()?;
+        let output = ndarray::Array2::from_shape_fn((frames.shape()[0], 1), |(f, _)| {
+            let energy = frames.row(f).iter().map(|x| x * x).sum::<f32>();
+            energy.max(std::f32::EPSILON).ln()
+        });
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl InferenceRulesOp for LogEnergy {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        frame_rules(s, inputs, outputs, Some(1))
+    }
+
+    inference_op_as_op!();
+    to_typed!();
+}
+
+impl TypedOp for LogEnergy {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        frame_output_facts(inputs, Some(1))
+    }
+
+    fn pulsify(
+        &self,
+        _source: &NormalizedModel,
+        node: &NormalizedNode,
+        target: &mut PulsedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+        _pulse: usize,
+    ) -> TractResult<TVec<OutletId>> {
+        pulsify_frame_op(self.clone(), node, target, mapping, Some(1))
+    }
+}
@@ -1,3 +1,5 @@
+#[cfg(feature = "serialize")]
+extern crate bincode;
 #[macro_use]
 extern crate derive_new;
 #[macro_use]
@@ -7,6 +9,7 @@ extern crate log;
 #[macro_use]
 extern crate tract_core;
 
+pub mod frontend;
 pub mod model;
 mod ops;
 pub mod parser;
@@ -32,25 +32,41 @@ pub struct OutputLine {
 #[derive(Clone, Debug, PartialEq)]
 pub enum GeneralDescriptor {
     Append(Vec<GeneralDescriptor>),
+    Const(f32, usize),
     IfDefined(Box<GeneralDescriptor>),
     Name(String),
     Offset(Box<GeneralDescriptor>, isize),
+    ReplaceIndex(Box<GeneralDescriptor>, isize),
+    Round(Box<GeneralDescriptor>, usize),
+    Scale(f32, Box<GeneralDescriptor>),
+    Sum(Box<GeneralDescriptor>, Box<GeneralDescriptor>),
+    Switch(Vec<GeneralDescriptor>),
 }
 
 impl GeneralDescriptor {
     pub fn inputs(&self) -> TVec<&str> {
-        match self {
-            GeneralDescriptor::Append(ref gds) => gds.iter().fold(tvec!(), |mut acc, gd| {
+        fn union<'a>(gds: &[&'a GeneralDescriptor]) -> TVec<&'a str> {
+            gds.iter().fold(tvec!(), |mut acc, gd| {
                 gd.inputs().iter().for_each(|i| {
                     if !acc.contains(i) {
                         acc.push(i)
                     }
                 });
                 acc
-            }),
+            })
+        }
+        match self {
+            GeneralDescriptor::Append(ref gds) | GeneralDescriptor::Switch(ref gds) => {
+                union(&*gds.iter().collect::<Vec<_>>())
+            }
+            GeneralDescriptor::Const(_, _) => tvec!(),
             GeneralDescriptor::IfDefined(ref gd) => gd.inputs(),
             GeneralDescriptor::Name(ref s) => tvec!(&**s),
             GeneralDescriptor::Offset(ref gd, _) => gd.inputs(),
+            GeneralDescriptor::ReplaceIndex(ref gd, _) => gd.inputs(),
+            GeneralDescriptor::Round(ref gd, _) => gd.inputs(),
+            GeneralDescriptor::Scale(_, ref gd) => gd.inputs(),
+            GeneralDescriptor::Sum(ref a, ref b) => union(&[a, b]),
         }
     }
 
@@ -75,6 +91,16 @@ impl GeneralDescriptor {
         return None;
     }
 
+    fn is_const(&self) -> bool {
+        if let GeneralDescriptor::Const(_, _) = self {
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Wire the descriptor into `inlet`. Edges are only recorded in `deferred`,
+    /// as nodes inputs must be connected in order.
     fn wire<'a>(
         &'a self,
         inlet: InletId,
@@ -92,19 +118,44 @@ impl GeneralDescriptor {
             &Append(appendees) => {
                 let name = format!("{}-Append", name);
                 let id = model.add_node_default(&*name, tract_core::ops::array::Concat::new(1))?;
-                model.add_edge(OutletId::new(id, 0), inlet)?;
+                deferred.insert(inlet, name.to_string());
+                // constants take their length from one of the other appendees
+                let reference = appendees.iter().find(|app| !app.is_const());
                 for (ix, appendee) in appendees.iter().enumerate() {
                     let name = format!("{}-{}", name, ix);
-                    appendee.wire(
-                        InletId::new(id, ix),
-                        &*name,
-                        model,
-                        deferred,
-                        adjust_final_offset,
-                    )?;
+                    if let (Const(value, dim), Some(reference)) = (appendee, reference) {
+                        let konst = crate::ops::descriptor::Const::new(*value, *dim);
+                        let konst = model.add_node_default(&*name, konst)?;
+                        deferred.insert(InletId::new(id, ix), name.to_string());
+                        let name = format!("{}-Reference", name);
+                        reference.wire(
+                            InletId::new(konst, 0),
+                            &*name,
+                            model,
+                            deferred,
+                            adjust_final_offset,
+                        )?;
+                    } else {
+                        appendee.wire(
+                            InletId::new(id, ix),
+                            &*name,
+                            model,
+                            deferred,
+                            adjust_final_offset,
+                        )?;
+                    }
                 }
                 return Ok(());
             }
+            &Const(value, dim) => {
+                let name = format!("{}-Const", name);
+                let konst = crate::ops::descriptor::Const::new(*value, *dim);
+                let id = model.add_node_default(&*name, konst)?;
+                deferred.insert(inlet, name.to_string());
+                let source = model.node(model.input_outlets()?[0].node).name.clone();
+                deferred.insert(InletId::new(id, 0), source);
+                return Ok(());
+            }
             &IfDefined(ref o) => {
                 if let &Offset(ref n, ref o) = &**o {
                     if let Name(n) = &**n {
@@ -128,10 +179,85 @@ impl GeneralDescriptor {
                     &*name,
                     tract_core::ops::array::Crop::new(0, crop as usize, 0),
                 )?;
-                model.add_edge(OutletId::new(id, 0), inlet)?;
+                deferred.insert(inlet, name.to_string());
+                n.wire(InletId::new(id, 0), &*name, model, deferred, adjust_final_offset)?;
+                return Ok(());
+            }
+            &ReplaceIndex(ref n, t) if *t >= 0 => {
+                let name = format!("{}-ReplaceIndex", name);
+                let op = crate::ops::descriptor::FrameSelect::new(
+                    crate::ops::descriptor::Selector::ReplaceIndex(*t as usize),
+                );
+                let id = model.add_node_default(&*name, op)?;
+                deferred.insert(inlet, name.to_string());
+                n.wire(InletId::new(id, 0), &*name, model, deferred, adjust_final_offset)?;
+                return Ok(());
+            }
+            &Round(ref n, modulus) if *modulus > 0 => {
+                let name = format!("{}-Round", name);
+                let op = crate::ops::descriptor::FrameSelect::new(
+                    crate::ops::descriptor::Selector::Round(*modulus),
+                );
+                let id = model.add_node_default(&*name, op)?;
+                deferred.insert(inlet, name.to_string());
+                n.wire(InletId::new(id, 0), &*name, model, deferred, adjust_final_offset)?;
+                return Ok(());
+            }
+            &Scale(scale, ref n) => {
+                let name = format!("{}-Scale", name);
+                let factor = format!("{}-Factor", name);
+                model.add_const(&*factor, tensor0(*scale))?;
+                let id = model.add_node_default(&*name, tract_core::ops::math::mul::bin())?;
+                deferred.insert(inlet, name.to_string());
+                deferred.insert(InletId::new(id, 1), factor);
                 n.wire(InletId::new(id, 0), &*name, model, deferred, adjust_final_offset)?;
                 return Ok(());
             }
+            &Sum(ref a, ref b) => {
+                let name = format!("{}-Sum", name);
+                // adding a constant does not need it to be materialized
+                let (konst, other) = match (&**a, &**b) {
+                    (Const(value, dim), other) | (other, Const(value, dim)) => {
+                        (Some(tensor1(&*vec![*value; *dim])), other)
+                    }
+                    _ => (None, &**a),
+                };
+                if let Some(konst) = konst {
+                    let konst_name = format!("{}-Const", name);
+                    model.add_const(&*konst_name, konst)?;
+                    let id = model.add_node_default(&*name, tract_core::ops::math::add::bin())?;
+                    deferred.insert(inlet, name.to_string());
+                    deferred.insert(InletId::new(id, 1), konst_name);
+                    other.wire(
+                        InletId::new(id, 0),
+                        &*name,
+                        model,
+                        deferred,
+                        adjust_final_offset,
+                    )?;
+                } else {
+                    let id = model.add_node_default(&*name, tract_core::ops::math::add::bin())?;
+                    deferred.insert(inlet, name.to_string());
+                    for (ix, n) in [a, b].iter().enumerate() {
+                        let name = format!("{}-{}", name, ix);
+                        n.wire(InletId::new(id, ix), &*name, model, deferred, adjust_final_offset)?;
+                    }
+                }
+                return Ok(());
+            }
+            &Switch(ref switched) if switched.len() > 0 => {
+                let name = format!("{}-Switch", name);
+                let op = crate::ops::descriptor::FrameSelect::new(
+                    crate::ops::descriptor::Selector::Switch(switched.len()),
+                );
+                let id = model.add_node_default(&*name, op)?;
+                deferred.insert(inlet, name.to_string());
+                for (ix, n) in switched.iter().enumerate() {
+                    let name = format!("{}-{}", name, ix);
+                    n.wire(InletId::new(id, ix), &*name, model, deferred, adjust_final_offset)?;
+                }
+                return Ok(());
+            }
             _ => (),
         }
         bail!("Unhandled input descriptor: {:?}", self)
@@ -157,10 +283,44 @@ pub struct Component {
     pub attributes: HashMap<String, Arc<Tensor>>,
 }
 
+impl Component {
+    pub fn get_attr_tensor(&self, name: &str) -> TractResult<&Arc<Tensor>> {
+        self.attributes
+            .get(name)
+            .ok_or_else(|| format!("{}: missing attribute {}", self.klass, name).into())
+    }
+
+    /// Numeric scalar attribute. The text format does not distinguish
+    /// integers from floats, so they are all accepted.
+    pub fn get_attr_f32(&self, name: &str) -> TractResult<f32> {
+        Ok(*self.get_attr_tensor(name)?.cast_to::<f32>()?.to_scalar::<f32>()?)
+    }
+
+    pub fn get_attr_usize(&self, name: &str) -> TractResult<usize> {
+        Ok(self.get_attr_f32(name)? as usize)
+    }
+}
+
 pub struct ParsingContext<'a> {
     pub proto_model: &'a KaldiProtoModel,
 }
 
+impl<'a> ParsingContext<'a> {
+    /// The component referenced by the component node `name`.
+    pub fn component(&self, name: &str) -> TractResult<&'a Component> {
+        let node = self.proto_model.config_lines.nodes.iter().find(|l| l.0 == name);
+        let line = if let Some((_, NodeLine::Component(line))) = node {
+            line
+        } else {
+            bail!("Could not find component {}", name);
+        };
+        self.proto_model
+            .components
+            .get(&line.component)
+            .ok_or_else(|| format!("Could not find component {}", line.component).into())
+    }
+}
+
 #[derive(Clone, Default)]
 pub struct KaldiOpRegister(
     pub HashMap<String, fn(&ParsingContext, node: &str) -> TractResult<Box<dyn InferenceOp>>>,
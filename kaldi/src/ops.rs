@@ -1,23 +1,35 @@
 use crate::model::KaldiOpRegister;
 
 pub(crate) mod affine;
+pub(crate) mod descriptor;
+mod elementwise_product;
 pub(crate) mod lstm_nonlin;
 pub(crate) mod memory;
 mod renorm;
+pub(crate) mod scale_and_offset;
 
 pub const AFFINE: &'static [&'static str] =
-    &["FixedAffineComponent", "NaturalGradientAffineComponent"];
+    &["FixedAffineComponent", "NaturalGradientAffineComponent", "LinearComponent"];
 
 pub fn register_all_ops(reg: &mut KaldiOpRegister) {
     for affine in AFFINE {
         reg.insert(affine, affine::affine_component);
     }
-    reg.insert("BackpropTruncationComponent", |_, _| {
-        Ok(Box::new(tract_core::ops::identity::Identity::default()))
-    });
+    reg.insert("TdnnComponent", affine::tdnn_component);
+    for identity in &["BackpropTruncationComponent", "NoOpComponent", "GeneralDropoutComponent"] {
+        reg.insert(identity, |_, _| Ok(Box::new(tract_core::ops::identity::Identity::default())));
+    }
+    reg.insert("BatchNormComponent", scale_and_offset::batch_norm);
+    reg.insert("ElementwiseProductComponent", elementwise_product::elementwise_product);
     reg.insert("NormalizeComponent", renorm::renorm);
+    reg.insert("LogSoftmaxComponent", |_, _| {
+        Ok(Box::new(tract_core::ops::nn::LayerLogSoftmax::new(1)))
+    });
     reg.insert("LstmNonlinearityComponent", lstm_nonlin::lstm_nonlin);
     reg.insert("RectifiedLinearComponent", |_, _| {
         Ok(Box::new(tract_core::ops::math::ScalarMax { max: 0.0 }))
     });
+    reg.insert("ScaleAndOffsetComponent", scale_and_offset::scale_and_offset);
+    reg.insert("SigmoidComponent", |_, _| Ok(Box::new(tract_core::ops::nn::Sigmoid::default())));
+    reg.insert("TanhComponent", |_, _| Ok(Box::new(tract_core::ops::math::Tanh::default())));
 }
@@ -1,8 +1,7 @@
 use tract_core::internal::*;
 use tract_core::ndarray;
 
-use crate::model::NodeLine;
-use crate::model::ParsingContext;
+use crate::model::{Component, NodeLine, ParsingContext};
 
 pub fn affine_component(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn InferenceOp>> {
     let node = &ctx.proto_model.config_lines.nodes.iter().find(|l| l.0 == name);
@@ -13,9 +12,13 @@ pub fn affine_component(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn
     };
     let component = &ctx.proto_model.components[&line.component];
     let (kernel_len, dilation) = line.input.as_conv_shape_dilation().unwrap_or((1, 1));
-    let kernel: &Tensor =
-        component.attributes.get("LinearParams").ok_or("missing attribute LinearParams")?;
-    let bias = component.attributes.get("BiasParams").ok_or("missing attribute BiasParams")?;
+    // LinearComponent has no bias, and calls its weights Params
+    let kernel: &Tensor = if component.klass == "LinearComponent" {
+        component.get_attr_tensor("Params")?
+    } else {
+        component.get_attr_tensor("LinearParams")?
+    };
+    let bias = bias_params(component, kernel.shape()[0])?;
     // O•TI -> t -> TI•O -> T•I•O = HWIO
     let o_ti = kernel.to_array_view::<f32>()?;
     let t_i_o_shape = (kernel_len, kernel.len() / kernel_len / bias.len(), bias.len());
@@ -24,10 +27,66 @@ pub fn affine_component(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn
         kernel_len,
         dilation,
         linear_params: t_i_o.into_arc_tensor(),
-        bias_params: Arc::clone(bias),
+        bias_params: bias,
     }))
 }
 
+/// TdnnComponent splices the input frames at its time offsets before the
+/// affine transform: this is a convolution with one tap per offset. Offsets
+/// that are not evenly spaced are padded with null taps.
+pub fn tdnn_component(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn InferenceOp>> {
+    let component = ctx.component(name)?;
+    let offsets: Vec<isize> = component
+        .get_attr_tensor("TimeOffsets")?
+        .cast_to::<f32>()?
+        .as_slice::<f32>()?
+        .iter()
+        .map(|&o| o as isize)
+        .collect();
+    if offsets.len() == 0 || offsets.windows(2).any(|w| w[1] <= w[0]) {
+        bail!("{}: time offsets must be sorted and distinct, got {:?}", name, offsets)
+    }
+    fn gcd(a: usize, b: usize) -> usize {
+        if b == 0 {
+            a
+        } else {
+            gcd(b, a % b)
+        }
+    }
+    let dilation = offsets.windows(2).map(|w| (w[1] - w[0]) as usize).fold(0, gcd).max(1);
+    let kernel_len = (offsets[offsets.len() - 1] - offsets[0]) as usize / dilation + 1;
+    let params = component.get_attr_tensor("LinearParams")?.to_array_view::<f32>()?;
+    let params = params.into_dimensionality::<ndarray::Ix2>()?;
+    let output_dim = params.shape()[0];
+    let input_dim = params.shape()[1] / offsets.len();
+    let mut t_i_o = ndarray::Array3::<f32>::zeros((kernel_len, input_dim, output_dim));
+    for (ix, offset) in offsets.iter().enumerate() {
+        let tap = (offset - offsets[0]) as usize / dilation;
+        let weights =
+            params.slice_axis(ndarray::Axis(1), (ix * input_dim..(ix + 1) * input_dim).into());
+        t_i_o.index_axis_mut(ndarray::Axis(0), tap).assign(&weights.t());
+    }
+    Ok(Box::new(Affine {
+        kernel_len,
+        dilation,
+        linear_params: t_i_o.into_arc_tensor(),
+        bias_params: bias_params(component, output_dim)?,
+    }))
+}
+
+/// Bias of an affine component, zero if it has none (or an empty one).
+fn bias_params(component: &Component, output_dim: usize) -> TractResult<Arc<Tensor>> {
+    if let Some(bias) = component.attributes.get("BiasParams") {
+        if bias.len() == output_dim {
+            return Ok(Arc::clone(bias));
+        }
+        if bias.as_slice::<f32>()?.iter().any(|&b| b != 0.0) {
+            bail!("{}: expected {} biases, got {:?}", component.klass, output_dim, bias)
+        }
+    }
+    Ok(ndarray::Array1::<f32>::zeros(output_dim).into_arc_tensor())
+}
+
 #[derive(Clone, Debug, new)]
 struct Affine {
     kernel_len: usize,
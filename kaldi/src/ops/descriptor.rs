@@ -0,0 +1,319 @@
+use tract_core::internal::*;
+use tract_core::ndarray;
+use tract_core::ndarray::Axis;
+
+/// Const(value, dim) descriptor: a [T, dim] constant, as long as its input.
+#[derive(Clone, Debug, new)]
+pub struct Const {
+    value: f32,
+    dim: usize,
+}
+
+impl Op for Const {
+    fn name(&self) -> std::borrow::Cow<str> {
+        "kaldi.Const".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("value:{} dim:{}", self.value, self.dim)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for Const {
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let len = inputs[0].shape()[0];
+        Ok(tvec!(ndarray::Array2::from_elem((len, self.dim), self.value).into_arc_tensor()))
+    }
+}
+
+impl InferenceRulesOp for Const {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape[0], &outputs[0].shape[0])?;
+        s.equals(&outputs[0].shape[1], self.dim.to_dim())?;
+        Ok(())
+    }
+
+    inference_op_as_op!();
+    to_typed!();
+}
+
+impl TypedOp for Const {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        let shape = [inputs[0].shape.dim(0), self.dim.to_dim()];
+        Ok(tvec!(TypedTensorInfo::dt_shape(f32::datum_type(), shape.as_ref())?))
+    }
+
+    fn pulsify(
+        &self,
+        _source: &NormalizedModel,
+        node: &NormalizedNode,
+        target: &mut PulsedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+        _pulse: usize,
+    ) -> TractResult<TVec<OutletId>> {
+        let input = mapping[&node.inputs[0]];
+        let mut fact = target.outlet_fact(input)?.clone();
+        fact.dt = f32::datum_type();
+        fact.shape[1] = self.dim;
+        let id = target.chain_after(input, &*node.name, self.clone(), tvec!(fact))?;
+        Ok(tvec!(OutletId::new(id, 0)))
+    }
+}
+
+/// Descriptors picking, for each output frame, a frame of one of their
+/// inputs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Selector {
+    /// Round(input, modulus): frame t is input frame t rounded down to a
+    /// multiple of modulus.
+    Round(usize),
+    /// ReplaceIndex(input, t, value): all frames are input frame value.
+    ReplaceIndex(usize),
+    /// Switch(inputs...): frame t is taken from input t modulo the input
+    /// count.
+    Switch(usize),
+}
+
+impl Selector {
+    fn arity(&self) -> usize {
+        match self {
+            Selector::Switch(n) => *n,
+            _ => 1,
+        }
+    }
+
+    /// Input and frame output frame `t` is copied from.
+    fn source(&self, t: usize) -> (usize, usize) {
+        match self {
+            Selector::Round(modulus) => (0, t - t % modulus),
+            Selector::ReplaceIndex(value) => (0, *value),
+            Selector::Switch(n) => (t % n, t),
+        }
+    }
+
+    /// Frames the output lags behind its inputs when streaming.
+    fn delay(&self) -> usize {
+        match self {
+            Selector::ReplaceIndex(value) => *value,
+            _ => 0,
+        }
+    }
+}
+
+/// Output frames depend on their absolute time index, so the time axis is
+/// not an invariant of this op: a downsampling can not be pulled above it.
+#[derive(Clone, Debug, new)]
+pub struct FrameSelect {
+    selector: Selector,
+}
+
+impl Op for FrameSelect {
+    fn name(&self) -> std::borrow::Cow<str> {
+        "kaldi.FrameSelect".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("{:?}", self.selector)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for FrameSelect {
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let inputs = inputs
+            .iter()
+            .map(|i| Ok(i.to_array_view::<f32>()?.into_dimensionality::<ndarray::Ix2>()?))
+            .collect::<TractResult<TVec<_>>>()?;
+        let mut output = ndarray::Array2::<f32>::zeros(inputs[0].raw_dim());
+        for (t, mut row) in output.outer_iter_mut().enumerate() {
+            let (ix, frame) = self.selector.source(t);
+            if frame >= inputs[ix].shape()[0] {
+                bail!(
+                    "{:?} needs frame {}, input has {}",
+                    self.selector,
+                    frame,
+                    inputs[ix].shape()[0]
+                )
+            }
+            row.assign(&inputs[ix].index_axis(Axis(0), frame));
+        }
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl InferenceRulesOp for FrameSelect {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, self.selector.arity())?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        s.equals(&outputs[0].rank, 2)?;
+        for input in inputs {
+            s.equals(&input.datum_type, f32::datum_type())?;
+            s.equals(&input.shape, &outputs[0].shape)?;
+        }
+        Ok(())
+    }
+
+    inference_op_as_op!();
+    to_typed!();
+}
+
+impl TypedOp for FrameSelect {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        Ok(tvec!(inputs[0].clone()))
+    }
+
+    fn pulsify(
+        &self,
+        _source: &NormalizedModel,
+        node: &NormalizedNode,
+        target: &mut PulsedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+        _pulse: usize,
+    ) -> TractResult<TVec<OutletId>> {
+        use tract_core::pulse::delay::Delay;
+        let facts = node
+            .inputs
+            .iter()
+            .map(|i| Ok(target.outlet_fact(mapping[i])?.clone()))
+            .collect::<TractResult<TVec<_>>>()?;
+        if facts[0].axis != 0 {
+            bail!("{} expects time to be the streaming axis", node.name)
+        }
+        let delay = facts.iter().map(|f| f.delay).max().unwrap();
+        let mut fact = facts[0].clone();
+        fact.delay = delay + self.selector.delay();
+        let op = PulsedFrameSelect { selector: self.selector, input_delay: delay };
+        let id = target.add_node(&*node.name, op, tvec!(fact))?;
+        for (ix, input) in node.inputs.iter().enumerate() {
+            let mut source = mapping[input];
+            if facts[ix].delay < delay {
+                let mut fixed = facts[ix].clone();
+                fixed.delay = delay;
+                let delay_op = Delay::new(facts[ix].clone(), delay - facts[ix].delay, 0);
+                let name = format!("{}/Delay-{}", node.name, ix);
+                source =
+                    OutletId::new(target.chain_after(source, name, delay_op, tvec!(fixed))?, 0);
+            }
+            target.add_edge(source, InletId::new(id, ix))?;
+        }
+        Ok(tvec!(OutletId::new(id, 0)))
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct PulsedFrameSelectState {
+    /// Input frames seen so far, delay included.
+    current_pos: usize,
+    /// Last frame picked by Round or ReplaceIndex.
+    held: Option<ndarray::Array1<f32>>,
+}
+
+impl OpState for PulsedFrameSelectState {
+    fn eval(
+        &mut self,
+        _session: &mut SessionState,
+        op: &dyn Op,
+        inputs: TVec<Arc<Tensor>>,
+    ) -> TractResult<TVec<Arc<Tensor>>> {
+        let op = op.downcast_ref::<PulsedFrameSelect>().ok_or("Wrong Op type")?;
+        let inputs = inputs
+            .iter()
+            .map(|i| Ok(i.to_array_view::<f32>()?.into_dimensionality::<ndarray::Ix2>()?))
+            .collect::<TractResult<TVec<_>>>()?;
+        let pulse = inputs[0].shape()[0];
+        let mut output = ndarray::Array2::<f32>::zeros(inputs[0].raw_dim());
+        for (i, mut row) in output.outer_iter_mut().enumerate() {
+            // frames before the input delay are garbage, leave them null
+            let t = match (self.current_pos + i).checked_sub(op.input_delay) {
+                Some(t) => t,
+                None => continue,
+            };
+            match op.selector {
+                Selector::Switch(n) => row.assign(&inputs[t % n].index_axis(Axis(0), i)),
+                selector => {
+                    if selector.source(t) == (0, t) {
+                        self.held = Some(inputs[0].index_axis(Axis(0), i).to_owned());
+                    }
+                    if let Some(held) = &self.held {
+                        row.assign(held);
+                    }
+                }
+            }
+        }
+        self.current_pos += pulse;
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+
+    #[cfg(feature = "serialize")]
+    fn save_state(&self) -> TractResult<Vec<u8>> {
+        let held = self.held.as_ref().map(|h| h.to_vec());
+        Ok(bincode::serialize(&(self.current_pos, held))?)
+    }
+
+    #[cfg(feature = "serialize")]
+    fn load_state(&mut self, data: &[u8]) -> TractResult<()> {
+        let (current_pos, held): (usize, Option<Vec<f32>>) = bincode::deserialize(data)?;
+        self.current_pos = current_pos;
+        self.held = held.map(ndarray::Array1::from_vec);
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+struct PulsedFrameSelect {
+    selector: Selector,
+    input_delay: usize,
+}
+
+impl Op for PulsedFrameSelect {
+    fn name(&self) -> std::borrow::Cow<str> {
+        "kaldi.PulsedFrameSelect".into()
+    }
+
+    fn info(&self) -> TractResult<Vec<String>> {
+        Ok(vec![format!("{:?} input_delay:{}", self.selector, self.input_delay)])
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatefullOp for PulsedFrameSelect {
+    fn state(
+        &self,
+        _session: &mut SessionState,
+        _node_id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        Ok(Some(Box::new(PulsedFrameSelectState::default())))
+    }
+}
+
+impl TypedOp for PulsedFrameSelect {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        Ok(tvec!(inputs[0].clone()))
+    }
+}
@@ -0,0 +1,93 @@
+use tract_core::internal::*;
+use tract_core::ndarray;
+
+use crate::model::ParsingContext;
+
+pub fn elementwise_product(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn InferenceOp>> {
+    let component = ctx.component(name)?;
+    let input_dim = component.get_attr_usize("InputDim")?;
+    let output_dim = component.get_attr_usize("OutputDim")?;
+    if output_dim == 0 || input_dim % output_dim != 0 {
+        bail!("{}: input dim {} is not a multiple of output dim {}", name, input_dim, output_dim)
+    }
+    Ok(Box::new(ElementwiseProduct::new(output_dim)))
+}
+
+/// Multiplies together the consecutive blocks of `output_dim` columns of the
+/// input.
+#[derive(Clone, Debug, new)]
+struct ElementwiseProduct {
+    output_dim: usize,
+}
+
+impl Op for ElementwiseProduct {
+    fn name(&self) -> std::borrow::Cow<str> {
+        "kaldi.ElementwiseProduct".into()
+    }
+
+    fn axes_info(&self, _model: &TypedModel, _node: &TypedNode) -> TractResult<AxesInfo> {
+        Ok(vec![AxisInfo::simple(0)].into_iter().collect())
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for ElementwiseProduct {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let input = input.to_array_view::<f32>()?.into_dimensionality::<ndarray::Ix2>()?;
+        let mut output = input.slice_axis(ndarray::Axis(1), (0..self.output_dim).into()).to_owned();
+        for block in 1..input.shape()[1] / self.output_dim {
+            let range = block * self.output_dim..(block + 1) * self.output_dim;
+            output *= &input.slice_axis(ndarray::Axis(1), range.into());
+        }
+        Ok(tvec!(output.into_arc_tensor()))
+    }
+}
+
+impl InferenceRulesOp for ElementwiseProduct {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, f32::datum_type())?;
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&outputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape[0], &outputs[0].shape[0])?;
+        s.equals(&outputs[0].shape[1], self.output_dim.to_dim())?;
+        Ok(())
+    }
+
+    inference_op_as_op!();
+    to_typed!();
+}
+
+impl TypedOp for ElementwiseProduct {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        let mut shape = inputs[0].shape.to_tvec();
+        shape[1] = self.output_dim.to_dim();
+        Ok(tvec!(TypedTensorInfo::dt_shape(inputs[0].datum_type, &*shape)?))
+    }
+
+    fn pulsify(
+        &self,
+        _source: &NormalizedModel,
+        node: &NormalizedNode,
+        target: &mut PulsedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+        _pulse: usize,
+    ) -> TractResult<TVec<OutletId>> {
+        let input = mapping[&node.inputs[0]];
+        let mut fact = target.outlet_fact(input)?.clone();
+        fact.shape[1] = self.output_dim;
+        let id = target.chain_after(input, &*node.name, self.clone(), tvec!(fact))?;
+        Ok(tvec!(OutletId::new(id, 0)))
+    }
+}
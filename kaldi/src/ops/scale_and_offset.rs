@@ -0,0 +1,137 @@
+use tract_core::internal::*;
+use tract_core::ndarray;
+
+use crate::model::ParsingContext;
+
+/// BatchNormComponent in test mode: normalizes each block of `BlockDim`
+/// values with the stored statistics.
+pub fn batch_norm(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn InferenceOp>> {
+    let component = ctx.component(name)?;
+    let dim = component.get_attr_usize("Dim")?;
+    let epsilon = component.get_attr_f32("Epsilon")?;
+    let target_rms = component.get_attr_f32("TargetRms")?;
+    let mean = component.get_attr_tensor("StatsMean")?.to_array_view::<f32>()?;
+    let var = component.get_attr_tensor("StatsVar")?.to_array_view::<f32>()?;
+    let scales = var.mapv(|v| target_rms / (v + epsilon).sqrt());
+    let offsets = -&mean * &scales;
+    ScaleAndOffset::tiled(dim, scales.into_tensor(), offsets.into_tensor())
+}
+
+pub fn scale_and_offset(ctx: &ParsingContext, name: &str) -> TractResult<Box<dyn InferenceOp>> {
+    let component = ctx.component(name)?;
+    let dim = component.get_attr_usize("Dim")?;
+    let scales = component.get_attr_tensor("Scales")?;
+    let offsets = component.get_attr_tensor("Offsets")?;
+    ScaleAndOffset::tiled(dim, scales.as_ref().clone(), offsets.as_ref().clone())
+}
+
+/// Applies cepstral mean (and optionally variance) normalization, from Kaldi
+/// CMVN statistics: a [2, dim + 1] matrix with the feature sums and the
+/// frame count on the first row, and the feature sums of squares on the
+/// second one.
+pub fn cmvn(stats: &Tensor, norm_vars: bool) -> TractResult<Box<dyn InferenceOp>> {
+    let stats = stats.to_array_view::<f32>()?.into_dimensionality::<ndarray::Ix2>()?;
+    if stats.shape()[0] != 2 || stats.shape()[1] < 2 {
+        bail!("Expected CMVN stats of shape [2, dim + 1], got {:?}", stats.shape())
+    }
+    let dim = stats.shape()[1] - 1;
+    let count = stats[(0, dim)];
+    if count < 1.0 {
+        bail!("Insufficient frame count in CMVN stats: {}", count)
+    }
+    let mean = stats.slice_axis(ndarray::Axis(1), (0..dim).into()).row(0).mapv(|s| s / count);
+    let scales = if norm_vars {
+        ndarray::Array1::from_shape_fn(dim, |i| {
+            let var = stats[(1, i)] / count - mean[i] * mean[i];
+            var.max(1.0e-20).sqrt().recip()
+        })
+    } else {
+        ndarray::Array1::from_elem(dim, 1.0)
+    };
+    let offsets = -&mean * &scales;
+    ScaleAndOffset::tiled(dim, scales.into_tensor(), offsets.into_tensor())
+}
+
+/// Per-column affine transform of [T, dim] inputs.
+#[derive(Clone, Debug, new)]
+struct ScaleAndOffset {
+    scales: Arc<Tensor>,
+    offsets: Arc<Tensor>,
+}
+
+impl ScaleAndOffset {
+    /// Scales and offsets apply to blocks, repeat them over the whole input
+    /// dimension.
+    fn tiled(dim: usize, scales: Tensor, offsets: Tensor) -> TractResult<Box<dyn InferenceOp>> {
+        let block_dim = scales.len();
+        if block_dim == 0 || dim % block_dim != 0 || offsets.len() != block_dim {
+            bail!(
+                "Inconsistent dimensions: dim={}, {} scales, {} offsets",
+                dim,
+                block_dim,
+                offsets.len()
+            )
+        }
+        fn tile(t: &Tensor, dim: usize) -> TractResult<Arc<Tensor>> {
+            let t = t.as_slice::<f32>()?;
+            let tiled: Vec<f32> = t.iter().cycle().take(dim).cloned().collect();
+            Ok(ndarray::Array1::from_vec(tiled).into_arc_tensor())
+        }
+        Ok(Box::new(ScaleAndOffset::new(tile(&scales, dim)?, tile(&offsets, dim)?)))
+    }
+}
+
+impl Op for ScaleAndOffset {
+    fn name(&self) -> std::borrow::Cow<str> {
+        "kaldi.ScaleAndOffset".into()
+    }
+
+    not_a_typed_op!();
+}
+
+impl StatelessOp for ScaleAndOffset {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let input = args_1!(inputs);
+        let mut input = input.into_tensor().into_array::<f32>()?;
+        input *= &self.scales.to_array_view::<f32>()?;
+        input += &self.offsets.to_array_view::<f32>()?;
+        Ok(tvec!(input.into_arc_tensor()))
+    }
+}
+
+impl InferenceRulesOp for ScaleAndOffset {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 1)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, f32::datum_type())?;
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&inputs[0].shape[1], self.scales.len().to_dim())?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    inference_op_as_op!();
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        use tract_core::ops::math;
+        let input = mapping[&node.inputs[0]];
+        let scaled = target.wire_node(
+            format!("{}-Scale", node.name),
+            math::mul::unary(self.scales.clone()),
+            [input].as_ref(),
+        )?;
+        target.wire_node(&*node.name, math::add::unary(self.offsets.clone()), &*scaled)
+    }
+}
@@ -14,3 +14,171 @@ fn attribute<'a>(i: &'a [u8], klass: &str) -> IResult<&'a [u8], (String, Arc<Ten
     let (i, value) = COMPONENTS[klass][name].parse_bin(i)?;
     Ok((i, (name.to_string(), value.into_arc_tensor())))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tract_core::framework::Framework;
+
+    /// Kaldi binary encoding, as written by `WriteToken`, `WriteBasicType` and
+    /// friends.
+    #[derive(Default)]
+    struct Writer(Vec<u8>);
+
+    impl Writer {
+        fn token(mut self, token: &str) -> Writer {
+            self.0.extend_from_slice(token.as_bytes());
+            self.0.push(b' ');
+            self
+        }
+
+        fn int(mut self, i: i32) -> Writer {
+            self.0.push(4);
+            self.0.extend_from_slice(&i.to_le_bytes());
+            self
+        }
+
+        fn float(mut self, f: f32) -> Writer {
+            self.0.push(4);
+            self.0.extend_from_slice(&f.to_bits().to_le_bytes());
+            self
+        }
+
+        fn bool(mut self, b: bool) -> Writer {
+            self.0.push(if b { b'T' } else { b'F' });
+            self
+        }
+
+        fn int_vector(mut self, v: &[i32]) -> Writer {
+            self = self.int(v.len() as i32);
+            for i in v {
+                self.0.extend_from_slice(&i.to_le_bytes());
+            }
+            self
+        }
+
+        fn floats(mut self, v: &[f32]) -> Writer {
+            for f in v {
+                self.0.extend_from_slice(&f.to_bits().to_le_bytes());
+            }
+            self
+        }
+
+        fn float_vector(self, v: &[f32]) -> Writer {
+            self.token("FV").int(v.len() as i32).floats(v)
+        }
+
+        fn float_matrix(self, rows: usize, cols: usize, v: &[f32]) -> Writer {
+            self.token("FM").int(rows as i32).int(cols as i32).floats(v)
+        }
+    }
+
+    fn tdnn() -> Writer {
+        // y(t) = x(t-1) + 10 x(t) + 100 x(t+2) + 0.5
+        Writer::default()
+            .token("<TdnnComponent>")
+            .token("<MaxChange>")
+            .float(0.75)
+            .token("<TimeOffsets>")
+            .int_vector(&[-1, 0, 2])
+            .token("<LinearParams>")
+            .float_matrix(1, 3, &[1.0, 10.0, 100.0])
+            .token("<BiasParams>")
+            .float_vector(&[0.5])
+            .token("<OrthonormalConstraint>")
+            .float(0.0)
+            .token("<UseNaturalGradient>")
+            .bool(true)
+            .token("<NumSamplesHistory>")
+            .float(2000.0)
+            .token("<AlphaInOut>")
+            .float(4.0)
+            .float(2.0)
+            .token("<RankInOut>")
+            .int(20)
+            .int(10)
+            .token("</TdnnComponent>")
+    }
+
+    fn linear() -> Writer {
+        Writer::default()
+            .token("<LinearComponent>")
+            .token("<LearningRate>")
+            .float(0.001)
+            .token("<Params>")
+            .float_matrix(3, 2, &[1.0, 2.0, 3.0, 4.0, 0.0, -1.0])
+            .token("<OrthonormalConstraint>")
+            .float(0.0)
+            .token("<UseNaturalGradient>")
+            .bool(true)
+            .token("<RankInOut>")
+            .int(20)
+            .int(10)
+            .token("<Alpha>")
+            .float(4.0)
+            .token("<NumSamplesHistory>")
+            .float(2000.0)
+            .token("<UpdatePeriod>")
+            .int(4)
+            .token("</LinearComponent>")
+    }
+
+    fn component_attributes(component: Writer) -> HashMap<String, Arc<Tensor>> {
+        let (i, klass) = super::super::open_any(&component.0).unwrap();
+        attributes(i, klass).unwrap().1
+    }
+
+    #[test]
+    fn tdnn_component_attributes() {
+        let attributes = component_attributes(tdnn());
+        assert_eq!(*attributes["TimeOffsets"], tensor1(&[-1i32, 0, 2]));
+        assert_eq!(*attributes["LinearParams"], tensor2(&[[1.0f32, 10.0, 100.0]]));
+        assert_eq!(*attributes["BiasParams"], tensor1(&[0.5f32]));
+        assert_eq!(*attributes["UseNaturalGradient"], Tensor::from(true));
+        assert_eq!(*attributes["AlphaInOut"], tensor1(&[4.0f32, 2.0]));
+        assert_eq!(*attributes["RankInOut"], tensor1(&[20i32, 10]));
+    }
+
+    #[test]
+    fn linear_component_attributes() {
+        let attributes = component_attributes(linear());
+        assert_eq!(*attributes["Params"], tensor2(&[[1.0f32, 2.0], [3.0, 4.0], [0.0, -1.0]]));
+        assert_eq!(*attributes["RankInOut"], tensor1(&[20i32, 10]));
+        assert_eq!(*attributes["Alpha"], Tensor::from(4.0f32));
+        assert_eq!(*attributes["UpdatePeriod"], Tensor::from(4i32));
+    }
+
+    /// Parse and run a binary network made of a single component.
+    fn run_component(input_dim: usize, component: Writer, input: Tensor) -> Arc<Tensor> {
+        let config = format!(
+            "\ninput-node name=input dim={}\n\
+             component-node name=c component=c input=input\n\
+             output-node name=output input=c\n\n",
+            input_dim
+        );
+        let mut model = vec![0, b'B'];
+        model.extend(Writer::default().token("<Nnet3>").0);
+        model.extend(config.as_bytes());
+        model.extend(Writer::default().token("<NumComponents>").int(1).0);
+        model.extend(Writer::default().token("<ComponentName>").token("c").0);
+        model.extend(component.0);
+        model.extend(Writer::default().token("</Nnet3>").0);
+        let model = crate::kaldi().model_for_read(&mut &*model).unwrap();
+        let plan = SimplePlan::new(model.into_optimized().unwrap()).unwrap();
+        plan.run(tvec!(input)).unwrap().remove(0)
+    }
+
+    #[test]
+    fn tdnn_component() {
+        let input = tensor2(&[[1.0f32], [2.0], [3.0], [4.0], [5.0], [6.0]]);
+        let output = run_component(1, tdnn(), input);
+        output.close_enough(&tensor2(&[[421.5f32], [532.5], [643.5]]), true).unwrap();
+    }
+
+    #[test]
+    fn linear_component() {
+        let input = tensor2(&[[1.0f32, 1.0], [2.0, 0.0]]);
+        let output = run_component(2, linear(), input);
+        output.close_enough(&tensor2(&[[3.0f32, 7.0, -1.0], [2.0, 6.0, 0.0]]), true).unwrap();
+    }
+}
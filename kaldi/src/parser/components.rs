@@ -10,7 +10,7 @@ use nom::{
     bytes::complete::*,
     combinator::*,
     multi::many_m_n,
-    number::complete::{le_f32, le_f64},
+    number::complete::{le_f32, le_f64, le_i32},
     sequence::*,
     IResult,
 };
@@ -21,6 +21,13 @@ pub enum KaldiAttributeKind {
     Float,
     FloatVector,
     FloatMatrix,
+    /// Two consecutive integers, like `<RankInOut>`.
+    IntPair,
+    /// Two consecutive floats, like `<AlphaInOut>`.
+    FloatPair,
+    IntVector,
+    /// A token without value, like `<Continuous>`.
+    Flag,
 }
 
 impl KaldiAttributeKind {
@@ -34,6 +41,24 @@ impl KaldiAttributeKind {
             Float => map(Self::parse_float_value, Tensor::from)(i),
             FloatVector => preceded(multispaced(tag("FV")), Self::parse_float_vector)(i),
             FloatMatrix => preceded(multispaced(tag("FM")), Self::parse_float_matrix)(i),
+            IntPair => {
+                map(pair(super::integer(true), super::integer(true)), |(a, b)| tensor1(&[a, b]))(i)
+            }
+            FloatPair => map(pair(Self::parse_float_value, Self::parse_float_value), |(a, b)| {
+                tensor1(&[a, b])
+            })(i),
+            IntVector => Self::parse_int_vector(i),
+            Flag => Ok((i, Tensor::from(true))),
+        }
+    }
+
+    fn parse_int_vector<'a>(i: &'a [u8]) -> IResult<&'a [u8], Tensor> {
+        let (i, len) = super::integer(true)(i)?;
+        // FIXME pending merge of https://github.com/Geal/nom/pull/995
+        if len == 0 {
+            Ok((i, tensor1(&[0i32; 0])))
+        } else {
+            map(many_m_n(len as usize, len as usize, le_i32), |data| tensor1(&*data))(i)
         }
     }
 
@@ -123,22 +148,94 @@ lazy_static::lazy_static! {
             "NumElementsProcessed" => Float,
             "NumZeroingBoundaries" => Float,
         },
-        "LogSoftmaxComponent" => hashmap!{
+        "LogSoftmaxComponent" => nonlinear(),
+        "RectifiedLinearComponent" => nonlinear(),
+        "SigmoidComponent" => nonlinear(),
+        "TanhComponent" => nonlinear(),
+        "BatchNormComponent" => hashmap!{
+            "Dim" => Int,
+            "BlockDim" => Int,
+            "Epsilon" => Float,
+            "TargetRms" => Float,
+            "TestMode" => Bool,
+            "Count" => Float,
+            "StatsMean" => FloatVector,
+            "StatsVar" => FloatVector,
+        },
+        "TdnnComponent" => hashmap!{
+            "LearningRateFactor" => Float,
+            "IsGradient" => Bool,
+            "MaxChange" => Float,
+            "L2Regularize" => Float,
+            "LearningRate" => Float,
+            "TimeOffsets" => IntVector,
+            "LinearParams" => FloatMatrix,
+            "BiasParams" => FloatVector,
+            "OrthonormalConstraint" => Float,
+            "UseNaturalGradient" => Bool,
+            "NumSamplesHistory" => Float,
+            "AlphaInOut" => FloatPair,
+            "RankInOut" => IntPair,
+        },
+        "LinearComponent" => hashmap!{
+            "LearningRateFactor" => Float,
+            "IsGradient" => Bool,
+            "MaxChange" => Float,
+            "L2Regularize" => Float,
+            "LearningRate" => Float,
+            "Params" => FloatMatrix,
+            "OrthonormalConstraint" => Float,
+            "UseNaturalGradient" => Bool,
+            "RankInOut" => IntPair,
+            "Alpha" => Float,
+            "NumSamplesHistory" => Float,
+            "UpdatePeriod" => Int,
+        },
+        "ScaleAndOffsetComponent" => hashmap!{
+            "LearningRateFactor" => Float,
+            "IsGradient" => Bool,
+            "MaxChange" => Float,
+            "L2Regularize" => Float,
+            "LearningRate" => Float,
+            "Dim" => Int,
+            "Scales" => FloatVector,
+            "Offsets" => FloatVector,
+            "UseNaturalGradient" => Bool,
+            "Rank" => Int,
+        },
+        "NoOpComponent" => hashmap!{
             "Dim" => Int,
-            "ValueAvg" => FloatVector,
-            "DerivAvg" => FloatVector,
-            "Count" => Int,
-            "NumDimsSelfRepaired" => Int,
-            "NumDimsProcessed" => Int,
+            "BackpropScale" => Float,
         },
-        "RectifiedLinearComponent" => hashmap!{
+        "GeneralDropoutComponent" => hashmap!{
             "Dim" => Int,
-            "ValueAvg" => FloatVector,
-            "DerivAvg" => FloatVector,
-            "Count" => Float,
-            "NumDimsSelfRepaired" => Float,
-            "NumDimsProcessed" => Float,
-            "SelfRepairScale" => Float,
+            "BlockDim" => Int,
+            "TimePeriod" => Int,
+            "DropoutProportion" => Float,
+            "Continuous" => Flag,
+            "TestMode" => Bool,
+        },
+        "ElementwiseProductComponent" => hashmap!{
+            "InputDim" => Int,
+            "OutputDim" => Int,
         }
     };
 }
+
+/// Attributes common to all the `NonlinearComponent` subclasses.
+fn nonlinear() -> HashMap<&'static str, KaldiAttributeKind> {
+    hashmap! {
+        "Dim" => Int,
+        "BlockDim" => Int,
+        "ValueAvg" => FloatVector,
+        "DerivAvg" => FloatVector,
+        "Count" => Float,
+        "OderivRms" => FloatVector,
+        "OderivCount" => Float,
+        "NumDimsSelfRepaired" => Float,
+        "NumDimsProcessed" => Float,
+        "SelfRepairLowerThreshold" => Float,
+        "SelfRepairUpperThreshold" => Float,
+        "SelfRepairScale" => Float,
+    }
+}
@@ -9,42 +9,90 @@ use crate::parser::spaced;
 pub fn parse_general(i: &str) -> IResult<&str, GeneralDescriptor> {
     spaced(nom::branch::alt((
         map(
-            preceded(
-                tag("Append"),
-                cut(delimited(
-                    spaced(tag("(")),
-                    separated_list(spaced(tag(",")), parse_general),
-                    spaced(tag(")")),
-                )),
-            ),
+            function("Append", separated_list(spaced(tag(",")), parse_general)),
             GeneralDescriptor::Append,
         ),
         map(
-            preceded(
-                tag("Offset"),
-                cut(delimited(
-                    spaced(tag("(")),
+            function(
+                "Offset",
+                pair(
                     separated_pair(parse_general, spaced(tag(",")), integer),
-                    spaced(tag(")")),
-                )),
+                    // x offsets are not supported, only accept them when null
+                    opt(preceded(spaced(tag(",")), verify(integer, |x: &i32| *x == 0))),
+                ),
             ),
-            |(inner, offset)| GeneralDescriptor::Offset(Box::new(inner), offset as isize),
+            |((inner, offset), _)| GeneralDescriptor::Offset(Box::new(inner), offset as isize),
         ),
+        map(function("IfDefined", parse_general), |inner| {
+            GeneralDescriptor::IfDefined(Box::new(inner))
+        }),
         map(
-            preceded(
-                tag("IfDefined"),
-                cut(delimited(spaced(tag("(")), parse_general, spaced(tag(")")))),
-            ),
-            |inner| GeneralDescriptor::IfDefined(Box::new(inner)),
+            function("Sum", separated_pair(parse_general, spaced(tag(",")), parse_general)),
+            |(a, b)| GeneralDescriptor::Sum(Box::new(a), Box::new(b)),
+        ),
+        map(
+            function("Scale", separated_pair(float, spaced(tag(",")), parse_general)),
+            |(scale, inner)| GeneralDescriptor::Scale(scale, Box::new(inner)),
+        ),
+        map(function("Const", separated_pair(float, spaced(tag(",")), integer)), |(value, dim)| {
+            GeneralDescriptor::Const(value, dim as usize)
+        }),
+        map(
+            function("Round", separated_pair(parse_general, spaced(tag(",")), integer)),
+            |(inner, modulus)| GeneralDescriptor::Round(Box::new(inner), modulus as usize),
+        ),
+        function(
+            "ReplaceIndex",
+            nom::branch::alt((
+                map(
+                    separated_pair(
+                        parse_general,
+                        spaced(tag(",")),
+                        preceded(pair(tag("t"), spaced(tag(","))), integer),
+                    ),
+                    |(inner, t)| GeneralDescriptor::ReplaceIndex(Box::new(inner), t as isize),
+                ),
+                // x is always zero, replacing it with zero is a no-op
+                terminated(
+                    parse_general,
+                    tuple((spaced(tag(",")), tag("x"), spaced(tag(",")), tag("0"))),
+                ),
+            )),
+        ),
+        map(
+            function("Switch", separated_list(spaced(tag(",")), parse_general)),
+            GeneralDescriptor::Switch,
         ),
         map(super::config_lines::identifier, |i| GeneralDescriptor::Name(i.to_string())),
     )))(i)
 }
 
+/// A descriptor function call: its name, immediately followed by its
+/// arguments between parens. Node names can start like a function name, so
+/// we only commit once the opening paren is found.
+fn function<'a, O>(
+    name: &'static str,
+    args: impl Fn(&'a str) -> IResult<&'a str, O>,
+) -> impl Fn(&'a str) -> IResult<&'a str, O> {
+    preceded(pair(tag(name), spaced(tag("("))), cut(terminated(args, spaced(tag(")")))))
+}
+
 pub fn integer(i: &str) -> IResult<&str, i32> {
     map_res(recognize(pair(opt(tag("-")), digit1)), |s: &str| s.parse::<i32>())(i)
 }
 
+pub fn float(i: &str) -> IResult<&str, f32> {
+    map_res(
+        recognize(tuple((
+            opt(nom::branch::alt((tag("-"), tag("+")))),
+            digit1,
+            opt(pair(tag("."), digit0)),
+            opt(tuple((one_of("eE"), opt(one_of("+-")), digit1))),
+        ))),
+        |s: &str| s.parse::<f32>(),
+    )(i)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -71,6 +119,34 @@ mod test {
         )
     }
 
+    #[test]
+    fn test_sum_scale_const() {
+        assert_eq!(
+            parse_general("Sum(Scale(0.5, tdnn1), Sum(Offset(tdnn2, 3, 0), Const(-1.5e-1, 10)))")
+                .unwrap()
+                .1,
+            Sum(
+                Scale(0.5, name("tdnn1").into()).into(),
+                Sum(Offset(name("tdnn2").into(), 3).into(), Const(-0.15, 10).into()).into()
+            )
+        )
+    }
+
+    #[test]
+    fn test_round_replace_index_switch() {
+        assert_eq!(
+            parse_general("Switch(Round(ivector, 3), ReplaceIndex(ivector, t, 0))").unwrap().1,
+            Switch(vec!(Round(name("ivector").into(), 3), ReplaceIndex(name("ivector").into(), 0)))
+        );
+        assert_eq!(parse_general("ReplaceIndex(ivector, x, 0)").unwrap().1, name("ivector"));
+        assert!(parse_general("Offset(input, 1, 2)").is_err());
+    }
+
+    #[test]
+    fn test_name_like_function() {
+        assert_eq!(parse_general("Summary").unwrap().1, name("Summary"));
+    }
+
     #[test]
     fn test_lstm() {
         assert_eq!(
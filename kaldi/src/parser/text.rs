@@ -2,21 +2,39 @@ use tract_core::internal::*;
 
 use nom::IResult;
 use nom::{
-    bytes::complete::*, character::complete::*, combinator::*, multi::separated_list,
-    number::complete::float, sequence::*,
+    bytes::complete::*,
+    character::complete::*,
+    combinator::*,
+    multi::{many1, separated_list},
+    number::complete::float,
+    sequence::*,
 };
 
 use super::{integer, multispaced, open_any, spaced};
 
+/// Attributes are a token followed by a value, or by nothing for flags like
+/// `<Continuous>`.
 pub fn attributes(i: &[u8]) -> IResult<&[u8], HashMap<String, Arc<Tensor>>> {
-    let (i, attributes) = nom::multi::many0(map(pair(open_any, tensor), |(k, v)| {
-        (k.to_string(), v.into_arc_tensor())
+    let (i, attributes) = nom::multi::many0(map(pair(open_any, opt(tensor)), |(k, v)| {
+        (k.to_string(), v.unwrap_or_else(|| Tensor::from(true)).into_arc_tensor())
     }))(i)?;
     Ok((i, attributes.into_iter().collect()))
 }
 
 pub fn tensor(i: &[u8]) -> IResult<&[u8], Tensor> {
-    nom::branch::alt((scalar, vector, matrix))(i)
+    nom::branch::alt((scalars, vector, matrix))(i)
+}
+
+/// A scalar, or a few numbers on the same line, like `<RankInOut> 80 80`.
+pub fn scalars(i: &[u8]) -> IResult<&[u8], Tensor> {
+    nom::branch::alt((
+        map(pair(float, many1(preceded(space1, float))), |(first, rest)| {
+            let mut values = vec![first];
+            values.extend(rest);
+            tensor1(&*values)
+        }),
+        scalar,
+    ))(i)
 }
 
 pub fn scalar(i: &[u8]) -> IResult<&[u8], Tensor> {
@@ -89,9 +107,223 @@ output-node name=output input=fixed1
         );
     }
 
+    #[test]
+    fn test_attributes_pairs_and_flags() {
+        let slice = r#"<RankInOut> 20 30 <Continuous> <TestMode> T <Dim> 8"#;
+        let attributes = attributes(slice.as_bytes()).unwrap().1;
+        assert_eq!(*attributes["RankInOut"], tensor1(&[20.0f32, 30.0]));
+        assert_eq!(*attributes["Continuous"], Tensor::from(true));
+        assert_eq!(*attributes["TestMode"], Tensor::from(true));
+        assert_eq!(*attributes["Dim"], Tensor::from(8.0f32));
+    }
+
+    #[test]
+    fn test_tdnnf_components() {
+        use tract_core::framework::Framework;
+        let slice = r#"<Nnet3>
+
+input-node name=input dim=2
+component-node name=tdnn1 component=tdnn1 input=input
+component-node name=bn1 component=bn1 input=tdnn1
+component-node name=lin1 component=lin1 input=bn1
+component-node name=so1 component=so1 input=lin1
+component-node name=prod1 component=prod1 input=so1
+component-node name=tanh1 component=tanh1 input=prod1
+component-node name=drop1 component=drop1 input=tanh1
+component-node name=noop1 component=noop1 input=drop1
+component-node name=sigmoid1 component=sigmoid1 input=noop1
+component-node name=lsm1 component=lsm1 input=sigmoid1
+output-node name=output input=lsm1
+
+<NumComponents> 10
+<ComponentName> tdnn1 <TdnnComponent> <MaxChange> 0.75 <LearningRate> 0.001 <TimeOffsets> [ -1 0 2 ] <LinearParams> [
+  1.0 0.0 0.5 0.0 0.0 -1.0
+  0.0 1.0 0.0 0.5 1.0 0.0 ]
+<BiasParams> [ 0.5 -0.5 ]
+<OrthonormalConstraint> 0 <UseNaturalGradient> T <NumSamplesHistory> 2000 <AlphaInOut> 4 4 <RankInOut> 20 20 </TdnnComponent>
+<ComponentName> bn1 <BatchNormComponent> <Dim> 2 <BlockDim> 2 <Epsilon> 0.001 <TargetRms> 1 <TestMode> T <Count> 100 <StatsMean> [ 0.1 -0.2 ] <StatsVar> [ 1.5 0.5 ] </BatchNormComponent>
+<ComponentName> lin1 <LinearComponent> <Params> [
+  1.0 0.0
+  0.0 1.0
+  1.0 1.0
+  1.0 -1.0 ]
+<OrthonormalConstraint> 0 <UseNaturalGradient> T <RankInOut> 20 20 <Alpha> 4 <NumSamplesHistory> 2000 <UpdatePeriod> 4 </LinearComponent>
+<ComponentName> so1 <ScaleAndOffsetComponent> <Dim> 4 <Scales> [ 1.0 2.0 ] <Offsets> [ 0.0 0.1 ] <UseNaturalGradient> T <Rank> 20 </ScaleAndOffsetComponent>
+<ComponentName> prod1 <ElementwiseProductComponent> <InputDim> 4 <OutputDim> 2 </ElementwiseProductComponent>
+<ComponentName> tanh1 <TanhComponent> <Dim> 2 <ValueAvg> [ ] <DerivAvg> [ ] <Count> 0 <NumDimsSelfRepaired> 0 <NumDimsProcessed> 0 </TanhComponent>
+<ComponentName> drop1 <GeneralDropoutComponent> <Dim> 2 <BlockDim> 2 <TimePeriod> 0 <DropoutProportion> 0.5 <Continuous> <TestMode> T </GeneralDropoutComponent>
+<ComponentName> noop1 <NoOpComponent> <Dim> 2 <BackpropScale> 1 </NoOpComponent>
+<ComponentName> sigmoid1 <SigmoidComponent> <Dim> 2 <ValueAvg> [ ] <DerivAvg> [ ] <Count> 0 <NumDimsSelfRepaired> 0 <NumDimsProcessed> 0 </SigmoidComponent>
+<ComponentName> lsm1 <LogSoftmaxComponent> <Dim> 2 <ValueAvg> [ ] <DerivAvg> [ ] <Count> 0 <NumDimsSelfRepaired> 0 <NumDimsProcessed> 0 </LogSoftmaxComponent>
+</Nnet3>"#;
+        let model = crate::kaldi().model_for_read(&mut slice.as_bytes()).unwrap();
+        let input: Vec<f32> = (0..12).map(|i| i as f32 / 10.0).collect();
+        let input = tract_core::ndarray::Array2::from_shape_vec((6, 2), input).unwrap();
+        let plan = SimplePlan::new(model.into_typed().unwrap()).unwrap();
+        let output = plan.run(tvec!(input.into_tensor())).unwrap();
+        let output = output[0].to_array_view::<f32>().unwrap();
+        // a 4-frame receptive field leaves 3 output frames
+        assert_eq!(output.shape(), &[3, 2]);
+        for row in output.genrows() {
+            assert!((row.iter().map(|x| x.exp()).sum::<f32>() - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_descriptors_pulsed() {
+        use tract_core::framework::Framework;
+        use tract_core::ndarray::{stack, Array2, Axis};
+        let slice = r#"<Nnet3>
+
+input-node name=input dim=2
+component-node name=a component=noop input=Sum(Scale(2.0, input), Const(1, 2))
+component-node name=b component=noop input=Switch(Round(a, 3), ReplaceIndex(input, t, 2))
+component-node name=c component=noop input=Append(b, Const(0.5, 1))
+output-node name=output input=c
+
+<NumComponents> 1
+<ComponentName> noop <NoOpComponent> <Dim> 2 <BackpropScale> 1 </NoOpComponent>
+</Nnet3>"#;
+        let model = crate::kaldi().model_for_read(&mut slice.as_bytes()).unwrap();
+        let input = Array2::from_shape_fn((7, 2), |(t, d)| (t * 10 + d) as f32);
+        let plan = SimplePlan::new(model.clone().into_typed().unwrap()).unwrap();
+        let output = plan.run(tvec!(input.clone().into_tensor())).unwrap().remove(0);
+        let output = output.to_array_view::<f32>().unwrap().into_dimensionality().unwrap();
+        assert_eq!(output.row(0).to_vec(), vec!(1.0, 3.0, 0.5)); // Round(a, 3) at 0
+        assert_eq!(output.row(1).to_vec(), vec!(20.0, 21.0, 0.5)); // input at 2
+        assert_eq!(output.row(4).to_vec(), vec!(61.0, 63.0, 0.5)); // Round(a, 3) at 3
+
+        let pulsed = PulsedModel::new(&model.into_normalized().unwrap(), 2).unwrap();
+        let plan = SimplePlan::new(pulsed).unwrap();
+        let mut state = tract_core::pulse::PulsedModelState::new(&plan).unwrap();
+        let mut chunks = vec![];
+        for t in (0..6).step_by(2) {
+            let chunk = input.slice_axis(Axis(0), (t..t + 2).into()).to_owned();
+            chunks.extend(state.run(tvec!(chunk.into_tensor())).unwrap());
+            // resume from a saved state: frame selections keep their position
+            #[cfg(feature = "serialize")]
+            {
+                let saved = state.save_state().unwrap();
+                state = tract_core::pulse::PulsedModelState::new(&plan).unwrap();
+                state.load_state(&saved).unwrap();
+            }
+        }
+        let last = input.slice_axis(Axis(0), (6..7).into()).to_owned();
+        chunks.extend(
+            state.finish_stream(tvec!(last.into_tensor()), &tensor0(std::f32::NAN)).unwrap(),
+        );
+        let views: Vec<_> = chunks
+            .iter()
+            .map(|c| c.to_array_view::<f32>().unwrap().into_dimensionality().unwrap())
+            .collect();
+        assert_eq!(stack(Axis(0), &*views).unwrap(), output);
+    }
+
     #[test]
     fn fixed_affine_40x10_T40_S3() {
         let slice = std::fs::read("test_cases/fixed_affine_40x10_T40_S3/model.raw.txt").unwrap();
         nnet3(&slice).unwrap();
     }
+
+    /// Parse and run a network made of a single component.
+    fn run_component(input_dim: usize, component: &str, input: Tensor) -> Arc<Tensor> {
+        use tract_core::framework::Framework;
+        let slice = format!(
+            "<Nnet3>\n\ninput-node name=input dim={}\n\
+             component-node name=c component=c input=input\n\
+             output-node name=output input=c\n\n\
+             <NumComponents> 1\n<ComponentName> c {}\n</Nnet3>",
+            input_dim, component
+        );
+        let model = crate::kaldi().model_for_read(&mut slice.as_bytes()).unwrap();
+        let plan = SimplePlan::new(model.into_optimized().unwrap()).unwrap();
+        plan.run(tvec!(input)).unwrap().remove(0)
+    }
+
+    fn check_component(input_dim: usize, component: &str, input: Tensor, expected: Tensor) {
+        let output = run_component(input_dim, component, input);
+        assert!(output.close_enough(&expected, true).is_ok(), "{:?} != {:?}", output, expected);
+    }
+
+    #[test]
+    fn tdnn_component() {
+        // y(t) = x(t-1) + 10 x(t) + 100 x(t+2) + 0.5
+        let component = "<TdnnComponent> <TimeOffsets> [ -1 0 2 ] \
+                         <LinearParams> [\n  1.0 10.0 100.0 ]\n<BiasParams> [ 0.5 ] \
+                         <OrthonormalConstraint> 0 <UseNaturalGradient> T </TdnnComponent>";
+        let input = tensor2(&[[1.0f32], [2.0], [3.0], [4.0], [5.0], [6.0]]);
+        check_component(1, component, input, tensor2(&[[421.5f32], [532.5], [643.5]]));
+    }
+
+    #[test]
+    fn linear_component() {
+        let component = "<LinearComponent> <Params> [\n  1.0 2.0\n  3.0 4.0\n  0.0 -1.0 ]\n\
+                         <OrthonormalConstraint> 0 <RankInOut> 20 20 </LinearComponent>";
+        let input = tensor2(&[[1.0f32, 1.0], [2.0, 0.0]]);
+        check_component(2, component, input, tensor2(&[[3.0f32, 7.0, -1.0], [2.0, 6.0, 0.0]]));
+    }
+
+    #[test]
+    fn batch_norm_component() {
+        // statistics are per block of 2, scales are [0.5, 2], offsets [-0.5, 2]
+        let component = "<BatchNormComponent> <Dim> 4 <BlockDim> 2 <Epsilon> 0 \
+                         <TargetRms> 1 <TestMode> T <Count> 100 \
+                         <StatsMean> [ 1.0 -1.0 ] <StatsVar> [ 4.0 0.25 ] </BatchNormComponent>";
+        let input = tensor2(&[[3.0f32, 0.0, 1.0, -1.0]]);
+        check_component(4, component, input, tensor2(&[[1.0f32, 2.0, 0.0, 0.0]]));
+    }
+
+    #[test]
+    fn scale_and_offset_component() {
+        let component = "<ScaleAndOffsetComponent> <Dim> 4 <Scales> [ 2.0 -1.0 ] \
+                         <Offsets> [ 0.5 0.0 ] <UseNaturalGradient> T <Rank> 20 \
+                         </ScaleAndOffsetComponent>";
+        let input = tensor2(&[[1.0f32, 2.0, 3.0, 4.0]]);
+        check_component(4, component, input, tensor2(&[[2.5f32, -2.0, 6.5, -4.0]]));
+    }
+
+    #[test]
+    fn elementwise_product_component() {
+        let component =
+            "<ElementwiseProductComponent> <InputDim> 6 <OutputDim> 2 </ElementwiseProductComponent>";
+        let input = tensor2(&[[1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0]]);
+        check_component(6, component, input, tensor2(&[[15.0f32, 48.0]]));
+    }
+
+    #[test]
+    fn nonlinear_components() {
+        let nonlinear = |klass: &str| {
+            format!(
+                "<{0}> <Dim> 2 <ValueAvg> [ ] <DerivAvg> [ ] <Count> 0 \
+                 <NumDimsSelfRepaired> 0 <NumDimsProcessed> 0 </{0}>",
+                klass
+            )
+        };
+        let input = tensor2(&[[0.0f32, 3.0f32.ln()]]);
+        check_component(
+            2,
+            &nonlinear("SigmoidComponent"),
+            input.clone(),
+            tensor2(&[[0.5f32, 0.75]]),
+        );
+        check_component(2, &nonlinear("TanhComponent"), input.clone(), tensor2(&[[0.0f32, 0.8]]));
+        check_component(
+            2,
+            &nonlinear("LogSoftmaxComponent"),
+            input,
+            tensor2(&[[0.25f32.ln(), 0.75f32.ln()]]),
+        );
+    }
+
+    #[test]
+    fn identity_components() {
+        let input = tensor2(&[[1.0f32, -2.0], [3.0, 0.5]]);
+        for component in &[
+            "<NoOpComponent> <Dim> 2 <BackpropScale> 1 </NoOpComponent>",
+            "<GeneralDropoutComponent> <Dim> 2 <BlockDim> 2 <TimePeriod> 0 \
+             <DropoutProportion> 0.5 <Continuous> <TestMode> T </GeneralDropoutComponent>",
+        ] {
+            check_component(2, component, input.clone(), input.clone());
+        }
+    }
 }
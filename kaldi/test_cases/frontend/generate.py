@@ -0,0 +1,134 @@
+#!/usr/bin/env python3
+"""Writes input.wav, and an estimate of the features Kaldi computes for it
+with `--dither=0` in fbank.ark and mfcc.ark.
+
+The features are computed here following feature-window.cc,
+mel-computations.cc, feature-fbank.cc and feature-mfcc.cc with default
+options, in double precision. They are not Kaldi's output: regenerate.sh
+replaces them with the output of compute-fbank-feats and compute-mfcc-feats,
+and records the Kaldi version in kaldi-version. test.sh checks the archives
+against the Kaldi binaries.
+
+    python3 generate.py
+"""
+
+import cmath
+import math
+import os
+import struct
+
+SAMPLE_FREQ = 16000
+FRAME_LENGTH = 400
+FRAME_SHIFT = 160
+FFT_LEN = 512
+NUM_BINS = 23
+NUM_CEPS = 13
+LOW_FREQ = 20.0
+LIFTER = 22.0
+PREEMPH = 0.97
+FLT_EPSILON = 1.1920928955078125e-07
+
+
+def signal(samples):
+    """A chirp from 100Hz to 7kHz, over a deterministic pseudo-random noise."""
+    seed = 1
+    out = []
+    for i in range(samples):
+        t = i / SAMPLE_FREQ
+        phase = 2 * math.pi * (100 * t + 0.5 * (6900 / (samples / SAMPLE_FREQ)) * t * t)
+        seed = (seed * 1103515245 + 12345) % (1 << 31)
+        noise = seed / (1 << 31) * 600 - 300
+        out.append(int(round(4000 * math.sin(phase) + noise)))
+    return out
+
+
+def write_wav(path, samples):
+    data = struct.pack("<%dh" % len(samples), *samples)
+    fmt = struct.pack("<HHIIHH", 1, 1, SAMPLE_FREQ, 2 * SAMPLE_FREQ, 2, 16)
+    with open(path, "wb") as f:
+        f.write(b"RIFF" + struct.pack("<I", 4 + 8 + len(fmt) + 8 + len(data)) + b"WAVE")
+        f.write(b"fmt " + struct.pack("<I", len(fmt)) + fmt)
+        f.write(b"data" + struct.pack("<I", len(data)) + data)
+
+
+def mel_scale(freq):
+    return 1127.0 * math.log(1.0 + freq / 700.0)
+
+
+def mel_banks():
+    fft_bin_width = SAMPLE_FREQ / FFT_LEN
+    mel_low = mel_scale(LOW_FREQ)
+    mel_high = mel_scale(SAMPLE_FREQ / 2)
+    delta = (mel_high - mel_low) / (NUM_BINS + 1)
+    banks = []
+    for b in range(NUM_BINS):
+        left, center, right = (mel_low + (b + k) * delta for k in range(3))
+        weights = []
+        for i in range(FFT_LEN // 2):
+            mel = mel_scale(fft_bin_width * i)
+            if left < mel < right:
+                weights.append((mel - left) / (center - left) if mel <= center
+                               else (right - mel) / (right - center))
+            else:
+                weights.append(0.0)
+        banks.append(weights)
+    return banks
+
+
+def frames(samples):
+    """Windowed frames and their raw log energies, as ProcessWindow does."""
+    a = 2 * math.pi / (FRAME_LENGTH - 1)
+    window = [(0.5 - 0.5 * math.cos(a * i)) ** 0.85 for i in range(FRAME_LENGTH)]
+    for f in range(1 + (len(samples) - FRAME_LENGTH) // FRAME_SHIFT):
+        frame = [float(s) for s in samples[f * FRAME_SHIFT:f * FRAME_SHIFT + FRAME_LENGTH]]
+        mean = sum(frame) / len(frame)
+        frame = [s - mean for s in frame]
+        log_energy = math.log(max(sum(s * s for s in frame), FLT_EPSILON))
+        for i in range(FRAME_LENGTH - 1, 0, -1):
+            frame[i] -= PREEMPH * frame[i - 1]
+        frame[0] -= PREEMPH * frame[0]
+        yield [s * w for s, w in zip(frame, window)], log_energy
+
+
+def log_mel_energies(frame, banks):
+    frame = frame + [0.0] * (FFT_LEN - len(frame))
+    power = []
+    for k in range(FFT_LEN // 2):
+        x = sum(s * cmath.exp(-2j * math.pi * k * n / FFT_LEN) for n, s in enumerate(frame) if s)
+        power.append(abs(x) ** 2)
+    return [math.log(max(sum(w * p for w, p in zip(bank, power)), FLT_EPSILON))
+            for bank in banks]
+
+
+def mfcc(log_mel, log_energy):
+    ceps = []
+    for k in range(NUM_CEPS):
+        norm = math.sqrt((1.0 if k == 0 else 2.0) / NUM_BINS)
+        dct = norm * sum(m * math.cos(math.pi / NUM_BINS * (n + 0.5) * k)
+                         for n, m in enumerate(log_mel))
+        ceps.append(dct * (1.0 + 0.5 * LIFTER * math.sin(math.pi * k / LIFTER)))
+    ceps[0] = log_energy
+    return ceps
+
+
+def write_ark(path, rows):
+    with open(path, "w") as f:
+        f.write("input  [\n")
+        for ix, row in enumerate(rows):
+            f.write("  " + " ".join("%g" % v for v in row) + (" ]\n" if ix + 1 == len(rows) else " \n"))
+
+
+if __name__ == "__main__":
+    here = os.path.dirname(os.path.abspath(__file__))
+    samples = signal(4000)
+    write_wav(os.path.join(here, "input.wav"), samples)
+    banks = mel_banks()
+    fbank, mfccs = [], []
+    for frame, log_energy in frames(samples):
+        log_mel = log_mel_energies(frame, banks)
+        fbank.append(log_mel)
+        mfccs.append(mfcc(log_mel, log_energy))
+    write_ark(os.path.join(here, "fbank.ark"), fbank)
+    write_ark(os.path.join(here, "mfcc.ark"), mfccs)
+    with open(os.path.join(here, "kaldi-version"), "w") as f:
+        f.write("none: fbank.ark and mfcc.ark come from generate.py, run regenerate.sh\n")
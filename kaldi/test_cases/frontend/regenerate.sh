@@ -0,0 +1,14 @@
+#!/bin/bash
+# Regenerates fbank.ark and mfcc.ark with the Kaldi binaries, and records
+# the Kaldi version they come from in kaldi-version.
+#
+#     KALDI_ROOT=/path/to/kaldi ./regenerate.sh
+
+set -e
+cd $(dirname $0)
+: ${KALDI_ROOT:?KALDI_ROOT must point to a Kaldi checkout with built binaries}
+export PATH=$KALDI_ROOT/src/featbin:$PATH
+
+compute-fbank-feats --dither=0 scp:wav.scp ark,t:fbank.ark
+compute-mfcc-feats --dither=0 scp:wav.scp ark,t:mfcc.ark
+echo "kaldi $(git -C $KALDI_ROOT describe --always --dirty)" > kaldi-version
@@ -0,0 +1,10 @@
+#!/bin/bash
+fail=0
+for feats in fbank mfcc
+do
+    compute-$feats-feats --dither=0 scp:wav.scp ark,t:predicted.ark 2>.error
+    paste -d' ' <(tr -s ' \n' '\n\n' < $feats.ark) <(tr -s ' \n' '\n\n' < predicted.ark) \
+        | awk '$1 != $2 { d = $1 - $2; if (d * d > 1e-6) { print "'$feats':", $0; exit 1 } }' || fail=1
+    rm predicted.ark
+done
+echo "fail=$fail" && [ $fail -gt 0 ] && exit 1;
@@ -23,10 +23,13 @@ fn has_neon() -> bool {
 
 pub fn plug(ops: &mut Ops) {
     if has_neon() {
-        log::info!("armv7neon activated (smmm, ssigmoid), stanh)");
+        log::info!("armv7neon activated (smmm, qmmm_i8_i8, ssigmoid), stanh)");
         ops.smmm = Box::new(|m, k, n| {
             Box::new(MatMatMulImpl::<armv7neon::SMatMatMul8x4, f32>::new(m, k, n))
         });
+        ops.qmmm_i8_i8 = Box::new(|m, k, n| {
+            Box::new(MatMatMulImpl::<armv7neon::I8MatMatMul8x4, i8, i8, i32, i32>::new(m, k, n))
+        });
         ops.ssigmoid = Box::new(|| Box::new(SigmoidImpl::<armv7neon::SSigmoid4, f32>::new()));
         ops.stanh = Box::new(|| Box::new(TanhImpl::<armv7neon::STanh4, f32>::new()));
     } else {
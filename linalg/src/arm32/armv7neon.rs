@@ -6,6 +6,8 @@ extern "C" {
     #[no_mangle]
     fn armv7neon_smmm_8x4(op: *const MatMatMulKerSpec<f32>) -> isize;
     #[no_mangle]
+    fn armv7neon_i8mmm_8x4(op: *const MatMatMulKerSpec<i8, i8, i32, i32>) -> isize;
+    #[no_mangle]
     fn armv7neon_ssigmoid_4(ptr: *mut f32, count: usize);
     #[no_mangle]
     fn armv7neon_stanh_4(ptr: *mut f32, count: usize);
@@ -39,6 +41,34 @@ impl MatMatMulKer<f32> for SMatMatMul8x4 {
     }
 }
 
+#[derive(Copy, Clone, Debug)]
+pub struct I8MatMatMul8x4;
+
+impl MatMatMulKer<i8, i8, i32, i32> for I8MatMatMul8x4 {
+    #[inline(always)]
+    fn name() -> &'static str {
+        "neon"
+    }
+    #[inline(always)]
+    fn mr() -> usize {
+        8
+    }
+    #[inline(always)]
+    fn nr() -> usize {
+        4
+    }
+    fn alignment_bytes_packed_a() -> usize {
+        4
+    }
+    fn alignment_bytes_packed_b() -> usize {
+        4
+    }
+    #[inline(never)]
+    fn kernel(spec: &MatMatMulKerSpec<i8, i8, i32, i32>) -> isize {
+        unsafe { armv7neon_i8mmm_8x4(spec) }
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct SSigmoid4;
 
@@ -89,4 +119,13 @@ mod test {
     mmm_frame_tests!(crate::arm32::has_neon(), crate::arm32::armv7neon::SMatMatMul8x4);
     sigmoid_frame_tests!(crate::arm32::has_neon(), crate::arm32::armv7neon::SSigmoid4);
     tanh_frame_tests!(crate::arm32::has_neon(), crate::arm32::armv7neon::STanh4);
+
+    mod i8 {
+        qmmm_frame_tests!(
+            crate::arm32::has_neon(),
+            crate::arm32::armv7neon::I8MatMatMul8x4,
+            i8,
+            i8
+        );
+    }
 }
@@ -5,7 +5,10 @@ use crate::Ops;
 use crate::frame::MatMatMulImpl;
 
 pub fn plug(ops: &mut Ops) {
-    log::info!("arm64simd activated for smmm");
+    log::info!("arm64simd activated for smmm and qmmm_i8_i8");
     ops.smmm =
         Box::new(|m, k, n| Box::new(MatMatMulImpl::<arm64simd::SMatMatMul8x8, f32>::new(m, k, n)));
+    ops.qmmm_i8_i8 = Box::new(|m, k, n| {
+        Box::new(MatMatMulImpl::<arm64simd::I8MatMatMul8x8, i8, i8, i32, i32>::new(m, k, n))
+    });
 }
@@ -1,6 +1,7 @@
 use std::{fmt, ops};
 
 #[allow(non_camel_case_types)]
+#[repr(transparent)]
 #[derive(Copy, Clone, Default, PartialEq, PartialOrd, Debug)]
 #[cfg_attr(feature = "serialize", derive(Serialize))]
 pub struct f16(pub half::f16);
@@ -137,6 +138,18 @@ impl num_traits::ToPrimitive for f16 {
     }
 }
 
+impl num_traits::FromPrimitive for f16 {
+    fn from_i64(n: i64) -> Option<Self> {
+        Some(f16(half::f16::from_f32(n as f32)))
+    }
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(f16(half::f16::from_f32(n as f32)))
+    }
+    fn from_f64(n: f64) -> Option<Self> {
+        Some(f16(half::f16::from_f64(n)))
+    }
+}
+
 impl num_traits::AsPrimitive<usize> for f16 {
     fn as_(self) -> usize {
         self.0.to_f32() as usize
@@ -249,6 +262,24 @@ impl ops::Mul<&f16> for f16 {
     }
 }
 
+impl ops::AddAssign<f16> for f16 {
+    fn add_assign(&mut self, other: f16) {
+        self.0 = half::f16::from_f32(self.0.to_f32() + other.0.to_f32())
+    }
+}
+
+impl ops::SubAssign<f16> for f16 {
+    fn sub_assign(&mut self, other: f16) {
+        self.0 = half::f16::from_f32(self.0.to_f32() - other.0.to_f32())
+    }
+}
+
+impl ops::MulAssign<f16> for f16 {
+    fn mul_assign(&mut self, other: f16) {
+        self.0 = half::f16::from_f32(self.0.to_f32() * other.0.to_f32())
+    }
+}
+
 impl ops::Div<f16> for f16 {
     type Output = f16;
     fn div(self, other: f16) -> f16 {
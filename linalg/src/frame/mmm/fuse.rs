@@ -17,6 +17,35 @@ where
     PerColAdd(Vec<T>),
 }
 
+/// Fused operations of integer kernels: the common ones, plus the
+/// requantization of the i32 accumulators to a narrower type.
+#[derive(PartialEq, Clone)]
+pub enum QFusedSpec {
+    Fused(FusedSpec<i32>),
+    /// Per-row fixed point multiplication by a Q31 multiplier, rounding to
+    /// nearest.
+    PerRowQScale(Vec<i32>),
+    /// Per-row shift: saturating to the left for positive amounts, rounding
+    /// to nearest to the right for negative ones.
+    PerRowQShift(Vec<i32>),
+}
+
+impl From<FusedSpec<i32>> for QFusedSpec {
+    fn from(spec: FusedSpec<i32>) -> QFusedSpec {
+        QFusedSpec::Fused(spec)
+    }
+}
+
+impl Debug for QFusedSpec {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            QFusedSpec::Fused(spec) => write!(fmt, "{:?}", spec),
+            QFusedSpec::PerRowQScale(_) => write!(fmt, "PerRowQScale"),
+            QFusedSpec::PerRowQShift(_) => write!(fmt, "PerRowQShift"),
+        }
+    }
+}
+
 impl<T> Debug for FusedSpec<T>
 where
     T: Copy + Clone + Debug + Add + Mul + Zero,
@@ -48,4 +77,7 @@ where
     PerRowAdd(*const T),
     PerColMul(*const T),
     PerColAdd(*const T),
+    /// Integer kernels only, see `QFusedSpec`.
+    PerRowQScale(*const i32),
+    PerRowQShift(*const i32),
 }
@@ -6,15 +6,18 @@ use super::*;
 
 #[repr(C)]
 #[derive(PartialEq, Copy, Clone, Debug)]
-pub struct MatMatMulKerSpec<'a, T>
+pub struct MatMatMulKerSpec<'a, TA, TB = TA, TC = TA, TI = TC>
 where
-    T: Copy + Clone + Debug + Add + Mul + Zero + Debug,
+    TA: Copy + Clone + Debug + Add + Mul + Zero,
+    TB: Copy + Clone + Debug + Add + Mul + Zero,
+    TC: Copy + Clone + Debug + Add + Mul + Zero,
+    TI: Copy + Clone + Debug + Add + Mul + Zero,
 {
-    pub a: &'a StorageKerSpec<T>,
-    pub b: &'a StorageKerSpec<T>,
-    pub c: &'a StorageKerSpec<T>,
+    pub a: &'a StorageKerSpec<TA>,
+    pub b: &'a StorageKerSpec<TB>,
+    pub c: &'a StorageKerSpec<TC>,
     pub linear: &'a LinearSpec,
-    pub non_linear: *const FusedKerSpec<T>,
+    pub non_linear: *const FusedKerSpec<TI>,
 }
 
 #[repr(C, usize)]
@@ -24,14 +27,17 @@ pub enum LinearSpec {
     Noop,
 }
 
-pub trait MatMatMulKer<T>: Copy + Clone + Debug + Send + Sync
+pub trait MatMatMulKer<TA, TB = TA, TC = TA, TI = TC>: Copy + Clone + Debug + Send + Sync
 where
-    T: Copy + Clone + Debug + Add + Mul + Zero,
+    TA: Copy + Clone + Debug + Add + Mul + Zero,
+    TB: Copy + Clone + Debug + Add + Mul + Zero,
+    TC: Copy + Clone + Debug + Add + Mul + Zero,
+    TI: Copy + Clone + Debug + Add + Mul + Zero,
 {
     #[inline(always)]
     fn name() -> &'static str;
     #[inline(always)]
-    fn kernel(op: &MatMatMulKerSpec<T>) -> isize;
+    fn kernel(op: &MatMatMulKerSpec<TA, TB, TC, TI>) -> isize;
     #[inline(always)]
     fn mr() -> usize;
     #[inline(always)]
@@ -166,6 +172,99 @@ pub mod test {
         };
     }
 
+    /// Same as mmm_kernel_tests, for f16 kernels. Products and sums stay in
+    /// the range where half precision is exact, so packed_offsets is not run
+    /// with a large k.
+    #[macro_export]
+    macro_rules! mmm_half_kernel_tests {
+        ($cond:expr, $ker:ty) => {
+            mod kernel {
+                #[allow(unused_imports)]
+                use crate::f16::f16;
+                #[allow(unused_imports)]
+                use crate::frame::mmm::kernel::test;
+                use crate::frame::mmm::MatMatMulKer;
+
+                #[test]
+                fn return_zeros() {
+                    if $cond {
+                        test::return_zeros::<$ker, f16>()
+                    }
+                }
+
+                #[test]
+                fn return_c() {
+                    if $cond {
+                        test::return_c::<$ker, f16>()
+                    }
+                }
+
+                #[test]
+                fn return_c_mul_row() {
+                    if $cond {
+                        test::return_c_mul_row::<$ker, f16>()
+                    }
+                }
+
+                #[test]
+                fn return_c_add_row() {
+                    if $cond {
+                        test::return_c_add_row::<$ker, f16>()
+                    }
+                }
+
+                #[test]
+                fn packed_packed_1() {
+                    if $cond {
+                        test::packed_packed::<$ker, f16>(1)
+                    }
+                }
+
+                #[test]
+                fn packed_packed_13() {
+                    if $cond {
+                        test::packed_packed::<$ker, f16>(13)
+                    }
+                }
+
+                #[test]
+                fn packed_offsets_k1() {
+                    if $cond {
+                        test::packed_offsets::<$ker, f16>(1, <$ker>::nr())
+                    }
+                }
+
+                #[test]
+                fn packed_offsets_k2() {
+                    if $cond {
+                        test::packed_offsets::<$ker, f16>(2, <$ker>::nr())
+                    }
+                }
+
+                #[test]
+                fn packed_vec_k1() {
+                    if $cond {
+                        test::packed_vec::<$ker, f16>(1)
+                    }
+                }
+
+                #[test]
+                fn packed_vec_k13() {
+                    if $cond {
+                        test::packed_vec::<$ker, f16>(13)
+                    }
+                }
+
+                #[test]
+                fn packed_offsets_with_row_stride() {
+                    if $cond {
+                        test::packed_offsets::<$ker, f16>(2, <$ker>::nr() + 5)
+                    }
+                }
+            }
+        };
+    }
+
     pub fn null_packed_storage<T>() -> StorageKerSpec<T>
     where
         T: Mul + Add + Zero + One + Debug + Copy + PartialEq + From<f32>,
@@ -5,6 +5,7 @@ use std::ops::{Add, Mul};
 use num_traits::Zero;
 
 use crate::frame::{PackA, PackB};
+use crate::threads::Shared;
 
 use super::*;
 
@@ -21,69 +22,87 @@ impl<T> ScratchSpace<T>
 where
     T: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync + Default,
 {
-    unsafe fn non_linear<K: MatMatMulKer<T>>(
+    unsafe fn non_linear<K, TA, TB, TC>(
         &mut self,
         specs: &[FusedSpec<T>],
         down: usize,
         right: usize,
-    ) -> *const FusedKerSpec<T> {
+    ) -> *const FusedKerSpec<T>
+    where
+        K: MatMatMulKer<TA, TB, TC, T>,
+        TA: Copy + Add + Mul + Zero + Debug,
+        TB: Copy + Add + Mul + Zero + Debug,
+        TC: Copy + Add + Mul + Zero + Debug,
+    {
+        self.uspecs.clear();
+        for spec in specs {
+            let s = self.fused::<K, TA, TB, TC>(spec, down, right);
+            self.uspecs.push(s);
+        }
+        self.uspecs.push(FusedKerSpec::Done);
+        self.uspecs.as_ptr()
+    }
+
+    unsafe fn fused<K, TA, TB, TC>(
+        &mut self,
+        spec: &FusedSpec<T>,
+        down: usize,
+        right: usize,
+    ) -> FusedKerSpec<T>
+    where
+        K: MatMatMulKer<TA, TB, TC, T>,
+        TA: Copy + Add + Mul + Zero + Debug,
+        TB: Copy + Add + Mul + Zero + Debug,
+        TC: Copy + Add + Mul + Zero + Debug,
+    {
+        match spec {
+            FusedSpec::Min(m) => FusedKerSpec::Min(*m),
+            FusedSpec::Max(m) => FusedKerSpec::Max(*m),
+            FusedSpec::AddC => FusedKerSpec::AddC,
+            FusedSpec::PerRowMul(v) => FusedKerSpec::PerRowMul(self.panel(v, down, K::mr())),
+            FusedSpec::PerRowAdd(v) => FusedKerSpec::PerRowAdd(self.panel(v, down, K::mr())),
+            FusedSpec::PerColMul(v) => FusedKerSpec::PerColMul(self.panel(v, right, K::nr())),
+            FusedSpec::PerColAdd(v) => FusedKerSpec::PerColAdd(self.panel(v, right, K::nr())),
+        }
+    }
+
+    /// The `len` values of `v` starting at `ix * len`, padded with zeros in a
+    /// scratch buffer if `v` is too short.
+    unsafe fn panel(&mut self, v: &[T], ix: usize, len: usize) -> *const T {
+        let have = v.len() - ix * len;
+        if have < len {
+            let mut buf = vec![T::zero(); len];
+            buf[..have].copy_from_slice(&v[ix * len..][..have]);
+            let ptr = buf.as_ptr();
+            self.non_linear_buffers.push(buf);
+            ptr
+        } else {
+            v.as_ptr().add(ix * len)
+        }
+    }
+}
+
+impl ScratchSpace<i32> {
+    unsafe fn q_non_linear<K, TA, TB>(
+        &mut self,
+        specs: &[QFusedSpec],
+        down: usize,
+        right: usize,
+    ) -> *const FusedKerSpec<i32>
+    where
+        K: MatMatMulKer<TA, TB, i32, i32>,
+        TA: Copy + Add + Mul + Zero + Debug,
+        TB: Copy + Add + Mul + Zero + Debug,
+    {
         self.uspecs.clear();
         for spec in specs {
             let s = match spec {
-                FusedSpec::Min(m) => FusedKerSpec::Min(*m),
-                FusedSpec::Max(m) => FusedKerSpec::Max(*m),
-                FusedSpec::AddC => FusedKerSpec::AddC,
-                FusedSpec::PerRowMul(v) => {
-                    let have = v.len() - down * K::mr();
-                    let ptr = if have < K::mr() {
-                        let mut buf = vec![T::zero(); K::mr()];
-                        buf[..have].copy_from_slice(&v[down * K::mr()..][..have]);
-                        let ptr = buf.as_ptr();
-                        self.non_linear_buffers.push(buf);
-                        ptr
-                    } else {
-                        v.as_ptr().add(down * K::mr())
-                    };
-                    FusedKerSpec::PerRowMul(ptr)
-                }
-                FusedSpec::PerRowAdd(v) => {
-                    let have = v.len() - down * K::mr();
-                    let ptr = if have < K::mr() {
-                        let mut buf = vec![T::zero(); K::mr()];
-                        buf[..have].copy_from_slice(&v[down * K::mr()..][..have]);
-                        let ptr = buf.as_ptr();
-                        self.non_linear_buffers.push(buf);
-                        ptr
-                    } else {
-                        v.as_ptr().add(down * K::mr())
-                    };
-                    FusedKerSpec::PerRowAdd(ptr)
+                QFusedSpec::Fused(spec) => self.fused::<K, TA, TB, i32>(spec, down, right),
+                QFusedSpec::PerRowQScale(v) => {
+                    FusedKerSpec::PerRowQScale(self.panel(v, down, K::mr()))
                 }
-                FusedSpec::PerColMul(v) => {
-                    let have = v.len() - right * K::nr();
-                    let ptr = if have < K::nr() {
-                        let mut buf = vec![T::zero(); K::nr()];
-                        buf[..have].copy_from_slice(&v[right * K::nr()..][..have]);
-                        let ptr = buf.as_ptr();
-                        self.non_linear_buffers.push(buf);
-                        ptr
-                    } else {
-                        v.as_ptr().add(right * K::nr())
-                    };
-                    FusedKerSpec::PerColMul(ptr)
-                }
-                FusedSpec::PerColAdd(v) => {
-                    let have = v.len() - right * K::nr();
-                    let ptr = if have < K::nr() {
-                        let mut buf = vec![T::zero(); K::nr()];
-                        buf[..have].copy_from_slice(&v[right * K::nr()..][..have]);
-                        let ptr = buf.as_ptr();
-                        self.non_linear_buffers.push(buf);
-                        ptr
-                    } else {
-                        v.as_ptr().add(right * K::nr())
-                    };
-                    FusedKerSpec::PerColAdd(ptr)
+                QFusedSpec::PerRowQShift(v) => {
+                    FusedKerSpec::PerRowQShift(self.panel(v, down, K::mr()))
                 }
             };
             self.uspecs.push(s);
@@ -93,73 +112,108 @@ where
     }
 }
 
-pub trait MatMatMul<T>: Send + Sync + Debug + objekt::Clone
+pub trait MatMatMul<TA, TB = TA, TC = TA, TI = TC>: Send + Sync + Debug + objekt::Clone
 where
-    T: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync,
+    TA: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync,
+    TB: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync,
+    TC: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync,
+    TI: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync,
 {
-    fn a_pack(&self) -> PackA<T>;
-    fn b_pack(&self) -> PackB<T>;
+    fn a_pack(&self) -> PackA<TA>;
+    fn b_pack(&self) -> PackB<TB>;
 
     fn m(&self) -> usize;
     fn k(&self) -> usize;
     fn n(&self) -> usize;
 
-    unsafe fn a_from_packed(&self, ptr: *const T) -> StorageSpec<T>;
-    unsafe fn b_from_packed(&self, ptr: *const T) -> StorageSpec<T>;
+    unsafe fn a_from_packed(&self, ptr: *const TA) -> StorageSpec<TA>;
+    unsafe fn b_from_packed(&self, ptr: *const TB) -> StorageSpec<TB>;
 
     unsafe fn b_from_data_and_offsets(
         &self,
-        data: *const T,
+        data: *const TB,
         rows_offsets: &[isize],
         cols_offsets: &[isize],
-    ) -> StorageSpec<T>;
+    ) -> StorageSpec<TB>;
 
-    unsafe fn b_vec_from_data_and_stride(&self, data: *const T, stride: isize) -> StorageSpec<T>;
-    unsafe fn b_vec_from_data(&self, data: *const T) -> StorageSpec<T>;
+    unsafe fn b_vec_from_data_and_stride(&self, data: *const TB, stride: isize) -> StorageSpec<TB>;
+    unsafe fn b_vec_from_data(&self, data: *const TB) -> StorageSpec<TB>;
 
     unsafe fn c_from_data_and_strides(
         &self,
-        data: *const T,
+        data: *const TC,
         row_stride: isize,
         col_stride: isize,
-    ) -> StorageSpec<T>;
+    ) -> StorageSpec<TC>;
 
-    unsafe fn c_vec_from_data_and_stride(&self, data: *mut T, stride: isize) -> StorageSpec<T>;
-    unsafe fn c_vec_from_data(&self, data: *mut T) -> StorageSpec<T>;
+    unsafe fn c_vec_from_data_and_stride(&self, data: *mut TC, stride: isize) -> StorageSpec<TC>;
+    unsafe fn c_vec_from_data(&self, data: *mut TC) -> StorageSpec<TC>;
 
     unsafe fn run(
         &self,
-        a: &StorageSpec<T>,
-        b: &StorageSpec<T>,
-        c: &mut StorageSpec<T>,
-        non_linear: &[FusedSpec<T>],
+        a: &StorageSpec<TA>,
+        b: &StorageSpec<TB>,
+        c: &mut StorageSpec<TC>,
+        non_linear: &[FusedSpec<TI>],
     );
 }
 
-clone_trait_object!(<T> MatMatMul<T> where T: Copy + Add + Mul + Zero);
+clone_trait_object!(<TA, TB, TC, TI> MatMatMul<TA, TB, TC, TI> where
+    TA: Copy + Add + Mul + Zero,
+    TB: Copy + Add + Mul + Zero,
+    TC: Copy + Add + Mul + Zero,
+    TI: Copy + Add + Mul + Zero
+);
+
+/// Integer product, with i32 accumulators and output, accepting the
+/// requantization fused operations.
+pub trait QMatMatMul<TA, TB>: MatMatMul<TA, TB, i32, i32>
+where
+    TA: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync,
+    TB: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync,
+{
+    unsafe fn run_q(
+        &self,
+        a: &StorageSpec<TA>,
+        b: &StorageSpec<TB>,
+        c: &mut StorageSpec<i32>,
+        non_linear: &[QFusedSpec],
+    );
+}
+
+clone_trait_object!(<TA, TB> QMatMatMul<TA, TB> where
+    TA: Copy + Add + Mul + Zero,
+    TB: Copy + Add + Mul + Zero
+);
 
 #[derive(Debug, Clone, new)]
-pub struct MatMatMulImpl<K, T>
+pub struct MatMatMulImpl<K, TA, TB = TA, TC = TA, TI = TC>
 where
-    T: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync,
-    K: MatMatMulKer<T>,
+    TA: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync,
+    TB: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync,
+    TC: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync,
+    TI: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync,
+    K: MatMatMulKer<TA, TB, TC, TI>,
 {
     pub m: usize,
     pub k: usize,
     pub n: usize,
-    phantom: PhantomData<(K, T)>,
+    phantom: PhantomData<(K, TA, TB, TC, TI)>,
 }
 
-impl<K, T> MatMatMul<T> for MatMatMulImpl<K, T>
+impl<K, TA, TB, TC, TI> MatMatMul<TA, TB, TC, TI> for MatMatMulImpl<K, TA, TB, TC, TI>
 where
-    T: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync + Default,
-    K: MatMatMulKer<T>,
+    TA: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync,
+    TB: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync,
+    TC: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync,
+    TI: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync + Default,
+    K: MatMatMulKer<TA, TB, TC, TI>,
 {
-    fn a_pack(&self) -> PackA<T> {
+    fn a_pack(&self) -> PackA<TA> {
         PackA::new(self.k, self.m, K::mr(), K::alignment_bytes_packed_a())
     }
 
-    fn b_pack(&self) -> PackB<T> {
+    fn b_pack(&self) -> PackB<TB> {
         PackB::new(self.k, self.n, K::nr(), K::alignment_bytes_packed_b())
     }
 
@@ -175,20 +229,20 @@ where
         self.k
     }
 
-    unsafe fn a_from_packed(&self, ptr: *const T) -> StorageSpec<T> {
+    unsafe fn a_from_packed(&self, ptr: *const TA) -> StorageSpec<TA> {
         StorageSpec::Packed { ptr, panel_len: (self.k * K::mr()) }
     }
 
-    unsafe fn b_from_packed(&self, ptr: *const T) -> StorageSpec<T> {
+    unsafe fn b_from_packed(&self, ptr: *const TB) -> StorageSpec<TB> {
         StorageSpec::Packed { ptr, panel_len: (self.k * K::nr()) }
     }
 
     unsafe fn b_from_data_and_offsets(
         &self,
-        data: *const T,
+        data: *const TB,
         rows_offsets: &[isize],
         cols_offsets: &[isize],
-    ) -> StorageSpec<T> {
+    ) -> StorageSpec<TB> {
         debug_assert!(rows_offsets.len() > 0);
         debug_assert!(cols_offsets.len() > 0);
         let wanted = (cols_offsets.len() + K::nr() - 1) / K::nr() * K::nr();
@@ -206,7 +260,7 @@ where
         row_byte_offsets.set_len(rows_offsets.len() + 4);
         for i in 0..rows_offsets.len() {
             *row_byte_offsets.get_unchecked_mut(i) =
-                *rows_offsets.get_unchecked(i) * std::mem::size_of::<T>() as isize;
+                *rows_offsets.get_unchecked(i) * std::mem::size_of::<TB>() as isize;
         }
         let pad = *row_byte_offsets.get_unchecked(rows_offsets.len() - 1);
         for i in 0..4 {
@@ -215,122 +269,165 @@ where
         StorageSpec::OffsetsAndPtrs { col_ptrs, row_byte_offsets, nr: K::nr() }
     }
 
-    unsafe fn b_vec_from_data_and_stride(&self, data: *const T, stride: isize) -> StorageSpec<T> {
+    unsafe fn b_vec_from_data_and_stride(&self, data: *const TB, stride: isize) -> StorageSpec<TB> {
         StorageSpec::VecStride {
             ptr: data,
-            byte_stride: stride * std::mem::size_of::<T>() as isize,
+            byte_stride: stride * std::mem::size_of::<TB>() as isize,
             mr: K::mr(),
             nr: K::nr(),
         }
     }
 
-    unsafe fn b_vec_from_data(&self, data: *const T) -> StorageSpec<T> {
+    unsafe fn b_vec_from_data(&self, data: *const TB) -> StorageSpec<TB> {
         self.b_vec_from_data_and_stride(data, 1)
     }
 
     unsafe fn c_from_data_and_strides(
         &self,
-        data: *const T,
+        data: *const TC,
         row_stride: isize,
         col_stride: isize,
-    ) -> StorageSpec<T> {
+    ) -> StorageSpec<TC> {
         StorageSpec::Strides {
             ptr: data,
-            row_byte_stride: row_stride * std::mem::size_of::<T>() as isize,
-            col_byte_stride: col_stride * std::mem::size_of::<T>() as isize,
+            row_byte_stride: row_stride * std::mem::size_of::<TC>() as isize,
+            col_byte_stride: col_stride * std::mem::size_of::<TC>() as isize,
             mr: K::mr(),
             nr: K::nr(),
         }
     }
 
-    unsafe fn c_vec_from_data_and_stride(&self, data: *mut T, stride: isize) -> StorageSpec<T> {
+    unsafe fn c_vec_from_data_and_stride(&self, data: *mut TC, stride: isize) -> StorageSpec<TC> {
         StorageSpec::VecStride {
             ptr: data,
-            byte_stride: stride * std::mem::size_of::<T>() as isize,
+            byte_stride: stride * std::mem::size_of::<TC>() as isize,
             mr: K::mr(),
             nr: K::nr(),
         }
     }
 
-    unsafe fn c_vec_from_data(&self, data: *mut T) -> StorageSpec<T> {
+    unsafe fn c_vec_from_data(&self, data: *mut TC) -> StorageSpec<TC> {
         self.c_vec_from_data_and_stride(data, 1)
     }
 
     unsafe fn run(
         &self,
-        a: &StorageSpec<T>,
-        b: &StorageSpec<T>,
-        c: &mut StorageSpec<T>,
-        non_linear: &[FusedSpec<T>],
+        a: &StorageSpec<TA>,
+        b: &StorageSpec<TB>,
+        c: &mut StorageSpec<TC>,
+        non_linear: &[FusedSpec<TI>],
+    ) {
+        let add_c = non_linear.iter().any(|spec| *spec == FusedSpec::AddC);
+        self.run_tiles(a, b, c, add_c, |scratch, ia, ib| {
+            scratch.non_linear::<K, TA, TB, TC>(non_linear, ia, ib)
+        })
+    }
+}
+
+impl<K, TA, TB> QMatMatMul<TA, TB> for MatMatMulImpl<K, TA, TB, i32, i32>
+where
+    TA: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync,
+    TB: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync,
+    K: MatMatMulKer<TA, TB, i32, i32>,
+{
+    unsafe fn run_q(
+        &self,
+        a: &StorageSpec<TA>,
+        b: &StorageSpec<TB>,
+        c: &mut StorageSpec<i32>,
+        non_linear: &[QFusedSpec],
     ) {
+        let add_c = non_linear.iter().any(|spec| *spec == FusedSpec::AddC.into());
+        self.run_tiles(a, b, c, add_c, |scratch, ia, ib| {
+            scratch.q_non_linear::<K, TA, TB>(non_linear, ia, ib)
+        })
+    }
+}
+
+impl<K, TA, TB, TC, TI> MatMatMulImpl<K, TA, TB, TC, TI>
+where
+    TA: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync,
+    TB: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync,
+    TC: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync,
+    TI: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync + Default,
+    K: MatMatMulKer<TA, TB, TC, TI>,
+{
+    unsafe fn run_tiles<F>(
+        &self,
+        a: &StorageSpec<TA>,
+        b: &StorageSpec<TB>,
+        c: &mut StorageSpec<TC>,
+        add_c: bool,
+        non_linear: F,
+    ) where
+        F: Fn(&mut ScratchSpace<TI>, usize, usize) -> *const FusedKerSpec<TI> + Sync,
+    {
         let mr = K::mr();
         let nr = K::nr();
-        let m = self.m;
-        let k = self.k;
-        let n = self.n;
-        let mut scratch = ScratchSpace::default();
-        let tmpc = vec![T::zero(); mr * nr];
-        let ref mut tmp_tile = self.c_from_data_and_strides(tmpc.as_ptr(), nr as isize, 1);
-        let ref linear = LinearSpec::Mul { k };
-        for ia in 0..m / mr {
-            let ref a = a.panel_a(ia);
-            for ib in 0..n / nr {
-                let ref b = b.panel_b(nr, ib, nr);
-                let ref mmm_c = c.mmm(ia, ib);
-                let non_linear = scratch.non_linear::<K>(non_linear, ia, ib);
-                let err = K::kernel(&MatMatMulKerSpec {
-                    a: a as _,
-                    b: b as _,
-                    c: mmm_c as _,
-                    linear,
-                    non_linear,
-                });
-                debug_assert_eq!(err, 0, "Kernel return error {}", err);
+        let panels_down = (self.m + mr - 1) / mr;
+        let panels_right = (self.n + nr - 1) / nr;
+        // tiles are independent: they can be computed in any order, on any
+        // thread, without altering the result
+        let shared = Shared((self, a, b, &*c, &non_linear));
+        crate::threads::for_each_range(panels_down * panels_right, |tiles| {
+            let (op, a, b, c, non_linear) = shared.0;
+            let mut scratch = ScratchSpace::default();
+            let tmpc = vec![TC::zero(); mr * nr];
+            let ref tmp_tile = op.c_from_data_and_strides(tmpc.as_ptr(), nr as isize, 1);
+            for tile in tiles {
+                let (ia, ib) = (tile / panels_right, tile % panels_right);
+                let non_linear = non_linear(&mut scratch, ia, ib);
+                op.run_tile(a, b, c, add_c, non_linear, tmp_tile, &tmpc, ia, ib);
             }
-            if n % nr != 0 {
-                let ref b = b.panel_b(nr, n / nr, n % nr);
-                let ref tmp_tile_c = tmp_tile.mmm(0, 0);
-                let non_linear = scratch.non_linear::<K>(non_linear, ia, n / nr);
-                let err = K::kernel(&MatMatMulKerSpec {
-                    a: a as _,
-                    b: b as _,
-                    c: tmp_tile_c as _,
-                    linear,
-                    non_linear,
-                });
-                debug_assert_eq!(err, 0, "Kernel return error {}", err);
-                c.set_from_mmm(ia, n / nr, mr, n % nr, &*tmpc);
+        });
+    }
+
+    /// Computes the (ia, ib) tile of c. Partial tiles on the bottom and right
+    /// edges go through the temporary tile, loaded from c first if the fused
+    /// specs read it.
+    unsafe fn run_tile(
+        &self,
+        a: &StorageSpec<TA>,
+        b: &StorageSpec<TB>,
+        c: &StorageSpec<TC>,
+        add_c: bool,
+        non_linear: *const FusedKerSpec<TI>,
+        tmp_tile: &StorageSpec<TC>,
+        tmpc: &[TC],
+        ia: usize,
+        ib: usize,
+    ) {
+        let mr = K::mr();
+        let nr = K::nr();
+        let height = if ia < self.m / mr { mr } else { self.m % mr };
+        let width = if ib < self.n / nr { nr } else { self.n % nr };
+        let ref linear = LinearSpec::Mul { k: self.k };
+        let ref a = a.panel_a(ia);
+        let ref b = b.panel_b(nr, ib, width);
+        if height == mr && width == nr {
+            let ref mmm_c = c.mmm(ia, ib);
+            let err = K::kernel(&MatMatMulKerSpec {
+                a: a as _,
+                b: b as _,
+                c: mmm_c as _,
+                linear,
+                non_linear,
+            });
+            debug_assert_eq!(err, 0, "Kernel return error {}", err);
+        } else {
+            if add_c {
+                c.get_into_mmm(ia, ib, height, width, tmpc.as_ptr() as *mut TC);
             }
-        }
-        if m % mr != 0 {
-            let ref panel_a = a.panel_a(m / mr);
             let ref tmp_tile_c = tmp_tile.mmm(0, 0);
-            for ib in 0..n / nr {
-                let ref b = b.panel_b(nr, ib, nr);
-                let non_linear = scratch.non_linear::<K>(non_linear, m / mr, ib);
-                let err = K::kernel(&MatMatMulKerSpec {
-                    a: panel_a as _,
-                    b: b as _,
-                    c: tmp_tile_c as _,
-                    linear,
-                    non_linear,
-                });
-                debug_assert_eq!(err, 0, "Kernel return error {}", err);
-                c.set_from_mmm(m / mr, ib, m % mr, nr, &*tmpc);
-            }
-            if n % nr != 0 {
-                let ref b = b.panel_b(nr, n / nr, n % nr);
-                let non_linear = scratch.non_linear::<K>(non_linear, m / mr, n / nr);
-                let err = K::kernel(&MatMatMulKerSpec {
-                    a: panel_a as _,
-                    b: b as _,
-                    c: tmp_tile_c as _,
-                    linear,
-                    non_linear,
-                });
-                debug_assert_eq!(err, 0, "Kernel return error {}", err);
-                c.set_from_mmm(m / mr, n / nr, m % mr, n % nr, &*tmpc);
-            }
+            let err = K::kernel(&MatMatMulKerSpec {
+                a: a as _,
+                b: b as _,
+                c: tmp_tile_c as _,
+                linear,
+                non_linear,
+            });
+            debug_assert_eq!(err, 0, "Kernel return error {}", err);
+            c.set_from_mmm(ia, ib, height, width, tmpc);
         }
     }
 }
@@ -340,6 +437,8 @@ where
 pub mod test {
     use super::*;
     use crate::align;
+    use crate::f16::f16;
+    use num_traits::AsPrimitive;
     use proptest::prelude::*;
 
     #[macro_export]
@@ -363,6 +462,13 @@ pub mod test {
                         }
                     }
 
+                    #[test]
+                    fn mat_mul_threaded((m, k, n, ref a, ref b) in strat_mat_mat_mul()) {
+                        if $cond {
+                            test_mat_mat_mul_threaded_f32::<$ker>(m, k, n, a, b)?
+                        }
+                    }
+
                     #[test]
                     fn conv_prepacked(pb in strat_conv_1d()) {
                         if $cond {
@@ -459,6 +565,100 @@ pub mod test {
         };
     }
 
+    #[macro_export]
+    macro_rules! mmm_half_frame_tests {
+        ($cond:expr, $ker:ty) => {
+            mod frame {
+                #[allow(unused_imports)]
+                use crate::frame::mmm::mmm::test::*;
+                proptest::proptest! {
+                    #[test]
+                    fn mat_mul_prepacked((m, k, n, ref a, ref b) in strat_mat_mat_mul()) {
+                        if $cond {
+                            test_mat_mat_mul_prep_f16::<$ker>(m, k, n, a, b)?
+                        }
+                    }
+                }
+
+                #[test]
+                fn mat_mul_1() {
+                    if $cond {
+                        test_mat_mat_mul_prep_f16::<$ker>(
+                            3,
+                            4,
+                            2,
+                            &[-3.0, 3.0, 5.0, -5.0, 6.0, 0.0, -6.0, -5.0, 0.0, 0.0, 9.0, 7.0],
+                            &[-8.0, 5.0, 5.0, -3.0, 5.0, 7.0, -8.0, -1.0],
+                        )
+                        .unwrap()
+                    }
+                }
+            }
+        };
+    }
+
+    #[macro_export]
+    macro_rules! qmmm_frame_tests {
+        ($cond:expr, $ker:ty, $ta:ty, $tb:ty) => {
+            mod frame {
+                #[allow(unused_imports)]
+                use crate::frame::mmm::mmm::test::*;
+                proptest::proptest! {
+                    #[test]
+                    fn mat_mul_prepacked((m, k, n, ref a, ref b) in strat_q_mat_mat_mul::<$ta, $tb>()) {
+                        if $cond {
+                            test_q_mat_mat_mul_prep::<$ker, $ta, $tb>(m, k, n, a, b)?
+                        }
+                    }
+
+                    #[test]
+                    fn mat_mul_offsets((m, k, n, ref a, ref b) in strat_q_mat_mat_mul::<$ta, $tb>()) {
+                        if $cond {
+                            test_q_mat_mat_mul_offsets::<$ker, $ta, $tb>(m, k, n, a, b)?
+                        }
+                    }
+
+                    #[test]
+                    fn mat_vec_prepacked((m, k, _n, ref a, ref b) in strat_q_mat_mat_mul::<$ta, $tb>()) {
+                        if $cond {
+                            test_q_mat_vec_mul_prep::<$ker, $ta, $tb>(m, k, a, &b[..k])?
+                        }
+                    }
+
+                    #[test]
+                    fn requantize(pb in strat_q_requant::<$ta, $tb>()) {
+                        if $cond {
+                            proptest::prop_assert_eq!(pb.run::<$ker>(), pb.expected())
+                        }
+                    }
+                }
+
+                #[test]
+                fn requantize_1() {
+                    if $cond {
+                        // 10 * 0.5 / 4 rounded to 1, plus the row offset,
+                        // clamped to 3
+                        let pb = QRequantProblem::<$ta, $tb> {
+                            m: 3,
+                            k: 5,
+                            n: 2,
+                            a: vec![1 as $ta; 3 * 5],
+                            b: vec![2 as $tb; 5 * 2],
+                            c: vec![0; 3 * 2],
+                            col_sums: vec![0, 0],
+                            scale: vec![1 << 30; 3],
+                            shift: vec![-2; 3],
+                            zero_point: 1,
+                            max: 3,
+                        };
+                        assert_eq!(pb.run::<$ker>(), vec![2, 2, 2, 2, 2, 2]);
+                        assert_eq!(pb.expected(), vec![2, 2, 2, 2, 2, 2]);
+                    }
+                }
+            }
+        };
+    }
+
     pub fn strat_mat_mat_mul() -> BoxedStrategy<(usize, usize, usize, Vec<f32>, Vec<f32>)> {
         (1usize..5, 1usize..5, 1usize..5)
             .prop_flat_map(move |(m, k, n)| {
@@ -531,6 +731,79 @@ pub mod test {
         Ok(())
     }
 
+    pub fn test_mat_mat_mul_prep_f16<K: MatMatMulKer<f16>>(
+        m: usize,
+        k: usize,
+        n: usize,
+        a: &[f32],
+        b: &[f32],
+    ) -> Result<(), proptest::test_runner::TestCaseError> {
+        let op = MatMatMulImpl::<K, f16>::new(m, k, n);
+        let a: Vec<f16> = a.iter().map(|&x| x.into()).collect();
+        let b: Vec<f16> = b.iter().map(|&x| x.into()).collect();
+        unsafe {
+            let mut packed_a: Vec<f16> =
+                align::uninitialized(op.a_pack().len(), op.a_pack().alignment());
+            op.a_pack().pack(packed_a.as_mut_ptr(), a.as_ptr(), k as isize, 1);
+
+            let mut packed_b: Vec<f16> =
+                align::uninitialized(op.b_pack().len(), op.b_pack().alignment());
+            op.b_pack().pack(packed_b.as_mut_ptr(), b.as_ptr(), n as isize, 1);
+
+            let mut found = vec![f16::from(9999.0f32); m * n];
+
+            op.run(
+                &op.a_from_packed(packed_a.as_ptr()),
+                &op.b_from_packed(packed_b.as_ptr()),
+                &mut op.c_from_data_and_strides(found.as_mut_ptr(), n as isize, 1),
+                &[],
+            );
+
+            let mut expected = vec![0.0f32; m * n];
+            for x in 0..n {
+                for y in 0..m {
+                    for i in 0..k {
+                        expected[x + y * n] += a[i + k * y].0.to_f32() * b[x + i * n].0.to_f32()
+                    }
+                }
+            }
+            let found: Vec<f32> = found.iter().map(|x| x.0.to_f32()).collect();
+            crate::check_close(&*found, &*expected)?;
+        }
+        Ok(())
+    }
+
+    pub fn test_mat_mat_mul_threaded_f32<K: MatMatMulKer<f32>>(
+        m: usize,
+        k: usize,
+        n: usize,
+        a: &[f32],
+        b: &[f32],
+    ) -> Result<(), proptest::test_runner::TestCaseError> {
+        let op = MatMatMulImpl::<K, f32>::new(m, k, n);
+        let bias: Vec<f32> = (0..m).map(|i| i as f32 / 3.0).collect();
+        let run = || unsafe {
+            let mut packed_a: Vec<f32> =
+                align::uninitialized(op.a_pack().len(), op.a_pack().alignment());
+            op.a_pack().pack(packed_a.as_mut_ptr(), a.as_ptr(), k as isize, 1);
+            let mut packed_b: Vec<f32> =
+                align::uninitialized(op.b_pack().len(), op.b_pack().alignment());
+            op.b_pack().pack(packed_b.as_mut_ptr(), b.as_ptr(), n as isize, 1);
+            let mut found = vec![9999.0f32; m * n];
+            op.run(
+                &op.a_from_packed(packed_a.as_ptr()),
+                &op.b_from_packed(packed_b.as_ptr()),
+                &mut op.c_from_data_and_strides(found.as_mut_ptr(), n as isize, 1),
+                &[FusedSpec::PerRowAdd(bias.clone())],
+            );
+            found
+        };
+        let single = crate::threads::with_pool(None, run);
+        let threaded = crate::threads::with_pool(Some(crate::threads::pool(3).unwrap()), run);
+        proptest::prop_assert_eq!(single, threaded);
+        Ok(())
+    }
+
     pub fn test_mat_vec_mul_prep_f32<K: MatMatMulKer<f32>>(
         m: usize,
         k: usize,
@@ -696,6 +969,234 @@ pub mod test {
         })
     }
 
+    pub fn strat_q_mat_mat_mul<TA, TB>() -> BoxedStrategy<(usize, usize, usize, Vec<TA>, Vec<TB>)>
+    where
+        TA: Arbitrary + 'static,
+        TB: Arbitrary + 'static,
+    {
+        (1usize..20, 1usize..20, 1usize..20)
+            .prop_flat_map(move |(m, k, n)| {
+                (
+                    Just(m),
+                    Just(k),
+                    Just(n),
+                    proptest::collection::vec(any::<TA>(), m * k),
+                    proptest::collection::vec(any::<TB>(), n * k),
+                )
+            })
+            .boxed()
+    }
+
+    pub fn q_expected<TA, TB>(m: usize, k: usize, n: usize, a: &[TA], b: &[TB]) -> Vec<i32>
+    where
+        TA: Copy + AsPrimitive<i32>,
+        TB: Copy + AsPrimitive<i32>,
+    {
+        let mut expected = vec![0i32; m * n];
+        for x in 0..n {
+            for y in 0..m {
+                for i in 0..k {
+                    expected[x + y * n] += a[i + k * y].as_() * b[x + i * n].as_()
+                }
+            }
+        }
+        expected
+    }
+
+    pub fn test_q_mat_mat_mul_prep<K, TA, TB>(
+        m: usize,
+        k: usize,
+        n: usize,
+        a: &[TA],
+        b: &[TB],
+    ) -> proptest::test_runner::TestCaseResult
+    where
+        K: MatMatMulKer<TA, TB, i32, i32>,
+        TA: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync + AsPrimitive<i32>,
+        TB: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync + AsPrimitive<i32>,
+    {
+        let op = MatMatMulImpl::<K, TA, TB, i32, i32>::new(m, k, n);
+        unsafe {
+            let mut packed_a: Vec<TA> =
+                align::uninitialized(op.a_pack().len(), op.a_pack().alignment());
+            op.a_pack().pack(packed_a.as_mut_ptr(), a.as_ptr(), k as isize, 1);
+            let mut packed_b: Vec<TB> =
+                align::uninitialized(op.b_pack().len(), op.b_pack().alignment());
+            op.b_pack().pack(packed_b.as_mut_ptr(), b.as_ptr(), n as isize, 1);
+            let mut found = vec![9999i32; m * n];
+            op.run_q(
+                &op.a_from_packed(packed_a.as_ptr()),
+                &op.b_from_packed(packed_b.as_ptr()),
+                &mut op.c_from_data_and_strides(found.as_mut_ptr(), n as isize, 1),
+                &[],
+            );
+            proptest::prop_assert_eq!(found, q_expected(m, k, n, a, b));
+        }
+        Ok(())
+    }
+
+    pub fn test_q_mat_mat_mul_offsets<K, TA, TB>(
+        m: usize,
+        k: usize,
+        n: usize,
+        a: &[TA],
+        b: &[TB],
+    ) -> proptest::test_runner::TestCaseResult
+    where
+        K: MatMatMulKer<TA, TB, i32, i32>,
+        TA: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync + AsPrimitive<i32>,
+        TB: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync + AsPrimitive<i32>,
+    {
+        let op = MatMatMulImpl::<K, TA, TB, i32, i32>::new(m, k, n);
+        unsafe {
+            let mut packed_a: Vec<TA> =
+                align::uninitialized(op.a_pack().len(), op.a_pack().alignment());
+            op.a_pack().pack(packed_a.as_mut_ptr(), a.as_ptr(), k as isize, 1);
+            let rows_offsets: Vec<isize> = (0..k).map(|i| (i * n) as isize).collect();
+            let cols_offsets: Vec<isize> = (0..n).map(|i| i as isize).collect();
+            let mut found = vec![9999i32; m * n];
+            op.run_q(
+                &op.a_from_packed(packed_a.as_ptr()),
+                &op.b_from_data_and_offsets(b.as_ptr(), &rows_offsets, &cols_offsets),
+                &mut op.c_from_data_and_strides(found.as_mut_ptr(), n as isize, 1),
+                &[],
+            );
+            proptest::prop_assert_eq!(found, q_expected(m, k, n, a, b));
+        }
+        Ok(())
+    }
+
+    pub fn test_q_mat_vec_mul_prep<K, TA, TB>(
+        m: usize,
+        k: usize,
+        a: &[TA],
+        b: &[TB],
+    ) -> proptest::test_runner::TestCaseResult
+    where
+        K: MatMatMulKer<TA, TB, i32, i32>,
+        TA: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync + AsPrimitive<i32>,
+        TB: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync + AsPrimitive<i32>,
+    {
+        let op = MatMatMulImpl::<K, TA, TB, i32, i32>::new(m, k, 1);
+        unsafe {
+            let mut packed_a: Vec<TA> =
+                align::uninitialized(op.a_pack().len(), op.a_pack().alignment());
+            op.a_pack().pack(packed_a.as_mut_ptr(), a.as_ptr(), k as isize, 1);
+            let mut found = vec![9999i32; m];
+            op.run_q(
+                &op.a_from_packed(packed_a.as_ptr()),
+                &op.b_vec_from_data_and_stride(b.as_ptr(), 1),
+                &mut op.c_vec_from_data_and_stride(found.as_mut_ptr(), 1),
+                &[],
+            );
+            proptest::prop_assert_eq!(found, q_expected(m, k, 1, a, b));
+        }
+        Ok(())
+    }
+
+    /// Integer product requantized the way quantized operators do it: column
+    /// sums correction, per-row scale and shift, output zero point and
+    /// saturation.
+    #[derive(Clone, Debug)]
+    pub struct QRequantProblem<TA, TB> {
+        pub m: usize,
+        pub k: usize,
+        pub n: usize,
+        pub a: Vec<TA>,
+        pub b: Vec<TB>,
+        pub c: Vec<i32>,
+        pub col_sums: Vec<i32>,
+        pub scale: Vec<i32>,
+        pub shift: Vec<i32>,
+        pub zero_point: i32,
+        pub max: i32,
+    }
+
+    impl<TA, TB> QRequantProblem<TA, TB>
+    where
+        TA: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync + AsPrimitive<i32>,
+        TB: Copy + Add + Mul + Zero + Debug + PartialEq + Send + Sync + AsPrimitive<i32>,
+    {
+        pub fn specs(&self) -> Vec<QFusedSpec> {
+            vec![
+                FusedSpec::AddC.into(),
+                FusedSpec::PerColAdd(self.col_sums.clone()).into(),
+                QFusedSpec::PerRowQShift(self.shift.iter().map(|&s| s.max(0)).collect()),
+                QFusedSpec::PerRowQScale(self.scale.clone()),
+                QFusedSpec::PerRowQShift(self.shift.iter().map(|&s| s.min(0)).collect()),
+                FusedSpec::PerRowAdd(vec![self.zero_point; self.m]).into(),
+                FusedSpec::Max(0).into(),
+                FusedSpec::Min(self.max).into(),
+            ]
+        }
+
+        pub fn expected(&self) -> Vec<i32> {
+            let mut c = q_expected(self.m, self.k, self.n, &self.a, &self.b);
+            for y in 0..self.m {
+                for x in 0..self.n {
+                    let mut v = c[x + y * self.n] as i64
+                        + self.c[x + y * self.n] as i64
+                        + self.col_sums[x] as i64;
+                    let shift = self.shift[y] as i64;
+                    if shift > 0 {
+                        v = (v << shift).max(std::i32::MIN as i64).min(std::i32::MAX as i64);
+                    }
+                    v = (v * self.scale[y] as i64 + (1 << 30)) >> 31;
+                    if shift < 0 {
+                        v = (v + (1 << (-shift - 1))) >> -shift;
+                    }
+                    v += self.zero_point as i64;
+                    c[x + y * self.n] = v.max(0).min(self.max as i64) as i32;
+                }
+            }
+            c
+        }
+
+        pub fn run<K: MatMatMulKer<TA, TB, i32, i32>>(&self) -> Vec<i32> {
+            let (m, k, n) = (self.m, self.k, self.n);
+            let op = MatMatMulImpl::<K, TA, TB, i32, i32>::new(m, k, n);
+            unsafe {
+                let mut packed_a: Vec<TA> =
+                    align::uninitialized(op.a_pack().len(), op.a_pack().alignment());
+                op.a_pack().pack(packed_a.as_mut_ptr(), self.a.as_ptr(), k as isize, 1);
+                let mut packed_b: Vec<TB> =
+                    align::uninitialized(op.b_pack().len(), op.b_pack().alignment());
+                op.b_pack().pack(packed_b.as_mut_ptr(), self.b.as_ptr(), n as isize, 1);
+                let mut found = self.c.clone();
+                op.run_q(
+                    &op.a_from_packed(packed_a.as_ptr()),
+                    &op.b_from_packed(packed_b.as_ptr()),
+                    &mut op.c_from_data_and_strides(found.as_mut_ptr(), n as isize, 1),
+                    &self.specs(),
+                );
+                found
+            }
+        }
+    }
+
+    pub fn strat_q_requant<TA, TB>() -> BoxedStrategy<QRequantProblem<TA, TB>>
+    where
+        TA: Arbitrary + Clone + Debug + 'static,
+        TB: Arbitrary + Clone + Debug + 'static,
+    {
+        strat_q_mat_mat_mul::<TA, TB>()
+            .prop_flat_map(|(m, k, n, a, b)| {
+                (
+                    Just((m, k, n, a, b)),
+                    proptest::collection::vec(-1000i32..1000, m * n),
+                    proptest::collection::vec(-1000i32..1000, n),
+                    proptest::collection::vec(1i32 << 30..std::i32::MAX, m),
+                    proptest::collection::vec(-12i32..4, m),
+                    -128i32..128,
+                    prop_oneof!(Just(127), Just(255)),
+                )
+            })
+            .prop_map(|((m, k, n, a, b), c, col_sums, scale, shift, zero_point, max)| {
+                QRequantProblem { m, k, n, a, b, c, col_sums, scale, shift, zero_point, max }
+            })
+            .boxed()
+    }
+
     #[derive(Clone, Debug)]
     pub struct ConvProblem {
         pub ci: usize,
@@ -69,8 +69,41 @@ where
         }
     }
 
+    /// Copies the (down, right) tile of self to a mr x nr buffer, the
+    /// reverse of `set_from_mmm`.
+    pub(super) unsafe fn get_into_mmm(
+        &self,
+        down: usize,
+        right: usize,
+        height: usize,
+        width: usize,
+        mmm: *mut T,
+    ) {
+        match self {
+            StorageSpec::Strides { ptr, row_byte_stride, col_byte_stride, mr, nr } => {
+                for y in 0..height {
+                    for x in 0..width {
+                        let ptr = ((*ptr as isize)
+                            + (*row_byte_stride as usize * (down * *mr + y)
+                                + *col_byte_stride as usize * (right * *nr + x))
+                                as isize) as *const T;
+                        *mmm.add(y * *nr + x) = *ptr;
+                    }
+                }
+            }
+            StorageSpec::VecStride { ptr, byte_stride, mr, nr } => {
+                for y in 0..height {
+                    let ptr =
+                        ((*ptr as isize) + (*byte_stride * (down * *mr + y) as isize)) as *const T;
+                    *mmm.add(y * *nr) = *ptr;
+                }
+            }
+            _ => unimplemented!(),
+        }
+    }
+
     pub(super) unsafe fn set_from_mmm(
-        &mut self,
+        &self,
         down: usize,
         right: usize,
         height: usize,
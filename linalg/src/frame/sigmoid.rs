@@ -11,6 +11,12 @@ impl SigmoidFunc for f32 {
     }
 }
 
+impl SigmoidFunc for crate::f16::f16 {
+    fn sigmoid(self) -> crate::f16::f16 {
+        crate::generic::sigmoid::ssigmoid(self.0.to_f32()).into()
+    }
+}
+
 pub trait Sigmoid<T>: Send + Sync + Debug + objekt::Clone
 where
     T: Copy + Debug + PartialEq + Send + Sync + SigmoidFunc,
@@ -70,6 +76,7 @@ where
 #[macro_use]
 pub mod test {
     use super::SigmoidKer;
+    use crate::f16::f16;
     use proptest::test_runner::TestCaseResult;
 
     #[macro_export]
@@ -121,4 +128,14 @@ pub mod test {
         let expected = values.iter().map(|x| 1.0 / (1.0 + (-x).exp())).collect::<Vec<_>>();
         crate::check_close(&*found, &*expected)
     }
+
+    pub fn test_sigmoid_f16<K: SigmoidKer<f16>>(values: &[f32]) -> TestCaseResult {
+        use crate::frame::sigmoid::Sigmoid;
+        let op = crate::frame::sigmoid::SigmoidImpl::<K, f16>::new();
+        let mut found: Vec<f16> = values.iter().map(|&x| x.into()).collect();
+        op.run(&mut found);
+        let found: Vec<f32> = found.iter().map(|x| x.0.to_f32()).collect();
+        let expected = values.iter().map(|x| 1.0 / (1.0 + (-x).exp())).collect::<Vec<_>>();
+        crate::check_close(&*found, &*expected)
+    }
 }
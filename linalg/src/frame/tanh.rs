@@ -11,6 +11,12 @@ impl TanhFunc for f32 {
     }
 }
 
+impl TanhFunc for crate::f16::f16 {
+    fn tanh(self) -> crate::f16::f16 {
+        crate::generic::tanh::stanh(self.0.to_f32()).into()
+    }
+}
+
 pub trait Tanh<T>: Send + Sync + Debug + objekt::Clone
 where
     T: Copy + Debug + PartialEq + Send + Sync + TanhFunc,
@@ -70,6 +76,7 @@ where
 #[macro_use]
 pub mod test {
     use super::TanhKer;
+    use crate::f16::f16;
     use proptest::test_runner::TestCaseResult;
 
     #[macro_export]
@@ -120,4 +127,14 @@ pub mod test {
         let expected = values.iter().map(|x| x.tanh()).collect::<Vec<_>>();
         crate::check_close(&*found, &*expected)
     }
+
+    pub fn test_tanh_f16<K: TanhKer<f16>>(values: &[f32]) -> TestCaseResult {
+        use crate::frame::tanh::Tanh;
+        let op = crate::frame::tanh::TanhImpl::<K, f16>::new();
+        let mut found: Vec<f16> = values.iter().map(|&x| x.into()).collect();
+        op.run(&mut found);
+        let found: Vec<f32> = found.iter().map(|x| x.0.to_f32()).collect();
+        let expected = values.iter().map(|x| x.tanh()).collect::<Vec<_>>();
+        crate::check_close(&*found, &*expected)
+    }
 }
@@ -128,6 +128,7 @@ where
 pub mod test {
     use super::*;
     use crate::align;
+    use crate::f16::f16;
     use proptest::prelude::*;
 
     pub fn strat_vec_mat_mul() -> BoxedStrategy<(usize, usize, Vec<f32>, Vec<f32>)> {
@@ -173,4 +174,38 @@ pub mod test {
         Ok(())
     }
 
+    pub fn test_vec_mat_mul_prep_f16<VMM: VecMatMul<f16>>(
+        mm: VMM,
+        k: usize,
+        n: usize,
+        a: &[f32],
+        b: &[f32],
+    ) -> Result<(), proptest::test_runner::TestCaseError> {
+        let a: Vec<f16> = a.iter().map(|&x| x.into()).collect();
+        let b: Vec<f16> = b.iter().map(|&x| x.into()).collect();
+        unsafe {
+            let mut packed_a: Vec<f16> =
+                align::uninitialized(mm.packed_a_len(), mm.packed_a_alignment());
+            mm.pack_a(packed_a.as_mut_ptr(), a.as_ptr(), 1);
+
+            let mut packed_b: Vec<f16> =
+                align::uninitialized(mm.b_pack().len(), mm.b_pack().alignment());
+            mm.b_pack().pack(packed_b.as_mut_ptr(), b.as_ptr(), n as isize, 1);
+
+            let mut found = vec![f16::from(9999.0f32); n];
+
+            mm.vec_mat_mul_prepacked(packed_a.as_ptr(), packed_b.as_ptr(), found.as_mut_ptr(), 1);
+            let mut expect = vec![0.0f32; n];
+            for x in 0..n {
+                for i in 0..k {
+                    expect[x] += a[i].0.to_f32() * b[x + i * n].0.to_f32()
+                }
+            }
+            // accumulation is done in f32, so only the final rounding differs
+            let expect: Vec<f16> = expect.into_iter().map(|x| x.into()).collect();
+            prop_assert_eq!(found, expect);
+        }
+        Ok(())
+    }
+
 }
@@ -1,9 +1,13 @@
+pub mod hmmm;
 pub mod mmm;
+pub mod qmmm;
 pub mod sigmoid;
 pub mod tanh;
 pub mod vecmatmul;
 
+pub use self::hmmm::HMmm4x4;
 pub use self::mmm::SMmm4x4;
-pub use self::sigmoid::SSigmoid4;
-pub use self::tanh::STanh4;
-pub use self::vecmatmul::SVecMatMul8;
+pub use self::qmmm::QMmm4x4;
+pub use self::sigmoid::{HSigmoid4, SSigmoid4};
+pub use self::tanh::{HTanh4, STanh4};
+pub use self::vecmatmul::{HVecMatMul8, SVecMatMul8};
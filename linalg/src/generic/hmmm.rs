@@ -0,0 +1,171 @@
+use crate::f16::f16;
+use crate::frame::mmm::LinearSpec::*;
+use crate::frame::mmm::StorageKerSpec::*;
+use crate::frame::mmm::*;
+
+/// Half precision kernel: f16 operands and storage, accumulating in f32.
+#[derive(Copy, Clone, Debug)]
+pub struct HMmm4x4;
+
+impl MatMatMulKer<f16> for HMmm4x4 {
+    #[inline(always)]
+    fn name() -> &'static str {
+        "generic-h"
+    }
+    #[inline(always)]
+    fn mr() -> usize {
+        4
+    }
+    #[inline(always)]
+    fn nr() -> usize {
+        4
+    }
+    #[inline(always)]
+    fn alignment_bytes_packed_a() -> usize {
+        2
+    }
+    #[inline(always)]
+    fn alignment_bytes_packed_b() -> usize {
+        2
+    }
+    #[inline(never)]
+    fn kernel(spec: &MatMatMulKerSpec<f16>) -> isize {
+        unsafe {
+            let mut ab = [[0.0f32; 4]; 4];
+            match (*spec.a, *spec.b, *spec.linear) {
+                (Packed { ptr: a }, Packed { ptr: b }, Mul { k }) => {
+                    for i in 0..k {
+                        let a = std::slice::from_raw_parts(a.offset(4 * i as isize), 4);
+                        let b = std::slice::from_raw_parts(b.offset(4 * i as isize), 4);
+                        for row in 0..4 {
+                            for col in 0..4 {
+                                ab[row][col] += a[row].0.to_f32() * b[col].0.to_f32();
+                            }
+                        }
+                    }
+                }
+                (Packed { ptr: a }, OffsetsAndPtrs { row_byte_offsets, col_ptrs }, Mul { k }) => {
+                    let pb = [
+                        *(col_ptrs.offset(0)),
+                        *(col_ptrs.offset(1)),
+                        *(col_ptrs.offset(2)),
+                        *(col_ptrs.offset(3)),
+                    ];
+                    for i in 0..k {
+                        let a = std::slice::from_raw_parts(a.offset(4 * i as isize), 4);
+                        let offset = *row_byte_offsets.offset(i as isize) / 2;
+                        for col in 0..4 {
+                            let b = (*pb[col].offset(offset)).0.to_f32();
+                            for row in 0..4 {
+                                ab[row][col] += a[row].0.to_f32() * b;
+                            }
+                        }
+                    }
+                }
+                (Packed { ptr: a }, VecStride { ptr: b, byte_stride }, Mul { k }) => {
+                    for i in 0..k {
+                        let a = std::slice::from_raw_parts(a.offset(4 * i as isize), 4);
+                        let b = (*b.offset(i as isize * byte_stride / 2)).0.to_f32();
+                        for row in 0..4 {
+                            ab[row][0] += a[row].0.to_f32() * b;
+                        }
+                    }
+                }
+                _ => return 1,
+            }
+            let mut pnl = spec.non_linear;
+            loop {
+                if pnl.is_null() {
+                    break;
+                }
+                match *pnl {
+                    FusedKerSpec::Done => break,
+                    FusedKerSpec::AddC => match *spec.c {
+                        Strides { ptr: c, row_byte_stride, col_byte_stride } => {
+                            let rsc = row_byte_stride as usize / 2;
+                            let csc = col_byte_stride as usize / 2;
+                            let c = std::slice::from_raw_parts_mut(c, 1 + 3 * csc + 3 * rsc);
+                            for row in 0..4 {
+                                for col in 0..4 {
+                                    ab[row][col] += c[col * csc + row * rsc].0.to_f32();
+                                }
+                            }
+                        }
+                        _ => return 1,
+                    },
+                    FusedKerSpec::PerRowMul(bias) => {
+                        for i in 0..4 {
+                            for j in 0..4 {
+                                ab[i][j] *= (*bias.offset(i as isize)).0.to_f32();
+                            }
+                        }
+                    }
+                    FusedKerSpec::PerRowAdd(bias) => {
+                        for i in 0..4 {
+                            for j in 0..4 {
+                                ab[i][j] += (*bias.offset(i as isize)).0.to_f32();
+                            }
+                        }
+                    }
+                    FusedKerSpec::PerColMul(bias) => {
+                        for i in 0..4 {
+                            for j in 0..4 {
+                                ab[j][i] *= (*bias.offset(i as isize)).0.to_f32();
+                            }
+                        }
+                    }
+                    FusedKerSpec::PerColAdd(bias) => {
+                        for i in 0..4 {
+                            for j in 0..4 {
+                                ab[j][i] += (*bias.offset(i as isize)).0.to_f32();
+                            }
+                        }
+                    }
+                    FusedKerSpec::Min(m) => {
+                        for i in 0..4 {
+                            for j in 0..4 {
+                                ab[i][j] = ab[i][j].min(m.0.to_f32())
+                            }
+                        }
+                    }
+                    FusedKerSpec::Max(m) => {
+                        for i in 0..4 {
+                            for j in 0..4 {
+                                ab[i][j] = ab[i][j].max(m.0.to_f32())
+                            }
+                        }
+                    }
+                    FusedKerSpec::PerRowQScale(_) | FusedKerSpec::PerRowQShift(_) => return 1,
+                }
+                pnl = pnl.add(1);
+            }
+            match *spec.c {
+                Strides { ptr: c, row_byte_stride, col_byte_stride } => {
+                    let rsc = row_byte_stride as usize / 2;
+                    let csc = col_byte_stride as usize / 2;
+                    let c = std::slice::from_raw_parts_mut(c, 1 + 3 * csc + 3 * rsc);
+                    for row in 0..4 {
+                        for col in 0..4 {
+                            c[col * csc + row * rsc] = ab[row][col].into();
+                        }
+                    }
+                }
+                VecStride { ptr: c, byte_stride } => {
+                    let stride = byte_stride / 2;
+                    let c: *mut f16 = c as _;
+                    for row in 0..4 {
+                        *c.offset(row as isize * stride) = ab[row][0].into();
+                    }
+                }
+                _ => return 1,
+            }
+        }
+        return 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    mmm_half_kernel_tests!(true, crate::generic::HMmm4x4);
+    mmm_half_frame_tests!(true, crate::generic::HMmm4x4);
+}
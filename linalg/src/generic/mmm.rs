@@ -172,6 +172,7 @@ impl MatMatMulKer<f32> for SMmm4x4 {
                             }
                         }
                     }
+                    FusedKerSpec::PerRowQScale(_) | FusedKerSpec::PerRowQShift(_) => return 1,
                 }
                 pnl = pnl.add(1);
             }
@@ -343,6 +344,7 @@ impl MatMatMulKer<f32> for SMmmTest3x2 {
                             }
                         }
                     }
+                    FusedKerSpec::PerRowQScale(_) | FusedKerSpec::PerRowQShift(_) => return 1,
                 }
                 pnl = pnl.add(1);
             }
@@ -0,0 +1,236 @@
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::ops::{Add, Mul};
+
+use num_traits::{AsPrimitive, Zero};
+
+use crate::frame::mmm::LinearSpec;
+use crate::frame::mmm::StorageKerSpec::*;
+use crate::frame::mmm::*;
+
+/// Integer kernel: 8-bit operands, accumulating and storing in i32.
+#[derive(Copy, Clone, Debug)]
+pub struct QMmm4x4<TA, TB>(PhantomData<(TA, TB)>);
+
+#[inline(always)]
+fn q_scale(x: i32, mult: i32) -> i32 {
+    ((x as i64 * mult as i64 + (1 << 30)) >> 31) as i32
+}
+
+#[inline(always)]
+fn q_shift(x: i32, shift: i32) -> i32 {
+    if shift >= 0 {
+        let shifted = (x as i64) << shift.min(32);
+        shifted.max(std::i32::MIN as i64).min(std::i32::MAX as i64) as i32
+    } else {
+        let shift = -shift.max(-32);
+        ((x as i64 + (1 << (shift - 1))) >> shift) as i32
+    }
+}
+
+impl<TA, TB> MatMatMulKer<TA, TB, i32, i32> for QMmm4x4<TA, TB>
+where
+    TA: Copy + Debug + Add + Mul + Zero + AsPrimitive<i32> + Send + Sync,
+    TB: Copy + Debug + Add + Mul + Zero + AsPrimitive<i32> + Send + Sync,
+{
+    #[inline(always)]
+    fn name() -> &'static str {
+        "generic-q"
+    }
+    #[inline(always)]
+    fn mr() -> usize {
+        4
+    }
+    #[inline(always)]
+    fn nr() -> usize {
+        4
+    }
+    #[inline(always)]
+    fn alignment_bytes_packed_a() -> usize {
+        4
+    }
+    #[inline(always)]
+    fn alignment_bytes_packed_b() -> usize {
+        4
+    }
+    #[inline(never)]
+    fn kernel(spec: &MatMatMulKerSpec<TA, TB, i32, i32>) -> isize {
+        unsafe {
+            let mut ab = [[0i32; 4]; 4];
+            match (*spec.a, *spec.b, *spec.linear) {
+                (Packed { ptr: a }, Packed { ptr: b }, LinearSpec::Mul { k }) => {
+                    for i in 0..k {
+                        let a = std::slice::from_raw_parts(a.offset(4 * i as isize), 4);
+                        let b = std::slice::from_raw_parts(b.offset(4 * i as isize), 4);
+                        for row in 0..4 {
+                            for col in 0..4 {
+                                ab[row][col] += a[row].as_() * b[col].as_();
+                            }
+                        }
+                    }
+                }
+                (
+                    Packed { ptr: a },
+                    OffsetsAndPtrs { row_byte_offsets, col_ptrs },
+                    LinearSpec::Mul { k },
+                ) => {
+                    let pb = [
+                        *(col_ptrs.offset(0)),
+                        *(col_ptrs.offset(1)),
+                        *(col_ptrs.offset(2)),
+                        *(col_ptrs.offset(3)),
+                    ];
+                    for i in 0..k {
+                        let a = std::slice::from_raw_parts(a.offset(4 * i as isize), 4);
+                        let offset = *row_byte_offsets.offset(i as isize)
+                            / std::mem::size_of::<TB>() as isize;
+                        for col in 0..4 {
+                            let b: i32 = (*pb[col].offset(offset)).as_();
+                            for row in 0..4 {
+                                ab[row][col] += a[row].as_() * b;
+                            }
+                        }
+                    }
+                }
+                (Packed { ptr: a }, VecStride { ptr: b, byte_stride }, LinearSpec::Mul { k }) => {
+                    for i in 0..k {
+                        let a = std::slice::from_raw_parts(a.offset(4 * i as isize), 4);
+                        let b: i32 = (*b
+                            .offset(i as isize * byte_stride / std::mem::size_of::<TB>() as isize))
+                        .as_();
+                        for row in 0..4 {
+                            ab[row][0] += a[row].as_() * b;
+                        }
+                    }
+                }
+                _ => return 1,
+            }
+            let mut pnl = spec.non_linear;
+            loop {
+                if pnl.is_null() {
+                    break;
+                }
+                match *pnl {
+                    FusedKerSpec::Done => break,
+                    FusedKerSpec::AddC => match *spec.c {
+                        Strides { ptr: c, row_byte_stride, col_byte_stride } => {
+                            let rsc = row_byte_stride as usize / 4;
+                            let csc = col_byte_stride as usize / 4;
+                            let c = std::slice::from_raw_parts_mut(c, 1 + 3 * csc + 3 * rsc);
+                            for row in 0..4 {
+                                for col in 0..4 {
+                                    ab[row][col] += c[col * csc + row * rsc];
+                                }
+                            }
+                        }
+                        _ => return 1,
+                    },
+                    FusedKerSpec::PerRowMul(bias) => {
+                        for i in 0..4 {
+                            for j in 0..4 {
+                                ab[i][j] *= *bias.offset(i as isize);
+                            }
+                        }
+                    }
+                    FusedKerSpec::PerRowAdd(bias) => {
+                        for i in 0..4 {
+                            for j in 0..4 {
+                                ab[i][j] += *bias.offset(i as isize);
+                            }
+                        }
+                    }
+                    FusedKerSpec::PerColMul(bias) => {
+                        for i in 0..4 {
+                            for j in 0..4 {
+                                ab[j][i] *= *bias.offset(i as isize);
+                            }
+                        }
+                    }
+                    FusedKerSpec::PerColAdd(bias) => {
+                        for i in 0..4 {
+                            for j in 0..4 {
+                                ab[j][i] += *bias.offset(i as isize);
+                            }
+                        }
+                    }
+                    FusedKerSpec::Min(m) => {
+                        for i in 0..4 {
+                            for j in 0..4 {
+                                ab[i][j] = ab[i][j].min(m)
+                            }
+                        }
+                    }
+                    FusedKerSpec::Max(m) => {
+                        for i in 0..4 {
+                            for j in 0..4 {
+                                ab[i][j] = ab[i][j].max(m)
+                            }
+                        }
+                    }
+                    FusedKerSpec::PerRowQScale(mult) => {
+                        for i in 0..4 {
+                            for j in 0..4 {
+                                ab[i][j] = q_scale(ab[i][j], *mult.offset(i as isize))
+                            }
+                        }
+                    }
+                    FusedKerSpec::PerRowQShift(shift) => {
+                        for i in 0..4 {
+                            for j in 0..4 {
+                                ab[i][j] = q_shift(ab[i][j], *shift.offset(i as isize))
+                            }
+                        }
+                    }
+                }
+                pnl = pnl.add(1);
+            }
+            match *spec.c {
+                Strides { ptr: c, row_byte_stride, col_byte_stride } => {
+                    let rsc = row_byte_stride as usize / 4;
+                    let csc = col_byte_stride as usize / 4;
+                    let c = std::slice::from_raw_parts_mut(c, 1 + 3 * csc + 3 * rsc);
+                    for row in 0..4 {
+                        for col in 0..4 {
+                            c[col * csc + row * rsc] = ab[row][col];
+                        }
+                    }
+                }
+                VecStride { ptr: c, byte_stride } => {
+                    let stride = byte_stride / 4;
+                    let c: *mut i32 = c as _;
+                    for row in 0..4 {
+                        *c.offset(row as isize * stride) = ab[row][0];
+                    }
+                }
+                _ => return 1,
+            }
+        }
+        return 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    mod i8_i8 {
+        qmmm_frame_tests!(true, crate::generic::QMmm4x4<i8, i8>, i8, i8);
+    }
+
+    mod u8_i8 {
+        qmmm_frame_tests!(true, crate::generic::QMmm4x4<u8, i8>, u8, i8);
+    }
+
+    #[test]
+    fn q_scale_and_shift() {
+        assert_eq!(q_scale(10, 1 << 30), 5);
+        assert_eq!(q_scale(-10, 1 << 30), -5);
+        assert_eq!(q_scale(3, 1 << 30), 2);
+        assert_eq!(q_shift(10, -2), 3);
+        assert_eq!(q_shift(-10, -2), -2);
+        assert_eq!(q_shift(10, 0), 10);
+        assert_eq!(q_shift(-10, 2), -40);
+        assert_eq!(q_shift(1 << 30, 2), std::i32::MAX);
+        assert_eq!(q_shift(-(1 << 30), 2), std::i32::MIN);
+    }
+}
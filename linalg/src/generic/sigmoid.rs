@@ -1,3 +1,4 @@
+use crate::f16::f16;
 use crate::frame::sigmoid::SigmoidKer;
 
 const LOW: f32 = -18.0;
@@ -59,8 +60,39 @@ impl SigmoidKer<f32> for SSigmoid4 {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct HSigmoid4;
+
+impl SigmoidKer<f16> for HSigmoid4 {
+    fn name() -> &'static str {
+        "generic"
+    }
+
+    fn alignment_bytes() -> usize {
+        8
+    }
+
+    fn nr() -> usize {
+        4
+    }
+
+    fn run(x: &mut [f16]) {
+        debug_assert!(x.len() % Self::nr() == 0);
+        debug_assert!(x.as_ptr() as usize % Self::alignment_bytes() == 0);
+        x.iter_mut().for_each(|px| *px = ssigmoid(px.0.to_f32()).into())
+    }
+}
+
 #[cfg(test)]
 #[macro_use]
 pub mod test {
     sigmoid_frame_tests!(true, crate::generic::sigmoid::SSigmoid4);
+
+    #[test]
+    fn half() {
+        crate::frame::sigmoid::test::test_sigmoid_f16::<crate::generic::sigmoid::HSigmoid4>(&[
+            -20.0, -4.0, -1.0, -0.5, 0.0, 0.5, 1.0, 4.0, 20.0,
+        ])
+        .unwrap()
+    }
 }
@@ -1,3 +1,4 @@
+use crate::f16::f16;
 use crate::frame::tanh::TanhKer;
 
 const LOW: f32 = -9.0;
@@ -57,8 +58,39 @@ impl TanhKer<f32> for STanh4 {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct HTanh4;
+
+impl TanhKer<f16> for HTanh4 {
+    fn name() -> &'static str {
+        "generic"
+    }
+
+    fn alignment_bytes() -> usize {
+        8
+    }
+
+    fn nr() -> usize {
+        4
+    }
+
+    fn run(x: &mut [f16]) {
+        debug_assert!(x.len() % Self::nr() == 0);
+        debug_assert!(x.as_ptr() as usize % Self::alignment_bytes() == 0);
+        x.iter_mut().for_each(|px| *px = stanh(px.0.to_f32()).into())
+    }
+}
+
 #[cfg(test)]
 #[macro_use]
 pub mod test {
     tanh_frame_tests!(true, crate::generic::tanh::STanh4);
+
+    #[test]
+    fn half() {
+        crate::frame::tanh::test::test_tanh_f16::<crate::generic::tanh::HTanh4>(&[
+            -20.0, -4.0, -1.0, -0.5, 0.0, 0.5, 1.0, 4.0, 20.0,
+        ])
+        .unwrap()
+    }
 }
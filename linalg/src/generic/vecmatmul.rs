@@ -1,3 +1,4 @@
+use crate::f16::f16;
 use crate::frame;
 
 #[derive(Copy, Clone, Debug)]
@@ -37,6 +38,43 @@ impl frame::vecmatmul::VecMatMulKer<f32> for SVecMatMul8 {
     }
 }
 
+#[derive(Copy, Clone, Debug)]
+pub struct HVecMatMul8;
+
+impl frame::vecmatmul::VecMatMulKer<f16> for HVecMatMul8 {
+    #[inline(always)]
+    fn name() -> &'static str {
+        "generic"
+    }
+    fn nr() -> usize {
+        8
+    }
+    #[inline(always)]
+    fn alignment_bytes_a() -> usize {
+        2
+    }
+    #[inline(always)]
+    fn alignment_bytes_b() -> usize {
+        2
+    }
+    #[inline(always)]
+    fn kernel(k: usize, a: *const f16, b: *const f16, c: *mut f16, sy: usize) {
+        unsafe {
+            let mut ab = [0.0f32; 8];
+            for i in 0..k {
+                let a = (*a.offset(i as isize)).0.to_f32();
+                let b = std::slice::from_raw_parts(b.offset(8 * i as isize), 8);
+                for j in 0..8 {
+                    ab[j] += a * b[j].0.to_f32();
+                }
+            }
+            for i in 0..8 {
+                *c.offset((i * sy) as isize) = ab[i].into();
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -52,6 +90,14 @@ mod test {
         }
     }
 
+    proptest! {
+        #[test]
+        fn half_vec_mat_mul_prepacked((k, n, ref a, ref b) in strat_vec_mat_mul()) {
+            let mm = PackedVecMatMul::<HVecMatMul8, f16>::new(k, n);
+            test_vec_mat_mul_prep_f16(mm, k, n, a, b)?
+        }
+    }
+
     #[test]
     fn test_1() {
         let mm = PackedVecMatMul::<SVecMatMul8, f32>::new(1, 5);
@@ -8,12 +8,14 @@ extern crate objekt;
 extern crate num_traits;
 #[cfg(test)]
 extern crate proptest;
+extern crate rayon;
 
 pub mod align;
 pub mod f16;
 #[macro_use]
 pub mod frame;
 mod generic;
+pub mod threads;
 
 #[cfg(target_arch = "x86_64")]
 pub mod x86_64_fma;
@@ -30,22 +32,46 @@ pub use self::frame::tanh;
 pub use self::frame::vecmatmul;
 
 pub struct Ops {
+    pub hvmm: Box<dyn Fn(usize, usize) -> Box<dyn vecmatmul::VecMatMul<f16::f16>> + Send + Sync>,
+    pub hmmm: Box<dyn Fn(usize, usize, usize) -> Box<dyn mmm::MatMatMul<f16::f16>> + Send + Sync>,
     pub svmm: Box<dyn Fn(usize, usize) -> Box<dyn vecmatmul::VecMatMul<f32>> + Send + Sync>,
     pub smmm: Box<dyn Fn(usize, usize, usize) -> Box<dyn mmm::MatMatMul<f32>> + Send + Sync>,
+    pub qmmm_i8_i8:
+        Box<dyn Fn(usize, usize, usize) -> Box<dyn mmm::QMatMatMul<i8, i8>> + Send + Sync>,
+    pub qmmm_u8_i8:
+        Box<dyn Fn(usize, usize, usize) -> Box<dyn mmm::QMatMatMul<u8, i8>> + Send + Sync>,
     pub ssigmoid: Box<dyn Fn() -> Box<dyn sigmoid::Sigmoid<f32>> + Send + Sync>,
     pub stanh: Box<dyn Fn() -> Box<dyn tanh::Tanh<f32>> + Send + Sync>,
+    pub hsigmoid: Box<dyn Fn() -> Box<dyn sigmoid::Sigmoid<f16::f16>> + Send + Sync>,
+    pub htanh: Box<dyn Fn() -> Box<dyn tanh::Tanh<f16::f16>> + Send + Sync>,
 }
 
 pub fn generic() -> Ops {
     Ops {
+        hvmm: Box::new(|k, n| {
+            Box::new(vecmatmul::PackedVecMatMul::<generic::HVecMatMul8, f16::f16>::new(k, n))
+        }),
+        hmmm: Box::new(|m, k, n| {
+            Box::new(mmm::MatMatMulImpl::<generic::HMmm4x4, f16::f16>::new(m, k, n))
+        }),
         svmm: Box::new(|k, n| {
             Box::new(vecmatmul::PackedVecMatMul::<generic::SVecMatMul8, f32>::new(k, n))
         }),
         smmm: Box::new(|m, k, n| {
             Box::new(mmm::MatMatMulImpl::<generic::SMmm4x4, f32>::new(m, k, n))
         }),
+        qmmm_i8_i8: Box::new(|m, k, n| {
+            Box::new(mmm::MatMatMulImpl::<generic::QMmm4x4<i8, i8>, i8, i8, i32, i32>::new(m, k, n))
+        }),
+        qmmm_u8_i8: Box::new(|m, k, n| {
+            Box::new(mmm::MatMatMulImpl::<generic::QMmm4x4<u8, i8>, u8, i8, i32, i32>::new(m, k, n))
+        }),
         ssigmoid: Box::new(|| Box::new(sigmoid::SigmoidImpl::<generic::SSigmoid4, f32>::new())),
         stanh: Box::new(|| Box::new(tanh::TanhImpl::<generic::STanh4, f32>::new())),
+        hsigmoid: Box::new(
+            || Box::new(sigmoid::SigmoidImpl::<generic::HSigmoid4, f16::f16>::new()),
+        ),
+        htanh: Box::new(|| Box::new(tanh::TanhImpl::<generic::HTanh4, f16::f16>::new())),
     }
 }
 
@@ -59,6 +85,14 @@ pub fn best() -> Ops {
                 Box::new(mmm::MatMatMulImpl::<x86_64_fma::mmm::SMatMatMul16x6, f32>::new(m, k, n))
             });
             log::info!("x86_64/fma activated");
+            if is_x86_feature_detected!("f16c") {
+                ops.hmmm = Box::new(|m, k, n| {
+                    Box::new(mmm::MatMatMulImpl::<x86_64_fma::mmm::HMatMatMul16x6, f16::f16>::new(
+                        m, k, n,
+                    ))
+                });
+                log::info!("x86_64/f16c activated");
+            }
         }
     }
     #[cfg(any(target_arch = "arm", target_arch = "armv7"))]
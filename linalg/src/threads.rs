@@ -0,0 +1,119 @@
+//! Opt-in intra-op parallelism.
+//!
+//! Everything runs on the calling thread unless a thread pool is set, either
+//! for the whole process with `set_default_pool`, or for the duration of a
+//! closure with `with_pool` (plans use the latter).
+//!
+//! Work is only split across independent outputs (matrix product tiles,
+//! convolution batch items and groups), so results are bit-identical to
+//! single-threaded runs.
+
+use std::cell::RefCell;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+use rayon::prelude::*;
+pub use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
+
+lazy_static::lazy_static! {
+    static ref DEFAULT_POOL: Mutex<Option<Arc<ThreadPool>>> = Mutex::new(None);
+}
+
+thread_local! {
+    static CURRENT_POOL: RefCell<Option<Option<Arc<ThreadPool>>>> = RefCell::new(None);
+}
+
+/// Builds a pool of `threads` workers.
+pub fn pool(threads: usize) -> Result<Arc<ThreadPool>, ThreadPoolBuildError> {
+    Ok(Arc::new(ThreadPoolBuilder::new().num_threads(threads).build()?))
+}
+
+/// Sets (or unsets) the pool used when no pool has been set with `with_pool`.
+pub fn set_default_pool(pool: Option<Arc<ThreadPool>>) {
+    *DEFAULT_POOL.lock().unwrap() = pool;
+}
+
+/// Runs `f` with `pool` as the current pool of the calling thread. `None`
+/// forces single-threaded execution.
+pub fn with_pool<R>(pool: Option<Arc<ThreadPool>>, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<Option<Arc<ThreadPool>>>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            CURRENT_POOL.with(|c| *c.borrow_mut() = previous);
+        }
+    }
+    let _restore = Restore(CURRENT_POOL.with(|c| c.replace(Some(pool))));
+    f()
+}
+
+/// The pool parallel loops will run on, if any.
+pub fn current_pool() -> Option<Arc<ThreadPool>> {
+    CURRENT_POOL
+        .with(|c| c.borrow().clone())
+        .unwrap_or_else(|| DEFAULT_POOL.lock().unwrap().clone())
+}
+
+/// Splits `0..len` in as many contiguous ranges as the current pool has
+/// threads, and calls `f` on each of them, in parallel. Without a pool, `f` is
+/// called once with `0..len` on the calling thread.
+///
+/// Parallel loops nested in `f` run on the same pool.
+pub fn for_each_range<F>(len: usize, f: F)
+where
+    F: Fn(Range<usize>) + Send + Sync,
+{
+    let pool = match current_pool() {
+        Some(pool) if len > 1 && pool.current_num_threads() > 1 => pool,
+        _ => return f(0..len),
+    };
+    let chunks = pool.current_num_threads().min(len);
+    pool.install(|| {
+        (0..chunks).into_par_iter().for_each(|chunk| {
+            with_pool(Some(pool.clone()), || f(chunk * len / chunks..(chunk + 1) * len / chunks))
+        })
+    })
+}
+
+/// Asserts a value (typically holding raw pointers) can be shared by the
+/// workers of a parallel loop. Each worker must only write to disjoint parts
+/// of the pointed data.
+#[derive(Copy, Clone, Debug)]
+pub struct Shared<T>(pub T);
+
+unsafe impl<T> Send for Shared<T> {}
+unsafe impl<T> Sync for Shared<T> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn ranges_cover_everything_once() {
+        let pool = pool(3).unwrap();
+        for len in 0..10 {
+            let seen: Vec<AtomicUsize> = (0..len).map(|_| AtomicUsize::new(0)).collect();
+            with_pool(Some(pool.clone()), || {
+                for_each_range(len, |range| {
+                    for i in range {
+                        seen[i].fetch_add(1, Ordering::SeqCst);
+                    }
+                })
+            });
+            assert!(seen.iter().all(|s| s.load(Ordering::SeqCst) == 1));
+        }
+    }
+
+    #[test]
+    fn no_pool_runs_inline() {
+        let calls = AtomicUsize::new(0);
+        with_pool(None, || {
+            for_each_range(12, |range| {
+                assert_eq!(range, 0..12);
+                calls.fetch_add(1, Ordering::SeqCst);
+            })
+        });
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -1,8 +1,11 @@
+use crate::f16::f16;
 use crate::frame::mmm::*;
 
 extern "C" {
     #[no_mangle]
     fn fma_smmm16x6(op: *const MatMatMulKerSpec<f32>) -> isize;
+    #[no_mangle]
+    fn fma_hmmm16x6(op: *const MatMatMulKerSpec<f16>) -> isize;
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -33,9 +36,52 @@ impl MatMatMulKer<f32> for SMatMatMul16x6 {
     }
 }
 
+/// Half precision operands and storage, widened with F16C and accumulated
+/// in f32 with FMA. Requires both features.
+#[derive(Copy, Clone, Debug)]
+pub struct HMatMatMul16x6;
+
+impl MatMatMulKer<f16> for HMatMatMul16x6 {
+    #[inline(always)]
+    fn name() -> &'static str {
+        "fma-f16c"
+    }
+    #[inline(always)]
+    fn mr() -> usize {
+        16
+    }
+    #[inline(always)]
+    fn nr() -> usize {
+        6
+    }
+    fn alignment_bytes_packed_a() -> usize {
+        32
+    }
+    fn alignment_bytes_packed_b() -> usize {
+        2
+    }
+    #[inline(never)]
+    fn kernel(spec: &MatMatMulKerSpec<f16>) -> isize {
+        unsafe { fma_hmmm16x6(spec) }
+    }
+}
+
 #[cfg(test)]
 #[cfg(all(any(target_arch = "x86", target_arch = "x86_64"),))]
 mod test {
     mmm_frame_tests!(is_x86_feature_detected!("fma"), crate::x86_64_fma::mmm::SMatMatMul16x6);
     mmm_kernel_tests!(is_x86_feature_detected!("fma"), crate::x86_64_fma::mmm::SMatMatMul16x6, f32);
 }
+
+#[cfg(test)]
+#[cfg(all(any(target_arch = "x86", target_arch = "x86_64"),))]
+mod test_f16 {
+    mmm_half_frame_tests!(
+        is_x86_feature_detected!("fma") && is_x86_feature_detected!("f16c"),
+        crate::x86_64_fma::mmm::HMatMatMul16x6
+    );
+    mmm_half_kernel_tests!(
+        is_x86_feature_detected!("fma") && is_x86_feature_detected!("f16c"),
+        crate::x86_64_fma::mmm::HMatMatMul16x6
+    );
+}
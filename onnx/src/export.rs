@@ -0,0 +1,789 @@
+//! Translation of TypedModel and NormalizedModel back to ONNX.
+//!
+//! Constants become initializers, and each core operator is written as its
+//! ONNX counterpart. When tract and ONNX disagree on layout (NHWC
+//! convolutions, transposed matrix products), Transpose nodes are inserted
+//! around the translated operator.
+//!
+//! Graphs are written against opset 9, the last one where Pad, Slice and Clip
+//! take their parameters as attributes, which is what the tract-onnx loader
+//! expects.
+
+use std::convert::TryInto;
+
+use protobuf::{Message, ProtobufEnum};
+
+use tract_core::internal::*;
+use tract_core::ops;
+use tract_core::ops::binary::BinMiniOp;
+use tract_core::ops::cnn::{KernelFormat, PaddingSpec, PoolSpec};
+use tract_core::ops::nn::{DataFormat, Reducer};
+
+use crate::pb::*;
+
+/// Operator set version exported graphs are written against.
+pub const OPSET_VERSION: i64 = 9;
+
+/// Build an ONNX ModelProto from a TypedModel or a NormalizedModel.
+pub fn model_to_proto(model: &dyn Model) -> TractResult<ModelProto> {
+    let mut exporter = Exporter { model, graph: GraphProto::new() };
+    exporter.graph.set_name("tract".to_string());
+    for &input in model.input_outlets() {
+        let info = exporter.value_info(input)?;
+        exporter.graph.mut_input().push(info);
+    }
+    for node in model.eval_order()? {
+        if model.input_outlets().iter().any(|o| o.node == node) {
+            continue;
+        }
+        exporter.export_node(node)?;
+    }
+    for &output in model.output_outlets() {
+        let info = exporter.value_info(output)?;
+        exporter.graph.mut_output().push(info);
+    }
+    let mut proto = ModelProto::new();
+    proto.set_ir_version(6);
+    proto.set_producer_name("tract".to_string());
+    proto.set_producer_version(env!("CARGO_PKG_VERSION").to_string());
+    let mut opset = OperatorSetIdProto::new();
+    opset.set_domain(String::new());
+    opset.set_version(OPSET_VERSION);
+    proto.mut_opset_import().push(opset);
+    proto.set_graph(exporter.graph);
+    Ok(proto)
+}
+
+/// Serialize a TypedModel or a NormalizedModel as an ONNX protobuf.
+pub fn write_model(model: &dyn Model, w: &mut dyn std::io::Write) -> TractResult<()> {
+    let proto = model_to_proto(model)?;
+    proto.write_to_writer(w).map_err(|e| format!("{:?}", e))?;
+    Ok(())
+}
+
+trait NodeProtoExt {
+    fn attr(&mut self, name: &str, kind: AttributeProto_AttributeType) -> &mut AttributeProto;
+
+    fn with_int(&mut self, name: &str, v: i64) -> &mut Self {
+        self.attr(name, AttributeProto_AttributeType::INT).set_i(v);
+        self
+    }
+
+    fn with_ints(&mut self, name: &str, v: Vec<i64>) -> &mut Self {
+        self.attr(name, AttributeProto_AttributeType::INTS).set_ints(v);
+        self
+    }
+
+    fn with_float(&mut self, name: &str, v: f32) -> &mut Self {
+        self.attr(name, AttributeProto_AttributeType::FLOAT).set_f(v);
+        self
+    }
+
+    fn with_string(&mut self, name: &str, v: &str) -> &mut Self {
+        self.attr(name, AttributeProto_AttributeType::STRING).set_s(v.as_bytes().to_vec());
+        self
+    }
+}
+
+impl NodeProtoExt for NodeProto {
+    fn attr(&mut self, name: &str, kind: AttributeProto_AttributeType) -> &mut AttributeProto {
+        let mut attr = AttributeProto::new();
+        attr.set_name(name.to_string());
+        attr.set_field_type(kind);
+        self.mut_attribute().push(attr);
+        self.mut_attribute().last_mut().unwrap()
+    }
+}
+
+fn ints(v: &[usize]) -> Vec<i64> {
+    v.iter().map(|&x| x as i64).collect()
+}
+
+fn int_tensor(v: Vec<i64>) -> Tensor {
+    let len = v.len();
+    ndarray::Array1::from_vec(v).into_shape(len).unwrap().into()
+}
+
+fn permute<T: Datum>(t: &Tensor, axes: &[usize]) -> TractResult<Tensor> {
+    Ok(t.to_array_view::<T>()?.permuted_axes(axes).to_owned().into())
+}
+
+fn bin_op_type(mini_op: &dyn BinMiniOp) -> Option<&'static str> {
+    let op_type = match mini_op.name() {
+        "Add" => "Add",
+        "Sub" => "Sub",
+        "Mul" => "Mul",
+        "Div" => "Div",
+        "Pow" => "Pow",
+        "Min" => "Min",
+        "Max" => "Max",
+        "And" => "And",
+        "Or" => "Or",
+        "Xor" => "Xor",
+        "Equals" => "Equal",
+        "Lesser" => "Less",
+        "Greater" => "Greater",
+        _ => return None,
+    };
+    Some(op_type)
+}
+
+fn element_wise_op_type(op: &dyn Op) -> Option<&'static str> {
+    use tract_core::ops::{logic, math, nn};
+    macro_rules! map {
+        ($($tract: ty => $onnx: expr),*) => {
+            $(if op.is::<$tract>() {
+                return Some($onnx)
+            })*
+        }
+    }
+    map!(
+        math::Abs => "Abs",
+        math::Exp => "Exp",
+        math::Ln => "Log",
+        math::Sqrt => "Sqrt",
+        math::Recip => "Reciprocal",
+        math::Ceil => "Ceil",
+        math::Floor => "Floor",
+        math::Cos => "Cos",
+        math::Sin => "Sin",
+        math::Tan => "Tan",
+        math::Acos => "Acos",
+        math::Asin => "Asin",
+        math::Atan => "Atan",
+        math::Cosh => "Cosh",
+        math::Sinh => "Sinh",
+        math::Tanh => "Tanh",
+        math::Acosh => "Acosh",
+        math::Asinh => "Asinh",
+        math::Atanh => "Atanh",
+        math::Neg => "Neg",
+        math::Sign => "Sign",
+        math::IsNan => "IsNaN",
+        logic::Not => "Not",
+        nn::Sigmoid => "Sigmoid",
+        nn::Softplus => "Softplus",
+        nn::Softsign => "Softsign"
+    );
+    None
+}
+
+fn reducer_op_type(reducer: Reducer) -> &'static str {
+    match reducer {
+        Reducer::L1 => "ReduceL1",
+        Reducer::L2 => "ReduceL2",
+        Reducer::LogSum => "ReduceLogSum",
+        Reducer::LogSumExp => "ReduceLogSumExp",
+        Reducer::Max => "ReduceMax",
+        Reducer::Mean => "ReduceMean",
+        Reducer::Min => "ReduceMin",
+        Reducer::Prod => "ReduceProd",
+        Reducer::Sum => "ReduceSum",
+        Reducer::SumSquare => "ReduceSumSquare",
+    }
+}
+
+struct Exporter<'a> {
+    model: &'a dyn Model,
+    graph: GraphProto,
+}
+
+impl<'a> Exporter<'a> {
+    fn outlet_name(&self, outlet: OutletId) -> String {
+        let name = self.model.node_name(outlet.node);
+        if outlet.slot == 0 {
+            name.to_string()
+        } else {
+            format!("{}:{}", name, outlet.slot)
+        }
+    }
+
+    fn value_info(&self, outlet: OutletId) -> TractResult<ValueInfoProto> {
+        let fact = self.model.outlet_tensorfact(outlet);
+        let mut info = ValueInfoProto::new();
+        info.set_name(self.outlet_name(outlet));
+        info.mut_field_type().set_tensor_type((&fact).try_into()?);
+        Ok(info)
+    }
+
+    fn initializer(&mut self, name: String, tensor: &Tensor) -> TractResult<String> {
+        let mut proto: TensorProto = tensor.try_into()?;
+        proto.set_name(name.clone());
+        self.graph.mut_initializer().push(proto);
+        Ok(name)
+    }
+
+    fn node(
+        &mut self,
+        op_type: &str,
+        name: String,
+        inputs: Vec<String>,
+        outputs: Vec<String>,
+    ) -> &mut NodeProto {
+        let mut node = NodeProto::new();
+        node.set_op_type(op_type.to_string());
+        node.set_name(name);
+        node.set_input(inputs.into());
+        node.set_output(outputs.into());
+        self.graph.mut_node().push(node);
+        self.graph.mut_node().last_mut().unwrap()
+    }
+
+    fn transpose(&mut self, name: String, input: String, perm: Vec<i64>) -> String {
+        self.node("Transpose", name.clone(), vec![input], vec![name.clone()])
+            .with_ints("perm", perm);
+        name
+    }
+
+    /// Swap the last two axes.
+    fn transpose_matrix(
+        &mut self,
+        name: String,
+        input: String,
+        rank: usize,
+    ) -> TractResult<String> {
+        if rank < 2 {
+            bail!("Can not transpose {} of rank {}", input, rank)
+        }
+        let mut perm: Vec<i64> = (0..rank as i64).collect();
+        perm.swap(rank - 2, rank - 1);
+        Ok(self.transpose(name, input, perm))
+    }
+
+    fn export_node(&mut self, id: usize) -> TractResult<()> {
+        let op = self.model.node_op(id);
+        let name = self.model.node_name(id).to_string();
+        let inputs: Vec<String> =
+            self.model.node_inputs(id).iter().map(|&i| self.outlet_name(i)).collect();
+        let outputs: Vec<String> = (0..self.model.node_output_count(id))
+            .map(|slot| self.outlet_name(OutletId::new(id, slot)))
+            .collect();
+        let input_facts: Vec<TensorFact> =
+            self.model.node_inputs(id).iter().map(|&i| self.model.outlet_tensorfact(i)).collect();
+        let input_rank = |ix: usize| -> TractResult<usize> {
+            input_facts[ix]
+                .shape
+                .concretize()
+                .map(|s| s.len())
+                .ok_or_else(|| format!("Unknown rank for input {} of {}", ix, name).into())
+        };
+
+        if let Some(op) = op.downcast_ref::<ops::konst::Const>() {
+            self.initializer(outputs[0].clone(), &op.value)?;
+        } else if op.is::<ops::identity::Identity>() {
+            self.node("Identity", name, inputs, outputs);
+        } else if let Some(op) = op.downcast_ref::<ops::binary::TypedBinOp>() {
+            self.binary(&*op.0, name, inputs, outputs)?;
+        } else if let Some(op) = op.downcast_ref::<ops::binary::MergeOp>() {
+            self.binary(&*op.0, name, inputs, outputs)?;
+        } else if let Some(op) = op.downcast_ref::<ops::binary::MergeOpUnicast>() {
+            self.binary(&*op.0, name, inputs, outputs)?;
+        } else if let Some(op) = op.downcast_ref::<ops::binary::UnaryOp>() {
+            let a = self.initializer(format!("{}.a", name), &op.a)?;
+            self.binary(&*op.mini_op, name, vec![a, inputs[0].clone()], outputs)?;
+        } else if let Some(op_type) = element_wise_op_type(op) {
+            self.node(op_type, name, inputs, outputs);
+        } else if op.is::<ops::math::Rsqrt>() {
+            let sqrt = format!("{}.sqrt", name);
+            self.node("Sqrt", sqrt.clone(), inputs, vec![sqrt.clone()]);
+            self.node("Reciprocal", name, vec![sqrt], outputs);
+        } else if let Some(op) = op.downcast_ref::<ops::math::ScalarMax>() {
+            if op.max == 0.0 {
+                self.node("Relu", name, inputs, outputs);
+            } else {
+                self.node("Clip", name, inputs, outputs).with_float("min", op.max);
+            }
+        } else if let Some(op) = op.downcast_ref::<ops::math::ScalarMin>() {
+            self.node("Clip", name, inputs, outputs).with_float("max", op.min);
+        } else if let Some(op) = op.downcast_ref::<ops::math::ScalarMinMax>() {
+            // ScalarMinMax applies max first, then min
+            self.node("Clip", name, inputs, outputs)
+                .with_float("min", op.max)
+                .with_float("max", op.min);
+        } else if let Some(op) = op.downcast_ref::<ops::nn::Elu>() {
+            self.node("Elu", name, inputs, outputs).with_float("alpha", op.alpha);
+        } else if let Some(op) = op.downcast_ref::<ops::nn::LeakyRelu>() {
+            self.node("LeakyRelu", name, inputs, outputs).with_float("alpha", op.alpha);
+        } else if let Some(op) = op.downcast_ref::<ops::nn::ThresholdedRelu>() {
+            self.node("ThresholdedRelu", name, inputs, outputs).with_float("alpha", op.alpha);
+        } else if let Some(op) = op.downcast_ref::<ops::nn::Hardsigmoid>() {
+            self.node("HardSigmoid", name, inputs, outputs)
+                .with_float("alpha", op.alpha)
+                .with_float("beta", op.beta);
+        } else if let Some(op) = op.downcast_ref::<ops::nn::Selu>() {
+            self.node("Selu", name, inputs, outputs)
+                .with_float("alpha", op.alpha)
+                .with_float("gamma", op.gamma);
+        } else if let Some(op) = op.downcast_ref::<ops::nn::ParametricSoftplus>() {
+            self.node("ParametricSoftplus", name, inputs, outputs)
+                .with_float("alpha", op.alpha)
+                .with_float("beta", op.beta);
+        } else if let Some(op) = op.downcast_ref::<ops::nn::ScaledTanh>() {
+            self.node("ScaledTanh", name, inputs, outputs)
+                .with_float("alpha", op.alpha)
+                .with_float("beta", op.beta);
+        } else if let Some(op) = op.downcast_ref::<ops::math::MatMul>() {
+            let (a_rank, b_rank) = (input_rank(0)?, input_rank(1)?);
+            self.mat_mul(
+                name,
+                (inputs[0].clone(), a_rank, op.a_trans),
+                (inputs[1].clone(), b_rank, op.b_trans),
+                op.c_trans,
+                outputs,
+            )?;
+        } else if let Some(op) = op.downcast_ref::<ops::math::mat_mul::MatMulUnary>() {
+            let a = self.initializer(format!("{}.a", name), &op.a)?;
+            let b_rank = input_rank(0)?;
+            self.mat_mul(
+                name,
+                (a, op.a.shape().len(), op.a_trans),
+                (inputs[0].clone(), b_rank, op.b_trans),
+                op.c_trans,
+                outputs,
+            )?;
+        } else if let Some(op) = op.downcast_ref::<ops::cnn::ConvUnary>() {
+            self.conv(op, name, inputs, outputs)?;
+        } else if let Some(op) = op.downcast_ref::<ops::cnn::MaxPool>() {
+            let rank = input_rank(0)?;
+            self.pool("MaxPool", &op.pool_spec, rank, name, inputs, outputs, |_| ())?;
+        } else if let Some(op) = op.downcast_ref::<ops::cnn::AvgPool>() {
+            let rank = input_rank(0)?;
+            let count_include_pad = op.count_include_pad as i64;
+            self.pool("AveragePool", &op.pool_spec, rank, name, inputs, outputs, |node| {
+                node.with_int("count_include_pad", count_include_pad);
+            })?;
+        } else if op.is::<ops::nn::GlobalAvgPool>() {
+            self.node("GlobalAveragePool", name, inputs, outputs);
+        } else if op.is::<ops::nn::GlobalMaxPool>() {
+            self.node("GlobalMaxPool", name, inputs, outputs);
+        } else if let Some(op) = op.downcast_ref::<ops::nn::GlobalLpPool>() {
+            self.node("GlobalLpPool", name, inputs, outputs).with_int("p", op.p as i64);
+        } else if let Some(op) = op.downcast_ref::<ops::nn::TypedReduce>() {
+            self.node(reducer_op_type(op.reducer), name, inputs, outputs)
+                .with_ints("axes", ints(&op.axes))
+                .with_int("keepdims", 1);
+        } else if let Some(op) = op.downcast_ref::<ops::nn::LayerSoftmax>() {
+            self.node("Softmax", name, inputs, outputs).with_int("axis", op.axis as i64);
+        } else if let Some(op) = op.downcast_ref::<ops::nn::LayerLogSoftmax>() {
+            self.node("LogSoftmax", name, inputs, outputs).with_int("axis", op.axis as i64);
+        } else if let Some(op) = op.downcast_ref::<ops::nn::LayerHardmax>() {
+            self.node("Hardmax", name, inputs, outputs).with_int("axis", op.axis as i64);
+        } else if let Some(op) = op.downcast_ref::<ops::nn::ArgMaxMin>() {
+            self.node(if op.max { "ArgMax" } else { "ArgMin" }, name, inputs, outputs)
+                .with_int("axis", op.axis as i64)
+                .with_int("keepdims", op.keepdims as i64);
+        } else if let Some(op) = op.downcast_ref::<ops::nn::Lrn>() {
+            self.node("LRN", name, inputs, outputs)
+                .with_float("alpha", op.alpha)
+                .with_float("beta", op.beta)
+                .with_float("bias", op.bias)
+                .with_int("size", op.size as i64);
+        } else if let Some(op) = op.downcast_ref::<ops::cast::Cast>() {
+            let to: TensorProto_DataType = op.to.try_into()?;
+            self.node("Cast", name, inputs, outputs).with_int("to", to.value() as i64);
+        } else if let Some(op) = op.downcast_ref::<ops::array::AddDims>() {
+            self.node("Unsqueeze", name, inputs, outputs).with_ints("axes", ints(&op.axes));
+        } else if let Some(op) = op.downcast_ref::<ops::array::RmDims>() {
+            self.node("Squeeze", name, inputs, outputs).with_ints("axes", ints(&op.axes));
+        } else if let Some(op) = op.downcast_ref::<ops::array::PermuteAxes>() {
+            let node = self.node("Transpose", name, inputs, outputs);
+            if let Some(axes) = &op.axes {
+                node.with_ints("perm", ints(axes));
+            }
+        } else if let Some(op) = op.downcast_ref::<ops::array::IntoShape>() {
+            let shape =
+                self.initializer(format!("{}.shape", name), &int_tensor(ints(&op.shape)))?;
+            self.node("Reshape", name, vec![inputs[0].clone(), shape], outputs);
+        } else if let Some(op) = op.downcast_ref::<ops::array::Flatten>() {
+            self.node("Flatten", name, inputs, outputs).with_int("axis", op.axis as i64);
+        } else if let Some(op) = op.downcast_ref::<ops::array::Gather>() {
+            self.node("Gather", name, inputs, outputs).with_int("axis", op.axis);
+        } else if let Some(op) = op.downcast_ref::<ops::array::NormConcat>() {
+            let mut vars = inputs.into_iter();
+            let mut concat_inputs = vec![];
+            for (ix, slice) in op.slices.iter().enumerate() {
+                match slice {
+                    ops::array::NormConcatSlice::Const(t) => {
+                        concat_inputs.push(self.initializer(format!("{}.{}", name, ix), t)?)
+                    }
+                    ops::array::NormConcatSlice::Var => concat_inputs.push(
+                        vars.next().ok_or_else(|| format!("Missing input for concat {}", name))?,
+                    ),
+                }
+            }
+            self.node("Concat", name, concat_inputs, outputs).with_int("axis", op.axis as i64);
+        } else if let Some(op) = op.downcast_ref::<ops::array::Slice<usize>>() {
+            self.slice(name, inputs, outputs, op.axis, op.start as i64, op.end as i64);
+        } else if let Some(op) = op.downcast_ref::<ops::array::Slice<TDim>>() {
+            let start = op.start.to_integer()? as i64;
+            let end = op.end.to_integer()? as i64;
+            self.slice(name, inputs, outputs, op.axis, start, end);
+        } else if let Some(op) = op.downcast_ref::<ops::array::Split>() {
+            let node = self.node("Split", name, inputs, outputs);
+            node.with_int("axis", op.axis as i64);
+            if let Some(split) = &op.split {
+                node.with_ints("split", ints(split));
+            }
+        } else if let Some(op) = op.downcast_ref::<ops::array::Pad>() {
+            let mut pads: Vec<i64> = op.pads.iter().map(|p| p.0 as i64).collect();
+            pads.extend(op.pads.iter().map(|p| p.1 as i64));
+            let node = self.node("Pad", name, inputs, outputs);
+            node.with_ints("pads", pads);
+            match &op.mode {
+                ops::array::PadMode::Constant(value) => {
+                    let value = *value.cast_to::<f32>()?.to_scalar::<f32>()?;
+                    node.with_string("mode", "constant").with_float("value", value);
+                }
+                ops::array::PadMode::Reflect => {
+                    node.with_string("mode", "reflect");
+                }
+                ops::array::PadMode::Edge => {
+                    node.with_string("mode", "edge");
+                }
+            }
+        } else if let Some(op) = op.downcast_ref::<ops::array::TypedTile>() {
+            let repeats = int_tensor(ints(&op.multipliers));
+            let repeats = self.initializer(format!("{}.repeats", name), &repeats)?;
+            self.node("Tile", name, vec![inputs[0].clone(), repeats], outputs);
+        } else if let Some(op) = op.downcast_ref::<ops::array::TypedMultiBroadcastTo>() {
+            let shape =
+                op.shape.iter().map(|d| Ok(d.to_integer()? as i64)).collect::<TractResult<_>>()?;
+            let shape = self.initializer(format!("{}.shape", name), &int_tensor(shape))?;
+            self.node("Expand", name, vec![inputs[0].clone(), shape], outputs);
+        } else {
+            bail!("No ONNX form for {} ({})", op.name(), name)
+        }
+        Ok(())
+    }
+
+    fn binary(
+        &mut self,
+        mini_op: &dyn BinMiniOp,
+        name: String,
+        inputs: Vec<String>,
+        outputs: Vec<String>,
+    ) -> TractResult<()> {
+        if let Some(op_type) = bin_op_type(mini_op) {
+            self.node(op_type, name, inputs, outputs);
+            return Ok(());
+        }
+        // ONNX has no LessOrEqual and GreaterOrEqual before opset 12
+        let op_type = match mini_op.name() {
+            "LesserEqual" => "Greater",
+            "GreaterEqual" => "Less",
+            _ => bail!("No ONNX form for {} ({})", mini_op.name(), name),
+        };
+        let negated = format!("{}.negated", name);
+        self.node(op_type, negated.clone(), inputs, vec![negated.clone()]);
+        self.node("Not", name, vec![negated], outputs);
+        Ok(())
+    }
+
+    fn mat_mul(
+        &mut self,
+        name: String,
+        a: (String, usize, bool),
+        b: (String, usize, bool),
+        c_trans: bool,
+        outputs: Vec<String>,
+    ) -> TractResult<()> {
+        // C' = B' x A'
+        let ((a, a_rank, a_trans), (b, b_rank, b_trans)) =
+            if c_trans { ((b.0, b.1, !b.2), (a.0, a.1, !a.2)) } else { (a, b) };
+        let a =
+            if a_trans { self.transpose_matrix(format!("{}.a_t", name), a, a_rank)? } else { a };
+        let b =
+            if b_trans { self.transpose_matrix(format!("{}.b_t", name), b, b_rank)? } else { b };
+        self.node("MatMul", name, vec![a, b], outputs);
+        Ok(())
+    }
+
+    fn slice(
+        &mut self,
+        name: String,
+        inputs: Vec<String>,
+        outputs: Vec<String>,
+        axis: usize,
+        start: i64,
+        end: i64,
+    ) {
+        self.node("Slice", name, inputs, outputs)
+            .with_ints("starts", vec![start])
+            .with_ints("ends", vec![end])
+            .with_ints("axes", vec![axis as i64]);
+    }
+
+    /// Wrap a NCHW operator in transpositions if the data is NHWC.
+    fn with_data_format<F>(
+        &mut self,
+        data_format: DataFormat,
+        rank: usize,
+        name: String,
+        input: String,
+        outputs: Vec<String>,
+        f: F,
+    ) -> TractResult<()>
+    where
+        F: FnOnce(&mut Self, String, String, Vec<String>) -> TractResult<()>,
+    {
+        if data_format == DataFormat::NCHW {
+            return f(self, name, input, outputs);
+        }
+        let mut to_nchw = vec![0, rank as i64 - 1];
+        to_nchw.extend(1..rank as i64 - 1);
+        let mut to_nhwc = vec![0];
+        to_nhwc.extend(2..rank as i64);
+        to_nhwc.push(1);
+        let input = self.transpose(format!("{}.input.nchw", name), input, to_nchw);
+        let nchw_outputs = outputs.iter().map(|o| format!("{}.nchw", o)).collect::<Vec<_>>();
+        f(self, format!("{}.nchw", name), input, nchw_outputs.clone())?;
+        for (nchw, output) in nchw_outputs.into_iter().zip(outputs.into_iter()) {
+            self.node("Transpose", output.clone(), vec![nchw], vec![output])
+                .with_ints("perm", to_nhwc.clone());
+        }
+        Ok(())
+    }
+
+    fn conv(
+        &mut self,
+        op: &ops::cnn::ConvUnary,
+        name: String,
+        inputs: Vec<String>,
+        outputs: Vec<String>,
+    ) -> TractResult<()> {
+        let rank = op.full_input_shape.len();
+        let kernel = match op.kernel_fmt {
+            KernelFormat::OIHW => op.kernel.clone(),
+            KernelFormat::HWIO => {
+                let k_rank = op.kernel.shape().len();
+                let mut axes = vec![k_rank - 1, k_rank - 2];
+                axes.extend(0..k_rank - 2);
+                dispatch_datum!(self::permute(op.kernel.datum_type())(&op.kernel, &axes))?
+            }
+        };
+        let kernel_shape = ints(&kernel.shape()[2..]);
+        let kernel = self.initializer(format!("{}.kernel", name), &kernel)?;
+        let padding = op.padding.clone();
+        let strides = ints(&op.strides);
+        let dilations = ints(&op.dilations);
+        let group = op.group as i64;
+        self.with_data_format(
+            op.data_format,
+            rank,
+            name,
+            inputs[0].clone(),
+            outputs,
+            move |exporter, name, input, outputs| {
+                let node = exporter.node("Conv", name, vec![input, kernel], outputs);
+                node.with_ints("kernel_shape", kernel_shape)
+                    .with_ints("strides", strides)
+                    .with_ints("dilations", dilations)
+                    .with_int("group", group);
+                padding_attributes(node, &padding);
+                Ok(())
+            },
+        )
+    }
+
+    fn pool<F>(
+        &mut self,
+        op_type: &'static str,
+        spec: &PoolSpec,
+        rank: usize,
+        name: String,
+        inputs: Vec<String>,
+        outputs: Vec<String>,
+        extra: F,
+    ) -> TractResult<()>
+    where
+        F: FnOnce(&mut NodeProto),
+    {
+        let spec = spec.clone();
+        self.with_data_format(
+            spec.data_format,
+            rank,
+            name,
+            inputs[0].clone(),
+            outputs,
+            move |exporter, name, input, outputs| {
+                let node = exporter.node(op_type, name, vec![input], outputs);
+                node.with_ints("kernel_shape", ints(&spec.kernel_shape));
+                if let Some(strides) = &spec.strides {
+                    node.with_ints("strides", ints(strides));
+                }
+                padding_attributes(node, &spec.padding);
+                extra(node);
+                Ok(())
+            },
+        )
+    }
+}
+
+fn padding_attributes(node: &mut NodeProto, padding: &PaddingSpec) {
+    match padding {
+        PaddingSpec::Explicit(before, after) => {
+            let mut pads = ints(before);
+            pads.extend(ints(after));
+            node.with_ints("pads", pads);
+        }
+        PaddingSpec::Valid => {
+            node.with_string("auto_pad", "VALID");
+        }
+        PaddingSpec::SameUpper => {
+            node.with_string("auto_pad", "SAME_UPPER");
+        }
+        PaddingSpec::SameLower => {
+            node.with_string("auto_pad", "SAME_LOWER");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tract_core::framework::Framework;
+    use ndarray::{ArrayD, Dimension};
+    use tract_core::ops::array::{Pad, PadMode};
+    use tract_core::ops::cnn::{AvgPool, Conv, MaxPool};
+
+    fn data(shape: &[usize]) -> Tensor {
+        ArrayD::from_shape_fn(shape, |ix| (ix.slice().iter().sum::<usize>() as f32).sin()).into()
+    }
+
+    fn source(model: &mut InferenceModel, name: &str, shape: &[usize]) -> OutletId {
+        let fact = TensorFact::dt_shape(f32::datum_type(), shape);
+        OutletId::new(model.add_source(name, fact).unwrap(), 0)
+    }
+
+    /// Declutter `model`, export it, reload it, and check both versions
+    /// agree on `inputs`. `check` inspects the exported model.
+    fn round_trip(model: InferenceModel, inputs: TVec<Tensor>, check: impl Fn(&TypedModel)) {
+        let mut model = model;
+        model.auto_outputs().unwrap();
+        let model = model.into_typed().unwrap().declutter().unwrap();
+        check(&model);
+        let expected = SimplePlan::new(&model).unwrap().run(inputs.clone()).unwrap();
+        let mut buffer = vec![];
+        write_model(&model, &mut buffer).unwrap();
+        let reloaded = crate::onnx().model_for_read(&mut &*buffer).unwrap();
+        let reloaded = reloaded.into_optimized().unwrap();
+        let found = SimplePlan::new(&reloaded).unwrap().run(inputs).unwrap();
+        assert_eq!(found.len(), expected.len());
+        for (found, expected) in found.iter().zip(expected.iter()) {
+            found.close_enough(expected, true).unwrap();
+        }
+    }
+
+    fn has<O: Op>(model: &TypedModel) -> bool {
+        model.nodes().iter().any(|n| n.op_is::<O>())
+    }
+
+    fn conv(conv: Conv, input_shape: &[usize], kernel_shape: &[usize]) {
+        let mut model = InferenceModel::default();
+        let x = source(&mut model, "x", input_shape);
+        let k = OutletId::new(model.add_const("kernel", data(kernel_shape)).unwrap(), 0);
+        model.wire_node("conv", conv, &[x, k]).unwrap();
+        round_trip(model, tvec!(data(input_shape)), |m| assert!(has::<ops::cnn::ConvUnary>(m)));
+    }
+
+    #[test]
+    fn conv_nchw() {
+        let op = Conv::new(
+            DataFormat::NCHW,
+            KernelFormat::OIHW,
+            None,
+            None,
+            PaddingSpec::Explicit(tvec!(1, 0), tvec!(0, 1)),
+            Some(tvec!(2, 1)),
+            1,
+        );
+        conv(op, &[1, 2, 7, 5], &[3, 2, 3, 3]);
+    }
+
+    #[test]
+    fn conv_nchw_grouped() {
+        let op = Conv::new(
+            DataFormat::NCHW,
+            KernelFormat::OIHW,
+            None,
+            None,
+            PaddingSpec::Valid,
+            None,
+            2,
+        );
+        conv(op, &[1, 4, 5, 5], &[6, 2, 2, 2]);
+    }
+
+    #[test]
+    fn conv_nhwc() {
+        let op = Conv::new(
+            DataFormat::NHWC,
+            KernelFormat::HWIO,
+            Some(tvec!(1, 2)),
+            None,
+            PaddingSpec::SameUpper,
+            None,
+            1,
+        );
+        conv(op, &[1, 6, 5, 4], &[3, 2, 4, 6]);
+    }
+
+    fn mat_mul(a_trans: bool, b_trans: bool, c_trans: bool) {
+        let mut model = InferenceModel::default();
+        let a_shape = if a_trans { [2, 4, 3] } else { [2, 3, 4] };
+        let b_shape = if b_trans { [2, 5, 4] } else { [2, 4, 5] };
+        let a = source(&mut model, "a", &a_shape);
+        let b = source(&mut model, "b", &b_shape);
+        let op = ops::math::MatMul::new(a_trans, b_trans, c_trans);
+        model.wire_node("mat_mul", op, &[a, b]).unwrap();
+        round_trip(model, tvec!(data(&a_shape), data(&b_shape)), |m| {
+            assert!(has::<ops::math::MatMul>(m))
+        });
+    }
+
+    #[test]
+    fn mat_mul_transposes() {
+        for &a_trans in &[false, true] {
+            for &b_trans in &[false, true] {
+                for &c_trans in &[false, true] {
+                    mat_mul(a_trans, b_trans, c_trans);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pad() {
+        let modes = vec![PadMode::Constant(rctensor0(1.5f32)), PadMode::Reflect, PadMode::Edge];
+        for mode in modes {
+            let mut model = InferenceModel::default();
+            let x = source(&mut model, "x", &[3, 4]);
+            model.wire_node("pad", Pad::new(vec![(1, 2), (2, 0)], mode), &[x]).unwrap();
+            round_trip(model, tvec!(data(&[3, 4])), |m| assert!(has::<Pad>(m)));
+        }
+    }
+
+    #[test]
+    fn pools() {
+        for &data_format in &[DataFormat::NCHW, DataFormat::NHWC] {
+            let shape = data_format.from_n_c_hw(1, 2, [6, 5]).shape;
+            let spec = PoolSpec::new(
+                data_format,
+                tvec!(3, 2),
+                PaddingSpec::Explicit(tvec!(1, 1), tvec!(1, 0)),
+                Some(tvec!(2, 1)),
+            );
+            let mut model = InferenceModel::default();
+            let x = source(&mut model, "x", &shape);
+            model.wire_node("max", MaxPool::new(spec.clone(), None), &[x]).unwrap();
+            round_trip(model, tvec!(data(&shape)), |m| assert!(has::<MaxPool>(m)));
+            for &count_include_pad in &[false, true] {
+                let mut model = InferenceModel::default();
+                let x = source(&mut model, "x", &shape);
+                let op = AvgPool::new(spec.clone(), count_include_pad);
+                model.wire_node("avg", op, &[x]).unwrap();
+                round_trip(model, tvec!(data(&shape)), |m| assert!(has::<AvgPool>(m)));
+            }
+        }
+    }
+}
@@ -15,6 +15,7 @@ extern crate protobuf;
 extern crate tract_core;
 extern crate tract_linalg;
 
+pub mod export;
 pub mod model;
 pub mod ops;
 pub mod pb;
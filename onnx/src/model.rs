@@ -1,16 +1,19 @@
 use std::convert::TryInto;
 
 use std::collections::HashMap;
+use std::path::Path;
 
 use tract_core::internal::*;
 
 use crate::pb;
+use crate::tensor::load_tensor;
 
 #[derive(Clone)]
 pub struct ParsingContext<'a> {
     pub framework: &'a Onnx,
     pub model: &'a pb::ModelProto,
     pub parent_graphs: Vec<&'a pb::GraphProto>,
+    pub model_dir: Option<&'a Path>,
 }
 
 #[derive(Clone, Debug)]
@@ -30,7 +33,7 @@ impl<'a> ParsingContext<'a> {
         let mut initializers: HashMap<&str, Tensor> = graph
             .get_initializer()
             .iter()
-            .map(|init| Ok((init.get_name(), init.try_into()?)))
+            .map(|init| Ok((init.get_name(), load_tensor(init, self.model_dir)?)))
             .collect::<TractResult<_>>()?;
         for (k, v) in initializers.iter() {
             trace!("Initializer: {} {:?}", k, v);
@@ -153,10 +156,28 @@ pub struct Onnx {
 
 impl Onnx {
     pub fn parse(&self, proto: &pb::ModelProto) -> TractResult<ParseResult> {
+        self.parse_with_model_dir(proto, None)
+    }
+
+    /// Parse a proto model, looking for external tensor data in `model_dir`.
+    pub fn parse_with_model_dir(
+        &self,
+        proto: &pb::ModelProto,
+        model_dir: Option<&Path>,
+    ) -> TractResult<ParseResult> {
         let graph = proto.get_graph();
-        let ctx = ParsingContext { framework: self, model: proto, parent_graphs: vec![] };
+        let ctx =
+            ParsingContext { framework: self, model: proto, parent_graphs: vec![], model_dir };
         ctx.parse_graph(&graph)
     }
+
+    fn model_for_parse_result(&self, result: ParseResult) -> TractResult<InferenceModel> {
+        let ParseResult { model, unresolved_inputs, .. } = result;
+        if unresolved_inputs.len() > 0 {
+            bail!("Could not resolve inputs at top-level: {:?}", unresolved_inputs)
+        }
+        Ok(model)
+    }
 }
 
 impl Framework<pb::ModelProto> for Onnx {
@@ -165,10 +186,11 @@ impl Framework<pb::ModelProto> for Onnx {
     }
 
     fn model_for_proto_model(&self, proto: &pb::ModelProto) -> TractResult<InferenceModel> {
-        let ParseResult { model, unresolved_inputs, .. } = self.parse(proto)?;
-        if unresolved_inputs.len() > 0 {
-            bail!("Could not resolve inputs at top-level: {:?}", unresolved_inputs)
-        }
-        Ok(model)
+        self.model_for_parse_result(self.parse(proto)?)
+    }
+
+    fn model_for_path(&self, p: impl AsRef<Path>) -> TractResult<InferenceModel> {
+        let proto = self.proto_model_for_path(p.as_ref())?;
+        self.model_for_parse_result(self.parse_with_model_dir(&proto, p.as_ref().parent())?)
     }
 }
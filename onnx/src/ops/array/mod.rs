@@ -1,4 +1,5 @@
 mod compress;
+mod one_hot;
 mod slice;
 
 use std::convert::TryInto;
@@ -16,19 +17,27 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("Concat", concat);
     reg.insert("ConstantLike", constant_like);
     reg.insert("ConstantOfShape", constant_of_shape);
+    reg.insert("DepthToSpace", depth_to_space);
     reg.insert("Expand", |_, _| {
         Ok((Box::new(tractops::array::MultiBroadcastTo::default()), vec![]))
     });
     reg.insert("EyeLike", eye_like);
     reg.insert("Flatten", flatten);
     reg.insert("Gather", gather);
+    reg.insert("GatherElements", gather_elements);
+    reg.insert("GatherND", gather_nd);
+    reg.insert("OneHot", one_hot::one_hot);
     reg.insert("Pad", pad);
     reg.insert("Reshape", |_, _| Ok((Box::new(tractops::array::Reshape::default()), vec![])));
     reg.insert("Shape", |_, _| Ok((Box::new(tractops::array::Shape::new(DatumType::I64)), vec![])));
     reg.insert("Size", |_, _| Ok((Box::new(tractops::array::Size::new(DatumType::I64)), vec![])));
+    reg.insert("Scatter", scatter_elements);
+    reg.insert("ScatterElements", scatter_elements);
+    reg.insert("ScatterND", scatter_nd);
     reg.insert("Transpose", transpose);
     reg.insert("Tile", |_, _| Ok((Box::new(tractops::array::Tile::default()), vec![])));
     reg.insert("Slice", slice);
+    reg.insert("SpaceToDepth", space_to_depth);
     reg.insert("Split", split);
     reg.insert("Squeeze", squeeze);
     reg.insert("Unsqueeze", unsqueeze);
@@ -74,16 +83,31 @@ pub fn constant_like(
 }
 
 pub fn constant_of_shape(
-    _ctx: &ParsingContext,
+    ctx: &ParsingContext,
     node: &NodeProto,
 ) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
-    let value = match node.get_attr_opt::<Tensor>("value")? {
+    let value = match node.get_attr_opt_tensor("value", ctx.model_dir)? {
         Some(val) => val.into_arc_tensor(),
         None => make_const::<f32>(&vec![1], 0.0 as f32)?,
     };
     Ok((Box::new(tractops::array::ConstantOfShape::new(value)), vec![]))
 }
 
+pub fn depth_to_space(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let block_size = node.get_attr("blocksize")?;
+    node.expect_attr("blocksize", block_size > 0, "a positive block size")?;
+    let mode = match node.get_attr_opt("mode")?.unwrap_or("DCR") {
+        "DCR" => tractops::nn::DepthToSpaceMode::DCR,
+        "CRD" => tractops::nn::DepthToSpaceMode::CRD,
+        _ => node.bail_attr("mode", "expected DCR or CRD")?,
+    };
+    let op = tractops::nn::DepthToSpace::new(block_size, mode, tractops::nn::DataFormat::NCHW);
+    Ok((Box::new(op), vec![]))
+}
+
 pub fn eye_like(
     _ctx: &ParsingContext,
     node: &NodeProto,
@@ -119,6 +143,22 @@ pub fn gather(
     Ok((Box::new(tractops::array::Gather::new(axis)), vec![]))
 }
 
+pub fn gather_elements(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let axis = node.get_attr_opt("axis")?.unwrap_or(0);
+    Ok((Box::new(tractops::array::GatherElements::new(axis)), vec![]))
+}
+
+pub fn gather_nd(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let batch_dims = node.get_attr_opt("batch_dims")?.unwrap_or(0);
+    Ok((Box::new(tractops::array::GatherNd::new(batch_dims)), vec![]))
+}
+
 pub fn pad(
     _ctx: &ParsingContext,
     node: &NodeProto,
@@ -142,6 +182,35 @@ pub fn pad(
     Ok((Box::new(tractops::array::Pad::new(pads, mode)), vec![]))
 }
 
+fn scatter_reduction(node: &NodeProto) -> TractResult<tractops::array::ScatterReduction> {
+    use tractops::array::ScatterReduction::*;
+    match node.get_attr_opt("reduction")?.unwrap_or("none") {
+        "none" => Ok(None),
+        "add" => Ok(Add),
+        "mul" => Ok(Mul),
+        "max" => Ok(Max),
+        "min" => Ok(Min),
+        s => node.bail_attr("reduction", &format!("unsupported {}", s)),
+    }
+}
+
+pub fn scatter_elements(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let axis = node.get_attr_opt("axis")?.unwrap_or(0);
+    let reduction = scatter_reduction(node)?;
+    Ok((Box::new(tractops::array::ScatterElements::new(axis, reduction)), vec![]))
+}
+
+pub fn scatter_nd(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let reduction = scatter_reduction(node)?;
+    Ok((Box::new(tractops::array::ScatterNd::new(reduction)), vec![]))
+}
+
 pub fn slice(
     _ctx: &ParsingContext,
     node: &NodeProto,
@@ -152,6 +221,16 @@ pub fn slice(
     Ok((Box::new(slice::Slice::new(axes, begin, end)), vec![]))
 }
 
+pub fn space_to_depth(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let block_size = node.get_attr("blocksize")?;
+    node.expect_attr("blocksize", block_size > 0, "a positive block size")?;
+    let op = tractops::nn::SpaceToDepth::new(block_size, tractops::nn::DataFormat::NCHW);
+    Ok((Box::new(op), vec![]))
+}
+
 pub fn split(
     _ctx: &ParsingContext,
     node: &NodeProto,
@@ -0,0 +1,114 @@
+use crate::model::ParsingContext;
+use crate::pb::NodeProto;
+use tract_core::internal::*;
+use tract_core::ndarray::Axis;
+use tract_core::ops as tractops;
+
+pub fn one_hot(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    Ok((Box::new(OneHot::new(node.get_attr_opt("axis")?.unwrap_or(-1))), vec![]))
+}
+
+/// ONNX OneHot: indices, depth and [off, on] values as inputs. Translated to
+/// the core OneHot once depth and values are known.
+#[derive(Debug, Clone, new, Default)]
+pub struct OneHot {
+    axis: i64,
+}
+
+impl OneHot {
+    fn depth(depth: &Tensor) -> TractResult<usize> {
+        let depth = depth.cast_to::<f64>()?;
+        match depth.as_slice::<f64>()? {
+            [depth] if *depth >= 1.0 => Ok(*depth as usize),
+            _ => bail!("OneHot expects a single positive depth, got {:?}", depth),
+        }
+    }
+
+    fn typed(
+        &self,
+        indices_rank: usize,
+        depth: &Tensor,
+        values: &Tensor,
+    ) -> TractResult<tractops::array::OneHot> {
+        if values.shape() != &[2] {
+            bail!("OneHot expects [off, on] values, got {:?}", values)
+        }
+        fn value<T: Datum>(values: &Tensor, ix: usize) -> TractResult<Arc<Tensor>> {
+            Ok(values.to_array_view::<T>()?.index_axis(Axis(0), ix).to_owned().into_arc_tensor())
+        }
+        let off = dispatch_datum!(value(values.datum_type())(values, 0))?;
+        let on = dispatch_datum!(value(values.datum_type())(values, 1))?;
+        Ok(tractops::array::OneHot::new(
+            resolve_axis(self.axis, indices_rank + 1)?,
+            Self::depth(depth)?,
+            off,
+            on,
+            true,
+        ))
+    }
+}
+
+impl Op for OneHot {
+    fn name(&self) -> Cow<str> {
+        "onnx.OneHot".into()
+    }
+
+    not_a_typed_op!();
+}
+
+impl StatelessOp for OneHot {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (indices, depth, values) = args_3!(inputs);
+        self.typed(indices.shape().len(), &depth, &values)?.eval(tvec!(indices))
+    }
+}
+
+impl InferenceRulesOp for OneHot {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 3)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[2].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[2].rank, 1)?;
+        s.equals(&inputs[2].shape[0], 2.to_dim())?;
+        s.equals(inputs[0].rank.bex() + 1, outputs[0].rank.bex())?;
+        s.given(&inputs[0].rank, move |s, rank| {
+            let axis = resolve_axis(self.axis, rank as usize + 1)?;
+            for i in 0..rank as usize {
+                let o = if i < axis { i } else { i + 1 };
+                s.equals(&inputs[0].shape[i], &outputs[0].shape[o])?;
+            }
+            s.given(&inputs[1].value, move |s, depth| {
+                s.equals(&outputs[0].shape[axis], Self::depth(&depth)?.to_dim())
+            })
+        })
+    }
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let indices = mapping[&node.inputs[0]];
+        let depth = target.outlet_fact(mapping[&node.inputs[1]])?.konst.clone();
+        let values = target.outlet_fact(mapping[&node.inputs[2]])?.konst.clone();
+        if let (Some(depth), Some(values)) = (depth, values) {
+            let rank = target.outlet_fact(indices)?.shape.rank();
+            let op = self.typed(rank, &depth, &values)?;
+            target.wire_node(&*node.name, op, [indices].as_ref())
+        } else {
+            bail!("OneHot depth and values are expected to be constants")
+        }
+    }
+
+    inference_op_as_op!();
+}
@@ -0,0 +1,47 @@
+use crate::model::{OnnxOpRegister, ParseResult, ParsingContext};
+use crate::pb::*;
+use tract_core::internal::*;
+use tract_core::ops::control_flow::{If, Loop};
+
+use super::optional_inputs;
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("If", if_then_else);
+    reg.insert("Loop", loop_);
+}
+
+fn if_then_else(
+    ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let then_graph: &GraphProto = node.get_attr("then_branch")?;
+    let else_graph: &GraphProto = node.get_attr("else_branch")?;
+    let ParseResult { model: then_body, unresolved_inputs: then_closures, .. } =
+        ctx.parse_graph(then_graph)?;
+    let ParseResult { model: else_body, unresolved_inputs: else_closures, .. } =
+        ctx.parse_graph(else_graph)?;
+    // branches have no inputs of their own, just the outer values they use,
+    // wired after the condition
+    let mut closures = then_closures.clone();
+    for name in &else_closures {
+        if !closures.contains(name) {
+            closures.push(name.clone());
+        }
+    }
+    let mapping = |names: &[String]| -> Vec<usize> {
+        names.iter().map(|name| 1 + closures.iter().position(|c| c == name).unwrap()).collect()
+    };
+    let op = If::new(then_body, mapping(&then_closures), else_body, mapping(&else_closures));
+    Ok((Box::new(op), closures))
+}
+
+fn loop_(
+    ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let graph: &GraphProto = node.get_attr("body")?;
+    let ParseResult { model, unresolved_inputs, .. } = ctx.parse_graph(graph)?;
+    let optional = optional_inputs(node, 0, 2);
+    let carried = node.get_input().len().saturating_sub(2);
+    Ok((Box::new(Loop::new(model, optional[0], optional[1], carried)), unresolved_inputs))
+}
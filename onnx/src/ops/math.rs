@@ -50,6 +50,7 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
 
     reg.insert("MatMul", |_, _| Ok((Box::new(tractops::math::MatMul::default()), vec![])));
     reg.insert("Gemm", gemm);
+    reg.insert("Einsum", einsum);
 }
 
 pub fn clip(
@@ -67,6 +68,14 @@ pub fn clip(
     Ok((op, vec![]))
 }
 
+pub fn einsum(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let equation: &str = node.get_attr("equation")?;
+    Ok((Box::new(tractops::math::Einsum::parse(equation)?), vec![]))
+}
+
 element_map!(Erf, [f32], erf_f32);
 
 #[allow(non_upper_case_globals)]
@@ -6,9 +6,11 @@ use tract_core::internal::*;
 
 mod array;
 mod category_mapper;
+mod control_flow;
 mod logic;
 mod math;
 mod nn;
+mod quant;
 pub mod rec;
 
 pub fn register_all_ops(reg: &mut OnnxOpRegister) {
@@ -18,18 +20,20 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
         Ok((Box::new(::tract_core::ops::identity::Identity::default()), vec![]))
     });
     category_mapper::register_all_ops(reg);
+    control_flow::register_all_ops(reg);
     logic::register_all_ops(reg);
     math::register_all_ops(reg);
     nn::register_all_ops(reg);
+    quant::register_all_ops(reg);
     array::register_all_ops(reg);
     rec::register_all_ops(reg);
 }
 
 fn konst(
-    _ctx: &ParsingContext,
+    ctx: &ParsingContext,
     node: &NodeProto,
 ) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
-    let v = node.get_attr("value")?;
+    let v = node.get_attr_tensor("value", ctx.model_dir)?;
     Ok((Box::new(::tract_core::ops::konst::Const::for_tensor(v)), vec![]))
 }
 
@@ -43,3 +47,17 @@ fn cast(
         .ok_or_else(|| format!("Cannot convert integer {} into a TensorProto_DataType", to))?;
     Ok((Box::new(::tract_core::ops::cast::Cast::new(to.try_into()?)), vec![]))
 }
+
+/// Positions of the optional inputs `first..first + len` among the inputs
+/// actually present.
+pub(crate) fn optional_inputs(pb: &NodeProto, first: usize, len: usize) -> TVec<Option<usize>> {
+    let mut real_input_count = first;
+    (first..first + len)
+        .map(|i| {
+            pb.get_input().get(i).filter(|s| !s.is_empty()).map(|_| {
+                real_input_count += 1;
+                real_input_count - 1
+            })
+        })
+        .collect()
+}
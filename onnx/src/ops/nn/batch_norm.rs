@@ -20,7 +20,7 @@ impl BatchNorm {
         var: &Tensor,
     ) -> TractResult<(Tensor, Tensor)>
     where
-        T: Datum + ::num_traits::Float + ::num_traits::FromPrimitive + ::ndarray::ScalarOperand,
+        T: Datum + ::num_traits::Float + ::num_traits::FromPrimitive,
         f32: AsPrimitive<T>,
     {
         let scale = scale.to_array_view::<T>()?.into_shape((c_dim,))?;
@@ -28,7 +28,7 @@ impl BatchNorm {
         let mean = mean.to_array_view::<T>()?.into_shape((c_dim,))?;
         let var = var.to_array_view::<T>()?.into_shape((c_dim,))?;
 
-        let denominator = (var.to_owned() + self.epsilon.as_()).map(|x| x.sqrt());
+        let denominator = var.map(|x| (*x + self.epsilon.as_()).sqrt());
 
         let slope = &scale / &denominator;
         let intercept = beta.to_owned() - (&mean * &scale) / denominator;
@@ -37,7 +37,7 @@ impl BatchNorm {
 
     fn eval_t<T>(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>>
     where
-        T: Datum + ::num_traits::Float + ::num_traits::FromPrimitive + ::ndarray::ScalarOperand,
+        T: Datum + ::num_traits::Float + ::num_traits::FromPrimitive,
         f32: AsPrimitive<T>,
     {
         let (x, scale, beta, mean, var) = args_5!(&mut inputs);
@@ -4,11 +4,13 @@ use tract_core::ops::cnn::{KernelFormat, PaddingSpec};
 use tract_core::ops::nn::DataFormat;
 
 use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::ops::optional_inputs;
 use crate::pb::NodeProto;
 use crate::pb_helpers::OptionExt;
 
 mod batch_norm;
 mod dropout;
+mod resize;
 
 macro_rules! reduce {
     ($id:ident) => {
@@ -30,6 +32,7 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("AveragePool", average_pool);
     reg.insert("BatchNormalization", batch_normalization);
     reg.insert("Conv", conv);
+    reg.insert("ConvTranspose", conv_transpose);
     reg.insert("Dropout", dropout::dropout);
     reg.insert("Elu", elu);
     reg.insert("GlobalAveragePool", |_, _| {
@@ -41,10 +44,15 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     });
     reg.insert("Hardmax", layer_hard_max);
     reg.insert("HardSigmoid", hard_sigmoid);
+    reg.insert("InstanceNormalization", instance_normalization);
+    reg.insert("LayerNormalization", layer_normalization);
     reg.insert("LeakyRelu", leaky_relu);
     reg.insert("LogSoftmax", layer_log_soft_max);
+    reg.insert("LpNormalization", lp_normalization);
     reg.insert("LRN", lrn);
     reg.insert("MaxPool", max_pool);
+    reg.insert("MeanVarianceNormalization", mean_variance_normalization);
+    reg.insert("NonMaxSuppression", non_max_suppression);
     reg.insert("ParametricSoftplus", parametric_softplus);
     reg.insert("PRelu", |_, _| Ok((Box::new(prelu::bin()), vec![])));
     reg.insert("ReduceL1", reduce!(L1));
@@ -58,17 +66,20 @@ pub fn register_all_ops(reg: &mut OnnxOpRegister) {
     reg.insert("ReduceSum", reduce!(Sum));
     reg.insert("ReduceSumSquare", reduce!(SumSquare));
     reg.insert("Relu", |_, _| Ok((Box::new(tractops::math::ScalarMax::new(0.0)), vec![])));
+    reg.insert("Resize", resize::resize);
     reg.insert("ScaledTanh", scaled_tanh);
     reg.insert("Shrink", shrink);
     reg.insert("ThresholdedRelu", thresholded_relu);
+    reg.insert("TopK", top_k);
     reg.insert("Selu", selu);
     reg.insert("Sigmoid", |_, _| Ok((Box::new(tractops::nn::Sigmoid::default()), vec![])));
     reg.insert("Softmax", layer_soft_max);
     reg.insert("Softplus", |_, _| Ok((Box::new(tractops::nn::Softplus::default()), vec![])));
     reg.insert("Softsign", |_, _| Ok((Box::new(tractops::nn::Softsign::default()), vec![])));
+    reg.insert("Upsample", resize::upsample);
 }
 
-fn pad(node: &NodeProto) -> TractResult<PaddingSpec> {
+pub(crate) fn pad(node: &NodeProto) -> TractResult<PaddingSpec> {
     if let Some(pads) = node.get_attr_opt_tvec("pads")? {
         let len = pads.len();
         return Ok(PaddingSpec::Explicit(
@@ -93,11 +104,11 @@ fn pad(node: &NodeProto) -> TractResult<PaddingSpec> {
         .unwrap_or(PaddingSpec::Valid))
 }
 
-fn dilations(node: &NodeProto) -> TractResult<Option<TVec<usize>>> {
+pub(crate) fn dilations(node: &NodeProto) -> TractResult<Option<TVec<usize>>> {
     node.get_attr_opt_tvec("dilations")
 }
 
-fn strides(node: &NodeProto) -> TractResult<Option<TVec<usize>>> {
+pub(crate) fn strides(node: &NodeProto) -> TractResult<Option<TVec<usize>>> {
     node.get_attr_opt_tvec("strides")
 }
 
@@ -141,6 +152,28 @@ pub fn conv(
     ))
 }
 
+pub fn conv_transpose(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let kernel_shape = node.get_attr_opt_tvec("kernel_shape")?;
+    let group = node.get_attr_opt("group")?.unwrap_or(1);
+    Ok((
+        Box::new(tractops::cnn::Deconv::new(
+            DataFormat::NCHW,
+            KernelFormat::OIHW,
+            dilations(node)?,
+            kernel_shape,
+            pad(node)?,
+            strides(node)?,
+            node.get_attr_opt_tvec("output_padding")?,
+            node.get_attr_opt_tvec("output_shape")?,
+            group,
+        )),
+        vec![],
+    ))
+}
+
 pub fn average_pool(
     _ctx: &ParsingContext,
     node: &NodeProto,
@@ -183,6 +216,14 @@ pub fn hard_sigmoid(
     Ok((Box::new(tractops::nn::Hardsigmoid::new(alpha, beta)), vec![]))
 }
 
+pub fn instance_normalization(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let epsilon = node.get_attr_opt("epsilon")?.unwrap_or(1e-5);
+    Ok((Box::new(tractops::nn::InstanceNormalization::new(epsilon)), vec![]))
+}
+
 pub fn layer_hard_max(
     _ctx: &ParsingContext,
     node: &NodeProto,
@@ -207,6 +248,18 @@ pub fn layer_soft_max(
     Ok((Box::new(tractops::nn::LayerSoftmax::new(axis)), vec![]))
 }
 
+pub fn layer_normalization(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    if node.get_output().iter().skip(1).any(|o| !o.is_empty()) {
+        bail!("LayerNormalization mean and inverse standard deviation outputs are not supported")
+    }
+    let axis = node.get_attr_opt("axis")?.unwrap_or(-1);
+    let epsilon = node.get_attr_opt("epsilon")?.unwrap_or(1e-5);
+    Ok((Box::new(tractops::nn::LayerNorm::new(axis, epsilon)), vec![]))
+}
+
 pub fn leaky_relu(
     _ctx: &ParsingContext,
     node: &NodeProto,
@@ -215,6 +268,18 @@ pub fn leaky_relu(
     Ok((Box::new(tractops::nn::LeakyRelu::new(alpha)), vec![]))
 }
 
+pub fn lp_normalization(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let axis = node.get_attr_opt("axis")?.unwrap_or(-1);
+    let p = node.get_attr_opt("p")?.unwrap_or(2usize);
+    if p != 1 && p != 2 {
+        node.bail_attr("p", "expected 1 or 2")?
+    }
+    Ok((Box::new(tractops::nn::LpNormalization::new(axis, p)), vec![]))
+}
+
 pub fn lrn(
     _ctx: &ParsingContext,
     node: &NodeProto,
@@ -242,6 +307,30 @@ pub fn max_pool(
     ))
 }
 
+pub fn mean_variance_normalization(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let axes = node.get_attr_opt_tvec("axes")?.unwrap_or(tvec!(0, 2, 3));
+    Ok((Box::new(tractops::nn::MeanVarianceNormalization::new(axes, 1e-9)), vec![]))
+}
+
+pub fn non_max_suppression(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let box_format = match node.get_attr_opt("center_point_box")?.unwrap_or(0i64) {
+        0 => tractops::nn::BoxFormat::Corners,
+        1 => tractops::nn::BoxFormat::CenterSize,
+        _ => node.bail_attr("center_point_box", "expected 0 or 1")?,
+    };
+    let inputs = optional_inputs(node, 2, 3);
+    Ok((
+        Box::new(tractops::nn::NonMaxSuppression::new(box_format, inputs[0], inputs[1], inputs[2])),
+        vec![],
+    ))
+}
+
 pub fn parametric_softplus(
     _ctx: &ParsingContext,
     node: &NodeProto,
@@ -289,6 +378,19 @@ pub fn selu(
     Ok((Box::new(tractops::nn::Selu::new(alpha, gamma)), vec![]))
 }
 
+pub fn top_k(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    if node.get_attr_opt::<i64>("k")?.is_some() {
+        node.bail_attr("k", "TopK-1 is not supported, k is expected as an input")?
+    }
+    let axis = node.get_attr_opt("axis")?.unwrap_or(-1);
+    let largest = node.get_attr_opt("largest")?.unwrap_or(1i64) == 1;
+    let sorted = node.get_attr_opt("sorted")?.unwrap_or(1i64) == 1;
+    Ok((Box::new(tractops::nn::TopK::new(axis, largest, sorted, i64::datum_type())), vec![]))
+}
+
 pub fn thresholded_relu(
     _ctx: &ParsingContext,
     node: &NodeProto,
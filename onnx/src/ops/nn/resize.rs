@@ -0,0 +1,82 @@
+use crate::model::ParsingContext;
+use crate::pb::*;
+use tract_core::internal::*;
+use tract_core::ops::nn::{
+    CoordTransformer, Interpolator, NearestMode, Resize, ResizeTarget, TypedResize,
+};
+
+use crate::ops::optional_inputs;
+
+fn interpolator(node: &NodeProto) -> TractResult<Interpolator> {
+    match node.get_attr_opt("mode")?.unwrap_or("nearest") {
+        "nearest" => {
+            let mode = match node.get_attr_opt("nearest_mode")?.unwrap_or("round_prefer_floor") {
+                "round_prefer_floor" => NearestMode::RoundPreferFloor,
+                "round_prefer_ceil" => NearestMode::RoundPreferCeil,
+                "floor" => NearestMode::Floor,
+                "ceil" => NearestMode::Ceil,
+                s => node.bail_attr("nearest_mode", &format!("unsupported {}", s))?,
+            };
+            Ok(Interpolator::Nearest(mode))
+        }
+        "linear" | "bilinear" => Ok(Interpolator::Linear),
+        "cubic" => Ok(Interpolator::Cubic(node.get_attr_opt("cubic_coeff_a")?.unwrap_or(-0.75))),
+        s => node.bail_attr("mode", &format!("unsupported {}", s)),
+    }
+}
+
+/// Upsample (opset 7 to 9) and Resize (opset 10): asymmetric coordinates,
+/// nearest neighbour rounding down.
+fn legacy_interpolator(node: &NodeProto) -> TractResult<Interpolator> {
+    match interpolator(node)? {
+        Interpolator::Nearest(_) => Ok(Interpolator::Nearest(NearestMode::Floor)),
+        Interpolator::Linear => Ok(Interpolator::Linear),
+        _ => node.bail_attr("mode", "expected nearest or linear"),
+    }
+}
+
+pub fn upsample(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let interpolator = legacy_interpolator(node)?;
+    if let Some(scales) = node.get_attr_opt_tvec::<f32>("scales")? {
+        let axes = (0..scales.len()).collect();
+        let op = TypedResize::new(
+            axes,
+            ResizeTarget::Scales(scales),
+            CoordTransformer::Asymmetric,
+            interpolator,
+        );
+        Ok((Box::new(op), vec![]))
+    } else {
+        let op = Resize::new(None, CoordTransformer::Asymmetric, interpolator, Some(1), None);
+        Ok((Box::new(op), vec![]))
+    }
+}
+
+pub fn resize(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    if node.get_input().len() == 2 {
+        let interpolator = legacy_interpolator(node)?;
+        let op = Resize::new(None, CoordTransformer::Asymmetric, interpolator, Some(1), None);
+        return Ok((Box::new(op), vec![]));
+    }
+    let coord_transformer =
+        match node.get_attr_opt("coordinate_transformation_mode")?.unwrap_or("half_pixel") {
+            "half_pixel" => CoordTransformer::HalfPixel,
+            "pytorch_half_pixel" => CoordTransformer::PytorchHalfPixel,
+            "align_corners" => CoordTransformer::AlignCorners,
+            "asymmetric" => CoordTransformer::Asymmetric,
+            "tf_half_pixel_for_nn" => CoordTransformer::TfHalfPixelForNN,
+            s => node.bail_attr("coordinate_transformation_mode", &format!("unsupported {}", s))?,
+        };
+    if node.get_attr_opt("exclude_outside")?.unwrap_or(0i64) != 0 {
+        node.bail_attr("exclude_outside", "only 0 is supported")?
+    }
+    let inputs = optional_inputs(node, 1, 3);
+    let op = Resize::new(None, coord_transformer, interpolator(node)?, inputs[1], inputs[2]);
+    Ok((Box::new(op), vec![]))
+}
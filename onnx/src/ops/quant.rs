@@ -0,0 +1,580 @@
+use tract_core::internal::*;
+use tract_core::ops::cnn::PaddingSpec;
+use tract_core::ops::nn::DataFormat;
+use tract_core::ops::quant::{self as core_quant, QParams};
+
+use crate::model::{OnnxOpRegister, ParsingContext};
+use crate::pb::NodeProto;
+
+use super::optional_inputs;
+
+pub fn register_all_ops(reg: &mut OnnxOpRegister) {
+    reg.insert("QuantizeLinear", quantize_linear);
+    reg.insert("DequantizeLinear", dequantize_linear);
+    reg.insert("MatMulInteger", mat_mul_integer);
+    reg.insert("QLinearMatMul", |_, _| Ok((Box::new(QLinearMatMul), vec![])));
+    reg.insert("ConvInteger", conv_integer);
+    reg.insert("QLinearConv", qlinear_conv);
+}
+
+/// Values of the inputs that are known at translation time.
+fn const_inputs(
+    node: &InferenceNode,
+    target: &TypedModel,
+    mapping: &HashMap<OutletId, OutletId>,
+) -> TractResult<TVec<Option<Arc<Tensor>>>> {
+    node.inputs.iter().map(|i| Ok(target.outlet_fact(mapping[i])?.konst.clone())).collect()
+}
+
+fn param<'a>(inputs: &'a [Option<Arc<Tensor>>], ix: usize, name: &str) -> TractResult<&'a Tensor> {
+    Ok(inputs[ix].as_ref().map(|t| &**t).ok_or_else(|| format!("{} is not const", name))?)
+}
+
+/// A per-tensor scale or zero point.
+fn scalar<T: Datum + Copy>(t: &Tensor, name: &str) -> TractResult<T> {
+    if t.len() != 1 {
+        bail!("Only per-tensor {} is supported, got shape {:?}", name, t.shape())
+    }
+    Ok(t.cast_to::<T>()?.as_slice::<T>()?[0])
+}
+
+/// A per-tensor or per-row (per output channel for convolutions) scale or
+/// zero point.
+fn per_row<T: Datum + Copy>(t: &Tensor, name: &str) -> TractResult<TVec<T>> {
+    if t.shape().len() > 1 {
+        bail!("Only per-tensor or per-row {} is supported, got shape {:?}", name, t.shape())
+    }
+    Ok(t.cast_to::<T>()?.as_slice::<T>()?.into())
+}
+
+fn zero_point(inputs: &[Option<Arc<Tensor>>], ix: Option<usize>, name: &str) -> TractResult<i32> {
+    ix.map(|ix| scalar::<i32>(param(inputs, ix, name)?, name)).unwrap_or(Ok(0))
+}
+
+fn zero_points(
+    inputs: &[Option<Arc<Tensor>>],
+    ix: Option<usize>,
+    name: &str,
+) -> TractResult<TVec<i32>> {
+    ix.map(|ix| per_row::<i32>(param(inputs, ix, name)?, name)).unwrap_or(Ok(tvec!(0)))
+}
+
+pub fn quantize_linear(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let optional_zero_point_input = optional_inputs(node, 2, 1)[0];
+    Ok((Box::new(QuantizeLinear::new(optional_zero_point_input)), vec![]))
+}
+
+#[derive(Debug, Clone, new)]
+pub struct QuantizeLinear {
+    optional_zero_point_input: Option<usize>,
+}
+
+impl QuantizeLinear {
+    fn core_op(&self, inputs: &[Option<Arc<Tensor>>]) -> TractResult<core_quant::QuantizeLinear> {
+        let scale = scalar::<f32>(param(inputs, 1, "y_scale")?, "y_scale")?;
+        let zero_point = zero_point(inputs, self.optional_zero_point_input, "y_zero_point")?;
+        let to = self
+            .optional_zero_point_input
+            .map(|ix| param(inputs, ix, "y_zero_point").map(|t| t.datum_type()))
+            .unwrap_or(Ok(DatumType::U8))?;
+        Ok(core_quant::QuantizeLinear::new(scale, zero_point, to))
+    }
+}
+
+impl Op for QuantizeLinear {
+    fn name(&self) -> Cow<str> {
+        "onnx.QuantizeLinear".into()
+    }
+
+    not_a_typed_op!();
+}
+
+impl StatelessOp for QuantizeLinear {
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let params: TVec<_> = inputs.iter().cloned().map(Some).collect();
+        self.core_op(&params)?.eval(tvec!(inputs[0].clone()))
+    }
+}
+
+impl InferenceRulesOp for QuantizeLinear {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 2 + self.optional_zero_point_input.is_some() as usize)?;
+        check_output_arity(&outputs, 1)?;
+        if let Some(zp) = self.optional_zero_point_input {
+            s.equals(&outputs[0].datum_type, &inputs[zp].datum_type)?;
+        } else {
+            s.equals(&outputs[0].datum_type, DatumType::U8)?;
+        }
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    inference_op_as_op!();
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let op = self.core_op(&const_inputs(node, target, mapping)?)?;
+        target.wire_node(&*node.name, op, &[mapping[&node.inputs[0]]])
+    }
+}
+
+pub fn dequantize_linear(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let optional_zero_point_input = optional_inputs(node, 2, 1)[0];
+    Ok((Box::new(DequantizeLinear::new(optional_zero_point_input)), vec![]))
+}
+
+#[derive(Debug, Clone, new)]
+pub struct DequantizeLinear {
+    optional_zero_point_input: Option<usize>,
+}
+
+impl DequantizeLinear {
+    fn core_op(&self, inputs: &[Option<Arc<Tensor>>]) -> TractResult<core_quant::DequantizeLinear> {
+        let scale = scalar::<f32>(param(inputs, 1, "x_scale")?, "x_scale")?;
+        let zero_point = zero_point(inputs, self.optional_zero_point_input, "x_zero_point")?;
+        Ok(core_quant::DequantizeLinear::new(scale, zero_point))
+    }
+}
+
+impl Op for DequantizeLinear {
+    fn name(&self) -> Cow<str> {
+        "onnx.DequantizeLinear".into()
+    }
+
+    not_a_typed_op!();
+}
+
+impl StatelessOp for DequantizeLinear {
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let params: TVec<_> = inputs.iter().cloned().map(Some).collect();
+        self.core_op(&params)?.eval(tvec!(inputs[0].clone()))
+    }
+}
+
+impl InferenceRulesOp for DequantizeLinear {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 2 + self.optional_zero_point_input.is_some() as usize)?;
+        check_output_arity(&outputs, 1)?;
+        if let Some(zp) = self.optional_zero_point_input {
+            s.equals(&inputs[0].datum_type, &inputs[zp].datum_type)?;
+        }
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        s.equals(&inputs[0].shape, &outputs[0].shape)?;
+        Ok(())
+    }
+
+    inference_op_as_op!();
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let op = self.core_op(&const_inputs(node, target, mapping)?)?;
+        target.wire_node(&*node.name, op, &[mapping[&node.inputs[0]]])
+    }
+}
+
+pub fn mat_mul_integer(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let options = optional_inputs(node, 2, 2);
+    Ok((Box::new(MatMulInteger::new(options[0], options[1])), vec![]))
+}
+
+#[derive(Debug, Clone, new)]
+pub struct MatMulInteger {
+    optional_a_zero_point_input: Option<usize>,
+    optional_b_zero_point_input: Option<usize>,
+}
+
+impl MatMulInteger {
+    fn core_op(&self, inputs: &[Option<Arc<Tensor>>]) -> TractResult<core_quant::QMatMul> {
+        let za = zero_points(inputs, self.optional_a_zero_point_input, "a_zero_point")?;
+        let zb = zero_point(inputs, self.optional_b_zero_point_input, "b_zero_point")?;
+        Ok(core_quant::QMatMul::new(QParams::new(DatumType::I32, za, zb, 0, tvec!(1.0))))
+    }
+}
+
+impl Op for MatMulInteger {
+    fn name(&self) -> Cow<str> {
+        "onnx.MatMulInteger".into()
+    }
+
+    not_a_typed_op!();
+}
+
+impl StatelessOp for MatMulInteger {
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let params: TVec<_> = inputs.iter().cloned().map(Some).collect();
+        self.core_op(&params)?.eval(tvec!(inputs[0].clone(), inputs[1].clone()))
+    }
+}
+
+impl InferenceRulesOp for MatMulInteger {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(
+            &inputs,
+            2 + self.optional_a_zero_point_input.is_some() as usize
+                + self.optional_b_zero_point_input.is_some() as usize,
+        )?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&outputs[0].datum_type, i32::datum_type())?;
+        mat_mul_shape_rules(s, &inputs[0], &inputs[1], &outputs[0])
+    }
+
+    inference_op_as_op!();
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let op = self.core_op(&const_inputs(node, target, mapping)?)?;
+        target.wire_node(&*node.name, op, &[mapping[&node.inputs[0]], mapping[&node.inputs[1]]])
+    }
+}
+
+/// Inputs: a, a_scale, a_zero_point, b, b_scale, b_zero_point, y_scale,
+/// y_zero_point.
+#[derive(Debug, Clone)]
+pub struct QLinearMatMul;
+
+impl QLinearMatMul {
+    fn core_op(&self, inputs: &[Option<Arc<Tensor>>]) -> TractResult<core_quant::QMatMul> {
+        let b_scale = scalar::<f32>(param(inputs, 4, "b_scale")?, "b_scale")?;
+        let y_scale = scalar::<f32>(param(inputs, 6, "y_scale")?, "y_scale")?;
+        let scale = per_row::<f32>(param(inputs, 1, "a_scale")?, "a_scale")?
+            .into_iter()
+            .map(|a_scale| a_scale * b_scale / y_scale)
+            .collect();
+        let y_zero_point = param(inputs, 7, "y_zero_point")?;
+        Ok(core_quant::QMatMul::new(QParams::new(
+            y_zero_point.datum_type(),
+            per_row::<i32>(param(inputs, 2, "a_zero_point")?, "a_zero_point")?,
+            scalar::<i32>(param(inputs, 5, "b_zero_point")?, "b_zero_point")?,
+            scalar::<i32>(y_zero_point, "y_zero_point")?,
+            scale,
+        )))
+    }
+}
+
+impl Op for QLinearMatMul {
+    fn name(&self) -> Cow<str> {
+        "onnx.QLinearMatMul".into()
+    }
+
+    not_a_typed_op!();
+}
+
+impl StatelessOp for QLinearMatMul {
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let params: TVec<_> = inputs.iter().cloned().map(Some).collect();
+        self.core_op(&params)?.eval(tvec!(inputs[0].clone(), inputs[3].clone()))
+    }
+}
+
+impl InferenceRulesOp for QLinearMatMul {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 8)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &inputs[2].datum_type)?;
+        s.equals(&inputs[3].datum_type, &inputs[5].datum_type)?;
+        s.equals(&outputs[0].datum_type, &inputs[7].datum_type)?;
+        mat_mul_shape_rules(s, &inputs[0], &inputs[3], &outputs[0])
+    }
+
+    inference_op_as_op!();
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let op = self.core_op(&const_inputs(node, target, mapping)?)?;
+        target.wire_node(&*node.name, op, &[mapping[&node.inputs[0]], mapping[&node.inputs[3]]])
+    }
+}
+
+fn mat_mul_shape_rules<'r, 'p: 'r>(
+    s: &mut Solver<'r>,
+    a: &'p TensorProxy,
+    b: &'p TensorProxy,
+    c: &'p TensorProxy,
+) -> InferenceResult {
+    s.given_2(&a.shape, &b.shape, move |s, ashape, bshape| {
+        let (_, _, cshape) =
+            tract_core::ops::math::mat_mul::infer_shapes(ashape, bshape, false, false, false)?;
+        s.equals(&c.shape, cshape)
+    })
+}
+
+/// Convolution attributes shared by ConvInteger and QLinearConv.
+#[derive(Debug, Clone, new)]
+pub struct ConvGeometry {
+    padding: PaddingSpec,
+    dilations: Option<TVec<usize>>,
+    strides: Option<TVec<usize>>,
+    group: usize,
+}
+
+impl ConvGeometry {
+    fn parse(node: &NodeProto) -> TractResult<ConvGeometry> {
+        Ok(ConvGeometry::new(
+            super::nn::pad(node)?,
+            super::nn::dilations(node)?,
+            super::nn::strides(node)?,
+            node.get_attr_opt("group")?.unwrap_or(1),
+        ))
+    }
+
+    fn core_op(
+        &self,
+        kernel: &Tensor,
+        bias: Option<&Tensor>,
+        params: QParams,
+    ) -> TractResult<core_quant::QConv> {
+        let spatial_rank = kernel.shape().len() - 2;
+        let bias = bias
+            .map(|b| -> TractResult<Arc<Tensor>> {
+                Ok(b.cast_to::<i32>()?.into_owned().into_arc_tensor())
+            })
+            .transpose()?;
+        Ok(core_quant::QConv::new(
+            DataFormat::NCHW,
+            self.padding.clone(),
+            self.dilations.clone().unwrap_or(tvec!(1; spatial_rank)),
+            self.strides.clone().unwrap_or(tvec!(1; spatial_rank)),
+            self.group,
+            kernel.clone().into_arc_tensor(),
+            bias,
+            params,
+        ))
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        input: &'p TensorProxy,
+        kernel: &'p TensorProxy,
+        output: &'p TensorProxy,
+    ) -> InferenceResult {
+        s.equals(&input.rank, &kernel.rank)?;
+        s.equals(&input.rank, &output.rank)?;
+        s.equals(&output.shape[0], &input.shape[0])?;
+        s.equals(&output.shape[1], &kernel.shape[0])?;
+        s.given_2(&input.shape, &kernel.shape, move |s, ishape, kshape| {
+            if let Some(kshape) = kshape
+                .iter()
+                .map(|d| d.to_integer().ok().map(|d| d as usize))
+                .collect::<Option<TVec<_>>>()
+            {
+                let spatial_rank = kshape.len() - 2;
+                let computed = self.padding.compute(
+                    &ishape[2..],
+                    &kshape[2..],
+                    &*self.dilations.clone().unwrap_or(tvec!(1; spatial_rank)),
+                    &*self.strides.clone().unwrap_or(tvec!(1; spatial_rank)),
+                );
+                for (ix, d) in computed.into_iter().enumerate() {
+                    s.equals(&output.shape[ix + 2], d.output)?;
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+pub fn conv_integer(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let options = optional_inputs(node, 2, 2);
+    Ok((Box::new(ConvInteger::new(ConvGeometry::parse(node)?, options[0], options[1])), vec![]))
+}
+
+#[derive(Debug, Clone, new)]
+pub struct ConvInteger {
+    geometry: ConvGeometry,
+    optional_x_zero_point_input: Option<usize>,
+    optional_w_zero_point_input: Option<usize>,
+}
+
+impl ConvInteger {
+    fn core_op(&self, inputs: &[Option<Arc<Tensor>>]) -> TractResult<core_quant::QConv> {
+        let zx = zero_point(inputs, self.optional_x_zero_point_input, "x_zero_point")?;
+        let zw = zero_points(inputs, self.optional_w_zero_point_input, "w_zero_point")?;
+        let params = QParams::new(DatumType::I32, zw, zx, 0, tvec!(1.0));
+        self.geometry.core_op(param(inputs, 1, "w")?, None, params)
+    }
+}
+
+impl Op for ConvInteger {
+    fn name(&self) -> Cow<str> {
+        "onnx.ConvInteger".into()
+    }
+
+    not_a_typed_op!();
+}
+
+impl StatelessOp for ConvInteger {
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let params: TVec<_> = inputs.iter().cloned().map(Some).collect();
+        self.core_op(&params)?.eval(tvec!(inputs[0].clone()))
+    }
+}
+
+impl InferenceRulesOp for ConvInteger {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(
+            &inputs,
+            2 + self.optional_x_zero_point_input.is_some() as usize
+                + self.optional_w_zero_point_input.is_some() as usize,
+        )?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&outputs[0].datum_type, i32::datum_type())?;
+        self.geometry.rules(s, &inputs[0], &inputs[1], &outputs[0])
+    }
+
+    inference_op_as_op!();
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let op = self.core_op(&const_inputs(node, target, mapping)?)?;
+        target.wire_node(&*node.name, op, &[mapping[&node.inputs[0]]])
+    }
+}
+
+pub fn qlinear_conv(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let optional_bias_input = optional_inputs(node, 8, 1)[0];
+    Ok((Box::new(QLinearConv::new(ConvGeometry::parse(node)?, optional_bias_input)), vec![]))
+}
+
+/// Inputs: x, x_scale, x_zero_point, w, w_scale, w_zero_point, y_scale,
+/// y_zero_point and an optional i32 bias.
+#[derive(Debug, Clone, new)]
+pub struct QLinearConv {
+    geometry: ConvGeometry,
+    optional_bias_input: Option<usize>,
+}
+
+impl QLinearConv {
+    fn core_op(&self, inputs: &[Option<Arc<Tensor>>]) -> TractResult<core_quant::QConv> {
+        let x_scale = scalar::<f32>(param(inputs, 1, "x_scale")?, "x_scale")?;
+        let y_scale = scalar::<f32>(param(inputs, 6, "y_scale")?, "y_scale")?;
+        let scale = per_row::<f32>(param(inputs, 4, "w_scale")?, "w_scale")?
+            .into_iter()
+            .map(|w_scale| x_scale * w_scale / y_scale)
+            .collect();
+        let y_zero_point = param(inputs, 7, "y_zero_point")?;
+        let params = QParams::new(
+            y_zero_point.datum_type(),
+            per_row::<i32>(param(inputs, 5, "w_zero_point")?, "w_zero_point")?,
+            scalar::<i32>(param(inputs, 2, "x_zero_point")?, "x_zero_point")?,
+            scalar::<i32>(y_zero_point, "y_zero_point")?,
+            scale,
+        );
+        let bias = self.optional_bias_input.map(|ix| param(inputs, ix, "B")).transpose()?;
+        self.geometry.core_op(param(inputs, 3, "w")?, bias, params)
+    }
+}
+
+impl Op for QLinearConv {
+    fn name(&self) -> Cow<str> {
+        "onnx.QLinearConv".into()
+    }
+
+    not_a_typed_op!();
+}
+
+impl StatelessOp for QLinearConv {
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let params: TVec<_> = inputs.iter().cloned().map(Some).collect();
+        self.core_op(&params)?.eval(tvec!(inputs[0].clone()))
+    }
+}
+
+impl InferenceRulesOp for QLinearConv {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 8 + self.optional_bias_input.is_some() as usize)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].datum_type, &inputs[2].datum_type)?;
+        s.equals(&inputs[3].datum_type, &inputs[5].datum_type)?;
+        s.equals(&outputs[0].datum_type, &inputs[7].datum_type)?;
+        if let Some(bias) = self.optional_bias_input {
+            s.equals(&inputs[bias].datum_type, i32::datum_type())?;
+            s.equals(&inputs[bias].rank, 1)?;
+            s.equals(&inputs[bias].shape[0], &inputs[3].shape[0])?;
+        }
+        self.geometry.rules(s, &inputs[0], &inputs[3], &outputs[0])
+    }
+
+    inference_op_as_op!();
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let op = self.core_op(&const_inputs(node, target, mapping)?)?;
+        target.wire_node(&*node.name, op, &[mapping[&node.inputs[0]]])
+    }
+}
@@ -2884,6 +2884,8 @@ pub struct TensorProto {
     name: ::protobuf::SingularField<::std::string::String>,
     doc_string: ::protobuf::SingularField<::std::string::String>,
     raw_data: ::protobuf::SingularField<::std::vec::Vec<u8>>,
+    external_data: ::protobuf::RepeatedField<StringStringEntryProto>,
+    data_location: ::std::option::Option<TensorProto_DataLocation>,
     double_data: ::std::vec::Vec<f64>,
     uint64_data: ::std::vec::Vec<u64>,
     // special fields
@@ -3181,6 +3183,50 @@ impl TensorProto {
         }
     }
 
+    // repeated .onnx.StringStringEntryProto external_data = 13;
+
+    pub fn clear_external_data(&mut self) {
+        self.external_data.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_external_data(&mut self, v: ::protobuf::RepeatedField<StringStringEntryProto>) {
+        self.external_data = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_external_data(&mut self) -> &mut ::protobuf::RepeatedField<StringStringEntryProto> {
+        &mut self.external_data
+    }
+
+    // Take field
+    pub fn take_external_data(&mut self) -> ::protobuf::RepeatedField<StringStringEntryProto> {
+        ::std::mem::replace(&mut self.external_data, ::protobuf::RepeatedField::new())
+    }
+
+    pub fn get_external_data(&self) -> &[StringStringEntryProto] {
+        &self.external_data
+    }
+
+    // optional .onnx.TensorProto.DataLocation data_location = 14;
+
+    pub fn clear_data_location(&mut self) {
+        self.data_location = ::std::option::Option::None;
+    }
+
+    pub fn has_data_location(&self) -> bool {
+        self.data_location.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_data_location(&mut self, v: TensorProto_DataLocation) {
+        self.data_location = ::std::option::Option::Some(v);
+    }
+
+    pub fn get_data_location(&self) -> TensorProto_DataLocation {
+        self.data_location.unwrap_or(TensorProto_DataLocation::DEFAULT)
+    }
+
     // repeated double double_data = 10;
 
     pub fn clear_double_data(&mut self) {
@@ -3239,6 +3285,11 @@ impl ::protobuf::Message for TensorProto {
                 return false;
             }
         };
+        for v in &self.external_data {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
         true
     }
 
@@ -3276,6 +3327,12 @@ impl ::protobuf::Message for TensorProto {
                 9 => {
                     ::protobuf::rt::read_singular_bytes_into(wire_type, is, &mut self.raw_data)?;
                 },
+                13 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.external_data)?;
+                },
+                14 => {
+                    ::protobuf::rt::read_proto2_enum_with_unknown_fields_into(wire_type, is, &mut self.data_location, 14, &mut self.unknown_fields)?
+                },
                 10 => {
                     ::protobuf::rt::read_repeated_double_into(wire_type, is, &mut self.double_data)?;
                 },
@@ -3325,6 +3382,13 @@ impl ::protobuf::Message for TensorProto {
         if let Some(ref v) = self.raw_data.as_ref() {
             my_size += ::protobuf::rt::bytes_size(9, &v);
         }
+        for value in &self.external_data {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        if let Some(v) = self.data_location {
+            my_size += ::protobuf::rt::enum_size(14, v);
+        }
         if !self.double_data.is_empty() {
             my_size += 1 + ::protobuf::rt::compute_raw_varint32_size((self.double_data.len() * 8) as u32) + (self.double_data.len() * 8) as u32;
         }
@@ -3384,6 +3448,14 @@ impl ::protobuf::Message for TensorProto {
         if let Some(ref v) = self.raw_data.as_ref() {
             os.write_bytes(9, &v)?;
         }
+        for v in &self.external_data {
+            os.write_tag(13, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        if let Some(v) = self.data_location {
+            os.write_enum(14, v.value())?;
+        }
         if !self.double_data.is_empty() {
             os.write_tag(10, ::protobuf::wire_format::WireTypeLengthDelimited)?;
             // TODO: Data size is computed again, it should be cached
@@ -3492,6 +3564,16 @@ impl ::protobuf::Message for TensorProto {
                     |m: &TensorProto| { &m.raw_data },
                     |m: &mut TensorProto| { &mut m.raw_data },
                 ));
+                fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<StringStringEntryProto>>(
+                    "external_data",
+                    |m: &TensorProto| { &m.external_data },
+                    |m: &mut TensorProto| { &mut m.external_data },
+                ));
+                fields.push(::protobuf::reflect::accessor::make_option_accessor::<_, ::protobuf::types::ProtobufTypeEnum<TensorProto_DataLocation>>(
+                    "data_location",
+                    |m: &TensorProto| { &m.data_location },
+                    |m: &mut TensorProto| { &mut m.data_location },
+                ));
                 fields.push(::protobuf::reflect::accessor::make_vec_accessor::<_, ::protobuf::types::ProtobufTypeDouble>(
                     "double_data",
                     |m: &TensorProto| { &m.double_data },
@@ -3534,6 +3616,8 @@ impl ::protobuf::Clear for TensorProto {
         self.clear_name();
         self.clear_doc_string();
         self.clear_raw_data();
+        self.clear_external_data();
+        self.clear_data_location();
         self.clear_double_data();
         self.clear_uint64_data();
         self.unknown_fields.clear();
@@ -3842,6 +3926,55 @@ impl ::protobuf::reflect::ProtobufValue for TensorProto_DataType {
     }
 }
 
+#[derive(Clone,PartialEq,Eq,Debug,Hash)]
+pub enum TensorProto_DataLocation {
+    DEFAULT = 0,
+    EXTERNAL = 1,
+}
+
+impl ::protobuf::ProtobufEnum for TensorProto_DataLocation {
+    fn value(&self) -> i32 {
+        *self as i32
+    }
+
+    fn from_i32(value: i32) -> ::std::option::Option<TensorProto_DataLocation> {
+        match value {
+            0 => ::std::option::Option::Some(TensorProto_DataLocation::DEFAULT),
+            1 => ::std::option::Option::Some(TensorProto_DataLocation::EXTERNAL),
+            _ => ::std::option::Option::None
+        }
+    }
+
+    fn values() -> &'static [Self] {
+        static values: &'static [TensorProto_DataLocation] = &[
+            TensorProto_DataLocation::DEFAULT,
+            TensorProto_DataLocation::EXTERNAL,
+        ];
+        values
+    }
+
+    fn enum_descriptor_static() -> &'static ::protobuf::reflect::EnumDescriptor {
+        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::EnumDescriptor> = ::protobuf::lazy::Lazy {
+            lock: ::protobuf::lazy::ONCE_INIT,
+            ptr: 0 as *const ::protobuf::reflect::EnumDescriptor,
+        };
+        unsafe {
+            descriptor.get(|| {
+                ::protobuf::reflect::EnumDescriptor::new("TensorProto_DataLocation", file_descriptor_proto())
+            })
+        }
+    }
+}
+
+impl ::std::marker::Copy for TensorProto_DataLocation {
+}
+
+impl ::protobuf::reflect::ProtobufValue for TensorProto_DataLocation {
+    fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
+        ::protobuf::reflect::ProtobufValueRef::Enum(self.descriptor())
+    }
+}
+
 #[derive(PartialEq,Clone,Default)]
 pub struct TensorShapeProto {
     // message fields
@@ -5096,7 +5229,7 @@ static file_descriptor_proto_data: &'static [u8] = b"\
     er\x12\x1d\n\ndoc_string\x18\n\x20\x01(\tR\tdocString\x12*\n\x05input\
     \x18\x0b\x20\x03(\x0b2\x14.onnx.ValueInfoProtoR\x05input\x12,\n\x06outpu\
     t\x18\x0c\x20\x03(\x0b2\x14.onnx.ValueInfoProtoR\x06output\x123\n\nvalue\
-    _info\x18\r\x20\x03(\x0b2\x14.onnx.ValueInfoProtoR\tvalueInfo\"\xb3\x05\
+    _info\x18\r\x20\x03(\x0b2\x14.onnx.ValueInfoProtoR\tvalueInfo\"\xe6\x06\
     \n\x0bTensorProto\x12\x12\n\x04dims\x18\x01\x20\x03(\x03R\x04dims\x127\n\
     \tdata_type\x18\x02\x20\x01(\x0e2\x1a.onnx.TensorProto.DataTypeR\x08data\
     Type\x123\n\x07segment\x18\x03\x20\x01(\x0b2\x19.onnx.TensorProto.Segmen\
@@ -5105,31 +5238,34 @@ static file_descriptor_proto_data: &'static [u8] = b"\
     \x01\x12\x1f\n\x0bstring_data\x18\x06\x20\x03(\x0cR\nstringData\x12!\n\n\
     int64_data\x18\x07\x20\x03(\x03R\tint64DataB\x02\x10\x01\x12\x12\n\x04na\
     me\x18\x08\x20\x01(\tR\x04name\x12\x1d\n\ndoc_string\x18\x0c\x20\x01(\tR\
-    \tdocString\x12\x19\n\x08raw_data\x18\t\x20\x01(\x0cR\x07rawData\x12#\n\
-    \x0bdouble_data\x18\n\x20\x03(\x01R\ndoubleDataB\x02\x10\x01\x12#\n\x0bu\
-    int64_data\x18\x0b\x20\x03(\x04R\nuint64DataB\x02\x10\x01\x1a1\n\x07Segm\
-    ent\x12\x14\n\x05begin\x18\x01\x20\x01(\x03R\x05begin\x12\x10\n\x03end\
-    \x18\x02\x20\x01(\x03R\x03end\"\xcc\x01\n\x08DataType\x12\r\n\tUNDEFINED\
-    \x10\0\x12\t\n\x05FLOAT\x10\x01\x12\t\n\x05UINT8\x10\x02\x12\x08\n\x04IN\
-    T8\x10\x03\x12\n\n\x06UINT16\x10\x04\x12\t\n\x05INT16\x10\x05\x12\t\n\
-    \x05INT32\x10\x06\x12\t\n\x05INT64\x10\x07\x12\n\n\x06STRING\x10\x08\x12\
-    \x08\n\x04BOOL\x10\t\x12\x0b\n\x07FLOAT16\x10\n\x12\n\n\x06DOUBLE\x10\
-    \x0b\x12\n\n\x06UINT32\x10\x0c\x12\n\n\x06UINT64\x10\r\x12\r\n\tCOMPLEX6\
-    4\x10\x0e\x12\x0e\n\nCOMPLEX128\x10\x0f\"\xba\x01\n\x10TensorShapeProto\
-    \x122\n\x03dim\x18\x01\x20\x03(\x0b2\x20.onnx.TensorShapeProto.Dimension\
-    R\x03dim\x1ar\n\tDimension\x12\x1d\n\tdim_value\x18\x01\x20\x01(\x03H\0R\
-    \x08dimValue\x12\x1d\n\tdim_param\x18\x02\x20\x01(\tH\0R\x08dimParam\x12\
-    \x1e\n\ndenotation\x18\x03\x20\x01(\tR\ndenotationB\x07\n\x05value\"\xe0\
-    \x01\n\tTypeProto\x129\n\x0btensor_type\x18\x01\x20\x01(\x0b2\x16.onnx.T\
-    ypeProto.TensorH\0R\ntensorType\x12\x1e\n\ndenotation\x18\x06\x20\x01(\t\
-    R\ndenotation\x1ao\n\x06Tensor\x127\n\telem_type\x18\x01\x20\x01(\x0e2\
-    \x1a.onnx.TensorProto.DataTypeR\x08elemType\x12,\n\x05shape\x18\x02\x20\
-    \x01(\x0b2\x16.onnx.TensorShapeProtoR\x05shapeB\x07\n\x05value\"F\n\x12O\
-    peratorSetIdProto\x12\x16\n\x06domain\x18\x01\x20\x01(\tR\x06domain\x12\
-    \x18\n\x07version\x18\x02\x20\x01(\x03R\x07version*c\n\x07Version\x12\
-    \x12\n\x0e_START_VERSION\x10\0\x12\x19\n\x15IR_VERSION_2017_10_10\x10\
-    \x01\x12\x19\n\x15IR_VERSION_2017_10_30\x10\x02\x12\x0e\n\nIR_VERSION\
-    \x10\x03\
+    \tdocString\x12\x19\n\x08raw_data\x18\t\x20\x01(\x0cR\x07rawData\x12A\n\
+    \rexternal_data\x18\r\x20\x03(\x0b2\x1c.onnx.StringStringEntryProtoR\x0c\
+    externalData\x12C\n\rdata_location\x18\x0e\x20\x01(\x0e2\x1e.onnx.Tensor\
+    Proto.DataLocationR\x0cdataLocation\x12#\n\x0bdouble_data\x18\n\x20\x03(\
+    \x01R\ndoubleDataB\x02\x10\x01\x12#\n\x0buint64_data\x18\x0b\x20\x03(\
+    \x04R\nuint64DataB\x02\x10\x01\x1a1\n\x07Segment\x12\x14\n\x05begin\x18\
+    \x01\x20\x01(\x03R\x05begin\x12\x10\n\x03end\x18\x02\x20\x01(\x03R\x03en\
+    d\"\xcc\x01\n\x08DataType\x12\r\n\tUNDEFINED\x10\0\x12\t\n\x05FLOAT\x10\
+    \x01\x12\t\n\x05UINT8\x10\x02\x12\x08\n\x04INT8\x10\x03\x12\n\n\x06UINT1\
+    6\x10\x04\x12\t\n\x05INT16\x10\x05\x12\t\n\x05INT32\x10\x06\x12\t\n\x05I\
+    NT64\x10\x07\x12\n\n\x06STRING\x10\x08\x12\x08\n\x04BOOL\x10\t\x12\x0b\n\
+    \x07FLOAT16\x10\n\x12\n\n\x06DOUBLE\x10\x0b\x12\n\n\x06UINT32\x10\x0c\
+    \x12\n\n\x06UINT64\x10\r\x12\r\n\tCOMPLEX64\x10\x0e\x12\x0e\n\nCOMPLEX12\
+    8\x10\x0f\")\n\x0cDataLocation\x12\x0b\n\x07DEFAULT\x10\0\x12\x0c\n\x08E\
+    XTERNAL\x10\x01\"\xba\x01\n\x10TensorShapeProto\x122\n\x03dim\x18\x01\
+    \x20\x03(\x0b2\x20.onnx.TensorShapeProto.DimensionR\x03dim\x1ar\n\tDimen\
+    sion\x12\x1d\n\tdim_value\x18\x01\x20\x01(\x03H\0R\x08dimValue\x12\x1d\n\
+    \tdim_param\x18\x02\x20\x01(\tH\0R\x08dimParam\x12\x1e\n\ndenotation\x18\
+    \x03\x20\x01(\tR\ndenotationB\x07\n\x05value\"\xe0\x01\n\tTypeProto\x129\
+    \n\x0btensor_type\x18\x01\x20\x01(\x0b2\x16.onnx.TypeProto.TensorH\0R\nt\
+    ensorType\x12\x1e\n\ndenotation\x18\x06\x20\x01(\tR\ndenotation\x1ao\n\
+    \x06Tensor\x127\n\telem_type\x18\x01\x20\x01(\x0e2\x1a.onnx.TensorProto.\
+    DataTypeR\x08elemType\x12,\n\x05shape\x18\x02\x20\x01(\x0b2\x16.onnx.Ten\
+    sorShapeProtoR\x05shapeB\x07\n\x05value\"F\n\x12OperatorSetIdProto\x12\
+    \x16\n\x06domain\x18\x01\x20\x01(\tR\x06domain\x12\x18\n\x07version\x18\
+    \x02\x20\x01(\x03R\x07version*c\n\x07Version\x12\x12\n\x0e_START_VERSION\
+    \x10\0\x12\x19\n\x15IR_VERSION_2017_10_10\x10\x01\x12\x19\n\x15IR_VERSIO\
+    N_2017_10_30\x10\x02\x12\x0e\n\nIR_VERSION\x10\x03\
 ";
 
 static mut file_descriptor_proto_lazy: ::protobuf::lazy::Lazy<::protobuf::descriptor::FileDescriptorProto> = ::protobuf::lazy::Lazy {
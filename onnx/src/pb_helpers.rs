@@ -1,10 +1,12 @@
 use crate::pb::*;
+use crate::tensor::load_tensor;
 use tract_core::internal::*;
 
 use num_traits::{AsPrimitive, Bounded};
 
 use std::borrow::Cow;
 use std::fmt::{self, Debug, Display};
+use std::path::Path;
 use std::str;
 
 use std::convert::TryInto;
@@ -352,6 +354,22 @@ impl NodeProto {
         self.expect_ok_or_else(self.get_attr_opt(name)?, || format!("attribute '{}'", name))
     }
 
+    /// Tensor attribute, resolving external data relative to `model_dir`.
+    pub fn get_attr_opt_tensor(
+        &self,
+        name: &str,
+        model_dir: Option<&Path>,
+    ) -> TractResult<Option<Tensor>> {
+        self.get_attr_opt_with_type(name, AttributeProto_AttributeType::TENSOR)?
+            .and_try(|attr| load_tensor(attr.get_t(), model_dir))
+    }
+
+    pub fn get_attr_tensor(&self, name: &str, model_dir: Option<&Path>) -> TractResult<Tensor> {
+        self.expect_ok_or_else(self.get_attr_opt_tensor(name, model_dir)?, || {
+            format!("attribute '{}'", name)
+        })
+    }
+
     pub fn check_value<T, V: Debug>(&self, attr: &str, value: Result<T, V>) -> TractResult<T> {
         match value {
             Ok(value) => Ok(value),
@@ -1,5 +1,6 @@
 use crate::pb::*;
 use std::convert::{TryFrom, TryInto};
+use std::path::{Component, Path};
 use tract_core::internal::*;
 use tract_core::*;
 
@@ -22,21 +23,27 @@ impl TryFrom<TensorProto_DataType> for DatumType {
             _ => Err(format!("Unknown DatumType {:?}", t))?,
         }
     }
+}
 
-    /*
-    fn to_onnx(&self) -> TractResult<TensorProto_DataType> {
+impl TryFrom<DatumType> for TensorProto_DataType {
+    type Error = TractError;
+    fn try_from(t: DatumType) -> TractResult<TensorProto_DataType> {
         use self::TensorProto_DataType::*;
-        match self {
+        match t {
+            DatumType::Bool => Ok(BOOL),
             DatumType::U8 => Ok(UINT8),
+            DatumType::U16 => Ok(UINT16),
             DatumType::I8 => Ok(INT8),
+            DatumType::I16 => Ok(INT16),
             DatumType::I32 => Ok(INT32),
+            DatumType::I64 => Ok(INT64),
+            DatumType::F16 => Ok(FLOAT16),
             DatumType::F32 => Ok(FLOAT),
             DatumType::F64 => Ok(DOUBLE),
             DatumType::String => Ok(STRING),
             DatumType::TDim => bail!("Dimension is not translatable in protobuf"),
         }
     }
-    */
 }
 
 impl<'a> TryFrom<&'a TypeProto_Tensor> for TensorFact {
@@ -72,84 +79,175 @@ impl TryFrom<TypeProto_Tensor> for TensorFact {
     }
 }
 
+impl<'a> TryFrom<&'a TensorFact> for TypeProto_Tensor {
+    type Error = TractError;
+    fn try_from(fact: &'a TensorFact) -> TractResult<TypeProto_Tensor> {
+        let mut t = TypeProto_Tensor::new();
+        if let Some(dt) = fact.datum_type.concretize() {
+            t.set_elem_type(dt.try_into()?);
+        }
+        if let Some(dims) = fact.shape.concretize() {
+            let mut shape = TensorShapeProto::new();
+            for d in dims {
+                let mut dim = TensorShapeProto_Dimension::new();
+                if let Ok(d) = d.to_integer() {
+                    dim.set_dim_value(d as i64);
+                } else {
+                    dim.set_dim_param(format!("{}", d));
+                }
+                shape.mut_dim().push(dim);
+            }
+            t.set_shape(shape);
+        }
+        Ok(t)
+    }
+}
+
 impl<'a> TryFrom<&'a TensorProto> for Tensor {
     type Error = TractError;
     fn try_from(t: &TensorProto) -> TractResult<Tensor> {
-        let dt = t.get_data_type().try_into()?;
-        let shape: Vec<usize> = t.get_dims().iter().map(|&i| i as usize).collect();
-        if t.has_raw_data() {
-            unsafe {
-                match dt {
-                    DatumType::U8 => Tensor::from_raw::<u8>(&*shape, t.get_raw_data()),
-                    DatumType::U16 => Tensor::from_raw::<u16>(&*shape, t.get_raw_data()),
-                    DatumType::I8 => Tensor::from_raw::<i8>(&*shape, t.get_raw_data()),
-                    DatumType::I16 => Tensor::from_raw::<i16>(&*shape, t.get_raw_data()),
-                    DatumType::I32 => Tensor::from_raw::<i32>(&*shape, t.get_raw_data()),
-                    DatumType::I64 => Tensor::from_raw::<i64>(&*shape, t.get_raw_data()),
-                    DatumType::F16 => Tensor::from_raw::<f16>(&*shape, t.get_raw_data()),
-                    DatumType::F32 => Tensor::from_raw::<f32>(&*shape, t.get_raw_data()),
-                    DatumType::F64 => Tensor::from_raw::<f64>(&*shape, t.get_raw_data()),
-                    DatumType::Bool => Ok(Tensor::from_raw::<u8>(&*shape, t.get_raw_data())?
-                        .into_array::<u8>()?
-                        .mapv(|x| x != 0)
-                        .into()),
-                    _ => unimplemented!("FIXME, raw tensor loading"),
-                }
+        load_tensor(t, None)
+    }
+}
+
+/// Decode a tensor, resolving external data relative to `model_dir`.
+pub fn load_tensor(t: &TensorProto, model_dir: Option<&Path>) -> TractResult<Tensor> {
+    let dt = t.get_data_type().try_into()?;
+    let shape: Vec<usize> = t.get_dims().iter().map(|&i| i as usize).collect();
+    if t.get_data_location() == TensorProto_DataLocation::EXTERNAL {
+        let model_dir = model_dir.ok_or_else(|| {
+            format!("Tensor {} uses external data, but the model path is unknown", t.get_name())
+        })?;
+        load_external_data(t, dt, &shape, model_dir)
+    } else if t.has_raw_data() {
+        from_raw_data(dt, &shape, t.get_raw_data())
+    } else {
+        use ndarray::Array;
+        let it = match dt {
+            DatumType::Bool => Array::from_shape_vec(
+                &*shape,
+                t.get_int32_data().iter().map(|&x| x != 0).collect(),
+            )?
+            .into(),
+            DatumType::U8 => Array::from_shape_vec(
+                &*shape,
+                t.get_int32_data().iter().map(|&x| x as u8).collect(),
+            )?
+            .into(),
+            DatumType::U16 => Array::from_shape_vec(
+                &*shape,
+                t.get_int32_data().iter().map(|&x| x as u16).collect(),
+            )?
+            .into(),
+            DatumType::I8 => Array::from_shape_vec(
+                &*shape,
+                t.get_int32_data().iter().map(|&x| x as i8).collect(),
+            )?
+            .into(),
+            DatumType::I16 => Array::from_shape_vec(
+                &*shape,
+                t.get_int32_data().iter().map(|&x| x as i16).collect(),
+            )?
+            .into(),
+            DatumType::I32 => Array::from_shape_vec(&*shape, t.get_int32_data().to_vec())?.into(),
+            DatumType::I64 => Array::from_shape_vec(&*shape, t.get_int64_data().to_vec())?.into(),
+            // half floats are stored as their bit pattern
+            DatumType::F16 => Array::from_shape_vec(
+                &*shape,
+                t.get_int32_data().iter().map(|&x| f16(half::f16::from_bits(x as u16))).collect(),
+            )?
+            .into(),
+            DatumType::F32 => Array::from_shape_vec(&*shape, t.get_float_data().to_vec())?.into(),
+            DatumType::F64 => Array::from_shape_vec(&*shape, t.get_double_data().to_vec())?.into(),
+            DatumType::String => {
+                let strings = t
+                    .get_string_data()
+                    .iter()
+                    .cloned()
+                    .map(String::from_utf8)
+                    .collect::<Result<Vec<String>, _>>()
+                    .map_err(|_| format!("Invalid UTF8 buffer"))?;
+                Array::from_shape_vec(&*shape, strings)?.into()
             }
-        } else {
-            use ndarray::Array;
-            let it = match dt {
-                DatumType::Bool => Array::from_shape_vec(
-                    &*shape,
-                    t.get_int32_data().iter().map(|&x| x != 0).collect(),
-                )?
-                .into(),
-                DatumType::U8 => Array::from_shape_vec(
-                    &*shape,
-                    t.get_int32_data().iter().map(|&x| x as u8).collect(),
-                )?
-                .into(),
-                DatumType::U16 => Array::from_shape_vec(
-                    &*shape,
-                    t.get_int32_data().iter().map(|&x| x as u16).collect(),
-                )?
-                .into(),
-                DatumType::I8 => Array::from_shape_vec(
-                    &*shape,
-                    t.get_int32_data().iter().map(|&x| x as i8).collect(),
-                )?
-                .into(),
-                DatumType::I16 => Array::from_shape_vec(
-                    &*shape,
-                    t.get_int32_data().iter().map(|&x| x as i16).collect(),
-                )?
-                .into(),
-                DatumType::I32 => {
-                    Array::from_shape_vec(&*shape, t.get_int32_data().to_vec())?.into()
-                }
-                DatumType::I64 => {
-                    Array::from_shape_vec(&*shape, t.get_int64_data().to_vec())?.into()
-                }
-                DatumType::F32 => {
-                    Array::from_shape_vec(&*shape, t.get_float_data().to_vec())?.into()
-                }
-                DatumType::F64 => {
-                    Array::from_shape_vec(&*shape, t.get_double_data().to_vec())?.into()
-                }
-                DatumType::String => {
-                    let strings = t
-                        .get_string_data()
-                        .iter()
-                        .cloned()
-                        .map(String::from_utf8)
-                        .collect::<Result<Vec<String>, _>>()
-                        .map_err(|_| format!("Invalid UTF8 buffer"))?;
-                    Array::from_shape_vec(&*shape, strings)?.into()
-                }
-                _ => unimplemented!("FIXME, struct tensor loading"),
-            };
-            Ok(it)
+            DatumType::TDim => bail!("Dimension is not translatable in protobuf"),
+        };
+        Ok(it)
+    }
+}
+
+fn from_raw_data(dt: DatumType, shape: &[usize], data: &[u8]) -> TractResult<Tensor> {
+    let len = shape.iter().product::<usize>() * dt.size_of();
+    if data.len() < len {
+        bail!(
+            "Expected {} bytes for a {:?} tensor of shape {:?}, got {}",
+            len,
+            dt,
+            shape,
+            data.len()
+        )
+    }
+    unsafe {
+        match dt {
+            DatumType::Bool => {
+                Ok(Tensor::from_raw::<u8>(shape, data)?.into_array::<u8>()?.mapv(|x| x != 0).into())
+            }
+            DatumType::String => bail!("String tensors can not be stored as raw data"),
+            DatumType::TDim => bail!("Dimension is not translatable in protobuf"),
+            _ => Tensor::from_raw_dt(dt, shape, data),
+        }
+    }
+}
+
+fn load_external_data(
+    t: &TensorProto,
+    dt: DatumType,
+    shape: &[usize],
+    model_dir: &Path,
+) -> TractResult<Tensor> {
+    let mut location = None;
+    let mut offset = 0usize;
+    let mut length = None;
+    for entry in t.get_external_data() {
+        match entry.get_key() {
+            "location" => location = Some(entry.get_value()),
+            "offset" => offset = entry.get_value().parse()?,
+            "length" => length = Some(entry.get_value().parse::<usize>()?),
+            _ => (),
+        }
+    }
+    let location =
+        location.ok_or_else(|| format!("Tensor {} has no external data location", t.get_name()))?;
+    // the location must stay inside the model directory
+    if Path::new(location).components().any(|c| match c {
+        Component::Normal(_) | Component::CurDir => false,
+        _ => true,
+    }) {
+        bail!(
+            "External data location {:?} of {} must be relative to the model directory",
+            location,
+            t.get_name()
+        )
+    }
+    let path = model_dir.join(location);
+    let mut file =
+        std::fs::File::open(&path).map_err(|e| format!("Could not open {:?}: {}", path, e))?;
+    let length = if let Some(length) = length {
+        length
+    } else {
+        (file.metadata()?.len() as usize).saturating_sub(offset)
+    };
+    // mapping fails on empty files and on some filesystems: fallback to reading
+    if let Ok(mmap) = unsafe { memmap::Mmap::map(&file) } {
+        if offset.checked_add(length).map(|end| end > mmap.len()).unwrap_or(true) {
+            bail!("External data for {} is out of {:?} bounds", t.get_name(), path)
         }
+        from_raw_data(dt, shape, &mmap[offset..][..length])
+    } else {
+        use std::io::{Read, Seek, SeekFrom};
+        let mut data = vec![0u8; length];
+        file.seek(SeekFrom::Start(offset as u64))?;
+        file.read_exact(&mut data)?;
+        from_raw_data(dt, shape, &data)
     }
 }
 
@@ -160,6 +258,32 @@ impl TryFrom<TensorProto> for Tensor {
     }
 }
 
+impl<'a> TryFrom<&'a Tensor> for TensorProto {
+    type Error = TractError;
+    fn try_from(t: &'a Tensor) -> TractResult<TensorProto> {
+        let mut proto = TensorProto::new();
+        proto.set_dims(t.shape().iter().map(|&d| d as i64).collect());
+        match t.datum_type() {
+            DatumType::String => {
+                proto.set_data_type(TensorProto_DataType::STRING);
+                let strings: Vec<Vec<u8>> =
+                    t.as_slice::<String>()?.iter().map(|s| s.as_bytes().to_vec()).collect();
+                proto.set_string_data(strings.into());
+            }
+            DatumType::Bool => {
+                proto.set_data_type(TensorProto_DataType::BOOL);
+                proto.set_raw_data(t.as_slice::<bool>()?.iter().map(|&b| b as u8).collect());
+            }
+            DatumType::TDim => return (&*t.cast_to::<i64>()?).try_into(),
+            dt => {
+                proto.set_data_type(dt.try_into()?);
+                proto.set_raw_data(t.as_bytes().to_vec());
+            }
+        }
+        Ok(proto)
+    }
+}
+
 pub fn proto_from_reader<R: ::std::io::Read>(mut r: R) -> TractResult<TensorProto> {
     protobuf::parse_from_reader(&mut r)
         .map_err(|e| format!("Can not parse protobuf input: {:?}", e).into())
@@ -168,3 +292,119 @@ pub fn proto_from_reader<R: ::std::io::Read>(mut r: R) -> TractResult<TensorProt
 pub fn from_reader<R: ::std::io::Read>(r: R) -> TractResult<Tensor> {
     proto_from_reader(r)?.try_into()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw(dt: DatumType, shape: &[usize], data: &[u8]) -> Tensor {
+        from_raw_data(dt, shape, data).unwrap()
+    }
+
+    #[test]
+    fn raw_data_per_datum_type() {
+        assert_eq!(raw(DatumType::Bool, &[3], &[1, 0, 2]), tensor1(&[true, false, true]));
+        assert_eq!(raw(DatumType::U8, &[2], &[1, 255]), tensor1(&[1u8, 255]));
+        assert_eq!(raw(DatumType::I8, &[2], &[1, 255]), tensor1(&[1i8, -1]));
+        assert_eq!(raw(DatumType::U16, &[1], &[1, 2]), tensor1(&[0x0201u16]));
+        assert_eq!(raw(DatumType::I16, &[2], &[254, 255, 3, 0]), tensor1(&[-2i16, 3]));
+        assert_eq!(raw(DatumType::I32, &[1], &[254, 255, 255, 255]), tensor1(&[-2i32]));
+        assert_eq!(raw(DatumType::I64, &[1], &[3, 0, 0, 0, 0, 0, 0, 0]), tensor1(&[3i64]));
+        assert_eq!(
+            raw(DatumType::F16, &[2], &[0x00, 0x3c, 0x00, 0xc0]),
+            tensor1(&[f16(half::f16::from_f32(1.0)), f16(half::f16::from_f32(-2.0))])
+        );
+        assert_eq!(
+            raw(DatumType::F32, &[1, 1], &1.5f32.to_bits().to_le_bytes()),
+            tensor2(&[[1.5f32]])
+        );
+        assert_eq!(
+            raw(DatumType::F64, &[], &(-0.25f64).to_bits().to_le_bytes()),
+            tensor0(-0.25f64)
+        );
+        assert!(from_raw_data(DatumType::I32, &[2], &[0; 7]).is_err());
+        assert!(from_raw_data(DatumType::String, &[1], &[0]).is_err());
+    }
+
+    fn external(location: &str, offset: Option<usize>, length: Option<usize>) -> TensorProto {
+        let mut t = TensorProto::new();
+        t.set_name("weights".to_string());
+        t.set_data_type(TensorProto_DataType::FLOAT);
+        t.set_dims(vec![3]);
+        t.set_data_location(TensorProto_DataLocation::EXTERNAL);
+        let mut entries = vec![("location", location.to_string())];
+        entries.extend(offset.map(|o| ("offset", o.to_string())));
+        entries.extend(length.map(|l| ("length", l.to_string())));
+        for (key, value) in entries {
+            let mut entry = StringStringEntryProto::new();
+            entry.set_key(key.to_string());
+            entry.set_value(value);
+            t.mut_external_data().push(entry);
+        }
+        t
+    }
+
+    fn model_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "tract-onnx-external-{}-{}",
+            name,
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut data = vec![0xffu8; 4];
+        for x in &[1f32, 2.0, 3.0, 4.0] {
+            data.extend_from_slice(&x.to_bits().to_le_bytes());
+        }
+        std::fs::write(dir.join("weights.bin"), &data).unwrap();
+        dir
+    }
+
+    #[test]
+    fn external_data() {
+        let dir = model_dir("data");
+        let t = load_tensor(&external("weights.bin", Some(4), Some(12)), Some(&dir)).unwrap();
+        assert_eq!(t, tensor1(&[1f32, 2.0, 3.0]));
+        let t = load_tensor(&external("./weights.bin", Some(8), None), Some(&dir)).unwrap();
+        assert_eq!(t, tensor1(&[2f32, 3.0, 4.0]));
+        assert!(load_tensor(&external("weights.bin", Some(12), None), Some(&dir)).is_err());
+        assert!(load_tensor(&external("weights.bin", Some(8), Some(16)), Some(&dir)).is_err());
+        assert!(load_tensor(&external("weights.bin", None, None), None).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn external_data_in_constant_attribute() {
+        use protobuf::Message;
+        let dir = model_dir("constant");
+        let mut value = AttributeProto::new();
+        value.set_name("value".to_string());
+        value.set_field_type(AttributeProto_AttributeType::TENSOR);
+        value.set_t(external("weights.bin", Some(4), Some(12)));
+        let mut node = NodeProto::new();
+        node.set_op_type("Constant".to_string());
+        node.set_name("konst".to_string());
+        node.mut_output().push("konst".to_string());
+        node.mut_attribute().push(value);
+        let mut output = ValueInfoProto::new();
+        output.set_name("konst".to_string());
+        let mut model = ModelProto::new();
+        model.mut_graph().mut_node().push(node);
+        model.mut_graph().mut_output().push(output);
+        std::fs::write(dir.join("model.onnx"), model.write_to_bytes().unwrap()).unwrap();
+        let model = crate::onnx().model_for_path(dir.join("model.onnx")).unwrap();
+        let outputs = SimplePlan::new(&model).unwrap().run(tvec!()).unwrap();
+        assert_eq!(outputs[0], rctensor1(&[1f32, 2.0, 3.0]));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn external_data_outside_model_dir() {
+        let dir = model_dir("outside");
+        let absolute = dir.join("weights.bin");
+        let absolute = absolute.to_str().unwrap();
+        assert!(load_tensor(&external(absolute, Some(4), Some(12)), Some(&dir)).is_err());
+        let parent = format!("../{}/weights.bin", dir.file_name().unwrap().to_str().unwrap());
+        assert!(load_tensor(&external(&parent, Some(4), Some(12)), Some(&dir)).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
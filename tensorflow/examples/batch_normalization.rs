@@ -0,0 +1,70 @@
+//! Writes tests/models/batch_normalization.pb, a layer normalization frozen
+//! from TensorFlow 1.x, node for node:
+//!
+//! ```python
+//! x = tf.placeholder(tf.float32, [2, 3, 4], name="x")
+//! mean, variance = tf.nn.moments(x, axes=[2], keep_dims=True)
+//! gamma = tf.Variable([1.0, 2.0, 0.5, -1.0], name="gamma")
+//! beta = tf.Variable([0.0, 0.5, -0.5, 1.0], name="beta")
+//! y = tf.nn.batch_normalization(x, mean, variance, beta, gamma, 1e-3)
+//! # then graph_util.convert_variables_to_constants(sess, graph_def, [y.op.name])
+//! ```
+extern crate tract_core;
+extern crate tract_tensorflow;
+use std::convert::TryFrom;
+use tract_core::prelude::*;
+use tract_tensorflow::tfpb;
+use tract_tensorflow::tfpb::node_def::NodeDef;
+use tract_tensorflow::tfpb::tensor::TensorProto;
+use tract_tensorflow::tfpb::types::DataType::{DT_FLOAT, DT_INT32};
+
+fn konst(name: &str, value: Tensor) -> NodeDef {
+    let dt = if value.datum_type() == i32::datum_type() { DT_INT32 } else { DT_FLOAT };
+    tfpb::node()
+        .op("Const")
+        .name(name)
+        .attr("dtype", dt)
+        .attr("value", TensorProto::try_from(&value).unwrap())
+}
+
+fn op(op: &str, name: &str, inputs: &[&str]) -> NodeDef {
+    inputs.iter().fold(tfpb::node().op(op).name(name).attr("T", DT_FLOAT), |n, i| n.input(*i))
+}
+
+fn mean(name: &str, input: &str) -> Vec<NodeDef> {
+    let axes = format!("{}/reduction_indices", name);
+    vec![
+        konst(&axes, tensor1(&[2i32])),
+        op("Mean", name, &[input, &axes]).attr("Tidx", DT_INT32).attr("keep_dims", true),
+    ]
+}
+
+fn main() {
+    let mut nodes = vec![
+        tfpb::node().op("Placeholder").name("x").attr("dtype", DT_FLOAT),
+        konst("gamma", tensor1(&[1.0f32, 2.0, 0.5, -1.0])),
+        op("Identity", "gamma/read", &["gamma"]),
+        konst("beta", tensor1(&[0.0f32, 0.5, -0.5, 1.0])),
+        op("Identity", "beta/read", &["beta"]),
+    ];
+    nodes.extend(mean("moments/mean", "x"));
+    nodes.push(op("StopGradient", "moments/StopGradient", &["moments/mean"]));
+    nodes.push(op(
+        "SquaredDifference",
+        "moments/SquaredDifference",
+        &["x", "moments/StopGradient"],
+    ));
+    nodes.extend(mean("moments/variance", "moments/SquaredDifference"));
+    nodes.extend(vec![
+        konst("batchnorm/add/y", tensor0(1e-3f32)),
+        op("Add", "batchnorm/add", &["moments/variance", "batchnorm/add/y"]),
+        op("Rsqrt", "batchnorm/Rsqrt", &["batchnorm/add"]),
+        op("Mul", "batchnorm/mul", &["batchnorm/Rsqrt", "gamma/read"]),
+        op("Mul", "batchnorm/mul_1", &["x", "batchnorm/mul"]),
+        op("Mul", "batchnorm/mul_2", &["moments/mean", "batchnorm/mul"]),
+        op("Sub", "batchnorm/sub", &["beta/read", "batchnorm/mul_2"]),
+        op("Add", "batchnorm/add_1", &["batchnorm/mul_1", "batchnorm/sub"]),
+    ]);
+    let graph = nodes.into_iter().fold(tfpb::graph(), |graph, node| graph.node(node));
+    graph.save_to("tests/models/batch_normalization.pb").unwrap();
+}
@@ -0,0 +1,284 @@
+//! Writes tests/models/dynamic_rnn.pb, a basic RNN frozen from TensorFlow
+//! 1.x, node for node:
+//!
+//! ```python
+//! x = tf.placeholder(tf.float32, [3, 2, 4], name="x")
+//! cell = tf.nn.rnn_cell.BasicRNNCell(5)
+//! outputs, state = tf.nn.dynamic_rnn(cell, x, dtype=tf.float32, time_major=True)
+//! # then graph_util.convert_variables_to_constants(sess, graph_def,
+//! #     [outputs.op.name, state.op.name])
+//! ```
+//!
+//! dynamic_rnn gives `maximum_iterations` to its while_loop, so the loop
+//! condition is `iteration_counter < time_steps && time < loop_bound`.
+extern crate ndarray;
+extern crate tract_core;
+extern crate tract_tensorflow;
+use std::convert::TryFrom;
+use tract_core::prelude::*;
+use tract_tensorflow::tfpb;
+use tract_tensorflow::tfpb::node_def::NodeDef;
+use tract_tensorflow::tfpb::tensor::TensorProto;
+use tract_tensorflow::tfpb::types::DataType;
+use tract_tensorflow::tfpb::types::DataType::{DT_FLOAT, DT_INT32, DT_RESOURCE};
+
+const FRAME: &str = "rnn/while/while_context";
+
+fn konst(name: &str, value: Tensor) -> NodeDef {
+    let dt = if value.datum_type() == i32::datum_type() { DT_INT32 } else { DT_FLOAT };
+    tfpb::node()
+        .op("Const")
+        .name(name)
+        .attr("dtype", dt)
+        .attr("value", TensorProto::try_from(&value).unwrap())
+}
+
+// constants of the loop body are anchored to the frame by a control input
+fn body_konst(name: &str, value: Tensor) -> NodeDef {
+    konst(name, value).input("^rnn/while/Identity")
+}
+
+fn op(op: &str, name: &str, inputs: &[&str], dt: DataType) -> NodeDef {
+    inputs.iter().fold(tfpb::node().op(op).name(name).attr("T", dt), |n, i| n.input(*i))
+}
+
+fn enter(name: &str, input: &str, dt: DataType, is_constant: bool) -> NodeDef {
+    op("Enter", name, &[input], dt)
+        .attr("frame_name", FRAME)
+        .attr("is_constant", is_constant)
+        .attr("parallel_iterations", 32i64)
+}
+
+fn tensor_array(op: &str, name: &str, inputs: &[&str]) -> NodeDef {
+    inputs.iter().fold(tfpb::node().op(op).name(name).attr("dtype", DT_FLOAT), |n, i| n.input(*i))
+}
+
+// time_steps, the first dimension of x
+fn time_steps(scope: &str) -> Vec<NodeDef> {
+    let name = |n: &str| format!("{}{}", scope, n);
+    vec![
+        tfpb::node().op("Shape").name(name("Shape")).input("x").attr("out_type", DT_INT32),
+        konst(&name("strided_slice/stack"), tensor1(&[0i32])),
+        konst(&name("strided_slice/stack_1"), tensor1(&[1i32])),
+        konst(&name("strided_slice/stack_2"), tensor1(&[1i32])),
+        op(
+            "StridedSlice",
+            &name("strided_slice"),
+            &[
+                &name("Shape"),
+                &name("strided_slice/stack"),
+                &name("strided_slice/stack_1"),
+                &name("strided_slice/stack_2"),
+            ],
+            DT_INT32,
+        )
+        .attr("Index", DT_INT32)
+        .attr("shrink_axis_mask", 1i64),
+    ]
+}
+
+fn range(name: &str, limit: &str) -> Vec<NodeDef> {
+    let start = format!("{}/start", name);
+    let delta = format!("{}/delta", name);
+    vec![
+        konst(&start, tensor0(0i32)),
+        konst(&delta, tensor0(1i32)),
+        tfpb::node()
+            .op("Range")
+            .name(name)
+            .input(&start)
+            .input(limit)
+            .input(&delta)
+            .attr("Tidx", DT_INT32),
+    ]
+}
+
+fn main() {
+    let kernel =
+        ndarray::Array2::from_shape_fn((9, 5), |(i, j)| ((i * 5 + j) as f32 * 0.37).sin() * 0.5);
+    let mut nodes = vec![
+        tfpb::node().op("Placeholder").name("x").attr("dtype", DT_FLOAT),
+        konst("rnn/basic_rnn_cell/kernel", kernel.into_tensor()),
+        op("Identity", "rnn/basic_rnn_cell/kernel/read", &["rnn/basic_rnn_cell/kernel"], DT_FLOAT),
+        konst("rnn/basic_rnn_cell/bias", tensor1(&[0.1f32, -0.2, 0.3, 0.0, -0.1])),
+        op("Identity", "rnn/basic_rnn_cell/bias/read", &["rnn/basic_rnn_cell/bias"], DT_FLOAT),
+        konst("rnn/BasicRNNCellZeroState/Const", tensor1(&[2i32, 5])),
+        konst("rnn/BasicRNNCellZeroState/zeros/Const", tensor0(0f32)),
+        op(
+            "Fill",
+            "rnn/BasicRNNCellZeroState/zeros",
+            &["rnn/BasicRNNCellZeroState/Const", "rnn/BasicRNNCellZeroState/zeros/Const"],
+            DT_FLOAT,
+        )
+        .attr("index_type", DT_INT32),
+    ];
+    nodes.extend(time_steps("rnn/"));
+    nodes.extend(vec![
+        konst("rnn/time", tensor0(0i32)),
+        tensor_array("TensorArrayV3", "rnn/TensorArray", &["rnn/strided_slice"])
+            .attr("tensor_array_name", "rnn/dynamic_rnn/output_0"),
+        tensor_array("TensorArrayV3", "rnn/TensorArray_1", &["rnn/strided_slice"])
+            .attr("tensor_array_name", "rnn/dynamic_rnn/input_0"),
+    ]);
+    nodes.extend(time_steps("rnn/TensorArrayUnstack/"));
+    nodes.extend(range("rnn/TensorArrayUnstack/range", "rnn/TensorArrayUnstack/strided_slice"));
+    nodes.extend(vec![
+        op(
+            "TensorArrayScatterV3",
+            "rnn/TensorArrayUnstack/TensorArrayScatter/TensorArrayScatterV3",
+            &["rnn/TensorArray_1", "rnn/TensorArrayUnstack/range", "x", "rnn/TensorArray_1:1"],
+            DT_FLOAT,
+        ),
+        konst("rnn/Maximum/x", tensor0(1i32)),
+        op("Maximum", "rnn/Maximum", &["rnn/Maximum/x", "rnn/strided_slice"], DT_INT32),
+        op("Minimum", "rnn/Minimum", &["rnn/strided_slice", "rnn/Maximum"], DT_INT32),
+        konst("rnn/while/iteration_counter", tensor0(0i32)),
+        enter("rnn/while/Enter", "rnn/while/iteration_counter", DT_INT32, false),
+        enter("rnn/while/Enter_1", "rnn/time", DT_INT32, false),
+        enter("rnn/while/Enter_2", "rnn/TensorArray:1", DT_FLOAT, false),
+        enter("rnn/while/Enter_3", "rnn/BasicRNNCellZeroState/zeros", DT_FLOAT, false),
+    ]);
+    let types = [DT_INT32, DT_INT32, DT_FLOAT, DT_FLOAT];
+    let suffix = |i: usize| if i == 0 { String::new() } else { format!("_{}", i) };
+    for (i, &dt) in types.iter().enumerate() {
+        let enter = format!("rnn/while/Enter{}", suffix(i));
+        let next = format!("rnn/while/NextIteration{}", suffix(i));
+        nodes.push(
+            op("Merge", &format!("rnn/while/Merge{}", suffix(i)), &[&enter, &next], dt)
+                .attr("N", 2i64),
+        );
+    }
+    nodes.extend(vec![
+        enter("rnn/while/Less/Enter", "rnn/strided_slice", DT_INT32, true),
+        op("Less", "rnn/while/Less", &["rnn/while/Merge", "rnn/while/Less/Enter"], DT_INT32),
+        enter("rnn/while/Less_1/Enter", "rnn/Minimum", DT_INT32, true),
+        op("Less", "rnn/while/Less_1", &["rnn/while/Merge_1", "rnn/while/Less_1/Enter"], DT_INT32),
+        tfpb::node()
+            .op("LogicalAnd")
+            .name("rnn/while/LogicalAnd")
+            .input("rnn/while/Less")
+            .input("rnn/while/Less_1"),
+        tfpb::node().op("LoopCond").name("rnn/while/LoopCond").input("rnn/while/LogicalAnd"),
+    ]);
+    for (i, &dt) in types.iter().enumerate() {
+        let switch = format!("rnn/while/Switch{}", suffix(i));
+        let merge = format!("rnn/while/Merge{}", suffix(i));
+        nodes.push(op("Switch", &switch, &[&merge, "rnn/while/LoopCond"], dt));
+        let body = format!("{}:1", switch);
+        nodes.push(op("Identity", &format!("rnn/while/Identity{}", suffix(i)), &[&body], dt));
+    }
+    nodes.extend(vec![
+        body_konst("rnn/while/add/y", tensor0(1i32)),
+        op("Add", "rnn/while/add", &["rnn/while/Identity", "rnn/while/add/y"], DT_INT32),
+        enter("rnn/while/TensorArrayReadV3/Enter", "rnn/TensorArray_1", DT_RESOURCE, true),
+        enter(
+            "rnn/while/TensorArrayReadV3/Enter_1",
+            "rnn/TensorArrayUnstack/TensorArrayScatter/TensorArrayScatterV3",
+            DT_FLOAT,
+            true,
+        ),
+        tensor_array(
+            "TensorArrayReadV3",
+            "rnn/while/TensorArrayReadV3",
+            &[
+                "rnn/while/TensorArrayReadV3/Enter",
+                "rnn/while/Identity_1",
+                "rnn/while/TensorArrayReadV3/Enter_1",
+            ],
+        ),
+        body_konst("rnn/while/basic_rnn_cell/concat/axis", tensor0(1i32)),
+        op(
+            "ConcatV2",
+            "rnn/while/basic_rnn_cell/concat",
+            &[
+                "rnn/while/TensorArrayReadV3",
+                "rnn/while/Identity_3",
+                "rnn/while/basic_rnn_cell/concat/axis",
+            ],
+            DT_FLOAT,
+        )
+        .attr("N", 2i64)
+        .attr("Tidx", DT_INT32),
+        enter(
+            "rnn/while/basic_rnn_cell/MatMul/Enter",
+            "rnn/basic_rnn_cell/kernel/read",
+            DT_FLOAT,
+            true,
+        ),
+        op(
+            "MatMul",
+            "rnn/while/basic_rnn_cell/MatMul",
+            &["rnn/while/basic_rnn_cell/concat", "rnn/while/basic_rnn_cell/MatMul/Enter"],
+            DT_FLOAT,
+        )
+        .attr("transpose_a", false)
+        .attr("transpose_b", false),
+        enter(
+            "rnn/while/basic_rnn_cell/BiasAdd/Enter",
+            "rnn/basic_rnn_cell/bias/read",
+            DT_FLOAT,
+            true,
+        ),
+        op(
+            "BiasAdd",
+            "rnn/while/basic_rnn_cell/BiasAdd",
+            &["rnn/while/basic_rnn_cell/MatMul", "rnn/while/basic_rnn_cell/BiasAdd/Enter"],
+            DT_FLOAT,
+        )
+        .attr("data_format", "NHWC"),
+        op(
+            "Tanh",
+            "rnn/while/basic_rnn_cell/Tanh",
+            &["rnn/while/basic_rnn_cell/BiasAdd"],
+            DT_FLOAT,
+        ),
+        enter(
+            "rnn/while/TensorArrayWrite/TensorArrayWriteV3/Enter",
+            "rnn/TensorArray",
+            DT_RESOURCE,
+            true,
+        ),
+        op(
+            "TensorArrayWriteV3",
+            "rnn/while/TensorArrayWrite/TensorArrayWriteV3",
+            &[
+                "rnn/while/TensorArrayWrite/TensorArrayWriteV3/Enter",
+                "rnn/while/Identity_1",
+                "rnn/while/basic_rnn_cell/Tanh",
+                "rnn/while/Identity_2",
+            ],
+            DT_FLOAT,
+        ),
+        body_konst("rnn/while/add_1/y", tensor0(1i32)),
+        op("Add", "rnn/while/add_1", &["rnn/while/Identity_1", "rnn/while/add_1/y"], DT_INT32),
+    ]);
+    let nexts = [
+        "rnn/while/add",
+        "rnn/while/add_1",
+        "rnn/while/TensorArrayWrite/TensorArrayWriteV3",
+        "rnn/while/basic_rnn_cell/Tanh",
+    ];
+    for (i, (&dt, next)) in types.iter().zip(nexts.iter()).enumerate() {
+        let name = format!("rnn/while/NextIteration{}", suffix(i));
+        nodes.push(op("NextIteration", &name, &[next], dt));
+    }
+    for (i, &dt) in types.iter().enumerate() {
+        let switch = format!("rnn/while/Switch{}", suffix(i));
+        nodes.push(op("Exit", &format!("rnn/while/Exit{}", suffix(i)), &[&switch], dt));
+    }
+    nodes.push(
+        tfpb::node()
+            .op("TensorArraySizeV3")
+            .name("rnn/TensorArrayStack/TensorArraySizeV3")
+            .input("rnn/TensorArray")
+            .input("rnn/while/Exit_2"),
+    );
+    nodes.extend(range("rnn/TensorArrayStack/range", "rnn/TensorArrayStack/TensorArraySizeV3"));
+    nodes.push(tensor_array(
+        "TensorArrayGatherV3",
+        "rnn/TensorArrayStack/TensorArrayGatherV3",
+        &["rnn/TensorArray", "rnn/TensorArrayStack/range", "rnn/while/Exit_2"],
+    ));
+    let graph = nodes.into_iter().fold(tfpb::graph(), |graph, node| graph.node(node));
+    graph.save_to("tests/models/dynamic_rnn.pb").unwrap();
+}
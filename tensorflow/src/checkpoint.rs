@@ -0,0 +1,300 @@
+//! Reader for TensorFlow V2 checkpoints (aka tensor bundles).
+//!
+//! A checkpoint with prefix `p` is made of an index file `p.index` and data
+//! shards `p.data-00000-of-0000N`. The index is a sorted string table (same
+//! format as leveldb) mapping variable names to BundleEntryProto, with the
+//! BundleHeaderProto stored under the empty key. Tensor bytes are stored raw
+//! in the shards.
+
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use tract_core::internal::*;
+
+use crate::tfpb::tensor_bundle::{
+    BundleEntryProto, BundleHeaderProto, BundleHeaderProto_Endianness,
+};
+use crate::tfpb::types::DataType;
+
+const TABLE_MAGIC: u64 = 0xdb4775248b80fb57;
+const FOOTER_LEN: usize = 48;
+const BLOCK_TRAILER_LEN: usize = 5;
+
+pub struct Checkpoint {
+    prefix: PathBuf,
+    pub header: BundleHeaderProto,
+    pub entries: BTreeMap<String, BundleEntryProto>,
+}
+
+impl Checkpoint {
+    /// Open the checkpoint with the given prefix (the path of the index file
+    /// without its `.index` extension).
+    pub fn open(prefix: impl AsRef<Path>) -> TractResult<Checkpoint> {
+        let prefix = prefix.as_ref().to_path_buf();
+        let index_path = PathBuf::from(format!("{}.index", prefix.display()));
+        let index = std::fs::read(&index_path)
+            .map_err(|e| format!("Could not read {}: {}", index_path.display(), e))?;
+        let mut header = None;
+        let mut entries = BTreeMap::new();
+        for (key, value) in read_table(&index)? {
+            if key.is_empty() {
+                header = Some(
+                    ::protobuf::parse_from_bytes::<BundleHeaderProto>(&value)
+                        .map_err(|e| format!("{:?}", e))?,
+                );
+            } else {
+                let entry = ::protobuf::parse_from_bytes::<BundleEntryProto>(&value)
+                    .map_err(|e| format!("{:?}", e))?;
+                entries.insert(String::from_utf8(key).map_err(|e| format!("{:?}", e))?, entry);
+            }
+        }
+        let header =
+            header.ok_or_else(|| format!("No bundle header in {}", index_path.display()))?;
+        if header.get_endianness() != BundleHeaderProto_Endianness::LITTLE {
+            bail!("Big endian checkpoints are not supported")
+        }
+        Ok(Checkpoint { prefix, header, entries })
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    /// Load the value of a variable.
+    pub fn tensor(&self, name: &str) -> TractResult<Tensor> {
+        let entry = self.entries.get(name).ok_or_else(|| format!("No {} in checkpoint", name))?;
+        if entry.get_slices().len() > 0 {
+            bail!("{} is a partitioned variable, this is not supported", name)
+        }
+        let shape: TVec<usize> = entry.get_shape().try_into()?;
+        let mut data = vec![0u8; entry.get_size() as usize];
+        let shard = format!(
+            "{}.data-{:05}-of-{:05}",
+            self.prefix.display(),
+            entry.get_shard_id(),
+            self.header.get_num_shards()
+        );
+        let mut file =
+            std::fs::File::open(&shard).map_err(|e| format!("Could not open {}: {}", shard, e))?;
+        file.seek(SeekFrom::Start(entry.get_offset() as u64))?;
+        file.read_exact(&mut data)?;
+        if entry.get_dtype() == DataType::DT_STRING {
+            return decode_strings(&shape, &data);
+        }
+        let dt: DatumType = entry.get_dtype().try_into()?;
+        let len = shape.iter().product::<usize>() * dt.size_of();
+        if data.len() != len {
+            bail!(
+                "Expected {} bytes for {} ({:?} {:?}), found {}",
+                len,
+                name,
+                dt,
+                shape,
+                data.len()
+            )
+        }
+        unsafe { Tensor::from_raw_dt(dt, &shape, &data) }
+    }
+}
+
+/// Strings are stored as the varint lengths of all the elements, a checksum
+/// of the lengths, then the concatenated bytes.
+fn decode_strings(shape: &[usize], data: &[u8]) -> TractResult<Tensor> {
+    let mut cursor = data;
+    let lengths = (0..shape.iter().product::<usize>())
+        .map(|_| read_varint(&mut cursor).map(|l| l as usize))
+        .collect::<TractResult<Vec<usize>>>()?;
+    cursor = cursor.get(4..).ok_or("Truncated string tensor")?;
+    let mut strings = Vec::with_capacity(lengths.len());
+    for len in lengths {
+        if cursor.len() < len {
+            bail!("Truncated string tensor")
+        }
+        strings.push(String::from_utf8_lossy(&cursor[..len]).into_owned());
+        cursor = &cursor[len..];
+    }
+    Ok(ndarray::ArrayD::from_shape_vec(shape, strings)?.into())
+}
+
+fn read_varint(data: &mut &[u8]) -> TractResult<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (byte, rest) = data.split_first().ok_or("Truncated varint")?;
+        *data = rest;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    bail!("Invalid varint")
+}
+
+fn read_block_handle(data: &mut &[u8]) -> TractResult<(usize, usize)> {
+    Ok((read_varint(data)? as usize, read_varint(data)? as usize))
+}
+
+fn read_block<'a>(table: &'a [u8], (offset, size): (usize, usize)) -> TractResult<&'a [u8]> {
+    let block = table.get(offset..offset + size + BLOCK_TRAILER_LEN).ok_or("Truncated table")?;
+    if block[size] != 0 {
+        bail!("Compressed checkpoint index blocks are not supported")
+    }
+    Ok(&block[..size])
+}
+
+/// Decode the key/value pairs of a block.
+fn read_block_entries(block: &[u8]) -> TractResult<Vec<(Vec<u8>, Vec<u8>)>> {
+    if block.len() < 4 {
+        bail!("Truncated block")
+    }
+    let restarts = u32::from_le_bytes(block[block.len() - 4..].try_into().unwrap()) as usize;
+    let end = block.len().checked_sub(4 * (restarts + 1)).ok_or("Truncated block")?;
+    let mut data = &block[..end];
+    let mut entries: Vec<(Vec<u8>, Vec<u8>)> = vec![];
+    while data.len() > 0 {
+        let shared = read_varint(&mut data)? as usize;
+        let non_shared = read_varint(&mut data)? as usize;
+        let value_len = read_varint(&mut data)? as usize;
+        if data.len() < non_shared + value_len {
+            bail!("Truncated block entry")
+        }
+        let prev: &[u8] = entries.last().map(|e| &*e.0).unwrap_or(&[]);
+        if shared > prev.len() {
+            bail!("Invalid block entry: shares {} bytes of a {} bytes key", shared, prev.len())
+        }
+        let mut key = prev[..shared].to_vec();
+        key.extend_from_slice(&data[..non_shared]);
+        let value = data[non_shared..][..value_len].to_vec();
+        data = &data[non_shared + value_len..];
+        entries.push((key, value));
+    }
+    Ok(entries)
+}
+
+/// Read all the key/value pairs of a sorted string table.
+fn read_table(table: &[u8]) -> TractResult<Vec<(Vec<u8>, Vec<u8>)>> {
+    if table.len() < FOOTER_LEN {
+        bail!("Truncated table")
+    }
+    let footer = &table[table.len() - FOOTER_LEN..];
+    if u64::from_le_bytes(footer[FOOTER_LEN - 8..].try_into().unwrap()) != TABLE_MAGIC {
+        bail!("Not a checkpoint index (wrong magic number)")
+    }
+    let mut handles = footer;
+    let _metaindex = read_block_handle(&mut handles)?;
+    let index = read_block_handle(&mut handles)?;
+    let mut entries = vec![];
+    for (_, handle) in read_block_entries(read_block(table, index)?)? {
+        let handle = read_block_handle(&mut &*handle)?;
+        entries.extend(read_block_entries(read_block(table, handle)?)?);
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::tfpb::tensor_shape::{TensorShapeProto, TensorShapeProto_Dim};
+    use protobuf::Message;
+    use std::io::Write;
+
+    fn write_varint(buf: &mut Vec<u8>, mut v: u64) {
+        while v >= 0x80 {
+            buf.push((v as u8) | 0x80);
+            v >>= 7;
+        }
+        buf.push(v as u8);
+    }
+
+    fn write_block(table: &mut Vec<u8>, entries: &[(Vec<u8>, Vec<u8>)]) -> Vec<u8> {
+        let offset = table.len();
+        let mut prev: &[u8] = &[];
+        for (k, v) in entries {
+            let shared = k.iter().zip(prev.iter()).take_while(|(a, b)| a == b).count();
+            write_varint(table, shared as u64);
+            write_varint(table, (k.len() - shared) as u64);
+            write_varint(table, v.len() as u64);
+            table.extend_from_slice(&k[shared..]);
+            table.extend_from_slice(v);
+            prev = k;
+        }
+        table.extend_from_slice(&[0, 0, 0, 0, 1, 0, 0, 0]);
+        let size = table.len() - offset;
+        table.extend_from_slice(&[0; BLOCK_TRAILER_LEN]);
+        let mut handle = vec![];
+        write_varint(&mut handle, offset as u64);
+        write_varint(&mut handle, size as u64);
+        handle
+    }
+
+    /// Write a single shard checkpoint (tensors must be sorted by name).
+    pub(crate) fn write_checkpoint(prefix: &Path, tensors: &[(&str, Tensor)]) {
+        let mut data = vec![];
+        let mut entries = vec![];
+        let mut header = BundleHeaderProto::new();
+        header.set_num_shards(1);
+        entries.push((vec![], header.write_to_bytes().unwrap()));
+        for (name, t) in tensors {
+            let mut entry = BundleEntryProto::new();
+            entry.set_dtype(t.datum_type().try_into().unwrap());
+            let mut shape = TensorShapeProto::new();
+            for &d in t.shape() {
+                let mut dim = TensorShapeProto_Dim::new();
+                dim.set_size(d as i64);
+                shape.mut_dim().push(dim);
+            }
+            entry.set_shape(shape);
+            entry.set_offset(data.len() as i64);
+            entry.set_size(t.as_bytes().len() as i64);
+            data.extend_from_slice(t.as_bytes());
+            entries.push((name.as_bytes().to_vec(), entry.write_to_bytes().unwrap()));
+        }
+        let mut table = vec![];
+        let data_handle = write_block(&mut table, &entries);
+        let index_handle = write_block(&mut table, &[(vec![0xff], data_handle)]);
+        let metaindex_handle = write_block(&mut table, &[]);
+        let mut footer = metaindex_handle;
+        footer.extend(index_handle);
+        footer.resize(FOOTER_LEN - 8, 0);
+        footer.extend_from_slice(&TABLE_MAGIC.to_le_bytes());
+        table.extend(footer);
+        let index = format!("{}.index", prefix.display());
+        std::fs::File::create(index).unwrap().write_all(&table).unwrap();
+        let shard = format!("{}.data-00000-of-00001", prefix.display());
+        std::fs::File::create(shard).unwrap().write_all(&data).unwrap();
+    }
+
+    #[test]
+    fn read_written_checkpoint() {
+        let dir = std::env::temp_dir().join(format!("tract-checkpoint-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let prefix = dir.join("variables");
+        let bias = tensor1(&[1.0f32, 2.0, 3.0]);
+        let kernel = tensor2(&[[1i64, 2], [3, 4]]);
+        write_checkpoint(
+            &prefix,
+            &[("dense/bias", bias.clone()), ("dense/kernel", kernel.clone())],
+        );
+        let checkpoint = Checkpoint::open(&prefix).unwrap();
+        assert_eq!(checkpoint.entries.len(), 2);
+        assert_eq!(checkpoint.tensor("dense/bias").unwrap(), bias);
+        assert_eq!(checkpoint.tensor("dense/kernel").unwrap(), kernel);
+        assert!(checkpoint.tensor("dense/nope").is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn invalid_shared_key_prefix() {
+        // first entry claims to share 3 bytes with a previous key
+        let block = [3, 1, 0, b'a', 0, 0, 0, 0, 1, 0, 0, 0];
+        assert!(read_block_entries(&block).is_err());
+        let mut table = vec![];
+        write_block(&mut table, &[(b"ab".to_vec(), vec![]), (b"abc".to_vec(), vec![])]);
+        table.truncate(table.len() - BLOCK_TRAILER_LEN);
+        let entries = read_block_entries(&table).unwrap();
+        assert_eq!(entries[1].0, b"abc");
+        table[5] = 5; // second entry now shares 5 bytes with "ab"
+        assert!(read_block_entries(&table).is_err());
+    }
+}
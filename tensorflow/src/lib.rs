@@ -53,8 +53,10 @@ extern crate tensorflow;
 #[cfg(feature = "conform")]
 pub mod conform;
 
+pub mod checkpoint;
 pub mod model;
 pub mod ops;
+pub mod saved_model;
 pub mod tensor;
 pub mod tfpb;
 
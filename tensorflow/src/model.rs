@@ -32,7 +32,7 @@ impl Tensorflow {
     // "src_output" indicating which output tensor to use from "node". If
     // "src_output" is 0 the ":0" suffix can be omitted. Regular inputs may
     // optionally be followed by control inputs that have the format "^node".
-    fn parse_input(i: &str) -> TractResult<(&str, usize)> {
+    pub(crate) fn parse_input(i: &str) -> TractResult<(&str, usize)> {
         let pair = if i.starts_with("^") {
             (&i[1..], 0)
         } else {
@@ -62,6 +62,18 @@ impl Framework<GraphDef> for Tensorflow {
         Ok(::protobuf::parse_from_reader::<GraphDef>(r).map_err(|e| format!("{:?}", e))?)
     }
 
+    /// Build a model from a frozen GraphDef file, or from a SavedModel
+    /// directory using the "serve" tag set and "serving_default" signature.
+    fn model_for_path(&self, p: impl AsRef<std::path::Path>) -> TractResult<InferenceModel> {
+        use crate::saved_model::{DEFAULT_SIGNATURE, DEFAULT_TAGS};
+        if p.as_ref().is_dir() {
+            return self.model_for_saved_model(p, DEFAULT_TAGS, DEFAULT_SIGNATURE);
+        }
+        let mut r = std::fs::File::open(p.as_ref())
+            .map_err(|e| format!("Could not open {:?}: {}", p.as_ref(), e))?;
+        self.model_for_read(&mut r)
+    }
+
     fn model_for_proto_model(&self, graph: &GraphDef) -> TractResult<InferenceModel> {
         use crate::ops::control_flow as cf;
 
@@ -4,29 +4,44 @@ use tract_core::internal::*;
 mod concatv2;
 mod expand_dims;
 mod fill;
-mod gather;
+mod one_hot;
 mod pack;
 mod pad;
 mod range;
 mod reshape;
+mod scatter_nd;
 mod slice;
 mod squeeze;
 mod strided_slice;
 mod transpose;
 
+pub use self::range::Range;
+
 pub fn register_all_ops(reg: &mut TfOpRegister) {
     reg.insert("ConcatV2", concatv2::build);
     reg.insert("ExpandDims", expand_dims::build);
     reg.insert("Fill", fill::fill);
-    reg.insert("GatherNd", gather::gather_nd);
+    reg.insert("GatherNd", |_, _| Ok(Box::new(::tract_core::ops::array::GatherNd::new(0))));
+    reg.insert("OneHot", one_hot::one_hot);
     reg.insert("Pack", pack::pack);
     reg.insert("Pad", pad::pad);
     reg.insert("Range", range::range);
     reg.insert("Reshape", reshape::reshape);
+    reg.insert("ScatterNd", scatter_nd::scatter_nd);
     reg.insert("Shape", |_, _| Ok(Box::new(::tract_core::ops::array::Shape::new(DatumType::I32))));
     reg.insert("Slice", |_, _| Ok(Box::new(slice::Slice)));
     reg.insert("Squeeze", squeeze::squeeze);
     reg.insert("StridedSlice", strided_slice::build);
     reg.insert("Tile", |_, _| Ok(Box::new(::tract_core::ops::array::Tile)));
+    reg.insert("TensorScatterAdd", |_, _| {
+        Ok(Box::new(::tract_core::ops::array::ScatterNd::new(
+            ::tract_core::ops::array::ScatterReduction::Add,
+        )))
+    });
+    reg.insert("TensorScatterUpdate", |_, _| {
+        Ok(Box::new(::tract_core::ops::array::ScatterNd::new(
+            ::tract_core::ops::array::ScatterReduction::None,
+        )))
+    });
     reg.insert("Transpose", transpose::transpose);
 }
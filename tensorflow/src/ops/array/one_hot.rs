@@ -0,0 +1,119 @@
+use crate::model::ParsingContext;
+use crate::tfpb::node_def::NodeDef;
+use tract_core::internal::*;
+use tract_core::ops as tractops;
+
+#[derive(Debug, Clone, new)]
+pub struct OneHot {
+    axis: i64,
+}
+
+pub fn one_hot(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    let axis = pb.get_attr_opt_int("axis")?.unwrap_or(-1);
+    Ok(Box::new(OneHot::new(axis)))
+}
+
+impl OneHot {
+    fn typed(
+        &self,
+        indices_rank: usize,
+        depth: &Tensor,
+        on: Arc<Tensor>,
+        off: Arc<Tensor>,
+    ) -> TractResult<tractops::array::OneHot> {
+        let depth = *depth.cast_to::<i32>()?.to_scalar::<i32>()?;
+        Ok(tractops::array::OneHot::new(
+            resolve_axis(self.axis, indices_rank + 1)?,
+            depth.max(0) as usize,
+            off,
+            on,
+            false,
+        ))
+    }
+}
+
+impl Op for OneHot {
+    fn name(&self) -> Cow<str> {
+        "tf.OneHot".into()
+    }
+
+    not_a_typed_op!();
+}
+
+impl StatelessOp for OneHot {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (indices, depth, on, off) = args_4!(inputs);
+        self.typed(indices.shape().len(), &depth, on, off)?.eval(tvec!(indices))
+    }
+}
+
+impl InferenceRulesOp for OneHot {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 4)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[1].rank, 0)?;
+        s.equals(&inputs[2].rank, 0)?;
+        s.equals(&inputs[3].rank, 0)?;
+        s.equals(&inputs[2].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[3].datum_type, &outputs[0].datum_type)?;
+        s.equals(inputs[0].rank.bex() + 1, outputs[0].rank.bex())?;
+        s.given(&inputs[0].rank, move |s, rank| {
+            let axis = resolve_axis(self.axis, rank as usize + 1)?;
+            for i in 0..rank as usize {
+                let o = if i < axis { i } else { i + 1 };
+                s.equals(&inputs[0].shape[i], &outputs[0].shape[o])?;
+            }
+            s.given(&inputs[1].value, move |s, depth| {
+                let depth = *depth.cast_to::<i32>()?.to_scalar::<i32>()?;
+                s.equals(&outputs[0].shape[axis], (depth.max(0) as usize).to_dim())
+            })
+        })
+    }
+
+    inference_op_as_op!();
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        if let (Some(depth), Some(on), Some(off)) = (
+            target.outlet_fact(mapping[&node.inputs[1]])?.konst.clone(),
+            target.outlet_fact(mapping[&node.inputs[2]])?.konst.clone(),
+            target.outlet_fact(mapping[&node.inputs[3]])?.konst.clone(),
+        ) {
+            let indices = mapping[&node.inputs[0]];
+            let rank = target.outlet_fact(indices)?.shape.rank();
+            let op = self.typed(rank, &depth, on, off)?;
+            target.wire_node(&*node.name, op, [indices].as_ref())
+        } else {
+            bail!("Can not type OneHot op with dynamic depth or values")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_hot() {
+        let op = OneHot::new(0);
+        let output = op
+            .eval(tvec!(
+                rctensor1(&[0i32, 2, -1]),
+                rctensor0(3i32),
+                rctensor0(5.0f32),
+                rctensor0(0.0f32)
+            ))
+            .unwrap();
+        assert_eq!(output[0], rctensor2(&[[5.0f32, 0.0, 0.0], [0.0, 0.0, 0.0], [0.0, 5.0, 0.0]]));
+    }
+}
@@ -44,7 +44,7 @@ impl<T: Datum> InferenceRulesOp for Reshape<T> {
         s.equals(&inputs[1].rank, 1)?;
         s.given_2(&inputs[0].shape, &inputs[1].value, move |solver, shape, dims| {
             let dims = dims.as_slice::<i32>().unwrap(); // checked
-            if shape.iter().all(|d| !d.is_stream()) {
+            if shape.iter().all(|d| !d.is_symbolic()) {
                 let len = shape.iter().map(|d| d.as_const().unwrap() as usize).product();
                 let shape = true_dims(dims, len);
                 solver.equals(&outputs[0].shape, ShapeFact::from(shape))?;
@@ -66,7 +66,7 @@ impl<T: Datum> InferenceRulesOp for Reshape<T> {
             let input_shape = target.outlet_fact(mapping[&node.inputs[0]])?;
             let output_shape = if dims.as_slice::<i32>()?.iter().all(|&x| x >= 0) {
                 dims.as_slice::<i32>()?.iter().map(|d| *d as usize).collect()
-            } else if input_shape.shape.stream_info.is_none() {
+            } else if input_shape.shape.as_finite().is_some() {
                 let input_len =
                     input_shape.shape.iter().fold(1, |a, b| a * b.to_integer().unwrap());
                 let dims = dims.cast_to::<i32>()?;
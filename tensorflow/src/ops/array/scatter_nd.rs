@@ -0,0 +1,103 @@
+use crate::model::ParsingContext;
+use crate::tfpb::node_def::NodeDef;
+use tract_core::internal::*;
+use tract_core::ndarray::ArrayD;
+use tract_core::ops::array::{ScatterNd as CoreScatterNd, ScatterReduction};
+
+#[derive(Debug, Clone)]
+pub struct ScatterNd;
+
+pub fn scatter_nd(_ctx: &ParsingContext, _pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    Ok(Box::new(ScatterNd))
+}
+
+impl ScatterNd {
+    fn zeros<T: Datum>(shape: &[usize]) -> TractResult<Arc<Tensor>> {
+        Ok(ArrayD::<T>::default(shape).into_arc_tensor())
+    }
+
+    fn zeros_for(dt: DatumType, shape: &Tensor) -> TractResult<Arc<Tensor>> {
+        let shape = shape.cast_to::<i64>()?;
+        let shape = shape.as_slice::<i64>()?.iter().map(|&d| d as usize).collect::<TVec<_>>();
+        dispatch_datum!(Self::zeros(dt)(&*shape))
+    }
+}
+
+impl Op for ScatterNd {
+    fn name(&self) -> Cow<str> {
+        "tf.ScatterNd".into()
+    }
+
+    not_a_typed_op!();
+}
+
+impl StatelessOp for ScatterNd {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (indices, updates, shape) = args_3!(inputs);
+        let zeros = Self::zeros_for(updates.datum_type(), &shape)?;
+        CoreScatterNd::new(ScatterReduction::Add).eval(tvec!(zeros, indices, updates))
+    }
+}
+
+impl InferenceRulesOp for ScatterNd {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 3)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[1].datum_type, &outputs[0].datum_type)?;
+        s.equals(&inputs[2].rank, 1)?;
+        s.equals(outputs[0].rank.bex().to_dim(), &inputs[2].shape[0])?;
+        s.given(&outputs[0].rank, move |s, rank| {
+            for dim in 0..(rank as usize) {
+                s.equals(&outputs[0].shape[dim], inputs[2].value[dim].bex().to_dim())?;
+            }
+            Ok(())
+        })
+    }
+
+    inference_op_as_op!();
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        if let Some(shape) = target.outlet_fact(mapping[&node.inputs[2]])?.konst.clone() {
+            let dt = target.outlet_fact(mapping[&node.inputs[1]])?.datum_type;
+            let zeros = Self::zeros_for(dt, &shape)?;
+            let zeros = target.add_const(format!("{}.zeros", node.name), zeros)?;
+            target.wire_node(
+                &*node.name,
+                CoreScatterNd::new(ScatterReduction::Add),
+                &[OutletId::new(zeros, 0), mapping[&node.inputs[0]], mapping[&node.inputs[1]]],
+            )
+        } else {
+            bail!("Can not type ScatterNd op with a dynamic shape")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // https://www.tensorflow.org/api_docs/python/tf/scatter_nd
+    #[test]
+    fn scatter_nd() {
+        let op = ScatterNd;
+        let output = op
+            .eval(tvec!(
+                rctensor2(&[[4i32], [3], [1], [7], [1]]),
+                rctensor1(&[9, 10, 11, 12, 13]),
+                rctensor1(&[8i32])
+            ))
+            .unwrap();
+        assert_eq!(output[0], rctensor1(&[0, 24, 0, 10, 9, 0, 0, 12]));
+    }
+}
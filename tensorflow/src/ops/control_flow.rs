@@ -1,6 +1,16 @@
 use tract_core::internal::*;
+use tract_core::ops::array::{AddDims, RmDims};
+use tract_core::ops::binary::{BinMiniOp, InferenceBinOp};
+use tract_core::ops::identity::Identity;
+use tract_core::ops::konst::Const;
+use tract_core::ops::logic::{And, Lesser};
+use tract_core::ops::math::{Add, Max, Min};
+use tract_core::ops::scan::{self, InputMapping, OutputMapping, StateInitializer};
 
 use crate::model::TfOpRegister;
+use crate::ops::array::Range;
+use crate::ops::logic::{Merge, Switch};
+use crate::ops::tensor_array::{TensorArray, TensorArrayRole};
 
 pub fn register_all_ops(reg: &mut TfOpRegister) {
     reg.insert("Enter", |_, node| {
@@ -10,7 +20,7 @@ pub fn register_all_ops(reg: &mut TfOpRegister) {
     reg.insert("LoopCond", |_, _| Ok(Box::new(LoopGate(LoopGateRole::LoopCond))));
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum LoopGateRole {
     Enter(String),
     Exit,
@@ -25,6 +35,19 @@ impl Op for LoopGate {
         format!("tf.{:?}", self.0).into()
     }
 
+    fn incorporate(
+        &self,
+        model: &InferenceModel,
+        node: &InferenceNode,
+    ) -> TractResult<Option<InferenceModelPatch>> {
+        if let LoopGateRole::LoopCond = self.0 {
+            let frame = Frame::for_loop_cond(model, node)?;
+            Ok(Some(frame.to_scan_patch(model)?))
+        } else {
+            Ok(None)
+        }
+    }
+
     not_a_typed_op!();
 }
 
@@ -101,5 +124,706 @@ impl InferenceRulesOp for NextIteration {
         Ok(())
     }
 
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(if self.role == NextIterationRole::Source { 1 } else { 0 })
+    }
+
     inference_op_as_op!();
 }
+
+fn loop_gate_role(node: &InferenceNode) -> Option<&LoopGateRole> {
+    node.op_as::<LoopGate>().map(|gate| &gate.0)
+}
+
+fn tensor_array_role(node: &InferenceNode) -> Option<TensorArrayRole> {
+    node.op_as::<TensorArray>().map(|array| array.role)
+}
+
+/// Follows a TensorArray handle through Enter and Identity nodes, up to the
+/// TensorArrayV3 node that created it.
+fn tensor_array_for_handle(model: &InferenceModel, mut outlet: OutletId) -> TractResult<usize> {
+    loop {
+        let node = model.node(outlet.node);
+        if tensor_array_role(node) == Some(TensorArrayRole::Create) {
+            return Ok(node.id);
+        } else if node.op_is::<Identity>() || loop_gate_role(node).is_some() {
+            outlet = node.inputs[0];
+        } else {
+            bail!("Could not find the TensorArray behind handle {}", node)
+        }
+    }
+}
+
+fn is_bin_op<O: BinMiniOp>(node: &InferenceNode) -> bool {
+    node.op_as::<InferenceBinOp>().map(|op| op.0.downcast_ref::<O>().is_some()).unwrap_or(false)
+}
+
+fn skip_identities(model: &InferenceModel, mut outlet: OutletId) -> OutletId {
+    while model.node(outlet.node).op_is::<Identity>() {
+        outlet = model.node(outlet.node).inputs[0];
+    }
+    outlet
+}
+
+fn scalar_value(model: &InferenceModel, outlet: OutletId) -> Option<i64> {
+    let value = model.outlet_fact(outlet).ok()?.value.concretize()?;
+    let value = value.cast_to::<i64>().ok()?;
+    value.to_scalar::<i64>().ok().cloned()
+}
+
+/// Is `n` equal to a length `is_len` recognizes, either directly or as
+/// `min(len, max(x, len))`, the way dynamic_rnn bounds its loop ?
+fn is_bounded_len(model: &InferenceModel, n: OutletId, is_len: &dyn Fn(OutletId) -> bool) -> bool {
+    let n = skip_identities(model, n);
+    if is_len(n) {
+        return true;
+    }
+    let min = model.node(n.node);
+    if !is_bin_op::<Min>(min) {
+        return false;
+    }
+    let is_max_with = |max: OutletId, len: OutletId| {
+        let max = model.node(skip_identities(model, max).node);
+        is_bin_op::<Max>(max)
+            && max.inputs.iter().any(|&i| skip_identities(model, i) == skip_identities(model, len))
+    };
+    let (a, b) = (min.inputs[0], min.inputs[1]);
+    (is_len(a) && is_max_with(b, a)) || (is_len(b) && is_max_with(a, b))
+}
+
+/// Is `n` the length of `array`: its size when created, its size as read
+/// by TensorArraySizeV3, or a constant matching the scattered value length ?
+fn is_tensor_array_len(model: &InferenceModel, n: OutletId, array: usize) -> bool {
+    let n = skip_identities(model, n);
+    if n == skip_identities(model, model.node(array).inputs[0]) {
+        return true;
+    }
+    let node = model.node(n.node);
+    if tensor_array_role(node) == Some(TensorArrayRole::Size) {
+        return tensor_array_for_handle(model, node.inputs[0]).ok() == Some(array);
+    }
+    if let Some(n) = scalar_value(model, n) {
+        return tensor_array_users(model, array, TensorArrayRole::Scatter).iter().any(|scatter| {
+            model
+                .outlet_fact(scatter.inputs[2])
+                .ok()
+                .and_then(|fact| fact.shape.dims().next())
+                .and_then(|dim| dim.concretize())
+                .and_then(|dim| dim.to_integer().ok())
+                == Some(n as i32)
+        });
+    }
+    false
+}
+
+fn tensor_array_users(
+    model: &InferenceModel,
+    array: usize,
+    role: TensorArrayRole,
+) -> Vec<&InferenceNode> {
+    model
+        .nodes()
+        .iter()
+        .filter(|n| {
+            tensor_array_role(n) == Some(role)
+                && tensor_array_for_handle(model, n.inputs[0]).ok() == Some(array)
+        })
+        .collect()
+}
+
+/// A loop variable: a value entering the frame is merged with the one from
+/// the previous iteration, then switched to the body or the exit by the loop
+/// condition.
+#[derive(Debug)]
+struct LoopVar {
+    enter: usize,
+    merge: usize,
+    switch: usize,
+    next_iteration_sink: usize,
+    exit: Option<usize>,
+}
+
+/// A while loop frame, as found from its LoopCond node.
+///
+/// The frame is rebuilt as a Scan. The loop variables become the scan
+/// states, the TensorArray read in the body the scanned inputs, and the
+/// TensorArray written in the body the scanned outputs. The Scan runs over
+/// the whole scattered TensorArrays, so the loop condition must be `i < n`,
+/// `i` counting the iterations from 0 and `n` the length of these
+/// TensorArrays, as built by tf.scan and dynamic RNNs. Conditions combining
+/// such bounds with LogicalAnd, like the ones while_loop builds when given
+/// `maximum_iterations`, are accepted too. A frame reading no TensorArray is
+/// run over a range of its iteration count.
+#[derive(Debug)]
+struct Frame {
+    name: String,
+    loop_cond: usize,
+    vars: Vec<LoopVar>,
+}
+
+impl Frame {
+    fn for_loop_cond(model: &InferenceModel, loop_cond: &InferenceNode) -> TractResult<Frame> {
+        let mut name = None;
+        let mut vars = vec![];
+        for succ in &loop_cond.outputs[0].successors {
+            let switch = model.node(succ.node);
+            if !switch.op_is::<Switch>() || succ.slot != 1 {
+                bail!("Unexpected {} after {}", switch, loop_cond)
+            }
+            let merge = model.node(switch.inputs[0].node);
+            if !merge.op_is::<Merge>() || merge.inputs.len() != 2 {
+                bail!("Expected a two-input Merge before {}, got {}", switch, merge)
+            }
+            let mut enter = None;
+            let mut next_iteration_sink = None;
+            for input in &merge.inputs {
+                let prec = model.node(input.node);
+                if let Some(LoopGateRole::Enter(frame)) = loop_gate_role(prec) {
+                    name = Some(frame.clone());
+                    enter = Some(prec.id);
+                } else if let Some(next) = prec.op_as::<NextIteration>() {
+                    next_iteration_sink =
+                        Some(model.node_by_name(format!("{}-Sink", next.name))?.id);
+                }
+            }
+            let exit = switch.outputs[0]
+                .successors
+                .iter()
+                .map(|s| s.node)
+                .find(|&n| loop_gate_role(model.node(n)) == Some(&LoopGateRole::Exit));
+            if let (Some(enter), Some(next_iteration_sink)) = (enter, next_iteration_sink) {
+                vars.push(LoopVar {
+                    enter,
+                    merge: merge.id,
+                    switch: switch.id,
+                    next_iteration_sink,
+                    exit,
+                });
+            } else {
+                bail!("{} does not merge an Enter and a NextIteration", merge)
+            }
+        }
+        let name = name.ok_or_else(|| format!("No loop variable found for {}", loop_cond))?;
+        Ok(Frame { name, loop_cond: loop_cond.id, vars })
+    }
+
+    /// The iteration bounds of the loop condition: the `n` of each `i < n`
+    /// it combines with LogicalAnd, `i` being an iteration counter and `n` a
+    /// loop invariant.
+    fn bounds(&self, model: &InferenceModel) -> TractResult<Vec<OutletId>> {
+        let mut conditions = vec![model.node(self.loop_cond).inputs[0]];
+        let mut bounds = vec![];
+        while let Some(condition) = conditions.pop() {
+            let node = model.node(skip_identities(model, condition).node);
+            if is_bin_op::<And>(node) {
+                conditions.extend(node.inputs.iter().cloned());
+                continue;
+            }
+            if !is_bin_op::<Lesser>(node) {
+                bail!("Frame {}: unsupported loop condition {}, expected a Less", self.name, node)
+            }
+            let counter = skip_identities(model, node.inputs[0]);
+            let counter = self.vars.iter().find(|var| OutletId::new(var.merge, 0) == counter);
+            if !counter.map(|var| Self::is_counter(model, var)).unwrap_or(false) {
+                bail!(
+                    "Frame {}: loop condition {} does not compare an iteration counter",
+                    self.name,
+                    node
+                )
+            }
+            let limit = model.node(skip_identities(model, node.inputs[1]).node);
+            match loop_gate_role(limit) {
+                Some(LoopGateRole::Enter(_)) => bounds.push(limit.inputs[0]),
+                _ => bail!("Frame {}: loop limit {} is not a loop invariant", self.name, limit),
+            };
+        }
+        Ok(bounds)
+    }
+
+    /// Checks every bound of the loop condition is the length of one of the
+    /// scattered `arrays`, or, if the frame reads no TensorArray, that they
+    /// are all the same iteration count. Returns this iteration count.
+    fn check_condition(&self, model: &InferenceModel, arrays: &[usize]) -> TractResult<OutletId> {
+        let bounds = self.bounds(model)?;
+        if arrays.is_empty() {
+            let same_as = |n: OutletId, other: OutletId| {
+                skip_identities(model, other) == skip_identities(model, n)
+                    || scalar_value(model, other)
+                        .map_or(false, |v| scalar_value(model, n) == Some(v))
+            };
+            return bounds
+                .iter()
+                .cloned()
+                .find(|&n| bounds.iter().all(|&b| is_bounded_len(model, b, &|o| same_as(n, o))))
+                .ok_or_else(|| format!("Frame {}: loop limits may differ", self.name).into());
+        }
+        for &bound in &bounds {
+            if !arrays.iter().any(|&array| {
+                is_bounded_len(model, bound, &|len| is_tensor_array_len(model, len, array))
+            }) {
+                bail!(
+                    "Frame {}: loop limit {} is not the length of the scanned TensorArrays",
+                    self.name,
+                    model.node(bound.node)
+                )
+            }
+        }
+        Ok(bounds[0])
+    }
+
+    /// A loop variable starting at 0, and incremented by 1 at each iteration.
+    fn is_counter(model: &InferenceModel, var: &LoopVar) -> bool {
+        if scalar_value(model, model.node(var.enter).inputs[0]) != Some(0) {
+            return false;
+        }
+        let next =
+            model.node(skip_identities(model, model.node(var.next_iteration_sink).inputs[0]).node);
+        if !is_bin_op::<Add>(next) {
+            return false;
+        }
+        let current = OutletId::new(var.switch, 1);
+        let operands =
+            (skip_identities(model, next.inputs[0]), skip_identities(model, next.inputs[1]));
+        (operands.0 == current && scalar_value(model, operands.1) == Some(1))
+            || (operands.1 == current && scalar_value(model, operands.0) == Some(1))
+    }
+
+    /// TensorArray flows are loop variables without a value, only ordering
+    /// accesses to the TensorArrays.
+    fn is_flow(model: &InferenceModel, var: &LoopVar) -> bool {
+        let enter = model.node(var.enter);
+        tensor_array_role(model.node(enter.inputs[0].node)).is_some()
+    }
+
+    fn to_scan_patch(&self, model: &InferenceModel) -> TractResult<InferenceModelPatch> {
+        let (flows, states): (Vec<&LoopVar>, Vec<&LoopVar>) =
+            self.vars.iter().partition(|var| Self::is_flow(model, var));
+        let mut body = Body::new(model);
+        for var in &states {
+            let slot = body.outer_slot(model.node(var.enter).inputs[0]);
+            let merged = OutletId::new(var.merge, 0);
+            let source = body.add_input(
+                &*model.node(var.merge).name,
+                InputMapping::State { initializer: StateInitializer::FromInput(slot) },
+                model.outlet_fact(merged)?.clone().without_value(),
+            )?;
+            body.mapping.insert(merged, source);
+            body.mapping.insert(OutletId::new(var.switch, 1), source);
+        }
+
+        let mut body_outputs = vec![];
+        let mut output_mapping = vec![];
+        // outer nodes to replace, with the scan output slot replacing them
+        let mut shunts = vec![];
+        let mut outputs = 0;
+        for var in &states {
+            body_outputs.push(body.wire(model.node(var.next_iteration_sink).inputs[0])?);
+            let last_value_slot = var.exit.map(|exit| {
+                shunts.push((exit, outputs));
+                outputs += 1;
+                outputs - 1
+            });
+            output_mapping.push(OutputMapping::new(None, 0, (), None, last_value_slot, true));
+        }
+        for var in &flows {
+            let mut outlet = model.node(var.next_iteration_sink).inputs[0];
+            loop {
+                let node = model.node(outlet.node);
+                if tensor_array_role(node) == Some(TensorArrayRole::Write) {
+                    let value = body.wire(node.inputs[2])?;
+                    let chunk = body.body.wire_node(
+                        format!("{}-chunk", node.name),
+                        AddDims::new(vec![0]),
+                        &[value],
+                    )?[0];
+                    body_outputs.push(chunk);
+                    let array = tensor_array_for_handle(model, node.inputs[0])?;
+                    let gathers = tensor_array_users(model, array, TensorArrayRole::Gather);
+                    let full_slot = if !gathers.is_empty() {
+                        for gather in gathers {
+                            shunts.push((gather.id, outputs));
+                        }
+                        outputs += 1;
+                        Some(outputs - 1)
+                    } else {
+                        None
+                    };
+                    output_mapping.push(OutputMapping::new(full_slot, 0, (), None, None, false));
+                    outlet = node.inputs[3];
+                } else if node.op_is::<Identity>() {
+                    outlet = node.inputs[0];
+                } else {
+                    break;
+                }
+            }
+        }
+
+        let n = self.check_condition(model, &body.arrays)?;
+        // without TensorArray to scan, the scan runs over range(0, n)
+        let iterations = if body.arrays.is_empty() {
+            let slot = body.outer_inputs.len();
+            let dt = model.outlet_fact(n)?.datum_type.concretize().unwrap_or(DatumType::I32);
+            let fact = TensorFact::dt_shape(dt, tvec!(1));
+            let name = format!("{}-iterations", self.name);
+            body.add_input(&*name, InputMapping::Scan { slot, axis: 0, chunk: () }, fact)?;
+            Some(dt)
+        } else {
+            None
+        };
+        let Body { body: mut inner, input_mapping, outer_inputs, .. } = body;
+        inner.set_output_outlets(&*body_outputs)?;
+        let op = scan::Inference::new(inner, input_mapping, output_mapping, None);
+        let mut patch = InferenceModelPatch::default();
+        let mut taps = outer_inputs
+            .iter()
+            .map(|outlet| patch.tap_model(model, *outlet))
+            .collect::<TractResult<TVec<_>>>()?;
+        if let Some(dt) = iterations {
+            let name = format!("{}-iterations", self.name);
+            let start = patch
+                .add_const(format!("{}-start", name), tensor0(0i64).cast_to_dt(dt)?.into_owned())?;
+            let delta = patch
+                .add_const(format!("{}-delta", name), tensor0(1i64).cast_to_dt(dt)?.into_owned())?;
+            let n = patch.tap_model(model, n)?;
+            let wires = [OutletId::new(start, 0), n, OutletId::new(delta, 0)];
+            taps.push(patch.wire_node(name, Range::new(dt), &wires)?[0]);
+        }
+        let scan = patch.wire_node(&*self.name, op, &*taps)?;
+        for (node, slot) in shunts {
+            patch.shunt_outside(OutletId::new(node, 0), scan[slot])?;
+        }
+        Ok(patch)
+    }
+}
+
+/// Extraction of the loop body to a nested model, pulling the frame nodes
+/// on demand from the loop variables next values.
+struct Body<'a> {
+    model: &'a InferenceModel,
+    body: InferenceModel,
+    mapping: HashMap<OutletId, OutletId>,
+    outer_inputs: Vec<OutletId>,
+    input_mapping: Vec<InputMapping<()>>,
+    /// TensorArrays read in the body.
+    arrays: Vec<usize>,
+}
+
+impl<'a> Body<'a> {
+    fn new(model: &'a InferenceModel) -> Body<'a> {
+        Body {
+            model,
+            body: InferenceModel::default(),
+            mapping: HashMap::new(),
+            outer_inputs: vec![],
+            input_mapping: vec![],
+            arrays: vec![],
+        }
+    }
+
+    fn outer_slot(&mut self, outlet: OutletId) -> usize {
+        if let Some(slot) = self.outer_inputs.iter().position(|o| *o == outlet) {
+            slot
+        } else {
+            self.outer_inputs.push(outlet);
+            self.outer_inputs.len() - 1
+        }
+    }
+
+    fn add_input(
+        &mut self,
+        name: &str,
+        mapping: InputMapping<()>,
+        fact: TensorFact,
+    ) -> TractResult<OutletId> {
+        let id = self.body.add_source(name, fact)?;
+        self.input_mapping.push(mapping);
+        Ok(OutletId::new(id, 0))
+    }
+
+    fn wire(&mut self, outlet: OutletId) -> TractResult<OutletId> {
+        if let Some(wired) = self.mapping.get(&outlet) {
+            return Ok(*wired);
+        }
+        let model = self.model;
+        let node = model.node(outlet.node);
+        let wired = if let Some(LoopGateRole::Enter(_)) = loop_gate_role(node) {
+            // loop invariant
+            let slot = self.outer_slot(node.inputs[0]);
+            let fact = model.outlet_fact(outlet)?.clone().without_value();
+            self.add_input(&*node.name, InputMapping::Full { slot }, fact)?
+        } else if tensor_array_role(node) == Some(TensorArrayRole::Read) {
+            let array = tensor_array_for_handle(model, node.inputs[0])?;
+            self.arrays.push(array);
+            let scatter = *tensor_array_users(model, array, TensorArrayRole::Scatter)
+                .first()
+                .ok_or_else(|| format!("No TensorArrayScatterV3 found for {}", node))?;
+            let value = scatter.inputs[2];
+            let slot = self.outer_slot(value);
+            let fact = model.outlet_fact(value)?;
+            let mut dims = tvec!(GenericFact::Only(1.to_dim()));
+            let shape = if let Some(rank) = fact.shape.rank().concretize() {
+                dims.extend((1..rank).map(|_| GenericFact::Any));
+                ShapeFact::closed(dims)
+            } else {
+                ShapeFact::open(dims)
+            };
+            let fact =
+                TensorFact { datum_type: fact.datum_type.clone(), shape, value: GenericFact::Any };
+            let chunk = self.add_input(
+                &*scatter.name,
+                InputMapping::Scan { slot, axis: 0, chunk: () },
+                fact,
+            )?;
+            self.body.wire_node(&*node.name, RmDims::new(vec![0]), &[chunk])?[0]
+        } else if loop_gate_role(node).is_some()
+            || tensor_array_role(node).is_some()
+            || (node.inputs.is_empty() && !node.op_is::<Const>())
+        {
+            bail!("Unexpected {} in while loop body", node)
+        } else {
+            let inputs =
+                node.inputs.iter().map(|i| self.wire(*i)).collect::<TractResult<TVec<_>>>()?;
+            let facts = node.outputs.iter().map(|o| o.fact.clone().without_value()).collect();
+            let id = self.body.add_node(&*node.name, node.op.clone(), facts)?;
+            for (ix, input) in inputs.into_iter().enumerate() {
+                self.body.add_edge(input, InletId::new(id, ix))?;
+            }
+            for ix in 0..node.outputs.len() {
+                self.mapping.insert(OutletId::new(node.id, ix), OutletId::new(id, ix));
+            }
+            return Ok(OutletId::new(id, outlet.slot));
+        };
+        self.mapping.insert(outlet, wired);
+        Ok(wired)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tract_core::ops::logic::{and, lesser};
+    use tract_core::ops::math::add;
+
+    fn konst(model: &mut InferenceModel, name: &str, v: Tensor) -> OutletId {
+        OutletId::new(model.add_const(name, v).unwrap(), 0)
+    }
+
+    fn wire(
+        model: &mut InferenceModel,
+        name: &str,
+        op: impl Into<Box<dyn InferenceOp>>,
+        inputs: &[OutletId],
+    ) -> TVec<OutletId> {
+        model.wire_node(name, op, inputs).unwrap()
+    }
+
+    fn enter(model: &mut InferenceModel, name: &str, input: OutletId) -> OutletId {
+        wire(model, name, LoopGate(LoopGateRole::Enter("frame".to_string())), &[input])[0]
+    }
+
+    fn array(role: TensorArrayRole, dt: Option<DatumType>, arity: usize) -> TensorArray {
+        TensorArray::new(role, dt, arity)
+    }
+
+    // a loop variable, returning the merged value and the value switched in
+    // the body
+    fn var(
+        model: &mut InferenceModel,
+        name: &str,
+        init: OutletId,
+        cond: impl Fn(&mut InferenceModel, OutletId) -> OutletId,
+    ) -> (OutletId, usize) {
+        let enter = enter(model, &format!("{}-enter", name), init);
+        let next_name = format!("{}-next", name);
+        let next = model
+            .add_node(
+                &*next_name,
+                NextIteration::new(next_name.clone(), NextIterationRole::Source),
+                tvec!(TensorFact::default()),
+            )
+            .unwrap();
+        let merge = wire(
+            model,
+            &format!("{}-merge", name),
+            Merge::new(2),
+            &[enter, OutletId::new(next, 0)],
+        )[0];
+        let pred = cond(model, merge);
+        let switch = wire(model, &format!("{}-switch", name), Switch::new(2), &[merge, pred]);
+        let _ = model
+            .add_node(
+                format!("{}-Sink", next_name),
+                NextIteration::new(next_name, NextIterationRole::Sink),
+                tvec!(),
+            )
+            .unwrap();
+        (switch[1], switch[0].node)
+    }
+
+    // Cumulative sum over the first axis, as built by tf.scan. The loop runs
+    // while the counter is less than `limit`, or the TensorArray size.
+    fn cumsum(limit: Option<i32>) -> InferenceModel {
+        let mut model = InferenceModel::default();
+        let fact = TensorFact::dt_shape(f32::datum_type(), tvec!(3usize, 2));
+        let x = OutletId::new(model.add_source("x", fact).unwrap(), 0);
+        let size = konst(&mut model, "size", tensor0(3i32));
+        let indices = konst(&mut model, "indices", tensor1(&[0i32, 1, 2]));
+        let input = wire(
+            &mut model,
+            "input",
+            array(TensorArrayRole::Create, Some(DatumType::F32), 2),
+            &[size],
+        );
+        let scatter = wire(
+            &mut model,
+            "scatter",
+            array(TensorArrayRole::Scatter, None, 1),
+            &[input[0], indices, x, input[1]],
+        )[0];
+        let output = wire(
+            &mut model,
+            "output",
+            array(TensorArrayRole::Create, Some(DatumType::F32), 2),
+            &[size],
+        );
+
+        let zero = konst(&mut model, "zero", tensor0(0i32));
+        let limit = match limit {
+            Some(limit) => konst(&mut model, "limit-value", tensor0(limit)),
+            None => size,
+        };
+        let limit = enter(&mut model, "limit", limit);
+        let loop_cond = std::cell::Cell::new(None);
+        let cond = |model: &mut InferenceModel, merged: OutletId| {
+            if let Some(lc) = loop_cond.get() {
+                lc
+            } else {
+                let less = wire(model, "less", lesser::bin(), &[merged, limit])[0];
+                let lc = wire(model, "loop_cond", LoopGate(LoopGateRole::LoopCond), &[less])[0];
+                loop_cond.set(Some(lc));
+                lc
+            }
+        };
+        let (time, _) = var(&mut model, "time", zero, &cond);
+        let zeros = konst(&mut model, "zeros", tensor1(&[0f32, 0.0]));
+        let (acc, acc_switch) = var(&mut model, "acc", zeros, &cond);
+        let (flow, flow_switch) = var(&mut model, "flow", output[1], &cond);
+
+        let time = wire(&mut model, "time-id", Identity, &[time])[0];
+        let acc = wire(&mut model, "acc-id", Identity, &[acc])[0];
+        let flow = wire(&mut model, "flow-id", Identity, &[flow])[0];
+        let input_handle = enter(&mut model, "input-handle", input[0]);
+        let input_flow = enter(&mut model, "input-flow", scatter);
+        let read = wire(
+            &mut model,
+            "read",
+            array(TensorArrayRole::Read, Some(DatumType::F32), 1),
+            &[input_handle, time, input_flow],
+        )[0];
+        let acc = wire(&mut model, "acc-next", add::bin(), &[acc, read])[0];
+        let output_handle = enter(&mut model, "output-handle", output[0]);
+        let flow = wire(
+            &mut model,
+            "write",
+            array(TensorArrayRole::Write, None, 1),
+            &[output_handle, time, acc, flow],
+        )[0];
+        let one = konst(&mut model, "one", tensor0(1i32));
+        let time = wire(&mut model, "time-next", add::bin(), &[time, one])[0];
+        for (name, value) in &[("time", time), ("acc", acc), ("flow", flow)] {
+            let sink = model.node_by_name(format!("{}-next-Sink", name)).unwrap().id;
+            model.add_edge(*value, InletId::new(sink, 0)).unwrap();
+        }
+
+        let acc = wire(
+            &mut model,
+            "acc-exit",
+            LoopGate(LoopGateRole::Exit),
+            &[OutletId::new(acc_switch, 0)],
+        )[0];
+        let flow = wire(
+            &mut model,
+            "flow-exit",
+            LoopGate(LoopGateRole::Exit),
+            &[OutletId::new(flow_switch, 0)],
+        )[0];
+        let gather = wire(
+            &mut model,
+            "gather",
+            array(TensorArrayRole::Gather, Some(DatumType::F32), 1),
+            &[output[0], indices, flow],
+        )[0];
+        model.set_output_outlets(&[gather, acc]).unwrap();
+        model
+    }
+
+    #[test]
+    fn while_loop_to_scan() {
+        let model = cumsum(None).into_typed().unwrap();
+        assert!(model.nodes().iter().any(|n| n.op_is::<scan::Typed>()));
+        let plan = SimplePlan::new(&model).unwrap();
+        let result = plan.run(tvec!(tensor2(&[[1f32, 2.0], [3.0, 4.0], [5.0, 6.0]]))).unwrap();
+        assert_eq!(*result[0], tensor2(&[[1f32, 2.0], [4.0, 6.0], [9.0, 12.0]]));
+        assert_eq!(*result[1], tensor1(&[9f32, 12.0]));
+    }
+
+    #[test]
+    fn while_loop_with_constant_limit() {
+        let model = cumsum(Some(3)).into_typed().unwrap();
+        let plan = SimplePlan::new(&model).unwrap();
+        let result = plan.run(tvec!(tensor2(&[[1f32, 2.0], [3.0, 4.0], [5.0, 6.0]]))).unwrap();
+        assert_eq!(*result[1], tensor1(&[9f32, 12.0]));
+    }
+
+    #[test]
+    fn while_loop_stopping_early_is_rejected() {
+        assert!(cumsum(Some(2)).into_typed().is_err());
+    }
+
+    // Doubles x n times: a loop over no TensorArray, its condition combining
+    // two bounds as while_loop does when given maximum_iterations.
+    #[test]
+    fn while_loop_without_tensor_array() {
+        let mut model = InferenceModel::default();
+        let fact = TensorFact::dt_shape(f32::datum_type(), tvec!(2usize));
+        let x = OutletId::new(model.add_source("x", fact).unwrap(), 0);
+        let n = konst(&mut model, "n", tensor0(3i32));
+        let zero = konst(&mut model, "zero", tensor0(0i32));
+        let limit = enter(&mut model, "limit", n);
+        let max_limit = enter(&mut model, "max-limit", n);
+        let loop_cond = std::cell::Cell::new(None);
+        let cond = |model: &mut InferenceModel, merged: OutletId| {
+            if let Some(lc) = loop_cond.get() {
+                lc
+            } else {
+                let less = wire(model, "less", lesser::bin(), &[merged, max_limit])[0];
+                let less_1 = wire(model, "less_1", lesser::bin(), &[merged, limit])[0];
+                let and = wire(model, "and", and::bin(), &[less, less_1])[0];
+                let lc = wire(model, "loop_cond", LoopGate(LoopGateRole::LoopCond), &[and])[0];
+                loop_cond.set(Some(lc));
+                lc
+            }
+        };
+        let (time, _) = var(&mut model, "time", zero, &cond);
+        let (acc, acc_switch) = var(&mut model, "acc", x, &cond);
+        let one = konst(&mut model, "one", tensor0(1i32));
+        let time = wire(&mut model, "time-next", add::bin(), &[time, one])[0];
+        let acc = wire(&mut model, "acc-next", add::bin(), &[acc, acc])[0];
+        for (name, value) in &[("time", time), ("acc", acc)] {
+            let sink = model.node_by_name(format!("{}-next-Sink", name)).unwrap().id;
+            model.add_edge(*value, InletId::new(sink, 0)).unwrap();
+        }
+        let acc = wire(
+            &mut model,
+            "acc-exit",
+            LoopGate(LoopGateRole::Exit),
+            &[OutletId::new(acc_switch, 0)],
+        )[0];
+        model.set_output_outlets(&[acc]).unwrap();
+
+        let model = model.into_typed().unwrap();
+        assert!(model.nodes().iter().any(|n| n.op_is::<scan::Typed>()));
+        let result = SimplePlan::new(&model).unwrap().run(tvec!(tensor1(&[1f32, 2.0]))).unwrap();
+        assert_eq!(*result[0], tensor1(&[8f32, 16.0]));
+    }
+}
@@ -5,7 +5,7 @@ use crate::model::ParsingContext;
 use crate::model::TfOpRegister;
 use crate::tfpb::node_def::NodeDef;
 
-mod max;
+mod reduce;
 
 pub fn register_all_ops(reg: &mut TfOpRegister) {
     reg.insert("Abs", with_T!(tractops::math::Abs));
@@ -16,17 +16,24 @@ pub fn register_all_ops(reg: &mut TfOpRegister) {
     reg.insert("Div", |_, _| Ok(Box::new(tractops::math::div::bin())));
     reg.insert("FloorMod", |_, _| Ok(Box::new(tractops::math::rem::bin())));
     reg.insert("MatMul", mat_mul);
-    reg.insert("Max", max::max);
+    reg.insert("Max", reduce::max);
     reg.insert("Maximum", |_, _| Ok(Box::new(tractops::math::max::bin())));
+    reg.insert("Mean", reduce::mean);
+    reg.insert("Min", reduce::min);
     reg.insert("Minimum", |_, _| Ok(Box::new(tractops::math::min::bin())));
     reg.insert("Less", |_, _| Ok(Box::new(tractops::logic::lesser::bin())));
     reg.insert("Log", with_T!(tractops::math::Ln));
     reg.insert("Mul", |_, _| Ok(Box::new(tractops::math::mul::bin())));
     reg.insert("Pow", |_, _| Ok(Box::new(tractops::math::pow::bin())));
+    reg.insert("Prod", reduce::prod);
     reg.insert("Neg", with_T!(tractops::math::Neg));
     reg.insert("RealDiv", |_, _| Ok(Box::new(tractops::math::div::bin())));
     reg.insert("Rsqrt", with_T!(tractops::math::Rsqrt));
+    reg.insert("Sqrt", with_T!(tractops::math::Sqrt));
+    reg.insert("Square", with_T!(tractops::math::Square));
+    reg.insert("SquaredDifference", |_, _| Ok(Box::new(tractops::math::squared_difference::bin())));
     reg.insert("Sub", |_, _| Ok(Box::new(tractops::math::sub::bin())));
+    reg.insert("Sum", reduce::sum);
     reg.insert("Tanh", with_T!(tractops::math::Tanh));
 }
 
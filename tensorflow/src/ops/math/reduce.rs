@@ -0,0 +1,187 @@
+use tract_core::internal::*;
+use tract_core::ops::nn::{Reduce as CoreReduce, Reducer};
+
+use crate::model::ParsingContext;
+use crate::tfpb::node_def::NodeDef;
+
+/// TensorFlow reductions (Max, Mean, Sum...) take the axes as a second input.
+/// Evaluated by, and translated to, the core Reduce once axes are known.
+#[derive(Debug, Clone, new)]
+pub struct Reduce {
+    keep_dims: bool,
+    reducer: Reducer,
+}
+
+pub fn max(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    reduce(pb, Reducer::Max)
+}
+
+pub fn mean(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    reduce(pb, Reducer::Mean)
+}
+
+pub fn min(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    reduce(pb, Reducer::Min)
+}
+
+pub fn prod(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    reduce(pb, Reducer::Prod)
+}
+
+pub fn sum(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    reduce(pb, Reducer::Sum)
+}
+
+fn reduce(pb: &NodeDef, reducer: Reducer) -> TractResult<Box<dyn InferenceOp>> {
+    let keep_dims = pb.get_attr_bool("keep_dims")?;
+    Ok(Box::new(Reduce::new(keep_dims, reducer)))
+}
+
+impl Reduce {
+    fn core(&self, axes: &Tensor) -> TractResult<CoreReduce> {
+        let axes = axes.cast_to::<i64>()?.as_slice::<i64>()?.to_vec();
+        Ok(CoreReduce::new(Some(axes), self.keep_dims, self.reducer))
+    }
+}
+
+impl Op for Reduce {
+    fn name(&self) -> Cow<str> {
+        format!("tf.Reduce<{:?}>", self.reducer).into()
+    }
+
+    not_a_typed_op!();
+}
+
+impl StatelessOp for Reduce {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (input, axes) = args_2!(inputs);
+        self.core(&axes)?.eval(tvec!(input))
+    }
+}
+
+impl InferenceRulesOp for Reduce {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 2)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&outputs[0].datum_type, &inputs[0].datum_type)?;
+        s.equals(&inputs[1].rank, 1)?;
+        if self.keep_dims {
+            s.equals(&inputs[0].rank, &outputs[0].rank)?;
+        } else {
+            s.equals(
+                inputs[0].rank.bex().to_dim(),
+                inputs[1].shape[0].bex() + outputs[0].rank.bex().to_dim(),
+            )?;
+        }
+        s.given_3(
+            &inputs[0].rank,
+            &outputs[0].rank,
+            &inputs[1].value,
+            move |s, irank, orank, axes| {
+                let axes: TVec<usize> = axes
+                    .cast_to::<i32>()?
+                    .as_slice::<i32>()?
+                    .iter()
+                    .map(|&ax| if ax >= 0 { ax } else { ax + irank } as usize)
+                    .collect();
+                let mut od = 0;
+                for id in 0..(irank as usize) {
+                    if axes.contains(&id) {
+                        if self.keep_dims {
+                            s.equals(&outputs[0].shape[od], 1.to_dim())?;
+                            od += 1;
+                        }
+                    } else {
+                        if od < orank as usize {
+                            s.equals(&outputs[0].shape[od], &inputs[0].shape[id])?;
+                            od += 1;
+                        }
+                    }
+                }
+                Ok(())
+            },
+        )?;
+        Ok(())
+    }
+
+    fn to_typed(
+        &self,
+        source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        if let Some(ref axes) = target.outlet_fact(mapping[&node.inputs[1]])?.konst {
+            InferenceRulesOp::to_typed(&self.core(axes)?, source, node, target, mapping)
+        } else {
+            bail!("Reduce needs axes to be const")
+        }
+    }
+
+    inference_op_as_op!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // analyse, type and run a single reduction, checking the inferred shape
+    fn run(reducer: Reducer, keep_dims: bool, input: Tensor, axes: &[i32]) -> Arc<Tensor> {
+        let mut model = InferenceModel::default();
+        let fact = TensorFact::dt_shape(input.datum_type(), input.shape().to_vec());
+        let source = OutletId::new(model.add_source("input", fact).unwrap(), 0);
+        let axes = OutletId::new(model.add_const("axes", tensor1(axes)).unwrap(), 0);
+        model.wire_node("reduce", Reduce::new(keep_dims, reducer), &[source, axes]).unwrap();
+        model.auto_outputs().unwrap();
+        model.analyse(false).unwrap();
+        let shape = model.output_fact(0).unwrap().shape.as_concrete_finite().unwrap().unwrap();
+        let model = model.into_typed().unwrap();
+        let output = SimplePlan::new(&model).unwrap().run(tvec!(input)).unwrap().remove(0);
+        assert_eq!(&*shape, output.shape());
+        output
+    }
+
+    #[test]
+    fn max() {
+        let input = tensor2(&[[-3i32, -1, -7], [-5, -2, -4]]);
+        assert_eq!(run(Reducer::Max, false, input.clone(), &[1]), rctensor1(&[-1i32, -2]));
+        assert_eq!(run(Reducer::Max, true, input.clone(), &[0]), rctensor2(&[[-3i32, -1, -4]]));
+        assert_eq!(run(Reducer::Max, false, input, &[0, 1]), rctensor0(-1i32));
+    }
+
+    #[test]
+    fn mean() {
+        let input = tensor2(&[[1f32, 3.0], [2.0, 6.0]]);
+        assert_eq!(run(Reducer::Mean, false, input.clone(), &[-1]), rctensor1(&[2f32, 4.0]));
+        assert_eq!(run(Reducer::Mean, true, input, &[0]), rctensor2(&[[1.5f32, 4.5]]));
+    }
+
+    #[test]
+    fn min() {
+        let input = tensor2(&[[1f32, 3.0], [2.0, -6.0]]);
+        assert_eq!(run(Reducer::Min, false, input.clone(), &[0]), rctensor1(&[1f32, -6.0]));
+        assert_eq!(run(Reducer::Min, true, input, &[-1]), rctensor2(&[[1f32], [-6.0]]));
+    }
+
+    #[test]
+    fn prod() {
+        let input = tensor2(&[[1i32, 3], [2, 6]]);
+        assert_eq!(run(Reducer::Prod, false, input.clone(), &[1]), rctensor1(&[3i32, 12]));
+        assert_eq!(run(Reducer::Prod, true, input, &[0, 1]), rctensor2(&[[36i32]]));
+    }
+
+    #[test]
+    fn sum() {
+        let input = tensor3(&[[[1f32, 3.0], [2.0, 6.0]], [[0.0, 1.0], [-1.0, 4.0]]]);
+        assert_eq!(run(Reducer::Sum, false, input.clone(), &[0, 2]), rctensor1(&[5f32, 11.0]));
+        assert_eq!(
+            run(Reducer::Sum, true, input, &[1]),
+            rctensor3(&[[[3f32, 9.0]], [[-1.0, 5.0]]])
+        );
+    }
+}
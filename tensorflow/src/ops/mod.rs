@@ -15,6 +15,7 @@ pub mod nn;
 pub mod quant;
 pub mod random;
 pub mod rec;
+pub mod tensor_array;
 pub mod vars;
 
 pub fn register_all_ops(reg: &mut TfOpRegister) {
@@ -26,12 +27,14 @@ pub fn register_all_ops(reg: &mut TfOpRegister) {
     quant::register_all_ops(reg);
     random::register_all_ops(reg);
     rec::register_all_ops(reg);
+    tensor_array::register_all_ops(reg);
     vars::register_all_ops(reg);
     reg.insert("Cast", cast);
     reg.insert("Const", konst);
     reg.insert("Identity", |_, _| Ok(Box::new(tract_core::ops::identity::Identity)));
     reg.insert("NoOp", |_, _| Ok(Box::new(Noop)));
     reg.insert("Placeholder", |_, _| Ok(Box::new(::tract_core::ops::source::Source::new())));
+    reg.insert("StopGradient", |_, _| Ok(Box::new(tract_core::ops::identity::Identity)));
 }
 
 fn cast(_ctx: &ParsingContext, node: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
@@ -0,0 +1,145 @@
+use tract_core::internal::*;
+use tract_core::ops::cnn::*;
+use tract_core::ops::nn::DataFormat;
+
+use crate::model::ParsingContext;
+use crate::tfpb::node_def::NodeDef;
+
+pub fn conv2d_backprop_input(
+    _ctx: &ParsingContext,
+    pb: &NodeDef,
+) -> TractResult<Box<dyn InferenceOp>> {
+    let data_format = super::data_format(pb)?;
+    let padding = super::padding(pb)?;
+    let strides = super::strides(pb)?;
+    let strides = data_format.shape(strides).hw_dims().into();
+    let dilations = pb
+        .get_attr_opt_list_int::<usize>("dilations")?
+        .map(|dilations| data_format.shape(dilations).hw_dims().into());
+    Ok(Box::new(Conv2DBackpropInput::new(data_format, padding, strides, dilations)))
+}
+
+/// Gradient of Conv2D with respect to its input, that is a transposed
+/// convolution whose output shape is given by the `input_sizes` input.
+#[derive(Debug, Clone, new)]
+pub struct Conv2DBackpropInput {
+    data_format: DataFormat,
+    padding: PaddingSpec,
+    strides: TVec<usize>,
+    dilations: Option<TVec<usize>>,
+}
+
+impl Conv2DBackpropInput {
+    fn deconv(&self, input_sizes: &Tensor) -> TractResult<Deconv> {
+        let input_sizes = input_sizes.cast_to::<i32>()?;
+        let input_sizes: TVec<usize> =
+            input_sizes.as_slice::<i32>()?.iter().map(|&d| d as usize).collect();
+        let output_shape = self.data_format.shape(input_sizes).hw_dims().into();
+        Ok(Deconv::new(
+            self.data_format,
+            KernelFormat::HWIO,
+            self.dilations.clone(),
+            None,
+            self.padding.clone(),
+            Some(self.strides.clone()),
+            None,
+            Some(output_shape),
+            1,
+        ))
+    }
+}
+
+impl Op for Conv2DBackpropInput {
+    fn name(&self) -> Cow<str> {
+        "tf.Conv2DBackpropInput".into()
+    }
+
+    not_a_typed_op!();
+}
+
+impl StatelessOp for Conv2DBackpropInput {
+    fn eval(&self, mut inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let (input_sizes, filter, out_backprop) = args_3!(inputs);
+        self.deconv(&input_sizes)?.eval(tvec!(out_backprop, filter))
+    }
+}
+
+impl InferenceRulesOp for Conv2DBackpropInput {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 3)?;
+        check_output_arity(&outputs, 1)?;
+        s.equals(&inputs[0].rank, 1)?;
+        s.equals(&inputs[0].shape[0], 4.to_dim())?;
+        s.equals(&inputs[1].rank, 4)?;
+        s.equals(&inputs[2].rank, 4)?;
+        s.equals(&outputs[0].rank, 4)?;
+        s.equals_all(wrap![&outputs[0].datum_type, &inputs[1].datum_type, &inputs[2].datum_type])?;
+        s.given(&inputs[0].value, move |s, sizes| {
+            let sizes = sizes.cast_to::<i32>()?;
+            let sizes = sizes.as_slice::<i32>()?;
+            sizes
+                .iter()
+                .enumerate()
+                .try_for_each(|(axis, dim)| s.equals(&outputs[0].shape[axis], dim.to_dim()))
+        })?;
+        Ok(())
+    }
+
+    inference_op_as_op!();
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        if let Some(input_sizes) = target.outlet_fact(mapping[&node.inputs[0]])?.konst.clone() {
+            let op = self.deconv(&input_sizes)?;
+            target.wire_node(
+                &*node.name,
+                op,
+                [mapping[&node.inputs[2]], mapping[&node.inputs[1]]].as_ref(),
+            )
+        } else {
+            bail!("Conv2DBackpropInput needs a constant input_sizes")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+    use super::*;
+    use ndarray::*;
+
+    fn verify(padding: PaddingSpec, input_sizes: &[i32], filter: &[f32], expect: &[f32]) {
+        let op = Conv2DBackpropInput::new(DataFormat::NHWC, padding, tvec!(2, 1), None);
+        let filter = Array::from_shape_vec((filter.len(), 1, 1, 1), filter.to_vec()).unwrap();
+        let result = op
+            .eval(tvec!(
+                rctensor1(input_sizes),
+                filter.into_arc_tensor(),
+                rctensor4(&[[[[1.0f32]], [[2.0]]]])
+            ))
+            .unwrap()
+            .remove(0);
+        let expect = Array::from_shape_vec((1, expect.len(), 1, 1), expect.to_vec()).unwrap();
+        assert_eq!(*result, expect.into_tensor());
+    }
+
+    #[test]
+    fn testConv2DBackpropInputValid() {
+        verify(PaddingSpec::Valid, &[1, 4, 1, 1], &[1.0, 10.0], &[1.0, 10.0, 2.0, 20.0])
+    }
+
+    #[test]
+    fn testConv2DBackpropInputSame() {
+        verify(PaddingSpec::SameUpper, &[1, 3, 1, 1], &[1.0, 10.0, 100.0], &[10.0, 102.0, 20.0])
+    }
+}
@@ -1,30 +1,66 @@
 use tract_core::internal::*;
 use tract_core::ops::cnn::PaddingSpec;
-use tract_core::ops::nn::{DataFormat, LayerSoftmax};
+use tract_core::ops::nn::{
+    DataFormat, DepthToSpace, DepthToSpaceMode, LayerSoftmax, SpaceToDepth, TopK,
+};
 
-use crate::model::TfOpRegister;
+use crate::model::{ParsingContext, TfOpRegister};
 use crate::tfpb::node_def::NodeDef;
 
 pub mod conv2d;
+pub mod conv2d_backprop_input;
 pub mod dw_conv2d;
 pub mod fused_batch_norm;
+pub mod nms;
 pub mod pools;
+pub mod resize;
 pub mod s2b;
 
 pub fn register_all_ops(reg: &mut TfOpRegister) {
     reg.insert("AvgPool", pools::avgpool);
     reg.insert("Conv2D", conv2d::conv2d);
+    reg.insert("Conv2DBackpropInput", conv2d_backprop_input::conv2d_backprop_input);
+    reg.insert("DepthToSpace", depth_to_space);
     reg.insert("DepthwiseConv2dNative", dw_conv2d::depthwise_conv2d);
     reg.insert("FusedBatchNorm", fused_batch_norm::fused_batch_norm);
     reg.insert("MaxPool", pools::maxpool);
+    reg.insert("NonMaxSuppressionV2", nms::non_max_suppression);
+    reg.insert("NonMaxSuppressionV3", nms::non_max_suppression);
+    reg.insert("NonMaxSuppressionV4", nms::non_max_suppression);
     reg.insert("Relu", |_, _| Ok(Box::new(::tract_core::ops::math::ScalarMax::new(0.0))));
     reg.insert("Relu6", |_, _| Ok(Box::new(::tract_core::ops::math::ScalarMinMax::new(6.0, 0.0))));
+    reg.insert("ResizeBicubic", resize::resize_bicubic);
+    reg.insert("ResizeBilinear", resize::resize_bilinear);
+    reg.insert("ResizeNearestNeighbor", resize::resize_nearest_neighbor);
     reg.insert("Sigmoid", with_T!(::tract_core::ops::nn::Sigmoid));
     reg.insert("Softmax", |_, _| Ok(Box::new(LayerSoftmax::new(1))));
     reg.insert("SpaceToBatchND", s2b::space_to_batch_nd);
+    reg.insert("SpaceToDepth", space_to_depth);
+    reg.insert("TopKV2", top_k_v2);
     reg.insert("BatchToSpaceND", s2b::batch_to_space_nd);
 }
 
+fn block_size(pb: &NodeDef) -> TractResult<usize> {
+    let block_size = pb.get_attr_int("block_size")?;
+    if block_size == 0 {
+        bail!("Node {} ({}) expected a positive block_size", pb.get_name(), pb.get_op())
+    }
+    Ok(block_size)
+}
+
+pub fn depth_to_space(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    Ok(Box::new(DepthToSpace::new(block_size(pb)?, DepthToSpaceMode::DCR, data_format(pb)?)))
+}
+
+pub fn space_to_depth(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    Ok(Box::new(SpaceToDepth::new(block_size(pb)?, data_format(pb)?)))
+}
+
+pub fn top_k_v2(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    let sorted = pb.get_attr_opt_bool("sorted")?.unwrap_or(true);
+    Ok(Box::new(TopK::new(-1, true, sorted, i32::datum_type())))
+}
+
 pub fn strides(pb: &NodeDef) -> TractResult<Vec<usize>> {
     let strides: Vec<usize> = pb.get_attr_list_int("strides")?;
     if strides.len() != 4 || strides[0] != 1 && strides[3] != 1 {
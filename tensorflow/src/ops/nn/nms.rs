@@ -0,0 +1,169 @@
+use tract_core::internal::*;
+use tract_core::ndarray::*;
+use tract_core::ops::nn::{select_boxes, BoxFormat};
+
+use crate::model::ParsingContext;
+use crate::tfpb::node_def::NodeDef;
+
+pub fn non_max_suppression(
+    _ctx: &ParsingContext,
+    pb: &NodeDef,
+) -> TractResult<Box<dyn InferenceOp>> {
+    let op = match pb.get_op() {
+        "NonMaxSuppressionV2" => NonMaxSuppression::new(false, false, false),
+        "NonMaxSuppressionV3" => NonMaxSuppression::new(true, false, false),
+        _ => {
+            let pad = pb.get_attr_opt_bool("pad_to_max_output_size")?.unwrap_or(false);
+            NonMaxSuppression::new(true, pad, true)
+        }
+    };
+    Ok(Box::new(op))
+}
+
+/// TensorFlow NonMaxSuppressionV2 to V4, for [boxes, 4] boxes as
+/// [y1, x1, y2, x2] and [boxes] scores. Outputs the [selected] i32 indices,
+/// and for V4 the number of valid indices.
+///
+/// Unless padded to `max_output_size`, the number of selected boxes depends
+/// on the data: it is left open during analysis, and typed as a symbol of
+/// its own.
+#[derive(Debug, Clone, new)]
+pub struct NonMaxSuppression {
+    score_threshold: bool,
+    pad_to_max_output_size: bool,
+    valid_outputs: bool,
+    #[new(default)]
+    selected_symbol: Option<char>,
+}
+
+impl NonMaxSuppression {
+    fn max_output(max_output: &Tensor) -> TractResult<usize> {
+        Ok((*max_output.cast_to::<i32>()?.to_scalar::<i32>()?).max(0) as usize)
+    }
+}
+
+impl Op for NonMaxSuppression {
+    fn name(&self) -> Cow<str> {
+        "tf.NonMaxSuppression".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl StatelessOp for NonMaxSuppression {
+    fn eval(&self, inputs: TVec<Arc<Tensor>>) -> TractResult<TVec<Arc<Tensor>>> {
+        let boxes = inputs[0].to_array_view::<f32>()?.into_dimensionality::<Ix2>()?;
+        let scores = inputs[1].to_array_view::<f32>()?.into_dimensionality::<Ix1>()?;
+        let max_output = Self::max_output(&inputs[2])?;
+        let iou_threshold = *inputs[3].to_scalar::<f32>()?;
+        let score_threshold =
+            if self.score_threshold { Some(*inputs[4].to_scalar::<f32>()?) } else { None };
+        let selected = select_boxes(
+            boxes,
+            scores,
+            BoxFormat::Corners,
+            max_output,
+            iou_threshold,
+            score_threshold,
+        );
+        let valid = selected.len();
+        let mut selected: Vec<i32> = selected.into_iter().map(|ix| ix as i32).collect();
+        if self.pad_to_max_output_size {
+            selected.resize(max_output, 0);
+        }
+        let mut outputs = tvec!(Array1::from_vec(selected).into_arc_tensor());
+        if self.valid_outputs {
+            outputs.push(rctensor0(valid as i32));
+        }
+        Ok(outputs)
+    }
+}
+
+impl InferenceRulesOp for NonMaxSuppression {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(&inputs, 4 + self.score_threshold as usize)?;
+        check_output_arity(&outputs, 1 + self.valid_outputs as usize)?;
+        s.equals(&inputs[0].datum_type, f32::datum_type())?;
+        s.equals(&inputs[1].datum_type, f32::datum_type())?;
+        s.equals(&inputs[0].rank, 2)?;
+        s.equals(&inputs[1].rank, 1)?;
+        s.equals(&inputs[0].shape[0], &inputs[1].shape[0])?;
+        s.equals(&inputs[0].shape[1], 4.to_dim())?;
+        s.equals(&outputs[0].datum_type, i32::datum_type())?;
+        s.equals(&outputs[0].rank, 1)?;
+        if self.pad_to_max_output_size {
+            s.given(&inputs[2].value, move |s, max| {
+                s.equals(&outputs[0].shape[0], Self::max_output(&max)?.to_dim())
+            })?;
+        }
+        if self.valid_outputs {
+            s.equals(&outputs[1].datum_type, i32::datum_type())?;
+            s.equals(&outputs[1].rank, 0)?;
+        }
+        Ok(())
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(1 + self.valid_outputs as usize)
+    }
+
+    inference_op_as_op!();
+
+    fn to_typed(
+        &self,
+        _source: &InferenceModel,
+        node: &InferenceNode,
+        target: &mut TypedModel,
+        mapping: &HashMap<OutletId, OutletId>,
+    ) -> TractResult<TVec<OutletId>> {
+        let inputs = node.inputs.iter().map(|m| mapping[m]).collect::<TVec<_>>();
+        let op =
+            NonMaxSuppression { selected_symbol: Some(target.unused_symbol()?), ..self.clone() };
+        target.wire_node(&*node.name, op, &*inputs)
+    }
+}
+
+impl TypedOp for NonMaxSuppression {
+    typed_op_as_op!();
+
+    fn output_facts(&self, inputs: &[&TypedTensorInfo]) -> TractResult<TVec<TypedTensorInfo>> {
+        let len = match inputs[2].konst {
+            Some(ref max) if self.pad_to_max_output_size => Self::max_output(max)?.to_dim(),
+            _ => TDim::sym(self.selected_symbol.ok_or("NonMaxSuppression needs a symbol")?),
+        };
+        let mut facts = tvec!(TypedTensorInfo::dt_shape(i32::datum_type(), [len].as_ref())?);
+        if self.valid_outputs {
+            facts.push(TypedTensorInfo::dt_shape(i32::datum_type(), [0usize; 0].as_ref())?);
+        }
+        Ok(facts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn v4_padded() {
+        let boxes = rctensor2(&[
+            [0.0f32, 0.0, 1.0, 1.0],
+            [0.0, 0.1, 1.0, 1.1],
+            [0.0, -0.1, 1.0, 0.9],
+            [0.0, 10.0, 1.0, 11.0],
+            [0.0, 10.1, 1.0, 11.1],
+            [0.0, 100.0, 1.0, 101.0],
+        ]);
+        let scores = rctensor1(&[0.9f32, 0.75, 0.6, 0.95, 0.5, 0.3]);
+        let op = NonMaxSuppression::new(true, true, true);
+        let result = op
+            .eval(tvec!(boxes, scores, rctensor0(5i32), rctensor0(0.5f32), rctensor0(0.4f32)))
+            .unwrap();
+        assert_eq!(result[0], rctensor1(&[3i32, 0, 0, 0, 0]));
+        assert_eq!(result[1], rctensor0(2i32));
+    }
+}
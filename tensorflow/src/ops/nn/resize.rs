@@ -0,0 +1,60 @@
+use tract_core::internal::*;
+use tract_core::ops::nn::{CoordTransformer, Interpolator, NearestMode, Resize};
+
+use crate::model::ParsingContext;
+use crate::tfpb::node_def::NodeDef;
+
+/// Coordinate transformation shared by all TF resize ops: `align_corners`
+/// wins over `half_pixel_centers`, which are both off by default.
+fn coord_transformer(pb: &NodeDef, half_pixel: CoordTransformer) -> TractResult<CoordTransformer> {
+    if pb.get_attr_opt_bool("align_corners")?.unwrap_or(false) {
+        Ok(CoordTransformer::AlignCorners)
+    } else if pb.get_attr_opt_bool("half_pixel_centers")?.unwrap_or(false) {
+        Ok(half_pixel)
+    } else {
+        Ok(CoordTransformer::Asymmetric)
+    }
+}
+
+fn float_only(pb: &NodeDef) -> TractResult<()> {
+    let dt = pb.get_attr_datum_type("T")?;
+    if dt != DatumType::F32 && dt != DatumType::F64 {
+        bail!("{} is only implemented for floats, got {:?}", pb.get_op(), dt)
+    }
+    Ok(())
+}
+
+/// Input is NHWC, the size input covers H and W.
+fn hw_resize(
+    coord_transformer: CoordTransformer,
+    interpolator: Interpolator,
+) -> Box<dyn InferenceOp> {
+    Box::new(Resize::new(Some(tvec!(1, 2)), coord_transformer, interpolator, None, Some(1)))
+}
+
+pub fn resize_bilinear(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    float_only(pb)?;
+    let coord_transformer = coord_transformer(pb, CoordTransformer::HalfPixel)?;
+    Ok(hw_resize(coord_transformer, Interpolator::Linear))
+}
+
+pub fn resize_bicubic(_ctx: &ParsingContext, pb: &NodeDef) -> TractResult<Box<dyn InferenceOp>> {
+    float_only(pb)?;
+    let coord_transformer = coord_transformer(pb, CoordTransformer::HalfPixel)?;
+    // TF legacy bicubic kernel uses a = -0.75, the half pixel one uses -0.5
+    let a = if coord_transformer == CoordTransformer::HalfPixel { -0.5 } else { -0.75 };
+    Ok(hw_resize(coord_transformer, Interpolator::Cubic(a)))
+}
+
+pub fn resize_nearest_neighbor(
+    _ctx: &ParsingContext,
+    pb: &NodeDef,
+) -> TractResult<Box<dyn InferenceOp>> {
+    let coord_transformer = coord_transformer(pb, CoordTransformer::TfHalfPixelForNN)?;
+    let mode = if coord_transformer == CoordTransformer::AlignCorners {
+        NearestMode::RoundPreferCeil
+    } else {
+        NearestMode::Floor
+    };
+    Ok(hw_resize(coord_transformer, Interpolator::Nearest(mode)))
+}
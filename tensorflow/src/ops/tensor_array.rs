@@ -0,0 +1,111 @@
+use tract_core::internal::*;
+
+use crate::model::{ParsingContext, TfOpRegister};
+use crate::tfpb::node_def::NodeDef;
+
+pub fn register_all_ops(reg: &mut TfOpRegister) {
+    reg.insert("TensorArrayV3", |ctx, node| tensor_array(ctx, node, TensorArrayRole::Create));
+    reg.insert("TensorArrayGatherV3", |ctx, node| tensor_array(ctx, node, TensorArrayRole::Gather));
+    reg.insert("TensorArrayReadV3", |ctx, node| tensor_array(ctx, node, TensorArrayRole::Read));
+    reg.insert("TensorArrayScatterV3", |ctx, node| {
+        tensor_array(ctx, node, TensorArrayRole::Scatter)
+    });
+    reg.insert("TensorArraySizeV3", |ctx, node| tensor_array(ctx, node, TensorArrayRole::Size));
+    reg.insert("TensorArrayWriteV3", |ctx, node| tensor_array(ctx, node, TensorArrayRole::Write));
+}
+
+fn tensor_array(
+    ctx: &ParsingContext,
+    pb: &NodeDef,
+    role: TensorArrayRole,
+) -> TractResult<Box<dyn InferenceOp>> {
+    let dtype = match role {
+        TensorArrayRole::Create | TensorArrayRole::Gather | TensorArrayRole::Read => {
+            Some(pb.get_attr_datum_type("dtype")?)
+        }
+        _ => None,
+    };
+    let arity = ctx.node_output_arities.get(pb.get_name()).cloned().unwrap_or(1);
+    Ok(Box::new(TensorArray::new(role, dtype, arity)))
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TensorArrayRole {
+    Create,
+    Gather,
+    Read,
+    Scatter,
+    Size,
+    Write,
+}
+
+/// TensorArray operations.
+///
+/// They can not be evaluated by themselves: they only exist as markers
+/// until while loop frames are rebuilt as scans, TensorArray reads and writes
+/// becoming the scanned inputs and outputs.
+#[derive(Debug, Clone, new)]
+pub struct TensorArray {
+    pub role: TensorArrayRole,
+    pub dtype: Option<DatumType>,
+    output_arity: usize,
+}
+
+impl Op for TensorArray {
+    fn name(&self) -> Cow<str> {
+        format!("tf.TensorArray{:?}", self.role).into()
+    }
+
+    not_a_typed_op!();
+}
+
+impl StatefullOp for TensorArray {
+    fn state(
+        &self,
+        _state: &mut SessionState,
+        _id: usize,
+    ) -> TractResult<Option<Box<dyn OpState>>> {
+        bail!("TensorArray operations are only supported in while loops that can be converted to Scan")
+    }
+}
+
+impl InferenceRulesOp for TensorArray {
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        let input_arity = match self.role {
+            TensorArrayRole::Create => 1,
+            TensorArrayRole::Gather | TensorArrayRole::Read => 3,
+            TensorArrayRole::Scatter | TensorArrayRole::Write => 4,
+            TensorArrayRole::Size => 2,
+        };
+        check_input_arity(&inputs, input_arity)?;
+        check_output_arity(&outputs, self.output_arity)?;
+        match self.role {
+            TensorArrayRole::Gather | TensorArrayRole::Read => {
+                if let Some(dt) = self.dtype {
+                    s.equals(&outputs[0].datum_type, dt)?;
+                }
+            }
+            TensorArrayRole::Scatter | TensorArrayRole::Write => {
+                s.equals(&outputs[0].datum_type, f32::datum_type())?;
+                s.equals(&outputs[0].rank, 0)?;
+            }
+            TensorArrayRole::Size => {
+                s.equals(&outputs[0].datum_type, i32::datum_type())?;
+                s.equals(&outputs[0].rank, 0)?;
+            }
+            TensorArrayRole::Create => (),
+        }
+        Ok(())
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(self.output_arity)
+    }
+
+    inference_op_as_op!();
+}
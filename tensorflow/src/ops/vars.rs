@@ -39,6 +39,17 @@ impl OpState for VariableV2State {
             .ok_or_else(|| format!("Could not find state for variable {}", op.id))?;
         Ok(tvec!(tensor.clone().into()))
     }
+
+    // variable values live in the session, saved by SimpleState
+    #[cfg(feature = "serialize")]
+    fn save_state(&self) -> TractResult<Vec<u8>> {
+        Ok(vec![])
+    }
+
+    #[cfg(feature = "serialize")]
+    fn load_state(&mut self, _data: &[u8]) -> TractResult<()> {
+        Ok(())
+    }
 }
 
 #[derive(Clone, Debug, new)]
@@ -150,6 +161,16 @@ impl OpState for AssignState {
         dispatch_datum!(assign(new.datum_type())(session, var_id, &new))?;
         Ok(tvec!(new))
     }
+
+    #[cfg(feature = "serialize")]
+    fn save_state(&self) -> TractResult<Vec<u8>> {
+        Ok(vec![])
+    }
+
+    #[cfg(feature = "serialize")]
+    fn load_state(&mut self, _data: &[u8]) -> TractResult<()> {
+        Ok(())
+    }
 }
 
 impl StatefullOp for Assign {
@@ -0,0 +1,271 @@
+//! Loading of TensorFlow SavedModel directories.
+//!
+//! A SavedModel directory contains a `saved_model.pb` (one or more
+//! MetaGraphDef, each identified by a set of tags) and a `variables/`
+//! checkpoint holding the values of the graph variables. Signature defs give
+//! the names of the model inputs and outputs.
+//!
+//! Only TF1-style SavedModels are supported: reference variables
+//! (`VariableV2`) stored in the checkpoint under their node name. TF2
+//! SavedModels use resource variables (`VarHandleOp`) read through function
+//! calls, with checkpoint keys coming from the object graph
+//! (`.ATTRIBUTES/VARIABLE_VALUE`), and are rejected.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use tract_core::internal::*;
+use tract_core::ops::konst::Const;
+
+use crate::checkpoint::Checkpoint;
+use crate::model::Tensorflow;
+use crate::tfpb::graph::GraphDef;
+use crate::tfpb::meta_graph::SignatureDef;
+use crate::tfpb::saved_model::SavedModel;
+
+/// The default tag set and signature used by the serving tools.
+pub const DEFAULT_TAGS: &[&str] = &["serve"];
+pub const DEFAULT_SIGNATURE: &str = "serving_default";
+
+/// A graph extracted from a SavedModel, pruned to what its signature needs.
+pub struct SavedModelGraph {
+    pub graph: GraphDef,
+    /// Input tensor names, ordered by signature key.
+    pub inputs: Vec<String>,
+    /// Output tensor names, ordered by signature key.
+    pub outputs: Vec<String>,
+    pub variables: Option<Checkpoint>,
+}
+
+fn signature_tensors(
+    map: &::std::collections::HashMap<String, crate::tfpb::meta_graph::TensorInfo>,
+) -> Vec<String> {
+    let mut keys: Vec<&String> = map.keys().collect();
+    keys.sort();
+    keys.into_iter().map(|k| map[k].get_name().to_string()).collect()
+}
+
+fn node_name(tensor: &str) -> &str {
+    tensor.trim_start_matches('^').splitn(2, ':').next().unwrap()
+}
+
+/// Keep only the nodes the outputs depend on (through data or control
+/// inputs). This gets rid of the saver and initialization subgraphs.
+fn prune(graph: &GraphDef, outputs: &[String]) -> GraphDef {
+    let nodes: HashMap<&str, usize> =
+        graph.get_node().iter().enumerate().map(|(ix, n)| (n.get_name(), ix)).collect();
+    let mut kept = HashSet::new();
+    let mut todo: Vec<&str> = outputs.iter().map(|o| node_name(o)).collect();
+    while let Some(name) = todo.pop() {
+        if let Some(&ix) = nodes.get(name) {
+            if kept.insert(ix) {
+                todo.extend(graph.get_node()[ix].get_input().iter().map(|i| node_name(i)));
+            }
+        }
+    }
+    let mut pruned = graph.clone();
+    let kept_nodes = graph
+        .get_node()
+        .iter()
+        .enumerate()
+        .filter(|(ix, _)| kept.contains(ix))
+        .map(|(_, n)| n.clone())
+        .collect();
+    pruned.set_node(kept_nodes);
+    pruned
+}
+
+impl Tensorflow {
+    /// Read the graph and variables of a SavedModel directory for the meta
+    /// graph matching `tags` and the signature `signature`.
+    pub fn saved_model_graph(
+        &self,
+        dir: impl AsRef<Path>,
+        tags: &[&str],
+        signature: &str,
+    ) -> TractResult<SavedModelGraph> {
+        let dir = dir.as_ref();
+        let pb = dir.join("saved_model.pb");
+        let mut r =
+            std::fs::File::open(&pb).map_err(|e| format!("Could not open {:?}: {}", pb, e))?;
+        let saved_model =
+            ::protobuf::parse_from_reader::<SavedModel>(&mut r).map_err(|e| format!("{:?}", e))?;
+        let wanted: HashSet<&str> = tags.iter().cloned().collect();
+        let meta_graph = saved_model
+            .get_meta_graphs()
+            .iter()
+            .find(|mg| {
+                mg.get_meta_info_def().get_tags().iter().map(|s| &**s).collect::<HashSet<_>>()
+                    == wanted
+            })
+            .ok_or_else(|| {
+                let available: Vec<_> = saved_model
+                    .get_meta_graphs()
+                    .iter()
+                    .map(|mg| mg.get_meta_info_def().get_tags().join(","))
+                    .collect();
+                format!("No meta graph with tags {:?} in SavedModel (found {:?})", tags, available)
+            })?;
+        let sig: &SignatureDef =
+            meta_graph.get_signature_def().get(signature).ok_or_else(|| {
+                let mut available: Vec<_> = meta_graph.get_signature_def().keys().collect();
+                available.sort();
+                format!("No signature {} in meta graph (found {:?})", signature, available)
+            })?;
+        let inputs = signature_tensors(sig.get_inputs());
+        let outputs = signature_tensors(sig.get_outputs());
+        let graph = prune(meta_graph.get_graph_def(), &outputs);
+        let prefix: PathBuf = dir.join("variables").join("variables");
+        let variables = if PathBuf::from(format!("{}.index", prefix.display())).exists() {
+            Some(Checkpoint::open(&prefix)?)
+        } else {
+            None
+        };
+        Ok(SavedModelGraph { graph, inputs, outputs, variables })
+    }
+
+    /// Translate a SavedModelGraph into a model, turning the variables into
+    /// constants holding their checkpointed value.
+    pub fn model_for_saved_model_graph(
+        &self,
+        smg: &SavedModelGraph,
+    ) -> TractResult<InferenceModel> {
+        let mut model = self.model_for_proto_model(&smg.graph)?;
+        for pbnode in smg.graph.get_node() {
+            if pbnode.get_op() == "VarHandleOp" {
+                bail!(
+                    "Resource variable {} found: TF2 SavedModels are not supported",
+                    pbnode.get_name()
+                )
+            }
+            if ["VariableV2", "Variable"].contains(&pbnode.get_op()) {
+                let name = pbnode.get_name();
+                let value = smg
+                    .variables
+                    .as_ref()
+                    .ok_or_else(|| format!("Variable {} found, but no checkpoint", name))?
+                    .tensor(name)?;
+                let id = model.node_by_name(name)?.id;
+                model.node_mut(id).op = Box::new(Const::for_tensor(value));
+            }
+        }
+        let outlet = |model: &InferenceModel, tensor: &String| -> TractResult<OutletId> {
+            let (node, slot) = Tensorflow::parse_input(tensor)?;
+            Ok(OutletId::new(model.node_by_name(node)?.id, slot))
+        };
+        let inputs =
+            smg.inputs.iter().map(|i| outlet(&model, i)).collect::<TractResult<Vec<_>>>()?;
+        let outputs =
+            smg.outputs.iter().map(|o| outlet(&model, o)).collect::<TractResult<Vec<_>>>()?;
+        model.set_input_outlets(&inputs)?;
+        model.set_output_outlets(&outputs)?;
+        Ok(model)
+    }
+
+    /// Build a model from a SavedModel directory.
+    pub fn model_for_saved_model(
+        &self,
+        dir: impl AsRef<Path>,
+        tags: &[&str],
+        signature: &str,
+    ) -> TractResult<InferenceModel> {
+        let smg = self.saved_model_graph(dir, tags, signature)?;
+        self.model_for_saved_model_graph(&smg)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tfpb::meta_graph::{MetaGraphDef, TensorInfo};
+    use crate::tfpb::types::DataType::DT_FLOAT;
+    use crate::tfpb::*;
+    use protobuf::Message;
+
+    fn tensor_info(name: &str) -> TensorInfo {
+        let mut info = TensorInfo::new();
+        info.set_name(name.to_string());
+        info
+    }
+
+    #[test]
+    fn load_saved_model() {
+        let dir = std::env::temp_dir().join(format!("tract-saved-model-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("variables")).unwrap();
+        let shape = tensor_f32(vec![3], vec![]).get_tensor_shape().clone();
+        let graph = graph()
+            .node(
+                node()
+                    .name("x")
+                    .op("Placeholder")
+                    .attr("dtype", DT_FLOAT)
+                    .attr("shape", shape.clone()),
+            )
+            .node(
+                node()
+                    .name("v")
+                    .op("VariableV2")
+                    .attr("dtype", DT_FLOAT)
+                    .attr("shape", shape)
+                    .attr("container", "")
+                    .attr("shared_name", ""),
+            )
+            .node(node().name("v/read").op("Identity").input("v"))
+            .node(
+                node()
+                    .name("v/init")
+                    .op("Const")
+                    .attr("dtype", DT_FLOAT)
+                    .attr("value", tensor_f32(vec![3], vec![0.0; 3])),
+            )
+            .node(node().name("v/Assign").op("Assign").input("v").input("v/init"))
+            .node(node().name("y").op("Add").input("x").input("v/read"));
+        let mut signature = SignatureDef::new();
+        signature.mut_inputs().insert("x".to_string(), tensor_info("x:0"));
+        signature.mut_outputs().insert("y".to_string(), tensor_info("y:0"));
+        let mut meta_graph = MetaGraphDef::new();
+        meta_graph.mut_meta_info_def().mut_tags().push("serve".to_string());
+        meta_graph.set_graph_def(graph);
+        meta_graph.mut_signature_def().insert(DEFAULT_SIGNATURE.to_string(), signature);
+        let mut saved_model = SavedModel::new();
+        saved_model.mut_meta_graphs().push(meta_graph);
+        std::fs::write(dir.join("saved_model.pb"), saved_model.write_to_bytes().unwrap()).unwrap();
+        crate::checkpoint::test::write_checkpoint(
+            &dir.join("variables").join("variables"),
+            &[("v", tensor1(&[1.0f32, 2.0, 3.0]))],
+        );
+
+        let tf = crate::tensorflow();
+        assert!(tf.model_for_saved_model(&dir, &["train"], DEFAULT_SIGNATURE).is_err());
+        let model = tf.model_for_path(&dir).unwrap();
+        assert!(model.node_by_name("v/Assign").is_err());
+        let plan = SimplePlan::new(model.into_optimized().unwrap()).unwrap();
+        let outputs = plan.run(tvec!(tensor1(&[10.0f32, 20.0, 30.0]))).unwrap();
+        assert_eq!(*outputs[0], tensor1(&[11.0f32, 22.0, 33.0]));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn resource_variables_are_rejected() {
+        let shape = tensor_f32(vec![3], vec![]).get_tensor_shape().clone();
+        let graph = graph()
+            .node(
+                node()
+                    .name("v")
+                    .op("VarHandleOp")
+                    .attr("dtype", DT_FLOAT)
+                    .attr("shape", shape)
+                    .attr("container", "")
+                    .attr("shared_name", "v"),
+            )
+            .node(node().name("y").op("ReadVariableOp").input("v").attr("dtype", DT_FLOAT));
+        let smg = SavedModelGraph {
+            graph,
+            inputs: vec![],
+            outputs: vec!["y:0".to_string()],
+            variables: None,
+        };
+        let err = crate::tensorflow().model_for_saved_model_graph(&smg).unwrap_err();
+        assert!(format!("{}", err).contains("TF2"));
+    }
+}
@@ -19,11 +19,16 @@
 pub mod attr_value;
 pub mod function;
 pub mod graph;
+pub mod meta_graph;
 pub mod node_def;
 pub mod op_def;
 pub mod resource_handle;
+pub mod saved_model;
+pub mod saver;
 pub mod tensor;
+pub mod tensor_bundle;
 pub mod tensor_shape;
+pub mod tensor_slice;
 pub mod types;
 pub mod versions;
 
@@ -252,6 +257,14 @@ impl From<i64> for AttrValue {
     }
 }
 
+impl From<bool> for AttrValue {
+    fn from(t: bool) -> AttrValue {
+        let mut value = attr_value::AttrValue::new();
+        value.set_b(t);
+        value
+    }
+}
+
 impl From<f32> for AttrValue {
     fn from(t: f32) -> AttrValue {
         let mut value = attr_value::AttrValue::new();
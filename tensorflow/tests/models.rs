@@ -0,0 +1,66 @@
+//! Run the frozen graphs from tests/models. They are written by the examples
+//! of the same name.
+extern crate tract_core;
+extern crate tract_tensorflow;
+
+use tract_core::internal::*;
+use tract_core::ops::nn::MeanVarianceNormalization;
+
+fn model(name: &str) -> InferenceModel {
+    tract_tensorflow::tensorflow().model_for_path(format!("tests/models/{}.pb", name)).unwrap()
+}
+
+#[test]
+fn batch_normalization() {
+    let mut model = model("batch_normalization");
+    model.set_input_fact(0, TensorFact::dt_shape(f32::datum_type(), tvec!(2, 3, 4))).unwrap();
+    let input = tract_core::ndarray::Array3::from_shape_fn((2, 3, 4), |(a, b, c)| {
+        ((a * 12 + b * 4 + c * c) as f32 * 0.7).sin()
+    })
+    .into_tensor();
+    let expected = SimplePlan::new(&model).unwrap().run(tvec!(input.clone())).unwrap();
+
+    let model = model.into_optimized().unwrap();
+    assert!(model.nodes().iter().any(|n| n.op_is::<MeanVarianceNormalization>()));
+    assert!(!model.nodes().iter().any(|n| n.name.starts_with("moments/")));
+    let found = SimplePlan::new(&model).unwrap().run(tvec!(input.clone())).unwrap();
+    found[0].close_enough(&expected[0], true).unwrap();
+
+    // layer normalization over the last axis, with gamma and beta
+    let normalized = MeanVarianceNormalization::new(tvec!(2), 1e-3)
+        .eval(tvec!(input.into_arc_tensor()))
+        .unwrap();
+    let gamma = tract_core::ndarray::arr1(&[1.0f32, 2.0, 0.5, -1.0]);
+    let beta = tract_core::ndarray::arr1(&[0.0f32, 0.5, -0.5, 1.0]);
+    let reference = normalized[0].to_array_view::<f32>().unwrap().to_owned() * &gamma + &beta;
+    found[0].close_enough(&reference.into_tensor(), true).unwrap();
+}
+
+#[test]
+fn dynamic_rnn() {
+    use tract_core::ndarray::{s, stack, Array2, Array3, Axis};
+    let mut model = model("dynamic_rnn");
+    model.set_input_fact(0, TensorFact::dt_shape(f32::datum_type(), tvec!(3, 2, 4))).unwrap();
+    model
+        .set_output_names(&["rnn/TensorArrayStack/TensorArrayGatherV3", "rnn/while/Exit_3"])
+        .unwrap();
+    let model = model.into_typed().unwrap();
+    assert!(model.nodes().iter().any(|n| n.op_is::<tract_core::ops::scan::Typed>()));
+    let model = model.into_optimized().unwrap();
+    let x = Array3::from_shape_fn((3, 2, 4), |(t, b, i)| ((t * 8 + b * 4 + i) as f32 * 0.3).cos());
+    let result = SimplePlan::new(&model).unwrap().run(tvec!(x.clone().into_tensor())).unwrap();
+
+    // h = tanh([x, h] . kernel + bias)
+    let kernel = Array2::from_shape_fn((9, 5), |(i, j)| ((i * 5 + j) as f32 * 0.37).sin() * 0.5);
+    let bias = tract_core::ndarray::arr1(&[0.1f32, -0.2, 0.3, 0.0, -0.1]);
+    let mut h = Array2::<f32>::zeros((2, 5));
+    let mut outputs = vec![];
+    for t in 0..3 {
+        let input = stack(Axis(1), &[x.slice(s![t, .., ..]), h.view()]).unwrap();
+        h = (input.dot(&kernel) + &bias).mapv(f32::tanh);
+        outputs.push(h.clone());
+    }
+    let outputs = Array3::from_shape_fn((3, 2, 5), |(t, b, i)| outputs[t][(b, i)]);
+    result[0].close_enough(&outputs.into_tensor(), true).unwrap();
+    result[1].close_enough(&h.into_tensor(), true).unwrap();
+}
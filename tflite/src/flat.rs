@@ -0,0 +1,146 @@
+//! Minimal, bounds-checked reader for the FlatBuffers binary format.
+//!
+//! Only what the TensorFlow Lite schema needs is supported: tables, scalars,
+//! strings, vectors of scalars and vectors of tables. Unions are read as the
+//! table they point to, the caller knowing what type to expect.
+
+use std::convert::TryInto;
+
+use tract_core::internal::*;
+
+/// Scalar types that can be stored in a table field or a vector.
+pub trait Scalar: Sized + Copy {
+    const SIZE: usize;
+    fn read(bytes: &[u8]) -> Self;
+}
+
+macro_rules! scalar {
+    ($t:ty) => {
+        impl Scalar for $t {
+            const SIZE: usize = std::mem::size_of::<$t>();
+            fn read(bytes: &[u8]) -> $t {
+                <$t>::from_le_bytes(bytes[..Self::SIZE].try_into().unwrap())
+            }
+        }
+    };
+}
+
+scalar!(u8);
+scalar!(i8);
+scalar!(u16);
+scalar!(i16);
+scalar!(u32);
+scalar!(i32);
+scalar!(u64);
+scalar!(i64);
+scalar!(f32);
+scalar!(f64);
+
+impl Scalar for bool {
+    const SIZE: usize = 1;
+    fn read(bytes: &[u8]) -> bool {
+        bytes[0] != 0
+    }
+}
+
+fn read<T: Scalar>(buf: &[u8], pos: usize) -> TractResult<T> {
+    let bytes = buf
+        .get(pos..pos + T::SIZE)
+        .ok_or_else(|| format!("Truncated flatbuffer (reading at {})", pos))?;
+    Ok(T::read(bytes))
+}
+
+/// Follow the unsigned offset stored at `pos`.
+fn follow(buf: &[u8], pos: usize) -> TractResult<usize> {
+    Ok(pos + read::<u32>(buf, pos)? as usize)
+}
+
+/// A table in a flatbuffer: fields are addressed by their slot, which is
+/// their declaration index in the schema.
+#[derive(Clone, Copy)]
+pub struct Table<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> std::fmt::Debug for Table<'a> {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(fmt, "Table@{}", self.pos)
+    }
+}
+
+impl<'a> Table<'a> {
+    /// The root table of a buffer.
+    pub fn root(buf: &'a [u8]) -> TractResult<Table<'a>> {
+        Ok(Table { buf, pos: follow(buf, 0)? })
+    }
+
+    fn field_pos(&self, slot: usize) -> TractResult<Option<usize>> {
+        let vtable = self.pos as isize - read::<i32>(self.buf, self.pos)? as isize;
+        if vtable < 0 {
+            bail!("Invalid flatbuffer vtable offset")
+        }
+        let vtable = vtable as usize;
+        let vtable_len = read::<u16>(self.buf, vtable)? as usize;
+        let entry = 4 + 2 * slot;
+        if entry + 2 > vtable_len {
+            return Ok(None);
+        }
+        let offset = read::<u16>(self.buf, vtable + entry)? as usize;
+        Ok(if offset == 0 { None } else { Some(self.pos + offset) })
+    }
+
+    /// Read a scalar field, or its default value if absent.
+    pub fn scalar<T: Scalar>(&self, slot: usize, default: T) -> TractResult<T> {
+        match self.field_pos(slot)? {
+            Some(pos) => read(self.buf, pos),
+            None => Ok(default),
+        }
+    }
+
+    pub fn table(&self, slot: usize) -> TractResult<Option<Table<'a>>> {
+        match self.field_pos(slot)? {
+            Some(pos) => Ok(Some(Table { buf: self.buf, pos: follow(self.buf, pos)? })),
+            None => Ok(None),
+        }
+    }
+
+    /// Position and length of a vector field.
+    fn vector(&self, slot: usize, item_size: usize) -> TractResult<Option<(usize, usize)>> {
+        if let Some(pos) = self.field_pos(slot)? {
+            let vec = follow(self.buf, pos)?;
+            let len = read::<u32>(self.buf, vec)? as usize;
+            if self.buf.len() < vec + 4 + len * item_size {
+                bail!("Truncated flatbuffer (vector at {})", vec)
+            }
+            Ok(Some((vec + 4, len)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn bytes(&self, slot: usize) -> TractResult<&'a [u8]> {
+        Ok(self.vector(slot, 1)?.map(|(pos, len)| &self.buf[pos..pos + len]).unwrap_or(&[]))
+    }
+
+    pub fn string(&self, slot: usize) -> TractResult<&'a str> {
+        Ok(std::str::from_utf8(self.bytes(slot)?).map_err(|e| format!("{:?}", e))?)
+    }
+
+    pub fn scalars<T: Scalar>(&self, slot: usize) -> TractResult<Vec<T>> {
+        Ok(self
+            .vector(slot, T::SIZE)?
+            .map(|(pos, len)| (0..len).map(|i| T::read(&self.buf[pos + i * T::SIZE..])).collect())
+            .unwrap_or(vec![]))
+    }
+
+    pub fn tables(&self, slot: usize) -> TractResult<Vec<Table<'a>>> {
+        if let Some((pos, len)) = self.vector(slot, 4)? {
+            (0..len)
+                .map(|i| Ok(Table { buf: self.buf, pos: follow(self.buf, pos + 4 * i)? }))
+                .collect()
+        } else {
+            Ok(vec![])
+        }
+    }
+}
@@ -0,0 +1,33 @@
+//! TensorFlow Lite support for tract.
+//!
+//! Models are read from the TFLite flatbuffer format. Per-tensor 8-bit
+//! quantization is supported: convolutions and fully connected layers are
+//! translated to integer convolutions, other operators work on dequantized
+//! values.
+#[allow(unused_imports)]
+#[macro_use]
+extern crate derive_new;
+#[macro_use]
+extern crate error_chain;
+#[allow(unused_imports)]
+#[macro_use]
+extern crate log;
+extern crate ndarray;
+extern crate num_traits;
+#[allow(unused_imports)]
+#[macro_use]
+extern crate tract_core;
+
+pub mod flat;
+pub mod model;
+pub mod ops;
+pub mod schema;
+pub mod tensor;
+
+pub use model::Tflite;
+
+pub fn tflite() -> Tflite {
+    let mut ops = crate::model::TfliteOpRegister::default();
+    ops::register_all_ops(&mut ops);
+    Tflite { op_register: ops }
+}
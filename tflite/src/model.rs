@@ -0,0 +1,293 @@
+use std::convert::TryFrom;
+
+use tract_core::internal::*;
+use tract_core::ops::quant::{DequantizeLinear, QuantizeLinear};
+
+use crate::schema::{self, ActivationFunctionType, BuiltinOperator};
+use crate::tensor::{load_tensor, quant_params, tensor_fact, QuantParams};
+
+/// A TensorFlow Lite model, as the flatbuffer bytes it is read from.
+pub struct TfliteProtoModel {
+    pub data: Vec<u8>,
+}
+
+impl TfliteProtoModel {
+    pub fn new(data: Vec<u8>) -> TractResult<TfliteProtoModel> {
+        let proto = TfliteProtoModel { data };
+        proto.model()?;
+        Ok(proto)
+    }
+
+    pub fn model(&self) -> TractResult<schema::Model> {
+        schema::Model::root(&self.data)
+    }
+}
+
+impl std::fmt::Debug for TfliteProtoModel {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(fmt, "TfliteProtoModel ({} bytes)", self.data.len())
+    }
+}
+
+/// Translation state of a subgraph.
+///
+/// TensorFlow Lite operators do not carry names: they read and write tensors
+/// designated by their index in the subgraph. Builders wire as many nodes as
+/// they need and return the outlets computing the operator outputs.
+pub struct ParsingContext<'a> {
+    pub model: InferenceModel,
+    pub tensors: Vec<schema::Tensor<'a>>,
+    pub buffers: Vec<schema::Buffer<'a>>,
+    pub outlets: HashMap<i32, OutletId>,
+}
+
+impl<'a> ParsingContext<'a> {
+    pub fn tensor(&self, ix: i32) -> TractResult<&schema::Tensor<'a>> {
+        self.tensors.get(ix as usize).ok_or_else(|| format!("No tensor #{}", ix).into())
+    }
+
+    pub fn tensor_name(&self, ix: i32) -> TractResult<String> {
+        let name = self.tensor(ix)?.name()?;
+        Ok(if name != "" { name.to_string() } else { format!("tensor-{}", ix) })
+    }
+
+    /// Nodes are named after the first output of the operator they come from.
+    pub fn node_name(&self, op: &schema::Operator) -> TractResult<String> {
+        self.tensor_name(*op.outputs()?.get(0).ok_or("Operator without output")?)
+    }
+
+    pub fn datum_type(&self, ix: i32) -> TractResult<DatumType> {
+        DatumType::try_from(self.tensor(ix)?.tensor_type()?)
+    }
+
+    /// Quantization parameters of a tensor, if it holds 8-bit quantized
+    /// values.
+    pub fn quantization(&self, ix: i32) -> TractResult<Option<QuantParams>> {
+        match self.datum_type(ix)? {
+            DatumType::U8 | DatumType::I8 => quant_params(self.tensor(ix)?),
+            _ => Ok(None),
+        }
+    }
+
+    /// Value of a tensor stored in the model, if any.
+    pub fn const_value(&self, ix: i32) -> TractResult<Option<Tensor>> {
+        let tensor = self.tensor(ix)?;
+        let buffer = match self.buffers.get(tensor.buffer()? as usize) {
+            Some(buffer) => buffer,
+            None => bail!("No buffer for tensor {}", self.tensor_name(ix)?),
+        };
+        let data = buffer.data()?;
+        if data.len() == 0 {
+            return Ok(None);
+        }
+        Ok(Some(load_tensor(tensor, data)?))
+    }
+
+    pub fn required_const_value(&self, ix: i32) -> TractResult<Tensor> {
+        match self.const_value(ix)? {
+            Some(value) => Ok(value),
+            None => bail!("Tensor {} is expected to be a constant", self.tensor_name(ix)?),
+        }
+    }
+
+    /// The outlet holding a tensor, creating a Const node for stored values.
+    pub fn input(&mut self, ix: i32) -> TractResult<OutletId> {
+        if let Some(outlet) = self.outlets.get(&ix) {
+            return Ok(*outlet);
+        }
+        let value = self
+            .const_value(ix)?
+            .ok_or_else(|| format!("Tensor {} is neither a model input nor computed", ix))?;
+        let id = self.model.add_const(self.tensor_name(ix)?, value)?;
+        self.outlets.insert(ix, OutletId::new(id, 0));
+        Ok(OutletId::new(id, 0))
+    }
+
+    pub fn konst(&mut self, name: impl Into<String>, value: Tensor) -> TractResult<OutletId> {
+        Ok(OutletId::new(self.model.add_const(name, value)?, 0))
+    }
+
+    /// Add a single output node.
+    pub fn add(
+        &mut self,
+        name: impl Into<String>,
+        op: impl Into<Box<dyn InferenceOp>>,
+        inputs: &[OutletId],
+    ) -> TractResult<OutletId> {
+        let id = self.model.add_node(name, op.into(), tvec!(TensorFact::default()))?;
+        for (ix, i) in inputs.iter().enumerate() {
+            self.model.add_edge(*i, InletId::new(id, ix))?;
+        }
+        Ok(OutletId::new(id, 0))
+    }
+
+    /// The outlet holding a tensor as real values: quantized tensors are
+    /// dequantized.
+    pub fn dequantized(&mut self, name: &str, ix: i32) -> TractResult<OutletId> {
+        let wire = self.input(ix)?;
+        if let Some(q) = self.quantization(ix)? {
+            let op = DequantizeLinear::new(q.scale, q.zero_point);
+            self.add(format!("{}.dequantize-{}", name, self.tensor_name(ix)?), op, &[wire])
+        } else {
+            Ok(wire)
+        }
+    }
+
+    /// Quantize real values from `wire` if the `output` tensor is quantized.
+    pub fn requantized(
+        &mut self,
+        name: &str,
+        wire: OutletId,
+        output: i32,
+    ) -> TractResult<OutletId> {
+        if let Some(q) = self.quantization(output)? {
+            let op = QuantizeLinear::new(q.scale, q.zero_point, self.datum_type(output)?);
+            self.add(format!("{}.quantize", name), op, &[wire])
+        } else {
+            Ok(wire)
+        }
+    }
+
+    /// Wire an op working on real values, followed by a fused activation:
+    /// quantized inputs are dequantized, and the result is quantized if the
+    /// `output` tensor is.
+    pub fn wire_float(
+        &mut self,
+        name: &str,
+        op: impl Into<Box<dyn InferenceOp>>,
+        inputs: &[i32],
+        activation: ActivationFunctionType,
+        output: i32,
+    ) -> TractResult<OutletId> {
+        let wires =
+            inputs.iter().map(|&i| self.dequantized(name, i)).collect::<TractResult<TVec<_>>>()?;
+        let mut wire = self.add(name, op, &wires)?;
+        if let Some(op) = activation_op(activation)? {
+            wire = self.add(format!("{}.{:?}", name, activation).to_lowercase(), op, &[wire])?;
+        }
+        self.requantized(name, wire, output)
+    }
+
+    /// Apply a fused activation function to `wire`, which computes the
+    /// `output` tensor, possibly quantized.
+    pub fn wire_activation(
+        &mut self,
+        name: &str,
+        wire: OutletId,
+        activation: ActivationFunctionType,
+        output: i32,
+    ) -> TractResult<OutletId> {
+        let op = if let Some(op) = activation_op(activation)? { op } else { return Ok(wire) };
+        let name = format!("{}.{:?}", name, activation).to_lowercase();
+        let mut wire = wire;
+        if let Some(q) = self.quantization(output)? {
+            wire = self.add(
+                format!("{}.dequantize", name),
+                DequantizeLinear::new(q.scale, q.zero_point),
+                &[wire],
+            )?;
+        }
+        let wire = self.add(&*name, op, &[wire])?;
+        self.requantized(&name, wire, output)
+    }
+}
+
+fn activation_op(activation: ActivationFunctionType) -> TractResult<Option<Box<dyn InferenceOp>>> {
+    use tract_core::ops::math::*;
+    Ok(Some(match activation {
+        ActivationFunctionType::NONE => return Ok(None),
+        ActivationFunctionType::RELU => Box::new(ScalarMax::new(0.0)),
+        ActivationFunctionType::RELU6 => Box::new(ScalarMinMax::new(6.0, 0.0)),
+        ActivationFunctionType::RELU_N1_TO_1 => Box::new(ScalarMinMax::new(1.0, -1.0)),
+        ActivationFunctionType::TANH => Box::new(Tanh::default()),
+        ActivationFunctionType::SIGN_BIT => bail!("SIGN_BIT activation is not supported"),
+    }))
+}
+
+pub type TfliteOpBuilder =
+    fn(&mut ParsingContext, op: &schema::Operator) -> TractResult<TVec<OutletId>>;
+
+#[derive(Clone, Default)]
+pub struct TfliteOpRegister(pub HashMap<BuiltinOperator, TfliteOpBuilder>);
+
+impl TfliteOpRegister {
+    pub fn insert(&mut self, op: BuiltinOperator, builder: TfliteOpBuilder) {
+        self.0.insert(op, builder);
+    }
+}
+
+pub struct Tflite {
+    pub op_register: TfliteOpRegister,
+}
+
+impl Tflite {
+    fn wire_unimplemented(
+        ctx: &mut ParsingContext,
+        op: &schema::Operator,
+        op_name: &str,
+    ) -> TractResult<TVec<OutletId>> {
+        let name = ctx.node_name(op)?;
+        let facts = op
+            .outputs()?
+            .iter()
+            .map(|&o| tensor_fact(ctx.tensor(o)?))
+            .collect::<TractResult<TVec<_>>>()?;
+        let unimpl = tract_core::ops::unimpl::UnimplementedOp::new(op_name, format!("{:?}", op));
+        let id = ctx.model.add_node(name, Box::new(unimpl) as Box<dyn InferenceOp>, facts)?;
+        for (ix, &input) in op.inputs()?.iter().filter(|&&i| i >= 0).enumerate() {
+            let outlet = ctx.input(input)?;
+            ctx.model.add_edge(outlet, InletId::new(id, ix))?;
+        }
+        Ok((0..op.outputs()?.len()).map(|ix| OutletId::new(id, ix)).collect())
+    }
+}
+
+impl Framework<TfliteProtoModel> for Tflite {
+    fn proto_model_for_read(&self, r: &mut dyn std::io::Read) -> TractResult<TfliteProtoModel> {
+        let mut data = vec![];
+        r.read_to_end(&mut data)?;
+        TfliteProtoModel::new(data)
+    }
+
+    fn model_for_proto_model(&self, proto: &TfliteProtoModel) -> TractResult<InferenceModel> {
+        let model = proto.model()?;
+        let codes = model.operator_codes()?;
+        let subgraphs = model.subgraphs()?;
+        let subgraph = subgraphs.get(0).ok_or("Model has no subgraph")?;
+        let mut ctx = ParsingContext {
+            model: InferenceModel::default(),
+            tensors: subgraph.tensors()?,
+            buffers: model.buffers()?,
+            outlets: HashMap::new(),
+        };
+        for input in subgraph.inputs()? {
+            let fact = tensor_fact(ctx.tensor(input)?)?;
+            let id = ctx.model.add_source(ctx.tensor_name(input)?, fact)?;
+            ctx.outlets.insert(input, OutletId::new(id, 0));
+        }
+        for op in subgraph.operators()? {
+            let code = codes
+                .get(op.opcode_index()? as usize)
+                .ok_or_else(|| format!("No operator code #{}", op.opcode_index().unwrap_or(0)))?;
+            let builtin = BuiltinOperator::from_code(code.builtin_code()?);
+            let outputs = match builtin.and_then(|b| self.op_register.0.get(&b)) {
+                Some(builder) => (builder)(&mut ctx, &op)?,
+                None => {
+                    let op_name = match builtin {
+                        Some(BuiltinOperator::CUSTOM) => code.custom_code()?.to_string(),
+                        Some(b) => format!("{:?}", b),
+                        None => format!("builtin-{}", code.builtin_code()?),
+                    };
+                    Self::wire_unimplemented(&mut ctx, &op, &op_name)?
+                }
+            };
+            for (&tensor, outlet) in op.outputs()?.iter().zip(outputs.into_iter()) {
+                ctx.outlets.insert(tensor, outlet);
+            }
+        }
+        let outputs =
+            subgraph.outputs()?.iter().map(|&o| ctx.input(o)).collect::<TractResult<TVec<_>>>()?;
+        ctx.model.set_output_outlets(&outputs)?;
+        Ok(ctx.model)
+    }
+}
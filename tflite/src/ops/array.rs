@@ -0,0 +1,135 @@
+use tract_core::internal::*;
+use tract_core::ops as tractops;
+use tract_core::ops::nn::Reducer;
+
+use super::{const_ints, wire_float, wire_float_unary, wire_native};
+use crate::model::{ParsingContext, TfliteOpRegister};
+use crate::schema::BuiltinOperator::*;
+use crate::schema::*;
+
+pub fn register_all_ops(reg: &mut TfliteOpRegister) {
+    reg.insert(CAST, |ctx, op| {
+        let to = ctx.datum_type(op.outputs()?[0])?;
+        wire_float_unary(ctx, op, tractops::cast::Cast::new(to))
+    });
+    reg.insert(CONCATENATION, concatenation);
+    reg.insert(DEQUANTIZE, |ctx, op| {
+        wire_float_unary(ctx, op, tractops::cast::Cast::new(DatumType::F32))
+    });
+    reg.insert(EXPAND_DIMS, expand_dims);
+    reg.insert(GATHER, gather);
+    reg.insert(MEAN, |ctx, op| reduce(ctx, op, Reducer::Mean));
+    reg.insert(PAD, pad);
+    reg.insert(PADV2, pad);
+    reg.insert(QUANTIZE, |ctx, op| {
+        wire_float_unary(ctx, op, tractops::identity::Identity::default())
+    });
+    reg.insert(REDUCE_MAX, |ctx, op| reduce(ctx, op, Reducer::Max));
+    reg.insert(REDUCE_MIN, |ctx, op| reduce(ctx, op, Reducer::Min));
+    reg.insert(REDUCE_PROD, |ctx, op| reduce(ctx, op, Reducer::Prod));
+    reg.insert(RESHAPE, reshape);
+    reg.insert(SQUEEZE, squeeze);
+    reg.insert(SUM, |ctx, op| reduce(ctx, op, Reducer::Sum));
+    reg.insert(TRANSPOSE, transpose);
+}
+
+fn rank(ctx: &ParsingContext, ix: i32) -> TractResult<i64> {
+    Ok(ctx.tensor(ix)?.shape()?.len() as i64)
+}
+
+fn concatenation(ctx: &mut ParsingContext, op: &Operator) -> TractResult<TVec<OutletId>> {
+    let options = ConcatenationOptions(op.builtin_options()?);
+    let concat = tractops::array::Concat::new(options.axis()? as i64);
+    wire_float(ctx, op, concat, options.activation()?)
+}
+
+fn expand_dims(ctx: &mut ParsingContext, op: &Operator) -> TractResult<TVec<OutletId>> {
+    let inputs = op.inputs()?;
+    let axis = const_ints(ctx, inputs[1])?[0];
+    let axis = resolve_axis(axis, rank(ctx, inputs[0])? as usize + 1)?;
+    let input = ctx.input(inputs[0])?;
+    wire_native(ctx, op, tractops::array::AddDims::new(vec![axis]), &[input])
+}
+
+fn gather(ctx: &mut ParsingContext, op: &Operator) -> TractResult<TVec<OutletId>> {
+    let axis = GatherOptions(op.builtin_options()?).axis()?;
+    let inputs = op.inputs()?;
+    let data = ctx.input(inputs[0])?;
+    let indices = ctx.input(inputs[1])?;
+    wire_native(ctx, op, tractops::array::Gather::new(axis as i64), &[data, indices])
+}
+
+/// PAD and PADV2: paddings are a constant [rank, 2] tensor. The padding value
+/// of PADV2 is a scalar in the input type, zero otherwise.
+fn pad(ctx: &mut ParsingContext, op: &Operator) -> TractResult<TVec<OutletId>> {
+    let inputs = op.inputs()?;
+    let pads = const_ints(ctx, inputs[1])?;
+    let pads = pads.chunks(2).map(|p| (p[0] as usize, p[1] as usize)).collect();
+    let value = if let Some(&value) = inputs.get(2).filter(|&&v| v >= 0) {
+        let mut value = *ctx.required_const_value(value)?.cast_to::<f32>()?.to_scalar::<f32>()?;
+        if let Some(q) = ctx.quantization(inputs[2])? {
+            value = q.scale * (value - q.zero_point as f32);
+        }
+        value
+    } else {
+        0.0
+    };
+    let pad = tractops::array::Pad::new(
+        pads,
+        tractops::array::PadMode::Constant(Arc::new(tensor0(value))),
+    );
+    wire_float_unary(ctx, op, pad)
+}
+
+fn reduce(
+    ctx: &mut ParsingContext,
+    op: &Operator,
+    reducer: Reducer,
+) -> TractResult<TVec<OutletId>> {
+    let keep_dims = ReducerOptions(op.builtin_options()?).keep_dims()?;
+    let axes = const_ints(ctx, op.inputs()?[1])?;
+    wire_float_unary(ctx, op, tractops::nn::Reduce::new(Some(axes), keep_dims, reducer))
+}
+
+/// The new shape is either the second input, or the operator options for
+/// older models.
+fn reshape(ctx: &mut ParsingContext, op: &Operator) -> TractResult<TVec<OutletId>> {
+    let inputs = op.inputs()?;
+    let input = ctx.input(inputs[0])?;
+    let shape = match inputs.get(1).filter(|&&s| s >= 0) {
+        Some(&shape) => ctx.input(shape)?,
+        None => {
+            let shape = reshape_new_shape(op.builtin_options()?)?
+                .ok_or("RESHAPE without new shape input or option")?;
+            let shape: Vec<i64> = shape.into_iter().map(|d| d as i64).collect();
+            let name = format!("{}.shape", ctx.node_name(op)?);
+            ctx.konst(name, tensor1(&shape))?
+        }
+    };
+    wire_native(ctx, op, tractops::array::Reshape::new(), &[input, shape])
+}
+
+fn squeeze(ctx: &mut ParsingContext, op: &Operator) -> TractResult<TVec<OutletId>> {
+    let input = op.inputs()?[0];
+    let rank = rank(ctx, input)?;
+    let dims = squeeze_dims(op.builtin_options()?)?;
+    let axes = if dims.len() > 0 {
+        let mut axes: Vec<usize> = dims
+            .into_iter()
+            .map(|d| resolve_axis(d as i64, rank as usize))
+            .collect::<TractResult<_>>()?;
+        axes.sort();
+        Some(axes)
+    } else {
+        None
+    };
+    let input = ctx.input(input)?;
+    wire_native(ctx, op, tractops::array::Squeeze::new(axes), &[input])
+}
+
+fn transpose(ctx: &mut ParsingContext, op: &Operator) -> TractResult<TVec<OutletId>> {
+    let inputs = op.inputs()?;
+    let perm = const_ints(ctx, inputs[1])?.into_iter().map(|a| a as usize).collect();
+    let input = ctx.input(inputs[0])?;
+    wire_native(ctx, op, tractops::array::PermuteAxes::new(Some(perm)), &[input])
+}
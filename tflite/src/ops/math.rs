@@ -0,0 +1,44 @@
+use tract_core::ops as tractops;
+
+use super::wire_float;
+use crate::model::TfliteOpRegister;
+use crate::schema::ActivationFunctionType::NONE;
+use crate::schema::ArithmeticOptions;
+use crate::schema::BuiltinOperator::*;
+
+macro_rules! arithmetic {
+    ($op:expr) => {
+        |ctx, op| {
+            let activation = ArithmeticOptions(op.builtin_options()?).activation()?;
+            wire_float(ctx, op, $op, activation)
+        }
+    };
+}
+
+macro_rules! plain {
+    ($op:expr) => {
+        |ctx, op| wire_float(ctx, op, $op, NONE)
+    };
+}
+
+pub fn register_all_ops(reg: &mut TfliteOpRegister) {
+    reg.insert(ADD, arithmetic!(tractops::math::add::bin()));
+    reg.insert(SUB, arithmetic!(tractops::math::sub::bin()));
+    reg.insert(MUL, arithmetic!(tractops::math::mul::bin()));
+    reg.insert(DIV, arithmetic!(tractops::math::div::bin()));
+
+    reg.insert(MAXIMUM, plain!(tractops::math::max::bin()));
+    reg.insert(MINIMUM, plain!(tractops::math::min::bin()));
+    reg.insert(POW, plain!(tractops::math::pow::bin()));
+
+    reg.insert(ABS, plain!(tractops::math::Abs::default()));
+    reg.insert(CEIL, plain!(tractops::math::Ceil::default()));
+    reg.insert(COS, plain!(tractops::math::Cos::default()));
+    reg.insert(EXP, plain!(tractops::math::Exp::default()));
+    reg.insert(FLOOR, plain!(tractops::math::Floor::default()));
+    reg.insert(LOG, plain!(tractops::math::Ln::default()));
+    reg.insert(NEG, plain!(tractops::math::Neg::default()));
+    reg.insert(RSQRT, plain!(tractops::math::Rsqrt::default()));
+    reg.insert(SIN, plain!(tractops::math::Sin::default()));
+    reg.insert(SQRT, plain!(tractops::math::Sqrt::default()));
+}
@@ -0,0 +1,66 @@
+use tract_core::internal::*;
+
+use crate::model::{ParsingContext, TfliteOpRegister};
+use crate::schema::{ActivationFunctionType, Operator};
+
+mod array;
+mod math;
+mod nn;
+
+pub fn register_all_ops(reg: &mut TfliteOpRegister) {
+    array::register_all_ops(reg);
+    math::register_all_ops(reg);
+    nn::register_all_ops(reg);
+}
+
+/// Wire an element-wise or shape-agnostic op on the operator inputs, in the
+/// real domain.
+pub(crate) fn wire_float(
+    ctx: &mut ParsingContext,
+    op: &Operator,
+    inference_op: impl Into<Box<dyn InferenceOp>>,
+    activation: ActivationFunctionType,
+) -> TractResult<TVec<OutletId>> {
+    let name = ctx.node_name(op)?;
+    let inputs: Vec<i32> = op.inputs()?.into_iter().filter(|&i| i >= 0).collect();
+    let output = op.outputs()?[0];
+    Ok(tvec!(ctx.wire_float(&name, inference_op, &inputs, activation, output)?))
+}
+
+/// Wire an op on the first operator input only, the others being attributes
+/// already read by the caller.
+pub(crate) fn wire_float_unary(
+    ctx: &mut ParsingContext,
+    op: &Operator,
+    inference_op: impl Into<Box<dyn InferenceOp>>,
+) -> TractResult<TVec<OutletId>> {
+    let name = ctx.node_name(op)?;
+    let input = op.inputs()?[0];
+    let output = op.outputs()?[0];
+    Ok(tvec!(ctx.wire_float(
+        &name,
+        inference_op,
+        &[input],
+        ActivationFunctionType::NONE,
+        output
+    )?))
+}
+
+/// Wire an op that only moves values around, and works directly on
+/// quantized values.
+pub(crate) fn wire_native(
+    ctx: &mut ParsingContext,
+    op: &Operator,
+    inference_op: impl Into<Box<dyn InferenceOp>>,
+    inputs: &[OutletId],
+) -> TractResult<TVec<OutletId>> {
+    let name = ctx.node_name(op)?;
+    Ok(tvec!(ctx.add(name, inference_op, inputs)?))
+}
+
+/// Read a constant integer input, as used for axes, shapes and paddings.
+pub(crate) fn const_ints(ctx: &ParsingContext, ix: i32) -> TractResult<Vec<i64>> {
+    let value = ctx.required_const_value(ix)?;
+    let value = value.cast_to::<i64>()?;
+    Ok(value.as_slice::<i64>()?.to_vec())
+}
@@ -0,0 +1,261 @@
+use tract_core::internal::*;
+use tract_core::ops as tractops;
+use tract_core::ops::cnn::{AvgPool, Conv, KernelFormat, MaxPool, PaddingSpec, PoolSpec};
+use tract_core::ops::nn::DataFormat;
+use tract_core::ops::quant::{QConv, QParams};
+
+use super::{wire_float, wire_float_unary};
+use crate::model::{ParsingContext, TfliteOpRegister};
+use crate::schema::ActivationFunctionType::NONE;
+use crate::schema::BuiltinOperator::*;
+use crate::schema::{
+    ActivationFunctionType, Conv2DOptions, DepthwiseConv2DOptions, FullyConnectedOptions,
+    LeakyReluOptions, Operator, Padding, Pool2DOptions, SoftmaxOptions,
+};
+
+pub fn register_all_ops(reg: &mut TfliteOpRegister) {
+    reg.insert(AVERAGE_POOL_2D, |ctx, op| pool_2d(ctx, op, false));
+    reg.insert(CONV_2D, conv_2d);
+    reg.insert(DEPTHWISE_CONV_2D, depthwise_conv_2d);
+    reg.insert(ELU, |ctx, op| wire_float(ctx, op, tractops::nn::Elu::new(1.0), NONE));
+    reg.insert(FULLY_CONNECTED, fully_connected);
+    reg.insert(LEAKY_RELU, |ctx, op| {
+        let alpha = LeakyReluOptions(op.builtin_options()?).alpha()?;
+        wire_float(ctx, op, tractops::nn::LeakyRelu::new(alpha), NONE)
+    });
+    reg.insert(LOG_SOFTMAX, |ctx, op| {
+        wire_float(ctx, op, tractops::nn::LayerLogSoftmax::new(-1), NONE)
+    });
+    reg.insert(LOGISTIC, |ctx, op| wire_float(ctx, op, tractops::nn::Sigmoid::default(), NONE));
+    reg.insert(MAX_POOL_2D, |ctx, op| pool_2d(ctx, op, true));
+    reg.insert(RELU, |ctx, op| wire_float(ctx, op, tractops::math::ScalarMax::new(0.0), NONE));
+    reg.insert(RELU6, |ctx, op| {
+        wire_float(ctx, op, tractops::math::ScalarMinMax::new(6.0, 0.0), NONE)
+    });
+    reg.insert(RELU_N1_TO_1, |ctx, op| {
+        wire_float(ctx, op, tractops::math::ScalarMinMax::new(1.0, -1.0), NONE)
+    });
+    reg.insert(SOFTMAX, softmax);
+    reg.insert(TANH, |ctx, op| wire_float(ctx, op, tractops::math::Tanh::default(), NONE));
+}
+
+fn padding(padding: Padding) -> PaddingSpec {
+    match padding {
+        Padding::SAME => PaddingSpec::SameUpper,
+        Padding::VALID => PaddingSpec::Valid,
+    }
+}
+
+/// Convolution geometry, spatial dimensions in HW order.
+struct Geometry {
+    padding: PaddingSpec,
+    strides: TVec<usize>,
+    dilations: TVec<usize>,
+}
+
+/// TensorFlow Lite kernel layouts, and how they map to OIHW.
+#[derive(Clone, Copy, Debug)]
+enum KernelLayout {
+    /// CONV_2D
+    OHWI,
+    /// DEPTHWISE_CONV_2D: input channels times multiplier are the last axis.
+    IHWO,
+    /// FULLY_CONNECTED: [output, input] weights.
+    OI,
+}
+
+impl KernelLayout {
+    fn to_oihw_op(&self) -> Box<dyn InferenceOp> {
+        match self {
+            KernelLayout::OHWI => {
+                Box::new(tractops::array::PermuteAxes::new(Some(vec![0, 3, 1, 2])))
+            }
+            KernelLayout::IHWO => {
+                Box::new(tractops::array::PermuteAxes::new(Some(vec![3, 0, 1, 2])))
+            }
+            KernelLayout::OI => Box::new(tractops::array::AddDims::new(vec![2, 3])),
+        }
+    }
+
+    fn to_oihw(&self, kernel: Tensor) -> TractResult<Tensor> {
+        fn permute<T: Datum>(kernel: Tensor, axes: &[usize]) -> TractResult<Tensor> {
+            Ok(kernel.into_array::<T>()?.permuted_axes(axes).into_tensor())
+        }
+        match self {
+            KernelLayout::OHWI => {
+                dispatch_datum!(permute(kernel.datum_type())(kernel, &[0, 3, 1, 2]))
+            }
+            KernelLayout::IHWO => {
+                dispatch_datum!(permute(kernel.datum_type())(kernel, &[3, 0, 1, 2]))
+            }
+            KernelLayout::OI => {
+                let shape = [kernel.shape()[0], kernel.shape()[1], 1, 1];
+                Ok(unsafe { kernel.into_shape(&shape)? })
+            }
+        }
+    }
+}
+
+/// Wire a NHWC convolution. Quantized operators translate to an integer
+/// convolution with a constant kernel and bias, and requantized output.
+fn wire_conv(
+    ctx: &mut ParsingContext,
+    op: &Operator,
+    input: OutletId,
+    layout: KernelLayout,
+    geometry: Geometry,
+    group: usize,
+    activation: ActivationFunctionType,
+) -> TractResult<OutletId> {
+    let name = ctx.node_name(op)?;
+    let inputs = op.inputs()?;
+    let output = op.outputs()?[0];
+    let bias = inputs.get(2).cloned().filter(|&b| b >= 0);
+    if let Some(qx) = ctx.quantization(inputs[0])? {
+        let qk = ctx
+            .quantization(inputs[1])?
+            .ok_or_else(|| format!("{}: quantized input, but kernel is not quantized", name))?;
+        let qo = ctx
+            .quantization(output)?
+            .ok_or_else(|| format!("{}: quantized input, but output is not quantized", name))?;
+        let kernel = layout.to_oihw(ctx.required_const_value(inputs[1])?)?;
+        let bias = bias
+            .map(|b| -> TractResult<Arc<Tensor>> {
+                Ok(ctx.required_const_value(b)?.cast_to::<i32>()?.into_owned().into_arc_tensor())
+            })
+            .transpose()?;
+        let params = QParams::per_tensor(
+            ctx.datum_type(output)?,
+            qk.zero_point,
+            qx.zero_point,
+            qo.zero_point,
+            qx.scale * qk.scale / qo.scale,
+        );
+        let conv = QConv::new(
+            DataFormat::NHWC,
+            geometry.padding,
+            geometry.dilations,
+            geometry.strides,
+            group,
+            kernel.into_arc_tensor(),
+            bias,
+            params,
+        );
+        let wire = ctx.add(&*name, conv, &[input])?;
+        ctx.wire_activation(&name, wire, activation, output)
+    } else {
+        let kernel = ctx.dequantized(&name, inputs[1])?;
+        let kernel = ctx.add(format!("{}.kernel", name), layout.to_oihw_op(), &[kernel])?;
+        let mut wires = tvec!(input, kernel);
+        if let Some(bias) = bias {
+            wires.push(ctx.input(bias)?);
+        }
+        let conv = Conv::new(
+            DataFormat::NHWC,
+            KernelFormat::OIHW,
+            Some(geometry.dilations),
+            None,
+            geometry.padding,
+            Some(geometry.strides),
+            group,
+        );
+        let wire = ctx.add(&*name, conv, &wires)?;
+        ctx.wire_activation(&name, wire, activation, output)
+    }
+}
+
+fn conv_2d(ctx: &mut ParsingContext, op: &Operator) -> TractResult<TVec<OutletId>> {
+    let options = Conv2DOptions(op.builtin_options()?);
+    let geometry = Geometry {
+        padding: padding(options.padding()?),
+        strides: tvec!(options.stride_h()? as usize, options.stride_w()? as usize),
+        dilations: tvec!(
+            options.dilation_h_factor()? as usize,
+            options.dilation_w_factor()? as usize
+        ),
+    };
+    let input = ctx.input(op.inputs()?[0])?;
+    let activation = options.activation()?;
+    Ok(tvec!(wire_conv(ctx, op, input, KernelLayout::OHWI, geometry, 1, activation)?))
+}
+
+fn depthwise_conv_2d(ctx: &mut ParsingContext, op: &Operator) -> TractResult<TVec<OutletId>> {
+    let options = DepthwiseConv2DOptions(op.builtin_options()?);
+    let geometry = Geometry {
+        padding: padding(options.padding()?),
+        strides: tvec!(options.stride_h()? as usize, options.stride_w()? as usize),
+        dilations: tvec!(
+            options.dilation_h_factor()? as usize,
+            options.dilation_w_factor()? as usize
+        ),
+    };
+    let inputs = op.inputs()?;
+    let input_shape = ctx.tensor(inputs[0])?.shape()?;
+    let channels = *input_shape.get(3).ok_or_else(|| {
+        format!("Depthwise convolution input must be NHWC, got {:?}", input_shape)
+    })?;
+    let input = ctx.input(inputs[0])?;
+    let activation = options.activation()?;
+    let conv =
+        wire_conv(ctx, op, input, KernelLayout::IHWO, geometry, channels as usize, activation)?;
+    Ok(tvec!(conv))
+}
+
+/// A fully connected layer is a 1x1 convolution over the input flattened to
+/// [batch, 1, 1, input].
+fn fully_connected(ctx: &mut ParsingContext, op: &Operator) -> TractResult<TVec<OutletId>> {
+    let options = FullyConnectedOptions(op.builtin_options()?);
+    if options.weights_format()? != 0 {
+        bail!("Only default weights format is supported for FULLY_CONNECTED")
+    }
+    let name = ctx.node_name(op)?;
+    let inputs = op.inputs()?;
+    let output = op.outputs()?[0];
+    let weights_shape = ctx.tensor(inputs[1])?.shape()?;
+    let input = ctx.input(inputs[0])?;
+    let shape =
+        ctx.konst(format!("{}.input-shape", name), tensor1(&[-1, 1, 1, weights_shape[1] as i64]))?;
+    let input =
+        ctx.add(format!("{}.input", name), tractops::array::Reshape::new(), &[input, shape])?;
+    let geometry =
+        Geometry { padding: PaddingSpec::Valid, strides: tvec!(1, 1), dilations: tvec!(1, 1) };
+    let conv = wire_conv(ctx, op, input, KernelLayout::OI, geometry, 1, options.activation()?)?;
+    let mut output_shape: Vec<i64> =
+        ctx.tensor(output)?.shape()?.iter().map(|&d| d as i64).collect();
+    output_shape[0] = -1;
+    let shape = ctx.konst(format!("{}.output-shape", name), tensor1(&output_shape))?;
+    Ok(tvec!(ctx.add(
+        format!("{}.output", name),
+        tractops::array::Reshape::new(),
+        &[conv, shape]
+    )?))
+}
+
+fn pool_2d(ctx: &mut ParsingContext, op: &Operator, max: bool) -> TractResult<TVec<OutletId>> {
+    let options = Pool2DOptions(op.builtin_options()?);
+    let spec = PoolSpec::new(
+        DataFormat::NHWC,
+        tvec!(options.filter_height()? as usize, options.filter_width()? as usize),
+        padding(options.padding()?),
+        Some(tvec!(options.stride_h()? as usize, options.stride_w()? as usize)),
+    );
+    let pool: Box<dyn InferenceOp> =
+        if max { Box::new(MaxPool::new(spec, None)) } else { Box::new(AvgPool::new(spec, false)) };
+    let name = ctx.node_name(op)?;
+    let (input, output) = (op.inputs()?[0], op.outputs()?[0]);
+    Ok(tvec!(ctx.wire_float(&name, pool, &[input], options.activation()?, output)?))
+}
+
+fn softmax(ctx: &mut ParsingContext, op: &Operator) -> TractResult<TVec<OutletId>> {
+    let beta = SoftmaxOptions(op.builtin_options()?).beta()?;
+    if beta == 1.0 {
+        return wire_float_unary(ctx, op, tractops::nn::LayerSoftmax::new(-1));
+    }
+    let name = ctx.node_name(op)?;
+    let (input, output) = (op.inputs()?[0], op.outputs()?[0]);
+    let input = ctx.dequantized(&name, input)?;
+    let beta = ctx.konst(format!("{}.beta", name), tensor0(beta))?;
+    let scaled = ctx.add(format!("{}.scaled", name), tractops::math::mul::bin(), &[input, beta])?;
+    let wire = ctx.add(&*name, tractops::nn::LayerSoftmax::new(-1), &[scaled])?;
+    Ok(tvec!(ctx.requantized(&name, wire, output)?))
+}
@@ -0,0 +1,489 @@
+//! Accessors for the TensorFlow Lite flatbuffer schema (schema.fbs, version 3).
+//!
+//! Field slots are the declaration indexes of the schema fields.
+
+use tract_core::internal::*;
+
+use crate::flat::Table;
+
+macro_rules! table {
+    ($name:ident) => {
+        #[derive(Clone, Copy, Debug)]
+        pub struct $name<'a>(pub Table<'a>);
+    };
+}
+
+macro_rules! builtin_operators {
+    ($($op:ident = $code:expr),* $(,)?) => {
+        #[allow(non_camel_case_types)]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub enum BuiltinOperator {
+            $($op = $code),*
+        }
+
+        impl BuiltinOperator {
+            pub fn from_code(code: i32) -> Option<BuiltinOperator> {
+                match code {
+                    $($code => Some(BuiltinOperator::$op),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+builtin_operators! {
+    ADD = 0,
+    AVERAGE_POOL_2D = 1,
+    CONCATENATION = 2,
+    CONV_2D = 3,
+    DEPTHWISE_CONV_2D = 4,
+    DEPTH_TO_SPACE = 5,
+    DEQUANTIZE = 6,
+    EMBEDDING_LOOKUP = 7,
+    FLOOR = 8,
+    FULLY_CONNECTED = 9,
+    HASHTABLE_LOOKUP = 10,
+    L2_NORMALIZATION = 11,
+    L2_POOL_2D = 12,
+    LOCAL_RESPONSE_NORMALIZATION = 13,
+    LOGISTIC = 14,
+    LSH_PROJECTION = 15,
+    LSTM = 16,
+    MAX_POOL_2D = 17,
+    MUL = 18,
+    RELU = 19,
+    RELU_N1_TO_1 = 20,
+    RELU6 = 21,
+    RESHAPE = 22,
+    RESIZE_BILINEAR = 23,
+    RNN = 24,
+    SOFTMAX = 25,
+    SPACE_TO_DEPTH = 26,
+    SVDF = 27,
+    TANH = 28,
+    CONCAT_EMBEDDINGS = 29,
+    SKIP_GRAM = 30,
+    CALL = 31,
+    CUSTOM = 32,
+    EMBEDDING_LOOKUP_SPARSE = 33,
+    PAD = 34,
+    UNIDIRECTIONAL_SEQUENCE_RNN = 35,
+    GATHER = 36,
+    BATCH_TO_SPACE_ND = 37,
+    SPACE_TO_BATCH_ND = 38,
+    TRANSPOSE = 39,
+    MEAN = 40,
+    SUB = 41,
+    DIV = 42,
+    SQUEEZE = 43,
+    UNIDIRECTIONAL_SEQUENCE_LSTM = 44,
+    STRIDED_SLICE = 45,
+    BIDIRECTIONAL_SEQUENCE_RNN = 46,
+    EXP = 47,
+    TOPK_V2 = 48,
+    SPLIT = 49,
+    LOG_SOFTMAX = 50,
+    DELEGATE = 51,
+    BIDIRECTIONAL_SEQUENCE_LSTM = 52,
+    CAST = 53,
+    PRELU = 54,
+    MAXIMUM = 55,
+    ARG_MAX = 56,
+    MINIMUM = 57,
+    LESS = 58,
+    NEG = 59,
+    PADV2 = 60,
+    GREATER = 61,
+    GREATER_EQUAL = 62,
+    LESS_EQUAL = 63,
+    SELECT = 64,
+    SLICE = 65,
+    SIN = 66,
+    TRANSPOSE_CONV = 67,
+    SPARSE_TO_DENSE = 68,
+    TILE = 69,
+    EXPAND_DIMS = 70,
+    EQUAL = 71,
+    NOT_EQUAL = 72,
+    LOG = 73,
+    SUM = 74,
+    SQRT = 75,
+    RSQRT = 76,
+    SHAPE = 77,
+    POW = 78,
+    ARG_MIN = 79,
+    FAKE_QUANT = 80,
+    REDUCE_PROD = 81,
+    REDUCE_MAX = 82,
+    PACK = 83,
+    LOGICAL_OR = 84,
+    ONE_HOT = 85,
+    LOGICAL_AND = 86,
+    LOGICAL_NOT = 87,
+    UNPACK = 88,
+    REDUCE_MIN = 89,
+    FLOOR_DIV = 90,
+    REDUCE_ANY = 91,
+    SQUARE = 92,
+    ZEROS_LIKE = 93,
+    FILL = 94,
+    FLOOR_MOD = 95,
+    RANGE = 96,
+    RESIZE_NEAREST_NEIGHBOR = 97,
+    LEAKY_RELU = 98,
+    SQUARED_DIFFERENCE = 99,
+    MIRROR_PAD = 100,
+    ABS = 101,
+    SPLIT_V = 102,
+    UNIQUE = 103,
+    CEIL = 104,
+    REVERSE_V2 = 105,
+    ADD_N = 106,
+    GATHER_ND = 107,
+    COS = 108,
+    WHERE = 109,
+    RANK = 110,
+    ELU = 111,
+    REVERSE_SEQUENCE = 112,
+    MATRIX_DIAG = 113,
+    QUANTIZE = 114,
+    MATRIX_SET_DIAG = 115,
+    ROUND = 116,
+    HARD_SWISH = 117,
+    IF = 118,
+    WHILE = 119,
+    NON_MAX_SUPPRESSION_V4 = 120,
+    NON_MAX_SUPPRESSION_V5 = 121,
+    SCATTER_ND = 122,
+    SELECT_V2 = 123,
+    DENSIFY = 124,
+    SEGMENT_SUM = 125,
+    BATCH_MATMUL = 126,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TensorType {
+    FLOAT32 = 0,
+    FLOAT16 = 1,
+    INT32 = 2,
+    UINT8 = 3,
+    INT64 = 4,
+    STRING = 5,
+    BOOL = 6,
+    INT16 = 7,
+    COMPLEX64 = 8,
+    INT8 = 9,
+    FLOAT64 = 10,
+}
+
+impl TensorType {
+    pub fn from_code(code: i8) -> TractResult<TensorType> {
+        use self::TensorType::*;
+        Ok(match code {
+            0 => FLOAT32,
+            1 => FLOAT16,
+            2 => INT32,
+            3 => UINT8,
+            4 => INT64,
+            5 => STRING,
+            6 => BOOL,
+            7 => INT16,
+            8 => COMPLEX64,
+            9 => INT8,
+            10 => FLOAT64,
+            _ => bail!("Unknown TensorType {}", code),
+        })
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Padding {
+    SAME = 0,
+    VALID = 1,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ActivationFunctionType {
+    NONE = 0,
+    RELU = 1,
+    RELU_N1_TO_1 = 2,
+    RELU6 = 3,
+    TANH = 4,
+    SIGN_BIT = 5,
+}
+
+fn padding(code: i8) -> TractResult<Padding> {
+    match code {
+        0 => Ok(Padding::SAME),
+        1 => Ok(Padding::VALID),
+        _ => bail!("Unknown Padding {}", code),
+    }
+}
+
+fn activation(code: i8) -> TractResult<ActivationFunctionType> {
+    use self::ActivationFunctionType::*;
+    Ok(match code {
+        0 => NONE,
+        1 => RELU,
+        2 => RELU_N1_TO_1,
+        3 => RELU6,
+        4 => TANH,
+        5 => SIGN_BIT,
+        _ => bail!("Unknown ActivationFunctionType {}", code),
+    })
+}
+
+table!(Model);
+
+impl<'a> Model<'a> {
+    pub fn root(buf: &'a [u8]) -> TractResult<Model<'a>> {
+        if buf.get(4..8) != Some(b"TFL3") {
+            bail!("Not a TensorFlow Lite model (missing TFL3 file identifier)")
+        }
+        Ok(Model(Table::root(buf)?))
+    }
+    pub fn version(&self) -> TractResult<u32> {
+        self.0.scalar(0, 0)
+    }
+    pub fn operator_codes(&self) -> TractResult<Vec<OperatorCode<'a>>> {
+        Ok(self.0.tables(1)?.into_iter().map(OperatorCode).collect())
+    }
+    pub fn subgraphs(&self) -> TractResult<Vec<SubGraph<'a>>> {
+        Ok(self.0.tables(2)?.into_iter().map(SubGraph).collect())
+    }
+    pub fn description(&self) -> TractResult<&'a str> {
+        self.0.string(3)
+    }
+    pub fn buffers(&self) -> TractResult<Vec<Buffer<'a>>> {
+        Ok(self.0.tables(4)?.into_iter().map(Buffer).collect())
+    }
+}
+
+table!(OperatorCode);
+
+impl<'a> OperatorCode<'a> {
+    /// The operator code. Newer files store codes above 127 in a second,
+    /// 32-bit field, keeping the historical byte field for smaller ones.
+    pub fn builtin_code(&self) -> TractResult<i32> {
+        let deprecated = self.0.scalar::<i8>(0, 0)? as i32;
+        Ok(deprecated.max(self.0.scalar::<i32>(3, 0)?))
+    }
+    pub fn custom_code(&self) -> TractResult<&'a str> {
+        self.0.string(1)
+    }
+    pub fn version(&self) -> TractResult<i32> {
+        self.0.scalar(2, 1)
+    }
+}
+
+table!(SubGraph);
+
+impl<'a> SubGraph<'a> {
+    pub fn tensors(&self) -> TractResult<Vec<Tensor<'a>>> {
+        Ok(self.0.tables(0)?.into_iter().map(Tensor).collect())
+    }
+    pub fn inputs(&self) -> TractResult<Vec<i32>> {
+        self.0.scalars(1)
+    }
+    pub fn outputs(&self) -> TractResult<Vec<i32>> {
+        self.0.scalars(2)
+    }
+    pub fn operators(&self) -> TractResult<Vec<Operator<'a>>> {
+        Ok(self.0.tables(3)?.into_iter().map(Operator).collect())
+    }
+    pub fn name(&self) -> TractResult<&'a str> {
+        self.0.string(4)
+    }
+}
+
+table!(Tensor);
+
+impl<'a> Tensor<'a> {
+    pub fn shape(&self) -> TractResult<Vec<i32>> {
+        self.0.scalars(0)
+    }
+    pub fn tensor_type(&self) -> TractResult<TensorType> {
+        TensorType::from_code(self.0.scalar(1, 0)?)
+    }
+    /// Index in the model buffers. Buffer 0 is the empty sentinel buffer.
+    pub fn buffer(&self) -> TractResult<u32> {
+        self.0.scalar(2, 0)
+    }
+    pub fn name(&self) -> TractResult<&'a str> {
+        self.0.string(3)
+    }
+    pub fn quantization(&self) -> TractResult<Option<QuantizationParameters<'a>>> {
+        Ok(self.0.table(4)?.map(QuantizationParameters))
+    }
+    pub fn is_variable(&self) -> TractResult<bool> {
+        self.0.scalar(5, false)
+    }
+}
+
+table!(QuantizationParameters);
+
+impl<'a> QuantizationParameters<'a> {
+    pub fn min(&self) -> TractResult<Vec<f32>> {
+        self.0.scalars(0)
+    }
+    pub fn max(&self) -> TractResult<Vec<f32>> {
+        self.0.scalars(1)
+    }
+    pub fn scale(&self) -> TractResult<Vec<f32>> {
+        self.0.scalars(2)
+    }
+    pub fn zero_point(&self) -> TractResult<Vec<i64>> {
+        self.0.scalars(3)
+    }
+    pub fn quantized_dimension(&self) -> TractResult<i32> {
+        self.0.scalar(6, 0)
+    }
+}
+
+table!(Operator);
+
+impl<'a> Operator<'a> {
+    pub fn opcode_index(&self) -> TractResult<u32> {
+        self.0.scalar(0, 0)
+    }
+    /// Input tensor indexes, -1 standing for an omitted optional input.
+    pub fn inputs(&self) -> TractResult<Vec<i32>> {
+        self.0.scalars(1)
+    }
+    pub fn outputs(&self) -> TractResult<Vec<i32>> {
+        self.0.scalars(2)
+    }
+    /// The builtin options union value. Its type is implied by the operator.
+    pub fn builtin_options(&self) -> TractResult<Option<Table<'a>>> {
+        self.0.table(4)
+    }
+}
+
+table!(Buffer);
+
+impl<'a> Buffer<'a> {
+    pub fn data(&self) -> TractResult<&'a [u8]> {
+        self.0.bytes(0)
+    }
+}
+
+/// Builtin options tables. They are all optional in the operator: a missing
+/// table reads as all default values.
+macro_rules! options {
+    ($name:ident { $($field:ident : $t:ty = $default:expr ; $slot:expr),* $(,)? }) => {
+        #[derive(Clone, Copy, Debug)]
+        pub struct $name<'a>(pub Option<Table<'a>>);
+
+        impl<'a> $name<'a> {
+            $(
+                pub fn $field(&self) -> TractResult<$t> {
+                    self.0.map(|t| t.scalar($slot, $default)).unwrap_or(Ok($default))
+                }
+            )*
+        }
+    };
+}
+
+options!(Conv2DOptions {
+    padding_code: i8 = 0; 0,
+    stride_w: i32 = 0; 1,
+    stride_h: i32 = 0; 2,
+    activation_code: i8 = 0; 3,
+    dilation_w_factor: i32 = 1; 4,
+    dilation_h_factor: i32 = 1; 5,
+});
+
+options!(DepthwiseConv2DOptions {
+    padding_code: i8 = 0; 0,
+    stride_w: i32 = 0; 1,
+    stride_h: i32 = 0; 2,
+    depth_multiplier: i32 = 0; 3,
+    activation_code: i8 = 0; 4,
+    dilation_w_factor: i32 = 1; 5,
+    dilation_h_factor: i32 = 1; 6,
+});
+
+options!(Pool2DOptions {
+    padding_code: i8 = 0; 0,
+    stride_w: i32 = 0; 1,
+    stride_h: i32 = 0; 2,
+    filter_width: i32 = 0; 3,
+    filter_height: i32 = 0; 4,
+    activation_code: i8 = 0; 5,
+});
+
+options!(FullyConnectedOptions {
+    activation_code: i8 = 0; 0,
+    weights_format: i8 = 0; 1,
+    keep_num_dims: bool = false; 2,
+});
+
+// Shared by ADD, SUB, MUL and DIV.
+options!(ArithmeticOptions {
+    activation_code: i8 = 0; 0,
+});
+
+options!(ConcatenationOptions {
+    axis: i32 = 0; 0,
+    activation_code: i8 = 0; 1,
+});
+
+options!(SoftmaxOptions {
+    beta: f32 = 0.0; 0,
+});
+
+options!(ReducerOptions {
+    keep_dims: bool = false; 0,
+});
+
+options!(LeakyReluOptions {
+    alpha: f32 = 0.0; 0,
+});
+
+options!(GatherOptions {
+    axis: i32 = 0; 0,
+});
+
+macro_rules! padding_and_activation {
+    ($($name:ident),*) => {
+        $(
+            impl<'a> $name<'a> {
+                pub fn padding(&self) -> TractResult<Padding> {
+                    padding(self.padding_code()?)
+                }
+                pub fn activation(&self) -> TractResult<ActivationFunctionType> {
+                    activation(self.activation_code()?)
+                }
+            }
+        )*
+    };
+}
+
+padding_and_activation!(Conv2DOptions, DepthwiseConv2DOptions, Pool2DOptions);
+
+macro_rules! fused_activation {
+    ($($name:ident),*) => {
+        $(
+            impl<'a> $name<'a> {
+                pub fn activation(&self) -> TractResult<ActivationFunctionType> {
+                    activation(self.activation_code()?)
+                }
+            }
+        )*
+    };
+}
+
+fused_activation!(FullyConnectedOptions, ArithmeticOptions, ConcatenationOptions);
+
+/// Reshape new shape and squeeze dims are vectors, read them directly.
+pub fn reshape_new_shape(options: Option<Table>) -> TractResult<Option<Vec<i32>>> {
+    Ok(options.map(|t| t.scalars::<i32>(0)).transpose()?.filter(|s| s.len() > 0))
+}
+
+pub fn squeeze_dims(options: Option<Table>) -> TractResult<Vec<i32>> {
+    Ok(options.map(|t| t.scalars::<i32>(0)).transpose()?.unwrap_or(vec![]))
+}
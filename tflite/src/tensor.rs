@@ -0,0 +1,77 @@
+use std::convert::TryFrom;
+
+use tract_core::internal::*;
+
+use crate::schema::{self, TensorType};
+
+impl TryFrom<TensorType> for DatumType {
+    type Error = TractError;
+    fn try_from(t: TensorType) -> TractResult<DatumType> {
+        match t {
+            TensorType::FLOAT32 => Ok(DatumType::F32),
+            TensorType::FLOAT16 => Ok(DatumType::F16),
+            TensorType::FLOAT64 => Ok(DatumType::F64),
+            TensorType::INT8 => Ok(DatumType::I8),
+            TensorType::INT16 => Ok(DatumType::I16),
+            TensorType::INT32 => Ok(DatumType::I32),
+            TensorType::INT64 => Ok(DatumType::I64),
+            TensorType::UINT8 => Ok(DatumType::U8),
+            TensorType::BOOL => Ok(DatumType::Bool),
+            TensorType::STRING => Ok(DatumType::String),
+            TensorType::COMPLEX64 => bail!("Complex tensors are not supported"),
+        }
+    }
+}
+
+/// Per-tensor quantization: a quantized value `q` stands for the real value
+/// `scale * (q - zero_point)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuantParams {
+    pub scale: f32,
+    pub zero_point: i32,
+}
+
+/// Quantization parameters of a tensor, if it is quantized.
+pub fn quant_params(tensor: &schema::Tensor) -> TractResult<Option<QuantParams>> {
+    let q = if let Some(q) = tensor.quantization()? { q } else { return Ok(None) };
+    let scale = q.scale()?;
+    let zero_point = q.zero_point()?;
+    match scale.len() {
+        0 => Ok(None),
+        1 => Ok(Some(QuantParams {
+            scale: scale[0],
+            zero_point: zero_point.get(0).cloned().unwrap_or(0) as i32,
+        })),
+        _ => bail!(
+            "Tensor {} uses per-axis quantization, only per-tensor quantization is supported",
+            tensor.name()?
+        ),
+    }
+}
+
+pub fn tensor_fact(tensor: &schema::Tensor) -> TractResult<TensorFact> {
+    let dt = DatumType::try_from(tensor.tensor_type()?)?;
+    let shape: TVec<usize> = tensor.shape()?.iter().map(|&d| d as usize).collect();
+    Ok(TensorFact::dt_shape(dt, shape))
+}
+
+/// Decode a tensor value from its buffer data (little endian, row major).
+pub fn load_tensor(tensor: &schema::Tensor, data: &[u8]) -> TractResult<Tensor> {
+    let dt = DatumType::try_from(tensor.tensor_type()?)?;
+    if dt == DatumType::String {
+        bail!("Constant string tensors are not supported ({})", tensor.name()?)
+    }
+    let shape: TVec<usize> = tensor.shape()?.iter().map(|&d| d as usize).collect();
+    let len = shape.iter().product::<usize>() * dt.size_of();
+    if data.len() != len {
+        bail!(
+            "Tensor {} ({:?} {:?}) expects {} bytes of data, found {}",
+            tensor.name()?,
+            dt,
+            shape,
+            len,
+            data.len()
+        )
+    }
+    unsafe { Tensor::from_raw_dt(dt, &shape, data) }
+}
@@ -0,0 +1,60 @@
+//! Run the models from tests/models against reference outputs. The models
+//! and the outputs come from tests/models/generate.py.
+extern crate tract_core;
+extern crate tract_tflite;
+
+use tract_core::internal::*;
+use tract_core::ops::cnn::Conv;
+use tract_core::ops::quant::{DequantizeLinear, QConv, QuantizeLinear};
+
+fn model(name: &str) -> InferenceModel {
+    tract_tflite::tflite().model_for_path(format!("tests/models/{}.tflite", name)).unwrap()
+}
+
+fn run(model: InferenceModel, input: Tensor) -> Arc<Tensor> {
+    let plan = SimplePlan::new(model.into_optimized().unwrap()).unwrap();
+    plan.run(tvec!(input)).unwrap().remove(0)
+}
+
+fn count<O: Op>(model: &InferenceModel) -> usize {
+    model.nodes().iter().filter(|n| n.op_is::<O>()).count()
+}
+
+#[test]
+fn conv_add_f32() {
+    let model = model("conv_add_f32");
+    assert_eq!(count::<Conv>(&model), 1);
+    let input = tensor1(&[
+        -1.25f32, 0.5, -0.5, 1.25, 0.25, -0.75, 1.0, 0.0, -1.0, 0.75, -0.25, -1.25, 0.5, -0.5,
+        1.25, 0.25, -0.75, 1.0, 0.0, -1.0, 0.75, -0.25, -1.25, 0.5, -0.5, 1.25, 0.25, -0.75, 1.0,
+        0.0, -1.0, 0.75,
+    ]);
+    let input = unsafe { input.into_shape(&[1, 4, 4, 2]).unwrap() };
+    let expected = tensor1(&[
+        2.625f32, 2.5, 0.4375, 1.0, 2.5, 0.0, 1.0, 3.4375, 0.0, 1.9375, 2.5, 0.125, 1.0, 3.6875,
+        0.0, 1.0, 2.5, 0.3125, 1.9375, 2.5, 0.15625, 1.0, 3.5625, 0.0, 1.9375, 2.5, 1.71875,
+    ]);
+    let expected = unsafe { expected.into_shape(&[1, 3, 3, 3]).unwrap() };
+    let output = run(model, input);
+    assert!(output.close_enough(&expected, true).is_ok(), "{:?} != {:?}", output, expected);
+}
+
+/// The convolution runs on integers, rescaling its accumulator by
+/// `scale(x) * scale(kernel) / scale(conv)` in fixed point, and rounding
+/// twice like TensorFlow Lite does (14.44 becomes 15 for the first value).
+/// Its fused relu and the following addition work on dequantized values, the
+/// addition result being quantized again with its own parameters.
+#[test]
+fn conv_add_u8() {
+    let model = model("conv_add_u8");
+    assert_eq!(count::<QConv>(&model), 1);
+    assert_eq!(count::<DequantizeLinear>(&model), 3);
+    assert_eq!(count::<QuantizeLinear>(&model), 2);
+    let input = tensor1(&[
+        122u8, 129, 123, 130, 124, 131, 125, 132, 126, 133, 127, 134, 128, 122, 129, 123, 130, 124,
+    ]);
+    let input = unsafe { input.into_shape(&[1, 3, 3, 2]).unwrap() };
+    let expected = tensor1(&[83u8, 90, 83, 87, 44, 102, 44, 99]);
+    let expected = unsafe { expected.into_shape(&[1, 2, 2, 2]).unwrap() };
+    assert_eq!(*run(model, input), expected);
+}
@@ -0,0 +1,240 @@
+#!/usr/bin/env python3
+"""Writes the small TensorFlow Lite models used by tflite/tests/models.rs.
+
+There is no flatbuffers dependency: the few tables the models need are laid
+out by hand, children after their parents as uoffsets are unsigned. The
+expected outputs are computed here too, with TensorFlow Lite semantics, and
+printed for inclusion in the tests.
+
+    python3 generate.py
+"""
+
+import math
+import os
+import struct
+
+SCALARS = {"u8": "<B", "i8": "<b", "bool": "<B", "u16": "<H", "i32": "<i", "u32": "<I",
+           "f32": "<f", "i64": "<q"}
+
+
+class Table:
+    def __init__(self, *fields):
+        # fields: (slot, kind, value), kind is a scalar type or "off"
+        self.fields = fields
+
+
+class Vector:
+    def __init__(self, kind, items, align=4):
+        self.kind = kind
+        self.items = items
+        self.align = align
+
+
+class String:
+    def __init__(self, s):
+        self.s = s.encode()
+
+
+def size_of(kind):
+    return 4 if kind == "off" else struct.calcsize(SCALARS[kind])
+
+
+class Builder:
+    def __init__(self):
+        self.buf = bytearray()
+
+    def pad_to(self, align, extra=0):
+        while (len(self.buf) + extra) % align:
+            self.buf.append(0)
+
+    def patch(self, pos, target):
+        struct.pack_into("<I", self.buf, pos, target - pos)
+
+    def place(self, obj):
+        children = []
+        if isinstance(obj, String):
+            self.pad_to(4)
+            pos = len(self.buf)
+            self.buf += struct.pack("<I", len(obj.s)) + obj.s + b"\0"
+        elif isinstance(obj, Vector):
+            self.pad_to(max(obj.align, size_of(obj.kind)), 4)
+            pos = len(self.buf)
+            self.buf += struct.pack("<I", len(obj.items))
+            for item in obj.items:
+                if obj.kind == "off":
+                    children.append((len(self.buf), item))
+                    self.buf += b"\0\0\0\0"
+                else:
+                    self.buf += struct.pack(SCALARS[obj.kind], item)
+        else:
+            slots = max([f[0] for f in obj.fields], default=-1) + 1
+            self.pad_to(2)
+            vtable = len(self.buf)
+            self.buf += bytes(4 + 2 * slots)
+            self.pad_to(8)
+            pos = len(self.buf)
+            self.buf += struct.pack("<i", pos - vtable)
+            offsets = {}
+            for slot, kind, value in obj.fields:
+                self.pad_to(size_of(kind))
+                offsets[slot] = len(self.buf) - pos
+                if kind == "off":
+                    children.append((len(self.buf), value))
+                    self.buf += b"\0\0\0\0"
+                else:
+                    self.buf += struct.pack(SCALARS[kind], value)
+            struct.pack_into("<HH", self.buf, vtable, 4 + 2 * slots, len(self.buf) - pos)
+            for slot, offset in offsets.items():
+                struct.pack_into("<H", self.buf, vtable + 4 + 2 * slot, offset)
+        for field, child in children:
+            self.patch(field, self.place(child))
+        return pos
+
+    def finish(self, root):
+        self.buf += b"\0\0\0\0TFL3"
+        self.patch(0, self.place(root))
+        return bytes(self.buf)
+
+
+FLOAT32, INT32, UINT8 = 0, 2, 3
+ADD, CONV_2D = 0, 3
+ADD_OPTIONS, CONV_2D_OPTIONS = 11, 1
+NONE, RELU, RELU6 = 0, 1, 3
+VALID = 1
+
+
+def tensor(name, shape, ty, buffer=0, scale=None, zero_point=None):
+    fields = [(0, "off", Vector("i32", shape)), (1, "i8", ty), (2, "u32", buffer),
+              (3, "off", String(name))]
+    if scale is not None:
+        fields.append((4, "off", Table((2, "off", Vector("f32", [scale])),
+                                       (3, "off", Vector("i64", [zero_point])))))
+    return Table(*fields)
+
+
+def operator(opcode, inputs, outputs, options_type, options):
+    return Table((0, "u32", opcode), (1, "off", Vector("i32", inputs)),
+                 (2, "off", Vector("i32", outputs)), (3, "u8", options_type),
+                 (4, "off", options))
+
+
+def conv_options(activation):
+    return Table((0, "i8", VALID), (1, "i32", 1), (2, "i32", 1), (3, "i8", activation))
+
+
+def model(tensors, inputs, outputs, operators, buffers):
+    codes = [Table((0, "i8", ADD), (3, "i32", ADD)), Table((0, "i8", CONV_2D), (3, "i32", CONV_2D))]
+    subgraph = Table((0, "off", Vector("off", tensors)), (1, "off", Vector("i32", inputs)),
+                     (2, "off", Vector("i32", outputs)), (3, "off", Vector("off", operators)))
+    buffers = [Table()] + [Table((0, "off", Vector("u8", list(b), 16))) for b in buffers]
+    return Builder().finish(Table((0, "u32", 3), (1, "off", Vector("off", codes)),
+                                  (2, "off", Vector("off", [subgraph])),
+                                  (4, "off", Vector("off", buffers))))
+
+
+def pack(fmt, values):
+    return b"".join(struct.pack(fmt, v) for v in values)
+
+
+def conv_nhwc(x, x_shape, k, k_shape, bias):
+    """VALID, stride 1 convolution, OHWI kernel."""
+    _, h, w, c = x_shape
+    o, kh, kw, _ = k_shape
+    result = []
+    for y in range(h - kh + 1):
+        for xx in range(w - kw + 1):
+            for oc in range(o):
+                acc = bias[oc]
+                for dy in range(kh):
+                    for dx in range(kw):
+                        for ic in range(c):
+                            acc += (x[((y + dy) * w + xx + dx) * c + ic]
+                                    * k[((oc * kh + dy) * kw + dx) * c + ic])
+                result.append(acc)
+    return result
+
+
+def float_model():
+    """x -> CONV_2D (relu) -> ADD c (relu6)"""
+    x = [(i * 7 % 11 - 5) / 4.0 for i in range(32)]
+    k = [(i * 5 % 9 - 4) / 8.0 for i in range(24)]
+    bias = [0.5, -0.25, 1.0]
+    c = [1.0, 2.5, -0.5]
+    tensors = [
+        tensor("x", [1, 4, 4, 2], FLOAT32),
+        tensor("kernel", [3, 2, 2, 2], FLOAT32, 1),
+        tensor("bias", [3], FLOAT32, 2),
+        tensor("conv", [1, 3, 3, 3], FLOAT32),
+        tensor("c", [3], FLOAT32, 3),
+        tensor("y", [1, 3, 3, 3], FLOAT32),
+    ]
+    operators = [
+        operator(1, [0, 1, 2], [3], CONV_2D_OPTIONS, conv_options(RELU)),
+        operator(0, [3, 4], [5], ADD_OPTIONS, Table((0, "i8", RELU6))),
+    ]
+    data = model(tensors, [0], [5], operators, [pack("<f", k), pack("<f", bias), pack("<f", c)])
+    conv = [max(0.0, v) for v in conv_nhwc(x, [1, 4, 4, 2], k, [3, 2, 2, 2], bias)]
+    y = [min(6.0, max(0.0, v + c[i % 3])) for i, v in enumerate(conv)]
+    return data, x, y
+
+
+def multiply_by_quantized_multiplier(x, multiplier):
+    """TensorFlow Lite integer rescaling: a rounding doubling high multiply by
+    a 31 bits fixed point multiplier, then a rounding right shift."""
+    frac, exponent = math.frexp(multiplier)
+    q = int(round(frac * (1 << 31)))
+    if q == 1 << 31:
+        q, exponent = q // 2, exponent + 1
+    x = x << max(exponent, 0)
+    ab = x * q
+    nudge = (1 << 30) if ab >= 0 else 1 - (1 << 30)
+    high = abs(ab + nudge) >> 31
+    high = high if ab + nudge >= 0 else -high
+    shift = max(-exponent, 0)
+    mask = (1 << shift) - 1
+    threshold = (mask >> 1) + (1 if high < 0 else 0)
+    return (high >> shift) + (1 if (high & mask) > threshold else 0)
+
+
+def quant(v, scale, zero_point):
+    q = v / scale
+    assert abs(q - int(q) - 0.5) > 0.01 and abs(q - int(q) + 0.5) > 0.01, "tie at %s" % q
+    return min(255, max(0, int(round(q)) + zero_point))
+
+
+def quant_model():
+    """uint8 x -> CONV_2D (relu) -> ADD c, with quantization parameters
+    changing at each step. The addition is computed on real values."""
+    x = [(i * 7) % 13 + 122 for i in range(18)]
+    k = [(i * 5) % 9 + 116 for i in range(16)]
+    bias = [20, 75]
+    c = [3, 25]
+    sx, zx, sk, zk, sconv, zconv, sc, zc, sy, zy = 0.5, 128, 0.25, 120, 0.9, 100, 0.5, 10, 0.3, 50
+    tensors = [
+        tensor("x", [1, 3, 3, 2], UINT8, 0, sx, zx),
+        tensor("kernel", [2, 2, 2, 2], UINT8, 1, sk, zk),
+        tensor("bias", [2], INT32, 2, sx * sk, 0),
+        tensor("conv", [1, 2, 2, 2], UINT8, 0, sconv, zconv),
+        tensor("c", [2], UINT8, 3, sc, zc),
+        tensor("y", [1, 2, 2, 2], UINT8, 0, sy, zy),
+    ]
+    operators = [
+        operator(1, [0, 1, 2], [3], CONV_2D_OPTIONS, conv_options(RELU)),
+        operator(0, [3, 4], [5], ADD_OPTIONS, Table((0, "i8", NONE))),
+    ]
+    data = model(tensors, [0], [5], operators, [bytes(k), pack("<i", bias), bytes(c)])
+    acc = conv_nhwc([v - zx for v in x], [1, 3, 3, 2], [v - zk for v in k], [2, 2, 2, 2], bias)
+    conv = [multiply_by_quantized_multiplier(a, sx * sk / sconv) + zconv for a in acc]
+    conv = [max(zconv, min(255, v)) for v in conv]
+    y = [quant(sconv * (v - zconv) + sc * (c[i % 2] - zc), sy, zy) for i, v in enumerate(conv)]
+    return data, x, y
+
+
+if __name__ == "__main__":
+    here = os.path.dirname(os.path.abspath(__file__))
+    for name, (data, x, y) in [("conv_add_f32", float_model()), ("conv_add_u8", quant_model())]:
+        with open(os.path.join(here, name + ".tflite"), "wb") as f:
+            f.write(data)
+        print(name)
+        print("  input: ", x)
+        print("  output:", y)